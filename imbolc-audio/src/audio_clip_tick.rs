@@ -0,0 +1,499 @@
+//! Arrangement audio clip playback.
+//!
+//! In Song mode, audio clips on the timeline are played from their buffers by
+//! one-shot sampler synths, scheduled with the same lookahead as sequenced notes
+//! so they stay in sync with the piano roll. Clips already under the playhead
+//! when playback starts (or after a seek) are started mid-file. Muted clips,
+//! clips on instruments that are recording takes and clips on frozen
+//! instruments (whose render already holds them) are skipped.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use super::engine::AudioEngine;
+use super::snapshot::{InstrumentSnapshot, PianoRollSnapshot, SessionSnapshot};
+use super::take_recording::TakeRecordState;
+use imbolc_types::{AudioClipId, PlayMode};
+
+/// A clip synth that has been scheduled and has not reached its end yet.
+#[derive(Debug, Clone)]
+struct ActiveAudioClip {
+    node_id: i32,
+    start_tick: u32,
+    end_tick: u32,
+    /// Wall-clock time the synth is scheduled to start
+    starts_at: Instant,
+}
+
+/// Runtime state for audio clip playback.
+#[derive(Debug, Default)]
+pub struct AudioClipPlayState {
+    active: HashMap<AudioClipId, ActiveAudioClip>,
+    last_playhead: Option<u32>,
+}
+
+impl AudioClipPlayState {
+    /// Stop all scheduled or sounding clip synths.
+    pub fn stop_all(&mut self, engine: &mut AudioEngine) {
+        let now = Instant::now();
+        for (_, clip) in self.active.drain() {
+            stop_buffer_synth(engine, clip.node_id, clip.starts_at, now);
+        }
+        self.last_playhead = None;
+    }
+}
+
+/// When to start a buffer synth for a span beginning at `start_tick`: the
+/// delay from now (lookahead included) and the tick it plays from. A span
+/// already under the playhead starts at the playhead, mid-file.
+pub(crate) fn start_from_playhead(
+    piano_roll: &PianoRollSnapshot,
+    start_tick: u32,
+    lookahead_secs: f64,
+) -> (f64, u32) {
+    let playhead = piano_roll.playhead;
+    if start_tick >= playhead {
+        (
+            piano_roll.secs_between(playhead as f64, start_tick as f64) + lookahead_secs,
+            start_tick,
+        )
+    } else {
+        (lookahead_secs, playhead)
+    }
+}
+
+/// Drop finished synths from `active`, and remove and return the ones that no
+/// longer match what `current` expects so the caller can stop them.
+pub(crate) fn take_stale<K: Copy + Eq + Hash, V: Clone>(
+    active: &mut HashMap<K, V>,
+    finished: impl Fn(&V) -> bool,
+    current: impl Fn(K, &V) -> bool,
+) -> Vec<V> {
+    let mut stale = Vec::new();
+    active.retain(|id, synth| {
+        if finished(synth) {
+            return false;
+        }
+        let unchanged = current(*id, synth);
+        if !unchanged {
+            stale.push(synth.clone());
+        }
+        unchanged
+    });
+    stale
+}
+
+/// Stop a buffer synth, at its scheduled start if that is still ahead.
+pub(crate) fn stop_buffer_synth(
+    engine: &mut AudioEngine,
+    node_id: i32,
+    starts_at: Instant,
+    now: Instant,
+) {
+    let offset = starts_at.saturating_duration_since(now).as_secs_f64();
+    let _ = engine.stop_audio_clip(node_id, offset);
+}
+
+/// Tick audio clip playback, spawning clip synths that enter the lookahead window.
+///
+/// # Arguments
+/// * `engine` - Audio engine for spawning clip synths
+/// * `session` - Session snapshot (arrangement clips and play mode)
+/// * `instruments` - Instrument snapshot (frozen instruments are not played back)
+/// * `piano_roll` - Piano roll snapshot (for playhead, playing state, BPM)
+/// * `state` - Scheduled clip synths carried between ticks
+/// * `takes` - Take recording state (recording instruments are not played back)
+pub fn tick_audio_clips(
+    engine: &mut AudioEngine,
+    session: &SessionSnapshot,
+    instruments: &InstrumentSnapshot,
+    piano_roll: &PianoRollSnapshot,
+    state: &mut AudioClipPlayState,
+    takes: &TakeRecordState,
+) {
+    let arr = &session.arrangement;
+    let song_playing = piano_roll.playing
+        && arr.play_mode == PlayMode::Song
+        && arr.editing_clip.is_none()
+        && piano_roll.bpm > 0.0;
+    if !song_playing {
+        if !state.active.is_empty() || state.last_playhead.is_some() {
+            state.stop_all(engine);
+        }
+        return;
+    }
+
+    let playhead = piano_roll.playhead;
+    // A jump backwards or further than a beat is a seek: restart from the new position
+    if let Some(last) = state.last_playhead {
+        if playhead < last || playhead - last > piano_roll.ticks_per_beat {
+            state.stop_all(engine);
+        }
+    }
+    state.last_playhead = Some(playhead);
+
+    let lookahead_secs = engine.schedule_lookahead_secs;
+    let lookahead_ticks = piano_roll.ticks_after(playhead as f64, lookahead_secs) as u32;
+    let window_end = playhead + lookahead_ticks;
+    let now = Instant::now();
+
+    let audible = |clip: &imbolc_types::AudioClip| {
+        !clip.muted
+            && !takes.is_recording(clip.instrument_id)
+            && !instruments
+                .instrument(clip.instrument_id)
+                .is_some_and(|inst| inst.is_frozen())
+    };
+
+    // Drop finished clips, and stop ones that were moved, muted or removed since scheduling
+    let stale = take_stale(
+        &mut state.active,
+        |active| active.end_tick <= playhead,
+        |id, active| {
+            arr.audio_clip(id).is_some_and(|c| {
+                audible(c) && c.start_tick == active.start_tick && c.end_tick() == active.end_tick
+            })
+        },
+    );
+    for clip in stale {
+        stop_buffer_synth(engine, clip.node_id, clip.starts_at, now);
+    }
+
+    for clip in &arr.audio_clips {
+        if state.active.contains_key(&clip.id)
+            || !audible(clip)
+            || clip.start_tick >= window_end
+            || clip.end_tick() <= playhead
+        {
+            continue;
+        }
+
+        let (offset_secs, from_tick) =
+            start_from_playhead(piano_roll, clip.start_tick, lookahead_secs);
+        let file_offset_secs = piano_roll.secs_between(clip.start_tick as f64, from_tick as f64);
+        let play_secs = piano_roll.secs_between(from_tick as f64, clip.end_tick() as f64);
+
+        if let Ok(node_id) = engine.play_audio_clip(clip, file_offset_secs, play_secs, offset_secs)
+        {
+            state.active.insert(
+                clip.id,
+                ActiveAudioClip {
+                    node_id,
+                    start_tick: clip.start_tick,
+                    end_tick: clip.end_tick(),
+                    starts_at: now + Duration::from_secs_f64(offset_secs),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::backend::{RawArg, TestOp};
+    use crate::test_support::write_silent_wav;
+    use imbolc_types::{InstrumentId, InstrumentState, PianoRollState, SessionState};
+    use std::path::Path;
+
+    /// Song-mode session at 120 BPM with one 2-second clip starting at `start_tick`.
+    fn song_with_clip(path: &Path, start_tick: u32) -> (SessionState, PianoRollState) {
+        let mut session = SessionState::new();
+        session.arrangement.play_mode = PlayMode::Song;
+        let mut piano_roll = PianoRollState::new();
+        piano_roll.bpm = 120.0;
+        piano_roll.playing = true;
+        let tpb = piano_roll.ticks_per_beat;
+        let id = session.arrangement.add_audio_clip(
+            "Take 1".to_string(),
+            path.to_path_buf(),
+            InstrumentId::new(1),
+            start_tick,
+            4 * tpb,
+        );
+        session.arrangement.audio_clip_mut(id).unwrap().length_secs = 2.0;
+        (session, piano_roll)
+    }
+
+    fn clip_spawns(ops: &[TestOp]) -> Vec<(f64, f32, f32)> {
+        ops.iter()
+            .filter_map(|op| match op {
+                TestOp::SendBundle {
+                    messages,
+                    offset_secs,
+                } => messages.iter().find_map(|(addr, args)| {
+                    if addr != "/s_new" {
+                        return None;
+                    }
+                    let param = |name: &str| {
+                        args.windows(2).find_map(|w| match (&w[0], &w[1]) {
+                            (RawArg::Str(n), RawArg::Float(v)) if n == name => Some(*v),
+                            _ => None,
+                        })
+                    };
+                    Some((
+                        *offset_secs,
+                        param("sliceStart").unwrap(),
+                        param("sliceEnd").unwrap(),
+                    ))
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mono_clip_buffers_are_loaded_as_stereo() {
+        let dir = tempfile::tempdir().unwrap();
+        let mono = dir.path().join("mono.wav");
+        let stereo = dir.path().join("stereo.wav");
+        write_silent_wav(&mono, 1, 0.1);
+        write_silent_wav(&stereo, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (mut session, _) = song_with_clip(&mono, 0);
+        session.arrangement.add_audio_clip(
+            "Stereo".to_string(),
+            stereo.clone(),
+            InstrumentId::new(1),
+            0,
+            10,
+        );
+
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+
+        let ops = backend.operations();
+        let channel_reads = ops
+            .iter()
+            .filter(
+                |op| matches!(op, TestOp::SendRaw { addr, .. } if addr == "/b_allocReadChannel"),
+            )
+            .count();
+        let loads: Vec<&String> = ops
+            .iter()
+            .filter_map(|op| match op {
+                TestOp::LoadBuffer { path, .. } => Some(path),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(channel_reads, 1);
+        assert_eq!(loads, vec![&stereo.to_string_lossy().to_string()]);
+
+        // Removing a clip frees its buffer
+        session.arrangement.audio_clips.pop();
+        backend.clear();
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        assert_eq!(backend.count(|op| matches!(op, TestOp::FreeBuffer(_))), 1);
+    }
+
+    #[test]
+    fn clip_is_scheduled_once_with_lookahead() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        write_silent_wav(&path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (session, mut piano_roll) = song_with_clip(&path, 10);
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        backend.clear();
+
+        let mut state = AudioClipPlayState::default();
+        let instruments = InstrumentState::new();
+        let takes = TakeRecordState::default();
+        piano_roll.playhead = 0;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        piano_roll.playhead = 5;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+
+        let spawns = clip_spawns(&backend.operations());
+        assert_eq!(spawns.len(), 1);
+        let secs_per_tick = 60.0 / (120.0 * piano_roll.ticks_per_beat as f64);
+        let expected = 10.0 * secs_per_tick + engine.schedule_lookahead_secs;
+        assert!((spawns[0].0 - expected).abs() < 1e-9);
+        assert_eq!((spawns[0].1, spawns[0].2), (0.0, 1.0));
+    }
+
+    #[test]
+    fn starting_inside_clip_plays_from_file_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        write_silent_wav(&path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (session, mut piano_roll) = song_with_clip(&path, 0);
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        backend.clear();
+
+        // One beat into a four-beat clip
+        piano_roll.playhead = piano_roll.ticks_per_beat;
+        let mut state = AudioClipPlayState::default();
+        let instruments = InstrumentState::new();
+        let takes = TakeRecordState::default();
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+
+        let spawns = clip_spawns(&backend.operations());
+        assert_eq!(spawns.len(), 1);
+        assert!((spawns[0].0 - engine.schedule_lookahead_secs).abs() < 1e-9);
+        assert!((spawns[0].1 - 0.25).abs() < 1e-6);
+        assert!((spawns[0].2 - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stopping_or_seeking_frees_active_clips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        write_silent_wav(&path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (session, mut piano_roll) = song_with_clip(&path, 0);
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        backend.clear();
+
+        let mut state = AudioClipPlayState::default();
+        let instruments = InstrumentState::new();
+        let takes = TakeRecordState::default();
+        piano_roll.playhead = 100;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        // Seek backwards: the clip restarts from the new position
+        piano_roll.playhead = 0;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert_eq!(clip_spawns(&backend.operations()).len(), 2);
+
+        let frees = |backend: &crate::engine::backend::TestBackend| {
+            backend.count(|op| match op {
+                TestOp::SendBundle { messages, .. } => {
+                    messages.iter().any(|(addr, _)| addr == "/n_free")
+                }
+                _ => false,
+            })
+        };
+        assert_eq!(frees(&backend), 1);
+
+        piano_roll.playing = false;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert_eq!(frees(&backend), 2);
+        assert!(state.active.is_empty());
+    }
+
+    #[test]
+    fn pattern_mode_does_not_play_clips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        write_silent_wav(&path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (mut session, piano_roll) = song_with_clip(&path, 0);
+        session.arrangement.play_mode = PlayMode::Pattern;
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        backend.clear();
+
+        let mut state = AudioClipPlayState::default();
+        let instruments = InstrumentState::new();
+        let takes = TakeRecordState::default();
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert!(clip_spawns(&backend.operations()).is_empty());
+    }
+
+    #[test]
+    fn muted_and_recording_lanes_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        write_silent_wav(&path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let (mut session, piano_roll) = song_with_clip(&path, 0);
+        engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+        backend.clear();
+
+        let mut state = AudioClipPlayState::default();
+        let instruments = InstrumentState::new();
+        let mut takes = TakeRecordState::default();
+        engine.alloc_test_source_bus(InstrumentId::new(1));
+        takes
+            .start(
+                &engine,
+                vec![InstrumentId::new(1)],
+                dir.path().join("rec"),
+                None,
+                None,
+            )
+            .unwrap();
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert!(clip_spawns(&backend.operations()).is_empty());
+
+        let takes = TakeRecordState::default();
+        session.arrangement.audio_clips[0].muted = true;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert!(clip_spawns(&backend.operations()).is_empty());
+
+        session.arrangement.audio_clips[0].muted = false;
+        tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut state,
+            &takes,
+        );
+        assert_eq!(clip_spawns(&backend.operations()).len(), 1);
+    }
+}
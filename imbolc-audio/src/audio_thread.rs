@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crossbeam_channel::{Receiver, TryRecvError};
@@ -9,13 +11,20 @@ use super::commands::{AudioCmd, AudioFeedback, ExportKind};
 use super::engine::server::ServerSpawnResult;
 use super::engine::AudioEngine;
 use super::event_log::{EventLogReader, LogEntry, LogEntryKind};
-use super::osc_client::AudioMonitor;
+use super::osc_client::{AudioMonitor, ChannelMeterReply};
 use super::snapshot::{AutomationSnapshot, InstrumentSnapshot, PianoRollSnapshot, SessionSnapshot};
 use super::telemetry::AudioTelemetry;
 use super::ServerStatus;
 use crate::arp_state::ArpPlayState;
+use crate::midi_clock::{self, MidiClockEvent, MidiClockMessage, MidiClockState};
 use imbolc_types::VstTarget;
-use imbolc_types::{InstrumentId, InstrumentState, SessionState};
+use imbolc_types::{
+    ChannelLevels, ChannelMeterTarget, ExportOptions, InstrumentId, InstrumentState, MidiClockMode,
+    SessionState,
+};
+
+/// Minimum interval between `ChannelMeters` feedback messages (~20Hz).
+const CHANNEL_METER_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Deferred server connection: after spawning scsynth, wait before connecting
 /// so the server has time to initialize. Avoids blocking the audio thread.
@@ -55,14 +64,24 @@ struct RenderState {
     instrument_id: InstrumentId,
     loop_end: u32,
     tail_ticks: u32,
+    /// Scheduling lookahead when the render started
+    lead_in_secs: f64,
 }
 
 struct ExportState {
     kind: ExportKind,
     loop_end: u32,
     tail_ticks: u32,
-    #[allow(dead_code)]
+    /// Final output files, in capture order
     paths: Vec<PathBuf>,
+    options: ExportOptions,
+}
+
+/// Export work running on its own thread: an offline render, or converting
+/// a finished realtime capture
+struct ExportJob {
+    cancel: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<()>,
 }
 
 /// Tracks an in-flight VST parameter query via OSC /param_query
@@ -106,6 +125,10 @@ pub(crate) struct AudioThread {
     export_state: Option<ExportState>,
     /// Last export progress sent (for throttling)
     last_export_progress: f32,
+    /// Offline render or capture conversion in progress
+    export_job: Option<ExportJob>,
+    /// Sample rate the server was started with (0 until started)
+    sample_rate: u32,
     /// Fractional tick accumulator for sub-tick precision (avoids truncation drift)
     tick_accumulator: f64,
     /// Last time /status was polled from SuperCollider
@@ -125,10 +148,18 @@ pub(crate) struct AudioThread {
     pending_vst_queries: Vec<PendingVstQuery>,
     /// Tuner tone node ID (if currently playing)
     tuner_node_id: Option<i32>,
+    /// Scheduled arrangement audio clip synths (Song mode)
+    audio_clip_state: super::audio_clip_tick::AudioClipPlayState,
+    /// Scheduled frozen instrument render synths
+    freeze_state: super::freeze_tick::FreezePlayState,
+    /// Armed multi-track input recording
+    take_record_state: super::take_recording::TakeRecordState,
     /// Click track state (enabled, volume, muted)
     click_state: imbolc_types::ClickTrackState,
     /// Click track beat accumulator (fractional beats since last click)
     click_accumulator: f64,
+    /// MIDI clock sync (master output / slave input)
+    midi_clock: MidiClockState,
     /// High-water mark for piano roll pre-scheduling.
     /// Tracks the furthest tick already scheduled, so the next tick only
     /// schedules notes beyond this point. Reset to None on playhead changes
@@ -138,6 +169,10 @@ pub(crate) struct AudioThread {
     telemetry: AudioTelemetry,
     /// Last time telemetry was emitted
     last_telemetry_emit: Instant,
+    /// Channel meter replies gathered since the last emit (pre-fader)
+    channel_meter_readings: HashMap<ChannelMeterTarget, ChannelLevels>,
+    /// Last time channel meters were emitted
+    last_channel_meter_emit: Instant,
     /// Last time voice cleanup was performed (rate-limited to reduce overhead)
     last_voice_cleanup: Instant,
     /// Last time server health was checked (rate-limited to reduce overhead)
@@ -173,6 +208,8 @@ impl AudioThread {
             render_state: None,
             export_state: None,
             last_export_progress: 0.0,
+            export_job: None,
+            sample_rate: 0,
             tick_accumulator: 0.0,
             last_status_poll: Instant::now(),
             pending_server_connect: None,
@@ -182,11 +219,17 @@ impl AudioThread {
             pending_post_connect_rebuild: None,
             pending_vst_queries: Vec::new(),
             tuner_node_id: None,
+            audio_clip_state: Default::default(),
+            freeze_state: Default::default(),
+            take_record_state: Default::default(),
             click_state: imbolc_types::ClickTrackState::default(),
             click_accumulator: 0.0,
+            midi_clock: MidiClockState::default(),
             last_scheduled_tick: None,
             telemetry: AudioTelemetry::new(),
             last_telemetry_emit: Instant::now(),
+            channel_meter_readings: HashMap::new(),
+            last_channel_meter_emit: Instant::now(),
             last_voice_cleanup: Instant::now(),
             last_health_check: Instant::now(),
         }
@@ -321,12 +364,16 @@ impl AudioThread {
             // Playback control
             SetPlaying { .. }
             | ResetPlayhead
+            | SetPlayhead { .. }
             | SetBpm { .. }
             | SetClickEnabled { .. }
             | SetClickVolume { .. }
             | SetClickMuted { .. }
             | StartTunerTone { .. }
-            | StopTunerTone => self.handle_playback_cmd(cmd),
+            | StopTunerTone
+            | SetMidiOutput { .. }
+            | SetMidiClock { .. }
+            | SetMidiClockInput { .. } => self.handle_playback_cmd(cmd),
 
             // Routing & mixing parameters
             RebuildRouting
@@ -341,6 +388,7 @@ impl AudioThread {
             | SetFilterParam { .. }
             | SetEffectParam { .. }
             | SetLfoParam { .. }
+            | SetModController { .. }
             | SetBusEffectParam { .. }
             | SetLayerGroupEffectParam { .. }
             | SetLayerGroupEqParam { .. }
@@ -349,6 +397,7 @@ impl AudioThread {
             // Voice management
             SpawnVoice { .. }
             | ReleaseVoice { .. }
+            | SetVoiceExpression { .. }
             | RegisterActiveNote { .. }
             | ClearActiveNotes
             | ReleaseAllVoices
@@ -360,8 +409,11 @@ impl AudioThread {
             | StartInstrumentRender { .. }
             | StartRecording { .. }
             | StopRecording { .. }
+            | StartTakeRecording { .. }
+            | StopTakeRecording
             | StartMasterBounce { .. }
             | StartStemExport { .. }
+            | StartOfflineExport { .. }
             | CancelExport => self.handle_recording_cmd(cmd),
 
             // VST parameters
@@ -425,6 +477,7 @@ impl AudioThread {
                     if result.is_ok() {
                         self.monitor.set_audio_latency(buffer_size, sample_rate);
                         self.engine.set_lookahead(buffer_size, sample_rate);
+                        self.sample_rate = sample_rate;
                     }
                     match &result {
                         Ok(()) => self.send_server_status(ServerStatus::Running, "Server started"),
@@ -533,13 +586,26 @@ impl AudioThread {
                     log::debug!(target: "audio::reduce", "unreducible action: {:?}", std::mem::discriminant(&**action));
                 }
 
-                if *rebuild_routing {
+                // Deleting a sidechain key source leaves listeners pointing at a
+                // freed key bus; rebuild them all instead
+                let deletes_key_source = delete_instrument_routing.is_some_and(|deleted| {
+                    imbolc_types::sidechain_sources(&self.instruments, &self.session)
+                        .iter()
+                        .any(|key| match key {
+                            imbolc_types::SidechainSource::Instrument { id, .. } => *id == deleted,
+                            imbolc_types::SidechainSource::LayerGroup(_) => false,
+                        })
+                });
+                if *rebuild_routing || deletes_key_source {
                     self.routing_rebuild =
                         Some(super::engine::routing::RoutingRebuildPhase::TearDown);
                 } else {
                     // Targeted routing operations (only if no full rebuild)
                     if let Some(id) = delete_instrument_routing {
                         let _ = self.engine.delete_instrument_routing(*id);
+                        let _ = self
+                            .engine
+                            .apply_latency_compensation(&self.instruments, &self.session);
                     }
                     if let Some(id) = add_instrument_routing {
                         let _ = self.engine.add_instrument_routing(
@@ -591,7 +657,15 @@ impl AudioThread {
                                 new_seq.current_step = old_seq.current_step;
                                 new_seq.step_accumulator = old_seq.step_accumulator;
                                 new_seq.last_played_step = old_seq.last_played_step;
+                                // Trig conditions count loops through the chain
+                                new_seq.loop_counts = old_seq.loop_counts.clone();
+                                new_seq.pads_played = old_seq.pads_played.clone();
+                                if new_seq.chain_enabled && new_seq.chain == old_seq.chain {
+                                    new_seq.chain_position = old_seq.chain_position;
+                                    new_seq.current_pattern = old_seq.current_pattern;
+                                }
                             }
+                            new_seq.active_locks = old_seq.active_locks.clone();
                         }
                     }
                 }
@@ -603,7 +677,13 @@ impl AudioThread {
                 self.piano_roll = piano_roll.clone();
                 self.piano_roll.playhead = playhead;
                 self.piano_roll.playing = playing;
+                if let Some(bpm) = self.midi_clock.followed_bpm() {
+                    self.piano_roll.bpm = bpm;
+                }
                 self.automation_lanes = automation_lanes.clone();
+                self.engine
+                    .sync_audio_clip_buffers(&self.session.arrangement.audio_clips);
+                self.engine.sync_frozen_buffers(&self.instruments);
                 if *rebuild_routing {
                     self.routing_rebuild =
                         Some(super::engine::routing::RoutingRebuildPhase::TearDown);
@@ -636,6 +716,7 @@ impl AudioThread {
                     self.tick_accumulator = 0.0;
                     self.click_accumulator = 0.0;
                 }
+                self.send_clock_transport(playing);
             }
             AudioCmd::ResetPlayhead => {
                 self.piano_roll.playhead = 0;
@@ -643,11 +724,39 @@ impl AudioThread {
                 self.click_accumulator = 0.0;
                 self.last_scheduled_tick = None;
                 let _ = self.feedback_tx.send(AudioFeedback::PlayheadPosition(0));
+                if self.midi_clock.mode == MidiClockMode::Master && !self.piano_roll.playing {
+                    self.send_clock_message(MidiClockMessage::SongPosition(0));
+                }
+            }
+            AudioCmd::SetPlayhead { tick } => {
+                self.locate_transport(tick);
+                if self.midi_clock.mode == MidiClockMode::Master && !self.piano_roll.playing {
+                    let position =
+                        midi_clock::song_position_for_tick(tick, self.piano_roll.ticks_per_beat);
+                    self.send_clock_message(MidiClockMessage::SongPosition(position));
+                }
             }
             AudioCmd::SetBpm { bpm } => {
+                // A followed clock owns the tempo in slave mode
+                let bpm = self.midi_clock.followed_bpm().unwrap_or(bpm);
                 self.piano_roll.bpm = bpm;
                 let _ = self.feedback_tx.send(AudioFeedback::BpmUpdate(bpm));
             }
+            AudioCmd::SetMidiOutput { sender } => {
+                self.engine.set_midi_output(sender);
+            }
+            AudioCmd::SetMidiClock { mode, output_port } => {
+                if self.midi_clock.mode == MidiClockMode::Master
+                    && mode != MidiClockMode::Master
+                    && self.piano_roll.playing
+                {
+                    self.send_clock_message(MidiClockMessage::Stop);
+                }
+                self.midi_clock.set_mode(mode, output_port);
+            }
+            AudioCmd::SetMidiClockInput { receiver } => {
+                self.midi_clock.input = receiver;
+            }
             AudioCmd::SetClickEnabled { enabled } => {
                 self.click_state.enabled = enabled;
                 if enabled {
@@ -781,6 +890,15 @@ impl AudioThread {
             } => {
                 let _ = self.engine.set_lfo_param(instrument_id, &param, value);
             }
+            AudioCmd::SetModController {
+                instrument_id,
+                controller,
+                value,
+            } => {
+                let _ = self
+                    .engine
+                    .set_mod_controller(instrument_id, controller, value);
+            }
             AudioCmd::SetBusEffectParam {
                 bus_id,
                 effect_id,
@@ -852,6 +970,22 @@ impl AudioThread {
                     self.engine
                         .release_voice(instrument_id, pitch, offset_secs, &self.instruments);
             }
+            AudioCmd::SetVoiceExpression {
+                instrument_id,
+                pitch,
+                dimension,
+                value,
+            } => {
+                let _ = self.engine.set_voice_expression(
+                    instrument_id,
+                    pitch,
+                    dimension,
+                    value,
+                    0.0,
+                    &self.instruments,
+                    &self.session,
+                );
+            }
             AudioCmd::RegisterActiveNote {
                 instrument_id,
                 pitch,
@@ -920,6 +1054,7 @@ impl AudioThread {
                                 instrument_id,
                                 loop_end: self.piano_roll.loop_end,
                                 tail_ticks: self.calculate_tail_ticks(),
+                                lead_in_secs: self.engine.schedule_lookahead_secs,
                             });
                         })
                     } else {
@@ -935,46 +1070,99 @@ impl AudioThread {
                 let path = self.engine.stop_recording();
                 let _ = reply.send(path);
             }
-            AudioCmd::StartMasterBounce { path, reply } => {
-                let result = self.engine.start_export_master(&path).map(|_| {
+            AudioCmd::StartTakeRecording {
+                instruments,
+                file_prefix,
+                punch_in,
+                punch_out,
+                reply,
+            } => {
+                let result = self.take_record_state.start(
+                    &self.engine,
+                    instruments,
+                    file_prefix,
+                    punch_in,
+                    punch_out,
+                );
+                let _ = reply.send(result);
+            }
+            AudioCmd::StopTakeRecording => {
+                self.take_record_state.stop(&mut self.engine);
+            }
+            AudioCmd::StartMasterBounce {
+                path,
+                options,
+                reply,
+            } => {
+                if self.export_busy() {
+                    let _ = reply.send(Err("Already exporting".to_string()));
+                    return;
+                }
+                let capture = crate::export::capture_path(&path, &options);
+                let result = self.engine.start_export_master(&capture).map(|_| {
                     self.export_state = Some(ExportState {
                         kind: ExportKind::MasterBounce,
                         loop_end: self.piano_roll.loop_end,
                         tail_ticks: self.calculate_tail_ticks(),
                         paths: vec![path],
+                        options,
                     });
                     self.last_export_progress = 0.0;
                 });
                 let _ = reply.send(result);
             }
-            AudioCmd::StartStemExport { stems, reply } => {
+            AudioCmd::StartStemExport {
+                stems,
+                options,
+                reply,
+            } => {
+                if self.export_busy() {
+                    let _ = reply.send(Err("Already exporting".to_string()));
+                    return;
+                }
                 let instrument_buses: Vec<(InstrumentId, i32, PathBuf)> = stems
                     .iter()
                     .filter_map(|(inst_id, path)| {
-                        self.engine
-                            .instrument_final_buses
-                            .get(inst_id)
-                            .map(|&bus| (*inst_id, bus, path.clone()))
+                        self.engine.instrument_final_buses.get(inst_id).map(|&bus| {
+                            (*inst_id, bus, crate::export::capture_path(path, &options))
+                        })
                     })
                     .collect();
 
                 if instrument_buses.is_empty() {
                     let _ = reply.send(Err("No instrument buses available".to_string()));
                 } else {
-                    let paths: Vec<PathBuf> = stems.iter().map(|(_, p)| p.clone()).collect();
+                    let paths: Vec<PathBuf> = stems
+                        .iter()
+                        .filter(|(inst_id, _)| {
+                            self.engine.instrument_final_buses.contains_key(inst_id)
+                        })
+                        .map(|(_, p)| p.clone())
+                        .collect();
                     let result = self.engine.start_export_stems(&instrument_buses).map(|_| {
                         self.export_state = Some(ExportState {
                             kind: ExportKind::StemExport,
                             loop_end: self.piano_roll.loop_end,
                             tail_ticks: self.calculate_tail_ticks(),
                             paths,
+                            options,
                         });
                         self.last_export_progress = 0.0;
                     });
                     let _ = reply.send(result);
                 }
             }
+            AudioCmd::StartOfflineExport {
+                target,
+                options,
+                reply,
+            } => {
+                let _ = reply.send(self.start_offline_export(target, options));
+            }
             AudioCmd::CancelExport => {
+                if let Some(job) = self.export_job.take() {
+                    job.cancel.store(true, Ordering::Relaxed);
+                }
                 if self.export_state.is_some() {
                     let _ = self.engine.stop_export();
                     self.export_state = None;
@@ -1055,10 +1243,99 @@ impl AudioThread {
         }
     }
 
-    /// Calculate tail duration in ticks (1 second of tail time)
+    /// True while a capture is running or its worker has not finished
+    fn export_busy(&self) -> bool {
+        self.export_state.is_some()
+            || self
+                .export_job
+                .as_ref()
+                .is_some_and(|job| !job.handle.is_finished())
+    }
+
+    /// Build and render the export on a worker thread from copies of the
+    /// current snapshots; results arrive as export feedback.
+    fn start_offline_export(
+        &mut self,
+        target: crate::nrt::OfflineTarget,
+        options: ExportOptions,
+    ) -> Result<(), String> {
+        if self.export_busy() {
+            return Err("Already exporting".to_string());
+        }
+
+        let synthdef_dirs = [
+            crate::paths::synthdefs_dir(),
+            crate::paths::custom_synthdefs_dir(),
+        ]
+        .into_iter()
+        .filter(|dir| dir.exists())
+        .collect();
+        let project = crate::nrt::OfflineProject {
+            instruments: self.instruments.clone(),
+            session: self.session.clone(),
+            piano_roll: self.piano_roll.clone(),
+            automation_lanes: self.automation_lanes.clone(),
+            samples: self.engine.loaded_samples(),
+            synthdef_dirs,
+        };
+        let sample_rate = if self.sample_rate > 0 {
+            self.sample_rate
+        } else {
+            crate::nrt::DEFAULT_SAMPLE_RATE
+        };
+        let feedback_tx = self.feedback_tx.clone();
+        self.spawn_export_job("imbolc-nrt-export", move |cancel| {
+            crate::nrt::run_export(project, target, options, sample_rate, cancel, &feedback_tx);
+        })
+    }
+
+    fn spawn_export_job(
+        &mut self,
+        name: &str,
+        work: impl FnOnce(&AtomicBool) + Send + 'static,
+    ) -> Result<(), String> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job_cancel = Arc::clone(&cancel);
+        let handle = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || work(&job_cancel))
+            .map_err(|e| format!("Failed to start export thread: {}", e))?;
+        self.export_job = Some(ExportJob { cancel, handle });
+        Ok(())
+    }
+
+    /// Calculate tail duration in ticks (1 second of tail time after the loop end)
     fn calculate_tail_ticks(&self) -> u32 {
-        let ticks_per_second = (self.piano_roll.bpm / 60.0) * self.piano_roll.ticks_per_beat as f32;
-        ticks_per_second as u32
+        self.piano_roll
+            .ticks_after(self.piano_roll.loop_end as f64, 1.0) as u32
+    }
+
+    /// Report a finished realtime capture, converting it first on a worker
+    /// thread when the export asks for another format.
+    fn finish_export(&mut self, export: ExportState, captures: Vec<PathBuf>) {
+        if export.options.is_raw_capture() {
+            let _ = self.feedback_tx.send(AudioFeedback::ExportComplete {
+                kind: export.kind,
+                paths: captures,
+            });
+            return;
+        }
+        let files: Vec<(PathBuf, PathBuf)> = captures.into_iter().zip(export.paths).collect();
+        let feedback_tx = self.feedback_tx.clone();
+        let spawned = self.spawn_export_job("imbolc-export-convert", move |cancel| {
+            crate::export::complete_realtime_export(
+                export.kind,
+                &files,
+                &export.options,
+                cancel,
+                &feedback_tx,
+            );
+        });
+        if let Err(message) = spawned {
+            let _ = self
+                .feedback_tx
+                .send(AudioFeedback::ExportFailed { message });
+        }
     }
 
     fn apply_piano_roll_update(&mut self, updated: PianoRollSnapshot) {
@@ -1067,6 +1344,9 @@ impl AudioThread {
         self.piano_roll = updated;
         self.piano_roll.playhead = playhead;
         self.piano_roll.playing = playing;
+        if let Some(bpm) = self.midi_clock.followed_bpm() {
+            self.piano_roll.bpm = bpm;
+        }
     }
 
     /// Resolve a VstTarget to a SuperCollider node ID using the instrument snapshot and engine node map
@@ -1136,6 +1416,93 @@ impl AudioThread {
         }
     }
 
+    /// Record latencies reported by opened VST plugins and re-align the graph
+    /// when one changes.
+    fn poll_vst_latencies(&mut self) {
+        let reports = self.monitor.drain_vst_latencies();
+        if reports.is_empty() {
+            return;
+        }
+        let sample_rate = if self.sample_rate > 0 {
+            self.sample_rate
+        } else {
+            crate::nrt::DEFAULT_SAMPLE_RATE
+        };
+        let mut changed = false;
+        for (node_id, samples) in reports {
+            let Some(vst_plugin_id) =
+                self.engine
+                    .vst_plugin_for_node(node_id, &self.instruments, &self.session)
+            else {
+                continue;
+            };
+            let latency_secs = samples as f32 / sample_rate as f32;
+            if let Some(plugin) = self.session.vst_plugins.get_mut(vst_plugin_id) {
+                if plugin.latency_secs == Some(latency_secs) {
+                    continue;
+                }
+                plugin.latency_secs = Some(latency_secs);
+                changed = true;
+                let _ = self.feedback_tx.send(AudioFeedback::VstLatencyReported {
+                    vst_plugin_id,
+                    latency_secs,
+                });
+            }
+        }
+        if changed {
+            let _ = self
+                .engine
+                .apply_latency_compensation(&self.instruments, &self.session);
+        }
+    }
+
+    /// Gather channel meter replies and emit them post-fader at ~20Hz. Peaks
+    /// keep the maximum seen in the window so short transients still clip.
+    fn poll_channel_meters(&mut self) {
+        let replies = self.monitor.drain_channel_meters();
+        if !replies.is_empty() {
+            let nodes = self.engine.channel_meter_nodes();
+            for (node_id, reply) in replies {
+                let Some(&target) = nodes.get(&node_id) else {
+                    continue;
+                };
+                let levels = self.channel_meter_readings.entry(target).or_default();
+                match reply {
+                    ChannelMeterReply::PeakRms {
+                        peak_l,
+                        peak_r,
+                        rms_l,
+                        rms_r,
+                    } => {
+                        levels.peak_l = levels.peak_l.max(peak_l);
+                        levels.peak_r = levels.peak_r.max(peak_r);
+                        levels.rms_l = rms_l;
+                        levels.rms_r = rms_r;
+                    }
+                    ChannelMeterReply::Lufs(lufs) => levels.lufs_short = Some(lufs),
+                }
+            }
+        }
+
+        if self.channel_meter_readings.is_empty()
+            || self.last_channel_meter_emit.elapsed() < CHANNEL_METER_INTERVAL
+        {
+            return;
+        }
+        self.last_channel_meter_emit = Instant::now();
+        let readings = self
+            .channel_meter_readings
+            .drain()
+            .map(|(target, levels)| {
+                let gain = target.fader_gain(&self.instruments, &self.session);
+                (target, levels.with_gain(gain))
+            })
+            .collect();
+        let _ = self
+            .feedback_tx
+            .send(AudioFeedback::ChannelMeters(readings));
+    }
+
     fn resolve_vst_node_id(&self, instrument_id: InstrumentId, target: VstTarget) -> Option<i32> {
         let nodes = self.engine.node_map.get(&instrument_id)?;
         match target {
@@ -1210,6 +1577,9 @@ impl AudioThread {
         let _ = self.engine.initialize_wavetables();
 
         self.load_drum_samples();
+        self.engine
+            .sync_audio_clip_buffers(&self.session.arrangement.audio_clips);
+        self.engine.sync_frozen_buffers(&self.instruments);
 
         match (builtin_result, custom_result) {
             (Ok(()), Ok(())) => Ok(()),
@@ -1230,6 +1600,11 @@ impl AudioThread {
     }
 
     fn tick(&mut self, elapsed: Duration) {
+        self.tick_midi_clock(elapsed);
+        // Warped samplers follow the tempo under the playhead
+        let tempo = self.piano_roll.bpm_at(self.piano_roll.playhead as f64);
+        self.engine.set_tempo(tempo, &self.instruments);
+
         super::playback::tick_playback(
             &mut self.piano_roll,
             &mut self.instruments,
@@ -1244,6 +1619,31 @@ impl AudioThread {
             &mut self.tick_accumulator,
             &mut self.last_scheduled_tick,
         );
+        super::audio_clip_tick::tick_audio_clips(
+            &mut self.engine,
+            &self.session,
+            &self.instruments,
+            &self.piano_roll,
+            &mut self.audio_clip_state,
+            &self.take_record_state,
+        );
+        super::freeze_tick::tick_frozen_instruments(
+            &mut self.engine,
+            &self.instruments,
+            &self.piano_roll,
+            &mut self.freeze_state,
+        );
+        for take in super::take_recording::tick_take_recording(
+            &mut self.engine,
+            &self.piano_roll,
+            &mut self.take_record_state,
+        ) {
+            let _ = self.feedback_tx.send(AudioFeedback::TakeRecorded {
+                instrument_id: take.instrument_id,
+                path: take.path,
+                start_tick: take.start_tick,
+            });
+        }
 
         // Check if render-to-WAV should stop
         let mut render_finished = false;
@@ -1262,6 +1662,7 @@ impl AudioThread {
                     let _ = self.feedback_tx.send(AudioFeedback::RenderComplete {
                         instrument_id: render.instrument_id,
                         path: wav_path,
+                        lead_in_secs: render.lead_in_secs,
                     });
                 }
             }
@@ -1276,7 +1677,8 @@ impl AudioThread {
                 // Send progress feedback (throttled to ~2% increments)
                 let total = export.loop_end + export.tail_ticks;
                 if total > 0 {
-                    let progress = self.piano_roll.playhead as f32 / total as f32;
+                    let progress = self.piano_roll.playhead as f32 / total as f32
+                        * crate::export::capture_progress_share(&export.options);
                     if (progress - self.last_export_progress).abs() > 0.02 {
                         self.last_export_progress = progress;
                         let _ = self
@@ -1287,22 +1689,21 @@ impl AudioThread {
             }
         }
         if export_finished {
-            let paths = self.engine.stop_export();
+            let captures = self.engine.stop_export();
             self.piano_roll.playing = false;
             let _ = self.feedback_tx.send(AudioFeedback::PlayingChanged(false));
             self.engine.release_all_voices();
             if let Some(export) = self.export_state.take() {
-                let _ = self.feedback_tx.send(AudioFeedback::ExportComplete {
-                    kind: export.kind,
-                    paths,
-                });
+                self.finish_export(export, captures);
             }
         }
 
+        // Step-based ticks run at the tempo under the playhead
+        let bpm = self.piano_roll.bpm_at(self.piano_roll.playhead as f64);
         super::drum_tick::tick_drum_sequencer(
             &mut self.instruments,
             &self.session,
-            self.piano_roll.bpm,
+            bpm,
             &mut self.engine,
             &mut self.rng_state,
             &self.feedback_tx,
@@ -1311,7 +1712,7 @@ impl AudioThread {
         super::arpeggiator_tick::tick_arpeggiator(
             &self.instruments,
             &self.session,
-            self.piano_roll.bpm,
+            bpm,
             &mut self.arp_states,
             &mut self.engine,
             &mut self.rng_state,
@@ -1320,7 +1721,7 @@ impl AudioThread {
         super::generative_tick::tick_generative(
             &self.instruments,
             &self.session,
-            self.piano_roll.bpm,
+            bpm,
             &mut self.generative_states,
             &mut self.engine,
             &mut self.rng_state,
@@ -1337,6 +1738,141 @@ impl AudioThread {
         );
     }
 
+    // =========================================================================
+    // MIDI clock sync
+    // =========================================================================
+
+    /// Send master clock pulses, or follow incoming clock in slave mode.
+    fn tick_midi_clock(&mut self, elapsed: Duration) {
+        // Drain the input even when not following so stale clock doesn't pile up
+        let events: Vec<MidiClockEvent> = match &self.midi_clock.input {
+            Some(rx) => rx.try_iter().collect(),
+            None => Vec::new(),
+        };
+        if self.midi_clock.mode == MidiClockMode::Slave {
+            for event in events {
+                self.follow_clock(event);
+            }
+        }
+
+        if self.midi_clock.mode == MidiClockMode::Master && self.piano_roll.playing {
+            let port = self.midi_clock.output_port;
+            let lookahead = self.engine.schedule_lookahead_secs;
+            let bpm = self.piano_roll.bpm_at(self.piano_roll.playhead as f64) as f64;
+            for &offset in self.midi_clock.master_pulses(elapsed.as_secs_f64(), bpm) {
+                let _ =
+                    self.engine
+                        .midi_out_clock(port, MidiClockMessage::Clock, offset + lookahead);
+            }
+        }
+    }
+
+    /// Master: announce a transport start/stop to the clock output.
+    fn send_clock_transport(&mut self, playing: bool) {
+        if self.midi_clock.mode != MidiClockMode::Master {
+            return;
+        }
+        if !playing {
+            self.send_clock_message(MidiClockMessage::Stop);
+            return;
+        }
+        self.midi_clock.generator.reset();
+        if self.piano_roll.playhead == 0 {
+            self.send_clock_message(MidiClockMessage::Start);
+        } else {
+            let position = midi_clock::song_position_for_tick(
+                self.piano_roll.playhead,
+                self.piano_roll.ticks_per_beat,
+            );
+            self.send_clock_message(MidiClockMessage::SongPosition(position));
+            self.send_clock_message(MidiClockMessage::Continue);
+        }
+    }
+
+    fn send_clock_message(&self, message: MidiClockMessage) {
+        let _ = self.engine.midi_out_clock(
+            self.midi_clock.output_port,
+            message,
+            self.engine.schedule_lookahead_secs,
+        );
+    }
+
+    /// Slave: apply one incoming clock / transport message.
+    fn follow_clock(&mut self, event: MidiClockEvent) {
+        match event.message {
+            MidiClockMessage::Clock => {
+                if let Some(bpm) = self.midi_clock.follower.tempo_pulse(event.at) {
+                    self.piano_roll.bpm = bpm;
+                    let _ = self.feedback_tx.send(AudioFeedback::BpmUpdate(bpm));
+                }
+                if self.piano_roll.playing && self.midi_clock.follower.advance_position() {
+                    self.correct_clock_phase();
+                }
+            }
+            MidiClockMessage::Start => {
+                self.midi_clock.follower.locate(0);
+                self.locate_transport(0);
+                self.set_transport_playing(true);
+            }
+            MidiClockMessage::Continue => self.set_transport_playing(true),
+            MidiClockMessage::Stop => self.set_transport_playing(false),
+            MidiClockMessage::SongPosition(position) => {
+                self.midi_clock.follower.locate(position);
+                let tick =
+                    midi_clock::tick_for_song_position(position, self.piano_roll.ticks_per_beat);
+                self.locate_transport(tick);
+            }
+        }
+    }
+
+    /// Slave: on each incoming beat, pull the playhead toward the master's
+    /// beat. Audio for the playhead is heard one lookahead later, so the
+    /// playhead should lead the beat by that much.
+    fn correct_clock_phase(&mut self) {
+        let tpb = self.piano_roll.ticks_per_beat;
+        let lookahead_ticks = self.piano_roll.ticks_after(
+            self.piano_roll.playhead as f64,
+            self.engine.schedule_lookahead_secs,
+        );
+        let error = midi_clock::beat_phase_error(
+            self.piano_roll.playhead as i64 - lookahead_ticks.round() as i64,
+            tpb,
+        );
+        if error.abs() > tpb as i64 / 4 {
+            // Far off (missed Start, loop not on a beat): jump to the beat
+            let target = (self.piano_roll.playhead as i64 - error).max(0) as u32;
+            self.locate_transport(target);
+        } else {
+            // Close: absorb half the error over the next ticks to avoid audible jumps
+            self.tick_accumulator -= error as f64 * 0.5;
+        }
+    }
+
+    fn locate_transport(&mut self, tick: u32) {
+        self.piano_roll.playhead = tick;
+        self.tick_accumulator = 0.0;
+        self.click_accumulator = 0.0;
+        self.last_scheduled_tick = None;
+        let _ = self.feedback_tx.send(AudioFeedback::PlayheadPosition(tick));
+    }
+
+    fn set_transport_playing(&mut self, playing: bool) {
+        if self.piano_roll.playing == playing {
+            return;
+        }
+        self.piano_roll.playing = playing;
+        self.last_scheduled_tick = None;
+        if playing {
+            self.tick_accumulator = 0.0;
+            self.click_accumulator = 0.0;
+        } else {
+            self.engine.release_all_voices();
+        }
+        let _ = self
+            .feedback_tx
+            .send(AudioFeedback::PlayingChanged(playing));
+    }
+
     fn poll_engine(&mut self) {
         // Process /n_end notifications for authoritative voice cleanup
         let ended_nodes = self.monitor.drain_node_ends();
@@ -1386,6 +1922,7 @@ impl AudioThread {
                     self.engine.install_server_child(result);
                     self.monitor.set_audio_latency(buffer_size, sample_rate);
                     self.engine.set_lookahead(buffer_size, sample_rate);
+                    self.sample_rate = sample_rate;
                     self.send_server_status(ServerStatus::Running, "Server started, connecting...");
 
                     // Chain into deferred connect (let scsynth initialize before OSC connect)
@@ -1575,11 +2112,14 @@ impl AudioThread {
 
         // Poll pending VST param queries for completed OSC replies
         self.poll_vst_param_queries();
+        self.poll_vst_latencies();
+        self.poll_channel_meters();
 
         if self.engine.poll_pending_buffer_free() {
             let _ = self.feedback_tx.send(AudioFeedback::PendingBufferFreed);
         }
         self.engine.poll_pending_export_buffer_frees();
+        self.engine.poll_pending_take_buffer_frees();
 
         let is_recording = self.engine.is_recording();
         let elapsed_secs = self
@@ -13,8 +13,8 @@ use imbolc_types::ClickTrackState;
 /// # Arguments
 /// * `engine` - Audio engine for spawning clicks
 /// * `click_state` - Current click track state (enabled, volume, muted)
-/// * `session` - Session snapshot (for time signature)
-/// * `piano_roll` - Piano roll snapshot (for playhead, playing state, BPM)
+/// * `session` - Session snapshot (for the base time signature)
+/// * `piano_roll` - Piano roll snapshot (for playhead, playing state, tempo map)
 /// * `elapsed` - Time since last tick
 /// * `click_accumulator` - Accumulator tracking fractional beats
 pub fn tick_click(
@@ -31,7 +31,7 @@ pub fn tick_click(
     }
 
     let ticks_per_beat = piano_roll.ticks_per_beat as f64;
-    let bpm = piano_roll.bpm as f64;
+    let bpm = piano_roll.bpm_at(piano_roll.playhead as f64) as f64;
 
     if bpm <= 0.0 || ticks_per_beat <= 0.0 {
         return;
@@ -45,14 +45,7 @@ pub fn tick_click(
     let old_accum = *click_accumulator;
     *click_accumulator += elapsed.as_secs_f64() * beats_per_second;
 
-    // Pre-calculate bar/beat constants
-    let beats_per_bar = session.time_signature.0 as u32;
     let ticks_per_beat_u32 = piano_roll.ticks_per_beat;
-    let ticks_per_bar = beats_per_bar * ticks_per_beat_u32;
-
-    if ticks_per_bar == 0 {
-        return;
-    }
 
     // Calculate the base tick position before any beats in this tick
     // This is where we were at the start of the tick minus accumulated fractional beats
@@ -69,9 +62,12 @@ pub fn tick_click(
         // Calculate the tick position for THIS beat boundary
         let beat_tick = (base_tick + (beat_count as f64 * ticks_per_beat)) as u32;
 
-        // Determine beat number within bar for this specific beat
-        let beat_in_bar = (beat_tick % ticks_per_bar) / ticks_per_beat_u32;
-        let is_downbeat = beat_in_bar == 0;
+        // Determine beat number within bar for this specific beat, following meter changes
+        let bar =
+            piano_roll
+                .tempo_map
+                .bar_at_tick(session.time_signature, ticks_per_beat_u32, beat_tick);
+        let is_downbeat = bar.beat(beat_tick, ticks_per_beat_u32) == 0;
 
         // Calculate precise offset from tick start
         let offset_secs = ((beat_count as f64 - old_accum) * secs_per_beat).max(0.0)
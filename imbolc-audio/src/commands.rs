@@ -10,7 +10,9 @@ use std::sync::mpsc::Sender;
 
 use imbolc_types::AutomationTarget;
 use imbolc_types::VstTarget;
-use imbolc_types::{BufferId, BusId, EffectId, InstrumentId};
+use imbolc_types::{
+    BufferId, BusId, EffectId, ExportOptions, ExpressionDimension, InstrumentId, ModController,
+};
 
 /// Commands sent from the main thread to the audio engine.
 ///
@@ -59,6 +61,10 @@ pub enum AudioCmd {
         playing: bool,
     },
     ResetPlayhead,
+    /// Move the playhead to a tick without changing the play state
+    SetPlayhead {
+        tick: u32,
+    },
     SetBpm {
         bpm: f32,
     },
@@ -110,6 +116,12 @@ pub enum AudioCmd {
         param: String,
         value: f32,
     },
+    /// Mod matrix controller value (/c_set on the controller bus, no rebuild).
+    SetModController {
+        instrument_id: InstrumentId,
+        controller: ModController,
+        value: f32,
+    },
     /// Targeted /n_set to bus effect node (no routing rebuild).
     SetBusEffectParam {
         bus_id: BusId,
@@ -154,6 +166,13 @@ pub enum AudioCmd {
         pitch: u8,
         offset_secs: f64,
     },
+    /// Per-note expression (MPE bend/pressure/slide) on a held voice
+    SetVoiceExpression {
+        instrument_id: InstrumentId,
+        pitch: u8,
+        dimension: ExpressionDimension,
+        value: f32,
+    },
     RegisterActiveNote {
         instrument_id: InstrumentId,
         pitch: u8,
@@ -190,6 +209,16 @@ pub enum AudioCmd {
     StopRecording {
         reply: Sender<Option<PathBuf>>,
     },
+    /// Record each instrument's input to its own file, following the
+    /// transport and punch range; finished takes arrive as `TakeRecorded`
+    StartTakeRecording {
+        instruments: Vec<InstrumentId>,
+        file_prefix: PathBuf,
+        punch_in: Option<u32>,
+        punch_out: Option<u32>,
+        reply: Sender<Result<(), String>>,
+    },
+    StopTakeRecording,
     StartInstrumentRender {
         instrument_id: InstrumentId,
         path: PathBuf,
@@ -197,10 +226,19 @@ pub enum AudioCmd {
     },
     StartMasterBounce {
         path: PathBuf,
+        options: ExportOptions,
         reply: Sender<Result<(), String>>,
     },
     StartStemExport {
         stems: Vec<(InstrumentId, PathBuf)>,
+        options: ExportOptions,
+        reply: Sender<Result<(), String>>,
+    },
+    /// Render master or stems with `scsynth -N` from a score built off the
+    /// current snapshots, instead of recording playback in real time
+    StartOfflineExport {
+        target: crate::nrt::OfflineTarget,
+        options: ExportOptions,
         reply: Sender<Result<(), String>>,
     },
     CancelExport,
@@ -239,6 +277,21 @@ pub enum AudioCmd {
     },
     StopTunerTone,
 
+    // ── MIDI output ──────────────────────────────────────────────
+    /// Attach the scheduler that writes external MIDI instrument events to ports.
+    SetMidiOutput {
+        sender: Option<crate::midi_out::MidiOutSender>,
+    },
+    /// Select MIDI clock sync mode and the port master clock is sent to.
+    SetMidiClock {
+        mode: imbolc_types::MidiClockMode,
+        output_port: u8,
+    },
+    /// Attach the channel incoming clock / transport messages arrive on.
+    SetMidiClockInput {
+        receiver: Option<crate::midi_clock::MidiClockReceiver>,
+    },
+
     // ── Click track ──────────────────────────────────────────────
     SetClickEnabled {
         enabled: bool,
@@ -273,6 +326,7 @@ impl AudioCmd {
             // Voice management (most time-critical)
             AudioCmd::SpawnVoice { .. }
                 | AudioCmd::ReleaseVoice { .. }
+                | AudioCmd::SetVoiceExpression { .. }
                 | AudioCmd::PlayDrumHit { .. }
                 // Individual param changes (need low latency for knob tweaks)
                 | AudioCmd::SetSourceParam { .. }
@@ -280,6 +334,7 @@ impl AudioCmd {
                 | AudioCmd::SetFilterParam { .. }
                 | AudioCmd::SetEffectParam { .. }
                 | AudioCmd::SetLfoParam { .. }
+                | AudioCmd::SetModController { .. }
                 | AudioCmd::SetBusEffectParam { .. }
                 | AudioCmd::SetLayerGroupEffectParam { .. }
                 | AudioCmd::SetLayerGroupEqParam { .. }
@@ -288,6 +343,7 @@ impl AudioCmd {
                 | AudioCmd::SetPlaying { .. }
                 | AudioCmd::SetBpm { .. }
                 | AudioCmd::ResetPlayhead
+                | AudioCmd::SetPlayhead { .. }
                 // Automation (applied during playback)
                 | AudioCmd::ApplyAutomation { .. }
         )
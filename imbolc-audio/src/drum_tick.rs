@@ -4,7 +4,27 @@ use std::time::Duration;
 use super::commands::AudioFeedback;
 use super::engine::AudioEngine;
 use super::snapshot::{InstrumentSnapshot, SessionSnapshot};
-use imbolc_types::{InstrumentId, SourceExtra};
+use imbolc_types::{InstrumentId, ParamLock, ParameterTarget, SourceExtra, MAX_RATCHET};
+
+/// Parameter lock changes for one instrument chain at one offset.
+/// A `None` value restores the instrument's own setting.
+type LockChange = (InstrumentId, Vec<(ParameterTarget, Option<f32>)>, f64);
+
+fn push_lock_change(
+    changes: &mut Vec<LockChange>,
+    instrument_id: InstrumentId,
+    target: ParameterTarget,
+    value: Option<f32>,
+    offset_secs: f64,
+) {
+    match changes
+        .iter_mut()
+        .find(|(id, _, offset)| *id == instrument_id && *offset == offset_secs)
+    {
+        Some((_, locks, _)) => locks.push((target, value)),
+        None => changes.push((instrument_id, vec![(target, value)], offset_secs)),
+    }
+}
 
 pub fn tick_drum_sequencer(
     instruments: &mut InstrumentSnapshot,
@@ -16,8 +36,10 @@ pub fn tick_drum_sequencer(
     elapsed: Duration,
 ) {
     // Collect instrument triggers to execute after the main loop
-    // (target_instrument_id, freq, velocity, offset_secs)
-    let mut instrument_triggers: Vec<(InstrumentId, f32, f32, f64)> = Vec::new();
+    // (target_instrument_id, freq, velocity, offset_secs, voice locks)
+    let mut instrument_triggers: Vec<(InstrumentId, f32, f32, f64, Vec<ParamLock>)> = Vec::new();
+    // Chain parameter locks, applied after the loop for the same reason
+    let mut lock_changes: Vec<LockChange> = Vec::new();
 
     for instrument in &mut instruments.instruments {
         let seq = match &mut instrument.source_extra {
@@ -26,6 +48,16 @@ pub fn tick_drum_sequencer(
         };
         if !seq.playing {
             seq.last_played_step = None;
+            seq.reset_trig_state();
+            for (id, target) in seq.active_locks.drain(..) {
+                push_lock_change(
+                    &mut lock_changes,
+                    id,
+                    target,
+                    None,
+                    engine.schedule_lookahead_secs,
+                );
+            }
             continue;
         }
 
@@ -41,8 +73,8 @@ pub fn tick_drum_sequencer(
         seq.step_accumulator += elapsed.as_secs_f64() * steps_per_second;
 
         // Collect all steps that should fire in this tick with their precise offsets.
-        // Each entry: (step_index, pattern_index, offset_secs)
-        let mut steps_to_play: Vec<(usize, usize, f64)> = Vec::new();
+        // Each entry: (step_index, pattern_index, loop_count, offset_secs)
+        let mut steps_to_play: Vec<(usize, usize, u32, f64)> = Vec::new();
         let mut threshold_consumed: f64 = 0.0;
 
         loop {
@@ -66,7 +98,8 @@ pub fn tick_drum_sequencer(
             // Advance step
             let next = seq.current_step + 1;
             if next >= pattern_length {
-                // Pattern wrapped — advance chain if enabled
+                // Pattern wrapped — count the pass, advance chain if enabled
+                seq.count_loop(seq.current_pattern);
                 if seq.chain_enabled && !seq.chain.is_empty() {
                     seq.chain_position = (seq.chain_position + 1) % seq.chain.len();
                     let next_pattern = seq.chain[seq.chain_position];
@@ -83,7 +116,12 @@ pub fn tick_drum_sequencer(
             let offset_secs = ((threshold_consumed - old_accum) * secs_per_step_unit).max(0.0)
                 + engine.schedule_lookahead_secs;
 
-            steps_to_play.push((seq.current_step, seq.current_pattern, offset_secs));
+            steps_to_play.push((
+                seq.current_step,
+                seq.current_pattern,
+                seq.loop_count(seq.current_pattern),
+                offset_secs,
+            ));
         }
 
         // Handle initial step when sequencer first starts (no threshold crossed yet)
@@ -91,13 +129,19 @@ pub fn tick_drum_sequencer(
             steps_to_play.push((
                 seq.current_step,
                 seq.current_pattern,
+                seq.loop_count(seq.current_pattern),
                 engine.schedule_lookahead_secs,
             ));
         }
 
         // Play each step with its precise offset
-        for &(step, pattern_idx, offset_secs) in &steps_to_play {
-            if engine.is_running() && !instrument.mixer.mute {
+        for &(step, pattern_idx, loop_count, offset_secs) in &steps_to_play {
+            // Chain locks of the hits on this step: (instrument, target, value, offset)
+            let mut step_locks: Vec<(InstrumentId, ParameterTarget, f32, f64)> = Vec::new();
+            // Whether each active step played, for the pads' next `Previous` conditions
+            let mut pads_played: Vec<(usize, bool)> = Vec::new();
+            // A frozen kit keeps stepping for the UI but its render does the playing
+            if engine.is_running() && !instrument.mixer.mute && instrument.frozen.is_none() {
                 let pattern = &seq.patterns[pattern_idx];
                 for (pad_idx, pad) in seq.pads.iter().enumerate() {
                     if let Some(step_data) = pattern.steps.get(pad_idx).and_then(|s| s.get(step)) {
@@ -105,6 +149,16 @@ pub fn tick_drum_sequencer(
                             continue;
                         }
 
+                        // Trig condition: loop pass, previous step, fill
+                        if !step_data.condition.is_met(
+                            loop_count,
+                            seq.pad_played(pad_idx),
+                            seq.fill,
+                        ) {
+                            pads_played.push((pad_idx, false));
+                            continue;
+                        }
+
                         // Probability check: skip hit if random exceeds probability
                         if step_data.probability < 1.0 {
                             *rng_state = rng_state
@@ -112,9 +166,11 @@ pub fn tick_drum_sequencer(
                                 .wrapping_add(1442695040888963407);
                             let r = ((*rng_state >> 33) as f32) / (u32::MAX as f32);
                             if r > step_data.probability {
+                                pads_played.push((pad_idx, false));
                                 continue;
                             }
                         }
+                        pads_played.push((pad_idx, true));
 
                         // Per-track groove settings
                         let effective_humanize_vel = instrument
@@ -140,7 +196,11 @@ pub fn tick_drum_sequencer(
                             final_offset = (final_offset + jitter as f64).max(0.0);
                         }
 
-                        let mut amp = (step_data.velocity as f32 / 127.0) * pad.level;
+                        // An amp lock stands in for the pad level on this hit
+                        let pad_level = step_data
+                            .lock(&ParameterTarget::SampleAmp)
+                            .unwrap_or(pad.level);
+                        let mut amp = (step_data.velocity as f32 / 127.0) * pad_level;
                         // Velocity humanization using per-track setting
                         if effective_humanize_vel > 0.0 {
                             *rng_state = rng_state
@@ -154,33 +214,91 @@ pub fn tick_drum_sequencer(
                         // Calculate pitch offset (used for both samples and instruments)
                         let total_pitch = pad.pitch as i16 + step_data.pitch_offset as i16;
 
+                        // Locks on the chain the hit plays through, at the hit's time
+                        let lock_owner = pad.instrument_id.unwrap_or(instrument.id);
+                        for lock in &step_data.locks {
+                            if !ParamLock::on_voice(&lock.target) {
+                                step_locks.push((
+                                    lock_owner,
+                                    lock.target,
+                                    lock.value,
+                                    final_offset,
+                                ));
+                            }
+                        }
+
+                        // Ratchets spread the hits evenly across the step, each one
+                        // keeping `ratchet_decay` of the previous hit's velocity
+                        let ratchet = step_data.ratchet.clamp(1, MAX_RATCHET);
+                        let hits: Vec<(f32, f64)> = (0..ratchet)
+                            .map(|i| {
+                                (
+                                    amp * step_data.ratchet_decay.powi(i as i32),
+                                    final_offset + secs_per_step_unit * i as f64 / ratchet as f64,
+                                )
+                            })
+                            .collect();
+
                         // Check if this pad triggers an instrument (one-shot synth)
                         if let Some(target_instrument_id) = pad.instrument_id {
                             // Instrument trigger mode: collect for execution after loop
                             let freq = pad.trigger_freq * 2.0_f32.powf(total_pitch as f32 / 12.0);
-                            instrument_triggers.push((
-                                target_instrument_id,
-                                freq,
-                                amp,
-                                final_offset,
-                            ));
+                            for (hit_amp, hit_offset) in hits {
+                                instrument_triggers.push((
+                                    target_instrument_id,
+                                    freq,
+                                    hit_amp,
+                                    hit_offset,
+                                    step_data.locks.clone(),
+                                ));
+                            }
                         } else if let Some(buffer_id) = pad.buffer_id {
                             // Sample mode: play one-shot sample
-                            let pitch_rate = 2.0_f32.powf(total_pitch as f32 / 12.0);
+                            // Slices chopped from a warped loop follow the tempo; a
+                            // rate lock scales the speed (negative plays backwards)
+                            let pitch_rate = 2.0_f32.powf(total_pitch as f32 / 12.0)
+                                * imbolc_types::warp_ratio(pad.warp_bpm, bpm)
+                                * step_data.lock(&ParameterTarget::SampleRate).unwrap_or(1.0);
                             let rate = if pad.reverse { -pitch_rate } else { pitch_rate };
-                            let _ = engine.play_drum_hit_to_instrument(
-                                buffer_id,
-                                amp,
-                                instrument.id,
-                                pad.slice_start,
-                                pad.slice_end,
-                                rate,
-                                final_offset,
-                            );
+                            // Slice locks move the pad's start and end points for this hit
+                            let slice_start = step_data
+                                .lock(&ParameterTarget::SampleStart)
+                                .unwrap_or(pad.slice_start);
+                            let slice_end = step_data
+                                .lock(&ParameterTarget::SampleEnd)
+                                .unwrap_or(pad.slice_end);
+                            for (hit_amp, hit_offset) in hits {
+                                let _ = engine.play_drum_hit_to_instrument(
+                                    buffer_id,
+                                    hit_amp,
+                                    instrument.id,
+                                    slice_start,
+                                    slice_end,
+                                    rate,
+                                    hit_offset,
+                                );
+                            }
                         }
                     }
                 }
             }
+            for (pad_idx, played) in pads_played {
+                seq.set_pad_played(pad_idx, played);
+            }
+
+            // Locks hold until the next step: restore what this step leaves unlocked
+            for (id, target) in std::mem::take(&mut seq.active_locks) {
+                if !step_locks.iter().any(|(i, t, ..)| *i == id && *t == target) {
+                    push_lock_change(&mut lock_changes, id, target, None, offset_secs);
+                }
+            }
+            for (id, target, value, offset) in step_locks {
+                push_lock_change(&mut lock_changes, id, target, Some(value), offset);
+                if !seq.active_locks.contains(&(id, target)) {
+                    seq.active_locks.push((id, target));
+                }
+            }
+
             let _ = feedback_tx.send(AudioFeedback::DrumSequencerStep {
                 instrument_id: instrument.id,
                 step,
@@ -190,8 +308,177 @@ pub fn tick_drum_sequencer(
     }
 
     // Execute collected instrument triggers (needs immutable borrow of instruments)
-    for (target_id, freq, amp, offset) in instrument_triggers {
-        let _ =
-            engine.trigger_instrument_oneshot(target_id, freq, amp, offset, instruments, session);
+    for (target_id, locks, offset) in lock_changes {
+        let _ = engine.set_param_locks(target_id, &locks, instruments, session, offset);
+    }
+    for (target_id, freq, amp, offset, locks) in instrument_triggers {
+        let _ = engine.trigger_instrument_oneshot(
+            target_id,
+            freq,
+            amp,
+            offset,
+            &locks,
+            instruments,
+            session,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::backend::{RawArg, TestBackend, TestOp};
+    use imbolc_types::{InstrumentState, SessionState, SourceType, StepCondition};
+    use std::sync::mpsc;
+
+    /// Playing 120 BPM kit with a sample on pad 1 and step 1 active
+    fn kit_setup() -> (AudioEngine, std::sync::Arc<TestBackend>, InstrumentState) {
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let mut instruments = InstrumentState::new();
+        let id = instruments.add_instrument(SourceType::Kit);
+        let seq = instruments
+            .instrument_mut(id)
+            .unwrap()
+            .drum_sequencer_mut()
+            .unwrap();
+        seq.pads[0].buffer_id = Some(1);
+        seq.pads[0].level = 1.0;
+        seq.pattern_mut().steps[0][0].active = true;
+        seq.playing = true;
+        engine.load_sample(1, "kick.wav").unwrap();
+        backend.clear();
+        (engine, backend, instruments)
+    }
+
+    fn tick(engine: &mut AudioEngine, instruments: &mut InstrumentState, secs: f64) {
+        let (tx, _rx) = mpsc::channel();
+        tick_drum_sequencer(
+            instruments,
+            &SessionState::new(),
+            120.0,
+            engine,
+            &mut 1,
+            &tx,
+            Duration::from_secs_f64(secs),
+        );
+    }
+
+    /// (amp, offset_secs) of each one-shot sample hit sent
+    fn hits(backend: &TestBackend) -> Vec<(f32, f64)> {
+        backend
+            .operations()
+            .iter()
+            .filter_map(|op| match op {
+                TestOp::SendBundle {
+                    messages,
+                    offset_secs,
+                } => messages.iter().find_map(|(_, args)| {
+                    args.windows(2).find_map(|w| match (&w[0], &w[1]) {
+                        (RawArg::Str(n), RawArg::Float(v)) if n == "amp" => {
+                            Some((*v, *offset_secs))
+                        }
+                        _ => None,
+                    })
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn first_step(instruments: &mut InstrumentState) -> &mut imbolc_types::DrumStep {
+        let seq = instruments.instruments[0].drum_sequencer_mut().unwrap();
+        &mut seq.pattern_mut().steps[0][0]
+    }
+
+    #[test]
+    fn ratchet_spreads_decaying_hits_across_the_step() {
+        let (mut engine, backend, mut instruments) = kit_setup();
+        let step = first_step(&mut instruments);
+        step.velocity = 127;
+        step.ratchet = 3;
+        step.ratchet_decay = 0.5;
+
+        tick(&mut engine, &mut instruments, 0.0);
+
+        // A sixteenth at 120 BPM lasts 0.125s
+        let lookahead = engine.schedule_lookahead_secs;
+        let hits = hits(&backend);
+        assert_eq!(hits.len(), 3);
+        for (i, (amp, offset)) in hits.iter().enumerate() {
+            assert!((amp - 0.5_f32.powi(i as i32)).abs() < 1e-6);
+            assert!((offset - (lookahead + 0.125 * i as f64 / 3.0)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn slice_locks_move_the_hit_slice() {
+        let (mut engine, backend, mut instruments) = kit_setup();
+        let step = first_step(&mut instruments);
+        step.set_lock(ParameterTarget::SampleStart, 0.25);
+        step.set_lock(ParameterTarget::SampleEnd, 0.5);
+
+        tick(&mut engine, &mut instruments, 0.0);
+
+        let ops = backend.operations();
+        let slice = ops
+            .iter()
+            .find_map(|op| match op {
+                TestOp::SendBundle { messages, .. } => messages.first().map(|(_, args)| {
+                    let arg = |name: &str| {
+                        args.windows(2).find_map(|w| match (&w[0], &w[1]) {
+                            (RawArg::Str(n), RawArg::Float(v)) if n == name => Some(*v),
+                            _ => None,
+                        })
+                    };
+                    (arg("sliceStart"), arg("sliceEnd"))
+                }),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(slice, (Some(0.25), Some(0.5)));
+    }
+
+    #[test]
+    fn loop_condition_counts_pattern_passes() {
+        let (mut engine, backend, mut instruments) = kit_setup();
+        first_step(&mut instruments).condition = StepCondition::Loop { n: 2, m: 2 };
+
+        // First pass: skipped
+        tick(&mut engine, &mut instruments, 0.0);
+        assert!(hits(&backend).is_empty());
+        // Sixteen steps later the pattern is on its second pass
+        tick(&mut engine, &mut instruments, 2.0);
+        assert_eq!(hits(&backend).len(), 1);
+        // And skipped again on the third
+        backend.clear();
+        tick(&mut engine, &mut instruments, 2.0);
+        assert!(hits(&backend).is_empty());
+    }
+
+    #[test]
+    fn fill_and_previous_conditions() {
+        let (mut engine, backend, mut instruments) = kit_setup();
+        first_step(&mut instruments).condition = StepCondition::Fill;
+        {
+            let seq = instruments.instruments[0].drum_sequencer_mut().unwrap();
+            let step = &mut seq.pattern_mut().steps[0][1];
+            step.active = true;
+            step.condition = StepCondition::NotPrevious;
+        }
+
+        // Fill off: step 1 is skipped, so step 2 plays
+        tick(&mut engine, &mut instruments, 0.0);
+        tick(&mut engine, &mut instruments, 0.125);
+        assert_eq!(hits(&backend).len(), 1);
+
+        // Fill on: step 1 plays, so step 2 is skipped
+        backend.clear();
+        instruments.instruments[0]
+            .drum_sequencer_mut()
+            .unwrap()
+            .fill = true;
+        tick(&mut engine, &mut instruments, 1.875);
+        tick(&mut engine, &mut instruments, 0.125);
+        assert_eq!(hits(&backend).len(), 1);
     }
 }
@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use super::backend::{BackendMessage, RawArg};
+use super::{AudioEngine, GROUP_SOURCES};
+use imbolc_types::{AudioClip, AudioClipId};
+
+/// Fade applied before freeing a clip node early (matches the synth's `amp.lag`)
+const CLIP_STOP_FADE_SECS: f64 = 0.01;
+
+impl AudioEngine {
+    /// Load buffers for arrangement audio clips that are new or whose file
+    /// changed, and free buffers of clips that no longer exist.
+    pub fn sync_audio_clip_buffers(&mut self, clips: &[AudioClip]) {
+        let Some(backend) = self.backend.as_ref() else {
+            return;
+        };
+
+        let stale: Vec<AudioClipId> = self
+            .audio_clip_buffers
+            .iter()
+            .filter(|(id, (path, _))| !clips.iter().any(|c| c.id == **id && c.path == *path))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale {
+            if let Some((_, bufnum)) = self.audio_clip_buffers.remove(&id) {
+                let _ = backend.free_buffer(bufnum);
+            }
+        }
+
+        for clip in clips {
+            if self.audio_clip_buffers.contains_key(&clip.id) || !clip.path.exists() {
+                continue;
+            }
+            let bufnum = self.next_bufnum;
+            self.next_bufnum += 1;
+            if let Err(e) = load_stereo_buffer(backend.as_ref(), bufnum, &clip.path) {
+                log::warn!(target: "audio::samples", "Failed to load audio clip {:?}: {}", clip.path, e);
+                continue;
+            }
+            self.audio_clip_buffers
+                .insert(clip.id, (clip.path.clone(), bufnum));
+        }
+    }
+
+    /// Start playing an audio clip through its instrument's signal chain.
+    /// `file_offset_secs` is where playback starts within the file and
+    /// `play_secs` how much of it to play. Returns the synth node ID.
+    pub fn play_audio_clip(
+        &mut self,
+        clip: &AudioClip,
+        file_offset_secs: f64,
+        play_secs: f64,
+        offset_secs: f64,
+    ) -> Result<i32, String> {
+        if self.backend.is_none() {
+            return Err("Not connected".to_string());
+        }
+        let (_, bufnum) = *self
+            .audio_clip_buffers
+            .get(&clip.id)
+            .ok_or("Audio clip buffer not loaded")?;
+        if clip.length_secs <= 0.0 {
+            return Err("Audio clip has no length".to_string());
+        }
+        let length = clip.length_secs as f64;
+        let slice_start = (file_offset_secs / length).clamp(0.0, 1.0);
+        let slice_end = ((file_offset_secs + play_secs) / length).clamp(slice_start, 1.0);
+        let out_bus = self
+            .bus_allocator
+            .get_audio_bus(clip.instrument_id, "source_out")
+            .unwrap_or(0);
+
+        let node_id = self.next_node_id;
+        self.next_node_id += 1;
+
+        let msg = BackendMessage {
+            addr: "/s_new".to_string(),
+            args: vec![
+                RawArg::Str("imbolc_sampler_oneshot".to_string()),
+                RawArg::Int(node_id),
+                RawArg::Int(0), // addToHead
+                RawArg::Int(GROUP_SOURCES),
+                RawArg::Str("bufnum".to_string()),
+                RawArg::Int(bufnum),
+                RawArg::Str("amp".to_string()),
+                RawArg::Float(1.0),
+                RawArg::Str("sliceStart".to_string()),
+                RawArg::Float(slice_start as f32),
+                RawArg::Str("sliceEnd".to_string()),
+                RawArg::Float(slice_end as f32),
+                RawArg::Str("rate".to_string()),
+                RawArg::Float(1.0),
+                RawArg::Str("out".to_string()),
+                RawArg::Int(out_bus),
+            ],
+        };
+        self.queue_timed_bundle(vec![msg], offset_secs)?;
+
+        Ok(node_id)
+    }
+
+    /// Stop an audio clip node before it reaches its end. `offset_secs` must
+    /// not be earlier than the node's scheduled start.
+    pub fn stop_audio_clip(&mut self, node_id: i32, offset_secs: f64) -> Result<(), String> {
+        let fade = BackendMessage {
+            addr: "/n_set".to_string(),
+            args: vec![
+                RawArg::Int(node_id),
+                RawArg::Str("amp".to_string()),
+                RawArg::Float(0.0),
+            ],
+        };
+        self.queue_timed_bundle(vec![fade], offset_secs)?;
+        let free = BackendMessage {
+            addr: "/n_free".to_string(),
+            args: vec![RawArg::Int(node_id)],
+        };
+        self.queue_timed_bundle(vec![free], offset_secs + CLIP_STOP_FADE_SECS)
+    }
+}
+
+/// The oneshot sampler reads two channels, so mono files are loaded with
+/// their single channel duplicated.
+pub(super) fn load_stereo_buffer(
+    backend: &dyn super::backend::AudioBackend,
+    bufnum: i32,
+    path: &Path,
+) -> Result<(), String> {
+    let channels = hound::WavReader::open(path)
+        .map(|r| r.spec().channels)
+        .unwrap_or(2);
+    let result = if channels == 1 {
+        backend.send_raw(
+            "/b_allocReadChannel",
+            vec![
+                RawArg::Int(bufnum),
+                RawArg::Str(path.to_string_lossy().to_string()),
+                RawArg::Int(0),
+                RawArg::Int(0),
+                RawArg::Int(0),
+                RawArg::Int(0),
+            ],
+        )
+    } else {
+        backend.load_buffer(bufnum, path)
+    };
+    result.map_err(|e| e.to_string())
+}
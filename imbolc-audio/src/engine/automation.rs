@@ -64,11 +64,32 @@ impl AudioEngine {
     ) -> Result<(), String> {
         match param {
             InstrumentParameter::Standard(pt) => {
+                if self.send_midi_out_automation(instrument_id, pt, value, state, 0.0) {
+                    return Ok(());
+                }
                 self.apply_parameter_target(backend, instrument_id, pt, value, state, session)
             }
         }
     }
 
+    /// External MIDI instruments receive mapped targets as CC / pitch bend.
+    /// Returns true when the value was sent to a MIDI port.
+    fn send_midi_out_automation(
+        &self,
+        instrument_id: InstrumentId,
+        target: &ParameterTarget,
+        value: f32,
+        state: &InstrumentState,
+        offset_secs: f64,
+    ) -> bool {
+        state
+            .instrument(instrument_id)
+            .and_then(|inst| inst.source.midi_out())
+            .is_some_and(|(port, channel)| {
+                self.midi_out_param(port, channel, target, value, offset_secs)
+            })
+    }
+
     /// Apply automation for a ParameterTarget on a specific instrument
     fn apply_parameter_target(
         &self,
@@ -332,6 +353,8 @@ impl AudioEngine {
             | ParameterTarget::MembraneTension
             | ParameterTarget::StretchRatio
             | ParameterTarget::PitchShift
+            | ParameterTarget::SampleStart
+            | ParameterTarget::SampleEnd
             | ParameterTarget::DelayTime
             | ParameterTarget::DelayFeedback
             | ParameterTarget::ReverbMix
@@ -398,6 +421,11 @@ impl AudioEngine {
     ) {
         match param {
             InstrumentParameter::Standard(pt) => {
+                // Sequenced automation is sent with the playback lookahead
+                let offset = self.schedule_lookahead_secs;
+                if self.send_midi_out_automation(instrument_id, pt, value, state, offset) {
+                    return;
+                }
                 self.collect_parameter_target_messages(
                     msgs,
                     instrument_id,
@@ -638,6 +666,8 @@ impl AudioEngine {
             | ParameterTarget::MembraneTension
             | ParameterTarget::StretchRatio
             | ParameterTarget::PitchShift
+            | ParameterTarget::SampleStart
+            | ParameterTarget::SampleEnd
             | ParameterTarget::DelayTime
             | ParameterTarget::DelayFeedback
             | ParameterTarget::ReverbMix
@@ -647,6 +677,85 @@ impl AudioEngine {
         }
     }
 
+    /// Set or restore per-step parameter locks on an instrument's chain at
+    /// `offset_secs`. A `None` value restores the instrument's own setting.
+    /// Targets that live on the voice rather than the chain are skipped.
+    pub fn set_param_locks(
+        &self,
+        instrument_id: InstrumentId,
+        locks: &[(ParameterTarget, Option<f32>)],
+        state: &InstrumentState,
+        session: &SessionState,
+        offset_secs: f64,
+    ) -> Result<(), String> {
+        if !self.is_running {
+            return Ok(());
+        }
+        let backend = self.backend.as_ref().ok_or("Not connected")?;
+        let Some(instrument) = state.instrument(instrument_id) else {
+            return Ok(());
+        };
+
+        let mut per_node: Vec<(i32, Vec<(String, f32)>)> = Vec::new();
+        for (target, value) in locks {
+            let Some(value) = value.or_else(|| instrument.param_value(target)) else {
+                continue;
+            };
+            let Some((node_id, name, value)) =
+                self.chain_control(instrument_id, target, value, state, session)
+            else {
+                continue;
+            };
+            match per_node.iter_mut().find(|(id, _)| *id == node_id) {
+                Some((_, params)) => params.push((name, value)),
+                None => per_node.push((node_id, vec![(name, value)])),
+            }
+        }
+
+        for (node_id, params) in &per_node {
+            let params: Vec<(&str, f32)> = params.iter().map(|(n, v)| (n.as_str(), *v)).collect();
+            backend
+                .set_params_bundled(*node_id, &params, offset_secs)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Node and control a chain-level parameter is set on
+    fn chain_control(
+        &self,
+        instrument_id: InstrumentId,
+        target: &ParameterTarget,
+        value: f32,
+        state: &InstrumentState,
+        session: &SessionState,
+    ) -> Option<(i32, String, f32)> {
+        let nodes = self.node_map.get(&instrument_id)?;
+        match target {
+            ParameterTarget::Level => Some((
+                nodes.output,
+                "level".to_string(),
+                value * session.mixer.master_level,
+            )),
+            ParameterTarget::Pan => Some((nodes.output, "pan".to_string(), value)),
+            ParameterTarget::FilterCutoff => Some((nodes.filter?, "cutoff".to_string(), value)),
+            ParameterTarget::FilterResonance => {
+                Some((nodes.filter?, "resonance".to_string(), value))
+            }
+            ParameterTarget::SendLevel(bus_id) => {
+                let node_id = *self.send_node_map.get(&(instrument_id, *bus_id))?;
+                Some((node_id, "level".to_string(), value))
+            }
+            ParameterTarget::EffectParam(effect_id, param_idx) => {
+                let node_id = *nodes.effects.get(effect_id)?;
+                let effect = state.instrument(instrument_id)?.effect_by_id(*effect_id)?;
+                let param = effect.params.get(param_idx.get())?;
+                Some((node_id, param.name.clone(), value))
+            }
+            _ => None,
+        }
+    }
+
     /// Send a batch of automation messages as a single timestamped bundle.
     pub fn send_automation_bundle(
         &self,
@@ -701,3 +701,210 @@ impl AudioBackend for NullBackend {
         Ok(())
     }
 }
+
+// ─── Score Backend ──────────────────────────────────────────────────
+
+/// A bundle in a non-realtime score, timestamped in seconds from the start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreBundle {
+    pub time: f64,
+    pub messages: Vec<BackendMessage>,
+}
+
+#[derive(Default)]
+struct ScoreRecorder {
+    now: f64,
+    bundles: Vec<ScoreBundle>,
+}
+
+/// A backend that writes operations into a score on a virtual clock instead
+/// of sending them. Unbundled operations land at the current time and bundle
+/// offsets are taken from it, so the engine's scheduling code produces the
+/// same timeline it would against a live server.
+///
+/// Clones share the same score: the engine owns one while the caller keeps
+/// another to advance the clock and collect the bundles.
+#[derive(Clone, Default)]
+pub struct ScoreBackend {
+    inner: Arc<Mutex<ScoreRecorder>>,
+}
+
+impl ScoreBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the virtual clock to `secs` from the start of the score.
+    pub fn set_time(&self, secs: f64) {
+        self.inner.lock().unwrap().now = secs;
+    }
+
+    pub fn now(&self) -> f64 {
+        self.inner.lock().unwrap().now
+    }
+
+    /// Take the bundles recorded so far, in the order they were written.
+    pub fn take_bundles(&self) -> Vec<ScoreBundle> {
+        std::mem::take(&mut self.inner.lock().unwrap().bundles)
+    }
+
+    fn push(&self, messages: Vec<BackendMessage>, offset_secs: f64) -> BackendResult {
+        let mut inner = self.inner.lock().unwrap();
+        let time = inner.now + offset_secs.max(0.0);
+        inner.bundles.push(ScoreBundle { time, messages });
+        Ok(())
+    }
+
+    fn push_now(&self, addr: &str, args: Vec<RawArg>) -> BackendResult {
+        self.push(
+            vec![BackendMessage {
+                addr: addr.to_string(),
+                args,
+            }],
+            BUNDLE_IMMEDIATE,
+        )
+    }
+}
+
+fn param_args(node_id: i32, params: &[(&str, f32)]) -> Vec<RawArg> {
+    let mut args = vec![RawArg::Int(node_id)];
+    for &(name, value) in params {
+        args.push(RawArg::Str(name.to_string()));
+        args.push(RawArg::Float(value));
+    }
+    args
+}
+
+impl AudioBackend for ScoreBackend {
+    fn create_group(&self, group_id: i32, add_action: i32, target: i32) -> BackendResult {
+        self.push_now(
+            "/g_new",
+            vec![
+                RawArg::Int(group_id),
+                RawArg::Int(add_action),
+                RawArg::Int(target),
+            ],
+        )
+    }
+
+    fn create_synth(
+        &self,
+        def_name: &str,
+        node_id: i32,
+        group_id: i32,
+        params: &[(String, f32)],
+    ) -> BackendResult {
+        let mut args = vec![
+            RawArg::Str(def_name.to_string()),
+            RawArg::Int(node_id),
+            RawArg::Int(1), // addToTail
+            RawArg::Int(group_id),
+        ];
+        for (name, value) in params {
+            args.push(RawArg::Str(name.clone()));
+            args.push(RawArg::Float(*value));
+        }
+        self.push_now("/s_new", args)
+    }
+
+    fn free_node(&self, node_id: i32) -> BackendResult {
+        self.push_now("/n_free", vec![RawArg::Int(node_id)])
+    }
+
+    fn set_param(&self, node_id: i32, param: &str, value: f32) -> BackendResult {
+        self.push_now("/n_set", param_args(node_id, &[(param, value)]))
+    }
+
+    fn set_params(&self, node_id: i32, params: &[(&str, f32)]) -> BackendResult {
+        self.push_now("/n_set", param_args(node_id, params))
+    }
+
+    fn set_params_bundled(
+        &self,
+        node_id: i32,
+        params: &[(&str, f32)],
+        offset_secs: f64,
+    ) -> BackendResult {
+        self.push(
+            vec![BackendMessage {
+                addr: "/n_set".to_string(),
+                args: param_args(node_id, params),
+            }],
+            offset_secs,
+        )
+    }
+
+    fn send_bundle(&self, messages: Vec<BackendMessage>, offset_secs: f64) -> BackendResult {
+        self.push(messages, offset_secs)
+    }
+
+    fn send_unit_cmd(
+        &self,
+        node_id: i32,
+        ugen_index: i32,
+        cmd: &str,
+        args: Vec<RawArg>,
+    ) -> BackendResult {
+        let mut msg_args = vec![
+            RawArg::Int(node_id),
+            RawArg::Int(ugen_index),
+            RawArg::Str(cmd.to_string()),
+        ];
+        msg_args.extend(args);
+        self.push_now("/u_cmd", msg_args)
+    }
+
+    fn load_buffer(&self, bufnum: i32, path: &Path) -> BackendResult {
+        self.push_now(
+            "/b_allocRead",
+            vec![
+                RawArg::Int(bufnum),
+                RawArg::Str(path.to_string_lossy().to_string()),
+                RawArg::Int(0),
+                RawArg::Int(0),
+            ],
+        )
+    }
+
+    fn free_buffer(&self, bufnum: i32) -> BackendResult {
+        self.push_now("/b_free", vec![RawArg::Int(bufnum)])
+    }
+
+    fn alloc_buffer(&self, bufnum: i32, num_frames: i32, num_channels: i32) -> BackendResult {
+        self.push_now(
+            "/b_alloc",
+            vec![
+                RawArg::Int(bufnum),
+                RawArg::Int(num_frames),
+                RawArg::Int(num_channels),
+            ],
+        )
+    }
+
+    fn open_buffer_for_write(&self, bufnum: i32, path: &Path) -> BackendResult {
+        self.push_now(
+            "/b_write",
+            vec![
+                RawArg::Int(bufnum),
+                RawArg::Str(path.to_string_lossy().to_string()),
+                RawArg::Str("wav".to_string()),
+                RawArg::Str("float".to_string()),
+                RawArg::Int(0),
+                RawArg::Int(0),
+                RawArg::Int(1),
+            ],
+        )
+    }
+
+    fn close_buffer(&self, bufnum: i32) -> BackendResult {
+        self.push_now("/b_close", vec![RawArg::Int(bufnum)])
+    }
+
+    fn query_buffer(&self, bufnum: i32) -> BackendResult {
+        self.push_now("/b_query", vec![RawArg::Int(bufnum)])
+    }
+
+    fn send_raw(&self, addr: &str, args: Vec<RawArg>) -> BackendResult {
+        self.push_now(addr, args)
+    }
+}
@@ -0,0 +1,102 @@
+use super::audio_clips::load_stereo_buffer;
+use super::backend::{BackendMessage, RawArg};
+use super::{AudioEngine, GROUP_SOURCES};
+use imbolc_types::{InstrumentId, InstrumentState};
+
+impl AudioEngine {
+    /// Load buffers for instruments that were frozen or re-frozen, and free
+    /// buffers of instruments that are no longer frozen.
+    pub fn sync_frozen_buffers(&mut self, instruments: &InstrumentState) {
+        let Some(backend) = self.backend.as_ref() else {
+            return;
+        };
+
+        let stale: Vec<InstrumentId> = self
+            .frozen_buffers
+            .iter()
+            .filter(|(id, (path, _))| {
+                instruments
+                    .instrument(**id)
+                    .and_then(|inst| inst.frozen.as_ref())
+                    .is_none_or(|f| f.path != *path)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale {
+            if let Some((_, bufnum)) = self.frozen_buffers.remove(&id) {
+                let _ = backend.free_buffer(bufnum);
+            }
+        }
+
+        for inst in &instruments.instruments {
+            let Some(frozen) = inst.frozen.as_ref() else {
+                continue;
+            };
+            if self.frozen_buffers.contains_key(&inst.id) || !frozen.path.exists() {
+                continue;
+            }
+            let bufnum = self.next_bufnum;
+            self.next_bufnum += 1;
+            if let Err(e) = load_stereo_buffer(backend.as_ref(), bufnum, &frozen.path) {
+                log::warn!(target: "audio::samples", "Failed to load frozen render {:?}: {}", frozen.path, e);
+                continue;
+            }
+            self.frozen_buffers
+                .insert(inst.id, (frozen.path.clone(), bufnum));
+        }
+    }
+
+    /// Start playing an instrument's frozen render from `file_offset_secs` to
+    /// the end of the file, onto its source bus. Returns the synth node ID and
+    /// the bus it plays onto.
+    pub fn play_frozen_render(
+        &mut self,
+        instrument_id: InstrumentId,
+        length_secs: f32,
+        file_offset_secs: f64,
+        offset_secs: f64,
+    ) -> Result<(i32, i32), String> {
+        if self.backend.is_none() {
+            return Err("Not connected".to_string());
+        }
+        let (_, bufnum) = *self
+            .frozen_buffers
+            .get(&instrument_id)
+            .ok_or("Frozen render not loaded")?;
+        if length_secs <= 0.0 {
+            return Err("Frozen render has no length".to_string());
+        }
+        let out_bus = self
+            .instrument_source_bus(instrument_id)
+            .ok_or("No source bus for frozen instrument")?;
+        let slice_start = (file_offset_secs / length_secs as f64).clamp(0.0, 1.0);
+
+        let node_id = self.next_node_id;
+        self.next_node_id += 1;
+
+        let msg = BackendMessage {
+            addr: "/s_new".to_string(),
+            args: vec![
+                RawArg::Str("imbolc_sampler_oneshot".to_string()),
+                RawArg::Int(node_id),
+                RawArg::Int(0), // addToHead
+                RawArg::Int(GROUP_SOURCES),
+                RawArg::Str("bufnum".to_string()),
+                RawArg::Int(bufnum),
+                RawArg::Str("amp".to_string()),
+                RawArg::Float(1.0),
+                RawArg::Str("sliceStart".to_string()),
+                RawArg::Float(slice_start as f32),
+                RawArg::Str("sliceEnd".to_string()),
+                RawArg::Float(1.0),
+                RawArg::Str("rate".to_string()),
+                RawArg::Float(1.0),
+                RawArg::Str("out".to_string()),
+                RawArg::Int(out_bus),
+            ],
+        };
+        self.queue_timed_bundle(vec![msg], offset_secs)?;
+
+        Ok((node_id, out_bus))
+    }
+}
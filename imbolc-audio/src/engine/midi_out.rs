@@ -0,0 +1,165 @@
+use super::AudioEngine;
+use crate::midi_clock::MidiClockMessage;
+use crate::midi_out::{self, MidiOutEvent, MidiOutSender};
+use imbolc_types::ParameterTarget;
+
+impl AudioEngine {
+    /// Attach (or detach) the sink that receives scheduled MIDI output events.
+    pub fn set_midi_output(&mut self, sender: Option<MidiOutSender>) {
+        if sender.is_none() {
+            self.midi_out_all_notes_off();
+        }
+        self.midi_out_tx = sender;
+    }
+
+    fn send_midi_out(&self, port: u8, message: [u8; 3], offset_secs: f64) -> Result<(), String> {
+        self.send_midi_out_event(MidiOutEvent::after(port, message, offset_secs))
+    }
+
+    fn send_midi_out_event(&self, event: MidiOutEvent) -> Result<(), String> {
+        let tx = self
+            .midi_out_tx
+            .as_ref()
+            .ok_or("MIDI output not available")?;
+        tx.send(event).map_err(|e| e.to_string())
+    }
+
+    /// Send a clock or transport message (master sync)
+    pub fn midi_out_clock(
+        &self,
+        port: u8,
+        message: MidiClockMessage,
+        offset_secs: f64,
+    ) -> Result<(), String> {
+        let (bytes, len) = message.to_bytes();
+        self.send_midi_out_event(MidiOutEvent::bytes_after(port, &bytes[..len], offset_secs))
+    }
+
+    /// Send a note-on to an external MIDI instrument
+    pub(super) fn midi_out_note_on(
+        &mut self,
+        port: u8,
+        channel: u8,
+        pitch: u8,
+        velocity: f32,
+        offset_secs: f64,
+    ) -> Result<(), String> {
+        self.midi_out_held.insert((port, channel, pitch));
+        self.send_midi_out(
+            port,
+            midi_out::note_on(channel, pitch, velocity),
+            offset_secs,
+        )
+    }
+
+    /// Send a note-off to an external MIDI instrument
+    pub(super) fn midi_out_note_off(
+        &mut self,
+        port: u8,
+        channel: u8,
+        pitch: u8,
+        offset_secs: f64,
+    ) -> Result<(), String> {
+        self.midi_out_held.remove(&(port, channel, pitch));
+        self.send_midi_out(port, midi_out::note_off(channel, pitch), offset_secs)
+    }
+
+    /// Release every note still sounding on external MIDI instruments
+    pub(super) fn midi_out_all_notes_off(&mut self) {
+        for (port, channel, pitch) in std::mem::take(&mut self.midi_out_held) {
+            let _ = self.send_midi_out(port, midi_out::note_off(channel, pitch), 0.0);
+        }
+    }
+
+    /// Send an automated parameter as CC / pitch bend. Returns false when the
+    /// target has no MIDI mapping.
+    pub(super) fn midi_out_param(
+        &self,
+        port: u8,
+        channel: u8,
+        target: &ParameterTarget,
+        value: f32,
+        offset_secs: f64,
+    ) -> bool {
+        match midi_out::param_message(channel, target, value) {
+            Some(message) => {
+                let _ = self.send_midi_out(port, message, offset_secs);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imbolc_types::{InstrumentState, SessionState, SourceType};
+
+    #[test]
+    fn midi_out_voices_send_note_on_off_to_port() {
+        let mut engine = AudioEngine::new();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        engine.set_midi_output(Some(tx));
+
+        let mut instruments = InstrumentState::new();
+        let session = SessionState::new();
+        let id = instruments.add_instrument(SourceType::MidiOut {
+            port: 1,
+            channel: 2,
+        });
+
+        engine
+            .spawn_voice(id, 60, 1.0, 0.0, &instruments, &session)
+            .unwrap();
+        engine.release_voice(id, 60, 0.0, &instruments).unwrap();
+        engine
+            .spawn_voice(id, 64, 0.5, 0.0, &instruments, &session)
+            .unwrap();
+        engine.release_all_voices();
+
+        let events: Vec<MidiOutEvent> = rx.try_iter().collect();
+        let messages: Vec<[u8; 3]> = events.iter().map(|e| e.message).collect();
+        assert!(events.iter().all(|e| e.port == 1));
+        assert_eq!(
+            messages,
+            vec![
+                [0x92, 60, 127],
+                [0x82, 60, 0],
+                [0x92, 64, 64],
+                [0x82, 64, 0]
+            ]
+        );
+    }
+
+    #[test]
+    fn midi_out_instruments_skip_oneshot_triggers() {
+        let mut engine = AudioEngine::new();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        engine.set_midi_output(Some(tx));
+
+        let mut instruments = InstrumentState::new();
+        let session = SessionState::new();
+        let id = instruments.add_instrument(SourceType::MidiOut {
+            port: 0,
+            channel: 0,
+        });
+
+        engine
+            .trigger_instrument_oneshot(id, 440.0, 1.0, 0.0, &[], &instruments, &session)
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn midi_out_events_use_offset_as_due_time() {
+        let mut engine = AudioEngine::new();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        engine.set_midi_output(Some(tx));
+
+        let before = std::time::Instant::now();
+        engine.midi_out_note_on(0, 0, 60, 1.0, 0.25).unwrap();
+        let event = rx.try_recv().unwrap();
+        assert!(event.due >= before + std::time::Duration::from_millis(250));
+    }
+}
@@ -1,5 +1,9 @@
+mod audio_clips;
 mod automation;
 pub mod backend;
+mod freeze;
+mod midi_out;
+mod modulation;
 pub(crate) mod node_registry;
 mod recording;
 pub(crate) mod routing;
@@ -9,18 +13,21 @@ pub(crate) mod voice_allocator;
 mod voices;
 mod vst;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::Child;
 use std::sync::mpsc::Receiver;
 use std::time::Instant;
 
 use super::bus_allocator::BusAllocator;
 use backend::AudioBackend;
-use imbolc_types::{BufferId, BusId, EffectId, InstrumentId};
+use imbolc_types::{
+    AudioClipId, BufferId, BusId, ChannelMeterTarget, EffectId, InstrumentId, ModController,
+    SidechainSource,
+};
 use node_registry::NodeRegistry;
 use voice_allocator::VoiceAllocator;
 
-use recording::{ExportRecordingState, RecordingState};
+use recording::{ExportRecordingState, RecordingState, TakeRecordingState};
 
 #[allow(dead_code)]
 pub type ModuleId = u32;
@@ -83,6 +90,8 @@ pub struct VoiceChain {
     pub release_state: Option<(Instant, f32)>,
     /// Per-voice control bus triple (freq, gate, vel) for pool return
     pub control_buses: (i32, i32, i32),
+    /// Per-voice mod matrix buses (envelopes, summed targets) for pool return
+    pub mod_buses: Vec<i32>,
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +104,10 @@ pub struct InstrumentNodes {
     /// Ordered list of effect IDs matching the signal chain order (only enabled effects)
     pub effect_order: Vec<EffectId>,
     pub output: i32,
+    /// Channel meter reading the post-insert bus
+    pub meter: Option<i32>,
+    /// Mod matrix LFOs and chain-level route synths
+    pub modulators: Vec<i32>,
 }
 
 impl InstrumentNodes {
@@ -106,6 +119,7 @@ impl InstrumentNodes {
         if let Some(id) = self.lfo {
             ids.push(id);
         }
+        ids.extend_from_slice(&self.modulators);
         if let Some(id) = self.filter {
             ids.push(id);
         }
@@ -118,6 +132,9 @@ impl InstrumentNodes {
             }
         }
         ids.push(self.output);
+        if let Some(id) = self.meter {
+            ids.push(id);
+        }
         ids
     }
 }
@@ -151,8 +168,14 @@ pub struct AudioEngine {
     layer_group_effect_node_map: HashMap<(u32, EffectId), i32>,
     /// Layer group EQ synth nodes: group_id -> node_id
     layer_group_eq_node_map: HashMap<u32, i32>,
+    /// Channel meter synths on bus and layer group strips (instrument meters live in `node_map`)
+    strip_meter_node_map: HashMap<ChannelMeterTarget, i32>,
     /// Instrument final buses: instrument_id -> SC audio bus index (post-effects, pre-mixer)
     pub(crate) instrument_final_buses: HashMap<InstrumentId, i32>,
+    /// Sidechain key buses: source -> SC audio bus index (stereo, pre-fader)
+    sidechain_key_buses: HashMap<SidechainSource, i32>,
+    /// Sidechain key feed synth nodes, keyed by the instrument they tap
+    sidechain_feed_node_map: HashMap<InstrumentId, Vec<i32>>,
     /// Voice allocation, tracking, stealing, and control bus pooling
     pub(crate) voice_allocator: VoiceAllocator,
     /// Safety limiter synth node ID (persistent, never freed during routing rebuilds)
@@ -163,9 +186,15 @@ pub struct AudioEngine {
     analysis_node_ids: Vec<i32>,
     /// Sample buffer mapping: BufferId -> SuperCollider buffer number
     buffer_map: HashMap<BufferId, i32>,
+    /// Files behind loaded sample buffers, so an offline render can reload them
+    sample_paths: HashMap<BufferId, String>,
     /// Next available buffer number for SuperCollider
     #[allow(dead_code)]
     next_bufnum: i32,
+    /// Arrangement audio clip buffers: clip ID -> (file path, SC buffer number)
+    audio_clip_buffers: HashMap<AudioClipId, (std::path::PathBuf, i32)>,
+    /// Frozen instrument renders: instrument ID -> (file path, SC buffer number)
+    frozen_buffers: HashMap<InstrumentId, (std::path::PathBuf, i32)>,
     /// Whether wavetable buffers (100–107) have been initialized
     wavetables_initialized: bool,
     /// Active disk recording session
@@ -176,22 +205,38 @@ pub struct AudioEngine {
     export_state: Option<ExportRecordingState>,
     /// Buffers pending free after export stop
     pending_export_buffer_frees: Vec<(i32, Instant)>,
+    /// Input takes being recorded (one per armed instrument)
+    take_recordings: Vec<TakeRecordingState>,
+    /// Take buffers pending free (bufnum, when the file was closed)
+    pending_take_buffer_frees: Vec<(i32, Instant)>,
+    /// Index of the next take buffer to try
+    next_take_buffer: i32,
     /// Best-effort registry of which SC nodes are believed to be alive
     pub(crate) node_registry: NodeRegistry,
     /// Voice groups not tracked by `voice_allocator` (one-shots, stolen voices) ->
     /// control bus triple for return on /n_end.
     pub(crate) oneshot_buses: HashMap<i32, (i32, i32, i32)>,
+    /// Per-voice mod matrix buses of voice groups in `oneshot_buses`
+    pub(crate) oneshot_mod_buses: HashMap<i32, Vec<i32>>,
+    /// Last mod matrix controller values, re-applied when routing is rebuilt
+    mod_controller_values: HashMap<(InstrumentId, ModController), f32>,
     /// Dynamic scheduling lookahead for sequenced playback.
     /// Derived from buffer_size/sample_rate via `compute_lookahead()`.
     pub schedule_lookahead_secs: f64,
     /// Latest tuning drift in cents (JI vs ET) from most recent voice spawn
     pub(crate) last_drift_cents: f64,
+    /// Tempo under the playhead, which warped sampler voices follow
+    pub(crate) tempo_bpm: f32,
     /// OSC sender thread channel (None when no backend or test backend).
     osc_send_tx: Option<crossbeam_channel::Sender<super::osc_sender::OscSendEntry>>,
     /// Atomic queue depth counter for telemetry.
     osc_queue_depth: Option<std::sync::Arc<std::sync::atomic::AtomicUsize>>,
     /// Join handle for the OSC sender thread.
     osc_sender_handle: Option<std::thread::JoinHandle<()>>,
+    /// Scheduler channel for external MIDI instruments (None until attached).
+    midi_out_tx: Option<crate::midi_out::MidiOutSender>,
+    /// Notes sounding on external MIDI instruments: (port, channel, pitch)
+    midi_out_held: HashSet<(u8, u8, u8)>,
 }
 
 impl AudioEngine {
@@ -216,28 +261,53 @@ impl AudioEngine {
             bus_effect_node_map: HashMap::new(),
             layer_group_effect_node_map: HashMap::new(),
             layer_group_eq_node_map: HashMap::new(),
+            strip_meter_node_map: HashMap::new(),
             instrument_final_buses: HashMap::new(),
+            sidechain_key_buses: HashMap::new(),
+            sidechain_feed_node_map: HashMap::new(),
             voice_allocator: VoiceAllocator::new(),
             safety_node_id: None,
             meter_node_id: None,
             analysis_node_ids: Vec::new(),
             buffer_map: HashMap::new(),
+            sample_paths: HashMap::new(),
             next_bufnum: WAVETABLE_BUFNUM_START + WAVETABLE_NUM_TABLES, // Start after wavetable range
+            audio_clip_buffers: HashMap::new(),
+            frozen_buffers: HashMap::new(),
             wavetables_initialized: false,
             recording: None,
             pending_buffer_free: None,
             export_state: None,
             pending_export_buffer_frees: Vec::new(),
+            take_recordings: Vec::new(),
+            pending_take_buffer_frees: Vec::new(),
+            next_take_buffer: 0,
             node_registry: NodeRegistry::new(),
             oneshot_buses: HashMap::new(),
+            oneshot_mod_buses: HashMap::new(),
+            mod_controller_values: HashMap::new(),
             last_drift_cents: 0.0,
+            tempo_bpm: 120.0,
             schedule_lookahead_secs: DEFAULT_LOOKAHEAD_SECS,
             osc_send_tx: None,
             osc_queue_depth: None,
             osc_sender_handle: None,
+            midi_out_tx: None,
+            midi_out_held: HashSet::new(),
         }
     }
 
+    /// Engine that writes into a non-realtime score instead of a server.
+    /// Lookahead is zero so scheduled events land on their musical time.
+    pub(crate) fn with_score_backend(score: backend::ScoreBackend) -> Self {
+        let mut engine = Self::new();
+        engine.backend = Some(Box::new(score));
+        engine.is_running = true;
+        engine.server_status = ServerStatus::Connected;
+        engine.schedule_lookahead_secs = 0.0;
+        engine
+    }
+
     /// Update the scheduling lookahead based on audio device parameters.
     pub fn set_lookahead(&mut self, buffer_size: u32, sample_rate: u32) {
         self.schedule_lookahead_secs = compute_lookahead(buffer_size, sample_rate);
@@ -340,6 +410,12 @@ impl AudioEngine {
         self.is_running
     }
 
+    /// Bus an instrument's source writes to (before its processing chain)
+    pub fn instrument_source_bus(&self, instrument_id: InstrumentId) -> Option<i32> {
+        self.bus_allocator
+            .get_audio_bus(instrument_id, "source_out")
+    }
+
     pub fn status(&self) -> ServerStatus {
         self.server_status
     }
@@ -378,6 +454,19 @@ impl AudioEngine {
             let _ = backend.set_param(node_id, param, value);
         }
     }
+
+    /// Channel meter synths by node ID, for mapping meter replies to strips.
+    pub(crate) fn channel_meter_nodes(&self) -> HashMap<i32, ChannelMeterTarget> {
+        let instruments = self
+            .node_map
+            .iter()
+            .filter_map(|(&id, nodes)| Some((nodes.meter?, ChannelMeterTarget::Instrument(id))));
+        let strips = self
+            .strip_meter_node_map
+            .iter()
+            .map(|(&target, &node_id)| (node_id, target));
+        instruments.chain(strips).collect()
+    }
 }
 
 impl Drop for AudioEngine {
@@ -393,6 +482,28 @@ impl Default for AudioEngine {
     }
 }
 
+#[cfg(test)]
+impl AudioEngine {
+    /// Engine connected to a recording test backend, for tick-level tests
+    /// outside this module.
+    pub(crate) fn with_test_backend() -> (Self, std::sync::Arc<backend::TestBackend>) {
+        let backend = std::sync::Arc::new(backend::TestBackend::new());
+        let mut engine = Self::new();
+        engine.backend = Some(Box::new(backend::SharedTestBackend(std::sync::Arc::clone(
+            &backend,
+        ))));
+        engine.is_running = true;
+        engine.server_status = ServerStatus::Connected;
+        (engine, backend)
+    }
+
+    /// Allocate an instrument's source bus as a routing rebuild would.
+    pub(crate) fn alloc_test_source_bus(&mut self, instrument_id: InstrumentId) -> i32 {
+        self.bus_allocator
+            .get_or_alloc_audio_bus(instrument_id, "source_out")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::voice_allocator::MAX_VOICES_PER_INSTRUMENT;
@@ -400,7 +511,7 @@ mod tests {
     use crate::engine::backend::NullBackend;
     use imbolc_types::state::mixer::DEFAULT_BUS_COUNT;
     use imbolc_types::{AutomationTarget, BusId, ParamIndex, ParamValue};
-    use imbolc_types::{EffectType, FilterType, SourceType};
+    use imbolc_types::{EffectType, FilterType, ParameterTarget, SourceType};
 
     /// Test-only stand-in for AppState (which lives in imbolc-core).
     struct AppState {
@@ -525,6 +636,7 @@ mod tests {
             release_secs: 0.3,
             release_state: None,
             control_buses: (0, 0, 0),
+            mod_buses: Vec::new(),
         });
 
         engine
@@ -672,6 +784,7 @@ mod tests {
             release_secs: 0.3,
             release_state: None,
             control_buses: (0, 0, 0),
+            mod_buses: Vec::new(),
         }
     }
 
@@ -696,6 +809,7 @@ mod tests {
                 release_dur,
             )),
             control_buses: (0, 0, 0),
+            mod_buses: Vec::new(),
         }
     }
 
@@ -891,6 +1005,7 @@ mod tests {
             release_secs: 0.3,
             release_state: None,
             control_buses: buses,
+            mod_buses: Vec::new(),
         });
 
         assert_eq!(engine.voice_allocator.chains().len(), 1);
@@ -974,6 +1089,7 @@ mod tests {
                 release_secs: 0.3,
                 release_state: None,
                 control_buses,
+                mod_buses: Vec::new(),
             });
 
             engine
@@ -1017,6 +1133,74 @@ mod tests {
             );
         }
 
+        #[test]
+        fn voice_expression_targets_midi_and_source_nodes() {
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            let control_buses = engine.voice_allocator.alloc_control_buses();
+            engine.voice_allocator.add(VoiceChain {
+                instrument_id: inst_id,
+                pitch: 69,
+                velocity: 0.8,
+                group_id: 9200,
+                midi_node_id: 9201,
+                source_node: 9202,
+                spawn_time: Instant::now(),
+                release_secs: 0.3,
+                release_state: None,
+                control_buses,
+                mod_buses: Vec::new(),
+            });
+
+            engine
+                .set_voice_expression(
+                    inst_id,
+                    69,
+                    imbolc_types::ExpressionDimension::Bend,
+                    12.0,
+                    0.1,
+                    &state.instruments,
+                    &state.session,
+                )
+                .expect("bend");
+            engine
+                .set_voice_expression(
+                    inst_id,
+                    69,
+                    imbolc_types::ExpressionDimension::Pressure,
+                    0.5,
+                    0.2,
+                    &state.instruments,
+                    &state.session,
+                )
+                .expect("pressure");
+
+            let ops = backend.operations();
+            let bent = ops.iter().any(|op| {
+                matches!(
+                    op,
+                    TestOp::SendBundle { offset_secs, messages }
+                        if (*offset_secs - 0.1).abs() < f64::EPSILON
+                        && messages[0].0 == "/n_set"
+                        && messages[0].1[0] == RawArg::Int(9201)
+                        && matches!(messages[0].1[2], RawArg::Float(f) if (f - 880.0).abs() < 0.5)
+                )
+            });
+            assert!(bent, "bend should retune the midi node an octave up");
+            let pressed = ops.iter().any(|op| {
+                matches!(
+                    op,
+                    TestOp::SendBundle { messages, .. }
+                        if messages == &vec![(
+                            "/n_set".to_string(),
+                            vec![RawArg::Int(9202), RawArg::Str("pressure".to_string()), RawArg::Float(0.5)],
+                        )]
+                )
+            });
+            assert!(pressed, "pressure should go to the source synth");
+        }
+
         #[test]
         fn steal_voice_forces_gate_bus_zero_on_stolen_voice() {
             let (mut engine, backend) = engine_with_test_backend();
@@ -1039,6 +1223,7 @@ mod tests {
                 release_secs: 0.05,
                 release_state: None,
                 control_buses,
+                mod_buses: Vec::new(),
             });
 
             engine
@@ -1929,6 +2114,429 @@ mod tests {
                 "PreInsert tap should differ from post-effects final bus"
             );
         }
+
+        #[test]
+        fn sidechain_key_is_built_before_its_listener() {
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let bass = state.add_instrument(SourceType::AudioIn);
+            let kick = state.add_instrument(SourceType::AudioIn);
+            let key = imbolc_types::SidechainSource::Instrument {
+                id: kick,
+                tap: SendTapPoint::PostInsert,
+            };
+            if let Some(inst) = state.instruments.instrument_mut(bass) {
+                let effect_id = inst.add_effect(EffectType::SidechainComp);
+                inst.effect_by_id_mut(effect_id).unwrap().sidechain = Some(key);
+            }
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild routing");
+
+            let key_bus = *engine.sidechain_key_buses.get(&key).expect("key bus");
+            let feeds = engine
+                .sidechain_feed_node_map
+                .get(&kick)
+                .expect("key feeds");
+            let comp_node = *engine.node_map[&bass].effects.values().next().unwrap();
+            let synths = backend.synths_created();
+            let position = |node: i32| {
+                synths
+                    .iter()
+                    .position(
+                        |op| matches!(op, TestOp::CreateSynth { node_id, .. } if *node_id == node),
+                    )
+                    .expect("synth created")
+            };
+            let comp = position(comp_node);
+            for &feed in feeds {
+                assert!(
+                    position(feed) < comp,
+                    "kick feeds its key before the compressor reads it"
+                );
+            }
+            if let TestOp::CreateSynth { params, .. } = &synths[comp] {
+                assert!(params.contains(&("sidechain_in".to_string(), key_bus as f32)));
+            }
+        }
+
+        #[test]
+        fn every_strip_gets_a_channel_meter() {
+            use imbolc_types::ChannelMeterTarget;
+
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            if let Some(inst) = state.instruments.instrument_mut(inst_id) {
+                inst.layer.group = Some(1);
+            }
+            state
+                .session
+                .mixer
+                .add_layer_group_mixer(1, &[BusId::new(1)]);
+            let bus_effect = state.session.mixer.buses[0]
+                .effect_chain
+                .add_effect(EffectType::Reverb);
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild");
+
+            let meters = engine.channel_meter_nodes();
+            assert_eq!(meters.len(), 1 + 1 + state.session.mixer.buses.len());
+            let synths = backend.synths_created();
+            let meter_in = |target: ChannelMeterTarget| {
+                let (&node, _) = meters.iter().find(|(_, t)| **t == target).expect("meter");
+                synths
+                    .iter()
+                    .find_map(|op| match op {
+                        TestOp::CreateSynth {
+                            node_id, params, ..
+                        } if *node_id == node => params
+                            .iter()
+                            .find(|(name, _)| name == "in_l")
+                            .map(|(_, v)| *v as i32),
+                        _ => None,
+                    })
+                    .expect("meter synth")
+            };
+            assert_eq!(
+                meter_in(ChannelMeterTarget::Instrument(inst_id)),
+                engine.instrument_final_buses[&inst_id]
+            );
+            // Bus meters read the bus after its effects
+            let reverb_out = engine.bus_allocator.get_audio_bus(
+                InstrumentId::new(u32::MAX - 1),
+                &format!("bus_fx_{}_out", bus_effect),
+            );
+            assert_eq!(
+                Some(meter_in(ChannelMeterTarget::Bus(BusId::new(1)))),
+                reverb_out
+            );
+            assert!(meters
+                .values()
+                .any(|t| *t == ChannelMeterTarget::LayerGroup(1)));
+
+            engine
+                .rebuild_bus_processing(&state.instruments, &state.session)
+                .expect("rebuild buses");
+            assert_eq!(
+                engine.channel_meter_nodes().len(),
+                meters.len(),
+                "bus rebuild replaces strip meters instead of stacking them"
+            );
+        }
+
+        #[test]
+        fn frozen_instrument_routes_its_render_straight_to_output() {
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            if let Some(inst) = state.instruments.instrument_mut(inst_id) {
+                inst.set_filter(Some(FilterType::Lpf));
+                inst.add_effect(EffectType::Reverb);
+                inst.modulation.lfo.enabled = true;
+                inst.frozen = Some(imbolc_types::FrozenRender {
+                    path: "/tmp/freeze.wav".into(),
+                    start_tick: 0,
+                    end_tick: 1920,
+                    lead_in_secs: 0.015,
+                    length_secs: 2.0,
+                });
+            }
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild");
+
+            let nodes = &engine.node_map[&inst_id];
+            assert!(nodes.source.is_none() && nodes.lfo.is_none() && nodes.filter.is_none());
+            assert!(nodes.effects.is_empty());
+            let source_bus = engine.instrument_source_bus(inst_id).unwrap();
+            assert_eq!(engine.instrument_final_buses[&inst_id], source_bus);
+            let output_in = backend.synths_created().iter().find_map(|op| match op {
+                TestOp::CreateSynth {
+                    node_id, params, ..
+                } if *node_id == nodes.output => params
+                    .iter()
+                    .find(|(name, _)| name == "in")
+                    .map(|(_, v)| *v as i32),
+                _ => None,
+            });
+            assert_eq!(output_in, Some(source_bus));
+
+            // Frozen instruments take no voices
+            engine
+                .spawn_voice(inst_id, 60, 0.8, 0.0, &state.instruments, &state.session)
+                .expect("spawn");
+            assert!(engine.voice_allocator.chains().is_empty());
+        }
+
+        fn param(params: &[(String, f32)], name: &str) -> Option<f32> {
+            params.iter().find(|(k, _)| k == name).map(|(_, v)| *v)
+        }
+
+        #[test]
+        fn mod_matrix_routes_sum_into_filter_cutoff_bus() {
+            use imbolc_types::{ModController, ModMatrixSource, ModRoute, ParameterTarget};
+
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            if let Some(inst) = state.instruments.instrument_mut(inst_id) {
+                inst.set_filter(Some(FilterType::Lpf));
+                inst.modulation.lfo.enabled = true;
+                inst.modulation.lfo.target = ParameterTarget::FilterCutoff;
+                inst.modulation.matrix.routes = vec![
+                    ModRoute {
+                        source: ModMatrixSource::Lfo(1),
+                        target: ParameterTarget::FilterCutoff,
+                        depth: 0.3,
+                    },
+                    ModRoute {
+                        source: ModMatrixSource::ModWheel,
+                        target: ParameterTarget::FilterCutoff,
+                        depth: 0.2,
+                    },
+                ];
+            }
+            engine
+                .set_mod_controller(inst_id, ModController::ModWheel, 0.75)
+                .expect("set controller");
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild routing");
+
+            let synths = backend.synths_created();
+            let created = |def: &str| -> Vec<Vec<(String, f32)>> {
+                synths
+                    .iter()
+                    .filter_map(|op| match op {
+                        TestOp::CreateSynth {
+                            def_name, params, ..
+                        } if def_name == def => Some(params.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            // Original LFO plus one unit-depth matrix LFO
+            let lfos = created("imbolc_lfo");
+            assert_eq!(lfos.len(), 2);
+            assert!(lfos.iter().any(|p| param(p, "depth") == Some(1.0)));
+
+            // Two matrix routes plus the original LFO, all into one bus
+            let routes = created("imbolc_mod_route");
+            assert_eq!(routes.len(), 3);
+            let sum_bus = param(&routes[0], "out").unwrap();
+            assert!(routes.iter().all(|p| param(p, "out") == Some(sum_bus)));
+            let mut depths: Vec<f32> = routes.iter().filter_map(|p| param(p, "depth")).collect();
+            depths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(depths, vec![0.2, 0.3, 1.0]);
+
+            let filter = &created("imbolc_lpf")[0];
+            assert_eq!(param(filter, "cutoff_mod_in"), Some(sum_bus));
+            assert_eq!(param(filter, "res_mod_in"), Some(-1.0));
+
+            // Controller value restored on the rebuilt bus
+            assert!(backend
+                .find(
+                    |op| matches!(op, TestOp::SendRaw { addr, args } if addr == "/c_set"
+                    && args.get(1) == Some(&RawArg::Float(0.75)))
+                )
+                .is_some());
+
+            assert_eq!(engine.node_map[&inst_id].modulators.len(), 4);
+        }
+
+        #[test]
+        fn mod_matrix_voice_routes_use_per_voice_buses() {
+            use imbolc_types::{ModMatrixSource, ModRoute, ParameterTarget};
+
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            if let Some(inst) = state.instruments.instrument_mut(inst_id) {
+                inst.modulation.matrix.routes = vec![
+                    ModRoute {
+                        source: ModMatrixSource::Envelope(0),
+                        target: ParameterTarget::Pitch,
+                        depth: 0.5,
+                    },
+                    ModRoute {
+                        source: ModMatrixSource::Velocity,
+                        target: ParameterTarget::Level,
+                        depth: 1.0,
+                    },
+                ];
+            }
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild routing");
+            backend.clear();
+            engine
+                .spawn_voice(inst_id, 60, 0.8, 0.0, &state.instruments, &state.session)
+                .expect("spawn");
+
+            let messages = backend
+                .operations()
+                .into_iter()
+                .find_map(|op| match op {
+                    TestOp::SendBundle { messages, .. } => Some(messages),
+                    _ => None,
+                })
+                .expect("spawn bundle");
+            let defs: Vec<String> = messages
+                .iter()
+                .filter_map(|(_, args)| match args.first() {
+                    Some(RawArg::Str(def)) => Some(def.clone()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                defs,
+                vec![
+                    "imbolc_midi",
+                    "imbolc_mod_env",
+                    "imbolc_mod_vel",
+                    "imbolc_mod_route",
+                    "imbolc_mod_route",
+                    "imbolc_saw",
+                ]
+            );
+
+            let voice = &engine.voice_allocator.chains()[0];
+            assert_eq!(voice.mod_buses.len(), 3);
+            let source_args = &messages.last().unwrap().1;
+            let pitch_bus = source_args
+                .iter()
+                .position(|a| *a == RawArg::Str("pitch_mod_in".to_string()))
+                .map(|i| source_args[i + 1].clone());
+            assert!(
+                matches!(pitch_bus, Some(RawArg::Float(b)) if voice.mod_buses.contains(&(b as i32)))
+            );
+
+            let group_id = voice.group_id;
+            engine.process_node_ends(&[group_id]);
+            assert_eq!(engine.voice_allocator.mod_bus_pool_size(), 3);
+        }
+
+        #[test]
+        fn warped_time_stretch_follows_tempo() {
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::TimeStretch);
+            if let Some(config) = state
+                .instruments
+                .instrument_mut(inst_id)
+                .and_then(|inst| inst.sampler_config_mut())
+            {
+                config.buffer_id = Some(7);
+                config.source_bpm = Some(100.0);
+                config.warp = true;
+            }
+            engine.buffer_map.insert(7, 300);
+            engine.set_tempo(125.0, &state.instruments);
+            engine
+                .spawn_voice(inst_id, 60, 0.8, 0.0, &state.instruments, &state.session)
+                .expect("spawn");
+
+            let stretch_arg = |args: &[RawArg]| {
+                args.iter()
+                    .position(|a| *a == RawArg::Str("stretch".to_string()))
+                    .map(|i| args[i + 1].clone())
+            };
+            let spawned = backend
+                .operations()
+                .into_iter()
+                .find_map(|op| match op {
+                    TestOp::SendBundle { messages, .. } => {
+                        messages.into_iter().find(|(_, args)| {
+                            args.first() == Some(&RawArg::Str("imbolc_timestretch".to_string()))
+                        })
+                    }
+                    _ => None,
+                })
+                .expect("time stretch synth");
+            // 100 BPM material at 125 BPM plays in 0.8 of the time
+            assert!(
+                matches!(stretch_arg(&spawned.1), Some(RawArg::Float(v)) if (v - 0.8).abs() < 1e-6)
+            );
+
+            backend.clear();
+            engine.set_tempo(50.0, &state.instruments);
+            let source_node = engine.voice_allocator.chains()[0].source_node;
+            let retuned = backend.operations().into_iter().any(|op| {
+                matches!(
+                    op,
+                    TestOp::SendBundle { messages, .. }
+                        if messages == vec![("/n_set".to_string(), vec![
+                            RawArg::Int(source_node),
+                            RawArg::Str("stretch".to_string()),
+                            RawArg::Float(2.0),
+                        ])]
+                )
+            });
+            assert!(retuned, "sounding voice should follow the new tempo");
+        }
+
+        #[test]
+        fn param_locks_set_and_restore_chain_controls() {
+            let (mut engine, backend) = engine_with_test_backend();
+            let mut state = AppState::new();
+            let inst_id = state.add_instrument(SourceType::Saw);
+            let inst = state.instruments.instrument_mut(inst_id).unwrap();
+            inst.set_filter(Some(FilterType::Lpf));
+            inst.filter_mut().unwrap().cutoff.value = 2000.0;
+            engine
+                .rebuild_instrument_routing(&state.instruments, &state.session)
+                .expect("rebuild routing");
+            let filter_node = engine.node_map[&inst_id].filter.unwrap();
+
+            backend.clear();
+            engine
+                .set_param_locks(
+                    inst_id,
+                    &[
+                        (ParameterTarget::FilterCutoff, Some(400.0)),
+                        (ParameterTarget::FilterResonance, Some(0.7)),
+                        // Voice targets are not on the chain
+                        (ParameterTarget::Attack, Some(0.5)),
+                    ],
+                    &state.instruments,
+                    &state.session,
+                    0.1,
+                )
+                .unwrap();
+            engine
+                .set_param_locks(
+                    inst_id,
+                    &[(ParameterTarget::FilterCutoff, None)],
+                    &state.instruments,
+                    &state.session,
+                    0.2,
+                )
+                .unwrap();
+
+            let ops = backend.operations();
+            let sets: Vec<&TestOp> = ops
+                .iter()
+                .filter(|op| matches!(op, TestOp::SetParamsBundled { .. }))
+                .collect();
+            assert_eq!(sets.len(), 2);
+            assert!(matches!(
+                sets[0],
+                TestOp::SetParamsBundled { node_id, params, offset_secs }
+                    if *node_id == filter_node
+                    && *params == vec![("cutoff".to_string(), 400.0), ("resonance".to_string(), 0.7)]
+                    && *offset_secs == 0.1
+            ));
+            assert!(matches!(
+                sets[1],
+                TestOp::SetParamsBundled { node_id, params, offset_secs }
+                    if *node_id == filter_node
+                    && *params == vec![("cutoff".to_string(), 2000.0)]
+                    && *offset_secs == 0.2
+            ));
+        }
     }
 
     mod lookahead_tests {
@@ -0,0 +1,408 @@
+//! Mod matrix wiring.
+//!
+//! Every matrix source writes a control bus: free-running LFO synths and
+//! controller buses per instrument, mod envelopes and velocity per voice.
+//! Each route is an `imbolc_mod_route` synth that scales its source bus by
+//! the route depth and sums it into the target's bus, which the target
+//! synth reads through its `*_mod_in` control. Voice-level targets get a
+//! summing bus per voice; chain-level targets (filter, effects, pan, sends)
+//! get one per instrument and follow the most recent voice for per-note
+//! sources.
+
+use super::backend::{BackendMessage, RawArg};
+use super::{AudioEngine, GROUP_PROCESSING, GROUP_SOURCES};
+use imbolc_types::{Instrument, InstrumentId, ModController, ModMatrixSource, ParameterTarget};
+
+/// Which family of voice synth a voice-level target is wired into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum VoiceKind {
+    Oscillator,
+    Sampler,
+    TimeStretch,
+}
+
+/// Control on a voice synth that reads modulation for `target`, or `None`
+/// for chain-level targets and targets the synth family doesn't expose.
+pub(super) fn voice_mod_input(target: ParameterTarget, kind: VoiceKind) -> Option<&'static str> {
+    use ParameterTarget as T;
+    let common = match target {
+        T::Level => Some("amp_mod_in"),
+        T::Attack => Some("attack_mod_in"),
+        T::Decay => Some("decay_mod_in"),
+        T::Sustain => Some("sustain_mod_in"),
+        T::Release => Some("release_mod_in"),
+        _ => None,
+    };
+    if common.is_some() {
+        return common;
+    }
+    match kind {
+        VoiceKind::Oscillator => match target {
+            T::Pitch => Some("pitch_mod_in"),
+            T::Detune => Some("detune_mod_in"),
+            T::PulseWidth => Some("width_mod_in"),
+            T::FmIndex => Some("index_mod_in"),
+            T::WavetablePosition => Some("position_mod_in"),
+            T::FormantFreq => Some("formant_mod_in"),
+            T::SyncRatio => Some("sync_ratio_mod_in"),
+            T::Pressure => Some("pressure_mod_in"),
+            T::Embouchure => Some("embouchure_mod_in"),
+            T::GrainSize => Some("grain_size_mod_in"),
+            T::GrainDensity => Some("density_mod_in"),
+            T::FbFeedback => Some("feedback_mod_in"),
+            T::RingModDepth => Some("mod_depth_mod_in"),
+            T::ChaosParam => Some("chaos_param_mod_in"),
+            T::AdditiveRolloff => Some("rolloff_mod_in"),
+            T::MembraneTension => Some("tension_mod_in"),
+            _ => None,
+        },
+        VoiceKind::Sampler => match target {
+            T::SampleRate => Some("srate_mod_in"),
+            _ => None,
+        },
+        VoiceKind::TimeStretch => match target {
+            T::StretchRatio => Some("stretch_mod_in"),
+            T::PitchShift => Some("pitch_mod_in"),
+            T::GrainSize => Some("grain_size_mod_in"),
+            _ => None,
+        },
+    }
+}
+
+/// Whether `target` is read by a routing-chain synth (filter, effect,
+/// output or send) rather than by voices.
+pub(super) fn is_chain_target(target: ParameterTarget) -> bool {
+    matches!(
+        target,
+        ParameterTarget::FilterCutoff
+            | ParameterTarget::FilterResonance
+            | ParameterTarget::DelayTime
+            | ParameterTarget::DelayFeedback
+            | ParameterTarget::ReverbMix
+            | ParameterTarget::GateRate
+            | ParameterTarget::Pan
+            | ParameterTarget::SendLevel(_)
+    )
+}
+
+/// Whether the instrument's original LFO modulates `target`. Its send
+/// target applies to every send.
+fn legacy_lfo_hits(instrument: &Instrument, target: ParameterTarget) -> bool {
+    let lfo = &instrument.modulation.lfo;
+    lfo.enabled
+        && (lfo.target == target
+            || matches!(
+                (lfo.target, target),
+                (ParameterTarget::SendLevel(_), ParameterTarget::SendLevel(_))
+            ))
+}
+
+fn source_bus_name(source: ModMatrixSource) -> String {
+    format!("mod_{}", source.short_name())
+}
+
+fn target_bus_name(target: ParameterTarget) -> String {
+    format!("mod_target_{:?}", target)
+}
+
+fn route_params(in_bus: i32, out_bus: i32, depth: f32) -> Vec<(String, f32)> {
+    vec![
+        ("in".to_string(), in_bus as f32),
+        ("out".to_string(), out_bus as f32),
+        ("depth".to_string(), depth),
+    ]
+}
+
+/// Modulation wiring for one voice.
+#[derive(Debug, Default)]
+pub(super) struct VoiceModulation {
+    /// Helper synths to add to the voice group ahead of the source synth
+    pub messages: Vec<BackendMessage>,
+    /// `*_mod_in` control → bus pairs for the source synth
+    pub inputs: Vec<(&'static str, i32)>,
+    /// Per-voice buses to return to the pool when the voice ends
+    pub buses: Vec<i32>,
+}
+
+impl VoiceModulation {
+    /// Append the `*_mod_in` args for the source synth.
+    pub fn push_inputs(&self, args: &mut Vec<RawArg>) {
+        for (name, bus) in &self.inputs {
+            args.push(RawArg::Str(name.to_string()));
+            args.push(RawArg::Float(*bus as f32));
+        }
+    }
+}
+
+impl AudioEngine {
+    /// Create an instrument's mod matrix nodes: LFO synths for routed matrix
+    /// LFOs, controller buses seeded with their last value, and route synths
+    /// summing into a bus per routed chain-level target. Returns node IDs.
+    pub(super) fn build_mod_matrix(&mut self, instrument: &Instrument) -> Result<Vec<i32>, String> {
+        let matrix = &instrument.modulation.matrix;
+        let mut nodes = Vec::new();
+        if matrix.routes.is_empty() {
+            return Ok(nodes);
+        }
+
+        for source in ModMatrixSource::all() {
+            if !matrix.uses_source(source) {
+                continue;
+            }
+            let bus = self
+                .bus_allocator
+                .get_or_alloc_control_bus(instrument.id, &source_bus_name(source));
+            let controller = match source {
+                ModMatrixSource::Lfo(i) => {
+                    let Some(lfo) = matrix.lfos.get(i as usize) else {
+                        continue;
+                    };
+                    let node_id = self.next_node_id;
+                    self.next_node_id += 1;
+                    let params = vec![
+                        ("out".to_string(), bus as f32),
+                        ("rate".to_string(), lfo.rate),
+                        ("depth".to_string(), 1.0),
+                        ("shape".to_string(), lfo.shape.index() as f32),
+                    ];
+                    let client = self.backend.as_ref().ok_or("Not connected")?;
+                    client
+                        .create_synth("imbolc_lfo", node_id, GROUP_SOURCES, &params)
+                        .map_err(|e| e.to_string())?;
+                    nodes.push(node_id);
+                    continue;
+                }
+                ModMatrixSource::Aftertouch => ModController::Aftertouch,
+                ModMatrixSource::ModWheel => ModController::ModWheel,
+                // Written by each voice
+                ModMatrixSource::Envelope(_) | ModMatrixSource::Velocity => continue,
+            };
+            let value = self
+                .mod_controller_values
+                .get(&(instrument.id, controller))
+                .copied()
+                .unwrap_or(0.0);
+            let client = self.backend.as_ref().ok_or("Not connected")?;
+            client
+                .send_raw("/c_set", vec![RawArg::Int(bus), RawArg::Float(value)])
+                .map_err(|e| e.to_string())?;
+        }
+
+        // Route synths join processing ahead of this instrument's chain: after
+        // every voice has written its per-note sources, before the sums are read.
+        for target in matrix.routed_targets() {
+            if !is_chain_target(target) {
+                continue;
+            }
+            let out_bus = self
+                .bus_allocator
+                .get_or_alloc_control_bus(instrument.id, &target_bus_name(target));
+            let mut inputs: Vec<(i32, f32)> = matrix
+                .routes_to(target)
+                .filter_map(|r| {
+                    self.bus_allocator
+                        .get_control_bus(instrument.id, &source_bus_name(r.source))
+                        .map(|bus| (bus, r.depth))
+                })
+                .collect();
+            if legacy_lfo_hits(instrument, target) {
+                if let Some(lfo_bus) = self.bus_allocator.get_control_bus(instrument.id, "lfo_out")
+                {
+                    inputs.push((lfo_bus, 1.0));
+                }
+            }
+            for (in_bus, depth) in inputs {
+                let node_id = self.next_node_id;
+                self.next_node_id += 1;
+                let client = self.backend.as_ref().ok_or("Not connected")?;
+                client
+                    .create_synth(
+                        "imbolc_mod_route",
+                        node_id,
+                        GROUP_PROCESSING,
+                        &route_params(in_bus, out_bus, depth),
+                    )
+                    .map_err(|e| e.to_string())?;
+                nodes.push(node_id);
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// Control bus a chain-level target's `*_mod_in` should read, or -1 when
+    /// nothing modulates it. A target only the original LFO reaches reads the
+    /// LFO bus directly.
+    pub(super) fn chain_mod_bus(&self, instrument: &Instrument, target: ParameterTarget) -> f32 {
+        if instrument.is_frozen() {
+            return -1.0;
+        }
+        let port = if instrument.modulation.matrix.has_routes_to(target) {
+            target_bus_name(target)
+        } else if legacy_lfo_hits(instrument, target) {
+            "lfo_out".to_string()
+        } else {
+            return -1.0;
+        };
+        self.bus_allocator
+            .get_control_bus(instrument.id, &port)
+            .map(|b| b as f32)
+            .unwrap_or(-1.0)
+    }
+
+    /// Build the modulation helpers for a voice being spawned in `group_id`.
+    pub(super) fn voice_modulation(
+        &mut self,
+        instrument: &Instrument,
+        kind: VoiceKind,
+        group_id: i32,
+        gate_bus: i32,
+        vel_bus: i32,
+    ) -> VoiceModulation {
+        let id = instrument.id;
+        let matrix = &instrument.modulation.matrix;
+        let mut vm = VoiceModulation::default();
+
+        // Mod envelopes run per voice and mirror to the last-note bus
+        let mut env_buses: Vec<Option<i32>> = vec![None; matrix.envelopes.len()];
+        for (i, env) in matrix.envelopes.iter().enumerate() {
+            let source = ModMatrixSource::Envelope(i as u8);
+            if !matrix.uses_source(source) {
+                continue;
+            }
+            let Some(note_bus) = self
+                .bus_allocator
+                .get_control_bus(id, &source_bus_name(source))
+            else {
+                continue;
+            };
+            let bus = self.voice_allocator.alloc_mod_bus();
+            vm.buses.push(bus);
+            env_buses[i] = Some(bus);
+            let params = vec![
+                ("out".to_string(), bus as f32),
+                ("note_out".to_string(), note_bus as f32),
+                ("gate_in".to_string(), gate_bus as f32),
+                ("attack".to_string(), env.attack),
+                ("decay".to_string(), env.decay),
+                ("sustain".to_string(), env.sustain),
+                ("release".to_string(), env.release),
+            ];
+            self.push_voice_synth(&mut vm, "imbolc_mod_env", group_id, &params);
+        }
+        if matrix.uses_source(ModMatrixSource::Velocity) {
+            if let Some(note_bus) = self
+                .bus_allocator
+                .get_control_bus(id, &source_bus_name(ModMatrixSource::Velocity))
+            {
+                let params = vec![
+                    ("out".to_string(), note_bus as f32),
+                    ("vel_in".to_string(), vel_bus as f32),
+                ];
+                self.push_voice_synth(&mut vm, "imbolc_mod_vel", group_id, &params);
+            }
+        }
+
+        let legacy_bus = if instrument.modulation.lfo.enabled {
+            self.bus_allocator.get_control_bus(id, "lfo_out")
+        } else {
+            None
+        };
+        let mut targets = matrix.routed_targets();
+        if !targets.contains(&instrument.modulation.lfo.target) {
+            targets.push(instrument.modulation.lfo.target);
+        }
+        for target in targets {
+            let Some(input) = voice_mod_input(target, kind) else {
+                continue;
+            };
+            let legacy = legacy_bus.filter(|_| legacy_lfo_hits(instrument, target));
+            if !matrix.has_routes_to(target) {
+                if let Some(lfo_bus) = legacy {
+                    vm.inputs.push((input, lfo_bus));
+                }
+                continue;
+            }
+
+            let out_bus = self.voice_allocator.alloc_mod_bus();
+            vm.buses.push(out_bus);
+            let mut sources: Vec<(i32, f32)> = matrix
+                .routes_to(target)
+                .filter_map(|r| {
+                    let bus = match r.source {
+                        ModMatrixSource::Envelope(i) => env_buses.get(i as usize).copied()?,
+                        ModMatrixSource::Velocity => Some(vel_bus),
+                        source => self
+                            .bus_allocator
+                            .get_control_bus(id, &source_bus_name(source)),
+                    };
+                    bus.map(|b| (b, r.depth))
+                })
+                .collect();
+            if let Some(lfo_bus) = legacy {
+                sources.push((lfo_bus, 1.0));
+            }
+            for (in_bus, depth) in sources {
+                self.push_voice_synth(
+                    &mut vm,
+                    "imbolc_mod_route",
+                    group_id,
+                    &route_params(in_bus, out_bus, depth),
+                );
+            }
+            vm.inputs.push((input, out_bus));
+        }
+
+        vm
+    }
+
+    fn push_voice_synth(
+        &mut self,
+        vm: &mut VoiceModulation,
+        def: &str,
+        group_id: i32,
+        params: &[(String, f32)],
+    ) {
+        let node_id = self.next_node_id;
+        self.next_node_id += 1;
+        let mut args = vec![
+            RawArg::Str(def.to_string()),
+            RawArg::Int(node_id),
+            RawArg::Int(1), // addToTail
+            RawArg::Int(group_id),
+        ];
+        for (name, value) in params {
+            args.push(RawArg::Str(name.clone()));
+            args.push(RawArg::Float(*value));
+        }
+        vm.messages.push(BackendMessage {
+            addr: "/s_new".to_string(),
+            args,
+        });
+    }
+
+    /// Set a mod matrix controller (aftertouch, mod wheel) for an instrument.
+    /// The value is kept so routing rebuilds restore it.
+    pub fn set_mod_controller(
+        &mut self,
+        instrument_id: InstrumentId,
+        controller: ModController,
+        value: f32,
+    ) -> Result<(), String> {
+        self.mod_controller_values
+            .insert((instrument_id, controller), value);
+        if !self.is_running {
+            return Ok(());
+        }
+        let Some(bus) = self
+            .bus_allocator
+            .get_control_bus(instrument_id, &source_bus_name(controller.source()))
+        else {
+            return Ok(());
+        };
+        let client = self.backend.as_ref().ok_or("Not connected")?;
+        client
+            .send_raw("/c_set", vec![RawArg::Int(bus), RawArg::Float(value)])
+            .map_err(|e| e.to_string())
+    }
+}
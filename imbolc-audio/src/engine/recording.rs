@@ -18,6 +18,12 @@ pub(super) struct ExportRecordingState {
     pub recordings: Vec<RecordingState>,
 }
 
+/// An input take being written to disk from an instrument's source bus
+pub(super) struct TakeRecordingState {
+    pub instrument_id: InstrumentId,
+    pub recording: RecordingState,
+}
+
 impl AudioEngine {
     /// Buffer number reserved for disk recording (well above sampler range)
     const RECORD_BUFNUM: i32 = 900;
@@ -25,6 +31,11 @@ impl AudioEngine {
     /// First buffer number for export operations
     const EXPORT_BUFNUM_START: i32 = 901;
 
+    /// Buffer numbers cycled through by take recordings (below SC's default
+    /// limit of 1024 buffers, above the stem export range)
+    const TAKE_BUFNUM_START: i32 = 960;
+    const TAKE_BUFNUM_COUNT: i32 = 64;
+
     /// Start recording audio from the given bus to a WAV file.
     pub fn start_recording(&mut self, bus: i32, path: &Path) -> Result<(), String> {
         if self.recording.is_some() {
@@ -145,7 +156,7 @@ impl AudioEngine {
         if self.export_state.is_some() {
             return Err("Already exporting".to_string());
         }
-        if self.recording.is_some() {
+        if self.recording.is_some() || !self.take_recordings.is_empty() {
             return Err("Already recording".to_string());
         }
         let backend = self.backend.as_ref().ok_or("Not connected")?;
@@ -210,7 +221,7 @@ impl AudioEngine {
         if self.export_state.is_some() {
             return Err("Already exporting".to_string());
         }
-        if self.recording.is_some() {
+        if self.recording.is_some() || !self.take_recordings.is_empty() {
             return Err("Already recording".to_string());
         }
         if instrument_buses.is_empty() {
@@ -319,4 +330,155 @@ impl AudioEngine {
     pub fn is_exporting(&self) -> bool {
         self.export_state.is_some()
     }
+
+    // ── Take recording (multi-track input) ────────────────────────
+
+    /// Start one take per instrument, each written from the instrument's
+    /// source bus to its own file. The buffers are opened now; the DiskOut
+    /// synths all start together `offset_secs` from now, so every file
+    /// begins at the same point on the timeline.
+    pub fn start_takes(
+        &mut self,
+        takes: &[(InstrumentId, PathBuf)],
+        offset_secs: f64,
+    ) -> Result<(), String> {
+        if !self.take_recordings.is_empty() {
+            return Err("Already recording takes".to_string());
+        }
+        if self.export_state.is_some() {
+            return Err("Cannot record while exporting".to_string());
+        }
+        if takes.is_empty() {
+            return Err("No instruments to record".to_string());
+        }
+        if self.backend.is_none() {
+            return Err("Not connected".to_string());
+        }
+
+        let mut prepare = Vec::new();
+        let mut start = Vec::new();
+        let mut recordings = Vec::new();
+        for (instrument_id, path) in takes {
+            let bus = self
+                .instrument_source_bus(*instrument_id)
+                .ok_or_else(|| format!("No audio bus for instrument {}", instrument_id))?;
+            let bufnum = self.alloc_take_bufnum(&recordings)?;
+            let node_id = self.next_node_id;
+            self.next_node_id += 1;
+
+            prepare.push(BackendMessage {
+                addr: "/b_alloc".to_string(),
+                args: vec![RawArg::Int(bufnum), RawArg::Int(131072), RawArg::Int(2)],
+            });
+            prepare.push(BackendMessage {
+                addr: "/b_write".to_string(),
+                args: vec![
+                    RawArg::Int(bufnum),
+                    RawArg::Str(path.to_string_lossy().to_string()),
+                    RawArg::Str("wav".to_string()),
+                    RawArg::Str("float".to_string()),
+                    RawArg::Int(0),
+                    RawArg::Int(0),
+                    RawArg::Int(1),
+                ],
+            });
+            start.push(BackendMessage {
+                addr: "/s_new".to_string(),
+                args: vec![
+                    RawArg::Str("imbolc_disk_record".to_string()),
+                    RawArg::Int(node_id),
+                    RawArg::Int(1), // addToTail
+                    RawArg::Int(GROUP_RECORD),
+                    RawArg::Str("bufnum".to_string()),
+                    RawArg::Float(bufnum as f32),
+                    RawArg::Str("in".to_string()),
+                    RawArg::Float(bus as f32),
+                ],
+            });
+            recordings.push(TakeRecordingState {
+                instrument_id: *instrument_id,
+                recording: RecordingState {
+                    bufnum,
+                    node_id,
+                    path: path.clone(),
+                    started_at: Instant::now() + Duration::from_secs_f64(offset_secs.max(0.0)),
+                },
+            });
+        }
+
+        let backend = self.backend.as_ref().ok_or("Not connected")?;
+        backend
+            .send_bundle(prepare, BUNDLE_IMMEDIATE)
+            .map_err(|e| e.to_string())?;
+        self.queue_timed_bundle(start, offset_secs)?;
+
+        self.take_recordings = recordings;
+        Ok(())
+    }
+
+    /// Stop all takes `offset_secs` from now and return the instrument and
+    /// file of each. Buffers are freed by `poll_pending_take_buffer_frees()`
+    /// once scsynth has had time to flush the files.
+    pub fn stop_takes(&mut self, offset_secs: f64) -> Vec<(InstrumentId, PathBuf)> {
+        let takes = std::mem::take(&mut self.take_recordings);
+        let mut finished = Vec::new();
+        for take in takes {
+            let rec = take.recording;
+            let messages = vec![
+                BackendMessage {
+                    addr: "/n_free".to_string(),
+                    args: vec![RawArg::Int(rec.node_id)],
+                },
+                BackendMessage {
+                    addr: "/b_close".to_string(),
+                    args: vec![RawArg::Int(rec.bufnum)],
+                },
+            ];
+            if self.queue_timed_bundle(messages, offset_secs).is_ok() {
+                let closes_at = Instant::now() + Duration::from_secs_f64(offset_secs.max(0.0));
+                self.pending_take_buffer_frees.push((rec.bufnum, closes_at));
+                finished.push((take.instrument_id, rec.path));
+            }
+        }
+        finished
+    }
+
+    /// Free take buffers once their files have been closed.
+    pub fn poll_pending_take_buffer_frees(&mut self) {
+        let backend = &self.backend;
+        self.pending_take_buffer_frees.retain(|(bufnum, when)| {
+            if when.elapsed() >= Duration::from_millis(500) {
+                if let Some(backend) = backend {
+                    let _ = backend.free_buffer(*bufnum);
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn is_recording_takes(&self) -> bool {
+        !self.take_recordings.is_empty()
+    }
+
+    /// Pick the next take buffer that is neither recording nor waiting to be freed.
+    fn alloc_take_bufnum(&mut self, starting: &[TakeRecordingState]) -> Result<i32, String> {
+        for _ in 0..Self::TAKE_BUFNUM_COUNT {
+            let bufnum = Self::TAKE_BUFNUM_START + self.next_take_buffer;
+            self.next_take_buffer = (self.next_take_buffer + 1) % Self::TAKE_BUFNUM_COUNT;
+            let busy = starting
+                .iter()
+                .chain(&self.take_recordings)
+                .any(|t| t.recording.bufnum == bufnum)
+                || self
+                    .pending_take_buffer_frees
+                    .iter()
+                    .any(|(b, _)| *b == bufnum);
+            if !busy {
+                return Ok(bufnum);
+            }
+        }
+        Err("No free take recording buffers".to_string())
+    }
 }
@@ -5,9 +5,10 @@ use super::{
     VST_UGEN_INDEX,
 };
 use imbolc_types::{
-    BusId, CustomSynthDefRegistry, EffectId, EffectType, FilterType, Instrument, InstrumentId,
-    InstrumentState, LayerGroupMixer, MixerBus, ParamValue, ParameterTarget, SendTapPoint,
-    SessionState, SourceType, SourceTypeExt,
+    sidechain_sources, BusId, ChannelMeterTarget, CustomSynthDefRegistry, EffectId, EffectSlot,
+    EffectType, FilterType, Instrument, InstrumentId, InstrumentState, LatencyPlan,
+    LayerGroupMixer, MixerBus, ParamValue, ParameterTarget, SendTapPoint, SessionState,
+    SidechainSource, SourceType, SourceTypeExt,
 };
 use std::collections::HashMap;
 
@@ -19,7 +20,8 @@ pub(crate) enum RoutingRebuildPhase {
     TearDown,
     /// Allocate buses for mixer buses and layer groups.
     AllocBuses,
-    /// Build chain + sends for instrument at index `i` in the snapshot.
+    /// Build chain + sends for the instrument at step `i` of the snapshot's
+    /// sidechain build order.
     BuildInstrument(usize),
     /// Build bus output synths, layer group outputs, restore VST params.
     BuildOutputs,
@@ -64,6 +66,30 @@ impl AudioEngine {
         }
     }
 
+    /// Audio bus feeding an effect's key input. Falls back to the legacy
+    /// `sc_bus` mixer-bus index for SidechainComp without a key source.
+    fn sidechain_in(&self, effect: &EffectSlot) -> f32 {
+        if let Some(source) = effect.sidechain_source() {
+            return self.sidechain_key_buses.get(&source).copied().unwrap_or(0) as f32;
+        }
+        if effect.effect_type != EffectType::SidechainComp {
+            return 0.0;
+        }
+        match effect
+            .params
+            .iter()
+            .find(|p| p.name == "sc_bus")
+            .map(|p| &p.value)
+        {
+            Some(ParamValue::Int(v)) if *v > 0 => self
+                .bus_audio_buses
+                .get(&BusId::new(*v as u8))
+                .copied()
+                .unwrap_or(0) as f32,
+            _ => 0.0,
+        }
+    }
+
     // ── Shared helpers for per-instrument chain building ──────────
 
     /// Build the signal chain for a single instrument: source → LFO → filter → EQ → effects → output.
@@ -80,9 +106,19 @@ impl AudioEngine {
         let mut effect_nodes: HashMap<EffectId, i32> = HashMap::new();
         let mut effect_order: Vec<EffectId> = Vec::new();
 
+        // A frozen instrument's stereo render stands in for its source and
+        // inserts; its output keeps the width the live chain had
+        let frozen = instrument.is_frozen();
+
         // Determine channel count based on channel config
-        let is_mono = instrument.mixer.channel_config.is_mono();
-        let channels = instrument.mixer.channel_config.channels();
+        let (is_mono, channels) = if frozen {
+            (Self::post_insert_channels(instrument) == 1, 2)
+        } else {
+            (
+                instrument.mixer.channel_config.is_mono(),
+                instrument.mixer.channel_config.channels(),
+            )
+        };
 
         let source_out_bus = self.bus_allocator.get_or_alloc_audio_bus_with_channels(
             instrument.id,
@@ -92,7 +128,9 @@ impl AudioEngine {
         let mut current_bus = source_out_bus;
 
         // Source synth (AudioIn, BusIn, VST — oscillator voices are spawned dynamically)
-        if instrument.source.is_audio_input() {
+        if frozen {
+            // The freeze tick plays the render onto source_out
+        } else if instrument.source.is_audio_input() {
             let node_id = self.next_node_id;
             self.next_node_id += 1;
 
@@ -178,7 +216,7 @@ impl AudioEngine {
         }
 
         // LFO (if enabled)
-        let lfo_control_bus: Option<i32> = if instrument.modulation.lfo.enabled {
+        if instrument.modulation.lfo.enabled && !frozen {
             let lfo_node_id = self.next_node_id;
             self.next_node_id += 1;
             let lfo_out_bus = self
@@ -201,13 +239,17 @@ impl AudioEngine {
                 .map_err(|e| e.to_string())?;
 
             lfo_node = Some(lfo_node_id);
-            Some(lfo_out_bus)
+        }
+
+        // Mod matrix sources and chain-level routes
+        let modulators = if frozen {
+            Vec::new()
         } else {
-            None
+            self.build_mod_matrix(instrument)?
         };
 
         // Filter (if present)
-        if let Some(filter) = instrument.filter() {
+        if let Some(filter) = instrument.filter().filter(|_| !frozen) {
             let node_id = self.next_node_id;
             self.next_node_id += 1;
             let filter_out_bus = self.bus_allocator.get_or_alloc_audio_bus_with_channels(
@@ -216,20 +258,8 @@ impl AudioEngine {
                 channels,
             );
 
-            let cutoff_mod_bus = if instrument.modulation.lfo.enabled
-                && instrument.modulation.lfo.target == ParameterTarget::FilterCutoff
-            {
-                lfo_control_bus.map(|b| b as f32).unwrap_or(-1.0)
-            } else {
-                -1.0
-            };
-            let res_mod_bus = if instrument.modulation.lfo.enabled
-                && instrument.modulation.lfo.target == ParameterTarget::FilterResonance
-            {
-                lfo_control_bus.map(|b| b as f32).unwrap_or(-1.0)
-            } else {
-                -1.0
-            };
+            let cutoff_mod_bus = self.chain_mod_bus(instrument, ParameterTarget::FilterCutoff);
+            let res_mod_bus = self.chain_mod_bus(instrument, ParameterTarget::FilterResonance);
 
             let mut params = vec![
                 ("in".to_string(), current_bus as f32),
@@ -260,7 +290,7 @@ impl AudioEngine {
         // EQ (12-band parametric, if present)
         // Note: EQ doesn't have mono variants yet, stays stereo
         let mut eq_node: Option<i32> = None;
-        if let Some(eq) = instrument.eq() {
+        if let Some(eq) = instrument.eq().filter(|_| !frozen) {
             let node_id = self.next_node_id;
             self.next_node_id += 1;
             let eq_out_bus = self
@@ -289,7 +319,7 @@ impl AudioEngine {
 
         // Effects
         for effect in instrument.effects() {
-            if !effect.enabled {
+            if !effect.enabled || frozen {
                 continue;
             }
             let node_id = self.next_node_id;
@@ -312,19 +342,6 @@ impl AudioEngine {
             ];
             for p in &effect.params {
                 if effect.effect_type == EffectType::SidechainComp && p.name == "sc_bus" {
-                    let sc_bus_raw = match p.value {
-                        ParamValue::Int(v) => v as u8,
-                        _ => 0,
-                    };
-                    let sidechain_in = if sc_bus_raw == 0 {
-                        0.0
-                    } else {
-                        self.bus_audio_buses
-                            .get(&BusId::new(sc_bus_raw))
-                            .copied()
-                            .unwrap_or(0) as f32
-                    };
-                    params.push(("sidechain_in".to_string(), sidechain_in));
                     continue;
                 }
                 if effect.effect_type == EffectType::ConvolutionReverb && p.name == "ir_buffer" {
@@ -345,25 +362,24 @@ impl AudioEngine {
                 }
                 params.push((p.name.clone(), p.value.to_f32()));
             }
+            if let Some(control) = effect.effect_type.sidechain_control() {
+                params.push((control.to_string(), self.sidechain_in(effect)));
+            }
 
-            // Inject LFO mod bus if targeting this effect type
-            if instrument.modulation.lfo.enabled {
-                if let Some(lfo_bus) = lfo_control_bus {
-                    match (instrument.modulation.lfo.target, effect.effect_type) {
-                        (ParameterTarget::DelayTime, EffectType::Delay) => {
-                            params.push(("time_mod_in".to_string(), lfo_bus as f32));
-                        }
-                        (ParameterTarget::DelayFeedback, EffectType::Delay) => {
-                            params.push(("feedback_mod_in".to_string(), lfo_bus as f32));
-                        }
-                        (ParameterTarget::ReverbMix, EffectType::Reverb) => {
-                            params.push(("mix_mod_in".to_string(), lfo_bus as f32));
-                        }
-                        (ParameterTarget::GateRate, EffectType::Gate) => {
-                            params.push(("rate_mod_in".to_string(), lfo_bus as f32));
-                        }
-                        _ => {}
-                    }
+            // Inject mod buses for targets on this effect type
+            let mod_inputs: &[(ParameterTarget, &str)] = match effect.effect_type {
+                EffectType::Delay => &[
+                    (ParameterTarget::DelayTime, "time_mod_in"),
+                    (ParameterTarget::DelayFeedback, "feedback_mod_in"),
+                ],
+                EffectType::Reverb => &[(ParameterTarget::ReverbMix, "mix_mod_in")],
+                EffectType::Gate => &[(ParameterTarget::GateRate, "rate_mod_in")],
+                _ => &[],
+            };
+            for (target, input) in mod_inputs {
+                let mod_bus = self.chain_mod_bus(instrument, *target);
+                if mod_bus >= 0.0 {
+                    params.push((input.to_string(), mod_bus));
                 }
             }
 
@@ -407,13 +423,7 @@ impl AudioEngine {
                 instrument.mixer.mute || session.mixer.master_mute
             };
 
-            let pan_mod_bus = if instrument.modulation.lfo.enabled
-                && instrument.modulation.lfo.target == ParameterTarget::Pan
-            {
-                lfo_control_bus.map(|b| b as f32).unwrap_or(-1.0)
-            } else {
-                -1.0
-            };
+            let pan_mod_bus = self.chain_mod_bus(instrument, ParameterTarget::Pan);
 
             // Determine output destination: layer group bus, mixer bus, or master (0)
             let output_bus = if let Some(group_id) = instrument.layer.group {
@@ -455,6 +465,13 @@ impl AudioEngine {
             node_id
         };
 
+        let meter_right = if Self::post_insert_channels(instrument) == 1 {
+            current_bus
+        } else {
+            current_bus + 1
+        };
+        let meter_node_id = self.create_channel_meter(current_bus, meter_right, GROUP_OUTPUT)?;
+
         self.instrument_final_buses
             .insert(instrument.id, current_bus);
 
@@ -466,6 +483,8 @@ impl AudioEngine {
             effects: effect_nodes,
             effect_order,
             output: output_node_id,
+            meter: Some(meter_node_id),
+            modulators,
         };
         for nid in inst_nodes.all_node_ids() {
             self.node_registry.register(nid);
@@ -483,19 +502,6 @@ impl AudioEngine {
             .unwrap_or(16);
         let is_mono = instrument.mixer.channel_config.is_mono();
 
-        let send_lfo_bus = if instrument.modulation.lfo.enabled
-            && matches!(
-                instrument.modulation.lfo.target,
-                ParameterTarget::SendLevel(_)
-            ) {
-            self.bus_allocator
-                .get_control_bus(instrument.id, "lfo_out")
-                .map(|b| b as f32)
-                .unwrap_or(-1.0)
-        } else {
-            -1.0
-        };
-
         for send in instrument.mixer.sends.values() {
             if !send.enabled || send.level <= 0.0 {
                 continue;
@@ -516,8 +522,10 @@ impl AudioEngine {
                     ("out".to_string(), bus_audio as f32),
                     ("level".to_string(), send.level),
                 ];
-                if send_lfo_bus >= 0.0 {
-                    params.push(("level_mod_in".to_string(), send_lfo_bus));
+                let level_mod_bus =
+                    self.chain_mod_bus(instrument, ParameterTarget::SendLevel(send.bus_id));
+                if level_mod_bus >= 0.0 {
+                    params.push(("level_mod_in".to_string(), level_mod_bus));
                 }
                 let send_synth_def = if is_mono {
                     "imbolc_send_mono"
@@ -537,6 +545,157 @@ impl AudioEngine {
         Ok(())
     }
 
+    /// Channel count of an instrument's post-insert bus: the last enabled
+    /// effect's output, else EQ (always stereo), else the filter/source width.
+    fn post_insert_channels(instrument: &Instrument) -> i32 {
+        let channels = instrument.mixer.channel_config.channels() as i32;
+        let is_mono = instrument.mixer.channel_config.is_mono();
+        match instrument.effects().filter(|e| e.enabled).last() {
+            Some(effect) if is_mono && effect.effect_type.has_mono_variant() => 1,
+            Some(_) => 2,
+            None if instrument.eq().is_some() => 2,
+            None => channels,
+        }
+    }
+
+    /// Create key feed synths copying this instrument's signal onto every
+    /// sidechain key bus it contributes to. Runs right after the instrument's
+    /// chain so the feeds sit between it and any later listener in
+    /// GROUP_PROCESSING.
+    fn build_sidechain_feeds(
+        &mut self,
+        instrument: &Instrument,
+        keys: &[SidechainSource],
+    ) -> Result<(), String> {
+        let source_out_bus = self
+            .bus_allocator
+            .get_audio_bus(instrument.id, "source_out")
+            .unwrap_or(16);
+        let final_bus = self
+            .instrument_final_buses
+            .get(&instrument.id)
+            .copied()
+            .unwrap_or(source_out_bus);
+
+        for key in keys.iter().filter(|k| k.is_fed_by(instrument)) {
+            let (tap_bus, tap_channels, owner, name) = match *key {
+                SidechainSource::Instrument {
+                    tap: SendTapPoint::PreInsert,
+                    ..
+                } => (
+                    source_out_bus,
+                    instrument.mixer.channel_config.channels() as i32,
+                    instrument.id,
+                    "sc_key_pre",
+                ),
+                SidechainSource::Instrument {
+                    tap: SendTapPoint::PostInsert,
+                    ..
+                } => (
+                    final_bus,
+                    Self::post_insert_channels(instrument),
+                    instrument.id,
+                    "sc_key_post",
+                ),
+                SidechainSource::LayerGroup(group_id) => (
+                    final_bus,
+                    Self::post_insert_channels(instrument),
+                    InstrumentId::new(u32::MAX - 256 - group_id),
+                    "sidechain_key",
+                ),
+            };
+            let key_bus = self.bus_allocator.get_or_alloc_audio_bus(owner, name);
+            self.sidechain_key_buses.insert(*key, key_bus);
+
+            // Mono taps are copied to both key channels so stereo detectors see them
+            let outputs: &[(&str, i32)] = if tap_channels == 1 {
+                &[("imbolc_send_mono", 0), ("imbolc_send_mono", 1)]
+            } else {
+                &[("imbolc_send", 0)]
+            };
+            for &(def, offset) in outputs {
+                let node_id = self.next_node_id;
+                self.next_node_id += 1;
+                let params = vec![
+                    ("in".to_string(), tap_bus as f32),
+                    ("out".to_string(), (key_bus + offset) as f32),
+                    ("level".to_string(), 1.0),
+                ];
+                let client = self.backend.as_ref().ok_or("Not connected")?;
+                client
+                    .create_synth(def, node_id, GROUP_PROCESSING, &params)
+                    .map_err(|e| e.to_string())?;
+                self.node_registry.register(node_id);
+                self.sidechain_feed_node_map
+                    .entry(instrument.id)
+                    .or_default()
+                    .push(node_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a channel meter reading `in_l`/`in_r` at the tail of `group`.
+    /// Returns its node ID; the caller registers it.
+    fn create_channel_meter(&mut self, in_l: i32, in_r: i32, group: i32) -> Result<i32, String> {
+        let node_id = self.next_node_id;
+        self.next_node_id += 1;
+        let params = vec![
+            ("in_l".to_string(), in_l as f32),
+            ("in_r".to_string(), in_r as f32),
+        ];
+        let client = self.backend.as_ref().ok_or("Not connected")?;
+        client
+            .create_synth("imbolc_channel_meter", node_id, group, &params)
+            .map_err(|e| e.to_string())?;
+        Ok(node_id)
+    }
+
+    /// Create the meter on a bus or layer group strip, reading its post-effect bus.
+    fn build_strip_meter(
+        &mut self,
+        target: ChannelMeterTarget,
+        post_effect_bus: i32,
+    ) -> Result<(), String> {
+        if self.backend.is_none() {
+            return Ok(());
+        }
+        let node_id =
+            self.create_channel_meter(post_effect_bus, post_effect_bus + 1, GROUP_BUS_PROCESSING)?;
+        self.node_registry.register(node_id);
+        self.strip_meter_node_map.insert(target, node_id);
+        Ok(())
+    }
+
+    /// Free one instrument's key feed synths.
+    fn free_sidechain_feeds(&mut self, instrument_id: InstrumentId) {
+        let Some(nodes) = self.sidechain_feed_node_map.remove(&instrument_id) else {
+            return;
+        };
+        for node_id in nodes {
+            self.node_registry.unregister(node_id);
+            if let Some(ref client) = self.backend {
+                let _ = client.free_node(node_id);
+            }
+        }
+    }
+
+    /// Whether rebuilding just this instrument would break key ordering: it
+    /// feeds a key (its new feeds would land after the listeners) or listens
+    /// to a key that has no bus yet.
+    fn needs_full_rebuild_for_sidechain(
+        &self,
+        instrument: &Instrument,
+        keys: &[SidechainSource],
+    ) -> bool {
+        keys.iter().any(|k| k.is_fed_by(instrument))
+            || instrument
+                .effects()
+                .filter_map(|e| e.sidechain_source())
+                .any(|k| !self.sidechain_key_buses.contains_key(&k))
+    }
+
     /// Restore saved VST param values for a single instrument's source and effects.
     fn restore_instrument_vst_params(&self, instrument: &Instrument) {
         let client = match self.backend.as_ref() {
@@ -608,19 +767,6 @@ impl AudioEngine {
             ];
             for p in &effect.params {
                 if effect.effect_type == EffectType::SidechainComp && p.name == "sc_bus" {
-                    let sc_bus_raw = match p.value {
-                        ParamValue::Int(v) => v as u8,
-                        _ => 0,
-                    };
-                    let sidechain_in = if sc_bus_raw == 0 {
-                        0.0
-                    } else {
-                        self.bus_audio_buses
-                            .get(&BusId::new(sc_bus_raw))
-                            .copied()
-                            .unwrap_or(0) as f32
-                    };
-                    params.push(("sidechain_in".to_string(), sidechain_in));
                     continue;
                 }
                 if effect.effect_type == EffectType::ConvolutionReverb && p.name == "ir_buffer" {
@@ -641,6 +787,9 @@ impl AudioEngine {
                 }
                 params.push((p.name.clone(), p.value.to_f32()));
             }
+            if let Some(control) = effect.effect_type.sidechain_control() {
+                params.push((control.to_string(), self.sidechain_in(effect)));
+            }
 
             let client = self.backend.as_ref().ok_or("Not connected")?;
             client
@@ -732,19 +881,6 @@ impl AudioEngine {
             ];
             for p in &effect.params {
                 if effect.effect_type == EffectType::SidechainComp && p.name == "sc_bus" {
-                    let sc_bus_raw = match p.value {
-                        ParamValue::Int(v) => v as u8,
-                        _ => 0,
-                    };
-                    let sidechain_in = if sc_bus_raw == 0 {
-                        0.0
-                    } else {
-                        self.bus_audio_buses
-                            .get(&BusId::new(sc_bus_raw))
-                            .copied()
-                            .unwrap_or(0) as f32
-                    };
-                    params.push(("sidechain_in".to_string(), sidechain_in));
                     continue;
                 }
                 if effect.effect_type == EffectType::ConvolutionReverb && p.name == "ir_buffer" {
@@ -765,6 +901,9 @@ impl AudioEngine {
                 }
                 params.push((p.name.clone(), p.value.to_f32()));
             }
+            if let Some(control) = effect.effect_type.sidechain_control() {
+                params.push((control.to_string(), self.sidechain_in(effect)));
+            }
 
             let client = self.backend.as_ref().ok_or("Not connected")?;
             client
@@ -850,6 +989,12 @@ impl AudioEngine {
             for &node_id in self.layer_group_eq_node_map.values() {
                 let _ = client.free_node(node_id);
             }
+            for &node_id in self.strip_meter_node_map.values() {
+                let _ = client.free_node(node_id);
+            }
+            for &node_id in self.sidechain_feed_node_map.values().flatten() {
+                let _ = client.free_node(node_id);
+            }
             for chain in self.voice_allocator.drain_all() {
                 let _ = client.free_node(chain.group_id);
             }
@@ -860,11 +1005,14 @@ impl AudioEngine {
         self.bus_effect_node_map.clear();
         self.layer_group_effect_node_map.clear();
         self.layer_group_eq_node_map.clear();
+        self.strip_meter_node_map.clear();
         self.layer_group_audio_buses.clear();
         self.layer_group_node_map.clear();
         self.layer_group_send_node_map.clear();
         self.bus_audio_buses.clear();
         self.instrument_final_buses.clear();
+        self.sidechain_feed_node_map.clear();
+        self.sidechain_key_buses.clear();
         self.bus_allocator.reset();
         self.node_registry.invalidate_all();
 
@@ -886,10 +1034,13 @@ impl AudioEngine {
             self.layer_group_audio_buses.insert(group_id, group_bus);
         }
 
-        // Build signal chain for each instrument
+        // Build signal chain for each instrument, sidechain keys before their listeners
         let any_solo = state.any_instrument_solo();
-        for instrument in &state.instruments {
+        let keys = sidechain_sources(state, session);
+        for i in state.sidechain_build_order() {
+            let instrument = &state.instruments[i];
             self.build_instrument_chain(instrument, any_solo, session)?;
+            self.build_sidechain_feeds(instrument, &keys)?;
         }
 
         // Sync voice allocator bus watermarks from bus allocator
@@ -938,6 +1089,10 @@ impl AudioEngine {
                 self.node_registry.register(node_id);
                 self.layer_group_node_map
                     .insert(group_mixer.group_id, node_id);
+                self.build_strip_meter(
+                    ChannelMeterTarget::LayerGroup(group_mixer.group_id),
+                    post_effect_bus,
+                )?;
 
                 // Create group-level sends
                 for send in group_mixer.sends.values() {
@@ -992,6 +1147,7 @@ impl AudioEngine {
                 }
                 self.node_registry.register(node_id);
                 self.bus_node_map.insert(bus.id, node_id);
+                self.build_strip_meter(ChannelMeterTarget::Bus(bus.id), post_effect_bus)?;
             }
         }
 
@@ -1000,6 +1156,8 @@ impl AudioEngine {
             self.restore_instrument_vst_params(instrument);
         }
 
+        self.apply_latency_compensation(state, session)?;
+
         // (Re)create meter synth
         self.restart_meter();
 
@@ -1023,6 +1181,11 @@ impl AudioEngine {
             None => return Err(format!("Instrument {} not found", instrument_id)),
         };
 
+        let keys = sidechain_sources(state, session);
+        if self.needs_full_rebuild_for_sidechain(instrument, &keys) {
+            return self.rebuild_instrument_routing(state, session);
+        }
+
         let any_solo = state.any_instrument_solo();
         self.build_instrument_chain(instrument, any_solo, session)?;
 
@@ -1034,6 +1197,7 @@ impl AudioEngine {
 
         self.build_instrument_sends(instrument)?;
         self.restore_instrument_vst_params(instrument);
+        self.apply_latency_compensation(state, session)?;
 
         Ok(())
     }
@@ -1078,6 +1242,12 @@ impl AudioEngine {
             let _ = client.free_node(voice.group_id);
         }
 
+        // Free key feeds and forget the keys this instrument tapped
+        self.free_sidechain_feeds(instrument_id);
+        self.sidechain_key_buses.retain(
+            |key, _| !matches!(key, SidechainSource::Instrument { id, .. } if *id == instrument_id),
+        );
+
         // Clean up final bus entry
         self.instrument_final_buses.remove(&instrument_id);
 
@@ -1126,12 +1296,17 @@ impl AudioEngine {
             self.node_registry.unregister(node_id);
             let _ = client.free_node(node_id);
         }
+        for &node_id in self.strip_meter_node_map.values() {
+            self.node_registry.unregister(node_id);
+            let _ = client.free_node(node_id);
+        }
         self.bus_node_map.clear();
         self.bus_effect_node_map.clear();
         self.layer_group_node_map.clear();
         self.layer_group_send_node_map.clear();
         self.layer_group_effect_node_map.clear();
         self.layer_group_eq_node_map.clear();
+        self.strip_meter_node_map.clear();
 
         // Re-allocate layer group audio buses (new groups may have been added)
         // Keep existing bus_audio_buses — they don't change for bus effect rebuilds
@@ -1175,6 +1350,10 @@ impl AudioEngine {
                 self.node_registry.register(node_id);
                 self.layer_group_node_map
                     .insert(group_mixer.group_id, node_id);
+                self.build_strip_meter(
+                    ChannelMeterTarget::LayerGroup(group_mixer.group_id),
+                    post_effect_bus,
+                )?;
 
                 for send in group_mixer.sends.values() {
                     if !send.enabled || send.level <= 0.0 {
@@ -1225,9 +1404,12 @@ impl AudioEngine {
                     .map_err(|e| e.to_string())?;
                 self.node_registry.register(node_id);
                 self.bus_node_map.insert(bus.id, node_id);
+                self.build_strip_meter(ChannelMeterTarget::Bus(bus.id), post_effect_bus)?;
             }
         }
 
+        self.apply_latency_compensation(state, session)?;
+
         Ok(())
     }
 
@@ -1249,6 +1431,11 @@ impl AudioEngine {
             None => return Err(format!("Instrument {} not found", instrument_id)),
         };
 
+        let keys = sidechain_sources(state, session);
+        if self.needs_full_rebuild_for_sidechain(instrument, &keys) {
+            return self.rebuild_instrument_routing(state, session);
+        }
+
         // 1. Free existing nodes for this instrument
         {
             let client = self.backend.as_ref().ok_or("Not connected")?;
@@ -1299,6 +1486,7 @@ impl AudioEngine {
 
         self.build_instrument_sends(instrument)?;
         self.restore_instrument_vst_params(instrument);
+        self.apply_latency_compensation(state, session)?;
 
         Ok(())
     }
@@ -1352,6 +1540,12 @@ impl AudioEngine {
                     for &node_id in self.layer_group_eq_node_map.values() {
                         let _ = client.free_node(node_id);
                     }
+                    for &node_id in self.strip_meter_node_map.values() {
+                        let _ = client.free_node(node_id);
+                    }
+                    for &node_id in self.sidechain_feed_node_map.values().flatten() {
+                        let _ = client.free_node(node_id);
+                    }
                     for chain in self.voice_allocator.drain_all() {
                         let _ = client.free_node(chain.group_id);
                     }
@@ -1362,11 +1556,14 @@ impl AudioEngine {
                 self.bus_effect_node_map.clear();
                 self.layer_group_effect_node_map.clear();
                 self.layer_group_eq_node_map.clear();
+                self.strip_meter_node_map.clear();
                 self.layer_group_audio_buses.clear();
                 self.layer_group_node_map.clear();
                 self.layer_group_send_node_map.clear();
                 self.bus_audio_buses.clear();
                 self.instrument_final_buses.clear();
+                self.sidechain_feed_node_map.clear();
+                self.sidechain_key_buses.clear();
                 self.bus_allocator.reset();
                 self.node_registry.invalidate_all();
 
@@ -1405,8 +1602,10 @@ impl AudioEngine {
 
             RoutingRebuildPhase::BuildInstrument(i) => {
                 let any_solo = state.any_instrument_solo();
-                if let Some(instrument) = state.instruments.get(i) {
+                let order = state.sidechain_build_order();
+                if let Some(instrument) = order.get(i).map(|&idx| &state.instruments[idx]) {
                     self.build_instrument_chain(instrument, any_solo, session)?;
+                    self.build_sidechain_feeds(instrument, &sidechain_sources(state, session))?;
                     self.build_instrument_sends(instrument)?;
 
                     let next = i + 1;
@@ -1467,6 +1666,10 @@ impl AudioEngine {
                         self.node_registry.register(node_id);
                         self.layer_group_node_map
                             .insert(group_mixer.group_id, node_id);
+                        self.build_strip_meter(
+                            ChannelMeterTarget::LayerGroup(group_mixer.group_id),
+                            post_effect_bus,
+                        )?;
 
                         for send in group_mixer.sends.values() {
                             if !send.enabled || send.level <= 0.0 {
@@ -1525,6 +1728,7 @@ impl AudioEngine {
                         }
                         self.node_registry.register(node_id);
                         self.bus_node_map.insert(bus.id, node_id);
+                        self.build_strip_meter(ChannelMeterTarget::Bus(bus.id), post_effect_bus)?;
                     }
                 }
 
@@ -1543,6 +1747,8 @@ impl AudioEngine {
                     self.bus_allocator.next_control_bus,
                 );
 
+                self.apply_latency_compensation(state, session)?;
+
                 // (Re)create meter synth
                 self.restart_meter();
 
@@ -1635,6 +1841,51 @@ impl AudioEngine {
         Ok(())
     }
 
+    /// Apply plugin delay compensation to the live graph: every instrument output,
+    /// send, and layer group output/send is delayed so all paths reach master
+    /// together. Call after any routing change; missing nodes are skipped.
+    pub fn apply_latency_compensation(
+        &self,
+        state: &InstrumentState,
+        session: &SessionState,
+    ) -> Result<(), String> {
+        if !self.is_running {
+            return Ok(());
+        }
+        let client = self.backend.as_ref().ok_or("Not connected")?;
+        let plan = LatencyPlan::compute(state, session);
+
+        for (id, latency) in &plan.instruments {
+            if let Some(nodes) = self.node_map.get(id) {
+                client
+                    .set_param(nodes.output, "delay", latency.output_delay_secs)
+                    .map_err(|e| e.to_string())?;
+            }
+            for (bus_id, delay) in &latency.send_delay_secs {
+                if let Some(&node_id) = self.send_node_map.get(&(*id, *bus_id)) {
+                    client
+                        .set_param(node_id, "delay", *delay)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        for (group_id, latency) in &plan.layer_groups {
+            if let Some(&node_id) = self.layer_group_node_map.get(group_id) {
+                client
+                    .set_param(node_id, "delay", latency.output_delay_secs)
+                    .map_err(|e| e.to_string())?;
+            }
+            for (bus_id, delay) in &latency.send_delay_secs {
+                if let Some(&node_id) = self.layer_group_send_node_map.get(&(*group_id, *bus_id)) {
+                    client
+                        .set_param(node_id, "delay", *delay)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Set a source parameter on an instrument in real-time.
     /// Updates the persistent source node (AudioIn) and all active voice source nodes.
     pub fn set_source_param(
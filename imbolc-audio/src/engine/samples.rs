@@ -132,6 +132,7 @@ impl AudioEngine {
             .map_err(|e| e.to_string())?;
 
         self.buffer_map.insert(buffer_id, bufnum);
+        self.sample_paths.insert(buffer_id, path.to_string());
         Ok(bufnum)
     }
 
@@ -140,6 +141,7 @@ impl AudioEngine {
     pub fn free_sample(&mut self, buffer_id: BufferId) -> Result<(), String> {
         let backend = self.backend.as_ref().ok_or("Not connected")?;
 
+        self.sample_paths.remove(&buffer_id);
         if let Some(bufnum) = self.buffer_map.remove(&buffer_id) {
             backend.free_buffer(bufnum).map_err(|e| e.to_string())?;
         }
@@ -152,6 +154,17 @@ impl AudioEngine {
         self.buffer_map.get(&buffer_id).copied()
     }
 
+    /// Loaded sample buffers and the files they were read from
+    pub(crate) fn loaded_samples(&self) -> Vec<(BufferId, String)> {
+        let mut samples: Vec<(BufferId, String)> = self
+            .sample_paths
+            .iter()
+            .map(|(&id, path)| (id, path.clone()))
+            .collect();
+        samples.sort_by_key(|(id, _)| *id);
+        samples
+    }
+
     /// Check if a buffer is loaded
     #[allow(dead_code)]
     pub fn is_buffer_loaded(&self, buffer_id: BufferId) -> bool {
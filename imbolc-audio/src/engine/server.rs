@@ -533,6 +533,7 @@ impl AudioEngine {
     pub fn disconnect(&mut self) {
         self.stop_osc_sender();
         self.stop_recording();
+        self.stop_takes(0.0);
         if let Some(ref backend) = self.backend {
             if let Some(node_id) = self.safety_node_id.take() {
                 let _ = backend.free_node(node_id);
@@ -557,10 +558,22 @@ impl AudioEngine {
             for &node_id in self.layer_group_eq_node_map.values() {
                 let _ = backend.free_node(node_id);
             }
+            for &node_id in self.strip_meter_node_map.values() {
+                let _ = backend.free_node(node_id);
+            }
+            for &node_id in self.sidechain_feed_node_map.values().flatten() {
+                let _ = backend.free_node(node_id);
+            }
             // Free all loaded sample buffers
             for &bufnum in self.buffer_map.values() {
                 let _ = backend.free_buffer(bufnum);
             }
+            for &(_, bufnum) in self.audio_clip_buffers.values() {
+                let _ = backend.free_buffer(bufnum);
+            }
+            for &(_, bufnum) in self.frozen_buffers.values() {
+                let _ = backend.free_buffer(bufnum);
+            }
         }
         self.node_map.clear();
         self.send_node_map.clear();
@@ -568,6 +581,9 @@ impl AudioEngine {
         self.bus_effect_node_map.clear();
         self.layer_group_effect_node_map.clear();
         self.layer_group_eq_node_map.clear();
+        self.strip_meter_node_map.clear();
+        self.sidechain_feed_node_map.clear();
+        self.sidechain_key_buses.clear();
         self.bus_audio_buses.clear();
         // Drain all voices (no OSC needed since server is disconnecting)
         let _ = self.voice_allocator.drain_all();
@@ -576,8 +592,13 @@ impl AudioEngine {
             self.voice_allocator
                 .return_control_buses(buses.0, buses.1, buses.2);
         }
+        for (_, buses) in self.oneshot_mod_buses.drain() {
+            self.voice_allocator.return_mod_buses(&buses);
+        }
         self.analysis_node_ids.clear();
         self.buffer_map.clear();
+        self.audio_clip_buffers.clear();
+        self.frozen_buffers.clear();
         self.bus_allocator.reset();
         self.node_registry.invalidate_all();
         self.groups_created = false;
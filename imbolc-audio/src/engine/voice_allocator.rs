@@ -17,6 +17,8 @@ pub struct VoiceAllocator {
     next_control_bus: i32,
     /// Pool of freed control bus triples (freq, gate, vel) available for reuse
     control_bus_pool: Vec<(i32, i32, i32)>,
+    /// Pool of freed per-voice mod matrix buses available for reuse
+    mod_bus_pool: Vec<i32>,
 }
 
 #[allow(dead_code)]
@@ -27,6 +29,7 @@ impl VoiceAllocator {
             next_audio_bus: 16,
             next_control_bus: 0,
             control_bus_pool: Vec::new(),
+            mod_bus_pool: Vec::new(),
         }
     }
 
@@ -51,6 +54,26 @@ impl VoiceAllocator {
         self.control_bus_pool.push((freq, gate, vel));
     }
 
+    /// Allocate a single per-voice control bus for mod matrix signals.
+    pub fn alloc_mod_bus(&mut self) -> i32 {
+        self.mod_bus_pool.pop().unwrap_or_else(|| {
+            let bus = self.next_control_bus;
+            self.next_control_bus += 1;
+            bus
+        })
+    }
+
+    /// Return per-voice mod matrix buses to the pool for reuse.
+    pub fn return_mod_buses(&mut self, buses: &[i32]) {
+        self.mod_bus_pool.extend_from_slice(buses);
+    }
+
+    /// Return all of a removed voice's buses to their pools.
+    fn return_voice_buses(&mut self, voice: &VoiceChain) {
+        self.control_bus_pool.push(voice.control_buses);
+        self.mod_bus_pool.extend_from_slice(&voice.mod_buses);
+    }
+
     /// Add a voice to the active chain list.
     pub fn add(&mut self, voice: VoiceChain) {
         self.chains.push(voice);
@@ -157,7 +180,7 @@ impl VoiceAllocator {
     pub fn drain_all(&mut self) -> Vec<VoiceChain> {
         let drained: Vec<VoiceChain> = self.chains.drain(..).collect();
         for voice in &drained {
-            self.control_bus_pool.push(voice.control_buses);
+            self.return_voice_buses(voice);
         }
         drained
     }
@@ -170,7 +193,7 @@ impl VoiceAllocator {
         while i < self.chains.len() {
             if self.chains[i].instrument_id == instrument_id {
                 let voice = self.chains.remove(i);
-                self.control_bus_pool.push(voice.control_buses);
+                self.return_voice_buses(&voice);
                 drained.push(voice);
             } else {
                 i += 1;
@@ -195,7 +218,7 @@ impl VoiceAllocator {
             };
             if is_expired {
                 let voice = self.chains.remove(i);
-                self.control_bus_pool.push(voice.control_buses);
+                self.return_voice_buses(&voice);
                 expired.push(voice);
             } else {
                 i += 1;
@@ -209,7 +232,7 @@ impl VoiceAllocator {
     pub fn remove_by_group_id(&mut self, group_id: i32) -> Option<VoiceChain> {
         if let Some(pos) = self.chains.iter().position(|v| v.group_id == group_id) {
             let voice = self.chains.remove(pos);
-            self.control_bus_pool.push(voice.control_buses);
+            self.return_voice_buses(&voice);
             Some(voice)
         } else {
             None
@@ -236,6 +259,11 @@ impl VoiceAllocator {
         self.control_bus_pool.len()
     }
 
+    /// Number of per-voice mod buses in the reuse pool.
+    pub fn mod_bus_pool_size(&self) -> usize {
+        self.mod_bus_pool.len()
+    }
+
     /// Sync bus watermarks from the bus allocator after a routing rebuild.
     pub fn sync_bus_watermarks(&mut self, audio_bus: i32, control_bus: i32) {
         self.next_audio_bus = audio_bus;
@@ -272,6 +300,7 @@ mod tests {
             release_secs: 0.3,
             release_state: None,
             control_buses: buses,
+            mod_buses: Vec::new(),
         }
     }
 
@@ -292,6 +321,7 @@ mod tests {
             release_secs: 0.5,
             release_state: Some((Instant::now() - Duration::from_secs(5), 0.5)),
             control_buses: buses,
+            mod_buses: Vec::new(),
         }
     }
 
@@ -1,11 +1,13 @@
 use std::time::Instant;
 
-use super::backend::{AudioBackend, BackendMessage, RawArg};
+use super::backend::{build_n_set_message, AudioBackend, BackendMessage, RawArg};
+use super::modulation::VoiceKind;
 use super::{AudioEngine, VoiceChain, GROUP_SOURCES};
+use imbolc_types::tuning;
 use imbolc_types::{
-    BufferId, InstrumentId, InstrumentState, ParamValue, ParameterTarget, SessionState,
+    BufferId, ExpressionDimension, Instrument, InstrumentId, InstrumentState, ParamLock,
+    ParamValue, ParameterTarget, SamplerConfig, SessionState,
 };
-use imbolc_types::tuning;
 
 /// Anti-click fade time for voice stealing/freeing.
 /// Must exceed the midi control node's gate release (10ms) plus margin
@@ -17,6 +19,39 @@ const ANTI_CLICK_FADE_SECS: f64 = 0.030;
 /// industry standard used by Ableton Live, Logic Pro, etc.
 const MIN_ONSET_SECS: f32 = 0.005;
 
+/// Smallest tempo change that retunes warped sampler voices
+const TEMPO_EPSILON: f32 = 0.001;
+
+/// Float source param of an instrument, or `default` when it has none
+fn float_param(instrument: &Instrument, name: &str, default: f32) -> f32 {
+    instrument
+        .source_params
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| match p.value {
+            ParamValue::Float(v) => v,
+            ParamValue::Int(v) => v as f32,
+            _ => default,
+        })
+        .unwrap_or(default)
+}
+
+/// Tempo-dependent playback control of a sampler voice and its value at
+/// `bpm`. Warping divides the time-stretch amount (above 1 plays slower) or
+/// multiplies the pitched sampler's rate.
+fn warped_playback(
+    instrument: &Instrument,
+    config: &SamplerConfig,
+    bpm: f32,
+) -> (&'static str, f32) {
+    let ratio = config.warp_ratio(bpm);
+    if instrument.source.is_time_stretch() {
+        ("stretch", float_param(instrument, "stretch", 1.0) / ratio)
+    } else {
+        ("rate", float_param(instrument, "rate", 1.0) * ratio)
+    }
+}
+
 impl AudioEngine {
     /// Spawn a voice for an instrument
     pub fn spawn_voice(
@@ -32,6 +67,11 @@ impl AudioEngine {
             .instrument(instrument_id)
             .ok_or_else(|| format!("No instrument with id {}", instrument_id))?;
 
+        // Frozen instruments play their render instead of voices
+        if instrument.is_frozen() {
+            return Ok(());
+        }
+
         // AudioIn, BusIn, and VSTi instruments don't use voice spawning - they have persistent synths
         if instrument.source.is_audio_input() || instrument.source.is_bus_in() {
             return Ok(());
@@ -42,6 +82,11 @@ impl AudioEngine {
             return self.send_vsti_note_on(instrument_id, pitch, velocity);
         }
 
+        // External MIDI instruments: schedule note-on on the output port
+        if let Some((port, channel)) = instrument.source.midi_out() {
+            return self.midi_out_note_on(port, channel, pitch, velocity, offset_secs);
+        }
+
         // Sampler and TimeStretch instruments need special handling
         if instrument.source.is_sample() || instrument.source.is_time_stretch() {
             return self.spawn_sampler_voice(
@@ -123,6 +168,16 @@ impl AudioEngine {
             });
         }
 
+        // Mod matrix envelopes and routes run ahead of the source synth
+        let mut modulation = self.voice_modulation(
+            instrument,
+            VoiceKind::Oscillator,
+            group_id,
+            voice_gate_bus,
+            voice_vel_bus,
+        );
+        messages.append(&mut modulation.messages);
+
         // 3. Source synth
         let source_node_id = self.next_node_id;
         self.next_node_id += 1;
@@ -173,99 +228,8 @@ impl AudioEngine {
             args.push(RawArg::Str("out".to_string()));
             args.push(RawArg::Float(source_out_bus as f32));
 
-            // Wire LFO mod inputs based on target
-            if instrument.modulation.lfo.enabled {
-                if let Some(lfo_bus) = self.bus_allocator.get_control_bus(instrument_id, "lfo_out")
-                {
-                    match instrument.modulation.lfo.target {
-                        ParameterTarget::Level => {
-                            args.push(RawArg::Str("amp_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Pitch => {
-                            args.push(RawArg::Str("pitch_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Detune => {
-                            args.push(RawArg::Str("detune_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::PulseWidth => {
-                            args.push(RawArg::Str("width_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Attack => {
-                            args.push(RawArg::Str("attack_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Release => {
-                            args.push(RawArg::Str("release_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::FmIndex => {
-                            args.push(RawArg::Str("index_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::WavetablePosition => {
-                            args.push(RawArg::Str("position_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::FormantFreq => {
-                            args.push(RawArg::Str("formant_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::SyncRatio => {
-                            args.push(RawArg::Str("sync_ratio_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Pressure => {
-                            args.push(RawArg::Str("pressure_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Embouchure => {
-                            args.push(RawArg::Str("embouchure_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::GrainSize => {
-                            args.push(RawArg::Str("grain_size_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::GrainDensity => {
-                            args.push(RawArg::Str("density_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::FbFeedback => {
-                            args.push(RawArg::Str("feedback_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::RingModDepth => {
-                            args.push(RawArg::Str("mod_depth_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::ChaosParam => {
-                            args.push(RawArg::Str("chaos_param_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::AdditiveRolloff => {
-                            args.push(RawArg::Str("rolloff_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::MembraneTension => {
-                            args.push(RawArg::Str("tension_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Decay => {
-                            args.push(RawArg::Str("decay_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Sustain => {
-                            args.push(RawArg::Str("sustain_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        _ => {} // Routing-level targets handled in routing.rs
-                    }
-                }
-            }
+            // Mod matrix and LFO inputs
+            modulation.push_inputs(&mut args);
 
             messages.push(BackendMessage {
                 addr: "/s_new".to_string(),
@@ -296,6 +260,7 @@ impl AudioEngine {
                 .max(MIN_ONSET_SECS),
             release_state: None,
             control_buses: (voice_freq_bus, voice_gate_bus, voice_vel_bus),
+            mod_buses: modulation.buses,
         });
 
         Ok(())
@@ -404,10 +369,25 @@ impl AudioEngine {
             });
         }
 
+        let is_time_stretch = instrument.source.is_time_stretch();
+        let voice_kind = if is_time_stretch {
+            VoiceKind::TimeStretch
+        } else {
+            VoiceKind::Sampler
+        };
+        // Mod matrix envelopes and routes run ahead of the sampler synth
+        let mut modulation = self.voice_modulation(
+            instrument,
+            voice_kind,
+            group_id,
+            voice_gate_bus,
+            voice_vel_bus,
+        );
+        messages.append(&mut modulation.messages);
+
         // 3. Sampler/TimeStretch synth
         let sampler_node_id = self.next_node_id;
         self.next_node_id += 1;
-        let is_time_stretch = instrument.source.is_time_stretch();
         {
             let synthdef_name = if is_time_stretch {
                 "imbolc_timestretch"
@@ -422,19 +402,10 @@ impl AudioEngine {
                 RawArg::Int(group_id),
             ];
 
-            // Helper to get float param
-            let get_param = |name: &str, default: f32| -> f32 {
-                instrument
-                    .source_params
-                    .iter()
-                    .find(|p| p.name == name)
-                    .map(|p| match p.value {
-                        ParamValue::Float(v) => v,
-                        ParamValue::Int(v) => v as f32,
-                        _ => default,
-                    })
-                    .unwrap_or(default)
-            };
+            let get_param = |name: &str, default: f32| float_param(instrument, name, default);
+            // Stretch or rate, warped to the current tempo
+            let (playback_control, playback_value) =
+                warped_playback(instrument, sampler_config, self.tempo_bpm);
 
             let amp = get_param("amp", 0.8);
             let loop_mode = sampler_config.loop_mode;
@@ -451,13 +422,12 @@ impl AudioEngine {
 
             if is_time_stretch {
                 // TimeStretch-specific params
-                let stretch = get_param("stretch", 1.0);
                 let pitch = get_param("pitch", 0.0);
                 let grain_size = get_param("grain_size", 0.1);
                 let overlap = get_param("overlap", 4.0);
 
-                args.push(RawArg::Str("stretch".to_string()));
-                args.push(RawArg::Float(stretch));
+                args.push(RawArg::Str(playback_control.to_string()));
+                args.push(RawArg::Float(playback_value));
                 args.push(RawArg::Str("pitch".to_string()));
                 args.push(RawArg::Float(pitch));
                 args.push(RawArg::Str("grain_size".to_string()));
@@ -466,9 +436,8 @@ impl AudioEngine {
                 args.push(RawArg::Float(overlap));
             } else {
                 // PitchedSampler-specific params
-                let rate = get_param("rate", 1.0);
-                args.push(RawArg::Str("rate".to_string()));
-                args.push(RawArg::Float(rate));
+                args.push(RawArg::Str(playback_control.to_string()));
+                args.push(RawArg::Float(playback_value));
                 args.push(RawArg::Str("loop".to_string()));
                 args.push(RawArg::Float(if loop_mode { 1.0 } else { 0.0 }));
             }
@@ -509,51 +478,8 @@ impl AudioEngine {
             args.push(RawArg::Str("out".to_string()));
             args.push(RawArg::Float(source_out_bus as f32));
 
-            // Wire LFO mod inputs for sampler/timestretch voice
-            if instrument.modulation.lfo.enabled {
-                if let Some(lfo_bus) = self.bus_allocator.get_control_bus(instrument_id, "lfo_out")
-                {
-                    match instrument.modulation.lfo.target {
-                        ParameterTarget::Level => {
-                            args.push(RawArg::Str("amp_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::SampleRate if !is_time_stretch => {
-                            args.push(RawArg::Str("srate_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Attack => {
-                            args.push(RawArg::Str("attack_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Release => {
-                            args.push(RawArg::Str("release_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Decay => {
-                            args.push(RawArg::Str("decay_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Sustain => {
-                            args.push(RawArg::Str("sustain_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::StretchRatio if is_time_stretch => {
-                            args.push(RawArg::Str("stretch_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::PitchShift if is_time_stretch => {
-                            args.push(RawArg::Str("pitch_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::GrainSize if is_time_stretch => {
-                            args.push(RawArg::Str("grain_size_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        _ => {} // Routing-level targets handled in routing.rs
-                    }
-                }
-            }
+            // Mod matrix and LFO inputs
+            modulation.push_inputs(&mut args);
 
             messages.push(BackendMessage {
                 addr: "/s_new".to_string(),
@@ -584,11 +510,39 @@ impl AudioEngine {
                 .max(MIN_ONSET_SECS),
             release_state: None,
             control_buses: (voice_freq_bus, voice_gate_bus, voice_vel_bus),
+            mod_buses: modulation.buses,
         });
 
         Ok(())
     }
 
+    /// Follow the project tempo: sampler voices spawned from now on warp to
+    /// `bpm`, and sounding voices that warp are retuned to it.
+    pub fn set_tempo(&mut self, bpm: f32, state: &InstrumentState) {
+        if (bpm - self.tempo_bpm).abs() < TEMPO_EPSILON {
+            return;
+        }
+        self.tempo_bpm = bpm;
+        if self.backend.is_none() {
+            return;
+        }
+        let messages: Vec<BackendMessage> = self
+            .voice_allocator
+            .chains()
+            .iter()
+            .filter_map(|voice| {
+                let instrument = state.instrument(voice.instrument_id)?;
+                let config = instrument.sampler_config().filter(|c| c.warp)?;
+                let (control, value) = warped_playback(instrument, config, bpm);
+                Some(build_n_set_message(voice.source_node, control, value))
+            })
+            .collect();
+        if !messages.is_empty() {
+            // Lands with the notes being scheduled at this tempo
+            let _ = self.queue_timed_bundle(messages, self.schedule_lookahead_secs);
+        }
+    }
+
     /// Release a specific voice by instrument and pitch (note-off).
     /// Marks the voice as released instead of removing it, so it remains
     /// available as a steal candidate while its envelope fades out.
@@ -601,9 +555,15 @@ impl AudioEngine {
     ) -> Result<(), String> {
         // VSTi instruments: send MIDI note-off via /u_cmd
         if let Some(instrument) = state.instrument(instrument_id) {
+            if instrument.is_frozen() {
+                return Ok(());
+            }
             if instrument.source.is_vst() {
                 return self.send_vsti_note_off(instrument_id, pitch);
             }
+            if let Some((port, channel)) = instrument.source.midi_out() {
+                return self.midi_out_note_off(port, channel, pitch, offset_secs);
+            }
         }
 
         if self.backend.is_none() {
@@ -656,6 +616,7 @@ impl AudioEngine {
 
     /// Release all active voices with anti-click fade (force gate bus to 0, then delayed free)
     pub fn release_all_voices(&mut self) {
+        self.midi_out_all_notes_off();
         if let Some(ref backend) = self.backend {
             for chain in self.voice_allocator.drain_all() {
                 self.node_registry.unregister(chain.group_id);
@@ -666,6 +627,56 @@ impl AudioEngine {
         }
     }
 
+    /// Apply a per-note expression value to the held voice at `pitch`.
+    /// Bend retunes the voice's MIDI control node, pressure goes to the
+    /// source synth and slide sweeps the instrument filter around its cutoff.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_voice_expression(
+        &mut self,
+        instrument_id: InstrumentId,
+        pitch: u8,
+        dimension: ExpressionDimension,
+        value: f32,
+        offset_secs: f64,
+        state: &InstrumentState,
+        session: &SessionState,
+    ) -> Result<(), String> {
+        if self.backend.is_none() {
+            return Err("Not connected".to_string());
+        }
+        let Some(instrument) = state.instrument(instrument_id) else {
+            return Ok(());
+        };
+        let Some(voice) = self.voice_allocator.chains().iter().find(|v| {
+            v.instrument_id == instrument_id && v.pitch == pitch && v.release_state.is_none()
+        }) else {
+            return Ok(());
+        };
+
+        let message = match dimension.target() {
+            ParameterTarget::Pitch => {
+                let ctx = tuning::TuningContext::new(session.key, session.ji_flavor);
+                let freq =
+                    tuning::pitch_to_freq(pitch, session.tuning_a4 as f64, session.tuning, &ctx);
+                let bent = freq * 2.0_f64.powf(value as f64 / 12.0);
+                build_n_set_message(voice.midi_node_id, "freq", bent as f32)
+            }
+            ParameterTarget::FilterCutoff => {
+                let (Some(filter_node), Some(filter)) = (
+                    self.node_map.get(&instrument_id).and_then(|n| n.filter),
+                    instrument.filter(),
+                ) else {
+                    return Ok(());
+                };
+                // Slide spans three octaves either side of the set cutoff
+                let cutoff = filter.cutoff.value * 2.0_f32.powf((value - 0.5) * 6.0);
+                build_n_set_message(filter_node, "cutoff", cutoff.clamp(20.0, 20000.0))
+            }
+            _ => build_n_set_message(voice.source_node, dimension.name(), value),
+        };
+        self.queue_timed_bundle(vec![message], offset_secs)
+    }
+
     /// Remove voices whose release envelope has fully expired.
     /// Called periodically from the audio thread to prevent unbounded growth.
     pub fn cleanup_expired_voices(&mut self) {
@@ -699,6 +710,10 @@ impl AudioEngine {
             // Buses for stolen voices must be returned only after /n_end confirms free.
             self.oneshot_buses
                 .insert(voice.group_id, voice.control_buses);
+            if !voice.mod_buses.is_empty() {
+                self.oneshot_mod_buses
+                    .insert(voice.group_id, voice.mod_buses.clone());
+            }
             Self::anti_click_free_at(backend.as_ref(), voice, offset_secs)?;
         }
 
@@ -854,12 +869,14 @@ impl AudioEngine {
     /// Trigger an instrument as a one-shot (spawn voice + immediate release).
     /// The voice goes through Attack → Release, skipping sustained hold.
     /// Used by drum sequencer pads that trigger synth instruments.
+    #[allow(clippy::too_many_arguments)]
     pub fn trigger_instrument_oneshot(
         &mut self,
         target_instrument_id: InstrumentId,
         freq: f32,
         velocity: f32,
         offset_secs: f64,
+        locks: &[ParamLock],
         state: &InstrumentState,
         session: &SessionState,
     ) -> Result<(), String> {
@@ -867,10 +884,23 @@ impl AudioEngine {
             .instrument(target_instrument_id)
             .ok_or_else(|| format!("No instrument with id {}", target_instrument_id))?;
 
-        // Skip unsupported instrument types
+        // Step locks on the envelope shape this hit only
+        let mut envelope = instrument.modulation.amp_envelope.clone();
+        for lock in locks {
+            match lock.target {
+                ParameterTarget::Attack => envelope.attack = lock.value,
+                ParameterTarget::Decay => envelope.decay = lock.value,
+                ParameterTarget::Release => envelope.release = lock.value,
+                _ => {}
+            }
+        }
+
+        // Skip unsupported instrument types and frozen instruments
         if instrument.source.is_audio_input()
             || instrument.source.is_bus_in()
             || instrument.source.is_vst()
+            || instrument.source.is_midi_out()
+            || instrument.is_frozen()
         {
             return Ok(());
         }
@@ -949,6 +979,16 @@ impl AudioEngine {
             });
         }
 
+        // Mod matrix envelopes and routes run ahead of the source synth
+        let mut modulation = self.voice_modulation(
+            instrument,
+            VoiceKind::Oscillator,
+            group_id,
+            voice_gate_bus,
+            voice_vel_bus,
+        );
+        messages.append(&mut modulation.messages);
+
         // 3. Source synth
         let source_node_id = self.next_node_id;
         self.next_node_id += 1;
@@ -976,48 +1016,19 @@ impl AudioEngine {
             args.push(RawArg::Float(voice_gate_bus as f32));
             // Amp envelope (ADSR) — enforce minimum onset/offset time
             args.push(RawArg::Str("attack".to_string()));
-            args.push(RawArg::Float(
-                instrument
-                    .modulation
-                    .amp_envelope
-                    .attack
-                    .max(MIN_ONSET_SECS),
-            ));
+            args.push(RawArg::Float(envelope.attack.max(MIN_ONSET_SECS)));
             args.push(RawArg::Str("decay".to_string()));
-            args.push(RawArg::Float(instrument.modulation.amp_envelope.decay));
+            args.push(RawArg::Float(envelope.decay));
             args.push(RawArg::Str("sustain".to_string()));
-            args.push(RawArg::Float(instrument.modulation.amp_envelope.sustain));
+            args.push(RawArg::Float(envelope.sustain));
             args.push(RawArg::Str("release".to_string()));
-            args.push(RawArg::Float(
-                instrument
-                    .modulation
-                    .amp_envelope
-                    .release
-                    .max(MIN_ONSET_SECS),
-            ));
+            args.push(RawArg::Float(envelope.release.max(MIN_ONSET_SECS)));
             // Output to source_out_bus
             args.push(RawArg::Str("out".to_string()));
             args.push(RawArg::Float(source_out_bus as f32));
 
-            // Wire LFO mod inputs if enabled
-            if instrument.modulation.lfo.enabled {
-                if let Some(lfo_bus) = self
-                    .bus_allocator
-                    .get_control_bus(target_instrument_id, "lfo_out")
-                {
-                    match instrument.modulation.lfo.target {
-                        ParameterTarget::Level => {
-                            args.push(RawArg::Str("amp_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        ParameterTarget::Pitch => {
-                            args.push(RawArg::Str("pitch_mod_in".to_string()));
-                            args.push(RawArg::Float(lfo_bus as f32));
-                        }
-                        _ => {} // Other targets handled in routing
-                    }
-                }
-            }
+            // Mod matrix and LFO inputs
+            modulation.push_inputs(&mut args);
 
             messages.push(BackendMessage {
                 addr: "/s_new".to_string(),
@@ -1045,7 +1056,7 @@ impl AudioEngine {
         )?;
 
         // 5. Schedule cleanup after envelope completes
-        let release_time = instrument.modulation.amp_envelope.release;
+        let release_time = envelope.release;
         let cleanup_offset = release_offset + release_time as f64 + 0.5;
         self.queue_timed_bundle(
             vec![BackendMessage {
@@ -1058,6 +1069,9 @@ impl AudioEngine {
         // Track control buses for return when /n_end arrives
         self.oneshot_buses
             .insert(group_id, (voice_freq_bus, voice_gate_bus, voice_vel_bus));
+        if !modulation.buses.is_empty() {
+            self.oneshot_mod_buses.insert(group_id, modulation.buses);
+        }
 
         // Register nodes for the node registry
         self.node_registry.register(group_id);
@@ -1081,6 +1095,9 @@ impl AudioEngine {
                 // One-shot voice freed — return buses manually
                 self.voice_allocator
                     .return_control_buses(buses.0, buses.1, buses.2);
+                if let Some(mod_buses) = self.oneshot_mod_buses.remove(&node_id) {
+                    self.voice_allocator.return_mod_buses(&mod_buses);
+                }
                 self.node_registry.unregister(node_id);
             } else {
                 // Unknown node (routing synth, meter, etc.) — just unregister
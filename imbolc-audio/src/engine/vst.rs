@@ -1,7 +1,9 @@
 use super::backend::RawArg;
 use super::AudioEngine;
 use super::VST_UGEN_INDEX;
-use imbolc_types::InstrumentId;
+use imbolc_types::{
+    EffectType, InstrumentId, InstrumentState, SessionState, SourceType, VstPluginId,
+};
 
 impl AudioEngine {
     /// Send MIDI note-on to a VSTi persistent source node
@@ -54,6 +56,56 @@ impl AudioEngine {
         Ok(())
     }
 
+    /// Find which VST plugin a live node hosts: a VSTi source, or a VST effect in an
+    /// instrument, bus, or layer group chain.
+    pub(crate) fn vst_plugin_for_node(
+        &self,
+        node_id: i32,
+        state: &InstrumentState,
+        session: &SessionState,
+    ) -> Option<VstPluginId> {
+        let vst_effect = |effect_type: EffectType| match effect_type {
+            EffectType::Vst(id) => Some(id),
+            _ => None,
+        };
+        for (instrument_id, nodes) in &self.node_map {
+            let Some(instrument) = state.instrument(*instrument_id) else {
+                continue;
+            };
+            if nodes.source == Some(node_id) {
+                if let SourceType::Vst(id) = instrument.source {
+                    return Some(id);
+                }
+            }
+            if let Some((effect_id, _)) = nodes.effects.iter().find(|(_, &n)| n == node_id) {
+                return instrument
+                    .effect_by_id(*effect_id)
+                    .and_then(|e| vst_effect(e.effect_type));
+            }
+        }
+        if let Some(((bus_id, effect_id), _)) =
+            self.bus_effect_node_map.iter().find(|(_, &n)| n == node_id)
+        {
+            return session
+                .mixer
+                .bus(*bus_id)
+                .and_then(|b| b.effect_chain.effect_by_id(*effect_id))
+                .and_then(|e| vst_effect(e.effect_type));
+        }
+        if let Some(((group_id, effect_id), _)) = self
+            .layer_group_effect_node_map
+            .iter()
+            .find(|(_, &n)| n == node_id)
+        {
+            return session
+                .mixer
+                .layer_group_mixer(*group_id)
+                .and_then(|g| g.effect_chain.effect_by_id(*effect_id))
+                .and_then(|e| vst_effect(e.effect_type));
+        }
+        None
+    }
+
     /// Query VST parameter count from a VST node
     #[allow(dead_code)]
     pub(crate) fn query_vst_param_count_node(&self, node_id: i32) -> Result<(), String> {
@@ -0,0 +1,677 @@
+//! Minimal FLAC encoder: fixed-blocksize frames, fixed linear predictors
+//! (orders 0-4), partitioned Rice residuals and stereo decorrelation.
+//! The STREAMINFO MD5 is left as zero, which the format defines as unknown.
+
+/// Samples per channel in every frame except the last
+const BLOCK_SIZE: usize = 4096;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+/// Largest Rice parameter for the 4-bit (RICE) and 5-bit (RICE2) methods;
+/// the all-ones value is reserved as an escape code
+const MAX_RICE_PARAM: u32 = 14;
+const MAX_RICE2_PARAM: u32 = 30;
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    /// Append the low `count` bits of `value`, most significant first
+    fn write(&mut self, value: u64, count: u32) {
+        debug_assert!(count <= 32);
+        if count == 0 {
+            return;
+        }
+        self.acc = (self.acc << count) | (value & ((1u64 << count) - 1));
+        self.bits += count;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+        self.acc &= (1u64 << self.bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i64, count: u32) {
+        self.write(value as u64, count);
+    }
+
+    fn write_unary(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(0, 32);
+            zeros -= 32;
+        }
+        self.write(1, zeros as u32 + 1);
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn sample_rate_code(sample_rate: u32) -> u64 {
+    match sample_rate {
+        88200 => 0b0001,
+        176400 => 0b0010,
+        192000 => 0b0011,
+        8000 => 0b0100,
+        16000 => 0b0101,
+        22050 => 0b0110,
+        24000 => 0b0111,
+        32000 => 0b1000,
+        44100 => 0b1001,
+        48000 => 0b1010,
+        96000 => 0b1011,
+        // Taken from STREAMINFO
+        _ => 0b0000,
+    }
+}
+
+fn sample_size_code(bits_per_sample: u32) -> Option<u64> {
+    match bits_per_sample {
+        8 => Some(0b001),
+        12 => Some(0b010),
+        16 => Some(0b100),
+        20 => Some(0b101),
+        24 => Some(0b110),
+        _ => None,
+    }
+}
+
+/// Frame number in FLAC's extended UTF-8 coding
+fn write_utf8(w: &mut BitWriter, value: u32) {
+    if value < 0x80 {
+        w.write(value as u64, 8);
+        return;
+    }
+    let extra = match value {
+        v if v < 0x800 => 1,
+        v if v < 0x1_0000 => 2,
+        v if v < 0x20_0000 => 3,
+        v if v < 0x400_0000 => 4,
+        _ => 5,
+    };
+    let lead_marker = (0xFF00u32 >> (extra + 1)) & 0xFF;
+    w.write((lead_marker | (value >> (6 * extra))) as u64 & 0xFF, 8);
+    for i in (0..extra).rev() {
+        w.write((0x80 | ((value >> (6 * i)) & 0x3F)) as u64, 8);
+    }
+}
+
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let s = samples;
+            match order {
+                0 => s[i],
+                1 => s[i] - s[i - 1],
+                2 => s[i] - 2 * s[i - 1] + s[i - 2],
+                3 => s[i] - 3 * s[i - 1] + 3 * s[i - 2] - s[i - 3],
+                _ => s[i] - 4 * s[i - 1] + 6 * s[i - 2] - 4 * s[i - 3] + s[i - 4],
+            }
+        })
+        .collect()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Best Rice parameter and its estimated cost in bits for a partition
+fn rice_param(count: usize, sum: u64) -> (u32, u64) {
+    let count = count as u64;
+    if count == 0 {
+        return (0, 0);
+    }
+    let mean = sum / count;
+    let guess = if mean == 0 {
+        0
+    } else {
+        63 - mean.leading_zeros()
+    };
+    [guess.saturating_sub(1), guess, guess + 1]
+        .into_iter()
+        .map(|k| k.min(MAX_RICE2_PARAM))
+        .map(|k| (k, count * (k as u64 + 1) + (sum >> k)))
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, 0))
+}
+
+struct ResidualPlan {
+    partition_order: u32,
+    params: Vec<u32>,
+    bits: u64,
+}
+
+impl ResidualPlan {
+    fn uses_rice2(&self) -> bool {
+        self.params.iter().any(|k| *k > MAX_RICE_PARAM)
+    }
+}
+
+fn plan_residual(residual: &[i64], block_size: usize, order: usize) -> ResidualPlan {
+    let coded: Vec<u64> = residual.iter().map(|r| zigzag(*r)).collect();
+    let mut best: Option<ResidualPlan> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        if !block_size.is_multiple_of(partitions) || block_size / partitions <= order {
+            break;
+        }
+        let partition_len = block_size / partitions;
+        let mut params = Vec::with_capacity(partitions);
+        let mut bits = 0;
+        let mut start = 0;
+        for p in 0..partitions {
+            let len = if p == 0 {
+                partition_len - order
+            } else {
+                partition_len
+            };
+            let sum = coded[start..start + len].iter().sum();
+            let (k, cost) = rice_param(len, sum);
+            params.push(k);
+            bits += cost;
+            start += len;
+        }
+        let param_bits = if params.iter().any(|k| *k > MAX_RICE_PARAM) {
+            5
+        } else {
+            4
+        };
+        bits += 6 + param_bits * partitions as u64;
+        if best.as_ref().is_none_or(|b| bits < b.bits) {
+            best = Some(ResidualPlan {
+                partition_order,
+                params,
+                bits,
+            });
+        }
+    }
+    best.unwrap_or(ResidualPlan {
+        partition_order: 0,
+        params: vec![0],
+        bits: u64::MAX,
+    })
+}
+
+enum SubframePlan {
+    Constant,
+    Verbatim,
+    Fixed {
+        order: usize,
+        residual: Vec<i64>,
+        plan: ResidualPlan,
+    },
+}
+
+fn plan_subframe(samples: &[i64], bps: u32) -> (SubframePlan, u64) {
+    if samples.iter().all(|s| *s == samples[0]) {
+        return (SubframePlan::Constant, 8 + bps as u64);
+    }
+    let mut best = (
+        SubframePlan::Verbatim,
+        8 + bps as u64 * samples.len() as u64,
+    );
+    for order in 0..=MAX_FIXED_ORDER.min(samples.len() - 1) {
+        let residual = fixed_residual(samples, order);
+        let plan = plan_residual(&residual, samples.len(), order);
+        let bits = (8 + order as u64 * bps as u64).saturating_add(plan.bits);
+        if bits < best.1 {
+            best = (
+                SubframePlan::Fixed {
+                    order,
+                    residual,
+                    plan,
+                },
+                bits,
+            );
+        }
+    }
+    best
+}
+
+fn write_subframe(w: &mut BitWriter, samples: &[i64], bps: u32, plan: &SubframePlan) {
+    // Zero padding bit, 6-bit type, no wasted bits
+    match plan {
+        SubframePlan::Constant => {
+            w.write(0b0000_0000, 8);
+            w.write_signed(samples[0], bps);
+        }
+        SubframePlan::Verbatim => {
+            w.write(0b0000_0010, 8);
+            for s in samples {
+                w.write_signed(*s, bps);
+            }
+        }
+        SubframePlan::Fixed {
+            order,
+            residual,
+            plan,
+        } => {
+            w.write(((0b001000 | *order as u64) << 1) & 0x7E, 8);
+            for s in &samples[..*order] {
+                w.write_signed(*s, bps);
+            }
+            let rice2 = plan.uses_rice2();
+            let param_bits = if rice2 { 5 } else { 4 };
+            w.write(if rice2 { 0b01 } else { 0b00 }, 2);
+            w.write(plan.partition_order as u64, 4);
+            let partition_len = samples.len() >> plan.partition_order;
+            let mut start = 0;
+            for (p, k) in plan.params.iter().enumerate() {
+                let len = if p == 0 {
+                    partition_len - order
+                } else {
+                    partition_len
+                };
+                w.write(*k as u64, param_bits);
+                for r in &residual[start..start + len] {
+                    let u = zigzag(*r);
+                    w.write_unary(u >> k);
+                    w.write(u, *k);
+                }
+                start += len;
+            }
+        }
+    }
+}
+
+/// Pick the cheapest stereo coding: independent, left/side, right/side or
+/// mid/side. Returns the channel assignment code and the two subframes
+/// with their sample sizes.
+fn plan_stereo(left: &[i64], right: &[i64], bps: u32) -> (u64, Vec<(Vec<i64>, u32)>) {
+    let side: Vec<i64> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+    let mid: Vec<i64> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+    let cost = |samples: &[i64], bps: u32| plan_subframe(samples, bps).1;
+    let left_bits = cost(left, bps);
+    let right_bits = cost(right, bps);
+    let side_bits = cost(&side, bps + 1);
+    let mid_bits = cost(&mid, bps);
+    let options = [
+        (0b0001, left_bits + right_bits),
+        (0b1000, left_bits + side_bits),
+        (0b1001, side_bits + right_bits),
+        (0b1010, mid_bits + side_bits),
+    ];
+    let code = options
+        .iter()
+        .min_by_key(|(_, bits)| *bits)
+        .map_or(0b0001, |(code, _)| *code);
+    let channels = match code {
+        0b1000 => vec![(left.to_vec(), bps), (side, bps + 1)],
+        0b1001 => vec![(side, bps + 1), (right.to_vec(), bps)],
+        0b1010 => vec![(mid, bps), (side, bps + 1)],
+        _ => vec![(left.to_vec(), bps), (right.to_vec(), bps)],
+    };
+    (code, channels)
+}
+
+fn encode_frame(
+    w: &mut BitWriter,
+    block: &[Vec<i64>],
+    frame_number: u32,
+    sample_rate: u32,
+    bits_per_sample: u32,
+) {
+    let start = w.bytes.len();
+    let len = block[0].len();
+    let (assignment, channels) = if block.len() == 2 {
+        plan_stereo(&block[0], &block[1], bits_per_sample)
+    } else {
+        (
+            block.len() as u64 - 1,
+            block.iter().map(|c| (c.clone(), bits_per_sample)).collect(),
+        )
+    };
+
+    // Sync code, reserved bit, fixed blocking strategy
+    w.write(0xFFF8, 16);
+    // Block size stored as 16 bits after the frame number
+    w.write(0b0111, 4);
+    w.write(sample_rate_code(sample_rate), 4);
+    w.write(assignment, 4);
+    w.write(sample_size_code(bits_per_sample).unwrap_or(0), 3);
+    w.write(0, 1);
+    write_utf8(w, frame_number);
+    w.write(len as u64 - 1, 16);
+    let crc = crc8(&w.bytes[start..]);
+    w.write(crc as u64, 8);
+
+    for (samples, bps) in &channels {
+        let (plan, _) = plan_subframe(samples, *bps);
+        write_subframe(w, samples, *bps, &plan);
+    }
+    w.align();
+    let crc = crc16(&w.bytes[start..]);
+    w.write(crc as u64, 16);
+}
+
+/// Encode planar integer samples as a complete FLAC file.
+pub fn encode(
+    channels: &[Vec<i32>],
+    sample_rate: u32,
+    bits_per_sample: u32,
+) -> Result<Vec<u8>, String> {
+    if channels.is_empty() || channels.len() > 8 {
+        return Err(format!(
+            "FLAC supports 1-8 channels, got {}",
+            channels.len()
+        ));
+    }
+    if sample_size_code(bits_per_sample).is_none() {
+        return Err(format!("Unsupported FLAC bit depth: {}", bits_per_sample));
+    }
+    if sample_rate == 0 || sample_rate >= 1 << 20 {
+        return Err(format!("Unsupported FLAC sample rate: {}", sample_rate));
+    }
+    let frames = channels[0].len();
+    if channels.iter().any(|c| c.len() != frames) {
+        return Err("Channels differ in length".to_string());
+    }
+
+    let mut w = BitWriter::new();
+    let mut frame_sizes = Vec::new();
+    for (number, start) in (0..frames).step_by(BLOCK_SIZE).enumerate() {
+        let end = (start + BLOCK_SIZE).min(frames);
+        let block: Vec<Vec<i64>> = channels
+            .iter()
+            .map(|c| c[start..end].iter().map(|s| *s as i64).collect())
+            .collect();
+        let before = w.bytes.len();
+        encode_frame(&mut w, &block, number as u32, sample_rate, bits_per_sample);
+        frame_sizes.push(w.bytes.len() - before);
+    }
+
+    let mut header = BitWriter::new();
+    header.bytes.extend_from_slice(b"fLaC");
+    // Last metadata block, type STREAMINFO, 34 bytes
+    header.write(1, 1);
+    header.write(0, 7);
+    header.write(34, 24);
+    header.write(BLOCK_SIZE as u64, 16);
+    header.write(BLOCK_SIZE as u64, 16);
+    header.write(frame_sizes.iter().min().copied().unwrap_or(0) as u64, 24);
+    header.write(frame_sizes.iter().max().copied().unwrap_or(0) as u64, 24);
+    header.write(sample_rate as u64, 20);
+    header.write(channels.len() as u64 - 1, 3);
+    header.write(bits_per_sample as u64 - 1, 5);
+    header.write((frames as u64 >> 32) & 0xF, 4);
+    header.write(frames as u64 & 0xFFFF_FFFF, 32);
+    header.bytes.extend_from_slice(&[0; 16]);
+
+    let mut out = header.bytes;
+    out.extend_from_slice(&w.bytes);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read(&mut self, count: u32) -> u64 {
+            let mut value = 0;
+            for _ in 0..count {
+                let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+                value = (value << 1) | bit as u64;
+                self.pos += 1;
+            }
+            value
+        }
+
+        fn read_signed(&mut self, count: u32) -> i64 {
+            let value = self.read(count);
+            ((value << (64 - count)) as i64) >> (64 - count)
+        }
+
+        fn read_unary(&mut self) -> u64 {
+            let mut zeros = 0;
+            while self.read(1) == 0 {
+                zeros += 1;
+            }
+            zeros
+        }
+
+        fn read_utf8(&mut self) -> u64 {
+            let lead = self.read(8);
+            let ones = (lead as u8).leading_ones();
+            if ones == 0 {
+                return lead;
+            }
+            let extra = ones - 1;
+            let mut value = lead & ((1 << (6 - extra)) - 1);
+            for _ in 0..extra {
+                value = (value << 6) | (self.read(8) & 0x3F);
+            }
+            value
+        }
+    }
+
+    fn decode_subframe(r: &mut BitReader, len: usize, bps: u32) -> Vec<i64> {
+        assert_eq!(r.read(1), 0);
+        let kind = r.read(6);
+        assert_eq!(r.read(1), 0, "no wasted bits");
+        match kind {
+            0 => vec![r.read_signed(bps); len],
+            1 => (0..len).map(|_| r.read_signed(bps)).collect(),
+            k if k & 0b111000 == 0b001000 => {
+                let order = (k & 0b111) as usize;
+                let mut samples: Vec<i64> = (0..order).map(|_| r.read_signed(bps)).collect();
+                let param_bits = if r.read(2) == 1 { 5 } else { 4 };
+                let partition_order = r.read(4);
+                let partitions = 1usize << partition_order;
+                let mut residual = Vec::new();
+                for p in 0..partitions {
+                    let k = r.read(param_bits) as u32;
+                    let count = (len >> partition_order) - if p == 0 { order } else { 0 };
+                    for _ in 0..count {
+                        let u = (r.read_unary() << k) | r.read(k);
+                        residual.push(((u >> 1) as i64) ^ -((u & 1) as i64));
+                    }
+                }
+                for res in residual {
+                    let i = samples.len();
+                    let s = &samples;
+                    let predicted = match order {
+                        0 => 0,
+                        1 => s[i - 1],
+                        2 => 2 * s[i - 1] - s[i - 2],
+                        3 => 3 * s[i - 1] - 3 * s[i - 2] + s[i - 3],
+                        _ => 4 * s[i - 1] - 6 * s[i - 2] + 4 * s[i - 3] - s[i - 4],
+                    };
+                    samples.push(predicted + res);
+                }
+                samples
+            }
+            other => panic!("unexpected subframe type {:#b}", other),
+        }
+    }
+
+    /// Reference decoder for the subset the encoder produces
+    fn decode(data: &[u8]) -> (u32, u32, Vec<Vec<i32>>) {
+        assert_eq!(&data[..4], b"fLaC");
+        let mut r = BitReader { data, pos: 32 };
+        assert_eq!(r.read(1), 1, "single metadata block");
+        assert_eq!(r.read(7), 0);
+        assert_eq!(r.read(24), 34);
+        r.read(16 + 16 + 24 + 24);
+        let sample_rate = r.read(20) as u32;
+        let channel_count = r.read(3) as usize + 1;
+        let bps = r.read(5) as u32 + 1;
+        let total = r.read(36) as usize;
+        r.pos += 128;
+
+        let mut channels = vec![Vec::new(); channel_count];
+        let mut expected_frame = 0;
+        while r.pos / 8 < data.len() {
+            let start = r.pos / 8;
+            assert_eq!(r.read(16), 0xFFF8);
+            assert_eq!(r.read(4), 0b0111);
+            r.read(4);
+            let assignment = r.read(4);
+            assert_eq!(r.read(3), sample_size_code(bps).unwrap());
+            r.read(1);
+            assert_eq!(r.read_utf8(), expected_frame);
+            expected_frame += 1;
+            let len = r.read(16) as usize + 1;
+            let header_crc = crc8(&data[start..r.pos / 8]);
+            assert_eq!(r.read(8) as u8, header_crc, "header CRC");
+
+            let side_bps = |side: bool| if side { bps + 1 } else { bps };
+            let (a_side, b_side) = match assignment {
+                0b1000 => (false, true),
+                0b1001 => (true, false),
+                0b1010 => (false, true),
+                _ => (false, false),
+            };
+            let mut block: Vec<Vec<i64>> = Vec::new();
+            for ch in 0..channel_count {
+                let bits = match ch {
+                    0 => side_bps(a_side),
+                    1 => side_bps(b_side),
+                    _ => bps,
+                };
+                block.push(decode_subframe(&mut r, len, bits));
+            }
+            if channel_count == 2 {
+                let (a, b) = (&block[0], &block[1]);
+                let (left, right): (Vec<i64>, Vec<i64>) = match assignment {
+                    0b1000 => a.iter().zip(b).map(|(l, s)| (*l, l - s)).unzip(),
+                    0b1001 => a.iter().zip(b).map(|(s, r)| (r + s, *r)).unzip(),
+                    0b1010 => a
+                        .iter()
+                        .zip(b)
+                        .map(|(m, s)| {
+                            let mid = (m << 1) | (s & 1);
+                            ((mid + s) >> 1, (mid - s) >> 1)
+                        })
+                        .unzip(),
+                    _ => (a.clone(), b.clone()),
+                };
+                block = vec![left, right];
+            }
+
+            r.pos = r.pos.div_ceil(8) * 8;
+            let frame_crc = crc16(&data[start..r.pos / 8]);
+            assert_eq!(r.read(16) as u16, frame_crc, "frame CRC");
+            for (out, samples) in channels.iter_mut().zip(block) {
+                out.extend(samples.into_iter().map(|s| s as i32));
+            }
+        }
+        assert_eq!(channels[0].len(), total);
+        (sample_rate, bps, channels)
+    }
+
+    fn test_signal(frames: usize, amplitude: f64, seed: u64) -> Vec<i32> {
+        let mut state = seed;
+        (0..frames)
+            .map(|n| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let noise = (state % 64) as f64 - 32.0;
+                ((n as f64 * 0.031).sin() * amplitude + noise) as i32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stereo_16_bit_round_trips() {
+        let left = test_signal(10_000, 20_000.0, 1);
+        let right: Vec<i32> = left.iter().map(|s| s / 2 + 7).collect();
+        let data = encode(&[left.clone(), right.clone()], 44100, 16).unwrap();
+        let (rate, bps, decoded) = decode(&data);
+        assert_eq!((rate, bps), (44100, 16));
+        assert_eq!(decoded, vec![left, right]);
+        // Correlated, smooth input should compress well below PCM size
+        assert!(data.len() < 10_000 * 2 * 2 * 3 / 4);
+    }
+
+    #[test]
+    fn extreme_24_bit_values_round_trip() {
+        let max = (1 << 23) - 1;
+        let min = -(1 << 23);
+        let left: Vec<i32> = (0..5000)
+            .map(|n| if n % 2 == 0 { max } else { min })
+            .collect();
+        let right = test_signal(5000, 8_000_000.0, 9);
+        let data = encode(&[left.clone(), right.clone()], 96000, 24).unwrap();
+        let (_, bps, decoded) = decode(&data);
+        assert_eq!(bps, 24);
+        assert_eq!(decoded, vec![left, right]);
+    }
+
+    #[test]
+    fn mono_silence_and_odd_rates_round_trip() {
+        let mono = vec![0; 4096 * 2 + 17];
+        let data = encode(std::slice::from_ref(&mono), 37800, 16).unwrap();
+        let (rate, _, decoded) = decode(&data);
+        assert_eq!(rate, 37800);
+        assert_eq!(decoded, vec![mono]);
+    }
+
+    #[test]
+    fn frame_numbers_use_utf8_coding() {
+        for value in [0u32, 0x7F, 0x80, 0x7FF, 0x800, 0xFFFF, 0x1_0000, 0x12_3456] {
+            let mut w = BitWriter::new();
+            write_utf8(&mut w, value);
+            let mut r = BitReader {
+                data: &w.bytes,
+                pos: 0,
+            };
+            assert_eq!(r.read_utf8(), value as u64);
+            assert_eq!(r.pos / 8, w.bytes.len());
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_layouts() {
+        assert!(encode(&[], 48000, 16).is_err());
+        assert!(encode(&[vec![0; 10]], 48000, 32).is_err());
+        assert!(encode(&[vec![0; 10], vec![0; 9]], 48000, 16).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! ITU-R BS.1770 loudness and true-peak measurement.
+
+use std::f64::consts::PI;
+
+/// Loudness of a gating block below this is treated as silence
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Blocks quieter than the ungated mean by more than this are dropped
+const RELATIVE_GATE_LU: f64 = 10.0;
+/// Gating blocks are 400 ms long, advanced in 100 ms steps
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Direct form I biquad
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let out = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [out, self.y[0]];
+        out
+    }
+}
+
+/// K-weighting pre-filter (high shelf) and RLB high-pass, designed for any
+/// sample rate from the analog prototypes the 48 kHz coefficients come from.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    [shelf, high_pass]
+}
+
+fn energy_to_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// Gated integrated loudness (LUFS) of interleaved audio.
+pub struct LoudnessMeter {
+    filters: Vec<[Biquad; 2]>,
+    step_len: usize,
+    step_frames: usize,
+    step_energy: f64,
+    /// Mean-square energy of each completed 100 ms step, summed over channels
+    steps: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        Self {
+            filters: vec![k_weighting(sample_rate); channels.max(1)],
+            step_len: (sample_rate as usize / 10).max(1),
+            step_frames: 0,
+            step_energy: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Feed interleaved samples with the channel count given to `new`
+    pub fn process(&mut self, interleaved: &[f32]) {
+        let channels = self.filters.len();
+        for frame in interleaved.chunks_exact(channels) {
+            for (sample, filters) in frame.iter().zip(self.filters.iter_mut()) {
+                let shelved = filters[0].process(*sample as f64);
+                let weighted = filters[1].process(shelved);
+                self.step_energy += weighted * weighted;
+            }
+            self.step_frames += 1;
+            if self.step_frames == self.step_len {
+                self.steps.push(self.step_energy / self.step_len as f64);
+                self.step_energy = 0.0;
+                self.step_frames = 0;
+            }
+        }
+    }
+
+    /// Integrated loudness, or `None` when nothing rises above the
+    /// absolute gate (silence or less than one 400 ms block)
+    pub fn integrated(&self) -> Option<f64> {
+        if self.steps.len() < STEPS_PER_BLOCK {
+            return None;
+        }
+        let blocks: Vec<f64> = self
+            .steps
+            .windows(STEPS_PER_BLOCK)
+            .map(|w| w.iter().sum::<f64>() / STEPS_PER_BLOCK as f64)
+            .filter(|energy| *energy > 0.0 && energy_to_lufs(*energy) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        let mean = blocks.iter().sum::<f64>() / blocks.len() as f64;
+        let relative_gate = energy_to_lufs(mean) - RELATIVE_GATE_LU;
+        let gated: Vec<f64> = blocks
+            .into_iter()
+            .filter(|energy| energy_to_lufs(*energy) > relative_gate)
+            .collect();
+        let mean = gated.iter().sum::<f64>() / gated.len().max(1) as f64;
+        Some(energy_to_lufs(mean))
+    }
+}
+
+/// Interpolation taps per oversampling phase
+const PEAK_TAPS: usize = 16;
+/// Oversampling factor for true-peak detection
+const PEAK_OVERSAMPLE: usize = 4;
+
+/// True-peak level of interleaved audio, found by 4x oversampling with a
+/// windowed-sinc interpolator.
+pub struct TruePeakMeter {
+    coefficients: [[f32; PEAK_TAPS]; PEAK_OVERSAMPLE - 1],
+    history: Vec<[f32; PEAK_TAPS]>,
+    peak: f32,
+}
+
+impl TruePeakMeter {
+    pub fn new(channels: usize) -> Self {
+        let center = (PEAK_TAPS / 2 - 1) as f64;
+        let half_width = PEAK_TAPS as f64 / 2.0 + 0.5;
+        let mut coefficients = [[0.0; PEAK_TAPS]; PEAK_OVERSAMPLE - 1];
+        for (phase, taps) in coefficients.iter_mut().enumerate() {
+            let position = center + (phase + 1) as f64 / PEAK_OVERSAMPLE as f64;
+            for (j, tap) in taps.iter_mut().enumerate() {
+                let x = position - j as f64;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * x).sin() / (PI * x)
+                };
+                let window = 0.5 * (1.0 + (PI * x / half_width).cos());
+                *tap = (sinc * window) as f32;
+            }
+        }
+        Self {
+            coefficients,
+            history: vec![[0.0; PEAK_TAPS]; channels.max(1)],
+            peak: 0.0,
+        }
+    }
+
+    /// Feed interleaved samples with the channel count given to `new`
+    pub fn process(&mut self, interleaved: &[f32]) {
+        let channels = self.history.len();
+        for frame in interleaved.chunks_exact(channels) {
+            for (sample, history) in frame.iter().zip(self.history.iter_mut()) {
+                self.peak = self.peak.max(sample.abs());
+                history.copy_within(1.., 0);
+                history[PEAK_TAPS - 1] = *sample;
+                for taps in &self.coefficients {
+                    let value: f32 = taps.iter().zip(history.iter()).map(|(c, s)| c * s).sum();
+                    self.peak = self.peak.max(value.abs());
+                }
+            }
+        }
+    }
+
+    /// Linear true-peak amplitude (1.0 = 0 dBTP)
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f64, amplitude: f32, phase: f64, sample_rate: u32, secs: f64) -> Vec<f32> {
+        let frames = (sample_rate as f64 * secs) as usize;
+        (0..frames)
+            .flat_map(|n| {
+                let t = n as f64 / sample_rate as f64;
+                let v = amplitude * (2.0 * PI * freq * t + phase).sin() as f32;
+                [v, v]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn k_weighting_matches_reference_coefficients_at_48k() {
+        let [shelf, high_pass] = k_weighting(48000);
+        let expected_b = [1.53512485958697, -2.69169618940638, 1.19839281085285];
+        let expected_a = [-1.69065929318241, 0.73248077421585];
+        for (got, want) in shelf.b.iter().zip(expected_b) {
+            assert!((got - want).abs() < 1e-9, "{} vs {}", got, want);
+        }
+        for (got, want) in shelf.a.iter().zip(expected_a) {
+            assert!((got - want).abs() < 1e-9, "{} vs {}", got, want);
+        }
+        assert!((high_pass.a[0] - -1.99004745483398).abs() < 1e-9);
+        assert!((high_pass.a[1] - 0.99007225036621).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stereo_sine_reads_its_level_in_lufs() {
+        // A 1 kHz sine in both channels reads its peak level in dBFS
+        for sample_rate in [44100, 48000, 96000] {
+            let mut meter = LoudnessMeter::new(sample_rate, 2);
+            meter.process(&sine(1000.0, 0.1, 0.0, sample_rate, 3.0));
+            let lufs = meter.integrated().unwrap();
+            assert!((lufs - -20.0).abs() < 0.1, "{} Hz: {}", sample_rate, lufs);
+        }
+    }
+
+    #[test]
+    fn silence_and_short_input_have_no_loudness() {
+        let mut meter = LoudnessMeter::new(48000, 2);
+        meter.process(&vec![0.0; 48000 * 2]);
+        assert_eq!(meter.integrated(), None);
+
+        let mut meter = LoudnessMeter::new(48000, 2);
+        meter.process(&sine(1000.0, 0.5, 0.0, 48000, 0.2));
+        assert_eq!(meter.integrated(), None);
+    }
+
+    #[test]
+    fn true_peak_finds_peaks_between_samples() {
+        // fs/4 sine at 45 degrees: every sample sits at 0.707 of the peak
+        let input = sine(12000.0, 1.0, PI / 4.0, 48000, 0.1);
+        let sample_peak = input.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(sample_peak < 0.72);
+
+        let mut meter = TruePeakMeter::new(2);
+        meter.process(&input);
+        let db = 20.0 * meter.peak().log10();
+        assert!(db.abs() < 0.3, "true peak {} dBTP", db);
+    }
+}
@@ -0,0 +1,499 @@
+//! Post-processing of export captures.
+//!
+//! scsynth always captures exports as 32-bit float WAV. When the export
+//! options ask for anything else, the capture is written next to the
+//! output as `*.capture.wav` and converted here once it is complete:
+//! loudness normalization, sample-rate conversion, TPDF-dithered 16/24-bit
+//! quantization, then a WAV or FLAC writer.
+
+mod flac;
+mod loudness;
+mod resample;
+
+pub use loudness::{LoudnessMeter, TruePeakMeter};
+pub use resample::resample;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use imbolc_types::{
+    ExportBitDepth, ExportFormat, ExportOptions, LOUDNESS_TARGET_LUFS, TRUE_PEAK_CEILING_DBTP,
+};
+
+use crate::commands::{AudioFeedback, ExportKind};
+
+/// Share of the export progress bar given to post-processing
+const PROCESS_PROGRESS_SHARE: f32 = 0.2;
+
+/// Time scsynth gets to flush a realtime capture after `/b_close`
+/// (the same delay export buffers wait before being freed)
+const CAPTURE_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+/// Where scsynth writes the float capture for an export to `output`
+pub fn capture_path(output: &Path, options: &ExportOptions) -> PathBuf {
+    if options.is_raw_capture() {
+        output.to_path_buf()
+    } else {
+        output.with_extension("capture.wav")
+    }
+}
+
+/// Fraction of the export progress covered by the capture itself
+pub fn capture_progress_share(options: &ExportOptions) -> f32 {
+    if options.is_raw_capture() {
+        1.0
+    } else {
+        1.0 - PROCESS_PROGRESS_SHARE
+    }
+}
+
+/// Planar audio read from a capture
+struct Audio {
+    sample_rate: u32,
+    channels: Vec<Vec<f32>>,
+}
+
+impl Audio {
+    fn interleaved(&self) -> Vec<f32> {
+        let frames = self.channels.iter().map(Vec::len).max().unwrap_or(0);
+        let mut out = Vec::with_capacity(frames * self.channels.len());
+        for i in 0..frames {
+            for channel in &self.channels {
+                out.push(channel.get(i).copied().unwrap_or(0.0));
+            }
+        }
+        out
+    }
+
+    /// Sum another file into this one, folding extra channels
+    fn mix_in(&mut self, other: &Audio) {
+        let count = self.channels.len();
+        for (c, source) in other.channels.iter().enumerate() {
+            let target = &mut self.channels[c % count];
+            if target.len() < source.len() {
+                target.resize(source.len(), 0.0);
+            }
+            for (t, s) in target.iter_mut().zip(source) {
+                *t += s;
+            }
+        }
+    }
+}
+
+fn read_wav(path: &Path) -> Result<Audio, String> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let samples: Result<Vec<f32>, hound::Error> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 * scale))
+                .collect()
+        }
+    };
+    let samples = samples.map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let count = spec.channels.max(1) as usize;
+    let mut channels = vec![Vec::with_capacity(samples.len() / count); count];
+    for frame in samples.chunks_exact(count) {
+        for (channel, sample) in channels.iter_mut().zip(frame) {
+            channel.push(*sample);
+        }
+    }
+    Ok(Audio {
+        sample_rate: spec.sample_rate,
+        channels,
+    })
+}
+
+/// Linear gain reaching the loudness target, reduced as needed to keep the
+/// true peak at or below the ceiling. Silence is left untouched.
+fn normalization_gain(loudness: Option<f64>, true_peak: f32) -> f32 {
+    let mut gain_db = loudness.map_or(0.0, |lufs| (LOUDNESS_TARGET_LUFS as f64 - lufs) as f32);
+    if true_peak > 0.0 {
+        gain_db = gain_db.min(TRUE_PEAK_CEILING_DBTP - 20.0 * true_peak.log10());
+    }
+    10f32.powf(gain_db / 20.0)
+}
+
+/// One gain for every file, measured on their sum so stems keep their
+/// balance and still add up to a normalized mix. The peak limit covers the
+/// sum and each file on its own.
+fn measure_gain(
+    captures: &[&Path],
+    cancel: &AtomicBool,
+    on_progress: &dyn Fn(f32),
+) -> Result<f32, String> {
+    let mut mix: Option<Audio> = None;
+    let mut true_peak = 0.0f32;
+    for (i, path) in captures.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Export cancelled".to_string());
+        }
+        let audio = read_wav(path)?;
+        let mut meter = TruePeakMeter::new(audio.channels.len());
+        meter.process(&audio.interleaved());
+        true_peak = true_peak.max(meter.peak());
+        match mix.as_mut() {
+            Some(mix) => mix.mix_in(&audio),
+            None => mix = Some(audio),
+        }
+        on_progress((i + 1) as f32 / captures.len() as f32);
+    }
+    let Some(mix) = mix else {
+        return Ok(1.0);
+    };
+    let interleaved = mix.interleaved();
+    if captures.len() > 1 {
+        let mut meter = TruePeakMeter::new(mix.channels.len());
+        meter.process(&interleaved);
+        true_peak = true_peak.max(meter.peak());
+    }
+    let mut loudness = LoudnessMeter::new(mix.sample_rate, mix.channels.len());
+    loudness.process(&interleaved);
+    Ok(normalization_gain(loudness.integrated(), true_peak))
+}
+
+/// Round to signed `bits`-bit integers with TPDF dither of +/-1 LSB
+fn quantize(channels: &[Vec<f32>], bits: u16, seed: u64) -> Vec<Vec<i32>> {
+    let mut state = seed;
+    let mut uniform = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 40) as f64 / (1u64 << 24) as f64
+    };
+    let scale = (1i64 << (bits - 1)) as f64;
+    channels
+        .iter()
+        .map(|channel| {
+            channel
+                .iter()
+                .map(|sample| {
+                    let dither = uniform() - uniform();
+                    (*sample as f64 * scale + dither)
+                        .round()
+                        .clamp(-scale, scale - 1.0) as i32
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn write_wav(
+    path: &Path,
+    audio: &Audio,
+    bit_depth: ExportBitDepth,
+    seed: u64,
+) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: audio.channels.len() as u16,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: bit_depth.bits(),
+        sample_format: if bit_depth.is_float() {
+            hound::SampleFormat::Float
+        } else {
+            hound::SampleFormat::Int
+        },
+    };
+    let error = |e: hound::Error| format!("Cannot write {}: {}", path.display(), e);
+    let mut writer = hound::WavWriter::create(path, spec).map_err(error)?;
+    if bit_depth.is_float() {
+        for sample in audio.interleaved() {
+            writer.write_sample(sample).map_err(error)?;
+        }
+    } else {
+        let channels = quantize(&audio.channels, bit_depth.bits(), seed);
+        let frames = channels.first().map_or(0, Vec::len);
+        for i in 0..frames {
+            for channel in &channels {
+                writer.write_sample(channel[i]).map_err(error)?;
+            }
+        }
+    }
+    writer.finalize().map_err(error)
+}
+
+fn write_flac(
+    path: &Path,
+    audio: &Audio,
+    bit_depth: ExportBitDepth,
+    seed: u64,
+) -> Result<(), String> {
+    if bit_depth.is_float() {
+        return Err("FLAC cannot store float samples".to_string());
+    }
+    let channels = quantize(&audio.channels, bit_depth.bits(), seed);
+    let data = flac::encode(&channels, audio.sample_rate, bit_depth.bits() as u32)?;
+    std::fs::write(path, data).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+fn convert(
+    capture: &Path,
+    output: &Path,
+    options: &ExportOptions,
+    gain: f32,
+    seed: u64,
+) -> Result<(), String> {
+    let mut audio = read_wav(capture)?;
+    if gain != 1.0 {
+        for channel in &mut audio.channels {
+            channel.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+    if let Some(rate) = options
+        .sample_rate
+        .filter(|rate| *rate != audio.sample_rate)
+    {
+        for channel in &mut audio.channels {
+            *channel = resample(channel, audio.sample_rate, rate);
+        }
+        audio.sample_rate = rate;
+    }
+    let result = match options.format {
+        ExportFormat::Wav => write_wav(output, &audio, options.bit_depth, seed),
+        ExportFormat::Flac => write_flac(output, &audio, options.bit_depth, seed),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// Convert finished float captures into their output files. `files` pairs
+/// each capture with its output; captures are deleted once converted.
+pub fn process_captures(
+    files: &[(PathBuf, PathBuf)],
+    options: &ExportOptions,
+    cancel: &AtomicBool,
+    on_progress: impl Fn(f32),
+) -> Result<(), String> {
+    let options = options.validated();
+    let pending: Vec<&(PathBuf, PathBuf)> = files
+        .iter()
+        .filter(|(capture, output)| capture != output)
+        .collect();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let measure_share = if options.normalize { 0.5 } else { 0.0 };
+    let gain = if options.normalize {
+        let captures: Vec<&Path> = pending.iter().map(|(c, _)| c.as_path()).collect();
+        measure_gain(&captures, cancel, &|p| on_progress(p * measure_share))?
+    } else {
+        1.0
+    };
+
+    for (i, (capture, output)) in pending.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Export cancelled".to_string());
+        }
+        convert(capture, output, &options, gain, i as u64 + 1)?;
+        let _ = std::fs::remove_file(capture);
+        let done = (i + 1) as f32 / pending.len() as f32;
+        on_progress(measure_share + done * (1.0 - measure_share));
+    }
+    Ok(())
+}
+
+/// Convert the captures and report the export's outcome, with progress
+/// continuing from where the capture left off. Nothing is reported after
+/// a cancel.
+pub(crate) fn complete_export(
+    kind: ExportKind,
+    files: &[(PathBuf, PathBuf)],
+    options: &ExportOptions,
+    cancel: &AtomicBool,
+    feedback_tx: &Sender<AudioFeedback>,
+) {
+    let base = capture_progress_share(options);
+    let result = process_captures(files, options, cancel, |p| {
+        let progress = base + p * (1.0 - base);
+        let _ = feedback_tx.send(AudioFeedback::ExportProgress { progress });
+    });
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = match result {
+        Ok(()) => feedback_tx.send(AudioFeedback::ExportComplete {
+            kind,
+            paths: files.iter().map(|(_, output)| output.clone()).collect(),
+        }),
+        Err(message) => feedback_tx.send(AudioFeedback::ExportFailed { message }),
+    };
+}
+
+/// `complete_export` for a realtime capture, after giving scsynth time to
+/// flush and close the files
+pub(crate) fn complete_realtime_export(
+    kind: ExportKind,
+    files: &[(PathBuf, PathBuf)],
+    options: &ExportOptions,
+    cancel: &AtomicBool,
+    feedback_tx: &Sender<AudioFeedback>,
+) {
+    std::thread::sleep(CAPTURE_FLUSH_DELAY);
+    complete_export(kind, files, options, cancel, feedback_tx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn write_capture(path: &Path, sample_rate: u32, secs: f64, amplitude: f32) {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for n in 0..(sample_rate as f64 * secs) as usize {
+            let t = n as f64 / sample_rate as f64;
+            let v = amplitude * (2.0 * PI * 1000.0 * t).sin() as f32;
+            writer.write_sample(v).unwrap();
+            writer.write_sample(v).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn loudness_of(path: &Path) -> (f64, f32) {
+        let audio = read_wav(path).unwrap();
+        let interleaved = audio.interleaved();
+        let mut meter = LoudnessMeter::new(audio.sample_rate, audio.channels.len());
+        meter.process(&interleaved);
+        let mut peak = TruePeakMeter::new(audio.channels.len());
+        peak.process(&interleaved);
+        (meter.integrated().unwrap(), 20.0 * peak.peak().log10())
+    }
+
+    #[test]
+    fn capture_path_only_diverts_when_processing() {
+        let output = Path::new("/tmp/bounce_1.flac");
+        assert_eq!(capture_path(output, &ExportOptions::default()), output);
+        let flac = ExportOptions {
+            format: ExportFormat::Flac,
+            bit_depth: ExportBitDepth::Int24,
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            capture_path(output, &flac),
+            Path::new("/tmp/bounce_1.capture.wav")
+        );
+    }
+
+    #[test]
+    fn dither_stays_within_one_lsb_and_averages_out() {
+        let input = vec![vec![0.25f32 + 0.3 / 32768.0; 20_000]];
+        let output = quantize(&input, 16, 7);
+        let target = 0.25 * 32768.0 + 0.3;
+        assert!(output[0].iter().all(|s| (*s as f64 - target).abs() <= 1.5));
+        let mean = output[0].iter().map(|s| *s as f64).sum::<f64>() / 20_000.0;
+        assert!((mean - target).abs() < 0.05, "mean {}", mean);
+
+        let clipped = quantize(&[vec![1.5, -1.5]], 16, 1);
+        assert_eq!(clipped[0], vec![32767, -32768]);
+    }
+
+    #[test]
+    fn normalization_reaches_target_or_ceiling() {
+        let gain = normalization_gain(Some(-20.0), 0.1);
+        assert!((20.0 * gain.log10() - 6.0).abs() < 1e-4);
+        // A loud peak limits the gain to the ceiling
+        let gain = normalization_gain(Some(-20.0), 0.8);
+        assert!((20.0 * (0.8 * gain).log10() - TRUE_PEAK_CEILING_DBTP).abs() < 1e-4);
+        assert_eq!(normalization_gain(None, 0.0), 1.0);
+    }
+
+    #[test]
+    fn capture_becomes_normalized_resampled_24_bit_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("bounce.wav");
+        let options = ExportOptions {
+            format: ExportFormat::Wav,
+            bit_depth: ExportBitDepth::Int24,
+            sample_rate: Some(44100),
+            normalize: true,
+        };
+        let capture = capture_path(&output, &options);
+        write_capture(&capture, 48000, 2.0, 0.05);
+
+        let cancel = AtomicBool::new(false);
+        process_captures(
+            &[(capture.clone(), output.clone())],
+            &options,
+            &cancel,
+            |_| {},
+        )
+        .unwrap();
+        assert!(!capture.exists());
+
+        let reader = hound::WavReader::open(&output).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.sample_rate, 44100);
+        assert_eq!(spec.bits_per_sample, 24);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Int);
+        assert_eq!(reader.duration(), 88200);
+
+        let (lufs, peak_db) = loudness_of(&output);
+        assert!((lufs - LOUDNESS_TARGET_LUFS as f64).abs() < 0.2, "{}", lufs);
+        assert!(peak_db <= TRUE_PEAK_CEILING_DBTP + 0.05);
+    }
+
+    #[test]
+    fn stems_share_one_gain() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ExportOptions {
+            normalize: true,
+            ..ExportOptions::default()
+        };
+        let files: Vec<(PathBuf, PathBuf)> = [0.1f32, 0.02]
+            .iter()
+            .enumerate()
+            .map(|(i, amplitude)| {
+                let output = dir.path().join(format!("stem_{}.wav", i));
+                let capture = capture_path(&output, &options);
+                write_capture(&capture, 48000, 1.0, *amplitude);
+                (capture, output)
+            })
+            .collect();
+
+        let cancel = AtomicBool::new(false);
+        process_captures(&files, &options, &cancel, |_| {}).unwrap();
+        let (loud, _) = loudness_of(&files[0].1);
+        let (quiet, _) = loudness_of(&files[1].1);
+        // 0.1 vs 0.02 is 14 dB apart before and after
+        assert!((loud - quiet - 20.0 * 5f64.log10()).abs() < 0.1);
+    }
+
+    #[test]
+    fn flac_output_and_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("bounce.flac");
+        let options = ExportOptions {
+            format: ExportFormat::Flac,
+            bit_depth: ExportBitDepth::Int16,
+            ..ExportOptions::default()
+        };
+        let capture = capture_path(&output, &options);
+        write_capture(&capture, 48000, 0.5, 0.5);
+        let files = [(capture.clone(), output.clone())];
+
+        let cancel = AtomicBool::new(true);
+        assert!(process_captures(&files, &options, &cancel, |_| {}).is_err());
+        assert!(capture.exists());
+        assert!(!output.exists());
+
+        let cancel = AtomicBool::new(false);
+        process_captures(&files, &options, &cancel, |_| {}).unwrap();
+        let data = std::fs::read(&output).unwrap();
+        assert_eq!(&data[..4], b"fLaC");
+        assert!(data.len() < 24000 * 2 * 2);
+    }
+}
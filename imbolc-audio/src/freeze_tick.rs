@@ -0,0 +1,287 @@
+//! Frozen instrument playback.
+//!
+//! A frozen instrument's render is played onto its source bus by a one-shot
+//! sampler synth, scheduled with the same lookahead as sequenced notes so it
+//! lines up with the timeline. Playback starting inside the render (or after a
+//! seek) starts mid-file. At a loop wrap the running synth is left to ring out
+//! its release tail under the next pass, as the live voices would have.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::audio_clip_tick::{start_from_playhead, stop_buffer_synth, take_stale};
+use super::engine::AudioEngine;
+use super::snapshot::{InstrumentSnapshot, PianoRollSnapshot};
+use imbolc_types::InstrumentId;
+
+/// A render synth that has been scheduled and has not reached its end yet.
+#[derive(Debug, Clone)]
+struct ActiveFreeze {
+    node_id: i32,
+    path: PathBuf,
+    /// Bus the synth writes to; a routing rebuild may move the instrument
+    out_bus: i32,
+    /// Wall-clock time the synth is scheduled to start
+    starts_at: Instant,
+    /// Wall-clock time the synth reaches the end of the file
+    ends_at: Instant,
+}
+
+/// Runtime state for frozen instrument playback.
+#[derive(Debug, Default)]
+pub struct FreezePlayState {
+    active: HashMap<InstrumentId, ActiveFreeze>,
+    /// Synths ringing out past a loop wrap
+    tails: Vec<ActiveFreeze>,
+    last_playhead: Option<u32>,
+}
+
+impl FreezePlayState {
+    /// Stop all scheduled or sounding render synths.
+    pub fn stop_all(&mut self, engine: &mut AudioEngine) {
+        let now = Instant::now();
+        for freeze in self
+            .active
+            .drain()
+            .map(|(_, f)| f)
+            .chain(self.tails.drain(..))
+        {
+            stop(engine, &freeze, now);
+        }
+        self.last_playhead = None;
+    }
+}
+
+fn stop(engine: &mut AudioEngine, freeze: &ActiveFreeze, now: Instant) {
+    // Same one-shot sampler as arrangement clips
+    stop_buffer_synth(engine, freeze.node_id, freeze.starts_at, now);
+}
+
+/// Tick frozen instrument playback, spawning render synths for frozen
+/// instruments whose render enters the lookahead window.
+///
+/// # Arguments
+/// * `engine` - Audio engine for spawning render synths
+/// * `instruments` - Instrument snapshot (which instruments are frozen)
+/// * `piano_roll` - Piano roll snapshot (for playhead, playing state, loop, BPM)
+/// * `state` - Scheduled render synths carried between ticks
+pub fn tick_frozen_instruments(
+    engine: &mut AudioEngine,
+    instruments: &InstrumentSnapshot,
+    piano_roll: &PianoRollSnapshot,
+    state: &mut FreezePlayState,
+) {
+    if !piano_roll.playing || piano_roll.bpm <= 0.0 {
+        if !state.active.is_empty() || !state.tails.is_empty() || state.last_playhead.is_some() {
+            state.stop_all(engine);
+        }
+        return;
+    }
+
+    let playhead = piano_roll.playhead;
+    let tpb = piano_roll.ticks_per_beat;
+    let now = Instant::now();
+    // A jump backwards or further than a beat is a seek, unless it is the loop wrapping
+    if let Some(last) = state.last_playhead {
+        if playhead < last || playhead - last > tpb {
+            let wrapped = piano_roll.looping
+                && playhead < last
+                && playhead.saturating_sub(piano_roll.loop_start) <= tpb
+                && last + tpb >= piano_roll.loop_end;
+            if wrapped {
+                state.tails.extend(state.active.drain().map(|(_, f)| f));
+            } else {
+                state.stop_all(engine);
+            }
+        }
+    }
+    state.last_playhead = Some(playhead);
+    state.tails.retain(|tail| tail.ends_at > now);
+
+    let lookahead_secs = engine.schedule_lookahead_secs;
+    let lookahead_ticks = piano_roll.ticks_after(playhead as f64, lookahead_secs) as u32;
+    let window_end = playhead + lookahead_ticks;
+
+    // Drop finished synths, and stop ones whose instrument was unfrozen,
+    // re-frozen or moved to another bus since scheduling
+    let stale = take_stale(
+        &mut state.active,
+        |active| active.ends_at <= now,
+        |id, active| {
+            instruments
+                .instrument(id)
+                .and_then(|inst| inst.frozen.as_ref())
+                .is_some_and(|f| f.path == active.path)
+                && engine.instrument_source_bus(id) == Some(active.out_bus)
+        },
+    );
+    for freeze in stale {
+        stop(engine, &freeze, now);
+    }
+
+    for inst in &instruments.instruments {
+        let Some(frozen) = inst.frozen.as_ref() else {
+            continue;
+        };
+        if state.active.contains_key(&inst.id)
+            || frozen.start_tick >= window_end
+            || frozen.end_tick <= playhead
+        {
+            continue;
+        }
+
+        let (offset_secs, from_tick) =
+            start_from_playhead(piano_roll, frozen.start_tick, lookahead_secs);
+        let file_offset_secs = frozen.file_offset_secs(from_tick, piano_roll);
+        let remaining_secs = frozen.length_secs as f64 - file_offset_secs;
+        if remaining_secs <= 0.0 {
+            continue;
+        }
+
+        if let Ok((node_id, out_bus)) =
+            engine.play_frozen_render(inst.id, frozen.length_secs, file_offset_secs, offset_secs)
+        {
+            let starts_at = now + Duration::from_secs_f64(offset_secs);
+            state.active.insert(
+                inst.id,
+                ActiveFreeze {
+                    node_id,
+                    path: frozen.path.clone(),
+                    out_bus,
+                    starts_at,
+                    ends_at: starts_at + Duration::from_secs_f64(remaining_secs),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::backend::{RawArg, TestBackend, TestOp};
+    use crate::test_support::write_silent_wav;
+    use imbolc_types::{FrozenRender, InstrumentState, PianoRollState, SourceType};
+    use std::path::Path;
+
+    /// Looping 120 BPM piano roll over two bars, with one instrument frozen
+    /// onto a 4.5-second render of that loop plus a half-second tail.
+    fn frozen_setup(
+        path: &Path,
+    ) -> (
+        AudioEngine,
+        std::sync::Arc<TestBackend>,
+        InstrumentState,
+        PianoRollState,
+    ) {
+        write_silent_wav(path, 2, 0.1);
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        let mut instruments = InstrumentState::new();
+        let id = instruments.add_instrument(SourceType::Saw);
+        let mut piano_roll = PianoRollState::new();
+        piano_roll.bpm = 120.0;
+        piano_roll.playing = true;
+        piano_roll.looping = true;
+        piano_roll.loop_start = 0;
+        piano_roll.loop_end = 8 * piano_roll.ticks_per_beat;
+        instruments.instrument_mut(id).unwrap().frozen = Some(FrozenRender {
+            path: path.to_path_buf(),
+            start_tick: 0,
+            end_tick: 9 * piano_roll.ticks_per_beat,
+            lead_in_secs: 0.02,
+            length_secs: 4.52,
+        });
+        engine.alloc_test_source_bus(id);
+        engine.sync_frozen_buffers(&instruments);
+        backend.clear();
+        (engine, backend, instruments, piano_roll)
+    }
+
+    fn slice_starts(backend: &TestBackend) -> Vec<f32> {
+        backend
+            .operations()
+            .iter()
+            .filter_map(|op| match op {
+                TestOp::SendBundle { messages, .. } => messages.iter().find_map(|(addr, args)| {
+                    if addr != "/s_new" {
+                        return None;
+                    }
+                    args.windows(2).find_map(|w| match (&w[0], &w[1]) {
+                        (RawArg::Str(n), RawArg::Float(v)) if n == "sliceStart" => Some(*v),
+                        _ => None,
+                    })
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn frees(backend: &TestBackend) -> usize {
+        backend.count(|op| match op {
+            TestOp::SendBundle { messages, .. } => {
+                messages.iter().any(|(addr, _)| addr == "/n_free")
+            }
+            _ => false,
+        })
+    }
+
+    #[test]
+    fn render_plays_from_the_playhead_after_its_lead_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut engine, backend, instruments, mut piano_roll) =
+            frozen_setup(&dir.path().join("freeze.wav"));
+        let mut state = FreezePlayState::default();
+
+        // Two beats (one second) in
+        piano_roll.playhead = 2 * piano_roll.ticks_per_beat;
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        piano_roll.playhead += 10;
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+
+        let starts = slice_starts(&backend);
+        assert_eq!(starts.len(), 1, "scheduled once");
+        assert!((starts[0] - 1.02 / 4.52).abs() < 1e-6);
+    }
+
+    #[test]
+    fn loop_wrap_lets_the_tail_ring_but_a_seek_stops_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut engine, backend, instruments, mut piano_roll) =
+            frozen_setup(&dir.path().join("freeze.wav"));
+        let mut state = FreezePlayState::default();
+
+        piano_roll.playhead = piano_roll.loop_end - 10;
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        piano_roll.playhead = piano_roll.loop_start + 5;
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        assert_eq!(slice_starts(&backend).len(), 2, "next pass starts over");
+        assert_eq!(frees(&backend), 0, "previous pass keeps ringing");
+
+        // Seek into the middle: both the pass and the tail stop
+        piano_roll.playhead = 4 * piano_roll.ticks_per_beat;
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        assert_eq!(frees(&backend), 2);
+        assert_eq!(slice_starts(&backend).len(), 3);
+    }
+
+    #[test]
+    fn unfreezing_stops_the_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut engine, backend, mut instruments, piano_roll) =
+            frozen_setup(&dir.path().join("freeze.wav"));
+        let mut state = FreezePlayState::default();
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        assert_eq!(slice_starts(&backend).len(), 1);
+
+        for inst in &mut instruments.instruments {
+            inst.frozen = None;
+        }
+        tick_frozen_instruments(&mut engine, &instruments, &piano_roll, &mut state);
+        assert_eq!(frees(&backend), 1);
+        assert!(state.active.is_empty());
+
+        engine.sync_frozen_buffers(&instruments);
+        assert_eq!(backend.count(|op| matches!(op, TestOp::FreeBuffer(_))), 1);
+    }
+}
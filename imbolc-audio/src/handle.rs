@@ -25,7 +25,9 @@ use imbolc_types::AudioEffect;
 use imbolc_types::Note;
 use imbolc_types::{ArrangementState, PlayMode};
 use imbolc_types::{AutomationLane, AutomationTarget};
-use imbolc_types::{BufferId, BusId, EffectId, InstrumentId};
+use imbolc_types::{
+    BufferId, BusId, EffectId, ExportOptions, ExpressionDimension, InstrumentId, ModController,
+};
 
 /// Audio-owned read state: values that the audio thread is the authority on.
 /// UI reads these for display; audio feedback updates them.
@@ -117,6 +119,13 @@ impl ArrangementFlattenCache {
             placement.length_override.hash(&mut hasher);
         }
 
+        // Audio clips only affect the arrangement length
+        for clip in &arr.audio_clips {
+            clip.id.hash(&mut hasher);
+            clip.start_tick.hash(&mut hasher);
+            clip.duration_ticks.hash(&mut hasher);
+        }
+
         hasher.finish()
     }
 
@@ -263,14 +272,17 @@ impl AudioHandle {
                 };
             }
             AudioFeedback::RecordingStopped(_) => {}
+            AudioFeedback::TakeRecorded { .. } => {}
             AudioFeedback::RenderComplete { .. } => {}
             AudioFeedback::CompileResult(_) => {}
             AudioFeedback::LoadResult(_) => {}
             AudioFeedback::PendingBufferFreed => {}
             AudioFeedback::VstParamsDiscovered { .. } => {}
+            AudioFeedback::VstLatencyReported { .. } => {}
             AudioFeedback::VstStateSaved { .. } => {}
             AudioFeedback::ExportComplete { .. } => {}
             AudioFeedback::ExportProgress { .. } => {}
+            AudioFeedback::ExportFailed { .. } => {}
             AudioFeedback::ServerCrashed { .. } => {
                 self.is_running = false;
             }
@@ -283,6 +295,9 @@ impl AudioHandle {
             AudioFeedback::TelemetrySummary { .. } => {
                 // Telemetry is logged/monitored elsewhere; no state update needed
             }
+            AudioFeedback::ChannelMeters(_) => {
+                // Forwarded to dispatch for the mixer meters
+            }
         }
     }
 
@@ -486,6 +501,11 @@ impl AudioHandle {
                         log::warn!(target: "audio", "set_lfo_param dropped: {}", e);
                     }
                 }
+                AudioEffect::SetModController(instrument_id, controller, value) => {
+                    if let Err(e) = self.set_mod_controller(*instrument_id, *controller, *value) {
+                        log::warn!(target: "audio", "set_mod_controller dropped: {}", e);
+                    }
+                }
                 AudioEffect::SetBusEffectParam(bus_id, effect_id, param_idx, value) => {
                     if let Some(bus) = state.session().mixer.buses.iter().find(|b| b.id == *bus_id)
                     {
@@ -556,6 +576,10 @@ impl AudioHandle {
         self.send(AudioCmd::ResetPlayhead);
     }
 
+    pub fn set_playhead(&mut self, tick: u32) {
+        self.send(AudioCmd::SetPlayhead { tick });
+    }
+
     pub fn set_bpm(&mut self, bpm: f32) {
         self.send(AudioCmd::SetBpm { bpm });
     }
@@ -930,6 +954,19 @@ impl AudioHandle {
         })
     }
 
+    pub fn set_mod_controller(
+        &self,
+        instrument_id: InstrumentId,
+        controller: ModController,
+        value: f32,
+    ) -> Result<(), String> {
+        self.send_cmd(AudioCmd::SetModController {
+            instrument_id,
+            controller,
+            value,
+        })
+    }
+
     pub fn set_bus_effect_param(
         &self,
         bus_id: BusId,
@@ -1003,6 +1040,21 @@ impl AudioHandle {
         })
     }
 
+    pub fn set_voice_expression(
+        &mut self,
+        instrument_id: InstrumentId,
+        pitch: u8,
+        dimension: ExpressionDimension,
+        value: f32,
+    ) -> Result<(), String> {
+        self.send_cmd(AudioCmd::SetVoiceExpression {
+            instrument_id,
+            pitch,
+            dimension,
+            value,
+        })
+    }
+
     pub fn push_active_note(
         &mut self,
         instrument_id: InstrumentId,
@@ -1108,12 +1160,44 @@ impl AudioHandle {
         }
     }
 
+    /// Arm take recording on several instruments at once. Takes start when
+    /// the transport reaches the punch range and come back as
+    /// `AudioFeedback::TakeRecorded`.
+    pub fn start_take_recording(
+        &mut self,
+        instruments: Vec<InstrumentId>,
+        file_prefix: &Path,
+        punch_in: Option<u32>,
+        punch_out: Option<u32>,
+    ) -> Result<(), String> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.send_cmd(AudioCmd::StartTakeRecording {
+            instruments,
+            file_prefix: file_prefix.to_path_buf(),
+            punch_in,
+            punch_out,
+            reply: reply_tx,
+        })?;
+        reply_rx
+            .recv()
+            .unwrap_or_else(|_| Err("Audio thread disconnected".to_string()))
+    }
+
+    pub fn stop_take_recording(&mut self) {
+        self.send(AudioCmd::StopTakeRecording);
+    }
+
     // ── Export (bounce / stems) ──────────────────────────────────
 
-    pub fn start_master_bounce(&mut self, path: &Path) -> Result<(), String> {
+    pub fn start_master_bounce(
+        &mut self,
+        path: &Path,
+        options: ExportOptions,
+    ) -> Result<(), String> {
         let (reply_tx, reply_rx) = mpsc::channel();
         self.send_cmd(AudioCmd::StartMasterBounce {
             path: path.to_path_buf(),
+            options,
             reply: reply_tx,
         })?;
         match reply_rx.recv() {
@@ -1122,10 +1206,34 @@ impl AudioHandle {
         }
     }
 
-    pub fn start_stem_export(&mut self, stems: &[(InstrumentId, PathBuf)]) -> Result<(), String> {
+    pub fn start_stem_export(
+        &mut self,
+        stems: &[(InstrumentId, PathBuf)],
+        options: ExportOptions,
+    ) -> Result<(), String> {
         let (reply_tx, reply_rx) = mpsc::channel();
         self.send_cmd(AudioCmd::StartStemExport {
             stems: stems.to_vec(),
+            options,
+            reply: reply_tx,
+        })?;
+        match reply_rx.recv() {
+            Ok(result) => result,
+            Err(_) => Err("Audio thread disconnected".to_string()),
+        }
+    }
+
+    /// Render the project offline with `scsynth -N`; progress and the
+    /// result arrive as export feedback.
+    pub fn start_offline_export(
+        &mut self,
+        target: crate::nrt::OfflineTarget,
+        options: ExportOptions,
+    ) -> Result<(), String> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.send_cmd(AudioCmd::StartOfflineExport {
+            target,
+            options,
             reply: reply_tx,
         })?;
         match reply_rx.recv() {
@@ -1157,6 +1265,33 @@ impl AudioHandle {
         self.send(AudioCmd::StopTunerTone);
     }
 
+    // ── MIDI Output ──────────────────────────────────────────────
+
+    /// Route external MIDI instrument events to an output scheduler.
+    pub fn set_midi_output(
+        &self,
+        sender: Option<crate::midi_out::MidiOutSender>,
+    ) -> Result<(), String> {
+        self.send_cmd(AudioCmd::SetMidiOutput { sender })
+    }
+
+    /// Send clock as master, follow incoming clock as slave, or neither.
+    pub fn set_midi_clock(
+        &self,
+        mode: imbolc_types::MidiClockMode,
+        output_port: u8,
+    ) -> Result<(), String> {
+        self.send_cmd(AudioCmd::SetMidiClock { mode, output_port })
+    }
+
+    /// Route clock messages from the MIDI input to the transport.
+    pub fn set_midi_clock_input(
+        &self,
+        receiver: Option<crate::midi_clock::MidiClockReceiver>,
+    ) -> Result<(), String> {
+        self.send_cmd(AudioCmd::SetMidiClockInput { receiver })
+    }
+
     // ── Click Track ──────────────────────────────────────────────
 
     pub fn set_click_enabled(&self, enabled: bool) -> Result<(), String> {
@@ -1,5 +1,6 @@
 pub mod arp_state;
 pub mod arpeggiator_tick;
+pub mod audio_clip_tick;
 pub mod audio_thread;
 pub mod bus_allocator;
 pub mod click_tick;
@@ -8,16 +9,25 @@ pub mod devices;
 pub mod drum_tick;
 pub mod engine;
 pub mod event_log;
+pub mod export;
+pub mod freeze_tick;
 pub mod generative_state;
 pub mod generative_tick;
 pub mod handle;
 pub mod input;
+pub mod midi_clock;
+pub mod midi_out;
+pub mod nrt;
 pub mod osc_client;
 pub mod osc_sender;
 pub mod paths;
 pub mod playback;
 pub mod snapshot;
+pub mod take_recording;
 pub mod telemetry;
+pub mod tempo_detect;
+#[cfg(test)]
+mod test_support;
 pub mod triple_buffer;
 
 pub use engine::{AudioEngine, ServerStatus};
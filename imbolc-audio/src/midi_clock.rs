@@ -0,0 +1,382 @@
+//! MIDI clock and transport sync.
+//!
+//! As master, the audio thread sends 24 PPQN timing clock while playing,
+//! scheduled with the same lookahead as notes, plus Start/Stop/Continue and
+//! Song Position Pointer on transport changes. As slave, clock messages from
+//! the MIDI input are timestamped on arrival and forwarded here; the audio
+//! thread derives a smoothed tempo from them and follows the transport.
+
+use std::time::Instant;
+
+use imbolc_types::MidiClockMode;
+
+/// Timing clock resolution (pulses per quarter note)
+pub const PULSES_PER_QUARTER: u32 = 24;
+
+/// Song Position Pointer unit: one MIDI beat is a sixteenth note
+const PULSES_PER_SONG_POSITION: u64 = 6;
+
+/// Weight of each new pulse interval in the smoothed tempo estimate
+const SMOOTHING: f64 = 0.08;
+
+/// Gap after which clock is considered stopped and tempo tracking restarts
+const MAX_PULSE_INTERVAL_SECS: f64 = 0.25;
+
+/// Consecutive out-of-range intervals accepted as a real tempo change
+const OUTLIERS_BEFORE_JUMP: u32 = 3;
+
+/// Smallest tempo change reported to the transport (avoids BPM flicker)
+const BPM_REPORT_THRESHOLD: f32 = 0.05;
+
+/// System real-time / common messages used for sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiClockMessage {
+    Clock,
+    Start,
+    Continue,
+    Stop,
+    /// Song position in MIDI beats (sixteenth notes)
+    SongPosition(u16),
+}
+
+impl MidiClockMessage {
+    /// Parse a raw message. Returns `None` for anything that isn't clock or
+    /// transport.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        match *data.first()? {
+            0xF8 => Some(Self::Clock),
+            0xFA => Some(Self::Start),
+            0xFB => Some(Self::Continue),
+            0xFC => Some(Self::Stop),
+            0xF2 if data.len() >= 3 => Some(Self::SongPosition(
+                (data[1] as u16 & 0x7F) | ((data[2] as u16 & 0x7F) << 7),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Raw bytes and their length
+    pub fn to_bytes(self) -> ([u8; 3], usize) {
+        match self {
+            Self::Clock => ([0xF8, 0, 0], 1),
+            Self::Start => ([0xFA, 0, 0], 1),
+            Self::Continue => ([0xFB, 0, 0], 1),
+            Self::Stop => ([0xFC, 0, 0], 1),
+            Self::SongPosition(pos) => ([0xF2, (pos & 0x7F) as u8, ((pos >> 7) & 0x7F) as u8], 3),
+        }
+    }
+}
+
+/// A clock message from the MIDI input, stamped when it arrived.
+#[derive(Debug, Clone, Copy)]
+pub struct MidiClockEvent {
+    pub message: MidiClockMessage,
+    pub at: Instant,
+}
+
+/// Channel end the MIDI input uses to forward clock messages to the audio thread.
+pub type MidiClockSender = crossbeam_channel::Sender<MidiClockEvent>;
+pub type MidiClockReceiver = crossbeam_channel::Receiver<MidiClockEvent>;
+
+/// Song Position Pointer value for a playhead tick (rounded down to a sixteenth)
+pub fn song_position_for_tick(tick: u32, ticks_per_beat: u32) -> u16 {
+    if ticks_per_beat == 0 {
+        return 0;
+    }
+    (tick as u64 * 4 / ticks_per_beat as u64).min(0x3FFF) as u16
+}
+
+/// Playhead tick for a Song Position Pointer value
+pub fn tick_for_song_position(position: u16, ticks_per_beat: u32) -> u32 {
+    position as u32 * ticks_per_beat / 4
+}
+
+/// Signed distance (in ticks) of `tick` from the nearest beat: positive when
+/// past the beat, negative when before it.
+pub fn beat_phase_error(tick: i64, ticks_per_beat: u32) -> i64 {
+    if ticks_per_beat == 0 {
+        return 0;
+    }
+    let tpb = ticks_per_beat as i64;
+    let phase = tick.rem_euclid(tpb);
+    if phase * 2 >= tpb {
+        phase - tpb
+    } else {
+        phase
+    }
+}
+
+/// Generates master clock pulses at the transport tempo.
+#[derive(Debug, Default)]
+pub struct ClockGenerator {
+    /// Seconds from now until the next pulse is due
+    until_next: f64,
+}
+
+impl ClockGenerator {
+    /// Restart so the next pulse falls at the start of the next window
+    /// (called on Start / Continue so the first clock lines up with beat one).
+    pub fn reset(&mut self) {
+        self.until_next = 0.0;
+    }
+
+    /// Append the offsets (seconds from now) of pulses falling in the next
+    /// `elapsed_secs` window at `bpm`.
+    pub fn advance(&mut self, elapsed_secs: f64, bpm: f64, out: &mut Vec<f64>) {
+        if bpm <= 0.0 {
+            return;
+        }
+        let pulse_secs = 60.0 / (bpm * PULSES_PER_QUARTER as f64);
+        while self.until_next < elapsed_secs {
+            out.push(self.until_next);
+            self.until_next += pulse_secs;
+        }
+        self.until_next -= elapsed_secs;
+    }
+}
+
+/// Follows an incoming clock: smoothed tempo and song position in pulses.
+#[derive(Debug, Default)]
+pub struct ClockFollower {
+    last_pulse: Option<Instant>,
+    /// Smoothed seconds per pulse
+    interval: Option<f64>,
+    /// Intervals measured since tracking (re)started, for faster initial lock
+    samples: u32,
+    /// Consecutive intervals rejected as jitter
+    outliers: u32,
+    /// Last tempo reported to the transport
+    reported_bpm: Option<f32>,
+    /// Pulses since song start (set by Start / Song Position)
+    position: u64,
+}
+
+impl ClockFollower {
+    /// Forget the measured tempo (e.g. when leaving slave mode)
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Followed tempo, once at least one pulse interval has been measured
+    pub fn bpm(&self) -> Option<f32> {
+        self.reported_bpm
+    }
+
+    /// Move the song position (Start = 0, or a Song Position Pointer value)
+    pub fn locate(&mut self, song_position: u16) {
+        self.position = song_position as u64 * PULSES_PER_SONG_POSITION;
+    }
+
+    /// Count a pulse while running. Returns true when the pulse lands on a beat.
+    pub fn advance_position(&mut self) -> bool {
+        self.position += 1;
+        self.position.is_multiple_of(PULSES_PER_QUARTER as u64)
+    }
+
+    /// Feed a pulse arrival time. Returns the new tempo when it moved by more
+    /// than the report threshold.
+    pub fn tempo_pulse(&mut self, at: Instant) -> Option<f32> {
+        let last = self.last_pulse.replace(at);
+        let dt = at.saturating_duration_since(last?).as_secs_f64();
+        if dt <= 0.0 || dt > MAX_PULSE_INTERVAL_SECS {
+            // Clock paused or restarted: measure afresh
+            self.interval = None;
+            self.samples = 0;
+            return None;
+        }
+
+        let smoothed = match self.interval {
+            None => dt,
+            Some(current) if dt < current * 0.5 || dt > current * 2.0 => {
+                // Likely a late/early delivery; only accept after several in a row
+                self.outliers += 1;
+                if self.outliers < OUTLIERS_BEFORE_JUMP {
+                    return None;
+                }
+                self.samples = 0;
+                dt
+            }
+            Some(current) => {
+                let weight = (1.0 / (self.samples + 1) as f64).max(SMOOTHING);
+                current + (dt - current) * weight
+            }
+        };
+        self.outliers = 0;
+        self.samples += 1;
+        self.interval = Some(smoothed);
+
+        let bpm = (60.0 / (smoothed * PULSES_PER_QUARTER as f64)) as f32;
+        let changed = self
+            .reported_bpm
+            .is_none_or(|reported| (bpm - reported).abs() >= BPM_REPORT_THRESHOLD);
+        if changed {
+            self.reported_bpm = Some(bpm);
+            Some(bpm)
+        } else {
+            None
+        }
+    }
+}
+
+/// Audio-thread clock sync state.
+#[derive(Debug, Default)]
+pub struct MidiClockState {
+    pub mode: MidiClockMode,
+    /// Output port clock is sent to in master mode
+    pub output_port: u8,
+    pub generator: ClockGenerator,
+    pub follower: ClockFollower,
+    /// Clock messages forwarded from the MIDI input
+    pub input: Option<MidiClockReceiver>,
+    /// Scratch buffer for master pulse offsets
+    pulses: Vec<f64>,
+}
+
+impl MidiClockState {
+    pub fn set_mode(&mut self, mode: MidiClockMode, output_port: u8) {
+        if mode != self.mode {
+            self.generator.reset();
+            self.follower.reset();
+        }
+        self.mode = mode;
+        self.output_port = output_port;
+    }
+
+    /// Tempo imposed on the transport by the incoming clock (slave mode, once locked)
+    pub fn followed_bpm(&self) -> Option<f32> {
+        if self.mode == MidiClockMode::Slave {
+            self.follower.bpm()
+        } else {
+            None
+        }
+    }
+
+    /// Offsets (seconds from now) of master pulses in the next `elapsed_secs`
+    pub fn master_pulses(&mut self, elapsed_secs: f64, bpm: f64) -> &[f64] {
+        self.pulses.clear();
+        self.generator.advance(elapsed_secs, bpm, &mut self.pulses);
+        &self.pulses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn pulse_interval(bpm: f64) -> Duration {
+        Duration::from_secs_f64(60.0 / (bpm * PULSES_PER_QUARTER as f64))
+    }
+
+    #[test]
+    fn parse_round_trips_transport_messages() {
+        for msg in [
+            MidiClockMessage::Clock,
+            MidiClockMessage::Start,
+            MidiClockMessage::Continue,
+            MidiClockMessage::Stop,
+            MidiClockMessage::SongPosition(1234),
+        ] {
+            let (bytes, len) = msg.to_bytes();
+            assert_eq!(MidiClockMessage::parse(&bytes[..len]), Some(msg));
+        }
+        assert_eq!(MidiClockMessage::parse(&[0x90, 60, 100]), None);
+        assert_eq!(MidiClockMessage::parse(&[0xF2, 1]), None);
+    }
+
+    #[test]
+    fn song_position_converts_in_sixteenths() {
+        assert_eq!(song_position_for_tick(480 * 4, 480), 16);
+        assert_eq!(song_position_for_tick(130, 480), 1);
+        assert_eq!(tick_for_song_position(16, 480), 480 * 4);
+    }
+
+    #[test]
+    fn beat_phase_error_wraps_to_nearest_beat() {
+        assert_eq!(beat_phase_error(960, 480), 0);
+        assert_eq!(beat_phase_error(970, 480), 10);
+        assert_eq!(beat_phase_error(950, 480), -10);
+        assert_eq!(beat_phase_error(-20, 480), -20);
+    }
+
+    #[test]
+    fn generator_emits_24_pulses_per_beat() {
+        let mut generator = ClockGenerator::default();
+        let mut offsets = Vec::new();
+        // One beat at 120 BPM in 0.5ms ticks
+        for _ in 0..999 {
+            let before = offsets.len();
+            generator.advance(0.0005, 120.0, &mut offsets);
+            assert!(offsets[before..].iter().all(|o| *o < 0.0005));
+        }
+        assert_eq!(offsets.len(), 24);
+        assert_eq!(offsets[0], 0.0);
+    }
+
+    #[test]
+    fn follower_derives_bpm_and_ignores_jitter() {
+        let mut follower = ClockFollower::default();
+        let interval = pulse_interval(128.0);
+        let mut at = Instant::now();
+        for i in 0..96 {
+            // ±1ms alternating jitter
+            let jitter = if i % 2 == 0 {
+                Duration::from_millis(1)
+            } else {
+                Duration::ZERO
+            };
+            follower.tempo_pulse(at + jitter);
+            at += interval;
+        }
+        let bpm = follower.bpm().unwrap();
+        assert!((bpm - 128.0).abs() < 1.0, "bpm = {bpm}");
+
+        // A single very late pulse doesn't move the tempo
+        at += interval * 3;
+        assert_eq!(follower.tempo_pulse(at), None);
+        assert_eq!(follower.bpm(), Some(bpm));
+    }
+
+    #[test]
+    fn follower_tracks_tempo_change() {
+        let mut follower = ClockFollower::default();
+        let mut at = Instant::now();
+        for _ in 0..48 {
+            follower.tempo_pulse(at);
+            at += pulse_interval(100.0);
+        }
+        for _ in 0..96 {
+            follower.tempo_pulse(at);
+            at += pulse_interval(140.0);
+        }
+        assert!((follower.bpm().unwrap() - 140.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn followed_bpm_only_applies_in_slave_mode() {
+        let mut clock = MidiClockState::default();
+        let mut at = Instant::now();
+        for _ in 0..4 {
+            clock.follower.tempo_pulse(at);
+            at += pulse_interval(90.0);
+        }
+        assert_eq!(clock.followed_bpm(), None);
+
+        clock.set_mode(MidiClockMode::Slave, 0);
+        assert_eq!(clock.followed_bpm(), None); // mode change resets tracking
+        for _ in 0..4 {
+            clock.follower.tempo_pulse(at);
+            at += pulse_interval(90.0);
+        }
+        assert!((clock.followed_bpm().unwrap() - 90.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn follower_counts_beats_from_song_position() {
+        let mut follower = ClockFollower::default();
+        follower.locate(2); // half a beat in
+        let beats: Vec<bool> = (0..12).map(|_| follower.advance_position()).collect();
+        assert_eq!(beats.iter().filter(|b| **b).count(), 1);
+        assert!(beats[11]);
+    }
+}
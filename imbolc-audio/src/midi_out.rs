@@ -0,0 +1,137 @@
+//! Timed MIDI output for external instruments (`SourceType::MidiOut`).
+//!
+//! The audio thread schedules note and controller messages with the same
+//! lookahead offsets it uses for OSC bundles. Events carry an absolute due
+//! time and are handed to a sink (the MIDI output manager in imbolc-core),
+//! which holds them until they are due and writes them to the port.
+
+use std::time::{Duration, Instant};
+
+use imbolc_types::ParameterTarget;
+
+/// A MIDI message scheduled for an output port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MidiOutEvent {
+    /// Index into the available MIDI output ports
+    pub port: u8,
+    /// Raw message bytes (channel messages use all three)
+    pub message: [u8; 3],
+    /// Number of bytes of `message` to send (1 for clock / transport)
+    pub len: u8,
+    /// When the message should be written to the port
+    pub due: Instant,
+}
+
+impl MidiOutEvent {
+    /// Create a three-byte channel message event due `offset_secs` from now.
+    pub fn after(port: u8, message: [u8; 3], offset_secs: f64) -> Self {
+        Self::bytes_after(port, &message, offset_secs)
+    }
+
+    /// Create an event of up to three bytes due `offset_secs` from now.
+    pub fn bytes_after(port: u8, bytes: &[u8], offset_secs: f64) -> Self {
+        let len = bytes.len().min(3);
+        let mut message = [0u8; 3];
+        message[..len].copy_from_slice(&bytes[..len]);
+        Self {
+            port,
+            message,
+            len: len as u8,
+            due: Instant::now() + Duration::from_secs_f64(offset_secs.max(0.0)),
+        }
+    }
+
+    /// The bytes to write to the port
+    pub fn bytes(&self) -> &[u8] {
+        &self.message[..self.len as usize]
+    }
+}
+
+/// Channel end the audio engine uses to hand events to the MIDI output scheduler.
+pub type MidiOutSender = crossbeam_channel::Sender<MidiOutEvent>;
+
+/// Note-on message. Velocity is 0.0–1.0 and never rounds down to 0 (which
+/// receivers treat as note-off).
+pub fn note_on(channel: u8, pitch: u8, velocity: f32) -> [u8; 3] {
+    let vel = (velocity * 127.0).round().clamp(1.0, 127.0) as u8;
+    [0x90 | (channel & 0x0F), pitch & 0x7F, vel]
+}
+
+pub fn note_off(channel: u8, pitch: u8) -> [u8; 3] {
+    [0x80 | (channel & 0x0F), pitch & 0x7F, 0]
+}
+
+/// Map an automatable parameter to a channel message for an external synth.
+///
+/// Pitch drives pitch bend; mixer, filter, envelope and LFO targets use the
+/// GM2 sound controller CCs. The value is scaled from the target's default
+/// range. Targets with no MIDI equivalent return `None`.
+pub fn param_message(channel: u8, target: &ParameterTarget, value: f32) -> Option<[u8; 3]> {
+    let (min, max) = target.default_range();
+    let normalized = if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let channel = channel & 0x0F;
+
+    if matches!(target, ParameterTarget::Pitch) {
+        let bend = (normalized * 16383.0).round() as u16;
+        return Some([0xE0 | channel, (bend & 0x7F) as u8, (bend >> 7) as u8]);
+    }
+
+    let controller = match target {
+        ParameterTarget::Level => 7,
+        ParameterTarget::Pan => 10,
+        ParameterTarget::FilterResonance => 71,
+        ParameterTarget::Release => 72,
+        ParameterTarget::Attack => 73,
+        ParameterTarget::FilterCutoff => 74,
+        ParameterTarget::Decay => 75,
+        ParameterTarget::LfoRate => 76,
+        ParameterTarget::LfoDepth => 77,
+        _ => return None,
+    };
+    Some([
+        0xB0 | channel,
+        controller,
+        (normalized * 127.0).round() as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_messages_carry_channel() {
+        assert_eq!(note_on(9, 36, 1.0), [0x99, 36, 127]);
+        assert_eq!(note_on(0, 60, 0.0), [0x90, 60, 1]);
+        assert_eq!(note_off(3, 64), [0x83, 64, 0]);
+    }
+
+    #[test]
+    fn pitch_maps_to_centered_bend() {
+        assert_eq!(
+            param_message(0, &ParameterTarget::Pitch, 0.0),
+            Some([0xE0, 0x00, 0x40])
+        );
+        assert_eq!(
+            param_message(1, &ParameterTarget::Pitch, 24.0),
+            Some([0xE1, 0x7F, 0x7F])
+        );
+    }
+
+    #[test]
+    fn sound_controllers_scale_from_default_range() {
+        assert_eq!(
+            param_message(2, &ParameterTarget::FilterCutoff, 20000.0),
+            Some([0xB2, 74, 127])
+        );
+        assert_eq!(
+            param_message(0, &ParameterTarget::Pan, 0.0),
+            Some([0xB0, 10, 64])
+        );
+        assert_eq!(param_message(0, &ParameterTarget::FmIndex, 5.0), None);
+    }
+}
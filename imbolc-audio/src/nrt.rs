@@ -0,0 +1,678 @@
+//! Offline (non-realtime) export.
+//!
+//! The project is played through an `AudioEngine` whose backend writes into a
+//! score on a virtual clock (`ScoreBackend`), driven by the same tick
+//! functions as the audio thread. The score is then handed to `scsynth -N`,
+//! which renders it as fast as the machine allows instead of in real time.
+//!
+//! Score building needs no server, so tests can inspect the OSC it produces.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+
+use crate::commands::{AudioFeedback, ExportKind};
+use crate::engine::backend::{raw_to_osc_pub, BackendMessage, RawArg, ScoreBackend, ScoreBundle};
+use crate::engine::AudioEngine;
+use crate::export;
+use crate::snapshot::{AutomationSnapshot, InstrumentSnapshot, PianoRollSnapshot, SessionSnapshot};
+use imbolc_types::{BufferId, ExportOptions, InstrumentId};
+
+/// Simulation step for driving the tick functions. Matches the audio
+/// thread's tick interval.
+const SIM_STEP: Duration = Duration::from_millis(1);
+
+/// Release tail rendered after the loop end, as for realtime export.
+const TAIL_SECS: f64 = 1.0;
+
+/// Share of the progress bar given to building the score; the rest follows
+/// the render.
+const BUILD_PROGRESS_SHARE: f32 = 0.1;
+
+/// Sample rate used when the server's rate is unknown.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// What an offline export writes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfflineTarget {
+    /// The master output to a single file
+    Master(PathBuf),
+    /// Each instrument's post-effects bus to its own file
+    Stems(Vec<(InstrumentId, PathBuf)>),
+}
+
+impl OfflineTarget {
+    pub fn kind(&self) -> ExportKind {
+        match self {
+            OfflineTarget::Master(_) => ExportKind::MasterBounce,
+            OfflineTarget::Stems(_) => ExportKind::StemExport,
+        }
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            OfflineTarget::Master(path) => vec![path.clone()],
+            OfflineTarget::Stems(stems) => stems.iter().map(|(_, p)| p.clone()).collect(),
+        }
+    }
+
+    /// The same target writing to different files
+    fn map_paths(&self, f: impl Fn(&Path) -> PathBuf) -> OfflineTarget {
+        match self {
+            OfflineTarget::Master(path) => OfflineTarget::Master(f(path)),
+            OfflineTarget::Stems(stems) => {
+                OfflineTarget::Stems(stems.iter().map(|(id, p)| (*id, f(p))).collect())
+            }
+        }
+    }
+}
+
+/// Everything the score is built from, copied off the audio thread.
+#[derive(Clone)]
+pub struct OfflineProject {
+    pub instruments: InstrumentSnapshot,
+    pub session: SessionSnapshot,
+    pub piano_roll: PianoRollSnapshot,
+    pub automation_lanes: AutomationSnapshot,
+    /// Sampler buffers to load, with their files
+    pub samples: Vec<(BufferId, String)>,
+    /// SynthDef directories loaded with /d_loadDir
+    pub synthdef_dirs: Vec<PathBuf>,
+}
+
+/// A time-sorted OSC score ready for `scsynth -N`.
+#[derive(Debug, Clone)]
+pub struct Score {
+    pub bundles: Vec<ScoreBundle>,
+    /// Length of the render in seconds (time of the end marker)
+    pub duration: f64,
+}
+
+fn osc_time(secs: f64) -> OscTime {
+    let secs = secs.max(0.0);
+    OscTime {
+        seconds: secs.trunc() as u32,
+        fractional: (secs.fract() * (u32::MAX as f64)) as u32,
+    }
+}
+
+impl Score {
+    /// Encode as an NRT command file: each bundle is prefixed with its
+    /// length as a big-endian int32, timetags are seconds from the start.
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        for bundle in &self.bundles {
+            let content = bundle
+                .messages
+                .iter()
+                .map(|m| {
+                    OscPacket::Message(OscMessage {
+                        addr: m.addr.clone(),
+                        args: m.args.iter().cloned().map(raw_to_osc_pub).collect(),
+                    })
+                })
+                .collect();
+            let packet = OscPacket::Bundle(OscBundle {
+                timetag: osc_time(bundle.time),
+                content,
+            });
+            let bytes =
+                rosc::encoder::encode(&packet).map_err(|e| format!("OSC encode error: {e}"))?;
+            out.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+            out.extend_from_slice(&bytes);
+        }
+        Ok(out)
+    }
+
+    /// All messages with the given address, with their bundle times.
+    pub fn messages(&self, addr: &str) -> Vec<(f64, &BackendMessage)> {
+        self.bundles
+            .iter()
+            .flat_map(|b| b.messages.iter().map(move |m| (b.time, m)))
+            .filter(|(_, m)| m.addr == addr)
+            .collect()
+    }
+}
+
+/// Play the project from loop start to loop end plus a release tail into a
+/// score. `on_progress` receives 0.0–1.0 as the simulation advances.
+pub fn build_score(
+    project: &OfflineProject,
+    target: &OfflineTarget,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(f32),
+) -> Result<Score, String> {
+    let score = ScoreBackend::new();
+    let mut engine = AudioEngine::with_score_backend(score.clone());
+
+    let mut instruments = project.instruments.clone();
+    let mut session = project.session.clone();
+    let mut piano_roll = project.piano_roll.clone();
+
+    for dir in &project.synthdef_dirs {
+        engine.load_synthdefs(dir)?;
+    }
+    engine.initialize_wavetables()?;
+    for (buffer_id, path) in &project.samples {
+        engine.load_sample(*buffer_id, path)?;
+    }
+    for instrument in &mut instruments.instruments {
+        if let Some(seq) = instrument.drum_sequencer_mut() {
+            for pad in &seq.pads {
+                if let (Some(buffer_id), Some(path)) = (pad.buffer_id, pad.path.as_ref()) {
+                    engine.load_sample(buffer_id, path)?;
+                }
+            }
+            // Sequencers that are running start from the top with the export
+            seq.current_step = 0;
+            seq.step_accumulator = 0.0;
+            seq.last_played_step = None;
+        }
+    }
+    engine.sync_audio_clip_buffers(&session.arrangement.audio_clips);
+    engine.sync_frozen_buffers(&instruments);
+    engine.rebuild_instrument_routing(&instruments, &session)?;
+    engine.update_all_instrument_mixer_params(&instruments, &session)?;
+
+    if let OfflineTarget::Stems(stems) = target {
+        let buses: Vec<(InstrumentId, i32, PathBuf)> = stems
+            .iter()
+            .filter_map(|(id, path)| {
+                engine
+                    .instrument_final_buses
+                    .get(id)
+                    .map(|&bus| (*id, bus, path.clone()))
+            })
+            .collect();
+        if buses.is_empty() {
+            return Err("No instrument buses available".to_string());
+        }
+        engine.start_export_stems(&buses)?;
+    }
+
+    piano_roll.playhead = piano_roll.loop_start;
+    piano_roll.playing = true;
+    piano_roll.looping = false;
+    let tail_ticks = piano_roll.ticks_after(piano_roll.loop_end as f64, TAIL_SECS) as u32;
+    let end_tick = piano_roll.loop_end + tail_ticks;
+    let total_ticks = end_tick.saturating_sub(piano_roll.loop_start).max(1);
+
+    // Feedback from the tick functions (playhead, generative events) has no
+    // listener offline
+    let (feedback_tx, _feedback_rx) = mpsc::channel();
+    let mut active_notes = Vec::new();
+    let mut arp_states = HashMap::new();
+    let mut generative_states = crate::generative_state::GenerativePlayState::default();
+    let mut audio_clip_state = crate::audio_clip_tick::AudioClipPlayState::default();
+    let mut freeze_state = crate::freeze_tick::FreezePlayState::default();
+    let take_record_state = crate::take_recording::TakeRecordState::default();
+    let mut rng_state: u64 = 12345;
+    let mut tick_accumulator = 0.0;
+    let mut last_scheduled_tick = None;
+    let mut last_progress = 0.0;
+
+    let mut step: u64 = 0;
+    while piano_roll.playhead < end_tick {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Export cancelled".to_string());
+        }
+        score.set_time(step as f64 * SIM_STEP.as_secs_f64());
+        engine.set_tempo(piano_roll.bpm_at(piano_roll.playhead as f64), &instruments);
+
+        crate::playback::tick_playback(
+            &mut piano_roll,
+            &mut instruments,
+            &mut session,
+            &project.automation_lanes,
+            &mut engine,
+            &mut active_notes,
+            &mut arp_states,
+            &mut rng_state,
+            &feedback_tx,
+            SIM_STEP,
+            &mut tick_accumulator,
+            &mut last_scheduled_tick,
+        );
+        crate::audio_clip_tick::tick_audio_clips(
+            &mut engine,
+            &session,
+            &instruments,
+            &piano_roll,
+            &mut audio_clip_state,
+            &take_record_state,
+        );
+        crate::freeze_tick::tick_frozen_instruments(
+            &mut engine,
+            &instruments,
+            &piano_roll,
+            &mut freeze_state,
+        );
+        // Step-based ticks run at the tempo under the playhead
+        let bpm = piano_roll.bpm_at(piano_roll.playhead as f64);
+        crate::drum_tick::tick_drum_sequencer(
+            &mut instruments,
+            &session,
+            bpm,
+            &mut engine,
+            &mut rng_state,
+            &feedback_tx,
+            SIM_STEP,
+        );
+        crate::arpeggiator_tick::tick_arpeggiator(
+            &instruments,
+            &session,
+            bpm,
+            &mut arp_states,
+            &mut engine,
+            &mut rng_state,
+            SIM_STEP,
+        );
+        crate::generative_tick::tick_generative(
+            &instruments,
+            &session,
+            bpm,
+            &mut generative_states,
+            &mut engine,
+            &mut rng_state,
+            SIM_STEP,
+            &feedback_tx,
+        );
+
+        let progress =
+            piano_roll.playhead.saturating_sub(piano_roll.loop_start) as f32 / total_ticks as f32;
+        if progress - last_progress > 0.02 {
+            last_progress = progress;
+            on_progress(progress.min(1.0));
+        }
+        step += 1;
+    }
+
+    let duration = step as f64 * SIM_STEP.as_secs_f64();
+    score.set_time(duration);
+    engine.stop_export();
+    // scsynth stops rendering at the last bundle, so end with a no-op
+    let _ = engine.queue_timed_bundle(
+        vec![BackendMessage {
+            addr: "/c_set".to_string(),
+            args: vec![RawArg::Int(0), RawArg::Int(0)],
+        }],
+        0.0,
+    );
+
+    let mut bundles: Vec<ScoreBundle> = score
+        .take_bundles()
+        .into_iter()
+        .filter(|b| b.time <= duration)
+        .collect();
+    bundles.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(Score { bundles, duration })
+}
+
+fn find_scsynth() -> Option<PathBuf> {
+    let candidates = [
+        "scsynth",
+        "/Applications/SuperCollider.app/Contents/Resources/scsynth",
+        "/usr/local/bin/scsynth",
+        "/usr/bin/scsynth",
+    ];
+    candidates.iter().map(PathBuf::from).find(|path| {
+        Command::new(path)
+            .arg("-v")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok()
+    })
+}
+
+/// Parse the score time scsynth reports as it reaches each bundle.
+fn parse_next_packet(line: &str) -> Option<f64> {
+    line.trim()
+        .strip_prefix("nextOSCPacket")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Render a score with `scsynth -N` into `output` (stereo, 32-bit float WAV).
+/// `on_progress` receives 0.0–1.0 as scsynth works through the score.
+pub fn render_score(
+    score: &Score,
+    output: &Path,
+    sample_rate: u32,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(f32),
+) -> Result<(), String> {
+    let scsynth = find_scsynth().ok_or("Could not find scsynth. Install SuperCollider.")?;
+
+    let mut score_file = tempfile::Builder::new()
+        .prefix("imbolc_score_")
+        .suffix(".osc")
+        .tempfile()
+        .map_err(|e| format!("Cannot create score file: {}", e))?;
+    score_file
+        .write_all(&score.encode()?)
+        .map_err(|e| format!("Cannot write score file: {}", e))?;
+    score_file
+        .flush()
+        .map_err(|e| format!("Cannot write score file: {}", e))?;
+
+    let mut child = Command::new(&scsynth)
+        .arg("-N")
+        .arg(score_file.path())
+        .arg("_")
+        .arg(output)
+        .arg(sample_rate.to_string())
+        .args(["WAV", "float", "-o", "2", "-i", "0"])
+        // Same headroom as a busy live session: many voices, buses and buffers
+        .args(["-m", "262144", "-n", "16384", "-a", "4096", "-b", "4096"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start scsynth: {}", e))?;
+
+    // Read output on helper threads so cancellation is checked while
+    // scsynth is quiet
+    let (line_tx, line_rx) = mpsc::channel::<String>();
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        let tx = line_tx.clone();
+        readers.push(thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let _ = tx.send(line);
+            }
+        }));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let tx = line_tx.clone();
+        readers.push(thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                let _ = tx.send(line);
+            }
+        }));
+    }
+    drop(line_tx);
+
+    let mut last_progress = 0.0;
+    let mut recent: Vec<String> = Vec::new();
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_file(output);
+            return Err("Export cancelled".to_string());
+        }
+        match line_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(line) => {
+                if let Some(time) = parse_next_packet(&line) {
+                    let progress = (time / score.duration.max(f64::EPSILON)) as f32;
+                    if progress - last_progress > 0.02 {
+                        last_progress = progress;
+                        on_progress(progress.clamp(0.0, 1.0));
+                    }
+                } else if !line.trim().is_empty() {
+                    recent.push(line);
+                    if recent.len() > 5 {
+                        recent.remove(0);
+                    }
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                break child.wait().map_err(|e| e.to_string())?;
+            }
+        }
+    };
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    if !status.success() {
+        return Err(format!(
+            "scsynth exited ({}): {}",
+            status,
+            recent.join(" / ")
+        ));
+    }
+    if !output.exists() {
+        return Err("scsynth produced no output".to_string());
+    }
+    Ok(())
+}
+
+/// Build and render an export, reporting through `feedback_tx`. Runs on its
+/// own thread; sends `ExportComplete` or `ExportFailed` unless cancelled.
+pub(crate) fn run_export(
+    project: OfflineProject,
+    target: OfflineTarget,
+    options: ExportOptions,
+    sample_rate: u32,
+    cancel: &AtomicBool,
+    feedback_tx: &Sender<AudioFeedback>,
+) {
+    let report = |progress: f32| {
+        let _ = feedback_tx.send(AudioFeedback::ExportProgress { progress });
+    };
+    // scsynth renders float captures; anything else is converted afterwards
+    let capture = target.map_paths(|path| export::capture_path(path, &options));
+    let render_share = export::capture_progress_share(&options) - BUILD_PROGRESS_SHARE;
+    let result = build_score(&project, &capture, cancel, |p| {
+        report(p * BUILD_PROGRESS_SHARE)
+    })
+    .and_then(|score| {
+        // Master comes from the main outputs; stems are written by their
+        // DiskOut synths and the main outputs are thrown away
+        let scratch;
+        let output = match &capture {
+            OfflineTarget::Master(path) => path.as_path(),
+            OfflineTarget::Stems(_) => {
+                scratch = tempfile::Builder::new()
+                    .prefix("imbolc_nrt_")
+                    .suffix(".wav")
+                    .tempfile()
+                    .map_err(|e| format!("Cannot create scratch file: {}", e))?
+                    .into_temp_path();
+                &*scratch
+            }
+        };
+        render_score(&score, output, sample_rate, cancel, |p| {
+            report(BUILD_PROGRESS_SHARE + p * render_share)
+        })
+    });
+
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    match result {
+        Ok(()) => {
+            let files: Vec<(PathBuf, PathBuf)> =
+                capture.paths().into_iter().zip(target.paths()).collect();
+            export::complete_export(target.kind(), &files, &options, cancel, feedback_tx);
+        }
+        Err(message) => {
+            let _ = feedback_tx.send(AudioFeedback::ExportFailed { message });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imbolc_types::state::mixer::DEFAULT_BUS_COUNT;
+    use imbolc_types::{InstrumentState, SessionState, SourceType};
+
+    fn project() -> (OfflineProject, InstrumentId) {
+        let mut instruments = InstrumentState::new();
+        let mut session = SessionState::new_with_defaults(
+            imbolc_types::session::MusicalSettings::default(),
+            DEFAULT_BUS_COUNT,
+        );
+        let id = instruments.add_instrument(SourceType::Saw);
+        session.piano_roll.add_track(id);
+        let tpb = session.piano_roll.ticks_per_beat;
+        session.piano_roll.toggle_note(0, 60, 0, tpb, 100);
+        session.piano_roll.toggle_note(0, 64, tpb * 2, tpb, 100);
+        session.piano_roll.loop_start = 0;
+        session.piano_roll.loop_end = tpb * 4;
+        session.piano_roll.looping = true;
+        let piano_roll = session.piano_roll.clone();
+        (
+            OfflineProject {
+                instruments,
+                session,
+                piano_roll,
+                automation_lanes: Vec::new(),
+                samples: Vec::new(),
+                synthdef_dirs: Vec::new(),
+            },
+            id,
+        )
+    }
+
+    fn build(project: &OfflineProject, target: &OfflineTarget) -> Score {
+        build_score(project, target, &AtomicBool::new(false), |_| {}).unwrap()
+    }
+
+    fn voice_spawn_times(score: &Score, def_prefix: &str) -> Vec<f64> {
+        score
+            .messages("/s_new")
+            .into_iter()
+            .filter(|(_, m)| matches!(&m.args[0], RawArg::Str(d) if d.starts_with(def_prefix)))
+            .map(|(t, _)| t)
+            .collect()
+    }
+
+    #[test]
+    fn notes_land_on_their_musical_time() {
+        let (project, _) = project();
+        let score = build(&project, &OfflineTarget::Master(PathBuf::from("out.wav")));
+
+        // 120 BPM: notes at beat 0 and beat 2
+        let times = voice_spawn_times(&score, "imbolc_saw");
+        assert_eq!(times.len(), 2, "one voice per note: {:?}", times);
+        assert!(times[0] < 0.002);
+        assert!((times[1] - 1.0).abs() < 0.002, "{:?}", times);
+
+        // Four beats plus one second of tail
+        assert!((score.duration - 3.0).abs() < 0.01, "{}", score.duration);
+    }
+
+    #[test]
+    fn score_is_sorted_and_ends_with_marker() {
+        let (project, _) = project();
+        let score = build(&project, &OfflineTarget::Master(PathBuf::from("out.wav")));
+
+        assert!(score.bundles.windows(2).all(|w| w[0].time <= w[1].time));
+        let last = score.bundles.last().unwrap();
+        assert_eq!(last.time, score.duration);
+        assert_eq!(last.messages[0].addr, "/c_set");
+
+        // Graph is in place before anything plays
+        let sources = score
+            .messages("/g_new")
+            .into_iter()
+            .find(|(_, m)| m.args[0] == RawArg::Int(crate::engine::GROUP_SOURCES));
+        assert_eq!(sources.map(|(t, _)| t), Some(0.0));
+        assert!(score.messages("/b_gen").iter().all(|(t, _)| *t == 0.0));
+    }
+
+    #[test]
+    fn stems_record_each_instrument_bus() {
+        let (project, id) = project();
+        let path = PathBuf::from("/tmp/stem.wav");
+        let score = build(&project, &OfflineTarget::Stems(vec![(id, path.clone())]));
+
+        let writes = score.messages("/b_write");
+        assert_eq!(writes.len(), 1);
+        assert_eq!(
+            writes[0].1.args[1],
+            RawArg::Str(path.to_string_lossy().to_string())
+        );
+        assert_eq!(voice_spawn_times(&score, "imbolc_disk_record").len(), 1);
+        let closes = score.messages("/b_close");
+        assert_eq!(closes.len(), 1);
+        assert_eq!(closes[0].0, score.duration);
+    }
+
+    #[test]
+    fn running_drum_sequencer_is_scheduled_from_step_zero() {
+        let (mut project, _) = project();
+        let kit = project.instruments.add_instrument(SourceType::Kit);
+        project.session.piano_roll.add_track(kit);
+        let seq = project
+            .instruments
+            .instrument_mut(kit)
+            .unwrap()
+            .drum_sequencer_mut()
+            .unwrap();
+        seq.pads[0].buffer_id = Some(7);
+        seq.pads[0].path = Some("/samples/kick.wav".to_string());
+        seq.pattern_mut().steps[0][0].active = true;
+        seq.current_step = 5;
+        seq.playing = true;
+
+        let score = build(&project, &OfflineTarget::Master(PathBuf::from("out.wav")));
+
+        let loads = score.messages("/b_allocRead");
+        assert!(loads
+            .iter()
+            .any(|(t, m)| *t == 0.0 && m.args[1] == RawArg::Str("/samples/kick.wav".into())));
+        let hits = voice_spawn_times(&score, "imbolc_sampler");
+        assert!(!hits.is_empty());
+        assert!(hits[0] < 0.002, "first hit at step 0: {:?}", hits);
+    }
+
+    #[test]
+    fn encoded_score_decodes_back() {
+        let (project, _) = project();
+        let score = build(&project, &OfflineTarget::Master(PathBuf::from("out.wav")));
+        let bytes = score.encode().unwrap();
+
+        let mut offset = 0;
+        let mut decoded = 0;
+        let mut last_time = 0.0;
+        while offset < bytes.len() {
+            let len = i32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+            let (_, packet) = rosc::decoder::decode_udp(&bytes[offset..offset + len]).unwrap();
+            let OscPacket::Bundle(bundle) = packet else {
+                panic!("expected bundle");
+            };
+            let time =
+                bundle.timetag.seconds as f64 + bundle.timetag.fractional as f64 / u32::MAX as f64;
+            assert!(time + 1e-6 >= last_time);
+            last_time = time;
+            offset += len;
+            decoded += 1;
+        }
+        assert_eq!(decoded, score.bundles.len());
+        assert!((last_time - score.duration).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cancel_stops_building() {
+        let (project, _) = project();
+        let result = build_score(
+            &project,
+            &OfflineTarget::Master(PathBuf::from("out.wav")),
+            &AtomicBool::new(true),
+            |_| {},
+        );
+        assert_eq!(result.unwrap_err(), "Export cancelled");
+    }
+
+    #[test]
+    fn parses_scsynth_progress_lines() {
+        assert_eq!(parse_next_packet("nextOSCPacket 1.5"), Some(1.5));
+        assert_eq!(parse_next_packet("  nextOSCPacket 0\n"), Some(0.0));
+        assert_eq!(parse_next_packet("FAILURE /n_free Node not found"), None);
+    }
+}
@@ -63,6 +63,20 @@ pub struct VstParamReply {
     pub display: String,
 }
 
+/// A reply from a channel meter synth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelMeterReply {
+    /// `/channel_level`: peak and RMS per side
+    PeakRms {
+        peak_l: f32,
+        peak_r: f32,
+        rms_l: f32,
+        rms_r: f32,
+    },
+    /// `/channel_lufs`: short-term loudness
+    Lufs(f32),
+}
+
 /// Shared meter + waveform + visualization data accessible from both threads.
 ///
 /// Scalar fields use atomics for lock-free reads (reduces jitter from UI thread contention).
@@ -97,6 +111,12 @@ pub struct AudioMonitor {
     /// Channel for /n_end notifications from SuperCollider (node freed)
     node_end_tx: Sender<i32>,
     node_end_rx: Receiver<i32>,
+    /// Channel for VST plugin latency reports: (node_id, latency in samples)
+    vst_latency_tx: Sender<(i32, u32)>,
+    vst_latency_rx: Receiver<(i32, u32)>,
+    /// Channel for channel meter replies: (node_id, reply)
+    channel_meter_tx: Sender<(i32, ChannelMeterReply)>,
+    channel_meter_rx: Receiver<(i32, ChannelMeterReply)>,
 }
 
 impl Default for AudioMonitor {
@@ -111,6 +131,8 @@ impl AudioMonitor {
         scope.resize(SCOPE_BUFFER_SIZE, 0.0);
         let (vst_param_tx, vst_param_rx) = unbounded();
         let (node_end_tx, node_end_rx) = unbounded();
+        let (vst_latency_tx, vst_latency_rx) = unbounded();
+        let (channel_meter_tx, channel_meter_rx) = unbounded();
         Self {
             meter_data: Arc::new(AtomicU64::new(pack_f32_pair(0.0, 0.0))),
             audio_in_waveforms: TripleBufferHandle::new(),
@@ -126,6 +148,10 @@ impl AudioMonitor {
             vst_params_accumulated: Arc::new(Mutex::new(HashMap::new())),
             node_end_tx,
             node_end_rx,
+            vst_latency_tx,
+            vst_latency_rx,
+            channel_meter_tx,
+            channel_meter_rx,
         }
     }
 
@@ -229,6 +255,24 @@ impl AudioMonitor {
         }
         ids
     }
+
+    /// Drain all pending VST latency reports as (node_id, latency in samples).
+    pub fn drain_vst_latencies(&self) -> Vec<(i32, u32)> {
+        let mut reports = Vec::new();
+        while let Ok(report) = self.vst_latency_rx.try_recv() {
+            reports.push(report);
+        }
+        reports
+    }
+
+    /// Drain all pending channel meter replies as (node_id, reply).
+    pub fn drain_channel_meters(&self) -> Vec<(i32, ChannelMeterReply)> {
+        let mut replies = Vec::new();
+        while let Ok(reply) = self.channel_meter_rx.try_recv() {
+            replies.push(reply);
+        }
+        replies
+    }
 }
 
 pub struct OscClient {
@@ -250,6 +294,10 @@ pub struct OscClient {
     vst_params_accumulated: Arc<Mutex<HashMap<i32, Vec<VstParamReply>>>>,
     node_end_tx: Sender<i32>,
     node_end_rx: Receiver<i32>,
+    vst_latency_tx: Sender<(i32, u32)>,
+    vst_latency_rx: Receiver<(i32, u32)>,
+    channel_meter_tx: Sender<(i32, ChannelMeterReply)>,
+    channel_meter_rx: Receiver<(i32, ChannelMeterReply)>,
     _recv_thread: Option<JoinHandle<()>>,
 }
 
@@ -265,6 +313,8 @@ struct OscRefs {
     status_sent_at: Arc<AtomicU64>,
     vst_param_tx: Sender<(i32, VstParamReply)>,
     node_end_tx: Sender<i32>,
+    vst_latency_tx: Sender<(i32, u32)>,
+    channel_meter_tx: Sender<(i32, ChannelMeterReply)>,
 }
 
 fn handle_osc_packet(packet: &OscPacket, refs: &OscRefs) {
@@ -299,6 +349,40 @@ fn handle_osc_packet(packet: &OscPacket, refs: &OscRefs) {
                         buffer.pop_front();
                     }
                 });
+            } else if msg.addr == "/channel_level" && msg.args.len() >= 6 {
+                // SendPeakRMS format: /channel_level nodeID replyID peakL rmsL peakR rmsR
+                let node_id = match msg.args.first() {
+                    Some(OscType::Int(v)) => *v,
+                    Some(OscType::Float(v)) => *v as i32,
+                    _ => return,
+                };
+                let level = |i: usize| match msg.args.get(i) {
+                    Some(OscType::Float(v)) => *v,
+                    _ => 0.0,
+                };
+                let _ = refs.channel_meter_tx.send((
+                    node_id,
+                    ChannelMeterReply::PeakRms {
+                        peak_l: level(2),
+                        rms_l: level(3),
+                        peak_r: level(4),
+                        rms_r: level(5),
+                    },
+                ));
+            } else if msg.addr == "/channel_lufs" && msg.args.len() >= 3 {
+                // SendReply format: /channel_lufs nodeID replyID lufs
+                let node_id = match msg.args.first() {
+                    Some(OscType::Int(v)) => *v,
+                    Some(OscType::Float(v)) => *v as i32,
+                    _ => return,
+                };
+                let lufs = match msg.args.get(2) {
+                    Some(OscType::Float(v)) => *v,
+                    _ => return,
+                };
+                let _ = refs
+                    .channel_meter_tx
+                    .send((node_id, ChannelMeterReply::Lufs(lufs)));
             } else if msg.addr == "/spectrum" && msg.args.len() >= 9 {
                 // SendReply format: /spectrum nodeID replyID val0 val1 ... val6
                 let mut bands = [0.0_f32; 7];
@@ -393,6 +477,24 @@ fn handle_osc_packet(packet: &OscPacket, refs: &OscRefs) {
                         display,
                     },
                 ));
+            } else if (msg.addr == "/vst_open" && msg.args.len() >= 5)
+                || (msg.addr == "/vst_latency" && msg.args.len() >= 3)
+            {
+                // VSTPlugin reports latency once the plugin is opened and whenever it changes:
+                // /vst_open nodeID replyID success hasEditor latency
+                // /vst_latency nodeID replyID latency
+                let node_id = match msg.args.first() {
+                    Some(OscType::Int(v)) => *v,
+                    Some(OscType::Float(v)) => *v as i32,
+                    _ => return,
+                };
+                let latency_idx = if msg.addr == "/vst_open" { 4 } else { 2 };
+                let samples = match msg.args.get(latency_idx) {
+                    Some(OscType::Int(v)) => (*v).max(0) as u32,
+                    Some(OscType::Float(v)) => v.max(0.0) as u32,
+                    _ => return,
+                };
+                let _ = refs.vst_latency_tx.send((node_id, samples));
             } else if msg.addr == "/n_end" && !msg.args.is_empty() {
                 // /n_end nodeID [prev_node_id, ...] — SC notifies when a node is freed
                 let node_id = match msg.args.first() {
@@ -432,6 +534,10 @@ impl OscClient {
         let vst_params_accumulated = Arc::clone(&monitor.vst_params_accumulated);
         let node_end_tx = monitor.node_end_tx.clone();
         let node_end_rx = monitor.node_end_rx.clone();
+        let vst_latency_tx = monitor.vst_latency_tx.clone();
+        let vst_latency_rx = monitor.vst_latency_rx.clone();
+        let channel_meter_tx = monitor.channel_meter_tx.clone();
+        let channel_meter_rx = monitor.channel_meter_rx.clone();
 
         // Clone socket for receive thread
         let recv_socket = socket.try_clone()?;
@@ -449,6 +555,8 @@ impl OscClient {
             status_sent_at: Arc::clone(&status_sent_at),
             vst_param_tx: osc_vst_param_tx,
             node_end_tx: osc_node_end_tx,
+            vst_latency_tx: vst_latency_tx.clone(),
+            channel_meter_tx: channel_meter_tx.clone(),
         };
 
         let handle = thread::spawn(move || {
@@ -483,6 +591,10 @@ impl OscClient {
             vst_params_accumulated,
             node_end_tx,
             node_end_rx,
+            vst_latency_tx,
+            vst_latency_rx,
+            channel_meter_tx,
+            channel_meter_rx,
             _recv_thread: Some(handle),
         })
     }
@@ -504,6 +616,10 @@ impl OscClient {
             vst_params_accumulated: Arc::clone(&self.vst_params_accumulated),
             node_end_tx: self.node_end_tx.clone(),
             node_end_rx: self.node_end_rx.clone(),
+            vst_latency_tx: self.vst_latency_tx.clone(),
+            vst_latency_rx: self.vst_latency_rx.clone(),
+            channel_meter_tx: self.channel_meter_tx.clone(),
+            channel_meter_rx: self.channel_meter_rx.clone(),
         }
     }
 
@@ -7,7 +7,7 @@ use super::engine::AudioEngine;
 use super::snapshot::{AutomationSnapshot, InstrumentSnapshot, PianoRollSnapshot, SessionSnapshot};
 use crate::arp_state::ArpPlayState;
 use imbolc_types::SwingGrid;
-use imbolc_types::{AutomationTarget, InstrumentId};
+use imbolc_types::{AutomationTarget, InstrumentId, NoteExpression};
 
 fn next_random(state: &mut u64) -> f32 {
     *state = state
@@ -31,10 +31,20 @@ pub fn tick_playback(
     tick_accumulator: &mut f64,
     last_scheduled_tick: &mut Option<u32>,
 ) {
-    // (instrument_id, pitch, velocity, duration, note_tick, probability, ticks_from_old_playhead)
+    // (instrument_id, pitch, velocity, duration, note_tick, probability, secs_from_old_playhead,
+    //  expression)
     #[allow(clippy::type_complexity)]
     let mut playback_data: Option<(
-        Vec<(InstrumentId, u8, u8, u32, u32, f32, f64)>,
+        Vec<(
+            InstrumentId,
+            u8,
+            u8,
+            u32,
+            u32,
+            f32,
+            f64,
+            Option<NoteExpression>,
+        )>,
         u32,
         u32,
         u32,
@@ -43,9 +53,10 @@ pub fn tick_playback(
     )> = None;
 
     if piano_roll.playing {
-        *tick_accumulator += elapsed.as_secs_f64()
-            * (piano_roll.bpm as f64 / 60.0)
-            * piano_roll.ticks_per_beat as f64;
+        *tick_accumulator += piano_roll.ticks_after(
+            piano_roll.playhead as f64 + *tick_accumulator,
+            elapsed.as_secs_f64(),
+        );
         let tick_delta = *tick_accumulator as u32;
         *tick_accumulator -= tick_delta as f64;
 
@@ -54,11 +65,13 @@ pub fn tick_playback(
             piano_roll.advance(tick_delta);
             let new_playhead = piano_roll.playhead;
 
-            let secs_per_tick = 60.0 / (piano_roll.bpm as f64 * piano_roll.ticks_per_beat as f64);
+            let secs_per_tick = 60.0
+                / (piano_roll.bpm_at(new_playhead as f64) as f64
+                    * piano_roll.ticks_per_beat as f64);
 
             // Compute lookahead in ticks for pre-scheduling
-            let lookahead_ticks = ((engine.schedule_lookahead_secs * piano_roll.bpm as f64 / 60.0)
-                * piano_roll.ticks_per_beat as f64) as u32;
+            let lookahead_ticks =
+                piano_roll.ticks_after(new_playhead as f64, engine.schedule_lookahead_secs) as u32;
 
             // Determine scan window using high-water mark
             // scan_start: resume from where we left off, or old_playhead if no prior scheduling
@@ -68,7 +81,8 @@ pub fn tick_playback(
 
             // Build scan ranges: handle loop wrapping
             // Notes in [scan_start, scan_end) need to be scheduled.
-            // base_ticks is relative to old_playhead for offset calculation.
+            // base_secs is the time from old_playhead to the range start,
+            // following the tempo map, for offset calculation.
             let wrapped_playhead = new_playhead < old_playhead;
             let _loop_len = if piano_roll.loop_end > piano_roll.loop_start {
                 piano_roll.loop_end - piano_roll.loop_start
@@ -84,32 +98,34 @@ pub fn tick_playback(
                 // scan_start is before loop_end; new_playhead is after loop_start.
                 // We need: [scan_start, loop_end) and [loop_start, new_playhead + lookahead)
                 let after_wrap_end = (new_playhead + lookahead_ticks).min(piano_roll.loop_end);
-                let _pre_wrap_base = 0.0_f64; // ticks from old_playhead to scan_start
-                let post_wrap_base = (piano_roll.loop_end - old_playhead) as f64;
+                let post_wrap_base =
+                    piano_roll.secs_between(old_playhead as f64, piano_roll.loop_end as f64);
 
                 scan_ranges = vec![
                     (
                         scan_start,
                         piano_roll.loop_end,
-                        (scan_start as f64 - old_playhead as f64),
+                        piano_roll.secs_between(old_playhead as f64, scan_start as f64),
                     ),
                     (piano_roll.loop_start, after_wrap_end, post_wrap_base),
                 ];
                 effective_scan_end = after_wrap_end;
-            } else if scan_end_raw > piano_roll.loop_end
+            } else if piano_roll.looping
+                && scan_end_raw > piano_roll.loop_end
                 && piano_roll.loop_end > piano_roll.loop_start
             {
                 // Pre-scheduling crosses the loop boundary but playhead hasn't wrapped yet.
                 // Scan [scan_start, loop_end) and [loop_start, loop_start + overflow)
                 let overflow = scan_end_raw - piano_roll.loop_end;
                 let after_wrap_end = (piano_roll.loop_start + overflow).min(piano_roll.loop_end);
-                let post_wrap_base = (piano_roll.loop_end - old_playhead) as f64;
+                let post_wrap_base =
+                    piano_roll.secs_between(old_playhead as f64, piano_roll.loop_end as f64);
 
                 scan_ranges = vec![
                     (
                         scan_start,
                         piano_roll.loop_end,
-                        (scan_start as f64 - old_playhead as f64),
+                        piano_roll.secs_between(old_playhead as f64, scan_start as f64),
                     ),
                     (piano_roll.loop_start, after_wrap_end, post_wrap_base),
                 ];
@@ -120,19 +136,19 @@ pub fn tick_playback(
                 scan_ranges = vec![(
                     scan_start,
                     clamped_end,
-                    (scan_start as f64 - old_playhead as f64),
+                    piano_roll.secs_between(old_playhead as f64, scan_start as f64),
                 )];
                 effective_scan_end = clamped_end;
             };
 
-            let mut note_ons: Vec<(InstrumentId, u8, u8, u32, u32, f32, f64)> = Vec::new();
+            let mut note_ons = Vec::new();
             let any_solo = instruments.any_instrument_solo();
             for &instrument_id in &piano_roll.track_order {
                 if let Some(track) = piano_roll.tracks.get(&instrument_id) {
                     // Expand layer group: collect all target IDs for this instrument
                     let targets = instruments.layer_group_members(instrument_id);
 
-                    for &(range_start, range_end, base_ticks) in &scan_ranges {
+                    for &(range_start, range_end, base_secs) in &scan_ranges {
                         if range_start >= range_end {
                             continue;
                         }
@@ -142,7 +158,8 @@ pub fn tick_playback(
                         let end_idx = track.notes.partition_point(|n| n.tick < range_end);
 
                         for note in &track.notes[start_idx..end_idx] {
-                            let ticks_from_old = base_ticks + (note.tick - range_start) as f64;
+                            let secs_from_old = base_secs
+                                + piano_roll.secs_between(range_start as f64, note.tick as f64);
                             for &target_id in &targets {
                                 // Skip muted/inactive siblings
                                 let skip = instruments.instrument(target_id).is_none_or(|inst| {
@@ -163,7 +180,8 @@ pub fn tick_playback(
                                     note.duration,
                                     note.tick,
                                     note.probability,
-                                    ticks_from_old,
+                                    secs_from_old,
+                                    note.expression.as_deref().cloned(),
                                 ));
                             }
                         }
@@ -200,15 +218,16 @@ pub fn tick_playback(
             let global_humanize_vel = session.humanize.velocity;
             let global_humanize_time = session.humanize.timing;
 
-            for &(
+            for (
                 instrument_id,
                 pitch,
                 velocity,
                 duration,
                 note_tick,
                 probability,
-                ticks_from_old,
-            ) in &note_ons
+                secs_from_old,
+                expression,
+            ) in note_ons
             {
                 // Probability check: skip note if random exceeds probability
                 if probability < 1.0 && next_random(rng_state) > probability {
@@ -249,7 +268,7 @@ pub fn tick_playback(
                     continue;
                 }
 
-                let mut offset = ticks_from_old * secs_per_tick + engine.schedule_lookahead_secs;
+                let mut offset = secs_from_old + engine.schedule_lookahead_secs;
 
                 // Apply timing offset (rush/drag) - negative = rush, positive = drag
                 offset += (timing_offset_ms / 1000.0) as f64;
@@ -303,6 +322,27 @@ pub fn tick_playback(
                 let _ =
                     engine.spawn_voice(instrument_id, pitch, vel_f, offset, instruments, session);
                 active_notes.push((instrument_id, pitch, duration));
+
+                // Per-note expression curves follow the voice, clipped to the note
+                if let Some(expression) = expression {
+                    for (dimension, point) in expression.points() {
+                        if point.tick >= duration {
+                            break;
+                        }
+                        let at = offset
+                            + piano_roll
+                                .secs_between(note_tick as f64, (note_tick + point.tick) as f64);
+                        let _ = engine.set_voice_expression(
+                            instrument_id,
+                            pitch,
+                            dimension,
+                            point.value,
+                            at,
+                            instruments,
+                            session,
+                        );
+                    }
+                }
             }
 
             // Collect automation updates into a single bundle
@@ -732,4 +772,42 @@ mod tests {
             expected_lt
         );
     }
+
+    #[test]
+    fn playhead_follows_tempo_events() {
+        let (mut pr, mut inst, mut session, mut engine, _rx, tx) = make_fixtures();
+        pr.looping = false;
+        pr.playhead = 0;
+        // Half speed from tick 480 onward
+        pr.tempo_map.set_tempo(imbolc_types::TempoEvent {
+            tick: 480,
+            bpm: 60.0,
+            ramp: false,
+        });
+
+        let mut tick_acc = 0.0;
+        let mut last_sched: Option<u32> = None;
+
+        // Half a second covers 480 ticks at 120 BPM, the next quarter 120 at 60 BPM
+        do_tick(
+            &mut pr,
+            &mut inst,
+            &mut session,
+            &mut engine,
+            &tx,
+            Duration::from_millis(750),
+            &mut tick_acc,
+            &mut last_sched,
+        );
+        assert!(
+            (599..=600).contains(&pr.playhead),
+            "playhead {}",
+            pr.playhead
+        );
+
+        // Lookahead is measured at the slower tempo
+        let expected_lt = ((engine.schedule_lookahead_secs * 60.0 / 60.0) * 480.0) as u32;
+        let sched = last_sched.unwrap();
+        assert!(sched.abs_diff(pr.playhead + expected_lt) <= 1);
+    }
 }
@@ -0,0 +1,445 @@
+//! Multi-track input take recording.
+//!
+//! Armed instruments are recorded from their source buses, each to its own
+//! file, with every file starting at the same timeline tick. Recording follows
+//! the transport: takes start at the punch-in point (or wherever the playhead
+//! is), and end at the punch-out point, when playback stops, or when the loop
+//! wraps — in which case a new set of takes starts at the loop start, stacking
+//! one take per pass. Finished takes are reported once their files are closed.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::engine::AudioEngine;
+use super::snapshot::PianoRollSnapshot;
+use imbolc_types::InstrumentId;
+
+/// Time allowed for scsynth to flush and close a take file before it is reported
+const FILE_CLOSE_SECS: f64 = 0.6;
+
+/// A take whose file has been written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedTake {
+    pub instrument_id: InstrumentId,
+    pub path: PathBuf,
+    pub start_tick: u32,
+}
+
+/// An armed recording session, alive from record start until record stop.
+#[derive(Debug)]
+struct TakeSession {
+    instruments: Vec<InstrumentId>,
+    /// File name prefix; each take appends `_<instrument>_<pass>.wav`
+    file_prefix: PathBuf,
+    punch_in: Option<u32>,
+    punch_out: Option<u32>,
+    /// Start tick of the takes currently being recorded
+    recording_from: Option<u32>,
+    last_playhead: Option<u32>,
+    /// Takes started so far (numbers the files)
+    pass: u32,
+}
+
+impl TakeSession {
+    fn in_punch_range(&self, tick: u32) -> bool {
+        self.punch_in.is_none_or(|p| tick >= p) && self.punch_out.is_none_or(|p| tick < p)
+    }
+}
+
+/// Runtime state for take recording.
+#[derive(Debug, Default)]
+pub struct TakeRecordState {
+    session: Option<TakeSession>,
+    /// Stopped takes waiting for their files to close
+    closing: Vec<(FinishedTake, Instant)>,
+}
+
+impl TakeRecordState {
+    /// Arm a recording session. Takes start on the next tick the transport
+    /// is inside the punch range.
+    pub fn start(
+        &mut self,
+        engine: &AudioEngine,
+        instruments: Vec<InstrumentId>,
+        file_prefix: PathBuf,
+        punch_in: Option<u32>,
+        punch_out: Option<u32>,
+    ) -> Result<(), String> {
+        if self.session.is_some() {
+            return Err("Already recording takes".to_string());
+        }
+        if !engine.is_running() {
+            return Err("Audio engine not running".to_string());
+        }
+        if engine.is_exporting() {
+            return Err("Cannot record while exporting".to_string());
+        }
+        if instruments.is_empty() {
+            return Err("No instruments to record".to_string());
+        }
+        if let Some(id) = instruments
+            .iter()
+            .find(|id| engine.instrument_source_bus(**id).is_none())
+        {
+            return Err(format!("No audio bus for instrument {}", id));
+        }
+        self.session = Some(TakeSession {
+            instruments,
+            file_prefix,
+            punch_in,
+            punch_out,
+            recording_from: None,
+            last_playhead: None,
+            pass: 0,
+        });
+        Ok(())
+    }
+
+    /// End the session, closing any takes in progress.
+    pub fn stop(&mut self, engine: &mut AudioEngine) {
+        if let Some(mut session) = self.session.take() {
+            let offset = engine.schedule_lookahead_secs;
+            self.finish(engine, &mut session, offset);
+        }
+    }
+
+    /// Whether an instrument belongs to the armed session (its existing
+    /// audio clips are not played back while it records).
+    pub fn is_recording(&self, instrument_id: InstrumentId) -> bool {
+        self.session
+            .as_ref()
+            .is_some_and(|s| s.instruments.contains(&instrument_id))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.session.is_some()
+    }
+
+    fn begin(
+        &mut self,
+        engine: &mut AudioEngine,
+        session: &mut TakeSession,
+        start_tick: u32,
+        offset_secs: f64,
+    ) {
+        session.pass += 1;
+        let takes: Vec<(InstrumentId, PathBuf)> = session
+            .instruments
+            .iter()
+            .map(|id| {
+                let mut name = session.file_prefix.clone().into_os_string();
+                name.push(format!("_{}_{}.wav", id, session.pass));
+                (*id, PathBuf::from(name))
+            })
+            .collect();
+        match engine.start_takes(&takes, offset_secs) {
+            Ok(()) => session.recording_from = Some(start_tick),
+            Err(e) => log::warn!(target: "audio::recording", "Failed to start takes: {}", e),
+        }
+    }
+
+    fn finish(&mut self, engine: &mut AudioEngine, session: &mut TakeSession, offset_secs: f64) {
+        let Some(start_tick) = session.recording_from.take() else {
+            return;
+        };
+        let ready_at = Instant::now() + Duration::from_secs_f64(offset_secs + FILE_CLOSE_SECS);
+        for (instrument_id, path) in engine.stop_takes(offset_secs) {
+            self.closing.push((
+                FinishedTake {
+                    instrument_id,
+                    path,
+                    start_tick,
+                },
+                ready_at,
+            ));
+        }
+    }
+
+    /// Takes whose files have had time to close.
+    fn drain_ready(&mut self) -> Vec<FinishedTake> {
+        let now = Instant::now();
+        let mut ready = Vec::new();
+        self.closing.retain(|(take, at)| {
+            if *at <= now {
+                ready.push(take.clone());
+                false
+            } else {
+                true
+            }
+        });
+        ready
+    }
+}
+
+/// Start and stop takes as the playhead moves, returning takes that are
+/// ready to be placed on the timeline.
+///
+/// Call after the playhead has advanced. An event at `tick` that the playhead
+/// has already passed is scheduled `lookahead - (playhead - tick)` seconds from
+/// now, which is when the audio for that tick reaches the server.
+pub fn tick_take_recording(
+    engine: &mut AudioEngine,
+    piano_roll: &PianoRollSnapshot,
+    state: &mut TakeRecordState,
+) -> Vec<FinishedTake> {
+    let Some(mut session) = state.session.take() else {
+        return state.drain_ready();
+    };
+
+    // The engine drops takes on disconnect
+    if session.recording_from.is_some() && !engine.is_recording_takes() {
+        session.recording_from = None;
+    }
+
+    let lookahead = engine.schedule_lookahead_secs;
+    if !piano_roll.playing || piano_roll.bpm <= 0.0 {
+        state.finish(engine, &mut session, lookahead);
+        session.last_playhead = None;
+        state.session = Some(session);
+        return state.drain_ready();
+    }
+
+    let playhead = piano_roll.playhead;
+    let secs_per_tick =
+        60.0 / (piano_roll.bpm_at(playhead as f64) as f64 * piano_roll.ticks_per_beat as f64);
+    // Seconds from now at which `ticks_ago` ticks behind the playhead is heard
+    let offset = |ticks_ago: u32| (lookahead - ticks_ago as f64 * secs_per_tick).max(0.0);
+
+    // Timeline segments covered since the last tick: (from, to, ticks from `to` to the playhead)
+    let segments: Vec<(u32, u32, u32)> = match session.last_playhead {
+        None => vec![(playhead, playhead, 0)],
+        Some(last) if playhead >= last && playhead - last <= piano_roll.ticks_per_beat => {
+            vec![(last, playhead, 0)]
+        }
+        Some(last)
+            if playhead < last
+                && piano_roll.looping
+                && last <= piano_roll.loop_end
+                && playhead >= piano_roll.loop_start =>
+        {
+            // Loop wrap: the end of the loop, then the start of the next pass
+            let after_wrap = playhead - piano_roll.loop_start;
+            vec![
+                (last, piano_roll.loop_end, after_wrap),
+                (piano_roll.loop_start, playhead, 0),
+            ]
+        }
+        Some(_) => {
+            // Seek: close the current takes and carry on from the new position
+            state.finish(engine, &mut session, lookahead);
+            vec![(playhead, playhead, 0)]
+        }
+    };
+    session.last_playhead = Some(playhead);
+
+    for (index, (from, to, ticks_after)) in segments.into_iter().enumerate() {
+        let ticks_ago = |tick: u32| to.saturating_sub(tick) + ticks_after;
+
+        if index > 0 {
+            // Crossed the loop end: this pass is complete
+            state.finish(engine, &mut session, offset(ticks_after));
+        }
+
+        if session.recording_from.is_some() {
+            if let Some(out) = session.punch_out.filter(|p| *p > from && *p <= to) {
+                state.finish(engine, &mut session, offset(ticks_ago(out)));
+            }
+        }
+
+        if session.recording_from.is_none() && session.in_punch_range(to) {
+            let start = session
+                .punch_in
+                .filter(|p| *p > from && *p <= to)
+                .unwrap_or(if index > 0 { from } else { to });
+            state.begin(engine, &mut session, start, offset(ticks_ago(start)));
+        }
+    }
+
+    state.session = Some(session);
+    state.drain_ready()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::backend::TestOp;
+
+    fn setup(
+        instruments: &[InstrumentId],
+    ) -> (
+        AudioEngine,
+        std::sync::Arc<crate::engine::backend::TestBackend>,
+        PianoRollSnapshot,
+    ) {
+        let (mut engine, backend) = AudioEngine::with_test_backend();
+        for id in instruments {
+            engine.alloc_test_source_bus(*id);
+        }
+        let mut pr = PianoRollSnapshot::new();
+        pr.bpm = 120.0;
+        pr.ticks_per_beat = 480;
+        pr.playing = true;
+        (engine, backend, pr)
+    }
+
+    #[test]
+    fn records_each_instrument_from_a_shared_start() {
+        let ids = [InstrumentId::new(1), InstrumentId::new(2)];
+        let (mut engine, backend, mut pr) = setup(&ids);
+        let mut state = TakeRecordState::default();
+        state
+            .start(
+                &engine,
+                ids.to_vec(),
+                PathBuf::from("/tmp/take"),
+                None,
+                None,
+            )
+            .unwrap();
+
+        pr.playhead = 960;
+        assert!(tick_take_recording(&mut engine, &pr, &mut state).is_empty());
+        assert!(engine.is_recording_takes());
+        // Both DiskOuts start together in one timed bundle
+        let timed: Vec<(usize, f64)> = backend
+            .operations()
+            .iter()
+            .filter_map(|op| match op {
+                TestOp::SendBundle {
+                    messages,
+                    offset_secs,
+                } if *offset_secs > 0.0 => Some((
+                    messages.iter().filter(|(addr, _)| addr == "/s_new").count(),
+                    *offset_secs,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(timed, vec![(2, engine.schedule_lookahead_secs)]);
+
+        state.stop(&mut engine);
+        assert!(!engine.is_recording_takes());
+        assert_eq!(state.closing.len(), 2);
+        assert!(state.closing.iter().all(|(t, _)| t.start_tick == 960));
+        assert_eq!(state.closing[0].0.path, PathBuf::from("/tmp/take_1_1.wav"));
+    }
+
+    #[test]
+    fn respects_punch_in_and_out() {
+        let ids = [InstrumentId::new(1)];
+        let (mut engine, _backend, mut pr) = setup(&ids);
+        let mut state = TakeRecordState::default();
+        state
+            .start(
+                &engine,
+                ids.to_vec(),
+                PathBuf::from("/tmp/take"),
+                Some(1000),
+                Some(1200),
+            )
+            .unwrap();
+
+        for playhead in [900, 990, 1005] {
+            pr.playhead = playhead;
+            tick_take_recording(&mut engine, &pr, &mut state);
+            assert_eq!(engine.is_recording_takes(), playhead >= 1000);
+        }
+        let session = state.session.as_ref().unwrap();
+        assert_eq!(session.recording_from, Some(1000));
+
+        pr.playhead = 1210;
+        tick_take_recording(&mut engine, &pr, &mut state);
+        assert!(!engine.is_recording_takes());
+        pr.playhead = 1400;
+        tick_take_recording(&mut engine, &pr, &mut state);
+        assert!(!engine.is_recording_takes());
+        assert_eq!(state.closing.len(), 1);
+        assert_eq!(state.closing[0].0.start_tick, 1000);
+    }
+
+    #[test]
+    fn loop_wrap_stacks_a_new_take_per_pass() {
+        let ids = [InstrumentId::new(1)];
+        let (mut engine, _backend, mut pr) = setup(&ids);
+        pr.looping = true;
+        pr.loop_start = 0;
+        pr.loop_end = 1920;
+        let mut state = TakeRecordState::default();
+        state
+            .start(
+                &engine,
+                ids.to_vec(),
+                PathBuf::from("/tmp/take"),
+                None,
+                None,
+            )
+            .unwrap();
+
+        for playhead in [0, 480, 960, 1440, 1900, 20, 480, 960, 1440, 1910, 10] {
+            pr.playhead = playhead;
+            tick_take_recording(&mut engine, &pr, &mut state);
+        }
+        let starts: Vec<u32> = state.closing.iter().map(|(t, _)| t.start_tick).collect();
+        assert_eq!(starts, vec![0, 0]);
+        assert_eq!(state.session.as_ref().unwrap().recording_from, Some(0));
+        assert_eq!(state.session.as_ref().unwrap().pass, 3);
+
+        state.stop(&mut engine);
+        let paths: Vec<PathBuf> = state.closing.iter().map(|(t, _)| t.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/tmp/take_1_1.wav"),
+                PathBuf::from("/tmp/take_1_2.wav"),
+                PathBuf::from("/tmp/take_1_3.wav"),
+            ]
+        );
+    }
+
+    #[test]
+    fn transport_stop_ends_takes_but_keeps_session_armed() {
+        let ids = [InstrumentId::new(1)];
+        let (mut engine, _backend, mut pr) = setup(&ids);
+        let mut state = TakeRecordState::default();
+        state
+            .start(
+                &engine,
+                ids.to_vec(),
+                PathBuf::from("/tmp/take"),
+                None,
+                None,
+            )
+            .unwrap();
+        pr.playhead = 0;
+        tick_take_recording(&mut engine, &pr, &mut state);
+
+        pr.playing = false;
+        tick_take_recording(&mut engine, &pr, &mut state);
+        assert!(!engine.is_recording_takes());
+        assert!(state.is_active());
+        assert!(state.is_recording(InstrumentId::new(1)));
+
+        pr.playing = true;
+        pr.playhead = 480;
+        tick_take_recording(&mut engine, &pr, &mut state);
+        assert!(engine.is_recording_takes());
+        assert_eq!(state.session.as_ref().unwrap().recording_from, Some(480));
+    }
+
+    #[test]
+    fn start_requires_instrument_buses() {
+        let (engine, _backend, _pr) = setup(&[]);
+        let mut state = TakeRecordState::default();
+        let err = state
+            .start(
+                &engine,
+                vec![InstrumentId::new(7)],
+                PathBuf::from("/tmp/take"),
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(err.contains("No audio bus"));
+        assert!(!state.is_active());
+    }
+}
@@ -0,0 +1,326 @@
+//! Tempo detection for imported loops.
+//!
+//! A tempo written into the file name (`break_128bpm.wav`, `bpm90 kit.wav`)
+//! wins. Otherwise the tempo is estimated from the audio: an onset-strength
+//! envelope is autocorrelated over the 60–200 BPM range, weighted towards
+//! moderate tempos to settle double/half-time ambiguity. Short files that
+//! come out close to a whole number of bars are snapped to it, since loops
+//! are cut to bar lengths and that figure is more exact than the estimate.
+
+use std::path::Path;
+
+/// Tempo range accepted from file names
+const NAME_MIN_BPM: f32 = 40.0;
+const NAME_MAX_BPM: f32 = 300.0;
+
+/// Tempo range searched by the onset estimate
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+/// Resolution of the tempo search
+const BPM_STEP: f32 = 0.1;
+/// Tempo the search is weighted towards, and the weighting width in octaves
+const PRIOR_BPM: f32 = 120.0;
+const PRIOR_OCTAVES: f32 = 1.0;
+
+/// Onset envelope frame length in seconds
+const HOP_SECS: f32 = 0.005;
+/// Beat multiples whose lags are combined into each tempo's score
+const COMB_BEATS: usize = 4;
+/// Audio analysed from the start of the file
+const MAX_ANALYSIS_SECS: f32 = 60.0;
+
+/// Files up to this long are treated as loops and snapped to whole bars
+const MAX_LOOP_SECS: f32 = 30.0;
+/// Largest relative distance to a whole-bar tempo that is snapped
+const LOOP_SNAP_TOLERANCE: f32 = 0.02;
+const BEATS_PER_BAR: f32 = 4.0;
+
+/// Tempo of a WAV file, from a `bpm` tag in its name or else from its onsets.
+pub fn detect_tempo(path: &Path) -> Option<f32> {
+    if let Some(bpm) = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(bpm_from_name)
+    {
+        return Some(bpm);
+    }
+    let (samples, sample_rate) = read_mono(path).ok()?;
+    estimate_bpm(&samples, sample_rate)
+}
+
+/// Tempo tagged in a file name: a number directly before or after `bpm`,
+/// optionally separated by `_`, `-` or a space (`loop_128bpm`, `BPM-92_kit`).
+pub fn bpm_from_name(name: &str) -> Option<f32> {
+    let lower = name.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(found) = lower[search..].find("bpm") {
+        let at = search + found;
+        let before = number_ending_at(&lower[..at]);
+        let after = number_starting_at(&lower[at + 3..]);
+        if let Some(bpm) = [before, after]
+            .into_iter()
+            .flatten()
+            .find(|bpm| (NAME_MIN_BPM..=NAME_MAX_BPM).contains(bpm))
+        {
+            return Some(bpm);
+        }
+        search = at + 3;
+    }
+    None
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '_' | '-' | ' ')
+}
+
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_digit() || c == '.'
+}
+
+fn number_ending_at(s: &str) -> Option<f32> {
+    let s = s.strip_suffix(is_separator).unwrap_or(s);
+    let start = s.rfind(|c: char| !is_number_char(c)).map_or(0, |i| i + 1);
+    s[start..].trim_matches('.').parse().ok()
+}
+
+fn number_starting_at(s: &str) -> Option<f32> {
+    let s = s.strip_prefix(is_separator).unwrap_or(s);
+    let end = s.find(|c: char| !is_number_char(c)).unwrap_or(s.len());
+    s[..end].trim_matches('.').parse().ok()
+}
+
+/// Estimate the tempo of mono audio from its onsets. `None` when the audio
+/// is too short to hold two beats at the slowest tempo, or has no onsets.
+pub fn estimate_bpm(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if sample_rate == 0 {
+        return None;
+    }
+    let hop = ((sample_rate as f32 * HOP_SECS) as usize).max(1);
+    let frame_rate = sample_rate as f32 / hop as f32;
+    let envelope = onset_envelope(samples, hop);
+
+    let max_lag = 60.0 * frame_rate / MIN_BPM;
+    if (envelope.len() as f32) < 2.0 * max_lag + 2.0 {
+        return None;
+    }
+    if envelope.iter().all(|&v| v == 0.0) {
+        return None;
+    }
+
+    let mean = envelope.iter().sum::<f32>() / envelope.len() as f32;
+    let centered: Vec<f32> = envelope.iter().map(|v| v - mean).collect();
+
+    let steps = ((MAX_BPM - MIN_BPM) / BPM_STEP).round() as usize;
+    let mut best: Option<(f32, f32)> = None;
+    for step in 0..=steps {
+        let bpm = MIN_BPM + step as f32 * BPM_STEP;
+        let lag = 60.0 * frame_rate / bpm;
+        let prior = (-0.5 * ((bpm / PRIOR_BPM).log2() / PRIOR_OCTAVES).powi(2)).exp();
+        let comb: f32 = (1..=COMB_BEATS)
+            .map(|k| autocorrelation(&centered, lag * k as f32))
+            .sum();
+        let score = comb * prior;
+        if best.is_none_or(|(_, s)| score > s) {
+            best = Some((bpm, score));
+        }
+    }
+    let (bpm, score) = best?;
+    if score <= 0.0 {
+        return None;
+    }
+
+    let duration_secs = samples.len() as f32 / sample_rate as f32;
+    let bpm = snap_to_bars(bpm, duration_secs);
+    Some((bpm * 10.0).round() / 10.0)
+}
+
+/// Smoothed, log-compressed rises in frame energy.
+fn onset_envelope(samples: &[f32], hop: usize) -> Vec<f32> {
+    let levels: Vec<f32> = samples
+        .chunks(hop)
+        .map(|frame| {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+            (1.0 + 1000.0 * rms).ln()
+        })
+        .collect();
+    let rises: Vec<f32> = std::iter::once(0.0)
+        .chain(levels.windows(2).map(|w| (w[1] - w[0]).max(0.0)))
+        .collect();
+    // A short blur lets onsets that land a frame or two apart still correlate
+    const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+    (0..rises.len())
+        .map(|i| {
+            KERNEL
+                .iter()
+                .enumerate()
+                .filter_map(|(k, w)| {
+                    (i + k)
+                        .checked_sub(2)
+                        .and_then(|j| rises.get(j))
+                        .map(|r| r * w)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Mean product of the signal with itself shifted by a fractional `lag`.
+fn autocorrelation(signal: &[f32], lag: f32) -> f32 {
+    let whole = lag.floor() as usize;
+    let frac = lag - whole as f32;
+    if whole + 1 >= signal.len() {
+        return 0.0;
+    }
+    let count = signal.len() - whole - 1;
+    let sum: f32 = (0..count)
+        .map(|i| {
+            let shifted = signal[i + whole] * (1.0 - frac) + signal[i + whole + 1] * frac;
+            signal[i] * shifted
+        })
+        .sum();
+    sum / count as f32
+}
+
+/// Snap to the tempo that fits a whole number of bars into a loop-length file.
+fn snap_to_bars(bpm: f32, duration_secs: f32) -> f32 {
+    if duration_secs <= 0.0 || duration_secs > MAX_LOOP_SECS {
+        return bpm;
+    }
+    let bars = (bpm * duration_secs / 60.0 / BEATS_PER_BAR).round();
+    if bars < 1.0 {
+        return bpm;
+    }
+    let snapped = bars * BEATS_PER_BAR * 60.0 / duration_secs;
+    if ((snapped - bpm) / bpm).abs() <= LOOP_SNAP_TOLERANCE {
+        snapped
+    } else {
+        bpm
+    }
+}
+
+/// First `MAX_ANALYSIS_SECS` of a WAV file, mixed down to mono.
+fn read_mono(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let limit = (MAX_ANALYSIS_SECS * spec.sample_rate as f32) as usize * channels;
+    let samples: Result<Vec<f32>, hound::Error> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().take(limit).collect(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .take(limit)
+                .map(|s| s.map(|v| v as f32 * scale))
+                .collect()
+        }
+    };
+    let samples = samples.map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mono = samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 44100;
+
+    /// Decaying 1 kHz blips every beat (with quieter off-beats if `eighths`).
+    fn click_track(bpm: f32, beats: usize, eighths: bool) -> Vec<f32> {
+        let beat_len = 60.0 / bpm * RATE as f32;
+        let len = (beats as f32 * beat_len).round() as usize;
+        let mut out = vec![0.0; len];
+        let blip_len = (0.03 * RATE as f32) as usize;
+        let mut place = |start: usize, gain: f32| {
+            for i in 0..blip_len {
+                if let Some(s) = out.get_mut(start + i) {
+                    let t = i as f32 / RATE as f32;
+                    *s += gain * (-t * 150.0).exp() * (std::f32::consts::TAU * 1000.0 * t).sin();
+                }
+            }
+        };
+        for beat in 0..beats {
+            let start = beat as f32 * beat_len;
+            place(start.round() as usize, 0.8);
+            if eighths {
+                place((start + beat_len / 2.0).round() as usize, 0.3);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn name_tags_before_and_after() {
+        assert_eq!(bpm_from_name("amen_break_136bpm"), Some(136.0));
+        assert_eq!(bpm_from_name("Drums 92 BPM"), Some(92.0));
+        assert_eq!(bpm_from_name("BPM-174_jungle"), Some(174.0));
+        assert_eq!(bpm_from_name("pad_87.5bpm"), Some(87.5));
+        assert_eq!(bpm_from_name("loop_bpm"), None);
+        assert_eq!(bpm_from_name("kick_01"), None);
+        // Out of range numbers are not tempos
+        assert_eq!(bpm_from_name("take_2bpm_120bpm"), Some(120.0));
+    }
+
+    #[test]
+    fn estimates_click_tempo() {
+        for bpm in [90.0, 100.0, 128.0, 160.0] {
+            let detected = estimate_bpm(&click_track(bpm, 16, false), RATE).unwrap();
+            assert!(
+                (detected - bpm).abs() < 0.2,
+                "{} detected as {}",
+                bpm,
+                detected
+            );
+        }
+    }
+
+    #[test]
+    fn off_beats_do_not_double_tempo() {
+        let detected = estimate_bpm(&click_track(100.0, 16, true), RATE).unwrap();
+        assert!((detected - 100.0).abs() < 0.2, "detected {}", detected);
+    }
+
+    #[test]
+    fn long_files_are_not_snapped() {
+        // 40 seconds at 97 BPM: whole bars would put it at 96 or 102
+        let detected = estimate_bpm(&click_track(97.0, 65, false), RATE).unwrap();
+        assert!((detected - 97.0).abs() < 1.0, "detected {}", detected);
+    }
+
+    #[test]
+    fn silence_and_short_audio_have_no_tempo() {
+        assert_eq!(estimate_bpm(&vec![0.0; RATE as usize * 4], RATE), None);
+        assert_eq!(estimate_bpm(&click_track(120.0, 2, false), RATE), None);
+    }
+
+    #[test]
+    fn detects_from_file_name_then_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let track = click_track(110.0, 8, false);
+        let write = |name: &str| {
+            let path = dir.path().join(name);
+            let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+            for s in &track {
+                let v = (s * i16::MAX as f32) as i16;
+                writer.write_sample(v).unwrap();
+                writer.write_sample(v).unwrap();
+            }
+            writer.finalize().unwrap();
+            path
+        };
+        assert_eq!(detect_tempo(&write("groove_140bpm.wav")), Some(140.0));
+        let detected = detect_tempo(&write("groove.wav")).unwrap();
+        assert!((detected - 110.0).abs() < 0.2, "detected {}", detected);
+    }
+}
@@ -0,0 +1,18 @@
+//! Fixtures shared by unit tests across modules.
+
+use std::path::Path;
+
+/// Write a silent 16-bit, 48 kHz WAV file.
+pub(crate) fn write_silent_wav(path: &Path, channels: u16, secs: f32) {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate: 48000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for _ in 0..(48000.0 * secs) as usize * channels as usize {
+        writer.write_sample(0i16).unwrap();
+    }
+    writer.finalize().unwrap();
+}
@@ -14,9 +14,9 @@ pub use imbolc_types::{
     Action, ArrangementAction, AudioEffect, AudioFeedback, AutomationAction, BusAction,
     ChopperAction, ClickAction, DispatchResult, DomainAction, EqParamKind, FileSelectAction,
     FilterParamKind, GenerativeAction, InstrumentAction, InstrumentUpdate, LayerGroupAction,
-    LfoParamKind, MidiAction, MixerAction, NavAction, NavIntent, PaneId, PianoRollAction,
-    RoutedAction, SequencerAction, ServerAction, SessionAction, StatusEvent, ToggleResult,
-    TunerAction, UiAction, VstParamAction, VstTarget,
+    LfoParamKind, MidiAction, MidiImportTarget, MixerAction, NavAction, NavIntent, PaneId,
+    PianoRollAction, RoutedAction, SequencerAction, ServerAction, SessionAction, StatusEvent,
+    ToggleResult, TunerAction, UiAction, VstParamAction, VstTarget,
 };
 
 /// Feedback from async I/O operations to the main thread.
@@ -1,3 +1,4 @@
+use super::helpers::add_audio_clip_from_file;
 use crate::action::{ArrangementAction, AudioEffect, DispatchResult, NavIntent, PaneId};
 use crate::state::arrangement::{ClipEditContext, PlayMode};
 use crate::state::AppState;
@@ -193,6 +194,205 @@ pub(super) fn dispatch_arrangement(
             result.audio_effects.push(AudioEffect::UpdateAutomation);
             result
         }
+        ArrangementAction::ImportAudioClip {
+            instrument_id,
+            path,
+            start_tick,
+        } => {
+            let mut result = DispatchResult::none();
+            result.push_nav(NavIntent::Pop);
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Audio".to_string());
+            match add_audio_clip_from_file(state, *instrument_id, path, *start_tick, name) {
+                Ok(_) => {
+                    result.push_status(audio.status(), format!("Imported {}", path.display()));
+                    result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+                }
+                Err(e) => {
+                    result.push_status(audio.status(), format!("Audio import failed: {}", e));
+                }
+            }
+            result
+        }
+        ArrangementAction::MoveAudioClip {
+            clip_id,
+            new_start_tick,
+        } => {
+            state
+                .session
+                .arrangement
+                .move_audio_clip(*clip_id, *new_start_tick);
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::DuplicateAudioClip(clip_id) => {
+            state.session.arrangement.duplicate_audio_clip(*clip_id);
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::DeleteAudioClip(clip_id) => {
+            state.session.arrangement.remove_audio_clip(*clip_id);
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::CycleTake {
+            instrument_id,
+            tick,
+        } => {
+            let arr = &mut state.session.arrangement;
+            let Some(next) = arr.next_take_at(*instrument_id, *tick) else {
+                return DispatchResult::none();
+            };
+            arr.solo_take(next);
+            let mut result = DispatchResult::none();
+            if let Some(clip) = arr.audio_clip(next) {
+                result.push_status(audio.status(), format!("Playing {}", clip.name));
+            }
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::SetTempoEvent(event) => {
+            state.session.piano_roll.tempo_map.set_tempo(*event);
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::RemoveTempoEvent(tick) => {
+            if !state.session.piano_roll.tempo_map.remove_tempo(*tick) {
+                return DispatchResult::none();
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::SetMeterChange(change) => {
+            state.session.piano_roll.tempo_map.set_meter(*change);
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::RemoveMeterChange(bar) => {
+            if !state.session.piano_roll.tempo_map.remove_meter(*bar) {
+                return DispatchResult::none();
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::AddMarker { name, tick } => {
+            state
+                .session
+                .arrangement
+                .add_marker(name.clone(), *tick, None);
+            DispatchResult::none()
+        }
+        ArrangementAction::AddSection {
+            name,
+            start_tick,
+            end_tick,
+        } => {
+            state
+                .session
+                .arrangement
+                .add_marker(name.clone(), *start_tick, Some(*end_tick));
+            DispatchResult::none()
+        }
+        ArrangementAction::RenameMarker(id, name) => {
+            if let Some(marker) = state.session.arrangement.marker_mut(*id) {
+                marker.name = name.clone();
+            }
+            DispatchResult::none()
+        }
+        ArrangementAction::RemoveMarker(id) => {
+            state.session.arrangement.remove_marker(*id);
+            DispatchResult::none()
+        }
+        ArrangementAction::JumpToMarker(id) => {
+            let arr = &mut state.session.arrangement;
+            let Some(marker) = arr.marker(*id) else {
+                return DispatchResult::none();
+            };
+            let tick = marker.tick;
+            let status = format!("Jumped to {}", marker.name);
+            arr.cursor_tick = tick;
+            arr.view_start_tick = tick;
+            state.audio.playhead = tick;
+            audio.set_playhead(tick);
+            let mut result = DispatchResult::none();
+            result.push_status(audio.status(), status);
+            result
+        }
+        ArrangementAction::LoopSection(id) => {
+            let arr = &state.session.arrangement;
+            let (Some(marker), Some((start, end))) = (arr.marker(*id), arr.section_range(*id))
+            else {
+                return DispatchResult::none();
+            };
+            let status = format!("Looping {}", marker.name);
+            let pr = &mut state.session.piano_roll;
+            pr.loop_start = start;
+            pr.loop_end = end;
+            pr.looping = true;
+            let mut result = DispatchResult::none();
+            result.push_status(audio.status(), status);
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
+        ArrangementAction::DuplicateSection(id) => {
+            let Some((start, end)) = state.session.arrangement.section_range(*id) else {
+                return DispatchResult::none();
+            };
+            state.session.arrangement.duplicate_time(start, end);
+            state.session.automation.duplicate_time(start, end);
+            state.session.piano_roll.duplicate_time(start, end);
+            timeline_edited()
+        }
+        ArrangementAction::InsertSection {
+            name,
+            start_tick,
+            length_ticks,
+        } => {
+            // Reject empty sections and ones that would run past the end of the timeline
+            let Some(end_tick) = start_tick
+                .checked_add(*length_ticks)
+                .filter(|_| *length_ticks > 0)
+            else {
+                return DispatchResult::none();
+            };
+            state
+                .session
+                .arrangement
+                .insert_time(*start_tick, *length_ticks);
+            state
+                .session
+                .automation
+                .insert_time(*start_tick, *length_ticks);
+            state
+                .session
+                .piano_roll
+                .insert_time(*start_tick, *length_ticks);
+            state
+                .session
+                .arrangement
+                .add_marker(name.clone(), *start_tick, Some(end_tick));
+            timeline_edited()
+        }
+        ArrangementAction::DeleteSection(id) => {
+            let Some((start, end)) = state.session.arrangement.section_range(*id) else {
+                return DispatchResult::none();
+            };
+            let arr = &mut state.session.arrangement;
+            arr.remove_marker(*id);
+            arr.delete_time(start, end);
+            state.session.automation.delete_time(start, end);
+            state.session.piano_roll.delete_time(start, end);
+            timeline_edited()
+        }
         ArrangementAction::SelectPlacement(selection) => {
             state.session.arrangement.selected_placement = *selection;
             DispatchResult::none()
@@ -383,3 +583,342 @@ pub(super) fn dispatch_arrangement(
         }
     }
 }
+
+/// Result for a structural edit that moved content on every timeline lane.
+fn timeline_edited() -> DispatchResult {
+    let mut result = DispatchResult::none();
+    result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+    result.audio_effects.push(AudioEffect::UpdateAutomation);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::audio_feedback::dispatch_audio_feedback;
+    use super::*;
+    use crate::state::SourceType;
+    use imbolc_audio::commands::AudioFeedback;
+    use std::path::{Path, PathBuf};
+
+    /// One second of 44.1 kHz mono audio with a ramp so the peaks are non-zero.
+    fn write_wav(dir: &Path) -> PathBuf {
+        let path = dir.join("take.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..44100 {
+            writer.write_sample((i % 1000) as i16 * 20).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn import_audio_clip_converts_length_to_ticks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_wav(dir.path());
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let inst = state.add_instrument(SourceType::AudioIn);
+        state.session.piano_roll.bpm = 120.0;
+
+        let action = ArrangementAction::ImportAudioClip {
+            instrument_id: inst,
+            path: path.clone(),
+            start_tick: 960,
+        };
+        let result = dispatch_arrangement(&action, &mut state, &mut audio);
+        assert!(result.audio_effects.contains(&AudioEffect::UpdatePianoRoll));
+
+        let arr = &state.session.arrangement;
+        assert_eq!(arr.audio_clips.len(), 1);
+        let clip = &arr.audio_clips[0];
+        assert_eq!(clip.name, "take");
+        assert_eq!(clip.path, path);
+        assert_eq!(clip.instrument_id, inst);
+        assert_eq!(clip.start_tick, 960);
+        assert_eq!(clip.sample_rate, 44100);
+        assert!((clip.length_secs - 1.0).abs() < 1e-4);
+        // One second at 120 BPM is two beats
+        let tpb = state.session.piano_roll.ticks_per_beat;
+        assert_eq!(clip.duration_ticks, 2 * tpb);
+        assert!(!clip.waveform_peaks.is_empty());
+    }
+
+    #[test]
+    fn import_missing_file_adds_nothing() {
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let inst = state.add_instrument(SourceType::AudioIn);
+
+        let action = ArrangementAction::ImportAudioClip {
+            instrument_id: inst,
+            path: PathBuf::from("/nonexistent/take.wav"),
+            start_tick: 0,
+        };
+        let result = dispatch_arrangement(&action, &mut state, &mut audio);
+        assert!(result.audio_effects.is_empty());
+        assert!(state.session.arrangement.audio_clips.is_empty());
+    }
+
+    #[test]
+    fn move_duplicate_delete_audio_clip() {
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let inst = state.add_instrument(SourceType::AudioIn);
+        let id = state.session.arrangement.add_audio_clip(
+            "Take 1".to_string(),
+            PathBuf::from("take.wav"),
+            inst,
+            0,
+            480,
+        );
+
+        let action = ArrangementAction::MoveAudioClip {
+            clip_id: id,
+            new_start_tick: 240,
+        };
+        let _ = dispatch_arrangement(&action, &mut state, &mut audio);
+        assert_eq!(
+            state.session.arrangement.audio_clip(id).unwrap().start_tick,
+            240
+        );
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::DuplicateAudioClip(id),
+            &mut state,
+            &mut audio,
+        );
+        let starts: Vec<u32> = state
+            .session
+            .arrangement
+            .audio_clips
+            .iter()
+            .map(|c| c.start_tick)
+            .collect();
+        assert_eq!(starts, vec![240, 720]);
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::DeleteAudioClip(id),
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(state.session.arrangement.audio_clips.len(), 1);
+        assert!(state.session.arrangement.audio_clip(id).is_none());
+    }
+
+    #[test]
+    fn take_recorded_feedback_stacks_takes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_wav(dir.path());
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let inst = state.add_instrument(SourceType::AudioIn);
+
+        for _ in 0..2 {
+            let feedback = AudioFeedback::TakeRecorded {
+                instrument_id: inst,
+                path: path.clone(),
+                start_tick: 480,
+            };
+            let result = dispatch_audio_feedback(&feedback, &mut state, &mut audio);
+            assert!(result.audio_effects.contains(&AudioEffect::UpdatePianoRoll));
+        }
+
+        let arr = &state.session.arrangement;
+        let takes: Vec<(&str, u32, bool)> = arr
+            .audio_clips
+            .iter()
+            .map(|c| (c.name.as_str(), c.start_tick, c.muted))
+            .collect();
+        assert_eq!(takes, vec![("Take 1", 480, true), ("Take 2", 480, false)]);
+
+        // Cycling brings the first take back
+        let _ = dispatch_arrangement(
+            &ArrangementAction::CycleTake {
+                instrument_id: inst,
+                tick: 500,
+            },
+            &mut state,
+            &mut audio,
+        );
+        let muted: Vec<bool> = state
+            .session
+            .arrangement
+            .audio_clips
+            .iter()
+            .map(|c| c.muted)
+            .collect();
+        assert_eq!(muted, vec![false, true]);
+    }
+
+    #[test]
+    fn tempo_and_meter_changes_update_piano_roll() {
+        use imbolc_types::{MeterChange, TempoEvent};
+
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let event = TempoEvent {
+            tick: 1920,
+            bpm: 90.0,
+            ramp: true,
+        };
+        let result = dispatch_arrangement(
+            &ArrangementAction::SetTempoEvent(event),
+            &mut state,
+            &mut audio,
+        );
+        assert!(result.audio_effects.contains(&AudioEffect::UpdatePianoRoll));
+        let _ = dispatch_arrangement(
+            &ArrangementAction::SetMeterChange(MeterChange {
+                bar: 1,
+                time_signature: (3, 4),
+            }),
+            &mut state,
+            &mut audio,
+        );
+        let map = &state.session.piano_roll.tempo_map;
+        assert_eq!(map.tempos, vec![event]);
+        assert_eq!(map.time_signature_at_bar((4, 4), 3), (3, 4));
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::RemoveTempoEvent(1920),
+            &mut state,
+            &mut audio,
+        );
+        let result = dispatch_arrangement(
+            &ArrangementAction::RemoveMeterChange(5),
+            &mut state,
+            &mut audio,
+        );
+        assert!(result.audio_effects.is_empty());
+        assert!(state.session.piano_roll.tempo_map.tempos.is_empty());
+        assert_eq!(state.session.piano_roll.tempo_map.meters.len(), 1);
+    }
+
+    #[test]
+    fn section_operations_shift_every_lane() {
+        use imbolc_types::{AutomationTarget, InstrumentId, TempoEvent};
+
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let inst = InstrumentId::new(1);
+        let arr = &mut state.session.arrangement;
+        let clip = arr.add_clip("Riff".to_string(), inst, 1920);
+        arr.add_placement(clip, inst, 0);
+        let late = arr.add_placement(clip, inst, 3840);
+        let lane = state
+            .session
+            .automation
+            .add_lane(AutomationTarget::level(inst));
+        state
+            .session
+            .automation
+            .lane_mut(lane)
+            .unwrap()
+            .add_point(3840, 0.5);
+        state.session.piano_roll.tempo_map.set_tempo(TempoEvent {
+            tick: 3840,
+            bpm: 100.0,
+            ramp: false,
+        });
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::AddSection {
+                name: "Verse".to_string(),
+                start_tick: 0,
+                end_tick: 1920,
+            },
+            &mut state,
+            &mut audio,
+        );
+        let verse = state.session.arrangement.markers[0].id;
+
+        let result = dispatch_arrangement(
+            &ArrangementAction::DuplicateSection(verse),
+            &mut state,
+            &mut audio,
+        );
+        assert!(result
+            .audio_effects
+            .contains(&AudioEffect::UpdateAutomation));
+        let start_of = |state: &AppState, id| {
+            state
+                .session
+                .arrangement
+                .placements
+                .iter()
+                .find(|p| p.id == id)
+                .unwrap()
+                .start_tick
+        };
+        assert_eq!(state.session.arrangement.placements.len(), 3);
+        assert_eq!(start_of(&state, late), 5760);
+        assert_eq!(state.session.automation.lanes[0].points[0].tick, 5760);
+        assert_eq!(state.session.piano_roll.tempo_map.tempos[0].tick, 5760);
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::InsertSection {
+                name: "Break".to_string(),
+                start_tick: 1920,
+                length_ticks: 960,
+            },
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(start_of(&state, late), 6720);
+        let names: Vec<&str> = state
+            .session
+            .arrangement
+            .markers
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Verse", "Break", "Verse"]);
+
+        // A section running past the end of the timeline is rejected
+        let _ = dispatch_arrangement(
+            &ArrangementAction::InsertSection {
+                name: "Overflow".to_string(),
+                start_tick: u32::MAX - 10,
+                length_ticks: 960,
+            },
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(state.session.arrangement.markers.len(), 3);
+        assert_eq!(start_of(&state, late), 6720);
+
+        let break_id = state.session.arrangement.markers[1].id;
+        let _ = dispatch_arrangement(
+            &ArrangementAction::LoopSection(break_id),
+            &mut state,
+            &mut audio,
+        );
+        let pr = &state.session.piano_roll;
+        assert_eq!((pr.loop_start, pr.loop_end, pr.looping), (1920, 2880, true));
+
+        let _ = dispatch_arrangement(
+            &ArrangementAction::DeleteSection(break_id),
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(start_of(&state, late), 5760);
+        assert_eq!(state.session.automation.lanes[0].points[0].tick, 5760);
+        assert_eq!(state.session.piano_roll.tempo_map.tempos[0].tick, 5760);
+        assert_eq!(state.session.arrangement.markers.len(), 2);
+
+        let second_verse = state.session.arrangement.markers[1].id;
+        let _ = dispatch_arrangement(
+            &ArrangementAction::JumpToMarker(second_verse),
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(state.session.arrangement.cursor_tick, 1920);
+        assert_eq!(state.audio.playhead, 1920);
+    }
+}
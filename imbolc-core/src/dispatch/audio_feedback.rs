@@ -2,7 +2,8 @@ use crate::action::{AudioEffect, DispatchResult, NavIntent, PaneId, VstTarget};
 use crate::state::AppState;
 use imbolc_audio::commands::AudioFeedback;
 use imbolc_audio::AudioHandle;
-use imbolc_types::SourceExtra;
+use imbolc_types::{FrozenRender, InstrumentId, SourceExtra};
+use std::path::Path;
 
 pub fn dispatch_audio_feedback(
     feedback: &AudioFeedback,
@@ -13,13 +14,22 @@ pub fn dispatch_audio_feedback(
 
     match feedback {
         AudioFeedback::PlayheadPosition(playhead) => {
+            if super::note_take::advance_note_take(state, *playhead) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
             state.audio.playhead = *playhead;
         }
         AudioFeedback::BpmUpdate(bpm) => {
             state.audio.bpm = *bpm;
         }
         AudioFeedback::PlayingChanged(playing) => {
+            // Transport stopped from elsewhere (e.g. MIDI clock): end the take
+            if !*playing && super::note_take::finish_note_take(state) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
             state.audio.playing = *playing;
+            // Transport may also be driven by incoming MIDI clock
+            state.session.piano_roll.playing = *playing;
         }
         AudioFeedback::DrumSequencerStep {
             instrument_id,
@@ -52,17 +62,33 @@ pub fn dispatch_audio_feedback(
         AudioFeedback::RenderComplete {
             instrument_id,
             path,
+            lead_in_secs,
         } => {
             // Stop playback and restore looping
             state.session.piano_roll.playing = false;
             state.audio.playing = false;
             state.audio.playhead = 0;
-            if let Some(render) = state.io.pending_render.take() {
-                state.session.piano_roll.looping = render.was_looping;
-            }
+            let freeze = match state.io.pending_render.take() {
+                Some(render) => {
+                    state.session.piano_roll.looping = render.was_looping;
+                    render.freeze
+                }
+                None => false,
+            };
             result.stop_playback = true;
             result.reset_playhead = true;
 
+            if freeze {
+                match freeze_instrument(state, *instrument_id, path, *lead_in_secs) {
+                    Ok(()) => result.push_status(audio.status(), "Instrument frozen".to_string()),
+                    Err(e) => result.push_status(audio.status(), e),
+                }
+                result
+                    .audio_effects
+                    .push(AudioEffect::RebuildRoutingForInstrument(*instrument_id));
+                return result;
+            }
+
             // Convert instrument to PitchedSampler
             let buffer_id = state.instruments.next_sampler_buffer_id;
             state.instruments.next_sampler_buffer_id += 1;
@@ -98,6 +124,38 @@ pub fn dispatch_audio_feedback(
                 }
             }
         }
+        AudioFeedback::TakeRecorded {
+            instrument_id,
+            path,
+            start_tick,
+        } => {
+            let name = format!(
+                "Take {}",
+                state
+                    .session
+                    .arrangement
+                    .audio_clips_for_instrument(*instrument_id)
+                    .len()
+                    + 1
+            );
+            match super::helpers::add_audio_clip_from_file(
+                state,
+                *instrument_id,
+                path,
+                *start_tick,
+                name.clone(),
+            ) {
+                Ok(clip_id) => {
+                    // The newest take plays; earlier takes under it are muted
+                    state.session.arrangement.solo_take(clip_id);
+                    result.push_status(audio.status(), format!("{} added to arrangement", name));
+                    result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+                }
+                Err(e) => {
+                    result.push_status(audio.status(), format!("Take not added: {}", e));
+                }
+            }
+        }
         AudioFeedback::VstParamsDiscovered {
             instrument_id,
             target,
@@ -142,16 +200,33 @@ pub fn dispatch_audio_feedback(
                 }
             }
         }
+        AudioFeedback::VstLatencyReported {
+            vst_plugin_id,
+            latency_secs,
+        } => {
+            // The audio thread has already re-aligned the graph; keep the mixer display in step
+            if let Some(plugin) = state.session.vst_plugins.get_mut(*vst_plugin_id) {
+                plugin.latency_secs = Some(*latency_secs);
+            }
+        }
         AudioFeedback::ExportComplete { kind, paths } => {
-            state.session.piano_roll.playing = false;
-            state.audio.playing = false;
-            state.audio.playhead = 0;
-            if let Some(export) = state.io.pending_export.take() {
-                state.session.piano_roll.looping = export.was_looping;
+            // Offline renders never started the transport, so leave it be
+            let offline = match state.io.pending_export.take() {
+                Some(export) if export.offline => true,
+                Some(export) => {
+                    state.session.piano_roll.looping = export.was_looping;
+                    false
+                }
+                None => false,
+            };
+            if !offline {
+                state.session.piano_roll.playing = false;
+                state.audio.playing = false;
+                state.audio.playhead = 0;
+                result.stop_playback = true;
+                result.reset_playhead = true;
             }
             state.io.export_progress = 0.0;
-            result.stop_playback = true;
-            result.reset_playhead = true;
 
             let message = match kind {
                 imbolc_audio::commands::ExportKind::MasterBounce => {
@@ -172,6 +247,18 @@ pub fn dispatch_audio_feedback(
         AudioFeedback::ExportProgress { progress } => {
             state.io.export_progress = *progress;
         }
+        AudioFeedback::ExportFailed { message } => {
+            if let Some(export) = state.io.pending_export.take() {
+                if !export.offline {
+                    state.session.piano_roll.looping = export.was_looping;
+                }
+            }
+            state.io.export_progress = 0.0;
+            result.push_status(
+                imbolc_audio::ServerStatus::Error,
+                format!("Export failed: {}", message),
+            );
+        }
         AudioFeedback::VstStateSaved {
             instrument_id,
             target,
@@ -212,15 +299,17 @@ pub fn dispatch_audio_feedback(
             tick,
         } => {
             if state.session.generative.capture_enabled {
-                state.session.generative.captured_events.push(
-                    imbolc_types::CapturedGenEvent {
+                state
+                    .session
+                    .generative
+                    .captured_events
+                    .push(imbolc_types::CapturedGenEvent {
                         instrument_id: *instrument_id,
                         pitch: *pitch,
                         velocity: *velocity,
                         duration_ticks: *duration_ticks,
                         tick: *tick,
-                    },
-                );
+                    });
             }
         }
         AudioFeedback::TuningDrift(drift) => {
@@ -245,7 +334,48 @@ pub fn dispatch_audio_feedback(
                 avg_tick_us, max_tick_us, p95_tick_us, overruns, schedule_lookahead_ms, osc_send_queue_depth
             );
         }
+        AudioFeedback::ChannelMeters(readings) => {
+            state.audio.visualization.update_channel_meters(readings);
+        }
     }
 
     result
 }
+
+/// Swap an instrument's live chain for its finished freeze render. The render
+/// covers the loop range plus the release tail recorded after it.
+fn freeze_instrument(
+    state: &mut AppState,
+    instrument_id: InstrumentId,
+    path: &Path,
+    lead_in_secs: f64,
+) -> Result<(), String> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let length_secs = reader.duration() as f32 / reader.spec().sample_rate as f32;
+    if length_secs <= 0.0 {
+        return Err(format!("{} contains no audio", path.display()));
+    }
+
+    let pr = &state.session.piano_roll;
+    let start_tick = pr.loop_start;
+    let Some(inst) = state.instruments.instrument_mut(instrument_id) else {
+        return Err("Frozen instrument no longer exists".to_string());
+    };
+    // The render was captured at the end of the chain, so it trails the
+    // timeline by the chain's latency on top of the scheduling lookahead
+    let lead_in_secs =
+        lead_in_secs + inst.processing_latency_secs(&state.session.vst_plugins) as f64;
+    let length_ticks = state.session.piano_roll.ticks_after(
+        start_tick as f64,
+        (length_secs as f64 - lead_in_secs).max(0.0),
+    ) as u32;
+    inst.frozen = Some(FrozenRender {
+        path: path.to_path_buf(),
+        start_tick,
+        end_tick: start_tick + length_ticks,
+        lead_in_secs,
+        length_secs,
+    });
+    Ok(())
+}
@@ -42,7 +42,7 @@ pub fn dispatch_bus(action: &BusAction, state: &mut AppState) -> DispatchResult
 
         BusAction::Rename(_, _) => {}
 
-        BusAction::AddEffect(_, _) => {
+        BusAction::AddEffect(_, _) | BusAction::AddEffectChain(_, _) => {
             result.audio_effects.push(AudioEffect::RebuildBusProcessing);
             result.audio_effects.push(AudioEffect::RebuildSession);
             result.nav.push(NavIntent::Pop);
@@ -63,6 +63,12 @@ pub fn dispatch_bus(action: &BusAction, state: &mut AppState) -> DispatchResult
             result.audio_effects.push(AudioEffect::RebuildSession);
         }
 
+        // Key feeds live on the instrument side of the graph
+        BusAction::SetEffectSidechain(_, _, _) => {
+            result.audio_effects.push(AudioEffect::RebuildRouting);
+            result.audio_effects.push(AudioEffect::RebuildSession);
+        }
+
         BusAction::AdjustEffectParam(bus_id, effect_id, param_idx, _delta) => {
             result.audio_effects.push(AudioEffect::RebuildSession);
             // Read back the param value after reducer mutation for targeted audio update
@@ -104,7 +110,7 @@ pub fn dispatch_layer_group(
     let mut result = DispatchResult::none();
 
     match action {
-        LayerGroupAction::AddEffect(_, _) => {
+        LayerGroupAction::AddEffect(_, _) | LayerGroupAction::AddEffectChain(_, _) => {
             result.audio_effects.push(AudioEffect::RebuildBusProcessing);
             result.audio_effects.push(AudioEffect::RebuildSession);
             result.nav.push(NavIntent::Pop);
@@ -125,6 +131,11 @@ pub fn dispatch_layer_group(
             result.audio_effects.push(AudioEffect::RebuildSession);
         }
 
+        LayerGroupAction::SetEffectSidechain(_, _, _) => {
+            result.audio_effects.push(AudioEffect::RebuildRouting);
+            result.audio_effects.push(AudioEffect::RebuildSession);
+        }
+
         LayerGroupAction::AdjustEffectParam(group_id, effect_id, param_idx, _delta) => {
             result.audio_effects.push(AudioEffect::RebuildSession);
             // Read back the param value after reducer mutation for targeted audio update
@@ -386,6 +397,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bus_set_effect_sidechain_dispatch() {
+        use crate::state::EffectType;
+        use imbolc_types::{SendTapPoint, SidechainSource};
+        let mut state = setup();
+        dispatch_bus(
+            &BusAction::AddEffect(BusId::new(1), EffectType::SidechainComp),
+            &mut state,
+        );
+        let effect_id = state
+            .session
+            .bus(BusId::new(1))
+            .unwrap()
+            .effect_chain
+            .effects[0]
+            .id;
+        let key = SidechainSource::Instrument {
+            id: crate::state::InstrumentId::new(0),
+            tap: SendTapPoint::PostInsert,
+        };
+
+        let result = dispatch_bus(
+            &BusAction::SetEffectSidechain(BusId::new(1), effect_id, Some(key)),
+            &mut state,
+        );
+        assert_eq!(
+            state
+                .session
+                .bus(BusId::new(1))
+                .unwrap()
+                .effect_chain
+                .effects[0]
+                .sidechain,
+            Some(key)
+        );
+        // The key feed sits after the source instrument's chain, so a bus-only rebuild can't add it
+        assert!(result.audio_effects.contains(&AudioEffect::RebuildRouting));
+    }
+
     #[test]
     fn bus_adjust_effect_param_dispatch() {
         use crate::state::EffectType;
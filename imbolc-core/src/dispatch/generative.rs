@@ -50,6 +50,7 @@ pub fn dispatch_generative(
                         duration: event.duration_ticks,
                         velocity: event.velocity,
                         probability: 1.0,
+                        expression: None,
                     });
                 }
             }
@@ -1,6 +1,8 @@
 use crate::action::{AudioEffect, DispatchResult};
+use crate::state::arrangement::AudioClipId;
 use crate::state::automation::AutomationTarget;
 use crate::state::AppState;
+use crate::state::InstrumentId;
 
 use super::automation::record_automation_point;
 
@@ -80,3 +82,42 @@ pub fn compute_waveform_peaks(path: &str) -> (Vec<f32>, f32) {
 
     (peaks, duration_secs)
 }
+
+/// Read a WAV file and add it to the arrangement as an audio clip on an
+/// instrument's lane. The clip length is converted to ticks at the current tempo.
+pub(super) fn add_audio_clip_from_file(
+    state: &mut AppState,
+    instrument_id: InstrumentId,
+    path: &std::path::Path,
+    start_tick: u32,
+    name: String,
+) -> Result<AudioClipId, String> {
+    let spec = hound::WavReader::open(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+        .spec();
+    let (peaks, length_secs) = compute_waveform_peaks(&path.to_string_lossy());
+    if length_secs <= 0.0 {
+        return Err(format!("{} contains no audio", path.display()));
+    }
+
+    let pr = &state.session.piano_roll;
+    let duration_ticks = (pr
+        .ticks_after(start_tick as f64, length_secs as f64)
+        .round() as u32)
+        .max(1);
+
+    let arr = &mut state.session.arrangement;
+    let id = arr.add_audio_clip(
+        name,
+        path.to_path_buf(),
+        instrument_id,
+        start_tick,
+        duration_ticks,
+    );
+    if let Some(clip) = arr.audio_clip_mut(id) {
+        clip.waveform_peaks = peaks;
+        clip.sample_rate = spec.sample_rate;
+        clip.length_secs = length_secs;
+    }
+    Ok(id)
+}
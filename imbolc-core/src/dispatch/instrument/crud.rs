@@ -178,6 +178,9 @@ mod tests {
             amp_envelope: inst.modulation.amp_envelope.clone(),
             polyphonic: inst.polyphonic,
             active: inst.mixer.active,
+            note_input: None,
+            groove: None,
+            mod_matrix: None,
         }
     }
 
@@ -28,6 +28,23 @@ pub(super) fn handle_add_effect(
     result
 }
 
+pub(super) fn handle_add_effect_chain(
+    state: &mut AppState,
+    id: crate::state::InstrumentId,
+    preset: &imbolc_types::EffectChainPreset,
+) -> DispatchResult {
+    reduce(
+        state,
+        &InstrumentAction::AddEffectChain(id, Box::new(preset.clone())),
+    );
+    let mut result = DispatchResult::with_nav(NavIntent::Pop);
+    result.audio_effects.push(AudioEffect::RebuildInstruments);
+    result
+        .audio_effects
+        .push(AudioEffect::RebuildRoutingForInstrument(id));
+    result
+}
+
 pub(super) fn handle_remove_effect(
     state: &mut AppState,
     id: crate::state::InstrumentId,
@@ -53,6 +70,23 @@ pub(super) fn handle_toggle_effect_bypass(
     result
 }
 
+pub(super) fn handle_set_effect_sidechain(
+    state: &mut AppState,
+    id: crate::state::InstrumentId,
+    effect_id: crate::state::EffectId,
+    source: Option<imbolc_types::SidechainSource>,
+) -> DispatchResult {
+    reduce(
+        state,
+        &InstrumentAction::SetEffectSidechain(id, effect_id, source),
+    );
+    let mut result = DispatchResult::none();
+    result.audio_effects.push(AudioEffect::RebuildInstruments);
+    // Reorders instrument chains so the key is computed before this effect
+    result.audio_effects.push(AudioEffect::RebuildRouting);
+    result
+}
+
 pub(super) fn handle_adjust_effect_param(
     state: &mut AppState,
     id: crate::state::InstrumentId,
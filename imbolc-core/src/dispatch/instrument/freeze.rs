@@ -0,0 +1,84 @@
+//! Dispatch handlers for track freeze.
+
+use crate::action::{AudioEffect, DispatchResult};
+use crate::audio::AudioHandle;
+use crate::state::AppState;
+use crate::state::InstrumentId;
+use imbolc_types::{DomainAction, InstrumentAction};
+
+/// Render the instrument's loop range through its full chain; the render
+/// replaces the live chain once `RenderComplete` arrives.
+pub(super) fn handle_freeze(
+    state: &mut AppState,
+    audio: &mut AudioHandle,
+    id: InstrumentId,
+) -> DispatchResult {
+    let Some(instrument) = state.instruments.instrument(id) else {
+        return DispatchResult::none();
+    };
+    if instrument.is_frozen() {
+        return DispatchResult::with_status(imbolc_audio::ServerStatus::Running, "Already frozen");
+    }
+    if state.io.pending_render.is_some() || state.io.pending_export.is_some() {
+        return DispatchResult::with_status(
+            imbolc_audio::ServerStatus::Running,
+            "Already rendering or exporting",
+        );
+    }
+    if !audio.is_running() {
+        return DispatchResult::with_status(
+            imbolc_audio::ServerStatus::Stopped,
+            "Audio engine not running",
+        );
+    }
+
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let render_dir = std::path::Path::new(&home).join(".config/imbolc/renders");
+    let _ = std::fs::create_dir_all(&render_dir);
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = render_dir.join(format!("freeze_{}_{}.wav", id, timestamp));
+
+    let pr = &mut state.session.piano_roll;
+    state.io.pending_render = Some(crate::state::PendingRender {
+        instrument_id: id,
+        path: path.clone(),
+        was_looping: pr.looping,
+        freeze: true,
+    });
+
+    pr.playhead = pr.loop_start;
+    pr.playing = true;
+    state.audio.playing = true;
+    pr.looping = false;
+
+    let _ = audio.start_instrument_render(id, &path);
+
+    let mut result =
+        DispatchResult::with_status(imbolc_audio::ServerStatus::Running, "Freezing...");
+    result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+    result
+}
+
+pub(super) fn handle_unfreeze(state: &mut AppState, id: InstrumentId) -> DispatchResult {
+    if !state
+        .instruments
+        .instrument(id)
+        .is_some_and(|inst| inst.is_frozen())
+    {
+        return DispatchResult::none();
+    }
+    imbolc_types::reduce::reduce_action(
+        &DomainAction::Instrument(InstrumentAction::Unfreeze(id)),
+        &mut state.instruments,
+        &mut state.session,
+    );
+    let mut result =
+        DispatchResult::with_status(imbolc_audio::ServerStatus::Running, "Instrument unfrozen");
+    result
+        .audio_effects
+        .push(AudioEffect::RebuildRoutingForInstrument(id));
+    result
+}
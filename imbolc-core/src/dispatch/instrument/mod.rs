@@ -4,6 +4,7 @@ mod effects;
 mod envelope;
 mod eq;
 mod filter;
+mod freeze;
 mod groove;
 mod layer;
 mod lfo;
@@ -31,6 +32,9 @@ pub(super) fn dispatch_instrument(
         InstrumentAction::PlayNotes(ref pitches, velocity) => {
             playback::handle_play_notes(state, audio, pitches, *velocity)
         }
+        InstrumentAction::NoteExpression(id, pitch, dimension, value) => {
+            playback::handle_note_expression(state, audio, *id, *pitch, *dimension, *value)
+        }
         InstrumentAction::Select(_)
         | InstrumentAction::SelectNext
         | InstrumentAction::SelectPrev
@@ -42,9 +46,15 @@ pub(super) fn dispatch_instrument(
         InstrumentAction::LoadSampleResult(instrument_id, ref path) => {
             sample::handle_load_sample_result(state, audio, *instrument_id, path)
         }
+        InstrumentAction::ToggleSampleWarp(_) | InstrumentAction::AdjustSampleBpm(_, _) => {
+            sample::handle_sample_warp(state, action)
+        }
         InstrumentAction::AddEffect(id, ref effect_type) => {
             effects::handle_add_effect(state, *id, *effect_type)
         }
+        InstrumentAction::AddEffectChain(id, ref preset) => {
+            effects::handle_add_effect_chain(state, *id, preset)
+        }
         InstrumentAction::RemoveEffect(id, effect_id) => {
             effects::handle_remove_effect(state, *id, *effect_id)
         }
@@ -54,6 +64,9 @@ pub(super) fn dispatch_instrument(
         InstrumentAction::ToggleEffectBypass(id, effect_id) => {
             effects::handle_toggle_effect_bypass(state, *id, *effect_id)
         }
+        InstrumentAction::SetEffectSidechain(id, effect_id, source) => {
+            effects::handle_set_effect_sidechain(state, *id, *effect_id, *source)
+        }
         InstrumentAction::ToggleFilter(id) => filter::handle_toggle_filter(state, *id),
         InstrumentAction::CycleFilterType(id) => filter::handle_cycle_filter_type(state, *id),
         InstrumentAction::AdjustFilterCutoff(id, delta) => {
@@ -133,6 +146,19 @@ pub(super) fn dispatch_instrument(
         InstrumentAction::AdjustEnvelopeRelease(id, delta) => {
             envelope::handle_adjust_envelope_release(state, *id, *delta)
         }
+        // Track freeze
+        InstrumentAction::Freeze(id) => freeze::handle_freeze(state, audio, *id),
+        InstrumentAction::Unfreeze(id) => freeze::handle_unfreeze(state, *id),
+        // Live mod matrix controllers
+        InstrumentAction::SetModController(id, controller, value) => {
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::SetModController(
+                *id,
+                *controller,
+                value.clamp(0.0, 1.0),
+            ));
+            result
+        }
         // Channel config
         InstrumentAction::ToggleChannelConfig(id) => {
             handle_toggle_channel_config(state, *id)
@@ -1,6 +1,8 @@
-use crate::action::DispatchResult;
-use crate::state::AppState;
+use crate::action::{AudioEffect, DispatchResult};
+use crate::dispatch::note_take;
+use crate::state::{AppState, InstrumentId};
 use imbolc_audio::AudioHandle;
+use imbolc_types::ExpressionDimension;
 
 pub(super) fn handle_play_note(
     state: &mut AppState,
@@ -80,6 +82,61 @@ pub(super) fn handle_play_notes(
     DispatchResult::none()
 }
 
+/// Live per-note expression: bend, pressure or slide on the held voice of
+/// `pitch` (and its layer siblings). While recording, the value is written
+/// into the note sounding at the playhead.
+pub(super) fn handle_note_expression(
+    state: &mut AppState,
+    audio: &mut AudioHandle,
+    instrument_id: InstrumentId,
+    pitch: u8,
+    dimension: ExpressionDimension,
+    value: f32,
+) -> DispatchResult {
+    let value = match dimension {
+        ExpressionDimension::Bend => value.clamp(-96.0, 96.0),
+        ExpressionDimension::Pressure | ExpressionDimension::Slide => value.clamp(0.0, 1.0),
+    };
+
+    if audio.is_running() {
+        for target_id in state.instruments.layer_group_members(instrument_id) {
+            if let Some(inst) = state.instruments.instrument(target_id) {
+                let pitches = match inst.note_input.chord_shape {
+                    Some(shape) => shape.expand(pitch),
+                    None => vec![pitch],
+                };
+                for p in &pitches {
+                    let _ = audio.set_voice_expression(
+                        target_id,
+                        inst.offset_pitch(*p),
+                        dimension,
+                        value,
+                    );
+                }
+            }
+        }
+    }
+
+    if !(state.session.piano_roll.recording && state.audio.playing) {
+        return DispatchResult::none();
+    }
+    // A key held in a MIDI take keeps its expression until it is written out
+    if note_take::record_take_expression(state, instrument_id, pitch, dimension, value) {
+        return DispatchResult::none();
+    }
+    let playhead = state.audio.playhead;
+    if state
+        .session
+        .piano_roll
+        .record_expression(instrument_id, pitch, playhead, dimension, value)
+    {
+        let mut result = DispatchResult::none();
+        result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+        return result;
+    }
+    DispatchResult::none()
+}
+
 pub(super) fn handle_play_drum_pad(
     state: &AppState,
     audio: &mut AudioHandle,
@@ -1,6 +1,7 @@
 use crate::action::{AudioEffect, DispatchResult, NavIntent};
 use crate::state::AppState;
 use imbolc_audio::AudioHandle;
+use imbolc_types::{DomainAction, InstrumentAction};
 
 pub(super) fn handle_load_sample_result(
     state: &mut AppState,
@@ -10,6 +11,7 @@ pub(super) fn handle_load_sample_result(
 ) -> DispatchResult {
     let path_str = path.to_string_lossy().to_string();
     let sample_name = path.file_stem().map(|s| s.to_string_lossy().to_string());
+    let source_bpm = imbolc_audio::tempo_detect::detect_tempo(path);
 
     let buffer_id = state.instruments.next_sampler_buffer_id;
     state.instruments.next_sampler_buffer_id += 1;
@@ -22,10 +24,29 @@ pub(super) fn handle_load_sample_result(
         if let Some(ref mut config) = instrument.sampler_config_mut() {
             config.buffer_id = Some(buffer_id);
             config.sample_name = sample_name;
+            config.source_bpm = source_bpm;
         }
     }
 
     let mut result = DispatchResult::with_nav(NavIntent::Pop);
     result.audio_effects.push(AudioEffect::RebuildInstruments);
+    if let Some(bpm) = source_bpm {
+        result.push_status(audio.status(), format!("Sample tempo {:.1} BPM", bpm));
+    }
+    result
+}
+
+/// Toggle warp or nudge the sample tempo through the shared reducer.
+pub(super) fn handle_sample_warp(
+    state: &mut AppState,
+    action: &InstrumentAction,
+) -> DispatchResult {
+    imbolc_types::reduce::reduce_action(
+        &DomainAction::Instrument(action.clone()),
+        &mut state.instruments,
+        &mut state.session,
+    );
+    let mut result = DispatchResult::none();
+    result.audio_effects.push(AudioEffect::RebuildInstruments);
     result
 }
@@ -1,8 +1,13 @@
 use crate::action::{DispatchResult, MidiAction};
 use crate::state::midi_recording::MidiCcMapping;
 use crate::state::AppState;
+use imbolc_audio::AudioHandle;
 
-pub(super) fn dispatch_midi(action: &MidiAction, state: &mut AppState) -> DispatchResult {
+pub(super) fn dispatch_midi(
+    action: &MidiAction,
+    state: &mut AppState,
+    audio: &mut AudioHandle,
+) -> DispatchResult {
     match action {
         MidiAction::ConnectPort(_port_index) => {
             // Port connection is intercepted in main.rs (needs MidiInputManager)
@@ -43,5 +48,58 @@ pub(super) fn dispatch_midi(action: &MidiAction, state: &mut AppState) -> Dispat
                 !state.session.midi_recording.note_passthrough;
             DispatchResult::none()
         }
+        MidiAction::SetClockMode(mode) => {
+            state.midi.clock_mode = *mode;
+            let _ = audio.set_midi_clock(state.midi.clock_mode, state.midi.clock_output_port);
+            DispatchResult::with_status(
+                audio.status(),
+                format!("MIDI clock: {}", state.midi.clock_mode.name()),
+            )
+        }
+        MidiAction::SetClockOutputPort(port) => {
+            state.midi.clock_output_port = *port;
+            let _ = audio.set_midi_clock(state.midi.clock_mode, state.midi.clock_output_port);
+            DispatchResult::none()
+        }
+        MidiAction::ToggleMpe => {
+            let midi_rec = &mut state.session.midi_recording;
+            midi_rec.mpe = !midi_rec.mpe;
+            DispatchResult::with_status(
+                audio.status(),
+                format!("MPE input: {}", if midi_rec.mpe { "ON" } else { "OFF" }),
+            )
+        }
+        MidiAction::SetMpeBendRange(range) => {
+            state.session.midi_recording.mpe_bend_range = (*range).clamp(1, 96);
+            DispatchResult::none()
+        }
+        MidiAction::SetNoteRecordMode(mode) => {
+            state.session.midi_recording.note_record_mode = *mode;
+            DispatchResult::with_status(audio.status(), format!("Note recording: {}", mode.name()))
+        }
+        MidiAction::SetInputQuantize(grid) => {
+            state.session.midi_recording.input_quantize = grid.filter(|g| *g > 0);
+            DispatchResult::none()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imbolc_types::MidiClockMode;
+
+    #[test]
+    fn set_clock_mode_updates_connection_state() {
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let _ = dispatch_midi(&MidiAction::SetClockOutputPort(2), &mut state, &mut audio);
+        let _ = dispatch_midi(
+            &MidiAction::SetClockMode(MidiClockMode::Master),
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(state.midi.clock_mode, MidiClockMode::Master);
+        assert_eq!(state.midi.clock_output_port, 2);
     }
 }
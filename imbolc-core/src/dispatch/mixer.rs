@@ -31,6 +31,10 @@ pub(super) fn dispatch_mixer(
         | MixerAction::SelectAt(_)
         | MixerAction::CycleSection => {}
 
+        MixerAction::ResetClips => {
+            state.audio.visualization.reset_clips();
+        }
+
         MixerAction::AdjustLevel(_delta) => match selection {
             MixerSelection::Instrument(idx) => {
                 result.audio_effects.push(AudioEffect::RebuildInstruments);
@@ -372,6 +376,32 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn reset_clips_clears_latches_without_effects() {
+        let (mut state, mut audio) = setup();
+        let target = imbolc_types::ChannelMeterTarget::Bus(BusId::new(1));
+        let hot = imbolc_types::ChannelLevels {
+            peak_l: 1.3,
+            ..Default::default()
+        };
+        state
+            .audio
+            .visualization
+            .update_channel_meters(&[(target, hot)]);
+        let clipped = |state: &AppState| {
+            state
+                .audio
+                .visualization
+                .channel_meter(target)
+                .is_some_and(|m| m.clipped)
+        };
+        assert!(clipped(&state));
+
+        let result = dispatch_mixer(&MixerAction::ResetClips, &mut state, &mut audio);
+        assert!(!clipped(&state));
+        assert!(result.audio_effects.is_empty());
+    }
+
     #[test]
     fn toggle_send_auto_sets_level() {
         let (mut state, mut audio) = setup();
@@ -8,6 +8,7 @@ mod instrument;
 mod local;
 mod midi;
 mod mixer;
+mod note_take;
 mod piano_roll;
 mod sequencer;
 mod server;
@@ -92,7 +93,7 @@ pub fn dispatch_action(
         DomainAction::Sequencer(a) => sequencer::dispatch_sequencer(a, state, audio),
         DomainAction::Chopper(a) => sequencer::dispatch_chopper(a, state, audio),
         DomainAction::Automation(a) => automation::dispatch_automation(a, state, audio),
-        DomainAction::Midi(a) => midi::dispatch_midi(a, state),
+        DomainAction::Midi(a) => midi::dispatch_midi(a, state, audio),
         DomainAction::Bus(a) => bus::dispatch_bus(a, state),
         DomainAction::LayerGroup(a) => bus::dispatch_layer_group(a, state, audio),
         DomainAction::VstParam(a) => vst_param::dispatch_vst_param(a, state, audio),
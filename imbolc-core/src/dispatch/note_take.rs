@@ -0,0 +1,327 @@
+//! MIDI note take recording: notes land where they were played, replace modes
+//! clear what the playhead passes, and loop passes are kept as take clips.
+
+use crate::state::arrangement::ArrangementState;
+use crate::state::AppState;
+use imbolc_types::{ExpressionDimension, InstrumentId, Note, NoteRecordMode, NoteTake};
+
+/// Pitches a key records, following the instrument's chord shape
+fn record_pitches(state: &AppState, instrument_id: InstrumentId, pitch: u8) -> Vec<u8> {
+    let chord_shape = state
+        .instruments
+        .instrument(instrument_id)
+        .and_then(|inst| inst.note_input.chord_shape);
+    match chord_shape {
+        Some(shape) => shape.expand(pitch),
+        None => vec![pitch],
+    }
+}
+
+/// Tick an event received `latency_us` ago was played at
+fn played_tick(state: &AppState, latency_us: u64) -> u32 {
+    state
+        .session
+        .piano_roll
+        .tick_before(state.audio.playhead, latency_us as f64 / 1_000_000.0)
+}
+
+/// Note start after input quantize; a start snapped onto the loop end moves
+/// to the loop start, where it will sound.
+fn quantized_tick(state: &AppState, tick: u32) -> u32 {
+    let pr = &state.session.piano_roll;
+    let snapped = state.session.midi_recording.quantize_input(tick);
+    if pr.looping && tick < pr.loop_end && snapped >= pr.loop_end {
+        pr.loop_start
+    } else {
+        snapped
+    }
+}
+
+fn is_recording(state: &AppState) -> bool {
+    state.session.piano_roll.recording && state.audio.playing
+}
+
+/// Start a take on the MIDI input instrument at the playhead.
+pub(super) fn start_note_take(state: &mut AppState) {
+    let midi_rec = &state.session.midi_recording;
+    let instrument_id = midi_rec
+        .live_input_instrument
+        .or_else(|| state.instruments.selected_instrument().map(|i| i.id));
+    state.recording.note_take =
+        instrument_id.map(|id| NoteTake::new(id, midi_rec.note_record_mode, state.audio.playhead));
+}
+
+/// MIDI key down while recording. Returns whether the piano roll changed
+/// (a take on another instrument was finished).
+pub(super) fn record_note_on(
+    state: &mut AppState,
+    instrument_id: InstrumentId,
+    pitch: u8,
+    velocity: u8,
+    latency_us: u64,
+) -> bool {
+    if !is_recording(state) {
+        return false;
+    }
+    let pressed = played_tick(state, latency_us);
+    let tick = quantized_tick(state, pressed);
+    let mut changed = false;
+    if state
+        .recording
+        .note_take
+        .as_ref()
+        .is_none_or(|t| t.instrument_id != instrument_id)
+    {
+        changed = finish_note_take(state);
+        let mode = state.session.midi_recording.note_record_mode;
+        state.recording.note_take = Some(NoteTake::new(instrument_id, mode, pressed));
+    }
+    let pitches = record_pitches(state, instrument_id, pitch);
+    if let Some(take) = state.recording.note_take.as_mut() {
+        for p in pitches {
+            take.note_on(p, velocity, pressed, tick);
+        }
+    }
+    changed
+}
+
+/// MIDI key up while recording: writes the finished notes to the track.
+/// Returns whether the piano roll changed.
+pub(super) fn record_note_off(
+    state: &mut AppState,
+    instrument_id: InstrumentId,
+    pitch: u8,
+    latency_us: u64,
+) -> bool {
+    let tick = played_tick(state, latency_us);
+    let pitches = record_pitches(state, instrument_id, pitch);
+    let Some(take) = state
+        .recording
+        .note_take
+        .as_mut()
+        .filter(|t| t.instrument_id == instrument_id)
+    else {
+        return false;
+    };
+    let notes: Vec<Note> = pitches
+        .into_iter()
+        .filter_map(|p| take.note_off(p, tick))
+        .collect();
+    let changed = !notes.is_empty();
+    for note in notes {
+        state.session.piano_roll.insert_note(instrument_id, note);
+    }
+    changed
+}
+
+/// Record live expression on a key held in the take. Returns false when the
+/// key is not part of the take.
+pub(super) fn record_take_expression(
+    state: &mut AppState,
+    instrument_id: InstrumentId,
+    pitch: u8,
+    dimension: ExpressionDimension,
+    value: f32,
+) -> bool {
+    let tick = state.audio.playhead;
+    state
+        .recording
+        .note_take
+        .as_mut()
+        .filter(|t| t.instrument_id == instrument_id)
+        .is_some_and(|t| t.record_expression(pitch, tick, dimension, value))
+}
+
+/// Follow the playhead during a take: replace modes clear the notes it
+/// passes over, and a loop wrap ends the pass (kept as a clip in loop-take
+/// mode). Returns whether the piano roll or arrangement changed.
+pub(super) fn advance_note_take(state: &mut AppState, playhead: u32) -> bool {
+    let Some(take) = state.recording.note_take.as_mut() else {
+        return false;
+    };
+    let pr = &mut state.session.piano_roll;
+    let id = take.instrument_id;
+    let mut changed = false;
+
+    if pr.looping && playhead < take.cleared_to {
+        let (loop_start, loop_end) = (pr.loop_start, pr.loop_end);
+        if take.mode.replaces() {
+            changed |= pr.clear_notes(id, take.cleared_to, loop_end, |n| take.contains(n));
+        }
+        let (cut, pass) = take.next_pass(loop_end, loop_start);
+        changed |= !cut.is_empty();
+        for note in cut {
+            pr.insert_note(id, note);
+        }
+        if take.mode == NoteRecordMode::LoopTakes {
+            changed |= keep_take(
+                &mut state.session.arrangement,
+                id,
+                pass,
+                loop_start,
+                loop_end,
+            );
+        }
+    }
+
+    if take.mode.replaces() && playhead > take.cleared_to {
+        changed |= pr.clear_notes(id, take.cleared_to, playhead, |n| take.contains(n));
+    }
+    take.cleared_to = take.cleared_to.max(playhead);
+    changed
+}
+
+/// End the take at the playhead: held keys are released there, and in
+/// loop-take mode the unfinished pass is kept as a take too. Returns whether
+/// the piano roll or arrangement changed.
+pub(super) fn finish_note_take(state: &mut AppState) -> bool {
+    let Some(mut take) = state.recording.note_take.take() else {
+        return false;
+    };
+    let playhead = state.audio.playhead;
+    let id = take.instrument_id;
+    let pr = &mut state.session.piano_roll;
+    let mut changed = false;
+
+    if take.mode.replaces() && playhead > take.cleared_to {
+        changed |= pr.clear_notes(id, take.cleared_to, playhead, |n| take.contains(n));
+    }
+    let held = take.release_all(playhead);
+    changed |= !held.is_empty();
+    for note in held {
+        pr.insert_note(id, note);
+    }
+    if take.mode == NoteRecordMode::LoopTakes {
+        let (start, end) = if pr.looping {
+            (pr.loop_start, pr.loop_end)
+        } else {
+            (take.pass_start, playhead)
+        };
+        changed |= keep_take(
+            &mut state.session.arrangement,
+            id,
+            take.pass_notes,
+            start,
+            end,
+        );
+    }
+    changed
+}
+
+/// Keep the notes of a pass that fall in `start..end` as a take clip.
+fn keep_take(
+    arrangement: &mut ArrangementState,
+    instrument_id: InstrumentId,
+    notes: Vec<Note>,
+    start: u32,
+    end: u32,
+) -> bool {
+    let mut notes: Vec<Note> = notes
+        .into_iter()
+        .filter(|n| n.tick >= start && n.tick < end)
+        .map(|n| Note {
+            tick: n.tick - start,
+            duration: n.duration.min(end - n.tick),
+            ..n
+        })
+        .collect();
+    if notes.is_empty() {
+        return false;
+    }
+    notes.sort_by_key(|n| n.tick);
+    arrangement.add_note_take(instrument_id, end - start, notes);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SourceType;
+
+    fn recording_state(mode: NoteRecordMode) -> (AppState, InstrumentId) {
+        let mut state = AppState::new();
+        let id = state.add_instrument(SourceType::Saw);
+        state.session.midi_recording.note_record_mode = mode;
+        state.session.midi_recording.live_input_instrument = Some(id);
+        let pr = &mut state.session.piano_roll;
+        pr.recording = true;
+        pr.looping = true;
+        pr.loop_start = 0;
+        pr.loop_end = 1920;
+        state.audio.playing = true;
+        (state, id)
+    }
+
+    fn track_notes(state: &AppState, id: InstrumentId) -> Vec<(u32, u8)> {
+        state.session.piano_roll.tracks[&id]
+            .notes
+            .iter()
+            .map(|n| (n.tick, n.pitch))
+            .collect()
+    }
+
+    #[test]
+    fn latency_and_input_quantize_place_notes() {
+        let (mut state, id) = recording_state(NoteRecordMode::Overdub);
+        state.session.midi_recording.input_quantize = Some(240);
+        state.audio.playhead = 600;
+        // 50 ms at 120 BPM is 48 ticks: played at 552, snapped to 480
+        record_note_on(&mut state, id, 60, 100, 50_000);
+        state.audio.playhead = 840;
+        assert!(record_note_off(&mut state, id, 60, 0));
+
+        let note = &state.session.piano_roll.tracks[&id].notes[0];
+        assert_eq!((note.tick, note.duration), (480, 288));
+    }
+
+    #[test]
+    fn replace_clears_notes_the_playhead_passes() {
+        let (mut state, id) = recording_state(NoteRecordMode::Replace);
+        state.session.piano_roll.toggle_note(0, 48, 240, 240, 100);
+        state.session.piano_roll.toggle_note(0, 50, 1440, 240, 100);
+        start_note_take(&mut state);
+
+        state.audio.playhead = 480;
+        record_note_on(&mut state, id, 60, 100, 0);
+        assert!(advance_note_take(&mut state, 720));
+        state.audio.playhead = 720;
+        record_note_off(&mut state, id, 60, 0);
+        assert_eq!(track_notes(&state, id), vec![(480, 60), (1440, 50)]);
+
+        // Stopping ends the take without clearing further
+        finish_note_take(&mut state);
+        assert!(state.recording.note_take.is_none());
+        assert_eq!(track_notes(&state, id), vec![(480, 60), (1440, 50)]);
+    }
+
+    #[test]
+    fn loop_passes_become_take_clips() {
+        let (mut state, id) = recording_state(NoteRecordMode::LoopTakes);
+        start_note_take(&mut state);
+
+        state.audio.playhead = 240;
+        record_note_on(&mut state, id, 60, 100, 0);
+        state.audio.playhead = 480;
+        record_note_off(&mut state, id, 60, 0);
+        advance_note_take(&mut state, 1800);
+
+        // Wrap: the first pass becomes Take 1
+        assert!(advance_note_take(&mut state, 120));
+        state.audio.playhead = 120;
+        record_note_on(&mut state, id, 64, 100, 0);
+        advance_note_take(&mut state, 960);
+        state.audio.playhead = 960;
+        finish_note_take(&mut state);
+
+        let takes: Vec<(&str, usize, u32)> = state
+            .session
+            .arrangement
+            .clips
+            .iter()
+            .map(|c| (c.name.as_str(), c.notes.len(), c.length_ticks))
+            .collect();
+        assert_eq!(takes, vec![("Take 1", 1, 1920), ("Take 2", 1, 1920)]);
+        // The track holds the latest pass
+        assert_eq!(track_notes(&state, id), vec![(120, 64)]);
+    }
+}
@@ -1,8 +1,10 @@
-use crate::action::{AudioEffect, DispatchResult};
+use crate::action::{AudioEffect, DispatchResult, NavIntent, PaneId};
 use crate::state::AppState;
 use crate::state::{ClipboardContents, ClipboardNote};
 use imbolc_audio::AudioHandle;
-use imbolc_types::{DomainAction, PianoRollAction};
+use imbolc_types::{DomainAction, InstrumentId, PianoRollAction};
+
+use super::note_take;
 
 fn reduce(action: &PianoRollAction, state: &mut AppState) {
     imbolc_types::reduce::reduce_action(
@@ -25,10 +27,19 @@ pub(super) fn dispatch_piano_roll(
             result
         }
         PianoRollAction::PlayStop => {
-            // Ignore play/stop while exporting — user must cancel first
-            if state.io.pending_export.is_some() || state.io.pending_render.is_some() {
+            // Ignore play/stop while exporting in realtime — user must cancel first
+            let realtime_export = state
+                .io
+                .pending_export
+                .as_ref()
+                .is_some_and(|export| !export.offline);
+            if realtime_export || state.io.pending_render.is_some() {
                 return DispatchResult::none();
             }
+            let mut result = DispatchResult::none();
+            if state.session.piano_roll.playing && note_take::finish_note_take(state) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
             reduce(action, state);
             let playing = state.session.piano_roll.playing;
             state.audio.playing = playing;
@@ -43,16 +54,21 @@ pub(super) fn dispatch_piano_roll(
             }
             // Clear recording unconditionally via normal play/stop
             state.session.piano_roll.recording = false;
-            DispatchResult::none()
+            result
         }
         PianoRollAction::PlayStopRecord => {
             let was_playing = state.audio.playing;
+            let mut result = DispatchResult::none();
+            if was_playing && note_take::finish_note_take(state) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
             reduce(action, state);
 
             if !was_playing {
                 // Started playing + recording
                 state.audio.playing = true;
                 audio.set_playing(true);
+                note_take::start_note_take(state);
             } else {
                 // Stopped playing + recording
                 state.audio.playing = false;
@@ -64,7 +80,7 @@ pub(super) fn dispatch_piano_roll(
                 }
                 audio.clear_active_notes();
             }
-            DispatchResult::none()
+            result
         }
         PianoRollAction::ToggleLoop => {
             reduce(action, state);
@@ -108,27 +124,8 @@ pub(super) fn dispatch_piano_roll(
             let instrument_id = *instrument_id;
             let track = *track;
 
-            // Fan-out to layer group members
-            let targets = state.instruments.layer_group_members(instrument_id);
-
             if audio.is_running() {
-                let vel_f = velocity as f32 / 127.0;
-                for &target_id in &targets {
-                    if let Some(inst) = state.instruments.instrument(target_id) {
-                        if state.effective_instrument_mute(inst) {
-                            continue;
-                        }
-                        let expanded: Vec<u8> = match inst.note_input.chord_shape {
-                            Some(shape) => shape.expand(pitch),
-                            None => vec![pitch],
-                        };
-                        for &p in &expanded {
-                            let p = inst.offset_pitch(p);
-                            let _ = audio.spawn_voice(target_id, p, vel_f, 0.0);
-                            audio.push_active_note(target_id, p, 240);
-                        }
-                    }
-                }
+                spawn_live_voices(state, audio, instrument_id, &[pitch], velocity);
             } else if !state.session.piano_roll.recording {
                 return DispatchResult::with_status(
                     imbolc_audio::ServerStatus::Stopped,
@@ -170,29 +167,8 @@ pub(super) fn dispatch_piano_roll(
             let instrument_id = *instrument_id;
             let track = *track;
 
-            // Fan-out to layer group members
-            let targets = state.instruments.layer_group_members(instrument_id);
-
             if audio.is_running() {
-                let vel_f = velocity as f32 / 127.0;
-                for &target_id in &targets {
-                    if let Some(inst) = state.instruments.instrument(target_id) {
-                        if state.effective_instrument_mute(inst) {
-                            continue;
-                        }
-                        for &pitch in pitches {
-                            let expanded: Vec<u8> = match inst.note_input.chord_shape {
-                                Some(shape) => shape.expand(pitch),
-                                None => vec![pitch],
-                            };
-                            for &p in &expanded {
-                                let p = inst.offset_pitch(p);
-                                let _ = audio.spawn_voice(target_id, p, vel_f, 0.0);
-                                audio.push_active_note(target_id, p, 240);
-                            }
-                        }
-                    }
-                }
+                spawn_live_voices(state, audio, instrument_id, pitches, velocity);
             } else if !state.session.piano_roll.recording {
                 return DispatchResult::with_status(
                     imbolc_audio::ServerStatus::Stopped,
@@ -262,6 +238,7 @@ pub(super) fn dispatch_piano_roll(
                 instrument_id,
                 path: path.clone(),
                 was_looping: pr.looping,
+                freeze: false,
             });
 
             pr.playhead = pr.loop_start;
@@ -282,7 +259,7 @@ pub(super) fn dispatch_piano_roll(
             result.audio_effects.push(AudioEffect::UpdatePianoRoll);
             result
         }
-        PianoRollAction::PasteNotes { .. } => {
+        PianoRollAction::PasteNotes { .. } | PianoRollAction::TransformNotes { .. } => {
             reduce(action, state);
             let mut result = DispatchResult::none();
             result.audio_effects.push(AudioEffect::UpdatePianoRoll);
@@ -315,13 +292,19 @@ pub(super) fn dispatch_piano_roll(
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs();
-            let path = export_dir.join(format!("bounce_{}.wav", timestamp));
+            let options = state.io.export_options;
+            let path = export_dir.join(format!(
+                "bounce_{}.{}",
+                timestamp,
+                options.format.extension()
+            ));
 
             let pr = &mut state.session.piano_roll;
             state.io.pending_export = Some(crate::state::PendingExport {
                 kind: imbolc_audio::commands::ExportKind::MasterBounce,
                 was_looping: pr.looping,
                 paths: vec![path.clone()],
+                offline: false,
             });
 
             pr.playhead = pr.loop_start;
@@ -329,13 +312,14 @@ pub(super) fn dispatch_piano_roll(
             state.audio.playing = true;
             pr.looping = false;
 
-            let _ = audio.start_master_bounce(&path);
+            let _ = audio.start_master_bounce(&path, options);
 
             let mut result = DispatchResult::with_status(
                 imbolc_audio::ServerStatus::Running,
-                "Bouncing to WAV...",
+                format!("Bouncing to {}...", options.format.name()),
             );
             result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result.push_nav(NavIntent::ConditionalPop(PaneId::Export));
             result
         }
         PianoRollAction::ExportStems => {
@@ -366,6 +350,7 @@ pub(super) fn dispatch_piano_roll(
                 .unwrap_or_default()
                 .as_secs();
 
+            let options = state.io.export_options;
             let mut stems = Vec::new();
             let mut paths = Vec::new();
             for inst in &state.instruments.instruments {
@@ -375,7 +360,12 @@ pub(super) fn dispatch_piano_roll(
                     .chars()
                     .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
                     .collect();
-                let path = export_dir.join(format!("stem_{}_{}.wav", safe_name, timestamp));
+                let path = export_dir.join(format!(
+                    "stem_{}_{}.{}",
+                    safe_name,
+                    timestamp,
+                    options.format.extension()
+                ));
                 stems.push((inst.id, path.clone()));
                 paths.push(path);
             }
@@ -385,6 +375,7 @@ pub(super) fn dispatch_piano_roll(
                 kind: imbolc_audio::commands::ExportKind::StemExport,
                 was_looping: pr.looping,
                 paths,
+                offline: false,
             });
 
             pr.playhead = pr.loop_start;
@@ -392,16 +383,98 @@ pub(super) fn dispatch_piano_roll(
             state.audio.playing = true;
             pr.looping = false;
 
-            let _ = audio.start_stem_export(&stems);
+            let _ = audio.start_stem_export(&stems, options);
 
             let mut result = DispatchResult::with_status(
                 imbolc_audio::ServerStatus::Running,
                 "Exporting stems...".to_string(),
             );
             result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result.push_nav(NavIntent::ConditionalPop(PaneId::Export));
+            result
+        }
+        PianoRollAction::BounceOffline | PianoRollAction::ExportStemsOffline => {
+            if state.io.pending_render.is_some() || state.io.pending_export.is_some() {
+                return DispatchResult::with_status(
+                    imbolc_audio::ServerStatus::Running,
+                    "Already rendering or exporting",
+                );
+            }
+            if state.instruments.instruments.is_empty() {
+                return DispatchResult::with_status(
+                    imbolc_audio::ServerStatus::Stopped,
+                    "No instruments",
+                );
+            }
+
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            let export_dir = std::path::Path::new(&home).join(".config/imbolc/exports");
+            let _ = std::fs::create_dir_all(&export_dir);
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let options = state.io.export_options;
+            let extension = options.format.extension();
+            let target = if matches!(action, PianoRollAction::BounceOffline) {
+                imbolc_audio::nrt::OfflineTarget::Master(
+                    export_dir.join(format!("bounce_{}.{}", timestamp, extension)),
+                )
+            } else {
+                imbolc_audio::nrt::OfflineTarget::Stems(
+                    state
+                        .instruments
+                        .instruments
+                        .iter()
+                        .map(|inst| {
+                            let safe_name: String = inst
+                                .name
+                                .replace(' ', "_")
+                                .chars()
+                                .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                                .collect();
+                            let file = format!("stem_{}_{}.{}", safe_name, timestamp, extension);
+                            (inst.id, export_dir.join(file))
+                        })
+                        .collect(),
+                )
+            };
+
+            let kind = target.kind();
+            let paths = target.paths();
+            if let Err(e) = audio.start_offline_export(target, options) {
+                return DispatchResult::with_status(
+                    imbolc_audio::ServerStatus::Error,
+                    format!("Offline export failed: {}", e),
+                );
+            }
+            state.io.pending_export = Some(crate::state::PendingExport {
+                kind,
+                was_looping: state.session.piano_roll.looping,
+                paths,
+                offline: true,
+            });
+            state.io.export_progress = 0.0;
+            let mut result = DispatchResult::with_status(audio.status(), "Rendering offline...");
+            result.push_nav(NavIntent::ConditionalPop(PaneId::Export));
             result
         }
+        PianoRollAction::SetExportOptions(options) => {
+            state.io.export_options = options.validated();
+            DispatchResult::none()
+        }
         PianoRollAction::CancelExport => {
+            if state
+                .io
+                .pending_export
+                .as_ref()
+                .is_some_and(|export| export.offline)
+            {
+                let _ = audio.cancel_export();
+                state.io.pending_export = None;
+                state.io.export_progress = 0.0;
+                return DispatchResult::with_status(audio.status(), "Export cancelled");
+            }
             if state.io.pending_export.is_some() {
                 let _ = audio.cancel_export();
                 let pr = &mut state.session.piano_roll;
@@ -422,6 +495,27 @@ pub(super) fn dispatch_piano_roll(
             }
             DispatchResult::none()
         }
+        PianoRollAction::ExportMidi => {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            let export_dir = std::path::Path::new(&home).join(".config/imbolc/exports");
+            let _ = std::fs::create_dir_all(&export_dir);
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let path = export_dir.join(format!("export_{}.mid", timestamp));
+
+            let msg = match crate::midi::smf::export_smf(&path, &state.session, &state.instruments)
+            {
+                Ok(count) => format!(
+                    "Exported {} tracks to MIDI: {}",
+                    count,
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                Err(e) => format!("MIDI export failed: {}", e),
+            };
+            DispatchResult::with_status(audio.status(), msg)
+        }
         PianoRollAction::CopyNotes {
             track,
             start_tick,
@@ -443,6 +537,7 @@ pub(super) fn dispatch_piano_roll(
                             duration: note.duration,
                             velocity: note.velocity,
                             probability: note.probability,
+                            expression: note.expression.clone(),
                         });
                     }
                 }
@@ -452,6 +547,47 @@ pub(super) fn dispatch_piano_roll(
             }
             DispatchResult::none()
         }
+        PianoRollAction::RecordNoteOn {
+            instrument_id,
+            pitch,
+            velocity,
+            latency_us,
+        } => {
+            if audio.is_running() {
+                spawn_live_voices(state, audio, *instrument_id, &[*pitch], *velocity);
+            }
+            let mut result = DispatchResult::none();
+            if note_take::record_note_on(state, *instrument_id, *pitch, *velocity, *latency_us) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
+            result
+        }
+        PianoRollAction::RecordNoteOff {
+            instrument_id,
+            pitch,
+            latency_us,
+        } => {
+            let mut result = DispatchResult::none();
+            if note_take::record_note_off(state, *instrument_id, *pitch, *latency_us) {
+                result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            }
+            result
+        }
+        PianoRollAction::LoadNoteTake(clip_id) => {
+            let Some(name) = state
+                .session
+                .arrangement
+                .clip(*clip_id)
+                .map(|c| c.name.clone())
+            else {
+                return DispatchResult::none();
+            };
+            reduce(action, state);
+            let mut result =
+                DispatchResult::with_status(audio.status(), format!("Auditioning {}", name));
+            result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+            result
+        }
         PianoRollAction::ReleaseNote {
             pitch,
             instrument_id,
@@ -489,6 +625,37 @@ pub(super) fn dispatch_piano_roll(
     }
 }
 
+/// Play live notes on an instrument and its layer group siblings, following
+/// each one's chord shape and pitch offset.
+fn spawn_live_voices(
+    state: &AppState,
+    audio: &mut AudioHandle,
+    instrument_id: InstrumentId,
+    pitches: &[u8],
+    velocity: u8,
+) {
+    let vel_f = velocity as f32 / 127.0;
+    // Fan-out to layer group members
+    for target_id in state.instruments.layer_group_members(instrument_id) {
+        if let Some(inst) = state.instruments.instrument(target_id) {
+            if state.effective_instrument_mute(inst) {
+                continue;
+            }
+            for &pitch in pitches {
+                let expanded: Vec<u8> = match inst.note_input.chord_shape {
+                    Some(shape) => shape.expand(pitch),
+                    None => vec![pitch],
+                };
+                for &p in &expanded {
+                    let p = inst.offset_pitch(p);
+                    let _ = audio.spawn_voice(target_id, p, vel_f, 0.0);
+                    audio.push_active_note(target_id, p, 240);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(unused_must_use)]
 mod tests {
@@ -543,6 +710,31 @@ mod tests {
         assert!(!state.session.piano_roll.recording);
     }
 
+    #[test]
+    fn record_transport_runs_a_note_take() {
+        let (mut state, mut audio) = setup();
+        let id = state.add_instrument(crate::state::SourceType::Saw);
+        state.session.midi_recording.live_input_instrument = Some(id);
+        dispatch_piano_roll(&PianoRollAction::PlayStopRecord, &mut state, &mut audio);
+        assert!(state.recording.note_take.is_some());
+
+        let note_on = PianoRollAction::RecordNoteOn {
+            instrument_id: id,
+            pitch: 60,
+            velocity: 100,
+            latency_us: 0,
+        };
+        dispatch_piano_roll(&note_on, &mut state, &mut audio);
+        state.audio.playhead = 240;
+
+        // Stopping writes out the held key
+        let result = dispatch_piano_roll(&PianoRollAction::PlayStopRecord, &mut state, &mut audio);
+        assert!(result.audio_effects.contains(&AudioEffect::UpdatePianoRoll));
+        assert!(state.recording.note_take.is_none());
+        let notes = &state.session.piano_roll.tracks[&id].notes;
+        assert_eq!((notes[0].tick, notes[0].duration), (0, 240));
+    }
+
     #[test]
     fn play_stop_noop_while_exporting() {
         let (mut state, mut audio) = setup();
@@ -550,12 +742,65 @@ mod tests {
             kind: imbolc_audio::commands::ExportKind::MasterBounce,
             was_looping: false,
             paths: vec![],
+            offline: false,
         });
         let action = PianoRollAction::PlayStop;
         dispatch_piano_roll(&action, &mut state, &mut audio);
         assert!(!state.session.piano_roll.playing);
     }
 
+    #[test]
+    fn offline_export_leaves_transport_usable() {
+        let (mut state, mut audio) = setup();
+        state.io.pending_export = Some(crate::state::PendingExport {
+            kind: imbolc_audio::commands::ExportKind::StemExport,
+            was_looping: true,
+            paths: vec![],
+            offline: true,
+        });
+        dispatch_piano_roll(&PianoRollAction::PlayStop, &mut state, &mut audio);
+        assert!(state.session.piano_roll.playing);
+
+        dispatch_piano_roll(&PianoRollAction::CancelExport, &mut state, &mut audio);
+        assert!(state.io.pending_export.is_none());
+        assert!(state.session.piano_roll.playing);
+    }
+
+    #[test]
+    fn offline_export_refused_while_exporting() {
+        let (mut state, mut audio) = setup();
+        state.io.pending_export = Some(crate::state::PendingExport {
+            kind: imbolc_audio::commands::ExportKind::MasterBounce,
+            was_looping: false,
+            paths: vec![],
+            offline: false,
+        });
+        let result = dispatch_piano_roll(&PianoRollAction::BounceOffline, &mut state, &mut audio);
+        assert_eq!(result.status[0].message, "Already rendering or exporting");
+        assert!(!state.io.pending_export.as_ref().unwrap().offline);
+    }
+
+    #[test]
+    fn export_options_are_stored_validated() {
+        let (mut state, mut audio) = setup();
+        let options = crate::state::ExportOptions {
+            format: crate::state::ExportFormat::Flac,
+            bit_depth: crate::state::ExportBitDepth::Float32,
+            sample_rate: Some(44100),
+            normalize: true,
+        };
+        dispatch_piano_roll(
+            &PianoRollAction::SetExportOptions(options),
+            &mut state,
+            &mut audio,
+        );
+        let stored = state.io.export_options;
+        assert_eq!(stored.bit_depth, crate::state::ExportBitDepth::Int24);
+        assert_eq!(stored.sample_rate, Some(44100));
+        assert!(stored.normalize);
+        assert!(!state.project.dirty);
+    }
+
     #[test]
     fn toggle_loop_flips() {
         let (mut state, mut audio) = setup();
@@ -624,6 +869,7 @@ mod tests {
                 duration: 480,
                 velocity: 100,
                 probability: 1.0,
+                expression: None,
             },
             ClipboardNote {
                 tick_offset: 480,
@@ -631,6 +877,7 @@ mod tests {
                 duration: 480,
                 velocity: 100,
                 probability: 1.0,
+                expression: None,
             }, // out of range
             ClipboardNote {
                 tick_offset: 480,
@@ -638,6 +885,7 @@ mod tests {
                 duration: 480,
                 velocity: 100,
                 probability: 1.0,
+                expression: None,
             },
         ];
         let action = PianoRollAction::PasteNotes {
@@ -676,4 +924,37 @@ mod tests {
             _ => panic!("Expected PianoRollNotes in clipboard"),
         }
     }
+
+    #[test]
+    fn transform_notes_only_touches_region() {
+        let (mut state, mut audio) = setup();
+        let _id = state.add_instrument(crate::state::SourceType::Saw);
+        state.session.piano_roll.toggle_note(0, 60, 10, 480, 100);
+        state.session.piano_roll.toggle_note(0, 64, 490, 480, 100);
+        state.session.piano_roll.toggle_note(0, 72, 970, 480, 100);
+
+        let action = PianoRollAction::TransformNotes {
+            track: 0,
+            start_tick: 0,
+            end_tick: 960,
+            start_pitch: 60,
+            end_pitch: 72,
+            transform: imbolc_types::NoteTransform::Quantize {
+                grid: 240,
+                strength: 1.0,
+                swing: 0.0,
+            },
+        };
+        dispatch_piano_roll(&action, &mut state, &mut audio);
+        let ticks: Vec<u32> = state
+            .session
+            .piano_roll
+            .track_at(0)
+            .unwrap()
+            .notes
+            .iter()
+            .map(|n| n.tick)
+            .collect();
+        assert_eq!(ticks, vec![0, 480, 970]);
+    }
 }
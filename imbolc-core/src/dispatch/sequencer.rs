@@ -1,10 +1,13 @@
 use crate::action::{
     AudioEffect, ChopperAction, DispatchResult, NavIntent, PaneId, SequencerAction,
 };
-use crate::state::drum_sequencer::{euclidean_rhythm, DrumPattern, DrumStep};
+use crate::state::drum_sequencer::{
+    euclidean_rhythm, DrumPattern, DrumStep, ParamLock, MAX_RATCHET,
+};
 use crate::state::sampler::Slice;
 use crate::state::{AppState, ClipboardContents};
 use imbolc_audio::AudioHandle;
+use imbolc_types::{SOURCE_BPM_MAX, SOURCE_BPM_MIN};
 
 use super::helpers::compute_waveform_peaks;
 
@@ -199,6 +202,7 @@ pub(super) fn dispatch_sequencer(
                     pad.buffer_id = Some(buffer_id);
                     pad.path = Some(path_str);
                     pad.name = name;
+                    pad.warp_bpm = None;
                 }
             }
 
@@ -284,6 +288,109 @@ pub(super) fn dispatch_sequencer(
             result.audio_effects.push(AudioEffect::RebuildInstruments);
             result
         }
+        SequencerAction::AdjustParamLock(pad_idx, step_idx, target, delta) => {
+            // A new lock starts from the pad's slice or the setting of the
+            // instrument the pad sounds on
+            let Some(kit) = state.instruments.selected_instrument() else {
+                return DispatchResult::none();
+            };
+            let pad = kit.drum_sequencer().and_then(|seq| seq.pads.get(*pad_idx));
+            let sounding = pad
+                .and_then(|pad| pad.instrument_id)
+                .and_then(|id| state.instruments.instrument(id))
+                .unwrap_or(kit);
+            let base = pad
+                .and_then(|pad| pad.slice_value(target))
+                .or_else(|| sounding.param_value(target))
+                .unwrap_or_else(|| ParamLock::default_value(target));
+            if let Some(step) = state
+                .instruments
+                .selected_drum_sequencer_mut()
+                .and_then(|seq| {
+                    seq.pattern_mut()
+                        .steps
+                        .get_mut(*pad_idx)
+                        .and_then(|s| s.get_mut(*step_idx))
+                })
+            {
+                let value = step.lock(target).unwrap_or(base);
+                step.set_lock(*target, ParamLock::nudge(target, value, *delta));
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        SequencerAction::ClearParamLock(pad_idx, step_idx, target) => {
+            if let Some(step) = state
+                .instruments
+                .selected_drum_sequencer_mut()
+                .and_then(|seq| {
+                    seq.pattern_mut()
+                        .steps
+                        .get_mut(*pad_idx)
+                        .and_then(|s| s.get_mut(*step_idx))
+                })
+            {
+                step.clear_lock(target);
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        SequencerAction::CycleStepCondition(pad_idx, step_idx, direction) => {
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                if let Some(step) = seq
+                    .pattern_mut()
+                    .steps
+                    .get_mut(*pad_idx)
+                    .and_then(|s| s.get_mut(*step_idx))
+                {
+                    step.condition = step.condition.cycle(*direction);
+                }
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        SequencerAction::AdjustRatchet(pad_idx, step_idx, delta) => {
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                if let Some(step) = seq
+                    .pattern_mut()
+                    .steps
+                    .get_mut(*pad_idx)
+                    .and_then(|s| s.get_mut(*step_idx))
+                {
+                    step.ratchet =
+                        (step.ratchet as i16 + *delta as i16).clamp(1, MAX_RATCHET as i16) as u8;
+                }
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        SequencerAction::AdjustRatchetDecay(pad_idx, step_idx, delta) => {
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                if let Some(step) = seq
+                    .pattern_mut()
+                    .steps
+                    .get_mut(*pad_idx)
+                    .and_then(|s| s.get_mut(*step_idx))
+                {
+                    step.ratchet_decay = (step.ratchet_decay + delta).clamp(0.0, 1.0);
+                }
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        SequencerAction::ToggleFill => {
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                seq.fill = !seq.fill;
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
         SequencerAction::DeleteStepsInRegion {
             start_pad,
             end_pad,
@@ -436,6 +543,7 @@ pub(super) fn dispatch_chopper(
 
             // Compute waveform peaks from WAV file
             let (peaks, duration_secs) = compute_waveform_peaks(&path_str);
+            let source_bpm = imbolc_audio::tempo_detect::detect_tempo(path);
 
             // Allocate from global counter to avoid ID collisions after instrument deletion
             let buffer_id = state.instruments.next_sampler_buffer_id;
@@ -456,12 +564,17 @@ pub(super) fn dispatch_chopper(
                     next_slice_id: 1,
                     waveform_peaks: peaks,
                     duration_secs,
+                    source_bpm,
+                    warp: false,
                 });
             }
 
             let mut result =
                 DispatchResult::with_nav(NavIntent::ConditionalPop(PaneId::FileBrowser));
             result.audio_effects.push(AudioEffect::RebuildInstruments);
+            if let Some(bpm) = source_bpm {
+                result.push_status(audio.status(), format!("Sample tempo {:.1} BPM", bpm));
+            }
             result
         }
         ChopperAction::AddSlice(cursor_pos) => {
@@ -516,13 +629,14 @@ pub(super) fn dispatch_chopper(
                 let assign_data = seq.chopper.as_ref().and_then(|c| {
                     c.slices
                         .get(c.selected_slice)
-                        .map(|s| (c.buffer_id, s.start, s.end))
+                        .map(|s| (c.buffer_id, s.start, s.end, c.pad_warp_bpm()))
                 });
-                if let Some((buffer_id, start, end)) = assign_data {
+                if let Some((buffer_id, start, end, warp_bpm)) = assign_data {
                     if let Some(pad) = seq.pads.get_mut(*pad_idx) {
                         pad.buffer_id = buffer_id;
                         pad.slice_start = start;
                         pad.slice_end = end;
+                        pad.warp_bpm = warp_bpm;
                         // Copy name from chopper
                         if let Some(chopper) = &seq.chopper {
                             pad.name = format!("{} {}", chopper.name, chopper.selected_slice + 1);
@@ -622,6 +736,30 @@ pub(super) fn dispatch_chopper(
             result.audio_effects.push(AudioEffect::RebuildInstruments);
             result
         }
+        ChopperAction::ToggleWarp => {
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                if let Some(chopper) = &mut seq.chopper {
+                    chopper.warp = !chopper.warp;
+                }
+                restamp_chopper_pads(seq);
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
+        ChopperAction::AdjustSourceBpm(delta) => {
+            let project_bpm = state.session.bpm as f32;
+            if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+                if let Some(chopper) = &mut seq.chopper {
+                    let bpm = chopper.source_bpm.unwrap_or(project_bpm) + delta;
+                    chopper.source_bpm = Some(bpm.clamp(SOURCE_BPM_MIN, SOURCE_BPM_MAX));
+                }
+                restamp_chopper_pads(seq);
+            }
+            let mut result = DispatchResult::none();
+            result.audio_effects.push(AudioEffect::RebuildInstruments);
+            result
+        }
         ChopperAction::CommitAll => {
             if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
                 if let Some(chopper) = &seq.chopper {
@@ -638,16 +776,18 @@ pub(super) fn dispatch_chopper(
                                 s.end,
                                 chopper.name.clone(),
                                 chopper.path.clone(),
+                                chopper.pad_warp_bpm(),
                             )
                         })
                         .collect();
-                    for (i, buffer_id, start, end, name, path) in assignments {
+                    for (i, buffer_id, start, end, name, path, warp_bpm) in assignments {
                         if let Some(pad) = seq.pads.get_mut(i) {
                             pad.buffer_id = buffer_id;
                             pad.slice_start = start;
                             pad.slice_end = end;
                             pad.name = format!("{} {}", name, i + 1);
                             pad.path = path;
+                            pad.warp_bpm = warp_bpm;
                         }
                     }
                 }
@@ -663,6 +803,22 @@ pub(super) fn dispatch_chopper(
     }
 }
 
+/// Keep pads holding slices of the chopper's sample in step with its warp.
+fn restamp_chopper_pads(seq: &mut crate::state::drum_sequencer::DrumSequencerState) {
+    let Some(chopper) = &seq.chopper else {
+        return;
+    };
+    let Some(buffer_id) = chopper.buffer_id else {
+        return;
+    };
+    let warp_bpm = chopper.pad_warp_bpm();
+    for pad in seq.pads.iter_mut() {
+        if pad.buffer_id == Some(buffer_id) {
+            pad.warp_bpm = warp_bpm;
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(unused_must_use)]
 mod tests {
@@ -947,6 +1103,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn param_lock_starts_from_instrument_setting() {
+        use crate::state::instrument::ParameterTarget;
+
+        let (mut state, mut audio) = setup();
+        let lock = SequencerAction::AdjustParamLock(0, 2, ParameterTarget::Level, 1);
+        dispatch_sequencer(&lock, &mut state, &mut audio);
+        dispatch_sequencer(&lock, &mut state, &mut audio);
+        let step = &state
+            .instruments
+            .selected_drum_sequencer()
+            .unwrap()
+            .pattern()
+            .steps[0][2];
+        // Kit level 0.8, two nudges of 5%
+        assert!((step.lock(&ParameterTarget::Level).unwrap() - 0.9).abs() < 1e-6);
+
+        dispatch_sequencer(
+            &SequencerAction::ClearParamLock(0, 2, ParameterTarget::Level),
+            &mut state,
+            &mut audio,
+        );
+        let seq = state.instruments.selected_drum_sequencer().unwrap();
+        assert!(seq.pattern().steps[0][2].locks.is_empty());
+    }
+
+    #[test]
+    fn step_conditions_ratchets_and_fill() {
+        use crate::state::drum_sequencer::StepCondition;
+
+        let (mut state, mut audio) = setup();
+        dispatch_sequencer(
+            &SequencerAction::CycleStepCondition(1, 3, -1),
+            &mut state,
+            &mut audio,
+        );
+        for _ in 0..10 {
+            dispatch_sequencer(
+                &SequencerAction::AdjustRatchet(1, 3, 1),
+                &mut state,
+                &mut audio,
+            );
+        }
+        dispatch_sequencer(
+            &SequencerAction::AdjustRatchetDecay(1, 3, -2.0),
+            &mut state,
+            &mut audio,
+        );
+        dispatch_sequencer(&SequencerAction::ToggleFill, &mut state, &mut audio);
+
+        let seq = state.instruments.selected_drum_sequencer().unwrap();
+        let step = &seq.pattern().steps[1][3];
+        assert_eq!(step.condition, StepCondition::NotFill);
+        assert_eq!(step.ratchet, MAX_RATCHET);
+        assert_eq!(step.ratchet_decay, 0.0);
+        assert!(seq.fill);
+    }
+
     #[test]
     fn chain_operations() {
         let (mut state, mut audio) = setup();
@@ -999,4 +1213,43 @@ mod tests {
             1
         );
     }
+
+    #[test]
+    fn chopper_warp_follows_onto_assigned_pads() {
+        let (mut state, mut audio) = setup();
+        if let Some(seq) = state.instruments.selected_drum_sequencer_mut() {
+            seq.chopper = Some(crate::state::drum_sequencer::ChopperState {
+                buffer_id: Some(9),
+                path: Some("/tmp/break_96bpm.wav".to_string()),
+                name: "break".to_string(),
+                slices: vec![Slice::full(0)],
+                selected_slice: 0,
+                next_slice_id: 1,
+                waveform_peaks: Vec::new(),
+                duration_secs: 2.5,
+                source_bpm: Some(96.0),
+                warp: false,
+            });
+        }
+        let pad_warp = |state: &AppState| {
+            state.instruments.selected_drum_sequencer().unwrap().pads[0].warp_bpm
+        };
+
+        dispatch_chopper(&ChopperAction::AssignToPad(0), &mut state, &mut audio);
+        assert_eq!(pad_warp(&state), None);
+
+        dispatch_chopper(&ChopperAction::ToggleWarp, &mut state, &mut audio);
+        assert_eq!(pad_warp(&state), Some(96.0));
+
+        dispatch_chopper(&ChopperAction::AdjustSourceBpm(2.0), &mut state, &mut audio);
+        assert_eq!(pad_warp(&state), Some(98.0));
+
+        // Loading another sample onto the pad drops the warp
+        dispatch_sequencer(
+            &SequencerAction::LoadSampleResult(0, "/tmp/kick.wav".into()),
+            &mut state,
+            &mut audio,
+        );
+        assert_eq!(pad_warp(&state), None);
+    }
 }
@@ -98,37 +98,75 @@ pub(super) fn dispatch_server(
             }
         }
         ServerAction::RecordInput => {
-            if audio.is_recording() {
-                // Stop recording — path comes back via AudioFeedback::RecordingStopped
-                let _ = audio.stop_recording();
+            if !state.recording.recording_tracks.is_empty() {
+                // Takes still being written arrive via AudioFeedback::TakeRecorded
+                audio.stop_take_recording();
+                state.recording.recording_tracks.clear();
+                result.push_status(audio.status(), "Stopping take recording...");
+            } else if audio.is_running() {
+                // Record every armed AudioIn track, or the selected one if none are armed
+                let mut instruments: Vec<_> = state
+                    .instruments
+                    .instruments
+                    .iter()
+                    .filter(|inst| {
+                        inst.source.is_audio_input() && state.recording.is_armed(inst.id)
+                    })
+                    .map(|inst| inst.id)
+                    .collect();
+                if instruments.is_empty() {
+                    if let Some(inst) = state
+                        .instruments
+                        .selected_instrument()
+                        .filter(|inst| inst.source.is_audio_input())
+                    {
+                        instruments.push(inst.id);
+                    }
+                }
+                if instruments.is_empty() {
+                    result.push_status(audio.status(), "Arm an audio input track to record");
+                    return result;
+                }
 
-                // Auto-deactivate AudioIn instrument on stop
-                if let Some(inst) = state.instruments.selected_instrument_mut() {
-                    if inst.source.is_audio_input() && inst.mixer.active {
-                        inst.mixer.active = false;
-                        result.audio_effects.push(AudioEffect::RebuildInstruments);
-                        result.audio_effects.push(AudioEffect::RebuildRouting);
+                // Auto-activate AudioIn instruments on start
+                let mut activated = false;
+                for id in &instruments {
+                    if let Some(inst) = state.instruments.instrument_mut(*id) {
+                        if !inst.mixer.active {
+                            inst.mixer.active = true;
+                            activated = true;
+                        }
                     }
                 }
-                // Don't set pending_recording_path here — AudioFeedback::RecordingStopped handles it
-                result.push_status(audio.status(), "Stopping recording...");
-            } else if audio.is_running() {
-                // Record from the selected instrument's source_out bus
-                if let Some(inst) = state.instruments.selected_instrument() {
-                    let inst_id = inst.id;
-                    // Auto-activate AudioIn instrument on start
-                    if inst.source.is_audio_input() && !inst.mixer.active {
-                        if let Some(inst_mut) = state.instruments.instrument_mut(inst_id) {
-                            inst_mut.mixer.active = true;
+                if activated {
+                    result.audio_effects.push(AudioEffect::RebuildInstruments);
+                    result.audio_effects.push(AudioEffect::RebuildRouting);
+                }
+
+                let file_prefix = super::recording_path("take").with_extension("");
+                match audio.start_take_recording(
+                    instruments.clone(),
+                    &file_prefix,
+                    state.recording.punch_in,
+                    state.recording.punch_out,
+                ) {
+                    Ok(()) => {
+                        state.recording.recording_tracks = instruments.iter().copied().collect();
+                        // Takes follow the transport, so recording starts playback
+                        let pr = &mut state.session.piano_roll;
+                        if !pr.playing {
+                            pr.playing = true;
+                            state.audio.playing = true;
+                            audio.set_playing(true);
                         }
-                        result.audio_effects.push(AudioEffect::RebuildInstruments);
-                        result.audio_effects.push(AudioEffect::RebuildRouting);
+                        result.push_status(
+                            audio.status(),
+                            format!("Recording {} track(s)", instruments.len()),
+                        );
+                    }
+                    Err(e) => {
+                        result.push_status(audio.status(), format!("Recording failed: {}", e));
                     }
-                    let path = super::recording_path(&format!("input_{}", inst_id));
-                    // Bus 0 is hardware out; for instrument recording we use bus 0
-                    // since instruments route through output to bus 0
-                    let _ = audio.start_recording(0, &path);
-                    result.push_status(audio.status(), format!("Recording to {}", path.display()));
                 }
             } else {
                 result.push_status(
@@ -137,6 +175,27 @@ pub(super) fn dispatch_server(
                 );
             }
         }
+        ServerAction::ToggleRecordArm(id) => match state.instruments.instrument(*id) {
+            Some(inst) if inst.source.is_audio_input() => {
+                state.recording.toggle_arm(*id);
+                let msg = if state.recording.is_armed(*id) {
+                    format!("{} armed", inst.name)
+                } else {
+                    format!("{} disarmed", inst.name)
+                };
+                result.push_status(audio.status(), msg);
+            }
+            Some(_) => {
+                result.push_status(audio.status(), "Only audio input tracks can be armed");
+            }
+            None => {}
+        },
+        ServerAction::SetPunchIn(tick) => {
+            state.recording.set_punch_in(*tick);
+        }
+        ServerAction::SetPunchOut(tick) => {
+            state.recording.set_punch_out(*tick);
+        }
         ServerAction::Restart {
             input_device,
             output_device,
@@ -1,8 +1,14 @@
-use crate::action::{AudioEffect, DispatchResult, IoFeedback, NavIntent, PaneId, SessionAction};
+use crate::action::{
+    AudioEffect, DispatchResult, IoFeedback, MidiImportTarget, NavIntent, PaneId, SessionAction,
+};
+use crate::midi::smf;
 use crate::scd_parser;
+use crate::state::preset_library;
 use crate::state::{AppState, CustomSynthDef, ParamSpec};
 use imbolc_audio::AudioHandle;
-use imbolc_types::DomainAction;
+use imbolc_types::{
+    DomainAction, EffectChainOwner, EffectChainPreset, InstrumentId, InstrumentPreset,
+};
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
 
@@ -90,6 +96,108 @@ fn dispatch_load(
     result.push_status(audio.status(), "Loading...");
 }
 
+/// Import a Standard MIDI File, mapping each track/channel part onto an
+/// instrument in order, starting from the selected instrument (piano roll)
+/// or the selected lane (arrangement).
+fn dispatch_import_midi(
+    path: &std::path::Path,
+    target: MidiImportTarget,
+    state: &mut AppState,
+    audio: &mut AudioHandle,
+    result: &mut DispatchResult,
+) {
+    result.push_nav(NavIntent::Pop);
+    let import = match smf::read_smf(path, state.session.piano_roll.ticks_per_beat) {
+        Ok(import) => import,
+        Err(e) => {
+            result.push_status(audio.status(), format!("MIDI import failed: {}", e));
+            return;
+        }
+    };
+
+    let mut settings = state.session.musical_settings();
+    if let Some(bpm) = import.bpm {
+        settings.bpm = bpm.round().clamp(1.0, u16::MAX as f64) as u16;
+    }
+    if let Some(ts) = import.time_signature {
+        settings.time_signature = ts;
+    }
+    state.session.apply_musical_settings(&settings);
+
+    let start = match target {
+        MidiImportTarget::PianoRoll => state.instruments.selected.unwrap_or(0),
+        MidiImportTarget::Arrangement => state.session.arrangement.selected_lane,
+    };
+    // Only note-playing instruments with a piano roll track take parts;
+    // kits, audio inputs and bus inputs are passed over
+    let targets: Vec<_> = state
+        .instruments
+        .instruments
+        .iter()
+        .skip(start)
+        .filter(|inst| {
+            !(inst.source.is_kit() || inst.source.is_audio_input() || inst.source.is_bus_in())
+                && state.session.piano_roll.tracks.contains_key(&inst.id)
+        })
+        .map(|inst| inst.id)
+        .collect();
+
+    let ticks_per_bar = state.session.piano_roll.ticks_per_bar().max(1);
+    let total = import.parts.len();
+    let mut imported = 0;
+    for (part, &instrument_id) in import.parts.into_iter().zip(targets.iter()) {
+        match target {
+            MidiImportTarget::PianoRoll => {
+                if let Some(track) = state.session.piano_roll.tracks.get_mut(&instrument_id) {
+                    track.notes = part.notes;
+                    imported += 1;
+                }
+            }
+            MidiImportTarget::Arrangement => {
+                let arr = &mut state.session.arrangement;
+                let end = part.notes.iter().map(|n| n.tick + n.duration).max();
+                let length = end.unwrap_or(0).div_ceil(ticks_per_bar).max(1) * ticks_per_bar;
+                let clip_id = arr.add_clip(part.name, instrument_id, length);
+                if let Some(clip) = arr.clip_mut(clip_id) {
+                    clip.notes = part.notes;
+                    imported += 1;
+                }
+                let cursor = arr.cursor_tick;
+                arr.add_placement(clip_id, instrument_id, cursor);
+            }
+        }
+    }
+
+    // Grow the loop to cover imported piano roll material
+    if target == MidiImportTarget::PianoRoll && imported > 0 {
+        let pr = &mut state.session.piano_roll;
+        let end = import.length_ticks.div_ceil(ticks_per_bar) * ticks_per_bar;
+        if end > pr.loop_end {
+            pr.loop_end = end;
+        }
+    }
+
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let skipped = total - imported;
+    let status = if skipped > 0 {
+        format!(
+            "Imported {} parts from {} ({} skipped: not enough instruments)",
+            imported, name, skipped
+        )
+    } else {
+        format!("Imported {} parts from {}", imported, name)
+    };
+    result.push_status(audio.status(), status);
+    result.audio_effects.push(AudioEffect::RebuildSession);
+    result.audio_effects.push(AudioEffect::UpdatePianoRoll);
+    if target == MidiImportTarget::Arrangement {
+        result.audio_effects.push(AudioEffect::UpdateAutomation);
+    }
+}
+
 pub(super) fn dispatch_session(
     action: &SessionAction,
     state: &mut AppState,
@@ -232,6 +340,9 @@ pub(super) fn dispatch_session(
             result.push_status(audio.status(), "Importing SynthDef...");
             result.push_nav(NavIntent::Pop);
         }
+        SessionAction::ImportMidiFile(ref path, target) => {
+            dispatch_import_midi(path, *target, state, audio, &mut result);
+        }
         SessionAction::AdjustHumanizeVelocity(_) => {
             imbolc_types::reduce::reduce_action(
                 &DomainAction::Session(action.clone()),
@@ -288,6 +399,7 @@ pub(super) fn dispatch_session(
                 plugin_path: path.clone(),
                 kind,
                 params,
+                latency_secs: None,
             };
 
             let _id = state.session.vst_plugins.add(plugin);
@@ -368,7 +480,300 @@ pub(super) fn dispatch_session(
                 }
             }
         }
+        SessionAction::SaveInstrumentPreset {
+            instrument_id,
+            ref name,
+            ref tags,
+        } => {
+            let msg = match save_instrument_preset(
+                state,
+                &crate::paths::instrument_presets_dir(),
+                *instrument_id,
+                name,
+                tags,
+            ) {
+                Ok(msg) => msg,
+                Err(e) => format!("Preset not saved: {}", e),
+            };
+            result.push_status(audio.status(), msg);
+        }
+        SessionAction::DeleteInstrumentPreset(path) => {
+            let msg = match crate::state::preset_library::delete_preset(path) {
+                Ok(()) => "Preset deleted".to_string(),
+                Err(e) => format!("Preset not deleted: {}", e),
+            };
+            result.push_status(audio.status(), msg);
+        }
+        SessionAction::SaveEffectChainPreset {
+            owner,
+            ref name,
+            ref tags,
+        } => {
+            let msg = match save_effect_chain_preset(
+                state,
+                &crate::paths::effect_chain_presets_dir(),
+                *owner,
+                name,
+                tags,
+            ) {
+                Ok(msg) => msg,
+                Err(e) => format!("Chain preset not saved: {}", e),
+            };
+            result.push_status(audio.status(), msg);
+        }
     }
 
     result
 }
+
+/// Write an instrument's patch to the preset library in `dir`. VST effects
+/// are left out since their plugin ids only mean something in this project.
+fn save_instrument_preset(
+    state: &AppState,
+    dir: &std::path::Path,
+    instrument_id: InstrumentId,
+    name: &str,
+    tags: &[String],
+) -> Result<String, String> {
+    let instrument = state
+        .instruments
+        .instrument(instrument_id)
+        .ok_or("Instrument not found")?;
+    let mut preset = InstrumentPreset::from_instrument(instrument, name.trim(), tags.to_vec())?;
+    let skipped = preset.strip_vst_effects();
+    preset_library::save_preset(dir, &preset)?;
+    Ok(if skipped > 0 {
+        format!(
+            "Saved preset '{}' ({} VST effect(s) left out)",
+            preset.name, skipped
+        )
+    } else {
+        format!("Saved preset '{}'", preset.name)
+    })
+}
+
+/// Write an instrument, bus or layer group effect chain to the chain
+/// preset library in `dir`.
+fn save_effect_chain_preset(
+    state: &AppState,
+    dir: &std::path::Path,
+    owner: EffectChainOwner,
+    name: &str,
+    tags: &[String],
+) -> Result<String, String> {
+    let registry = &state.session.vst_plugins;
+    let name = name.trim();
+    let preset = match owner {
+        EffectChainOwner::Instrument(id) => {
+            let instrument = state
+                .instruments
+                .instrument(id)
+                .ok_or("Instrument not found")?;
+            EffectChainPreset::from_stages(
+                name,
+                tags.to_vec(),
+                &instrument.processing_chain,
+                registry,
+            )
+        }
+        EffectChainOwner::Bus(id) => {
+            let bus = state.session.bus(id).ok_or("Bus not found")?;
+            EffectChainPreset::from_effects(
+                name,
+                tags.to_vec(),
+                &bus.effect_chain.effects,
+                registry,
+            )
+        }
+        EffectChainOwner::LayerGroup(id) => {
+            let group = state
+                .session
+                .mixer
+                .layer_group_mixer(id)
+                .ok_or("Layer group not found")?;
+            EffectChainPreset::from_effects(
+                name,
+                tags.to_vec(),
+                &group.effect_chain.effects,
+                registry,
+            )
+        }
+    };
+    if preset.stages.is_empty() {
+        return Err("chain is empty".to_string());
+    }
+    preset_library::save_chain_preset(dir, &preset)?;
+    Ok(format!(
+        "Saved chain preset '{}' ({} stage(s))",
+        preset.name,
+        preset.stages.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::arrangement::PlayMode;
+    use crate::state::SourceType;
+
+    /// Two-track format 1 file at 96 PPQ: 90 BPM, 3/4, one note on channels 1 and 2.
+    fn write_midi_file(dir: &std::path::Path) -> PathBuf {
+        let mut data = b"MThd".to_vec();
+        data.extend(6u32.to_be_bytes());
+        data.extend([0, 1, 0, 2, 0, 96]);
+        for events in [
+            vec![
+                0x00, 0xFF, 0x51, 0x03, 0x0A, 0x2C, 0x2A, // 666666 us/quarter
+                0x00, 0xFF, 0x58, 0x04, 3, 2, 24, 8,
+            ],
+            vec![
+                0x00, 0x90, 60, 100, 0x60, 0x80, 60, 0, 0x00, 0x91, 64, 70, 0x60, 0x81, 64, 0,
+            ],
+        ] {
+            data.extend(b"MTrk");
+            data.extend((events.len() as u32 + 4).to_be_bytes());
+            data.extend(events);
+            data.extend([0x00, 0xFF, 0x2F, 0x00]);
+        }
+        let path = dir.join("part.mid");
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn import_midi_into_piano_roll_tracks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_midi_file(dir.path());
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let (io_tx, _io_rx) = std::sync::mpsc::channel();
+        let a = state.add_instrument(SourceType::Saw);
+        let kit = state.add_instrument(SourceType::Kit);
+        let b = state.add_instrument(SourceType::Sin);
+        state.instruments.selected = Some(0);
+
+        let action = SessionAction::ImportMidiFile(path, MidiImportTarget::PianoRoll);
+        let result = dispatch_session(&action, &mut state, &mut audio, &io_tx);
+
+        assert!(result.audio_effects.contains(&AudioEffect::UpdatePianoRoll));
+        assert_eq!(state.session.bpm, 90);
+        assert_eq!(state.session.time_signature, (3, 4));
+        assert_eq!(state.session.piano_roll.time_signature, (3, 4));
+        let notes_a = &state.session.piano_roll.tracks[&a].notes;
+        assert_eq!(notes_a.len(), 1);
+        assert_eq!((notes_a[0].pitch, notes_a[0].duration), (60, 480));
+        // The kit is passed over, so the second part lands on the next synth
+        assert!(state.session.piano_roll.tracks[&kit].notes.is_empty());
+        let notes_b = &state.session.piano_roll.tracks[&b].notes;
+        assert_eq!((notes_b[0].pitch, notes_b[0].velocity), (64, 70));
+    }
+
+    #[test]
+    fn import_midi_as_arrangement_clips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_midi_file(dir.path());
+        let mut state = AppState::new();
+        let mut audio = AudioHandle::new();
+        let (io_tx, _io_rx) = std::sync::mpsc::channel();
+        let _a = state.add_instrument(SourceType::Saw);
+        let b = state.add_instrument(SourceType::Sin);
+        state.session.arrangement.play_mode = PlayMode::Song;
+        state.session.arrangement.selected_lane = 1;
+        state.session.arrangement.cursor_tick = 960;
+
+        let action = SessionAction::ImportMidiFile(path, MidiImportTarget::Arrangement);
+        let _ = dispatch_session(&action, &mut state, &mut audio, &io_tx);
+
+        // Only one instrument from lane 1 onwards: the second part is skipped
+        let arr = &state.session.arrangement;
+        assert_eq!(arr.clips.len(), 1);
+        assert_eq!(arr.clips[0].instrument_id, b);
+        assert_eq!(arr.clips[0].notes.len(), 1);
+        assert_eq!(arr.clips[0].length_ticks, 480 * 3);
+        assert_eq!(arr.placements.len(), 1);
+        assert_eq!(arr.placements[0].start_tick, 960);
+    }
+
+    #[test]
+    fn save_instrument_preset_leaves_out_vst_effects() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::new();
+        let id = state.add_instrument(SourceType::Organ);
+        let inst = state.instruments.instrument_mut(id).unwrap();
+        inst.add_effect(imbolc_types::EffectType::Reverb);
+        inst.add_effect(imbolc_types::EffectType::Vst(
+            imbolc_types::VstPluginId::new(0),
+        ));
+
+        let msg =
+            save_instrument_preset(&state, dir.path(), id, " Church ", &["keys".into()]).unwrap();
+        assert_eq!(msg, "Saved preset 'Church' (1 VST effect(s) left out)");
+
+        let library = preset_library::PresetLibrary::load_from(dir.path());
+        let saved = &library.entries[0].preset;
+        assert_eq!(saved.source, SourceType::Organ);
+        assert_eq!(saved.processing_chain.len(), 1);
+        assert_eq!(saved.tags, vec!["keys".to_string()]);
+    }
+
+    #[test]
+    fn save_bus_chain_preset_and_insert_into_instrument() {
+        use imbolc_types::{BusId, EffectType};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::new();
+        let bus_id = BusId::new(1);
+        let bus = state.session.bus_mut(bus_id).unwrap();
+        bus.effect_chain.add_effect(EffectType::ParaEq);
+        bus.effect_chain.add_effect(EffectType::TapeComp);
+        bus.effect_chain.add_effect(EffectType::SpringReverb);
+
+        let msg = save_effect_chain_preset(
+            &state,
+            dir.path(),
+            EffectChainOwner::Bus(bus_id),
+            "Vocal",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(msg, "Saved chain preset 'Vocal' (3 stage(s))");
+
+        let library = preset_library::EffectChainLibrary::load_from(dir.path());
+        let preset = library.entries[0].preset.clone();
+        let id = state.add_instrument(SourceType::AudioIn);
+        state
+            .instruments
+            .instrument_mut(id)
+            .unwrap()
+            .add_effect(EffectType::Delay);
+        imbolc_types::reduce::reduce_action(
+            &DomainAction::Instrument(imbolc_types::InstrumentAction::AddEffectChain(
+                id,
+                Box::new(preset),
+            )),
+            &mut state.instruments,
+            &mut state.session,
+        );
+        let inst = state.instruments.instrument(id).unwrap();
+        let types: Vec<EffectType> = inst.effects().map(|e| e.effect_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                EffectType::Delay,
+                EffectType::ParaEq,
+                EffectType::TapeComp,
+                EffectType::SpringReverb
+            ]
+        );
+        assert_eq!(inst.next_effect_id.get(), 4);
+
+        let empty = save_effect_chain_preset(
+            &state,
+            dir.path(),
+            EffectChainOwner::Bus(BusId::new(2)),
+            "Empty",
+            &[],
+        );
+        assert!(empty.is_err());
+    }
+}
@@ -1,7 +1,17 @@
 #![allow(dead_code)]
 
+pub mod output;
+pub mod smf;
+
+pub use output::MidiOutputManager;
+
+use imbolc_audio::midi_clock::{
+    MidiClockEvent, MidiClockMessage, MidiClockReceiver, MidiClockSender,
+};
 use midir::{MidiInput, MidiInputConnection};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 /// MIDI event types with optional timestamp for sample-accurate scheduling.
 /// Timestamp is in microseconds from a driver-specific epoch.
@@ -85,11 +95,19 @@ pub struct MidiInputManager {
     event_sender: Option<Sender<MidiEvent>>,
     connected_port_name: Option<String>,
     available_ports: Vec<MidiPortInfo>,
+    /// Clock / transport messages bypass `poll_events` and go straight to the
+    /// audio thread, stamped on arrival
+    clock_sender: MidiClockSender,
+    clock_receiver: Option<MidiClockReceiver>,
+    /// Driver timestamp and arrival time of the first event on the current
+    /// connection, relating driver timestamps to the wall clock
+    timestamp_anchor: Arc<OnceLock<(u64, Instant)>>,
 }
 
 impl MidiInputManager {
     pub fn new() -> Self {
         let midi_in = MidiInput::new("imbolc").ok();
+        let (clock_sender, clock_receiver) = crossbeam_channel::unbounded();
         Self {
             midi_in,
             connection: None,
@@ -97,9 +115,18 @@ impl MidiInputManager {
             event_sender: None,
             connected_port_name: None,
             available_ports: Vec::new(),
+            clock_sender,
+            clock_receiver: Some(clock_receiver),
+            timestamp_anchor: Arc::default(),
         }
     }
 
+    /// Take the receiving end for incoming MIDI clock (see
+    /// `AudioHandle::set_midi_clock_input`). Returns `None` after the first call.
+    pub fn take_clock_receiver(&mut self) -> Option<MidiClockReceiver> {
+        self.clock_receiver.take()
+    }
+
     /// Refresh the list of available MIDI input ports
     pub fn refresh_ports(&mut self) {
         self.available_ports.clear();
@@ -150,13 +177,22 @@ impl MidiInputManager {
         let (tx, rx) = mpsc::channel();
         self.event_sender = Some(tx.clone());
         self.event_receiver = Some(rx);
+        let clock_tx = self.clock_sender.clone();
+        self.timestamp_anchor = Arc::default();
+        let anchor = Arc::clone(&self.timestamp_anchor);
 
         let connection = midi_in
             .connect(
                 port,
                 "imbolc-input",
                 move |timestamp, message, _| {
-                    if let Some(kind) = parse_midi_message(message) {
+                    if let Some(clock) = MidiClockMessage::parse(message) {
+                        let _ = clock_tx.send(MidiClockEvent {
+                            message: clock,
+                            at: Instant::now(),
+                        });
+                    } else if let Some(kind) = parse_midi_message(message) {
+                        anchor.get_or_init(|| (timestamp, Instant::now()));
                         let _ = tx.send(MidiEvent::new(timestamp, kind));
                     }
                 },
@@ -198,6 +234,24 @@ impl MidiInputManager {
     pub fn poll_event(&self) -> Option<MidiEvent> {
         self.event_receiver.as_ref()?.try_recv().ok()
     }
+
+    /// How long ago a polled event was played, in microseconds, judged by its
+    /// driver timestamp. 0 before any event has arrived.
+    pub fn event_age_us(&self, event: &MidiEvent) -> u64 {
+        match self.timestamp_anchor.get() {
+            Some(&(anchor_us, arrived)) => {
+                age_since_anchor(anchor_us, arrived.elapsed(), event.timestamp_us)
+            }
+            None => 0,
+        }
+    }
+}
+
+/// Age of an event stamped `timestamp_us`, given the anchor event's stamp and
+/// the time elapsed since the anchor arrived
+fn age_since_anchor(anchor_us: u64, elapsed: Duration, timestamp_us: u64) -> u64 {
+    let after_anchor = timestamp_us.saturating_sub(anchor_us);
+    (elapsed.as_micros() as u64).saturating_sub(after_anchor)
 }
 
 impl Default for MidiInputManager {
@@ -421,6 +475,16 @@ mod tests {
         assert!(parse_midi_message(&[0xE0, 0x00]).is_none());
     }
 
+    #[test]
+    fn test_event_age_from_timestamp_anchor() {
+        let elapsed = Duration::from_millis(10);
+        // Stamped 4 ms after the anchor, 10 ms after the anchor arrived
+        assert_eq!(age_since_anchor(1_000, elapsed, 5_000), 6_000);
+        assert_eq!(age_since_anchor(1_000, elapsed, 1_000), 10_000);
+        // Stamps ahead of the clock count as just played
+        assert_eq!(age_since_anchor(1_000, elapsed, 20_000), 0);
+    }
+
     #[test]
     fn test_parse_unknown_status_returns_none() {
         assert!(parse_midi_message(&[0x00]).is_none());
@@ -0,0 +1,344 @@
+//! MIDI output to external hardware and soft synths.
+//!
+//! The audio engine schedules note, CC and pitch bend messages for
+//! `SourceType::MidiOut` instruments with the same lookahead it uses for OSC
+//! bundles. `MidiOutputManager` owns the scheduler thread that holds each
+//! event until its due time and writes it to the port, opening ports lazily
+//! on first use.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use imbolc_audio::midi_out::{MidiOutEvent, MidiOutSender};
+use midir::{MidiOutput, MidiOutputConnection};
+
+use super::MidiPortInfo;
+
+/// Destination for raw MIDI bytes on one output port.
+pub trait MidiSink: Send {
+    fn send(&mut self, message: &[u8]) -> Result<(), String>;
+}
+
+impl MidiSink for MidiOutputConnection {
+    fn send(&mut self, message: &[u8]) -> Result<(), String> {
+        MidiOutputConnection::send(self, message).map_err(|e| e.to_string())
+    }
+}
+
+/// Open ports keyed by port index, plus ports that failed to open (not
+/// retried until the port list is refreshed). `attached` marks indices routed
+/// to a custom sink rather than a device port.
+#[derive(Default)]
+struct Outputs {
+    sinks: HashMap<u8, Box<dyn MidiSink>>,
+    unavailable: HashSet<u8>,
+    attached: HashSet<u8>,
+}
+
+impl Outputs {
+    /// Close device ports so they reopen by their new index, and retry ports
+    /// that failed to open. Custom sinks stay attached.
+    fn reset_devices(&mut self) {
+        let attached = &self.attached;
+        self.sinks.retain(|port, _| attached.contains(port));
+        self.unavailable.clear();
+    }
+
+    fn deliver(&mut self, event: &MidiOutEvent) {
+        if !self.sinks.contains_key(&event.port) {
+            if self.unavailable.contains(&event.port) {
+                return;
+            }
+            match open_port(event.port) {
+                Ok(conn) => {
+                    self.sinks.insert(event.port, Box::new(conn));
+                }
+                Err(e) => {
+                    log::warn!(target: "midi::output", "MIDI output port {}: {}", event.port, e);
+                    self.unavailable.insert(event.port);
+                    return;
+                }
+            }
+        }
+        if let Some(sink) = self.sinks.get_mut(&event.port) {
+            if let Err(e) = sink.send(event.bytes()) {
+                // Drop the dead connection; the next event reopens the port
+                log::warn!(target: "midi::output", "MIDI output send failed: {}", e);
+                self.sinks.remove(&event.port);
+                self.attached.remove(&event.port);
+            }
+        }
+    }
+}
+
+fn open_port(index: u8) -> Result<MidiOutputConnection, String> {
+    let midi_out = MidiOutput::new("imbolc").map_err(|e| e.to_string())?;
+    let ports = midi_out.ports();
+    let port = ports
+        .get(index as usize)
+        .ok_or_else(|| format!("Invalid port index: {}", index))?;
+    midi_out
+        .connect(port, "imbolc-output")
+        .map_err(|e| e.to_string())
+}
+
+/// MIDI output manager
+pub struct MidiOutputManager {
+    midi_out: Option<MidiOutput>,
+    available_ports: Vec<MidiPortInfo>,
+    outputs: Arc<Mutex<Outputs>>,
+    event_sender: Option<MidiOutSender>,
+}
+
+impl MidiOutputManager {
+    pub fn new() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let outputs = Arc::new(Mutex::new(Outputs::default()));
+        let thread_outputs = Arc::clone(&outputs);
+        // The scheduler exits (flushing queued events) once every sender,
+        // ours and the audio engine's, has been dropped.
+        let spawned = std::thread::Builder::new()
+            .name("imbolc-midi-out".to_string())
+            .spawn(move || run_scheduler(rx, thread_outputs));
+        Self {
+            midi_out: MidiOutput::new("imbolc").ok(),
+            available_ports: Vec::new(),
+            outputs,
+            event_sender: spawned.is_ok().then_some(tx),
+        }
+    }
+
+    /// Sender for the audio engine (see `AudioHandle::set_midi_output`)
+    pub fn sender(&self) -> Option<MidiOutSender> {
+        self.event_sender.clone()
+    }
+
+    /// Refresh the list of available MIDI output ports. Open device ports are
+    /// closed, since indices may now name different devices, and ports that
+    /// failed to open are retried on their next event.
+    pub fn refresh_ports(&mut self) {
+        self.available_ports.clear();
+        if let Some(ref midi_out) = self.midi_out {
+            for (index, port) in midi_out.ports().iter().enumerate() {
+                if let Ok(name) = midi_out.port_name(port) {
+                    self.available_ports.push(MidiPortInfo { index, name });
+                }
+            }
+        }
+        if let Ok(mut outputs) = self.outputs.lock() {
+            outputs.reset_devices();
+        }
+    }
+
+    /// Get list of available MIDI output ports
+    pub fn list_ports(&self) -> &[MidiPortInfo] {
+        &self.available_ports
+    }
+
+    /// Route a port index to a custom sink instead of a device port.
+    pub fn attach_sink(&self, port: u8, sink: Box<dyn MidiSink>) {
+        if let Ok(mut outputs) = self.outputs.lock() {
+            outputs.unavailable.remove(&port);
+            outputs.attached.insert(port);
+            outputs.sinks.insert(port, sink);
+        }
+    }
+
+    /// Close all open output ports
+    pub fn disconnect_all(&self) {
+        if let Ok(mut outputs) = self.outputs.lock() {
+            outputs.sinks.clear();
+            outputs.attached.clear();
+        }
+    }
+}
+
+impl Default for MidiOutputManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Queued event: (due, sequence, event). The sequence keeps same-time events
+/// in send order.
+type Pending = Reverse<(std::time::Instant, u64, MidiOutEvent)>;
+
+/// Hold events until due and deliver them in time order. Exits when all
+/// senders are dropped, flushing anything still queued.
+fn run_scheduler(rx: Receiver<MidiOutEvent>, outputs: Arc<Mutex<Outputs>>) {
+    let mut queue: BinaryHeap<Pending> = BinaryHeap::new();
+    let mut sequence = 0u64;
+    let mut disconnected = false;
+
+    while !disconnected || !queue.is_empty() {
+        let received = match queue.peek() {
+            Some(Reverse((due, ..))) => rx.recv_deadline(*due),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(event) => {
+                queue.push(Reverse((event.due, sequence, event)));
+                sequence += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => disconnected = true,
+        }
+
+        let now = std::time::Instant::now();
+        while let Some(Reverse((due, ..))) = queue.peek() {
+            if *due > now && !disconnected {
+                break;
+            }
+            let Some(Reverse((_, _, event))) = queue.pop() else {
+                break;
+            };
+            if let Ok(mut outputs) = outputs.lock() {
+                outputs.deliver(&event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    type Received = Arc<Mutex<Vec<(Instant, Vec<u8>)>>>;
+
+    struct RecordingSink(Received);
+
+    impl MidiSink for RecordingSink {
+        fn send(&mut self, message: &[u8]) -> Result<(), String> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((Instant::now(), message.to_vec()));
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    impl MidiSink for FailingSink {
+        fn send(&mut self, _message: &[u8]) -> Result<(), String> {
+            Err("port closed".to_string())
+        }
+    }
+
+    #[test]
+    fn failed_send_drops_the_sink() {
+        let mut outputs = Outputs::default();
+        outputs.attached.insert(5);
+        outputs.sinks.insert(5, Box::new(FailingSink));
+        outputs.deliver(&MidiOutEvent::after(5, [0x90, 60, 100], 0.0));
+        assert!(!outputs.sinks.contains_key(&5));
+        assert!(!outputs.attached.contains(&5));
+    }
+
+    #[test]
+    fn reset_devices_keeps_attached_sinks() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut outputs = Outputs::default();
+        outputs.attached.insert(1);
+        outputs
+            .sinks
+            .insert(1, Box::new(RecordingSink(Arc::clone(&received))));
+        outputs
+            .sinks
+            .insert(2, Box::new(RecordingSink(Arc::clone(&received))));
+        outputs.unavailable.insert(3);
+
+        outputs.reset_devices();
+        assert!(outputs.sinks.contains_key(&1));
+        assert!(!outputs.sinks.contains_key(&2));
+        assert!(outputs.unavailable.is_empty());
+    }
+
+    #[test]
+    fn scheduler_delivers_in_due_order() {
+        let manager = MidiOutputManager::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        manager.attach_sink(3, Box::new(RecordingSink(Arc::clone(&received))));
+
+        let start = Instant::now();
+        let tx = manager.sender().unwrap();
+        tx.send(MidiOutEvent::after(3, [0x80, 60, 0], 0.060))
+            .unwrap();
+        tx.send(MidiOutEvent::after(3, [0x90, 60, 100], 0.020))
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while received.lock().unwrap().len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let received = received.lock().unwrap();
+        let messages: Vec<&Vec<u8>> = received.iter().map(|(_, m)| m).collect();
+        assert_eq!(messages, vec![&vec![0x90, 60, 100], &vec![0x80, 60, 0]]);
+        // Held until due, not sent on arrival
+        assert!(received[0].0 >= start + Duration::from_millis(20));
+        assert!(received[1].0 >= start + Duration::from_millis(60));
+    }
+
+    #[test]
+    fn pending_events_flush_when_senders_drop() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        {
+            let manager = MidiOutputManager::new();
+            manager.attach_sink(0, Box::new(RecordingSink(Arc::clone(&received))));
+            manager
+                .sender()
+                .unwrap()
+                .send(MidiOutEvent::after(0, [0x80, 64, 0], 0.010))
+                .unwrap();
+        }
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while received.lock().unwrap().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    /// Loops notes through a virtual ALSA port; skipped when no sequencer is
+    /// available (e.g. containers without /dev/snd/seq).
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sends_to_virtual_alsa_port() {
+        use midir::os::unix::VirtualInput;
+        use midir::MidiInput;
+
+        let Ok(input) = MidiInput::new("imbolc-test") else {
+            return;
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let Ok(_conn) = input.create_virtual(
+            "imbolc-test-in",
+            move |_, message, _| {
+                let _ = tx.send(message.to_vec());
+            },
+            (),
+        ) else {
+            return;
+        };
+
+        let mut manager = MidiOutputManager::new();
+        manager.refresh_ports();
+        let port = manager
+            .list_ports()
+            .iter()
+            .find(|p| p.name.contains("imbolc-test-in"))
+            .expect("virtual port listed")
+            .index as u8;
+        manager
+            .sender()
+            .unwrap()
+            .send(MidiOutEvent::after(port, [0x91, 62, 90], 0.0))
+            .unwrap();
+
+        let message = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(message, vec![0x91, 62, 90]);
+    }
+}
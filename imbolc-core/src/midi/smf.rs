@@ -0,0 +1,903 @@
+//! Standard MIDI File (SMF) import and export.
+//!
+//! Import parses format 0 and format 1 files into note parts — one per MIDI
+//! track and channel pair — with tick positions rescaled from the file's PPQ to
+//! the project's `ticks_per_beat`. Export writes a format 1 file with a
+//! conductor track (tempo, time signature) followed by one track per instrument.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+
+use imbolc_types::Note;
+
+use crate::state::arrangement::PlayMode;
+use crate::state::drum_sequencer::DrumSequencerState;
+use crate::state::{InstrumentState, SessionState};
+
+/// General MIDI percussion channel (channel 10, zero-based)
+pub const GM_DRUM_CHANNEL: u8 = 9;
+
+/// Result of parsing a Standard MIDI File.
+#[derive(Debug, Clone)]
+pub struct SmfImport {
+    /// Tempo from the earliest Set Tempo meta event, in BPM
+    pub bpm: Option<f64>,
+    /// Time signature from the earliest Time Signature meta event
+    pub time_signature: Option<(u8, u8)>,
+    /// Note parts in file order (only track/channel pairs that contain notes)
+    pub parts: Vec<SmfPart>,
+    /// End tick of the last note, in project ticks
+    pub length_ticks: u32,
+}
+
+/// Notes from a single MIDI track/channel pair.
+#[derive(Debug, Clone)]
+pub struct SmfPart {
+    pub name: String,
+    pub track_index: usize,
+    /// Zero-based MIDI channel
+    pub channel: u8,
+    pub notes: Vec<Note>,
+}
+
+/// Read and parse a Standard MIDI File from disk.
+pub fn read_smf(path: &Path, ticks_per_beat: u32) -> Result<SmfImport, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    parse_smf(&data, ticks_per_beat)
+}
+
+/// Parse Standard MIDI File bytes, rescaling ticks to `ticks_per_beat`.
+pub fn parse_smf(data: &[u8], ticks_per_beat: u32) -> Result<SmfImport, String> {
+    let mut reader = Reader::new(data);
+
+    if reader.take(4)? != b"MThd" {
+        return Err("Not a Standard MIDI File (missing MThd header)".to_string());
+    }
+    let header_len = reader.read_u32()? as usize;
+    if header_len < 6 {
+        return Err("Invalid MThd header length".to_string());
+    }
+    let format = reader.read_u16()?;
+    let num_tracks = reader.read_u16()?;
+    let division = reader.read_u16()?;
+    reader.take(header_len - 6)?;
+
+    if format > 1 {
+        return Err(format!("Unsupported SMF format {}", format));
+    }
+    if division & 0x8000 != 0 {
+        return Err("SMPTE time division is not supported".to_string());
+    }
+    let ppq = division as u64;
+    if ppq == 0 {
+        return Err("Invalid PPQ (0)".to_string());
+    }
+    let scale = |tick: u64| -> u32 {
+        ((tick * ticks_per_beat as u64 + ppq / 2) / ppq).min(u32::MAX as u64) as u32
+    };
+
+    let mut tempo: Option<(u64, u32)> = None;
+    let mut time_signature: Option<(u64, (u8, u8))> = None;
+    let mut parts = Vec::new();
+    let mut length_ticks = 0u32;
+    let mut track_index = 0usize;
+
+    while !reader.is_empty() && track_index < num_tracks as usize {
+        let chunk_id = reader.take(4)?;
+        let chunk_len = reader.read_u32()? as usize;
+        let chunk = reader.take(chunk_len)?;
+        if chunk_id != b"MTrk" {
+            // Unknown chunk types must be skipped per the SMF spec
+            continue;
+        }
+
+        let track = parse_track(chunk)?;
+        if let Some((tick, us)) = track.tempo {
+            if tempo.is_none_or(|(t, _)| tick < t) {
+                tempo = Some((tick, us));
+            }
+        }
+        if let Some((tick, ts)) = track.time_signature {
+            if time_signature.is_none_or(|(t, _)| tick < t) {
+                time_signature = Some((tick, ts));
+            }
+        }
+
+        let multi_channel = track.notes.len() > 1;
+        for (channel, raw_notes) in track.notes {
+            let mut notes: Vec<Note> = raw_notes
+                .iter()
+                .map(|n| {
+                    let tick = scale(n.start);
+                    let end = scale(n.end);
+                    Note {
+                        tick,
+                        duration: end.saturating_sub(tick).max(1),
+                        pitch: n.pitch,
+                        velocity: n.velocity,
+                        probability: 1.0,
+                        expression: None,
+                    }
+                })
+                .collect();
+            notes.sort_by_key(|n| (n.tick, n.pitch));
+            for note in &notes {
+                length_ticks = length_ticks.max(note.tick + note.duration);
+            }
+
+            let base = track
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Track {}", track_index + 1));
+            let name = if multi_channel || (format == 0 && track.name.is_none()) {
+                format!("{} ch{}", base, channel + 1)
+            } else {
+                base
+            };
+            parts.push(SmfPart {
+                name,
+                track_index,
+                channel,
+                notes,
+            });
+        }
+        track_index += 1;
+    }
+
+    Ok(SmfImport {
+        bpm: tempo.map(|(_, us)| 60_000_000.0 / us as f64),
+        time_signature: time_signature.map(|(_, ts)| ts),
+        parts,
+        length_ticks,
+    })
+}
+
+/// A note with absolute tick positions in file PPQ.
+struct RawNote {
+    start: u64,
+    end: u64,
+    pitch: u8,
+    velocity: u8,
+}
+
+struct ParsedTrack {
+    name: Option<String>,
+    tempo: Option<(u64, u32)>,
+    time_signature: Option<(u64, (u8, u8))>,
+    /// Notes grouped by channel
+    notes: BTreeMap<u8, Vec<RawNote>>,
+}
+
+fn parse_track(data: &[u8]) -> Result<ParsedTrack, String> {
+    let mut reader = Reader::new(data);
+    let mut track = ParsedTrack {
+        name: None,
+        tempo: None,
+        time_signature: None,
+        notes: BTreeMap::new(),
+    };
+    // Open notes per (channel, pitch): start tick and velocity, first-in first-out
+    let mut open: HashMap<(u8, u8), VecDeque<(u64, u8)>> = HashMap::new();
+    let mut tick = 0u64;
+    let mut running_status: Option<u8> = None;
+
+    while !reader.is_empty() {
+        tick += reader.read_vlq()? as u64;
+        let byte = reader.read_u8()?;
+
+        match byte {
+            0xFF => {
+                let meta_type = reader.read_u8()?;
+                let len = reader.read_vlq()? as usize;
+                let payload = reader.take(len)?;
+                match meta_type {
+                    0x03 if track.name.is_none() => {
+                        let name = String::from_utf8_lossy(payload).trim().to_string();
+                        if !name.is_empty() {
+                            track.name = Some(name);
+                        }
+                    }
+                    0x51 if payload.len() >= 3 && track.tempo.is_none() => {
+                        let us = ((payload[0] as u32) << 16)
+                            | ((payload[1] as u32) << 8)
+                            | payload[2] as u32;
+                        if us > 0 {
+                            track.tempo = Some((tick, us));
+                        }
+                    }
+                    0x58 if payload.len() >= 2 && track.time_signature.is_none() => {
+                        let denom = 1u32 << payload[1].min(7);
+                        track.time_signature = Some((tick, (payload[0], denom as u8)));
+                    }
+                    0x2F => break,
+                    _ => {}
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len = reader.read_vlq()? as usize;
+                reader.take(len)?;
+                running_status = None;
+            }
+            _ => {
+                let (status, first) = if byte & 0x80 != 0 {
+                    running_status = Some(byte);
+                    (byte, reader.read_u8()?)
+                } else {
+                    let status = running_status
+                        .ok_or_else(|| "Running status without a prior status byte".to_string())?;
+                    (status, byte)
+                };
+                let channel = status & 0x0F;
+                match status & 0xF0 {
+                    0x90 | 0x80 => {
+                        let velocity = reader.read_u8()?;
+                        let pitch = first & 0x7F;
+                        if status & 0xF0 == 0x90 && velocity > 0 {
+                            open.entry((channel, pitch))
+                                .or_default()
+                                .push_back((tick, velocity));
+                        } else if let Some((start, vel)) =
+                            open.get_mut(&(channel, pitch)).and_then(|q| q.pop_front())
+                        {
+                            track.notes.entry(channel).or_default().push(RawNote {
+                                start,
+                                end: tick,
+                                pitch,
+                                velocity: vel,
+                            });
+                        }
+                    }
+                    0xC0 | 0xD0 => {}
+                    _ => {
+                        reader.read_u8()?;
+                    }
+                }
+            }
+        }
+    }
+
+    // Close any notes left hanging at the end of the track
+    for ((channel, pitch), starts) in open {
+        for (start, velocity) in starts {
+            track.notes.entry(channel).or_default().push(RawNote {
+                start,
+                end: tick,
+                pitch,
+                velocity,
+            });
+        }
+    }
+
+    Ok(track)
+}
+
+/// Big-endian byte reader over a chunk of SMF data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| "Unexpected end of MIDI data".to_string())?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a variable-length quantity (at most 4 bytes).
+    fn read_vlq(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.read_u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Variable-length quantity too long".to_string())
+    }
+}
+
+/// A track to be written by [`write_smf`].
+#[derive(Debug, Clone)]
+pub struct SmfExportTrack {
+    pub name: String,
+    /// Zero-based MIDI channel
+    pub channel: u8,
+    pub notes: Vec<Note>,
+}
+
+/// Write an export to disk as a format 1 Standard MIDI File.
+pub fn export_smf(
+    path: &Path,
+    session: &SessionState,
+    instruments: &InstrumentState,
+) -> Result<usize, String> {
+    let tracks = collect_export_tracks(session, instruments);
+    if tracks.is_empty() {
+        return Err("no notes to export".to_string());
+    }
+    let data = write_smf(
+        &tracks,
+        session.bpm as f64,
+        session.time_signature,
+        session.piano_roll.ticks_per_beat,
+    )?;
+    std::fs::write(path, data).map_err(|e| e.to_string())?;
+    Ok(tracks.len())
+}
+
+/// Build one export track per instrument with material.
+///
+/// Song mode flattens arrangement placements into a single timeline; Pattern
+/// mode uses the piano roll tracks. Kit instruments render their drum patterns
+/// (or pattern chain) onto the GM percussion channel across the export length.
+pub fn collect_export_tracks(
+    session: &SessionState,
+    instruments: &InstrumentState,
+) -> Vec<SmfExportTrack> {
+    let pr = &session.piano_roll;
+    let song_mode = session.arrangement.play_mode == PlayMode::Song;
+    let mut flattened = if song_mode {
+        session.arrangement.flatten_to_notes()
+    } else {
+        HashMap::new()
+    };
+
+    let note_end = |notes: &[Note]| notes.iter().map(|n| n.tick + n.duration).max();
+    let length = if song_mode {
+        session.arrangement.arrangement_length()
+    } else {
+        pr.tracks
+            .values()
+            .filter_map(|t| note_end(&t.notes))
+            .fold(pr.loop_end, u32::max)
+    };
+
+    let mut tracks = Vec::new();
+    let mut next_channel = 0u8;
+    for inst in &instruments.instruments {
+        if let Some(seq) = inst.drum_sequencer() {
+            let notes = render_drum_pattern(seq, pr.ticks_per_beat, length);
+            if !notes.is_empty() {
+                tracks.push(SmfExportTrack {
+                    name: inst.name.clone(),
+                    channel: GM_DRUM_CHANNEL,
+                    notes,
+                });
+            }
+            continue;
+        }
+
+        let mut notes = if song_mode {
+            flattened.remove(&inst.id).unwrap_or_default()
+        } else {
+            pr.tracks
+                .get(&inst.id)
+                .map(|t| t.notes.clone())
+                .unwrap_or_default()
+        };
+        if notes.is_empty() {
+            continue;
+        }
+        notes.sort_by_key(|n| (n.tick, n.pitch));
+
+        tracks.push(SmfExportTrack {
+            name: inst.name.clone(),
+            channel: next_channel,
+            notes,
+        });
+        // Melodic tracks cycle through the 15 non-percussion channels
+        next_channel = (next_channel + 1) % 16;
+        if next_channel == GM_DRUM_CHANNEL {
+            next_channel += 1;
+        }
+    }
+    tracks
+}
+
+/// Render a drum sequencer's patterns to GM drum notes from tick 0 up to `length`.
+///
+/// Follows the pattern chain when enabled, applies the sequencer's swing to
+/// odd steps and maps each pad to a GM percussion note.
+pub fn render_drum_pattern(
+    seq: &DrumSequencerState,
+    ticks_per_beat: u32,
+    length: u32,
+) -> Vec<Note> {
+    let step_ticks = (ticks_per_beat as f64 / seq.step_resolution.steps_per_beat()).max(1.0);
+    let swing_ticks = seq.swing_amount as f64 * 0.5 * step_ticks;
+    let order: Vec<usize> = if seq.chain_enabled && !seq.chain.is_empty() {
+        seq.chain
+            .iter()
+            .copied()
+            .filter(|&i| i < seq.patterns.len())
+            .collect()
+    } else {
+        vec![seq
+            .current_pattern
+            .min(seq.patterns.len().saturating_sub(1))]
+    };
+    if order.is_empty() || seq.patterns.is_empty() {
+        return Vec::new();
+    }
+
+    let mut notes = Vec::new();
+    let mut step_index = 0u64;
+    let mut chain_position = 0usize;
+    'outer: loop {
+        let pattern = &seq.patterns[order[chain_position % order.len()]];
+        if pattern.length == 0 {
+            break;
+        }
+        for step in 0..pattern.length {
+            let mut start = step_index as f64 * step_ticks;
+            if step % 2 == 1 {
+                start += swing_ticks;
+            }
+            let tick = start.round() as u32;
+            if tick >= length {
+                break 'outer;
+            }
+            for (pad_idx, pad) in seq.pads.iter().enumerate() {
+                let Some(data) = pattern.steps.get(pad_idx).and_then(|s| s.get(step)) else {
+                    continue;
+                };
+                if !data.active {
+                    continue;
+                }
+                notes.push(Note {
+                    tick,
+                    duration: (step_ticks / 2.0).round().max(1.0) as u32,
+                    pitch: gm_drum_note(pad_idx, &pad.name),
+                    velocity: data.velocity.clamp(1, 127),
+                    probability: data.probability,
+                    expression: None,
+                });
+            }
+            step_index += 1;
+        }
+        chain_position += 1;
+    }
+    notes
+}
+
+/// Map a drum pad to a GM percussion note, by name when recognisable,
+/// otherwise by a conventional pad layout.
+pub fn gm_drum_note(pad_index: usize, name: &str) -> u8 {
+    let name = name.to_lowercase();
+    let by_name: &[(&str, u8)] = &[
+        ("kick", 36),
+        ("bd", 36),
+        ("rim", 37),
+        ("snare", 38),
+        ("sd", 38),
+        ("clap", 39),
+        ("open", 46),
+        ("oh", 46),
+        ("hat", 42),
+        ("hh", 42),
+        ("floor", 41),
+        ("low tom", 45),
+        ("mid tom", 47),
+        ("tom", 48),
+        ("crash", 49),
+        ("ride", 51),
+        ("tamb", 54),
+        ("cowbell", 56),
+        ("shaker", 70),
+    ];
+    for (pattern, note) in by_name {
+        if name.contains(pattern) {
+            return *note;
+        }
+    }
+    const DEFAULT_LAYOUT: [u8; 12] = [36, 38, 42, 46, 39, 45, 48, 49, 51, 37, 56, 54];
+    DEFAULT_LAYOUT[pad_index % DEFAULT_LAYOUT.len()]
+}
+
+/// Serialize tracks as a format 1 Standard MIDI File with `ticks_per_beat` PPQ.
+pub fn write_smf(
+    tracks: &[SmfExportTrack],
+    bpm: f64,
+    time_signature: (u8, u8),
+    ticks_per_beat: u32,
+) -> Result<Vec<u8>, String> {
+    if ticks_per_beat == 0 || ticks_per_beat > 0x7FFF {
+        return Err(format!("Cannot write PPQ {}", ticks_per_beat));
+    }
+    if bpm <= 0.0 {
+        return Err("Tempo must be positive".to_string());
+    }
+
+    let mut out = b"MThd".to_vec();
+    out.extend(6u32.to_be_bytes());
+    out.extend(1u16.to_be_bytes());
+    out.extend((tracks.len() as u16 + 1).to_be_bytes());
+    out.extend((ticks_per_beat as u16).to_be_bytes());
+
+    // Conductor track: tempo and time signature
+    let us_per_beat = (60_000_000.0 / bpm).round().clamp(1.0, 0xFF_FFFF as f64) as u32;
+    let (num, denom) = time_signature;
+    let denom_pow = denom.max(1).ilog2() as u8;
+    let mut tempo = vec![0xFF, 0x51, 0x03];
+    tempo.extend(&us_per_beat.to_be_bytes()[1..]);
+    let time_sig = vec![0xFF, 0x58, 0x04, num, denom_pow, 24, 8];
+    write_track(&mut out, vec![(0, 0, tempo), (0, 0, time_sig)]);
+
+    for track in tracks {
+        let channel = track.channel & 0x0F;
+        let mut events: Vec<(u32, u8, Vec<u8>)> = Vec::with_capacity(track.notes.len() * 2 + 1);
+        let mut name = vec![0xFF, 0x03];
+        write_vlq(&mut name, track.name.len() as u32);
+        name.extend(track.name.as_bytes());
+        events.push((0, 0, name));
+        for note in &track.notes {
+            let pitch = note.pitch & 0x7F;
+            events.push((
+                note.tick,
+                2,
+                vec![0x90 | channel, pitch, note.velocity.clamp(1, 127)],
+            ));
+            events.push((
+                note.tick + note.duration.max(1),
+                1,
+                vec![0x80 | channel, pitch, 0],
+            ));
+        }
+        write_track(&mut out, events);
+    }
+    Ok(out)
+}
+
+/// Append an MTrk chunk. Events are (tick, order, bytes); at equal ticks lower
+/// order sorts first so note-offs precede note-ons.
+fn write_track(out: &mut Vec<u8>, mut events: Vec<(u32, u8, Vec<u8>)>) {
+    events.sort_by_key(|(tick, order, _)| (*tick, *order));
+    let mut body = Vec::new();
+    let mut last = 0u32;
+    for (tick, _, bytes) in events {
+        write_vlq(&mut body, tick - last);
+        body.extend(bytes);
+        last = tick;
+    }
+    body.extend([0x00, 0xFF, 0x2F, 0x00]);
+    out.extend(b"MTrk");
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(body);
+}
+
+fn write_vlq(out: &mut Vec<u8>, value: u32) {
+    let mut buf = [0u8; 5];
+    let mut i = buf.len() - 1;
+    let mut v = value;
+    buf[i] = (v & 0x7F) as u8;
+    v >>= 7;
+    while v > 0 {
+        i -= 1;
+        buf[i] = (v & 0x7F) as u8 | 0x80;
+        v >>= 7;
+    }
+    out.extend(&buf[i..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vlq(value: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_vlq(&mut bytes, value);
+        bytes
+    }
+
+    fn header(format: u16, tracks: u16, ppq: u16) -> Vec<u8> {
+        let mut out = b"MThd".to_vec();
+        out.extend_from_slice(&6u32.to_be_bytes());
+        out.extend_from_slice(&format.to_be_bytes());
+        out.extend_from_slice(&tracks.to_be_bytes());
+        out.extend_from_slice(&ppq.to_be_bytes());
+        out
+    }
+
+    /// Build an MTrk chunk from (delta, event bytes) pairs, appending End of Track.
+    fn track(events: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (delta, bytes) in events {
+            body.extend(vlq(*delta));
+            body.extend_from_slice(bytes);
+        }
+        body.extend([0x00, 0xFF, 0x2F, 0x00]);
+        let mut out = b"MTrk".to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend(body);
+        out
+    }
+
+    #[test]
+    fn vlq_round_trip() {
+        for value in [0u32, 0x7F, 0x80, 0x2000, 0x3FFF, 0x0FFF_FFFF] {
+            let bytes = vlq(value);
+            assert_eq!(Reader::new(&bytes).read_vlq().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn format0_rescales_ppq_and_keeps_velocity() {
+        let mut data = header(0, 1, 96);
+        data.extend(track(&[
+            (0, vec![0x90, 60, 100]),
+            (96, vec![0x80, 60, 0]),
+            (0, vec![0x90, 64, 80]),
+            // Note On with velocity 0 acts as Note Off
+            (48, vec![0x90, 64, 0]),
+        ]));
+
+        let import = parse_smf(&data, 480).unwrap();
+        assert_eq!(import.parts.len(), 1);
+        let notes = &import.parts[0].notes;
+        assert_eq!(notes.len(), 2);
+        assert_eq!((notes[0].tick, notes[0].duration), (0, 480));
+        assert_eq!(notes[0].velocity, 100);
+        assert_eq!((notes[1].tick, notes[1].duration), (480, 240));
+        assert_eq!(notes[1].velocity, 80);
+        assert_eq!(import.length_ticks, 720);
+    }
+
+    #[test]
+    fn format0_splits_channels_into_parts() {
+        let mut data = header(0, 1, 480);
+        data.extend(track(&[
+            (0, vec![0x90, 60, 100]),
+            (0, vec![0x99, 36, 110]),
+            (240, vec![0x89, 36, 0]),
+            (240, vec![0x80, 60, 0]),
+        ]));
+
+        let import = parse_smf(&data, 480).unwrap();
+        assert_eq!(import.parts.len(), 2);
+        assert_eq!(import.parts[0].channel, 0);
+        assert_eq!(import.parts[1].channel, 9);
+        assert_eq!(import.parts[1].name, "Track 1 ch10");
+        assert_eq!(import.parts[1].notes[0].pitch, 36);
+    }
+
+    #[test]
+    fn format1_reads_tempo_time_signature_and_names() {
+        let mut data = header(1, 2, 480);
+        // Conductor track: 100 BPM (600000 us/quarter), 3/4
+        data.extend(track(&[
+            (0, vec![0xFF, 0x51, 0x03, 0x09, 0x27, 0xC0]),
+            (0, vec![0xFF, 0x58, 0x04, 3, 2, 24, 8]),
+        ]));
+        data.extend(track(&[
+            (0, vec![0xFF, 0x03, 0x04, b'B', b'a', b's', b's']),
+            (0, vec![0x91, 40, 90]),
+            // Running status: second Note On shares 0x91
+            (480, vec![43, 90]),
+            (480, vec![40, 0]),
+            (0, vec![43, 0]),
+        ]));
+
+        let import = parse_smf(&data, 480).unwrap();
+        assert_eq!(import.bpm.map(|b| b.round() as u16), Some(100));
+        assert_eq!(import.time_signature, Some((3, 4)));
+        assert_eq!(import.parts.len(), 1);
+        let part = &import.parts[0];
+        assert_eq!(part.name, "Bass");
+        assert_eq!(part.track_index, 1);
+        assert_eq!(part.channel, 1);
+        assert_eq!(part.notes.len(), 2);
+        assert_eq!(part.notes[0].duration, 960);
+        assert_eq!((part.notes[1].tick, part.notes[1].duration), (480, 480));
+    }
+
+    #[test]
+    fn unterminated_notes_end_at_track_end() {
+        let mut data = header(0, 1, 480);
+        data.extend(track(&[
+            (0, vec![0x90, 60, 100]),
+            (960, vec![0xB0, 7, 100]),
+        ]));
+
+        let import = parse_smf(&data, 480).unwrap();
+        assert_eq!(import.parts[0].notes[0].duration, 960);
+    }
+
+    #[test]
+    fn sysex_and_unknown_chunks_are_skipped() {
+        let mut data = header(0, 1, 480);
+        data.extend(b"XFIH");
+        data.extend(2u32.to_be_bytes());
+        data.extend([0xAA, 0xBB]);
+        data.extend(track(&[
+            (0, vec![0xF0, 0x03, 0x7E, 0x7F, 0xF7]),
+            (0, vec![0x90, 60, 100]),
+            (480, vec![0x80, 60, 0]),
+        ]));
+
+        let import = parse_smf(&data, 480).unwrap();
+        assert_eq!(import.parts.len(), 1);
+        assert_eq!(import.parts[0].notes.len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(parse_smf(b"RIFF0000", 480).is_err());
+        assert!(parse_smf(&header(2, 1, 480), 480).is_err());
+        // SMPTE division (-25 fps, 40 ticks per frame)
+        assert!(parse_smf(&header(0, 1, 0xE728), 480).is_err());
+        // Header claims a track but data is truncated
+        let mut data = header(0, 1, 480);
+        data.extend(b"MTrk");
+        data.extend(100u32.to_be_bytes());
+        assert!(parse_smf(&data, 480).is_err());
+    }
+
+    #[test]
+    fn write_then_parse_round_trips() {
+        let tracks = vec![
+            SmfExportTrack {
+                name: "Lead".to_string(),
+                channel: 0,
+                notes: vec![
+                    Note {
+                        tick: 0,
+                        duration: 240,
+                        pitch: 72,
+                        velocity: 90,
+                        probability: 1.0,
+                        expression: None,
+                    },
+                    // Back-to-back repeat of the same pitch
+                    Note {
+                        tick: 240,
+                        duration: 240,
+                        pitch: 72,
+                        velocity: 60,
+                        probability: 1.0,
+                        expression: None,
+                    },
+                ],
+            },
+            SmfExportTrack {
+                name: "Drums".to_string(),
+                channel: GM_DRUM_CHANNEL,
+                notes: vec![Note {
+                    tick: 960,
+                    duration: 60,
+                    pitch: 36,
+                    velocity: 127,
+                    probability: 1.0,
+                    expression: None,
+                }],
+            },
+        ];
+
+        let data = write_smf(&tracks, 128.0, (7, 8), 480).unwrap();
+        let import = parse_smf(&data, 480).unwrap();
+
+        assert_eq!(import.bpm.map(|b| b.round() as u16), Some(128));
+        assert_eq!(import.time_signature, Some((7, 8)));
+        assert_eq!(import.parts.len(), 2);
+        assert_eq!(import.parts[0].name, "Lead");
+        let lead = &import.parts[0].notes;
+        assert_eq!(lead.len(), 2);
+        assert_eq!(
+            (lead[1].tick, lead[1].duration, lead[1].velocity),
+            (240, 240, 60)
+        );
+        assert_eq!(import.parts[1].name, "Drums");
+        assert_eq!(import.parts[1].channel, GM_DRUM_CHANNEL);
+        assert_eq!(import.parts[1].notes[0].tick, 960);
+    }
+
+    #[test]
+    fn drum_pattern_renders_gm_notes_with_chain() {
+        use crate::state::drum_sequencer::DrumPattern;
+
+        let mut seq = DrumSequencerState::new();
+        seq.pads[0].name = "Kick".to_string();
+        seq.pads[1].name = "Closed Hat".to_string();
+        seq.patterns[0] = DrumPattern::new(4);
+        seq.patterns[0].steps[0][0].active = true;
+        seq.patterns[1] = DrumPattern::new(4);
+        seq.patterns[1].steps[1][2].active = true;
+        seq.patterns[1].steps[1][2].velocity = 64;
+        seq.chain = vec![0, 1];
+        seq.chain_enabled = true;
+
+        // 16th-note steps at 480 TPB: two 4-step patterns fill two beats
+        let notes = render_drum_pattern(&seq, 480, 480 * 4);
+        let hits: Vec<(u32, u8, u8)> = notes
+            .iter()
+            .map(|n| (n.tick, n.pitch, n.velocity))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![(0, 36, 100), (720, 42, 64), (960, 36, 100), (1680, 42, 64)]
+        );
+    }
+
+    #[test]
+    fn drum_swing_delays_odd_steps() {
+        let mut seq = DrumSequencerState::new();
+        seq.patterns[0] = crate::state::drum_sequencer::DrumPattern::new(2);
+        seq.patterns[0].steps[0][1].active = true;
+        seq.swing_amount = 1.0;
+
+        let notes = render_drum_pattern(&seq, 480, 240);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].tick, 120 + 60);
+    }
+
+    #[test]
+    fn export_tracks_flatten_song_mode_and_skip_empty() {
+        use crate::state::SourceType;
+
+        let mut session = SessionState::new();
+        let mut instruments = InstrumentState::new();
+        let lead = instruments.add_instrument(SourceType::Saw);
+        let _silent = instruments.add_instrument(SourceType::Sin);
+        session.piano_roll.add_track(lead);
+
+        let arr = &mut session.arrangement;
+        let clip = arr.add_clip("A".to_string(), lead, 480);
+        arr.clip_mut(clip).unwrap().notes.push(Note {
+            tick: 0,
+            duration: 480,
+            pitch: 60,
+            velocity: 100,
+            probability: 1.0,
+            expression: None,
+        });
+        arr.add_placement(clip, lead, 0);
+        arr.add_placement(clip, lead, 1920);
+        arr.play_mode = PlayMode::Song;
+
+        let tracks = collect_export_tracks(&session, &instruments);
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].channel, 0);
+        let ticks: Vec<u32> = tracks[0].notes.iter().map(|n| n.tick).collect();
+        assert_eq!(ticks, vec![0, 1920]);
+
+        // Pattern mode reads the (empty) piano roll track instead
+        session.arrangement.play_mode = PlayMode::Pattern;
+        assert!(collect_export_tracks(&session, &instruments).is_empty());
+    }
+
+    #[test]
+    fn gm_drum_note_prefers_names() {
+        assert_eq!(gm_drum_note(5, "808 Kick"), 36);
+        assert_eq!(gm_drum_note(0, "Open Hat"), 46);
+        assert_eq!(gm_drum_note(1, ""), 38);
+        assert_eq!(gm_drum_note(13, ""), 38);
+    }
+}
@@ -40,3 +40,29 @@ pub fn custom_synthdefs_dir() -> PathBuf {
         PathBuf::from("synthdefs")
     }
 }
+
+/// User-local directory for instrument presets (`~/.config/imbolc/presets/instruments/`).
+pub fn instrument_presets_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home)
+            .join(".config")
+            .join("imbolc")
+            .join("presets")
+            .join("instruments")
+    } else {
+        PathBuf::from("presets").join("instruments")
+    }
+}
+
+/// User-local directory for effect chain presets (`~/.config/imbolc/presets/effect_chains/`).
+pub fn effect_chain_presets_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home)
+            .join(".config")
+            .join("imbolc")
+            .join("presets")
+            .join("effect_chains")
+    } else {
+        PathBuf::from("presets").join("effect_chains")
+    }
+}
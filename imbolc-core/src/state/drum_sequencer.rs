@@ -1,6 +1,6 @@
 //! Drum sequencer types - re-exported from imbolc-types.
 
 pub use imbolc_types::{
-    euclidean_rhythm, ChopperState, DrumPad, DrumPattern, DrumSequencerState, DrumStep,
-    DEFAULT_STEPS, NUM_PADS, NUM_PATTERNS,
+    euclidean_rhythm, ChopperState, DrumPad, DrumPattern, DrumSequencerState, DrumStep, ParamLock,
+    StepCondition, DEFAULT_STEPS, MAX_RATCHET, NUM_PADS, NUM_PATTERNS,
 };
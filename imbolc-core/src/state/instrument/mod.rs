@@ -6,8 +6,9 @@ pub use imbolc_types::{
     decode_effect_cursor_from_slice, effects_max_cursor, instrument_row_count, instrument_row_info,
     instrument_section_for_row, EnvConfig, EqBand, EqBandType, EqConfig, FilterConfig, FilterType,
     GrooveConfig, Instrument, InstrumentSection, LayerGroupMixer, LfoConfig, LfoShape, MixerBus,
-    MixerSend, ModSource, ModulatedParam, OutputTarget, ParameterTarget, ProcessingStage,
-    SendTapPoint, SourceExtra, SwingGrid,
+    MixerSend, ModController, ModMatrix, ModMatrixRow, ModMatrixSource, ModRoute, ModSource,
+    ModulatedParam, OutputTarget, ParameterTarget, ProcessingStage, SendTapPoint, SourceExtra,
+    SwingGrid, MAX_MOD_ROUTES,
 };
 
 // Re-export from local modules (adds extension traits)
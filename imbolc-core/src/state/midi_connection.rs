@@ -1,5 +1,7 @@
 //! MIDI connection state (local hardware).
 
+use imbolc_types::MidiClockMode;
+
 /// MIDI hardware connection state.
 #[derive(Debug, Clone, Default)]
 pub struct MidiConnectionState {
@@ -7,4 +9,10 @@ pub struct MidiConnectionState {
     pub port_names: Vec<String>,
     /// Currently connected MIDI port name
     pub connected_port: Option<String>,
+    /// Available MIDI output port names (indexed by `SourceType::MidiOut` port)
+    pub output_port_names: Vec<String>,
+    /// Clock / transport sync mode
+    pub clock_mode: MidiClockMode,
+    /// Output port index clock is sent to in master mode
+    pub clock_output_port: u8,
 }
@@ -14,6 +14,7 @@ pub mod music;
 pub mod param;
 pub mod persistence;
 pub mod piano_roll;
+pub mod preset_library;
 pub mod recent_projects;
 pub mod sampler;
 pub mod session;
@@ -42,9 +43,9 @@ pub use vst_plugin::{VstParamSpec, VstPlugin, VstPluginId, VstPluginKind, VstPlu
 
 // Re-export types moved to imbolc-types
 pub use imbolc_types::{
-    BusId, ClientDisplayInfo, IoGeneration, IoState, KeyboardLayout, NetworkConnectionStatus,
-    NetworkDisplayContext, OwnershipDisplayStatus, PendingExport, PendingRender, ProjectMeta,
-    RecordingState, VisualizationState,
+    BusId, ClientDisplayInfo, ExportBitDepth, ExportFormat, ExportOptions, IoGeneration, IoState,
+    KeyboardLayout, NetworkConnectionStatus, NetworkDisplayContext, OwnershipDisplayStatus,
+    PendingExport, PendingRender, ProjectMeta, RecordingState, VisualizationState,
 };
 
 /// Top-level application state, owned by main.rs and passed to panes by reference.
@@ -144,6 +144,8 @@ mod tests {
                     next_slice_id: 2,
                     waveform_peaks: vec![0.1, 0.2],
                     duration_secs: 1.23,
+                    source_bpm: None,
+                    warp: false,
                 });
                 if let Some(chopper) = seq.chopper.as_mut() {
                     chopper.slices[0].name = "A".to_string();
@@ -201,6 +203,7 @@ mod tests {
                 velocity: 100,
                 duration: 120,
                 probability: 1.0,
+                expression: None,
             });
         }
         session.arrangement.add_placement(clip_id, saw_id, 0);
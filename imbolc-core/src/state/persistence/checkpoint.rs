@@ -26,6 +26,8 @@ const DIFF_TABLES: &[&str] = &[
     "mixer_buses",
     "mixer_master",
     "musical_settings",
+    "tempo_events",
+    "meter_changes",
     "piano_roll_tracks",
     "piano_roll_notes",
     "sampler_configs",
@@ -52,6 +54,7 @@ const DIFF_TABLES: &[&str] = &[
     "arrangement_placements",
     "arrangement_clip_automation_lanes",
     "arrangement_clip_automation_points",
+    "arrangement_markers",
 ];
 
 /// Metadata for a checkpoint, returned by list operations.
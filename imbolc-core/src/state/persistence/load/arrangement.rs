@@ -1,6 +1,8 @@
 use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
 
 use super::decoders::*;
+use super::{load_note_expressions, table_exists};
+use crate::state::persistence::schema::column_exists;
 use crate::state::session::SessionState;
 
 pub(super) fn load_automation(conn: &Connection, session: &mut SessionState) -> SqlResult<()> {
@@ -108,6 +110,36 @@ pub(super) fn load_midi_recording(conn: &Connection, session: &mut SessionState)
         session.midi_recording.channel_filter = channel.map(|v| v as u8);
     }
 
+    // MPE input (v22 files have none)
+    if column_exists(conn, "midi_recording_settings", "mpe")? {
+        if let Some((mpe, bend_range)) = conn
+            .query_row(
+                "SELECT mpe, mpe_bend_range FROM midi_recording_settings WHERE id = 1",
+                [],
+                |row| Ok((row.get::<_, i32>(0)?, row.get::<_, i32>(1)?)),
+            )
+            .optional()?
+        {
+            session.midi_recording.mpe = mpe != 0;
+            session.midi_recording.mpe_bend_range = bend_range.clamp(1, 96) as u8;
+        }
+    }
+
+    // Note take recording (v23 files have none)
+    if column_exists(conn, "midi_recording_settings", "note_record_mode")? {
+        if let Some((mode, quantize)) = conn
+            .query_row(
+                "SELECT note_record_mode, input_quantize FROM midi_recording_settings WHERE id = 1",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?)),
+            )
+            .optional()?
+        {
+            session.midi_recording.note_record_mode = decode_note_record_mode(&mode);
+            session.midi_recording.input_quantize = quantize.filter(|g| *g > 0).map(|g| g as u32);
+        }
+    }
+
     // CC mappings
     session.midi_recording.cc_mappings.clear();
     let mut cc_stmt = conn.prepare(
@@ -295,6 +327,7 @@ pub(super) fn load_arrangement(conn: &Connection, session: &mut SessionState) ->
         })?
         .collect::<SqlResult<_>>()?;
 
+    let has_clip_expression = table_exists(conn, "arrangement_clip_note_expression")?;
     for (id, name, inst_id, length) in clips {
         let mut clip = Clip {
             id,
@@ -317,10 +350,24 @@ pub(super) fn load_arrangement(conn: &Connection, session: &mut SessionState) ->
                     pitch: row.get::<_, i32>(2)? as u8,
                     velocity: row.get::<_, i32>(3)? as u8,
                     probability: row.get::<_, f32>(4)?,
+                    expression: None,
                 })
             })?
             .collect::<SqlResult<_>>()?;
 
+        // Clip note expression curves (v22 files have none)
+        if has_clip_expression {
+            let mut expressions = load_note_expressions(
+                conn,
+                "SELECT note_position, dimension, tick, value FROM arrangement_clip_note_expression
+                 WHERE clip_id = ?1 ORDER BY note_position, dimension, position",
+                params![id],
+            )?;
+            for (pos, note) in clip.notes.iter_mut().enumerate() {
+                note.expression = expressions.remove(&(pos as i64)).map(Box::new);
+            }
+        }
+
         // Clip automation lanes
         let mut lane_stmt = conn.prepare(
             "SELECT id, target_type, target_instrument_id, target_bus_id, target_effect_id, target_param_idx, target_extra, enabled, record_armed, min_value, max_value
@@ -419,6 +466,64 @@ pub(super) fn load_arrangement(conn: &Connection, session: &mut SessionState) ->
         })?
         .collect::<SqlResult<_>>()?;
 
+    // Audio clips (projects saved before audio clips existed have none)
+    session.arrangement.audio_clips.clear();
+    if table_exists(conn, "arrangement_audio_clips")? {
+        // Take mute flags were added in v16
+        let muted_col = if column_exists(conn, "arrangement_audio_clips", "muted")? {
+            "muted"
+        } else {
+            "0"
+        };
+        let mut audio_stmt = conn.prepare(&format!(
+            "SELECT id, name, path, instrument_id, start_tick, duration_ticks, sample_rate, length_secs, waveform_peaks, {}
+             FROM arrangement_audio_clips ORDER BY id",
+            muted_col
+        ))?;
+        session.arrangement.audio_clips = audio_stmt
+            .query_map([], |row| {
+                let peaks_blob: Option<Vec<u8>> = row.get(8)?;
+                Ok(AudioClip {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    path: std::path::PathBuf::from(row.get::<_, String>(2)?),
+                    instrument_id: imbolc_types::InstrumentId::new(row.get::<_, u32>(3)?),
+                    start_tick: row.get::<_, i64>(4)? as u32,
+                    duration_ticks: row.get::<_, i64>(5)? as u32,
+                    sample_rate: row.get(6)?,
+                    length_secs: row.get(7)?,
+                    waveform_peaks: peaks_blob
+                        .map(|bytes| {
+                            bytes
+                                .chunks_exact(4)
+                                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    muted: row.get::<_, i32>(9)? != 0,
+                })
+            })?
+            .collect::<SqlResult<_>>()?;
+    }
+
+    // Markers (v20 files have none)
+    session.arrangement.markers.clear();
+    if table_exists(conn, "arrangement_markers")? {
+        let mut marker_stmt = conn.prepare(
+            "SELECT id, name, tick, end_tick FROM arrangement_markers ORDER BY tick, id",
+        )?;
+        session.arrangement.markers = marker_stmt
+            .query_map([], |row| {
+                Ok(Marker {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    tick: row.get::<_, i64>(2)? as u32,
+                    end_tick: row.get::<_, Option<i64>>(3)?.map(|t| t as u32),
+                })
+            })?
+            .collect::<SqlResult<_>>()?;
+    }
+
     // Recalculate next IDs
     session.arrangement.recalculate_next_ids();
 
@@ -91,6 +91,13 @@ pub(crate) fn decode_source_type(s: &str) -> crate::state::instrument::SourceTyp
             return SourceType::Vst(VstPluginId::new(id));
         }
     }
+    if let Some(rest) = s.strip_prefix("MidiOut:") {
+        if let Some((port, channel)) = rest.split_once(':') {
+            if let (Ok(port), Ok(channel)) = (port.parse::<u8>(), channel.parse::<u8>()) {
+                return SourceType::MidiOut { port, channel };
+            }
+        }
+    }
     match s {
         "Saw" => SourceType::Saw,
         "Sin" => SourceType::Sin,
@@ -269,6 +276,45 @@ pub(crate) fn decode_lfo_shape(s: &str) -> crate::state::instrument::LfoShape {
     }
 }
 
+/// Parse step parameter locks written by `encode_step_locks`, skipping
+/// malformed pairs
+pub(crate) fn decode_step_locks(s: &str) -> Vec<crate::state::drum_sequencer::ParamLock> {
+    s.split(';')
+        .filter_map(|pair| {
+            let (target, value) = pair.rsplit_once('=')?;
+            Some(crate::state::drum_sequencer::ParamLock {
+                target: decode_parameter_target(target),
+                value: value.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn decode_step_condition(s: &str) -> crate::state::drum_sequencer::StepCondition {
+    use crate::state::drum_sequencer::StepCondition;
+    if let Some(rest) = s.strip_prefix("Loop:") {
+        if let Some((n, m)) = rest.split_once(':') {
+            if let (Ok(n), Ok(m)) = (n.parse(), m.parse()) {
+                return StepCondition::Loop { n, m };
+            }
+        }
+    }
+    match s {
+        "Always" => StepCondition::Always,
+        "Previous" => StepCondition::Previous,
+        "NotPrevious" => StepCondition::NotPrevious,
+        "Fill" => StepCondition::Fill,
+        "NotFill" => StepCondition::NotFill,
+        other => {
+            eprintln!(
+                "[imbolc] persistence: unknown StepCondition '{}', using Always",
+                other
+            );
+            StepCondition::Always
+        }
+    }
+}
+
 pub(crate) fn decode_parameter_target(s: &str) -> crate::state::instrument::ParameterTarget {
     use crate::state::instrument::ParameterTarget;
 
@@ -349,6 +395,8 @@ pub(crate) fn decode_parameter_target(s: &str) -> crate::state::instrument::Para
         "MembraneTension" => ParameterTarget::MembraneTension,
         "SampleRate" => ParameterTarget::SampleRate,
         "SampleAmp" => ParameterTarget::SampleAmp,
+        "SampleStart" => ParameterTarget::SampleStart,
+        "SampleEnd" => ParameterTarget::SampleEnd,
         "StretchRatio" => ParameterTarget::StretchRatio,
         "PitchShift" => ParameterTarget::PitchShift,
         "DelayTime" => ParameterTarget::DelayTime,
@@ -398,6 +446,25 @@ pub(crate) fn decode_tap_point(s: &str) -> crate::state::instrument::SendTapPoin
     }
 }
 
+pub(crate) fn decode_sidechain_source(s: &str) -> Option<imbolc_types::SidechainSource> {
+    use imbolc_types::SidechainSource;
+    let parsed = match s.split(':').collect::<Vec<_>>().as_slice() {
+        ["instrument", id, tap] => id.parse().ok().map(|id| SidechainSource::Instrument {
+            id: imbolc_types::InstrumentId::new(id),
+            tap: decode_tap_point(tap),
+        }),
+        ["group", group_id] => group_id.parse().ok().map(SidechainSource::LayerGroup),
+        _ => None,
+    };
+    if parsed.is_none() {
+        eprintln!(
+            "[imbolc] persistence: unknown sidechain source '{}', dropping key",
+            s
+        );
+    }
+    parsed
+}
+
 pub(crate) fn decode_channel_config(s: &str) -> imbolc_types::ChannelConfig {
     use imbolc_types::ChannelConfig;
     match s {
@@ -563,3 +630,79 @@ pub(crate) fn decode_automation_target(
         }
     }
 }
+
+pub(crate) fn decode_gen_rate(s: &str) -> imbolc_types::GenRate {
+    use imbolc_types::GenRate;
+    match s {
+        "Whole" => GenRate::Whole,
+        "Half" => GenRate::Half,
+        "DottedQuarter" => GenRate::DottedQuarter,
+        "Quarter" => GenRate::Quarter,
+        "DottedEighth" => GenRate::DottedEighth,
+        "Eighth" => GenRate::Eighth,
+        "DottedSixteenth" => GenRate::DottedSixteenth,
+        "Sixteenth" => GenRate::Sixteenth,
+        "ThirtySecond" => GenRate::ThirtySecond,
+        "TripletQuarter" => GenRate::TripletQuarter,
+        "TripletEighth" => GenRate::TripletEighth,
+        "TripletSixteenth" => GenRate::TripletSixteenth,
+        other => {
+            eprintln!(
+                "[imbolc] persistence: unknown GenRate '{}', using Eighth",
+                other
+            );
+            GenRate::Eighth
+        }
+    }
+}
+
+pub(crate) fn decode_euclidean_pitch_mode(
+    s: &str,
+    fixed_pitch: Option<u8>,
+) -> imbolc_types::EuclideanPitchMode {
+    use imbolc_types::EuclideanPitchMode;
+    match s {
+        "Fixed" => EuclideanPitchMode::Fixed(fixed_pitch.unwrap_or(60)),
+        "ScaleWalk" => EuclideanPitchMode::ScaleWalk,
+        "RandomInScale" => EuclideanPitchMode::RandomInScale,
+        other => {
+            eprintln!(
+                "[imbolc] persistence: unknown EuclideanPitchMode '{}', using Fixed",
+                other
+            );
+            EuclideanPitchMode::Fixed(fixed_pitch.unwrap_or(60))
+        }
+    }
+}
+
+pub(crate) fn decode_markov_duration_mode(s: &str) -> imbolc_types::MarkovDurationMode {
+    use imbolc_types::MarkovDurationMode;
+    match s {
+        "Fixed" => MarkovDurationMode::Fixed,
+        "Variable" => MarkovDurationMode::Variable,
+        "Legato" => MarkovDurationMode::Legato,
+        other => {
+            eprintln!(
+                "[imbolc] persistence: unknown MarkovDurationMode '{}', using Fixed",
+                other
+            );
+            MarkovDurationMode::Fixed
+        }
+    }
+}
+
+pub(crate) fn decode_note_record_mode(s: &str) -> imbolc_types::NoteRecordMode {
+    use imbolc_types::NoteRecordMode;
+    match s {
+        "Overdub" => NoteRecordMode::Overdub,
+        "Replace" => NoteRecordMode::Replace,
+        "LoopTakes" => NoteRecordMode::LoopTakes,
+        other => {
+            eprintln!(
+                "[imbolc] persistence: unknown NoteRecordMode '{}', using Overdub",
+                other
+            );
+            NoteRecordMode::Overdub
+        }
+    }
+}
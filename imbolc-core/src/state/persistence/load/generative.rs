@@ -0,0 +1,209 @@
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+
+use super::decoders::*;
+use super::table_exists;
+use crate::state::session::SessionState;
+use imbolc_types::{
+    EuclideanConfig, GenVoice, GenVoiceId, GenerativeAlgorithm, LSystemConfig, MarkovConfig,
+};
+
+pub(super) fn load_generative(conn: &Connection, session: &mut SessionState) -> SqlResult<()> {
+    // Projects saved before the generative tables existed keep the defaults
+    if !table_exists(conn, "generative_state")? {
+        return Ok(());
+    }
+
+    let gen = &mut session.generative;
+    let settings = conn
+        .query_row(
+            "SELECT enabled, capture_enabled, next_voice_id,
+                    macro_density, macro_chaos, macro_energy, macro_motion,
+                    scale_lock, pitch_min, pitch_max, max_notes_per_beat,
+                    humanize_timing, humanize_velocity
+             FROM generative_state WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, i32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    [
+                        row.get::<_, f32>(3)?,
+                        row.get::<_, f32>(4)?,
+                        row.get::<_, f32>(5)?,
+                        row.get::<_, f32>(6)?,
+                    ],
+                    row.get::<_, i32>(7)?,
+                    [
+                        row.get::<_, i32>(8)?,
+                        row.get::<_, i32>(9)?,
+                        row.get::<_, i32>(10)?,
+                    ],
+                    [row.get::<_, f32>(11)?, row.get::<_, f32>(12)?],
+                ))
+            },
+        )
+        .optional()?;
+
+    if let Some((enabled, capture, next_voice_id, macros, scale_lock, pitch, humanize)) = settings {
+        gen.enabled = enabled != 0;
+        gen.capture_enabled = capture != 0;
+        gen.next_voice_id = next_voice_id;
+        gen.macros.density = macros[0];
+        gen.macros.chaos = macros[1];
+        gen.macros.energy = macros[2];
+        gen.macros.motion = macros[3];
+        gen.constraints.scale_lock = scale_lock != 0;
+        gen.constraints.pitch_min = pitch[0] as u8;
+        gen.constraints.pitch_max = pitch[1] as u8;
+        gen.constraints.max_notes_per_beat = pitch[2] as u8;
+        gen.constraints.humanize_timing = humanize[0];
+        gen.constraints.humanize_velocity = humanize[1];
+    }
+
+    gen.voices.clear();
+    let mut voice_stmt = conn.prepare(
+        "SELECT id, name, enabled, muted, target_instrument_id,
+                velocity_min, velocity_max, octave_min, octave_max, algorithm, rate,
+                euclid_pulses, euclid_steps, euclid_rotation, euclid_pitch_mode, euclid_fixed_pitch,
+                markov_initial_pitch_class, markov_rest_probability, markov_duration_mode,
+                lsystem_axiom, lsystem_iterations, lsystem_step_interval,
+                lsystem_note_duration_steps, lsystem_velocity
+         FROM generative_voices ORDER BY position",
+    )?;
+    let mut transition_stmt = conn.prepare(
+        "SELECT from_pitch_class, to_pitch_class, weight
+         FROM generative_markov_transitions WHERE voice_id = ?1",
+    )?;
+    let mut rule_stmt = conn.prepare(
+        "SELECT symbol, replacement FROM generative_lsystem_rules
+         WHERE voice_id = ?1 ORDER BY position",
+    )?;
+
+    let voices: Vec<GenVoice> = voice_stmt
+        .query_map([], |row| {
+            let id = GenVoiceId::new(row.get(0)?);
+            let rate = decode_gen_rate(&row.get::<_, String>(10)?);
+            let algorithm = match row.get::<_, String>(9)?.as_str() {
+                "Markov" => {
+                    let mut cfg = MarkovConfig {
+                        rate,
+                        ..MarkovConfig::default()
+                    };
+                    if let Some(pc) = row.get::<_, Option<i32>>(16)? {
+                        cfg.initial_pitch_class = pc as u8;
+                    }
+                    if let Some(rest) = row.get::<_, Option<f32>>(17)? {
+                        cfg.rest_probability = rest;
+                    }
+                    if let Some(mode) = row.get::<_, Option<String>>(18)? {
+                        cfg.duration_mode = decode_markov_duration_mode(&mode);
+                    }
+                    GenerativeAlgorithm::Markov(cfg)
+                }
+                "LSystem" => {
+                    let mut cfg = LSystemConfig {
+                        rate,
+                        rules: Vec::new(),
+                        ..LSystemConfig::default()
+                    };
+                    if let Some(axiom) = row.get::<_, Option<String>>(19)? {
+                        cfg.axiom = axiom;
+                    }
+                    if let Some(iterations) = row.get::<_, Option<i32>>(20)? {
+                        cfg.iterations = iterations as u8;
+                    }
+                    if let Some(interval) = row.get::<_, Option<i32>>(21)? {
+                        cfg.step_interval = interval as i8;
+                    }
+                    if let Some(steps) = row.get::<_, Option<i32>>(22)? {
+                        cfg.note_duration_steps = steps as u8;
+                    }
+                    if let Some(velocity) = row.get::<_, Option<i32>>(23)? {
+                        cfg.velocity = velocity as u8;
+                    }
+                    GenerativeAlgorithm::LSystem(cfg)
+                }
+                other => {
+                    if other != "Euclidean" {
+                        eprintln!(
+                            "[imbolc] persistence: unknown GenerativeAlgorithm '{}', using Euclidean",
+                            other
+                        );
+                    }
+                    let mut cfg = EuclideanConfig {
+                        rate,
+                        ..EuclideanConfig::default()
+                    };
+                    if let Some(pulses) = row.get::<_, Option<i32>>(11)? {
+                        cfg.pulses = pulses as u8;
+                    }
+                    if let Some(steps) = row.get::<_, Option<i32>>(12)? {
+                        cfg.steps = steps as u8;
+                    }
+                    if let Some(rotation) = row.get::<_, Option<i32>>(13)? {
+                        cfg.rotation = rotation as u8;
+                    }
+                    if let Some(mode) = row.get::<_, Option<String>>(14)? {
+                        let fixed_pitch = row.get::<_, Option<i32>>(15)?.map(|p| p as u8);
+                        cfg.pitch_mode = decode_euclidean_pitch_mode(&mode, fixed_pitch);
+                    }
+                    GenerativeAlgorithm::Euclidean(cfg)
+                }
+            };
+
+            Ok(GenVoice {
+                id,
+                name: row.get(1)?,
+                enabled: row.get::<_, i32>(2)? != 0,
+                muted: row.get::<_, i32>(3)? != 0,
+                target_instrument: row
+                    .get::<_, Option<i64>>(4)?
+                    .map(|v| imbolc_types::InstrumentId::new(v as u32)),
+                algorithm,
+                velocity_min: row.get::<_, i32>(5)? as u8,
+                velocity_max: row.get::<_, i32>(6)? as u8,
+                octave_min: row.get::<_, i32>(7)? as i8,
+                octave_max: row.get::<_, i32>(8)? as i8,
+            })
+        })?
+        .collect::<SqlResult<_>>()?;
+
+    for mut voice in voices {
+        let voice_id = voice.id.get();
+        match &mut voice.algorithm {
+            GenerativeAlgorithm::Markov(cfg) => {
+                let cells = transition_stmt
+                    .query_map(params![voice_id], |row| {
+                        Ok((
+                            row.get::<_, i32>(0)?,
+                            row.get::<_, i32>(1)?,
+                            row.get::<_, f32>(2)?,
+                        ))
+                    })?
+                    .collect::<SqlResult<Vec<_>>>()?;
+                for (from, to, weight) in cells {
+                    if (0..12).contains(&from) && (0..12).contains(&to) {
+                        cfg.transition_matrix[from as usize][to as usize] = weight;
+                    }
+                }
+            }
+            GenerativeAlgorithm::LSystem(cfg) => {
+                cfg.rules = rule_stmt
+                    .query_map(params![voice_id], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<SqlResult<Vec<_>>>()?
+                    .into_iter()
+                    .filter_map(|(symbol, replacement)| {
+                        symbol.chars().next().map(|c| (c, replacement))
+                    })
+                    .collect();
+            }
+            GenerativeAlgorithm::Euclidean(_) => {}
+        }
+        gen.voices.push(voice);
+    }
+
+    Ok(())
+}
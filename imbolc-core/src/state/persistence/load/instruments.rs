@@ -322,6 +322,16 @@ pub(super) fn load_instruments(
             inst.processing_chain.push(ProcessingStage::Effect(effect));
         }
 
+        // Mod matrix (route columns absent before v19)
+        if super::super::schema::column_exists(conn, "instrument_modulations", "route_source")? {
+            load_mod_matrix(conn, r.id, &mut inst.modulation.matrix)?;
+        }
+
+        // Freeze render (table absent before v18)
+        if table_exists(conn, "instrument_freezes")? {
+            inst.frozen = load_frozen_render(conn, r.id)?;
+        }
+
         // Sends
         inst.mixer.sends = load_sends(conn, r.id)?;
 
@@ -350,6 +360,119 @@ pub(super) fn load_instruments(
     Ok(())
 }
 
+fn load_mod_matrix(
+    conn: &Connection,
+    instrument_id: u32,
+    matrix: &mut crate::state::instrument::ModMatrix,
+) -> SqlResult<()> {
+    use crate::state::instrument::{ModMatrixSource, ModRoute};
+
+    let mut stmt = conn.prepare(
+        "SELECT target_param, mod_type, lfo_rate, lfo_shape,
+            env_attack, env_decay, env_sustain, env_release,
+            route_source, route_target, route_depth
+         FROM instrument_modulations
+         WHERE instrument_id = ?1 AND mod_type LIKE 'Matrix%'",
+    )?;
+    let rows = stmt.query_map(params![instrument_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<f32>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<f32>>(4)?,
+            row.get::<_, Option<f32>>(5)?,
+            row.get::<_, Option<f32>>(6)?,
+            row.get::<_, Option<f32>>(7)?,
+            row.get::<_, Option<String>>(8)?,
+            row.get::<_, Option<String>>(9)?,
+            row.get::<_, Option<f32>>(10)?,
+        ))
+    })?;
+
+    let mut routes: Vec<(usize, ModRoute)> = Vec::new();
+    for row in rows {
+        let (
+            target_param,
+            mod_type,
+            lfo_rate,
+            lfo_shape,
+            env_a,
+            env_d,
+            env_s,
+            env_r,
+            route_source,
+            route_target,
+            route_depth,
+        ) = row?;
+        let Some(index) = target_param
+            .rsplit('_')
+            .next()
+            .and_then(|i| i.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        match mod_type.as_str() {
+            "MatrixLfo" => {
+                if let Some(lfo) = matrix.lfos.get_mut(index) {
+                    lfo.rate = lfo_rate.unwrap_or(lfo.rate);
+                    if let Some(shape) = lfo_shape {
+                        lfo.shape = decode_lfo_shape(&shape);
+                    }
+                }
+            }
+            "MatrixEnvelope" => {
+                if let Some(env) = matrix.envelopes.get_mut(index) {
+                    env.attack = env_a.unwrap_or(env.attack);
+                    env.decay = env_d.unwrap_or(env.decay);
+                    env.sustain = env_s.unwrap_or(env.sustain);
+                    env.release = env_r.unwrap_or(env.release);
+                }
+            }
+            "MatrixRoute" => {
+                let source = route_source
+                    .as_deref()
+                    .and_then(ModMatrixSource::from_short_name);
+                if let (Some(source), Some(target)) = (source, route_target) {
+                    routes.push((
+                        index,
+                        ModRoute {
+                            source,
+                            target: decode_parameter_target(&target),
+                            depth: route_depth.unwrap_or(0.0).clamp(-1.0, 1.0),
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    routes.sort_by_key(|(index, _)| *index);
+    matrix.routes = routes.into_iter().map(|(_, route)| route).collect();
+    Ok(())
+}
+
+fn load_frozen_render(
+    conn: &Connection,
+    instrument_id: u32,
+) -> SqlResult<Option<imbolc_types::FrozenRender>> {
+    conn.query_row(
+        "SELECT path, start_tick, end_tick, lead_in_secs, length_secs
+         FROM instrument_freezes WHERE instrument_id = ?1",
+        params![instrument_id],
+        |row| {
+            Ok(imbolc_types::FrozenRender {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                start_tick: row.get(1)?,
+                end_tick: row.get(2)?,
+                lead_in_secs: row.get(3)?,
+                length_secs: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+}
+
 #[derive(Debug)]
 struct InstrumentRow {
     id: u32,
@@ -547,9 +670,18 @@ fn load_sampler_config(
 ) -> SqlResult<Option<crate::state::sampler::SamplerConfig>> {
     use crate::state::sampler::{SamplerConfig, Slice};
 
+    // Tempo warp was added in v22
+    let warp_cols = if super::super::schema::column_exists(conn, "sampler_configs", "warp")? {
+        "source_bpm, warp"
+    } else {
+        "NULL, 0"
+    };
     let result = conn.query_row(
-        "SELECT buffer_id, sample_name, loop_mode, pitch_tracking, next_slice_id, selected_slice
-         FROM sampler_configs WHERE instrument_id = ?1",
+        &format!(
+            "SELECT buffer_id, sample_name, loop_mode, pitch_tracking, next_slice_id, selected_slice, {}
+             FROM sampler_configs WHERE instrument_id = ?1",
+            warp_cols
+        ),
         params![instrument_id],
         |row| {
             Ok((
@@ -559,12 +691,22 @@ fn load_sampler_config(
                 row.get::<_, i32>(3)?,
                 row.get::<_, u32>(4)?,
                 row.get::<_, i32>(5)?,
+                row.get::<_, Option<f32>>(6)?,
+                row.get::<_, i32>(7)?,
             ))
         },
     ).optional()?;
 
-    let Some((buffer_id, sample_name, loop_mode, pitch_tracking, next_slice_id, selected_slice)) =
-        result
+    let Some((
+        buffer_id,
+        sample_name,
+        loop_mode,
+        pitch_tracking,
+        next_slice_id,
+        selected_slice,
+        source_bpm,
+        warp,
+    )) = result
     else {
         return Ok(None);
     };
@@ -576,6 +718,8 @@ fn load_sampler_config(
     config.pitch_tracking = pitch_tracking != 0;
     config.set_next_slice_id(next_slice_id);
     config.selected_slice = selected_slice as usize;
+    config.source_bpm = source_bpm;
+    config.warp = warp != 0;
 
     // Slices
     let mut stmt = conn.prepare(
@@ -640,10 +784,17 @@ fn load_drum_sequencer(
         .collect::<SqlResult<_>>()?;
 
     // Pads
-    let mut pad_stmt = conn.prepare(
-        "SELECT pad_index, buffer_id, path, name, level, slice_start, slice_end, reverse, pitch, trigger_instrument_id, trigger_freq
-         FROM drum_pads WHERE instrument_id = ?1 ORDER BY pad_index"
-    )?;
+    // Pad warp tempos were added in v22
+    let warp_col = if super::super::schema::column_exists(conn, "drum_pads", "warp_bpm")? {
+        "warp_bpm"
+    } else {
+        "NULL"
+    };
+    let mut pad_stmt = conn.prepare(&format!(
+        "SELECT pad_index, buffer_id, path, name, level, slice_start, slice_end, reverse, pitch, trigger_instrument_id, trigger_freq, {}
+         FROM drum_pads WHERE instrument_id = ?1 ORDER BY pad_index",
+        warp_col
+    ))?;
     #[allow(clippy::type_complexity)]
     let pads: Vec<(
         usize,
@@ -657,6 +808,7 @@ fn load_drum_sequencer(
         i32,
         Option<i64>,
         f32,
+        Option<f32>,
     )> = pad_stmt
         .query_map(params![instrument_id], |row| {
             Ok((
@@ -671,6 +823,7 @@ fn load_drum_sequencer(
                 row.get(8)?,
                 row.get(9)?,
                 row.get(10)?,
+                row.get(11)?,
             ))
         })?
         .collect::<SqlResult<_>>()?;
@@ -687,6 +840,7 @@ fn load_drum_sequencer(
         pitch,
         trigger_inst,
         trigger_freq,
+        warp_bpm,
     ) in pads
     {
         if idx < seq.pads.len() {
@@ -701,6 +855,7 @@ fn load_drum_sequencer(
             seq.pads[idx].instrument_id =
                 trigger_inst.map(|id| imbolc_types::InstrumentId::new(id as u32));
             seq.pads[idx].trigger_freq = trigger_freq;
+            seq.pads[idx].warp_bpm = warp_bpm;
         }
     }
 
@@ -728,34 +883,50 @@ fn load_drum_sequencer(
     }
 
     // Steps (only active ones were saved)
-    let mut step_stmt = conn.prepare(
-        "SELECT pattern_index, pad_index, step_index, velocity, probability, pitch_offset
+    // Step parameter locks were added in v25, trig conditions and ratchets in v26
+    let locks_col = if super::super::schema::column_exists(conn, "drum_steps", "locks")? {
+        "locks"
+    } else {
+        "NULL"
+    };
+    let trig_cols = if super::super::schema::column_exists(conn, "drum_steps", "condition")? {
+        "condition, ratchet, ratchet_decay"
+    } else {
+        "'Always', 1, 1.0"
+    };
+    let mut step_stmt = conn.prepare(&format!(
+        "SELECT pattern_index, pad_index, step_index, velocity, probability, pitch_offset, {}, {}
          FROM drum_steps WHERE instrument_id = ?1",
-    )?;
-    let steps: Vec<(usize, usize, usize, u8, f32, i8)> = step_stmt
+        locks_col, trig_cols
+    ))?;
+    let steps: Vec<(usize, usize, usize, DrumStep)> = step_stmt
         .query_map(params![instrument_id], |row| {
+            let locks: Option<String> = row.get(6)?;
+            let condition: String = row.get(7)?;
             Ok((
                 row.get::<_, i32>(0)? as usize,
                 row.get::<_, i32>(1)? as usize,
                 row.get::<_, i32>(2)? as usize,
-                row.get::<_, i32>(3)? as u8,
-                row.get::<_, f32>(4)?,
-                row.get::<_, i32>(5)? as i8,
+                DrumStep {
+                    active: true,
+                    velocity: row.get::<_, i32>(3)? as u8,
+                    probability: row.get(4)?,
+                    pitch_offset: row.get::<_, i32>(5)? as i8,
+                    locks: locks.as_deref().map(decode_step_locks).unwrap_or_default(),
+                    condition: decode_step_condition(&condition),
+                    ratchet: row.get::<_, i32>(8)? as u8,
+                    ratchet_decay: row.get(9)?,
+                },
             ))
         })?
         .collect::<SqlResult<_>>()?;
 
-    for (pat_idx, pad_idx, step_idx, velocity, probability, pitch_offset) in steps {
+    for (pat_idx, pad_idx, step_idx, step) in steps {
         if pat_idx < seq.patterns.len()
             && pad_idx < seq.patterns[pat_idx].steps.len()
             && step_idx < seq.patterns[pat_idx].steps[pad_idx].len()
         {
-            seq.patterns[pat_idx].steps[pad_idx][step_idx] = DrumStep {
-                active: true,
-                velocity,
-                probability,
-                pitch_offset,
-            };
+            seq.patterns[pat_idx].steps[pad_idx][step_idx] = step;
         }
     }
 
@@ -772,9 +943,18 @@ fn load_chopper(
     use crate::state::drum_sequencer::ChopperState;
     use crate::state::sampler::Slice;
 
+    // Tempo warp was added in v22
+    let warp_cols = if super::super::schema::column_exists(conn, "chopper_states", "warp")? {
+        "source_bpm, warp"
+    } else {
+        "NULL, 0"
+    };
     let result = conn.query_row(
-        "SELECT buffer_id, path, name, selected_slice, next_slice_id, duration_secs, waveform_peaks
-         FROM chopper_states WHERE instrument_id = ?1",
+        &format!(
+            "SELECT buffer_id, path, name, selected_slice, next_slice_id, duration_secs, waveform_peaks, {}
+             FROM chopper_states WHERE instrument_id = ?1",
+            warp_cols
+        ),
         params![instrument_id],
         |row| {
             Ok((
@@ -785,12 +965,23 @@ fn load_chopper(
                 row.get::<_, u32>(4)?,
                 row.get::<_, f32>(5)?,
                 row.get::<_, Option<Vec<u8>>>(6)?,
+                row.get::<_, Option<f32>>(7)?,
+                row.get::<_, i32>(8)?,
             ))
         },
     ).optional()?;
 
-    let Some((buffer_id, path, name, selected_slice, next_slice_id, duration_secs, peaks_blob)) =
-        result
+    let Some((
+        buffer_id,
+        path,
+        name,
+        selected_slice,
+        next_slice_id,
+        duration_secs,
+        peaks_blob,
+        source_bpm,
+        warp,
+    )) = result
     else {
         return Ok(None);
     };
@@ -825,5 +1016,7 @@ fn load_chopper(
         next_slice_id,
         waveform_peaks,
         duration_secs,
+        source_bpm,
+        warp: warp != 0,
     }))
 }
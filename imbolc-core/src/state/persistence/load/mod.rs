@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use imbolc_types::{ExpressionDimension, ExpressionPoint, NoteExpression};
 use rusqlite::{params, Connection, Result as SqlResult};
 
 use crate::state::instrument_state::InstrumentState;
@@ -7,6 +9,7 @@ use crate::state::session::SessionState;
 
 mod arrangement;
 pub(crate) mod decoders;
+mod generative;
 mod instruments;
 mod mixer;
 mod session;
@@ -21,6 +24,7 @@ pub fn load_relational(conn: &Connection) -> SqlResult<(SessionState, Instrument
     mixer::load_mixer(conn, &mut session)?;
     mixer::load_layer_group_mixers(conn, &mut session)?;
     session::load_musical_settings(conn, &mut session)?;
+    session::load_tempo_map(conn, &mut session)?;
     session::load_piano_roll(conn, &mut session)?;
     session::load_custom_synthdefs(conn, &mut session)?;
     session::load_vst_plugins(conn, &mut session)?;
@@ -28,6 +32,7 @@ pub fn load_relational(conn: &Connection) -> SqlResult<(SessionState, Instrument
     arrangement::load_automation(conn, &mut session)?;
     arrangement::load_midi_recording(conn, &mut session)?;
     arrangement::load_arrangement(conn, &mut session)?;
+    generative::load_generative(conn, &mut session)?;
 
     // Recompute derived state
     session.recompute_next_bus_id();
@@ -45,6 +50,33 @@ pub(super) fn table_exists(conn: &Connection, name: &str) -> SqlResult<bool> {
     Ok(count > 0)
 }
 
+/// Load note expression curves keyed by the first selected column.
+/// `sql` selects (key, dimension, tick, value) ordered by key and position.
+pub(super) fn load_note_expressions<P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> SqlResult<HashMap<i64, NoteExpression>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows: Vec<(i64, String, u32, f32)> = stmt
+        .query_map(params, |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<SqlResult<_>>()?;
+
+    let mut expressions: HashMap<i64, NoteExpression> = HashMap::new();
+    for (key, dimension, tick, value) in rows {
+        if let Some(dimension) = ExpressionDimension::from_name(&dimension) {
+            expressions
+                .entry(key)
+                .or_default()
+                .curve_mut(dimension)
+                .push(ExpressionPoint { tick, value });
+        }
+    }
+    Ok(expressions)
+}
+
 pub(super) fn load_params(
     conn: &Connection,
     table: &str,
@@ -97,23 +129,39 @@ pub(super) fn load_effects_from(
 ) -> SqlResult<Vec<crate::state::instrument::EffectSlot>> {
     use crate::state::instrument::EffectSlot;
 
+    // Sidechain key sources were added in v17
+    let sidechain_col = if super::schema::column_exists(conn, effects_table, "sidechain_source")? {
+        "sidechain_source"
+    } else {
+        "NULL"
+    };
     let sql = format!(
-        "SELECT effect_id, effect_type, enabled, vst_state_path FROM {} WHERE {} = ?1 ORDER BY position",
-        effects_table, owner_col
+        "SELECT effect_id, effect_type, enabled, vst_state_path, {} FROM {} WHERE {} = ?1 ORDER BY position",
+        sidechain_col, effects_table, owner_col
     );
     let mut stmt = conn.prepare(&sql)?;
-    let effect_rows: Vec<(u32, String, i32, Option<String>)> = stmt
+    type EffectRow = (u32, String, i32, Option<String>, Option<String>);
+    let effect_rows: Vec<EffectRow> = stmt
         .query_map(params![owner_id], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
         })?
         .collect::<SqlResult<_>>()?;
 
     let mut effects = Vec::new();
-    for (effect_id, effect_type_str, enabled, vst_state) in effect_rows {
+    for (effect_id, effect_type_str, enabled, vst_state, sidechain) in effect_rows {
         let effect_type = decoders::decode_effect_type(&effect_type_str);
         let mut slot = EffectSlot::new(imbolc_types::EffectId::new(effect_id), effect_type);
         slot.enabled = enabled != 0;
         slot.vst_state_path = vst_state.map(PathBuf::from);
+        slot.sidechain = sidechain
+            .as_deref()
+            .and_then(decoders::decode_sidechain_source);
 
         // Effect params
         slot.params = load_effect_params_from(conn, params_table, owner_col, owner_id, effect_id)?;
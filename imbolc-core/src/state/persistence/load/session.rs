@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
 
 use super::decoders::*;
+use super::{load_note_expressions, table_exists};
 use crate::state::instrument_state::InstrumentState;
 use crate::state::session::SessionState;
 
@@ -192,6 +194,42 @@ pub(super) fn load_musical_settings(
     Ok(())
 }
 
+pub(super) fn load_tempo_map(conn: &Connection, session: &mut SessionState) -> SqlResult<()> {
+    use crate::state::piano_roll::{MeterChange, TempoEvent};
+
+    // v19 files have no tempo map
+    if !table_exists(conn, "tempo_events")? {
+        return Ok(());
+    }
+    let map = &mut session.piano_roll.tempo_map;
+
+    let mut stmt = conn.prepare("SELECT tick, bpm, ramp FROM tempo_events ORDER BY tick")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TempoEvent {
+            tick: row.get(0)?,
+            bpm: row.get(1)?,
+            ramp: row.get::<_, i32>(2)? != 0,
+        })
+    })?;
+    for event in rows {
+        map.set_tempo(event?);
+    }
+
+    let mut stmt =
+        conn.prepare("SELECT bar, time_sig_num, time_sig_denom FROM meter_changes ORDER BY bar")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(MeterChange {
+            bar: row.get(0)?,
+            time_signature: (row.get::<_, i32>(1)? as u8, row.get::<_, i32>(2)? as u8),
+        })
+    })?;
+    for change in rows {
+        map.set_meter(change?);
+    }
+
+    Ok(())
+}
+
 pub(super) fn load_piano_roll(conn: &Connection, session: &mut SessionState) -> SqlResult<()> {
     use crate::state::piano_roll::Note;
 
@@ -218,12 +256,24 @@ pub(super) fn load_piano_roll(conn: &Connection, session: &mut SessionState) ->
         }
     }
 
+    // Note expression curves (v22 files have none)
+    let mut expressions = if table_exists(conn, "piano_roll_note_expression")? {
+        load_note_expressions(
+            conn,
+            "SELECT note_id, dimension, tick, value FROM piano_roll_note_expression
+             ORDER BY note_id, dimension, position",
+            [],
+        )?
+    } else {
+        HashMap::new()
+    };
+
     // Load notes
     let mut note_stmt = conn.prepare(
-        "SELECT track_instrument_id, tick, duration, pitch, velocity, probability
-         FROM piano_roll_notes ORDER BY track_instrument_id, tick",
+        "SELECT track_instrument_id, tick, duration, pitch, velocity, probability, id
+         FROM piano_roll_notes ORDER BY track_instrument_id, tick, id",
     )?;
-    let notes: Vec<(imbolc_types::InstrumentId, Note)> = note_stmt
+    let notes: Vec<(imbolc_types::InstrumentId, Note, i64)> = note_stmt
         .query_map([], |row| {
             Ok((
                 imbolc_types::InstrumentId::new(row.get::<_, u32>(0)?),
@@ -233,13 +283,16 @@ pub(super) fn load_piano_roll(conn: &Connection, session: &mut SessionState) ->
                     pitch: row.get::<_, i32>(3)? as u8,
                     velocity: row.get::<_, i32>(4)? as u8,
                     probability: row.get::<_, f32>(5)?,
+                    expression: None,
                 },
+                row.get::<_, i64>(6)?,
             ))
         })?
         .collect::<SqlResult<_>>()?;
 
-    for (inst_id, note) in notes {
+    for (inst_id, mut note, note_id) in notes {
         if let Some(track) = session.piano_roll.tracks.get_mut(&inst_id) {
+            note.expression = expressions.remove(&note_id).map(Box::new);
             track.notes.push(note);
         }
     }
@@ -330,6 +383,7 @@ pub(super) fn load_vst_plugins(conn: &Connection, session: &mut SessionState) ->
             plugin_path: PathBuf::from(plugin_path),
             kind,
             params,
+            latency_secs: None,
         });
     }
 
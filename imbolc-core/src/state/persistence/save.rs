@@ -2,6 +2,7 @@ use rusqlite::{params, Connection, Result as SqlResult};
 
 use crate::state::instrument_state::InstrumentState;
 use crate::state::session::SessionState;
+use imbolc_types::{ExpressionDimension, ExpressionPoint, NoteExpression};
 
 use super::schema::{self, SCHEMA_VERSION};
 
@@ -25,12 +26,14 @@ pub fn save_relational(
     save_mixer(conn, session)?;
     save_layer_group_mixers(conn, session)?;
     save_musical_settings(conn, session)?;
+    save_tempo_map(conn, session)?;
     save_piano_roll(conn, session)?;
     save_automation(conn, session)?;
     save_custom_synthdefs(conn, session)?;
     save_vst_plugins(conn, session)?;
     save_midi_recording(conn, session)?;
     save_arrangement(conn, session)?;
+    save_generative(conn, session)?;
 
     Ok(())
 }
@@ -367,6 +370,9 @@ fn save_instruments(conn: &Connection, instruments: &InstrumentState) -> SqlResu
             )?;
         }
 
+        // Mod matrix
+        save_mod_matrix(conn, inst.id.get(), &inst.modulation.matrix)?;
+
         // EQ bands
         if let Some(eq) = inst.eq() {
             for (i, band) in eq.bands.iter().enumerate() {
@@ -384,6 +390,23 @@ fn save_instruments(conn: &Connection, instruments: &InstrumentState) -> SqlResu
         // Processing chain order
         save_processing_chain(conn, inst.id.get(), &inst.processing_chain)?;
 
+        // Freeze render
+        if let Some(frozen) = &inst.frozen {
+            conn.execute(
+                "INSERT INTO instrument_freezes
+                    (instrument_id, path, start_tick, end_tick, lead_in_secs, length_secs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    inst.id.get(),
+                    frozen.path.to_string_lossy().to_string(),
+                    frozen.start_tick,
+                    frozen.end_tick,
+                    frozen.lead_in_secs,
+                    frozen.length_secs
+                ],
+            )?;
+        }
+
         // VST param values
         for (param_idx, value) in inst.vst_source_params() {
             conn.execute(
@@ -462,9 +485,11 @@ fn save_effects_to(
             .as_ref()
             .map(|p| p.to_string_lossy().to_string());
 
+        let sidechain = effect.sidechain.map(encode_sidechain_source);
+
         let sql = format!(
-            "INSERT INTO {} ({}, effect_id, position, effect_type, enabled, vst_state_path)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO {} ({}, effect_id, position, effect_type, enabled, vst_state_path, sidechain_source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             effects_table, owner_col
         );
         conn.execute(
@@ -475,7 +500,8 @@ fn save_effects_to(
                 pos as i32,
                 effect_type,
                 effect.enabled as i32,
-                vst_state
+                vst_state,
+                sidechain
             ],
         )?;
 
@@ -593,14 +619,67 @@ fn save_modulation(
     Ok(())
 }
 
+/// Mod matrix sources and routes share `instrument_modulations` with the
+/// per-parameter modulations, keyed by `matrix_lfo_N`, `matrix_env_N` and
+/// `matrix_route_N`.
+fn save_mod_matrix(
+    conn: &Connection,
+    instrument_id: u32,
+    matrix: &crate::state::instrument::ModMatrix,
+) -> SqlResult<()> {
+    for (i, lfo) in matrix.lfos.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO instrument_modulations (instrument_id, target_param, mod_type,
+                lfo_rate, lfo_shape)
+             VALUES (?1, ?2, 'MatrixLfo', ?3, ?4)",
+            params![
+                instrument_id,
+                format!("matrix_lfo_{}", i),
+                lfo.rate,
+                format!("{:?}", lfo.shape),
+            ],
+        )?;
+    }
+    for (i, env) in matrix.envelopes.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO instrument_modulations (instrument_id, target_param, mod_type,
+                env_attack, env_decay, env_sustain, env_release)
+             VALUES (?1, ?2, 'MatrixEnvelope', ?3, ?4, ?5, ?6)",
+            params![
+                instrument_id,
+                format!("matrix_env_{}", i),
+                env.attack,
+                env.decay,
+                env.sustain,
+                env.release,
+            ],
+        )?;
+    }
+    for (i, route) in matrix.routes.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO instrument_modulations (instrument_id, target_param, mod_type,
+                route_source, route_target, route_depth)
+             VALUES (?1, ?2, 'MatrixRoute', ?3, ?4, ?5)",
+            params![
+                instrument_id,
+                format!("matrix_route_{}", i),
+                route.source.short_name(),
+                encode_parameter_target(&route.target),
+                route.depth,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
 fn save_sampler_config(
     conn: &Connection,
     instrument_id: u32,
     config: &crate::state::sampler::SamplerConfig,
 ) -> SqlResult<()> {
     conn.execute(
-        "INSERT INTO sampler_configs (instrument_id, buffer_id, sample_name, loop_mode, pitch_tracking, next_slice_id, selected_slice)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO sampler_configs (instrument_id, buffer_id, sample_name, loop_mode, pitch_tracking, next_slice_id, selected_slice, source_bpm, warp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
         params![
             instrument_id,
             config.buffer_id.map(|id| id as i64),
@@ -609,6 +688,8 @@ fn save_sampler_config(
             config.pitch_tracking as i32,
             config.next_slice_id(),
             config.selected_slice as i32,
+            config.source_bpm,
+            config.warp as i32,
         ],
     )?;
 
@@ -655,8 +736,8 @@ fn save_drum_sequencer(
     // Pads
     for (pad_idx, pad) in seq.pads.iter().enumerate() {
         conn.execute(
-            "INSERT INTO drum_pads (instrument_id, pad_index, buffer_id, path, name, level, slice_start, slice_end, reverse, pitch, trigger_instrument_id, trigger_freq)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            "INSERT INTO drum_pads (instrument_id, pad_index, buffer_id, path, name, level, slice_start, slice_end, reverse, pitch, trigger_instrument_id, trigger_freq, warp_bpm)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 instrument_id, pad_idx as i32,
                 pad.buffer_id.map(|id| id as i64),
@@ -669,6 +750,7 @@ fn save_drum_sequencer(
                 pad.pitch as i32,
                 pad.instrument_id.map(|id| id.get() as i64),
                 pad.trigger_freq,
+                pad.warp_bpm,
             ],
         )?;
     }
@@ -686,11 +768,14 @@ fn save_drum_sequencer(
             for (step_idx, step) in pad_steps.iter().enumerate() {
                 if step.active {
                     conn.execute(
-                        "INSERT INTO drum_steps (instrument_id, pattern_index, pad_index, step_index, velocity, probability, pitch_offset)
-                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        "INSERT INTO drum_steps (instrument_id, pattern_index, pad_index, step_index, velocity, probability, pitch_offset, locks, condition, ratchet, ratchet_decay)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
                         params![
                             instrument_id, pat_idx as i32, pad_idx as i32, step_idx as i32,
                             step.velocity as i32, step.probability, step.pitch_offset as i32,
+                            encode_step_locks(&step.locks),
+                            encode_step_condition(&step.condition),
+                            step.ratchet as i32, step.ratchet_decay,
                         ],
                     )?;
                 }
@@ -712,8 +797,8 @@ fn save_drum_sequencer(
         };
 
         conn.execute(
-            "INSERT INTO chopper_states (instrument_id, buffer_id, path, name, selected_slice, next_slice_id, duration_secs, waveform_peaks)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO chopper_states (instrument_id, buffer_id, path, name, selected_slice, next_slice_id, duration_secs, waveform_peaks, source_bpm, warp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 instrument_id,
                 chopper.buffer_id.map(|id| id as i64),
@@ -723,6 +808,8 @@ fn save_drum_sequencer(
                 chopper.next_slice_id,
                 chopper.duration_secs,
                 peaks_blob,
+                chopper.source_bpm,
+                chopper.warp as i32,
             ],
         )?;
 
@@ -855,6 +942,28 @@ fn save_musical_settings(conn: &Connection, session: &SessionState) -> SqlResult
     Ok(())
 }
 
+fn save_tempo_map(conn: &Connection, session: &SessionState) -> SqlResult<()> {
+    let map = &session.piano_roll.tempo_map;
+    {
+        let mut stmt =
+            conn.prepare("INSERT INTO tempo_events (tick, bpm, ramp) VALUES (?1, ?2, ?3)")?;
+        for event in &map.tempos {
+            stmt.execute(params![event.tick, event.bpm, event.ramp as i32])?;
+        }
+    }
+    let mut stmt = conn.prepare(
+        "INSERT INTO meter_changes (bar, time_sig_num, time_sig_denom) VALUES (?1, ?2, ?3)",
+    )?;
+    for change in &map.meters {
+        stmt.execute(params![
+            change.bar,
+            change.time_signature.0,
+            change.time_signature.1
+        ])?;
+    }
+    Ok(())
+}
+
 fn save_piano_roll(conn: &Connection, session: &SessionState) -> SqlResult<()> {
     let pr = &session.piano_roll;
     let mut note_id: i64 = 0;
@@ -878,6 +987,15 @@ fn save_piano_roll(conn: &Connection, session: &SessionState) -> SqlResult<()> {
                         note.probability,
                     ],
                 )?;
+                if let Some(expression) = &note.expression {
+                    for (dimension, pos, point) in expression_rows(expression) {
+                        conn.execute(
+                            "INSERT INTO piano_roll_note_expression (note_id, dimension, position, tick, value)
+                             VALUES (?1, ?2, ?3, ?4, ?5)",
+                            params![note_id, dimension, pos, point.tick as i64, point.value],
+                        )?;
+                    }
+                }
                 note_id += 1;
             }
         }
@@ -885,6 +1003,21 @@ fn save_piano_roll(conn: &Connection, session: &SessionState) -> SqlResult<()> {
     Ok(())
 }
 
+/// Rows of a note's expression curves: (dimension, position in curve, point)
+fn expression_rows(
+    expression: &NoteExpression,
+) -> impl Iterator<Item = (&'static str, i32, &ExpressionPoint)> {
+    ExpressionDimension::ALL
+        .into_iter()
+        .flat_map(move |dimension| {
+            expression
+                .curve(dimension)
+                .iter()
+                .enumerate()
+                .map(move |(pos, point)| (dimension.name(), pos as i32, point))
+        })
+}
+
 // ============================================================
 // Automation
 // ============================================================
@@ -993,12 +1126,16 @@ fn save_vst_plugins(conn: &Connection, session: &SessionState) -> SqlResult<()>
 fn save_midi_recording(conn: &Connection, session: &SessionState) -> SqlResult<()> {
     let midi = &session.midi_recording;
     conn.execute(
-        "INSERT INTO midi_recording_settings (id, live_input_instrument, note_passthrough, channel_filter)
-         VALUES (1, ?1, ?2, ?3)",
+        "INSERT INTO midi_recording_settings (id, live_input_instrument, note_passthrough, channel_filter, mpe, mpe_bend_range, note_record_mode, input_quantize)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         params![
             midi.live_input_instrument.map(|id| id.get() as i64),
             midi.note_passthrough as i32,
             midi.channel_filter.map(|ch| ch as i32),
+            midi.mpe as i32,
+            midi.mpe_bend_range as i32,
+            format!("{:?}", midi.note_record_mode),
+            midi.input_quantize.map(|grid| grid as i64),
         ],
     )?;
 
@@ -1095,6 +1232,18 @@ fn save_arrangement(conn: &Connection, session: &SessionState) -> SqlResult<()>
                     note.probability,
                 ],
             )?;
+            if let Some(expression) = &note.expression {
+                for (dimension, point_pos, point) in expression_rows(expression) {
+                    conn.execute(
+                        "INSERT INTO arrangement_clip_note_expression (clip_id, note_position, dimension, position, tick, value)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            clip.id, pos as i32, dimension, point_pos,
+                            point.tick as i64, point.value,
+                        ],
+                    )?;
+                }
+            }
         }
 
         // Clip automation lanes
@@ -1141,6 +1290,205 @@ fn save_arrangement(conn: &Connection, session: &SessionState) -> SqlResult<()>
         )?;
     }
 
+    // Audio clips
+    for clip in &arr.audio_clips {
+        let peaks_blob: Option<Vec<u8>> = if clip.waveform_peaks.is_empty() {
+            None
+        } else {
+            let mut bytes = Vec::with_capacity(clip.waveform_peaks.len() * 4);
+            for &peak in &clip.waveform_peaks {
+                bytes.extend_from_slice(&peak.to_le_bytes());
+            }
+            Some(bytes)
+        };
+        conn.execute(
+            "INSERT INTO arrangement_audio_clips (id, name, path, instrument_id, start_tick, duration_ticks, sample_rate, length_secs, waveform_peaks, muted)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                clip.id,
+                clip.name,
+                clip.path.to_string_lossy(),
+                clip.instrument_id.get(),
+                clip.start_tick as i64,
+                clip.duration_ticks as i64,
+                clip.sample_rate,
+                clip.length_secs,
+                peaks_blob,
+                clip.muted,
+            ],
+        )?;
+    }
+
+    // Markers
+    for marker in &arr.markers {
+        conn.execute(
+            "INSERT INTO arrangement_markers (id, name, tick, end_tick) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                marker.id,
+                marker.name,
+                marker.tick as i64,
+                marker.end_tick.map(|t| t as i64),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+// ============================================================
+// Generative Engine
+// ============================================================
+
+fn save_generative(conn: &Connection, session: &SessionState) -> SqlResult<()> {
+    use imbolc_types::{EuclideanPitchMode, GenerativeAlgorithm};
+
+    let gen = &session.generative;
+    let macros = &gen.macros;
+    let constraints = &gen.constraints;
+    conn.execute(
+        "INSERT INTO generative_state (id, enabled, capture_enabled, next_voice_id,
+            macro_density, macro_chaos, macro_energy, macro_motion,
+            scale_lock, pitch_min, pitch_max, max_notes_per_beat, humanize_timing, humanize_velocity)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            gen.enabled as i32,
+            gen.capture_enabled as i32,
+            gen.next_voice_id,
+            macros.density,
+            macros.chaos,
+            macros.energy,
+            macros.motion,
+            constraints.scale_lock as i32,
+            constraints.pitch_min as i32,
+            constraints.pitch_max as i32,
+            constraints.max_notes_per_beat as i32,
+            constraints.humanize_timing,
+            constraints.humanize_velocity,
+        ],
+    )?;
+
+    let mut voice_stmt = conn.prepare(
+        "INSERT INTO generative_voices (id, position, name, enabled, muted, target_instrument_id,
+            velocity_min, velocity_max, octave_min, octave_max, algorithm, rate,
+            euclid_pulses, euclid_steps, euclid_rotation, euclid_pitch_mode, euclid_fixed_pitch,
+            markov_initial_pitch_class, markov_rest_probability, markov_duration_mode,
+            lsystem_axiom, lsystem_iterations, lsystem_step_interval, lsystem_note_duration_steps, lsystem_velocity)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+            ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+    )?;
+    let mut transition_stmt = conn.prepare(
+        "INSERT INTO generative_markov_transitions (voice_id, from_pitch_class, to_pitch_class, weight)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut rule_stmt = conn.prepare(
+        "INSERT INTO generative_lsystem_rules (voice_id, position, symbol, replacement)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+
+    for (pos, voice) in gen.voices.iter().enumerate() {
+        let voice_id = voice.id.get();
+        let rate = format!("{:?}", voice.algorithm.rate());
+
+        #[allow(clippy::type_complexity)]
+        let mut euclid: (
+            Option<i32>,
+            Option<i32>,
+            Option<i32>,
+            Option<&str>,
+            Option<i32>,
+        ) = (None, None, None, None, None);
+        let mut markov: (Option<i32>, Option<f32>, Option<String>) = (None, None, None);
+        #[allow(clippy::type_complexity)]
+        let mut lsystem: (
+            Option<&str>,
+            Option<i32>,
+            Option<i32>,
+            Option<i32>,
+            Option<i32>,
+        ) = (None, None, None, None, None);
+
+        let algorithm = match &voice.algorithm {
+            GenerativeAlgorithm::Euclidean(cfg) => {
+                let (mode, fixed_pitch) = match cfg.pitch_mode {
+                    EuclideanPitchMode::Fixed(pitch) => ("Fixed", Some(pitch as i32)),
+                    EuclideanPitchMode::ScaleWalk => ("ScaleWalk", None),
+                    EuclideanPitchMode::RandomInScale => ("RandomInScale", None),
+                };
+                euclid = (
+                    Some(cfg.pulses as i32),
+                    Some(cfg.steps as i32),
+                    Some(cfg.rotation as i32),
+                    Some(mode),
+                    fixed_pitch,
+                );
+                "Euclidean"
+            }
+            GenerativeAlgorithm::Markov(cfg) => {
+                markov = (
+                    Some(cfg.initial_pitch_class as i32),
+                    Some(cfg.rest_probability),
+                    Some(format!("{:?}", cfg.duration_mode)),
+                );
+                for (from, row) in cfg.transition_matrix.iter().enumerate() {
+                    for (to, weight) in row.iter().enumerate() {
+                        transition_stmt.execute(params![
+                            voice_id,
+                            from as i32,
+                            to as i32,
+                            weight
+                        ])?;
+                    }
+                }
+                "Markov"
+            }
+            GenerativeAlgorithm::LSystem(cfg) => {
+                lsystem = (
+                    Some(cfg.axiom.as_str()),
+                    Some(cfg.iterations as i32),
+                    Some(cfg.step_interval as i32),
+                    Some(cfg.note_duration_steps as i32),
+                    Some(cfg.velocity as i32),
+                );
+                for (rule_pos, (symbol, replacement)) in cfg.rules.iter().enumerate() {
+                    rule_stmt.execute(params![
+                        voice_id,
+                        rule_pos as i32,
+                        symbol.to_string(),
+                        replacement,
+                    ])?;
+                }
+                "LSystem"
+            }
+        };
+
+        voice_stmt.execute(params![
+            voice_id,
+            pos as i32,
+            voice.name,
+            voice.enabled as i32,
+            voice.muted as i32,
+            voice.target_instrument.map(|id| id.get() as i64),
+            voice.velocity_min as i32,
+            voice.velocity_max as i32,
+            voice.octave_min as i32,
+            voice.octave_max as i32,
+            algorithm,
+            rate,
+            euclid.0,
+            euclid.1,
+            euclid.2,
+            euclid.3,
+            euclid.4,
+            markov.0,
+            markov.1,
+            markov.2,
+            lsystem.0,
+            lsystem.1,
+            lsystem.2,
+            lsystem.3,
+            lsystem.4,
+        ])?;
+    }
     Ok(())
 }
 
@@ -1153,6 +1501,7 @@ fn encode_source_type(source: &crate::state::instrument::SourceType) -> String {
     match source {
         SourceType::Custom(id) => format!("Custom:{}", id),
         SourceType::Vst(id) => format!("Vst:{}", id),
+        SourceType::MidiOut { port, channel } => format!("MidiOut:{}:{}", port, channel),
         other => format!("{:?}", other),
     }
 }
@@ -1165,6 +1514,16 @@ fn encode_effect_type(effect: &crate::state::instrument::EffectType) -> String {
     }
 }
 
+fn encode_sidechain_source(source: imbolc_types::SidechainSource) -> String {
+    use imbolc_types::SidechainSource;
+    match source {
+        SidechainSource::Instrument { id, tap } => {
+            format!("instrument:{}:{}", id, encode_tap_point(tap))
+        }
+        SidechainSource::LayerGroup(group_id) => format!("group:{}", group_id),
+    }
+}
+
 fn encode_tap_point(tap_point: crate::state::instrument::SendTapPoint) -> &'static str {
     use crate::state::instrument::SendTapPoint;
     match tap_point {
@@ -1195,6 +1554,27 @@ pub fn encode_parameter_target(target: &crate::state::instrument::ParameterTarge
     }
 }
 
+/// Step parameter locks as `target=value` pairs separated by `;`, or `None`
+/// when the step has no locks
+pub fn encode_step_locks(locks: &[crate::state::drum_sequencer::ParamLock]) -> Option<String> {
+    if locks.is_empty() {
+        return None;
+    }
+    let pairs: Vec<String> = locks
+        .iter()
+        .map(|l| format!("{}={}", encode_parameter_target(&l.target), l.value))
+        .collect();
+    Some(pairs.join(";"))
+}
+
+pub fn encode_step_condition(condition: &crate::state::drum_sequencer::StepCondition) -> String {
+    use crate::state::drum_sequencer::StepCondition;
+    match condition {
+        StepCondition::Loop { n, m } => format!("Loop:{}:{}", n, m),
+        other => format!("{:?}", other),
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn encode_automation_target(
     target: &crate::state::AutomationTarget,
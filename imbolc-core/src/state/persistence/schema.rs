@@ -1,11 +1,97 @@
 use rusqlite::{Connection, Result as SqlResult};
 
 /// Schema version for the relational format.
-pub const SCHEMA_VERSION: i32 = 13;
+pub const SCHEMA_VERSION: i32 = 26;
 
 /// Create all tables for the relational schema.
 pub fn create_tables(conn: &Connection) -> SqlResult<()> {
-    conn.execute_batch(SCHEMA_SQL)
+    conn.execute_batch(SCHEMA_SQL)?;
+    // v15 files have audio clips without the take mute flag
+    if !column_exists(conn, "arrangement_audio_clips", "muted")? {
+        conn.execute_batch(
+            "ALTER TABLE arrangement_audio_clips ADD COLUMN muted INTEGER NOT NULL DEFAULT 0",
+        )?;
+    }
+    // v16 files have effects without sidechain key sources
+    for table in ["instrument_effects", "bus_effects", "layer_group_effects"] {
+        if !column_exists(conn, table, "sidechain_source")? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN sidechain_source TEXT",
+                table
+            ))?;
+        }
+    }
+    // v18 files have modulations without mod matrix routes
+    for (column, ty) in [
+        ("route_source", "TEXT"),
+        ("route_target", "TEXT"),
+        ("route_depth", "REAL"),
+    ] {
+        if !column_exists(conn, "instrument_modulations", column)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE instrument_modulations ADD COLUMN {} {}",
+                column, ty
+            ))?;
+        }
+    }
+    // v21 files have samples without tempo warp
+    for (table, column, ty) in [
+        ("sampler_configs", "source_bpm", "REAL"),
+        ("sampler_configs", "warp", "INTEGER NOT NULL DEFAULT 0"),
+        ("chopper_states", "source_bpm", "REAL"),
+        ("chopper_states", "warp", "INTEGER NOT NULL DEFAULT 0"),
+        ("drum_pads", "warp_bpm", "REAL"),
+    ] {
+        if !column_exists(conn, table, column)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, ty
+            ))?;
+        }
+    }
+    // v22 files have MIDI input settings without MPE, v23 files without
+    // note take recording
+    for (column, ty) in [
+        ("mpe", "INTEGER NOT NULL DEFAULT 0"),
+        ("mpe_bend_range", "INTEGER NOT NULL DEFAULT 48"),
+        ("note_record_mode", "TEXT NOT NULL DEFAULT 'Overdub'"),
+        ("input_quantize", "INTEGER"),
+    ] {
+        if !column_exists(conn, "midi_recording_settings", column)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE midi_recording_settings ADD COLUMN {} {}",
+                column, ty
+            ))?;
+        }
+    }
+    // v24 files have drum steps without parameter locks
+    if !column_exists(conn, "drum_steps", "locks")? {
+        conn.execute_batch("ALTER TABLE drum_steps ADD COLUMN locks TEXT")?;
+    }
+    // v25 files have drum steps without trig conditions or ratchets
+    for (column, ty) in [
+        ("condition", "TEXT NOT NULL DEFAULT 'Always'"),
+        ("ratchet", "INTEGER NOT NULL DEFAULT 1"),
+        ("ratchet_decay", "REAL NOT NULL DEFAULT 1.0"),
+    ] {
+        if !column_exists(conn, "drum_steps", column)? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE drum_steps ADD COLUMN {} {}",
+                column, ty
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `table` has a column named `column`.
+pub(super) fn column_exists(conn: &Connection, table: &str, column: &str) -> SqlResult<bool> {
+    let count: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        rusqlite::params![table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
 }
 
 /// Delete all data from all tables (preserving schema).
@@ -160,6 +246,7 @@ CREATE TABLE IF NOT EXISTS instrument_effects (
     effect_type TEXT NOT NULL,
     enabled INTEGER NOT NULL,
     vst_state_path TEXT,
+    sidechain_source TEXT,
     PRIMARY KEY (instrument_id, effect_id)
 );
 
@@ -201,6 +288,9 @@ CREATE TABLE IF NOT EXISTS instrument_modulations (
     env_release REAL,
     source_instrument_id INTEGER,
     source_param_name TEXT,
+    route_source TEXT,
+    route_target TEXT,
+    route_depth REAL,
     PRIMARY KEY (instrument_id, target_param)
 );
 
@@ -236,6 +326,15 @@ CREATE TABLE IF NOT EXISTS instrument_processing_chain (
     PRIMARY KEY (instrument_id, position)
 );
 
+CREATE TABLE IF NOT EXISTS instrument_freezes (
+    instrument_id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    start_tick INTEGER NOT NULL,
+    end_tick INTEGER NOT NULL,
+    lead_in_secs REAL NOT NULL,
+    length_secs REAL NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS instrument_vst_params (
     instrument_id INTEGER NOT NULL,
     param_index INTEGER NOT NULL,
@@ -307,6 +406,7 @@ CREATE TABLE IF NOT EXISTS bus_effects (
     effect_type TEXT NOT NULL,
     enabled INTEGER NOT NULL,
     vst_state_path TEXT,
+    sidechain_source TEXT,
     PRIMARY KEY (bus_id, effect_id)
 );
 
@@ -339,6 +439,7 @@ CREATE TABLE IF NOT EXISTS layer_group_effects (
     effect_type TEXT NOT NULL,
     enabled INTEGER NOT NULL,
     vst_state_path TEXT,
+    sidechain_source TEXT,
     PRIMARY KEY (group_id, effect_id)
 );
 
@@ -380,6 +481,18 @@ CREATE TABLE IF NOT EXISTS musical_settings (
     swing_amount REAL NOT NULL DEFAULT 0.0
 );
 
+CREATE TABLE IF NOT EXISTS tempo_events (
+    tick INTEGER PRIMARY KEY,
+    bpm REAL NOT NULL,
+    ramp INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS meter_changes (
+    bar INTEGER PRIMARY KEY,
+    time_sig_num INTEGER NOT NULL,
+    time_sig_denom INTEGER NOT NULL
+);
+
 CREATE TABLE IF NOT EXISTS piano_roll_tracks (
     instrument_id INTEGER NOT NULL,
     position INTEGER NOT NULL,
@@ -397,6 +510,15 @@ CREATE TABLE IF NOT EXISTS piano_roll_notes (
     probability REAL NOT NULL DEFAULT 1.0
 );
 
+CREATE TABLE IF NOT EXISTS piano_roll_note_expression (
+    note_id INTEGER NOT NULL,
+    dimension TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    tick INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (note_id, dimension, position)
+);
+
 -- ============================================================
 -- Sampler
 -- ============================================================
@@ -408,7 +530,9 @@ CREATE TABLE IF NOT EXISTS sampler_configs (
     loop_mode INTEGER NOT NULL,
     pitch_tracking INTEGER NOT NULL,
     next_slice_id INTEGER NOT NULL,
-    selected_slice INTEGER NOT NULL DEFAULT 0
+    selected_slice INTEGER NOT NULL DEFAULT 0,
+    source_bpm REAL,
+    warp INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE TABLE IF NOT EXISTS sampler_slices (
@@ -525,6 +649,7 @@ CREATE TABLE IF NOT EXISTS drum_pads (
     pitch INTEGER NOT NULL DEFAULT 0,
     trigger_instrument_id INTEGER,
     trigger_freq REAL NOT NULL DEFAULT 440.0,
+    warp_bpm REAL,
     PRIMARY KEY (instrument_id, pad_index)
 );
 
@@ -543,6 +668,10 @@ CREATE TABLE IF NOT EXISTS drum_steps (
     velocity INTEGER NOT NULL DEFAULT 100,
     probability REAL NOT NULL DEFAULT 1.0,
     pitch_offset INTEGER NOT NULL DEFAULT 0,
+    locks TEXT,
+    condition TEXT NOT NULL DEFAULT 'Always',
+    ratchet INTEGER NOT NULL DEFAULT 1,
+    ratchet_decay REAL NOT NULL DEFAULT 1.0,
     PRIMARY KEY (instrument_id, pattern_index, pad_index, step_index)
 );
 
@@ -558,7 +687,9 @@ CREATE TABLE IF NOT EXISTS chopper_states (
     selected_slice INTEGER NOT NULL,
     next_slice_id INTEGER NOT NULL,
     duration_secs REAL NOT NULL,
-    waveform_peaks BLOB
+    waveform_peaks BLOB,
+    source_bpm REAL,
+    warp INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE TABLE IF NOT EXISTS chopper_slices (
@@ -580,7 +711,11 @@ CREATE TABLE IF NOT EXISTS midi_recording_settings (
     id INTEGER PRIMARY KEY CHECK (id = 1),
     live_input_instrument INTEGER,
     note_passthrough INTEGER NOT NULL,
-    channel_filter INTEGER
+    channel_filter INTEGER,
+    mpe INTEGER NOT NULL DEFAULT 0,
+    mpe_bend_range INTEGER NOT NULL DEFAULT 48,
+    note_record_mode TEXT NOT NULL DEFAULT 'Overdub',
+    input_quantize INTEGER
 );
 
 CREATE TABLE IF NOT EXISTS midi_cc_mappings (
@@ -645,6 +780,16 @@ CREATE TABLE IF NOT EXISTS arrangement_clip_notes (
     PRIMARY KEY (clip_id, position)
 );
 
+CREATE TABLE IF NOT EXISTS arrangement_clip_note_expression (
+    clip_id INTEGER NOT NULL,
+    note_position INTEGER NOT NULL,
+    dimension TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    tick INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (clip_id, note_position, dimension, position)
+);
+
 CREATE TABLE IF NOT EXISTS arrangement_placements (
     id INTEGER PRIMARY KEY,
     clip_id INTEGER NOT NULL,
@@ -676,6 +821,91 @@ CREATE TABLE IF NOT EXISTS arrangement_clip_automation_points (
     PRIMARY KEY (lane_id, tick)
 );
 
+CREATE TABLE IF NOT EXISTS arrangement_audio_clips (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    instrument_id INTEGER NOT NULL,
+    start_tick INTEGER NOT NULL,
+    duration_ticks INTEGER NOT NULL,
+    sample_rate INTEGER NOT NULL,
+    length_secs REAL NOT NULL,
+    waveform_peaks BLOB,
+    muted INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS arrangement_markers (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    tick INTEGER NOT NULL,
+    end_tick INTEGER
+);
+
+-- ============================================================
+-- Generative Engine
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS generative_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL,
+    capture_enabled INTEGER NOT NULL,
+    next_voice_id INTEGER NOT NULL,
+    macro_density REAL NOT NULL,
+    macro_chaos REAL NOT NULL,
+    macro_energy REAL NOT NULL,
+    macro_motion REAL NOT NULL,
+    scale_lock INTEGER NOT NULL,
+    pitch_min INTEGER NOT NULL,
+    pitch_max INTEGER NOT NULL,
+    max_notes_per_beat INTEGER NOT NULL,
+    humanize_timing REAL NOT NULL,
+    humanize_velocity REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS generative_voices (
+    id INTEGER PRIMARY KEY,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    enabled INTEGER NOT NULL,
+    muted INTEGER NOT NULL,
+    target_instrument_id INTEGER,
+    velocity_min INTEGER NOT NULL,
+    velocity_max INTEGER NOT NULL,
+    octave_min INTEGER NOT NULL,
+    octave_max INTEGER NOT NULL,
+    algorithm TEXT NOT NULL,
+    rate TEXT NOT NULL,
+    euclid_pulses INTEGER,
+    euclid_steps INTEGER,
+    euclid_rotation INTEGER,
+    euclid_pitch_mode TEXT,
+    euclid_fixed_pitch INTEGER,
+    markov_initial_pitch_class INTEGER,
+    markov_rest_probability REAL,
+    markov_duration_mode TEXT,
+    lsystem_axiom TEXT,
+    lsystem_iterations INTEGER,
+    lsystem_step_interval INTEGER,
+    lsystem_note_duration_steps INTEGER,
+    lsystem_velocity INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS generative_markov_transitions (
+    voice_id INTEGER NOT NULL,
+    from_pitch_class INTEGER NOT NULL,
+    to_pitch_class INTEGER NOT NULL,
+    weight REAL NOT NULL,
+    PRIMARY KEY (voice_id, from_pitch_class, to_pitch_class)
+);
+
+CREATE TABLE IF NOT EXISTS generative_lsystem_rules (
+    voice_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    replacement TEXT NOT NULL,
+    PRIMARY KEY (voice_id, position)
+);
+
 -- ============================================================
 -- Checkpoints (Phase 2+3)
 -- ============================================================
@@ -709,6 +939,7 @@ DELETE FROM instrument_modulations;
 DELETE FROM instrument_filter_extra_params;
 DELETE FROM instrument_eq_bands;
 DELETE FROM instrument_processing_chain;
+DELETE FROM instrument_freezes;
 DELETE FROM instrument_vst_params;
 DELETE FROM effect_vst_params;
 DELETE FROM mixer_buses;
@@ -723,8 +954,11 @@ DELETE FROM layer_group_effects;
 DELETE FROM layer_group_effect_params;
 DELETE FROM layer_group_effect_vst_params;
 DELETE FROM musical_settings;
+DELETE FROM tempo_events;
+DELETE FROM meter_changes;
 DELETE FROM piano_roll_tracks;
 DELETE FROM piano_roll_notes;
+DELETE FROM piano_roll_note_expression;
 DELETE FROM sampler_configs;
 DELETE FROM sampler_slices;
 DELETE FROM vst_plugins;
@@ -746,7 +980,14 @@ DELETE FROM midi_pitch_bend_configs;
 DELETE FROM arrangement_state;
 DELETE FROM arrangement_clips;
 DELETE FROM arrangement_clip_notes;
+DELETE FROM arrangement_clip_note_expression;
 DELETE FROM arrangement_placements;
 DELETE FROM arrangement_clip_automation_lanes;
 DELETE FROM arrangement_clip_automation_points;
+DELETE FROM arrangement_audio_clips;
+DELETE FROM arrangement_markers;
+DELETE FROM generative_state;
+DELETE FROM generative_voices;
+DELETE FROM generative_markov_transitions;
+DELETE FROM generative_lsystem_rules;
 ";
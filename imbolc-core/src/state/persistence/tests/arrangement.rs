@@ -24,6 +24,7 @@ fn save_and_load_round_trip_arrangement() {
             velocity: 100,
             duration: 120,
             probability: 1.0,
+            expression: None,
         });
         clip.notes.push(Note {
             tick: 120,
@@ -31,6 +32,7 @@ fn save_and_load_round_trip_arrangement() {
             velocity: 80,
             duration: 120,
             probability: 0.8,
+            expression: None,
         });
     }
 
@@ -44,6 +46,7 @@ fn save_and_load_round_trip_arrangement() {
             velocity: 127,
             duration: 480,
             probability: 1.0,
+            expression: None,
         });
     }
 
@@ -145,6 +148,7 @@ fn round_trip_arrangement_clips() {
             velocity: 100,
             duration: 240,
             probability: 1.0,
+            expression: None,
         });
         clip.notes.push(Note {
             tick: 480,
@@ -152,6 +156,7 @@ fn round_trip_arrangement_clips() {
             velocity: 80,
             duration: 240,
             probability: 0.5,
+            expression: None,
         });
     }
 
@@ -176,6 +181,127 @@ fn round_trip_arrangement_clips() {
     std::fs::remove_file(&path).ok();
 }
 
+#[test]
+fn round_trip_arrangement_audio_clips() {
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let inst_id = instruments.add_instrument(SourceType::AudioIn);
+
+    let id = session.arrangement.add_audio_clip(
+        "Take 1".to_string(),
+        std::path::PathBuf::from("/tmp/takes/input_1.wav"),
+        inst_id,
+        1920,
+        3840,
+    );
+    if let Some(clip) = session.arrangement.audio_clip_mut(id) {
+        clip.waveform_peaks = vec![0.0, 0.25, 1.0];
+        clip.sample_rate = 48000;
+        clip.length_secs = 4.0;
+        clip.muted = true;
+    }
+    session.arrangement.add_audio_clip(
+        "Loop".to_string(),
+        std::path::PathBuf::from("loop.wav"),
+        inst_id,
+        0,
+        960,
+    );
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (loaded, _) = load_project(&path).expect("load");
+
+    let arr = &loaded.arrangement;
+    assert_eq!(arr.audio_clips.len(), 2);
+    let clip = arr.audio_clip(id).unwrap();
+    assert_eq!(clip.name, "Take 1");
+    assert_eq!(
+        clip.path,
+        std::path::PathBuf::from("/tmp/takes/input_1.wav")
+    );
+    assert_eq!(clip.instrument_id, inst_id);
+    assert_eq!(clip.start_tick, 1920);
+    assert_eq!(clip.duration_ticks, 3840);
+    assert_eq!(clip.sample_rate, 48000);
+    assert_eq!(clip.length_secs, 4.0);
+    assert_eq!(clip.waveform_peaks, vec![0.0, 0.25, 1.0]);
+    assert!(clip.muted);
+    assert!(arr.audio_clip(id + 1).unwrap().waveform_peaks.is_empty());
+    assert!(!arr.audio_clip(id + 1).unwrap().muted);
+    assert_eq!(arr.next_audio_clip_id(), id + 2);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn round_trip_arrangement_markers() {
+    let mut session = SessionState::new();
+    let instruments = InstrumentState::new();
+    let intro = session.arrangement.add_marker("Intro".to_string(), 0, None);
+    let chorus = session
+        .arrangement
+        .add_marker("Chorus".to_string(), 7680, Some(11520));
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (loaded, _) = load_project(&path).expect("load");
+
+    let arr = &loaded.arrangement;
+    assert_eq!(arr.markers, session.arrangement.markers);
+    assert_eq!(arr.section_range(intro), Some((0, 7680)));
+    assert_eq!(arr.marker(chorus).unwrap().end_tick, Some(11520));
+    assert_eq!(arr.next_marker_id(), chorus + 1);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn audio_clips_without_mute_column_load_and_resave() {
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let inst_id = instruments.add_instrument(SourceType::AudioIn);
+    session.arrangement.add_audio_clip(
+        "Take 1".to_string(),
+        std::path::PathBuf::from("take.wav"),
+        inst_id,
+        0,
+        960,
+    );
+
+    // Rebuild the audio clip table as v15 wrote it
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE arrangement_audio_clips RENAME TO old_clips;
+             CREATE TABLE arrangement_audio_clips (
+                 id INTEGER PRIMARY KEY, name TEXT NOT NULL, path TEXT NOT NULL,
+                 instrument_id INTEGER NOT NULL, start_tick INTEGER NOT NULL,
+                 duration_ticks INTEGER NOT NULL, sample_rate INTEGER NOT NULL,
+                 length_secs REAL NOT NULL, waveform_peaks BLOB);
+             INSERT INTO arrangement_audio_clips
+                 SELECT id, name, path, instrument_id, start_tick, duration_ticks,
+                        sample_rate, length_secs, waveform_peaks FROM old_clips;
+             DROP TABLE old_clips;",
+        )
+        .unwrap();
+    }
+
+    let (loaded, loaded_instruments) = load_project(&path).expect("load v15 clips");
+    assert_eq!(loaded.arrangement.audio_clips.len(), 1);
+    assert!(!loaded.arrangement.audio_clips[0].muted);
+
+    let mut muted = loaded.clone();
+    muted.arrangement.audio_clips[0].muted = true;
+    save_project(&path, &muted, &loaded_instruments).expect("resave adds the column");
+    let (reloaded, _) = load_project(&path).expect("reload");
+    assert!(reloaded.arrangement.audio_clips[0].muted);
+
+    std::fs::remove_file(&path).ok();
+}
+
 #[test]
 fn round_trip_automation_with_curves() {
     let mut session = SessionState::new();
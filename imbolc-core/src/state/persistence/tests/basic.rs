@@ -216,6 +216,8 @@ fn save_and_load_round_trip_complex() {
                 next_slice_id: 2,
                 waveform_peaks: vec![0.1, 0.2],
                 duration_secs: 1.23,
+                source_bpm: None,
+                warp: false,
             });
             if let Some(chopper) = seq.chopper.as_mut() {
                 chopper.slices[0].name = "A".to_string();
@@ -379,3 +381,96 @@ fn save_and_load_round_trip_complex() {
 
     std::fs::remove_file(&path).ok();
 }
+
+#[test]
+fn round_trip_tempo_map() {
+    use crate::state::piano_roll::{MeterChange, TempoEvent};
+
+    let mut session = SessionState::new();
+    let map = &mut session.piano_roll.tempo_map;
+    map.set_tempo(TempoEvent {
+        tick: 1920,
+        bpm: 90.0,
+        ramp: true,
+    });
+    map.set_tempo(TempoEvent {
+        tick: 3840,
+        bpm: 140.0,
+        ramp: false,
+    });
+    map.set_meter(MeterChange {
+        bar: 2,
+        time_signature: (7, 8),
+    });
+    let instruments = InstrumentState::new();
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save_project");
+    let (loaded_session, _) = load_project(&path).expect("load_project");
+
+    assert_eq!(
+        loaded_session.piano_roll.tempo_map,
+        session.piano_roll.tempo_map
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn round_trip_note_expression() {
+    use imbolc_types::ExpressionDimension;
+
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let inst_id = instruments.add_instrument(SourceType::Saw);
+    session.piano_roll.add_track(inst_id);
+    session.piano_roll.toggle_note(0, 60, 0, 480, 100);
+    session.piano_roll.toggle_note(0, 64, 0, 480, 100);
+    session
+        .piano_roll
+        .record_expression(inst_id, 64, 0, ExpressionDimension::Bend, -2.0);
+    session
+        .piano_roll
+        .record_expression(inst_id, 64, 240, ExpressionDimension::Bend, 1.5);
+    session
+        .piano_roll
+        .record_expression(inst_id, 64, 120, ExpressionDimension::Slide, 0.8);
+    session.midi_recording.mpe = true;
+    session.midi_recording.mpe_bend_range = 24;
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save_project");
+    let (loaded_session, _) = load_project(&path).expect("load_project");
+
+    let notes = &loaded_session.piano_roll.tracks[&inst_id].notes;
+    let original = &session.piano_roll.tracks[&inst_id].notes;
+    for (loaded, original) in notes.iter().zip(original) {
+        assert_eq!(loaded.pitch, original.pitch);
+        assert_eq!(loaded.expression, original.expression);
+    }
+    assert!(notes.iter().any(|n| n.expression.is_some()));
+    assert!(loaded_session.midi_recording.mpe);
+    assert_eq!(loaded_session.midi_recording.mpe_bend_range, 24);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn round_trip_note_record_settings() {
+    let mut session = SessionState::new();
+    let instruments = InstrumentState::new();
+    session.midi_recording.note_record_mode = imbolc_types::NoteRecordMode::LoopTakes;
+    session.midi_recording.input_quantize = Some(120);
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save_project");
+    let (loaded_session, _) = load_project(&path).expect("load_project");
+
+    assert_eq!(
+        loaded_session.midi_recording.note_record_mode,
+        imbolc_types::NoteRecordMode::LoopTakes
+    );
+    assert_eq!(loaded_session.midi_recording.input_quantize, Some(120));
+
+    std::fs::remove_file(&path).ok();
+}
@@ -94,6 +94,10 @@ fn roundtrip_source_type() {
         Kit,
         Custom(CustomSynthDefId::new(42)),
         Vst(VstPluginId::new(99)),
+        MidiOut {
+            port: 2,
+            channel: 9,
+        },
     ];
     for s in &all {
         match s {
@@ -104,12 +108,14 @@ fn roundtrip_source_type() {
             | Cowbell | Rim | Tom | Clave | Conga | Choir | EPiano | Organ | BrassStab
             | Strings | Acid | Gendy | Chaos | Additive | Wavetable | Granular | AudioIn
             | BusIn | PitchedSampler | TimeStretch | Kit | Custom(_) | Vst(_) => {}
+            MidiOut { .. } => {}
         }
         let encoded = format!("{:?}", s);
-        // Custom and Vst use "Custom:42" / "Vst:99" encoding in save
+        // Custom, Vst and MidiOut use "Custom:42" / "Vst:99" / "MidiOut:2:9" encoding in save
         let save_str = match s {
             Custom(id) => format!("Custom:{}", id.get()),
             Vst(id) => format!("Vst:{}", id.get()),
+            MidiOut { port, channel } => format!("MidiOut:{}:{}", port, channel),
             _ => encoded,
         };
         let decoded = decoders::decode_source_type(&save_str);
@@ -255,6 +261,8 @@ fn roundtrip_parameter_target() {
         MembraneTension,
         SampleRate,
         SampleAmp,
+        SampleStart,
+        SampleEnd,
         StretchRatio,
         PitchShift,
         DelayTime,
@@ -307,6 +315,8 @@ fn roundtrip_parameter_target() {
             | MembraneTension
             | SampleRate
             | SampleAmp
+            | SampleStart
+            | SampleEnd
             | StretchRatio
             | PitchShift
             | DelayTime
@@ -501,3 +511,49 @@ fn roundtrip_step_resolution() {
         assert_eq!(decoded, sr, "StepResolution roundtrip failed for {:?}", sr);
     }
 }
+
+#[test]
+fn roundtrip_gen_rate() {
+    use imbolc_types::GenRate::*;
+    let all = [
+        Whole,
+        Half,
+        DottedQuarter,
+        Quarter,
+        DottedEighth,
+        Eighth,
+        DottedSixteenth,
+        Sixteenth,
+        ThirtySecond,
+        TripletQuarter,
+        TripletEighth,
+        TripletSixteenth,
+    ];
+    for &r in &all {
+        match r {
+            Whole | Half | DottedQuarter | Quarter | DottedEighth | Eighth | DottedSixteenth
+            | Sixteenth | ThirtySecond | TripletQuarter | TripletEighth | TripletSixteenth => {}
+        }
+        let encoded = format!("{:?}", r);
+        let decoded = decoders::decode_gen_rate(&encoded);
+        assert_eq!(decoded, r, "GenRate roundtrip failed for {:?}", r);
+    }
+}
+
+#[test]
+fn roundtrip_markov_duration_mode() {
+    use imbolc_types::MarkovDurationMode::*;
+    let all = [Fixed, Variable, Legato];
+    for &m in &all {
+        match m {
+            Fixed | Variable | Legato => {}
+        }
+        let encoded = format!("{:?}", m);
+        let decoded = decoders::decode_markov_duration_mode(&encoded);
+        assert_eq!(
+            decoded, m,
+            "MarkovDurationMode roundtrip failed for {:?}",
+            m
+        );
+    }
+}
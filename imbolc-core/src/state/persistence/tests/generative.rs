@@ -0,0 +1,196 @@
+use super::{load_project, save_project, temp_db_path};
+use crate::state::instrument::SourceType;
+use crate::state::instrument_state::InstrumentState;
+use crate::state::session::SessionState;
+use imbolc_types::{
+    EuclideanConfig, EuclideanPitchMode, GenRate, GenVoice, GenVoiceId, GenerativeAlgorithm,
+    LSystemConfig, MarkovConfig, MarkovDurationMode,
+};
+
+#[test]
+fn save_and_load_round_trip_generative() {
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let inst_id = instruments.add_instrument(SourceType::Saw);
+
+    let gen = &mut session.generative;
+    gen.enabled = true;
+    gen.capture_enabled = true;
+    gen.next_voice_id = 7;
+    gen.macros.density = 0.25;
+    gen.macros.chaos = 0.9;
+    gen.macros.energy = 0.1;
+    gen.macros.motion = 0.75;
+    gen.constraints.scale_lock = false;
+    gen.constraints.pitch_min = 40;
+    gen.constraints.pitch_max = 90;
+    gen.constraints.max_notes_per_beat = 3;
+    gen.constraints.humanize_timing = 0.2;
+    gen.constraints.humanize_velocity = 0.4;
+
+    let mut euclid = GenVoice::new(
+        GenVoiceId::new(4),
+        GenerativeAlgorithm::Euclidean(EuclideanConfig {
+            pulses: 5,
+            steps: 13,
+            rotation: 2,
+            rate: GenRate::DottedSixteenth,
+            pitch_mode: EuclideanPitchMode::Fixed(38),
+        }),
+    );
+    euclid.name = "Kick".to_string();
+    euclid.muted = true;
+    euclid.target_instrument = Some(inst_id);
+    euclid.velocity_min = 20;
+    euclid.velocity_max = 110;
+    euclid.octave_min = -1;
+    euclid.octave_max = 2;
+
+    let mut matrix = MarkovConfig::default().transition_matrix;
+    matrix[0][7] = 0.6;
+    matrix[11][3] = 0.05;
+    let markov = GenVoice::new(
+        GenVoiceId::new(2),
+        GenerativeAlgorithm::Markov(MarkovConfig {
+            transition_matrix: matrix,
+            initial_pitch_class: 9,
+            rate: GenRate::TripletEighth,
+            rest_probability: 0.35,
+            duration_mode: MarkovDurationMode::Legato,
+        }),
+    );
+
+    let mut lsystem = GenVoice::new(
+        GenVoiceId::new(6),
+        GenerativeAlgorithm::LSystem(LSystemConfig {
+            axiom: "FG".to_string(),
+            rules: vec![('F', "FG+".to_string()), ('G', "-F".to_string())],
+            iterations: 5,
+            step_interval: -3,
+            note_duration_steps: 2,
+            velocity: 90,
+            rate: GenRate::Whole,
+        }),
+    );
+    lsystem.enabled = false;
+
+    gen.voices = vec![euclid, markov, lsystem];
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save project");
+    let (loaded_session, _) = load_project(&path).expect("load project");
+
+    let loaded = &loaded_session.generative;
+    assert!(loaded.enabled);
+    assert!(loaded.capture_enabled);
+    assert_eq!(loaded.next_voice_id, 7);
+    assert_eq!(loaded.macros.density, 0.25);
+    assert_eq!(loaded.macros.chaos, 0.9);
+    assert_eq!(loaded.macros.energy, 0.1);
+    assert_eq!(loaded.macros.motion, 0.75);
+    assert!(!loaded.constraints.scale_lock);
+    assert_eq!(loaded.constraints.pitch_min, 40);
+    assert_eq!(loaded.constraints.pitch_max, 90);
+    assert_eq!(loaded.constraints.max_notes_per_beat, 3);
+    assert_eq!(loaded.constraints.humanize_timing, 0.2);
+    assert_eq!(loaded.constraints.humanize_velocity, 0.4);
+
+    // Voice order is preserved independently of voice ids
+    let ids: Vec<u32> = loaded.voices.iter().map(|v| v.id.get()).collect();
+    assert_eq!(ids, vec![4, 2, 6]);
+
+    let v = &loaded.voices[0];
+    assert_eq!(v.name, "Kick");
+    assert!(v.enabled);
+    assert!(v.muted);
+    assert_eq!(v.target_instrument, Some(inst_id));
+    assert_eq!(v.velocity_min, 20);
+    assert_eq!(v.velocity_max, 110);
+    assert_eq!(v.octave_min, -1);
+    assert_eq!(v.octave_max, 2);
+    match &v.algorithm {
+        GenerativeAlgorithm::Euclidean(cfg) => {
+            assert_eq!(cfg.pulses, 5);
+            assert_eq!(cfg.steps, 13);
+            assert_eq!(cfg.rotation, 2);
+            assert_eq!(cfg.rate, GenRate::DottedSixteenth);
+            assert!(matches!(cfg.pitch_mode, EuclideanPitchMode::Fixed(38)));
+        }
+        other => panic!("expected Euclidean voice, got {}", other.name()),
+    }
+
+    let v = &loaded.voices[1];
+    assert_eq!(v.target_instrument, None);
+    match &v.algorithm {
+        GenerativeAlgorithm::Markov(cfg) => {
+            assert_eq!(cfg.transition_matrix, matrix);
+            assert_eq!(cfg.initial_pitch_class, 9);
+            assert_eq!(cfg.rate, GenRate::TripletEighth);
+            assert_eq!(cfg.rest_probability, 0.35);
+            assert_eq!(cfg.duration_mode, MarkovDurationMode::Legato);
+        }
+        other => panic!("expected Markov voice, got {}", other.name()),
+    }
+
+    let v = &loaded.voices[2];
+    assert!(!v.enabled);
+    match &v.algorithm {
+        GenerativeAlgorithm::LSystem(cfg) => {
+            assert_eq!(cfg.axiom, "FG");
+            assert_eq!(
+                cfg.rules,
+                vec![('F', "FG+".to_string()), ('G', "-F".to_string())]
+            );
+            assert_eq!(cfg.iterations, 5);
+            assert_eq!(cfg.step_interval, -3);
+            assert_eq!(cfg.note_duration_steps, 2);
+            assert_eq!(cfg.velocity, 90);
+            assert_eq!(cfg.rate, GenRate::Whole);
+        }
+        other => panic!("expected L-System voice, got {}", other.name()),
+    }
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn euclidean_scale_pitch_modes_round_trip() {
+    let mut session = SessionState::new();
+    let instruments = InstrumentState::new();
+
+    for (id, mode) in [
+        (1, EuclideanPitchMode::ScaleWalk),
+        (2, EuclideanPitchMode::RandomInScale),
+    ] {
+        session.generative.voices.push(GenVoice::new(
+            GenVoiceId::new(id),
+            GenerativeAlgorithm::Euclidean(EuclideanConfig {
+                pitch_mode: mode,
+                ..EuclideanConfig::default()
+            }),
+        ));
+    }
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save project");
+    let (loaded_session, _) = load_project(&path).expect("load project");
+
+    let modes: Vec<&EuclideanPitchMode> = loaded_session
+        .generative
+        .voices
+        .iter()
+        .filter_map(|v| match &v.algorithm {
+            GenerativeAlgorithm::Euclidean(cfg) => Some(&cfg.pitch_mode),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(
+        modes[..],
+        [
+            EuclideanPitchMode::ScaleWalk,
+            EuclideanPitchMode::RandomInScale
+        ]
+    ));
+
+    std::fs::remove_file(&path).ok();
+}
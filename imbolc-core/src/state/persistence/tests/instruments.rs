@@ -55,6 +55,86 @@ fn round_trip_drum_sequencer() {
     std::fs::remove_file(&path).ok();
 }
 
+#[test]
+fn round_trip_step_param_locks() {
+    use crate::state::instrument::ParameterTarget;
+
+    let session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let kit_id = instruments.add_instrument(SourceType::Kit);
+
+    if let Some(seq) = instruments
+        .instrument_mut(kit_id)
+        .and_then(|inst| inst.drum_sequencer_mut())
+    {
+        let step = &mut seq.pattern_mut().steps[1][4];
+        step.active = true;
+        step.set_lock(ParameterTarget::FilterCutoff, 850.0);
+        step.set_lock(ParameterTarget::SendLevel(imbolc_types::BusId::new(2)), 0.4);
+        step.set_lock(ParameterTarget::SampleStart, 0.25);
+        seq.pattern_mut().steps[1][5].active = true;
+    }
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (_, loaded_inst) = load_project(&path).expect("load");
+
+    let seq = loaded_inst
+        .instrument(kit_id)
+        .and_then(|inst| inst.drum_sequencer())
+        .unwrap();
+    let step = &seq.patterns[0].steps[1][4];
+    assert_eq!(step.lock(&ParameterTarget::FilterCutoff), Some(850.0));
+    assert_eq!(
+        step.lock(&ParameterTarget::SendLevel(imbolc_types::BusId::new(2))),
+        Some(0.4)
+    );
+    assert_eq!(step.lock(&ParameterTarget::SampleStart), Some(0.25));
+    assert!(seq.patterns[0].steps[1][5].locks.is_empty());
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn round_trip_step_conditions_and_ratchets() {
+    use crate::state::drum_sequencer::StepCondition;
+
+    let session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let kit_id = instruments.add_instrument(SourceType::Kit);
+
+    if let Some(seq) = instruments
+        .instrument_mut(kit_id)
+        .and_then(|inst| inst.drum_sequencer_mut())
+    {
+        let step = &mut seq.pattern_mut().steps[2][0];
+        step.active = true;
+        step.condition = StepCondition::Loop { n: 3, m: 4 };
+        step.ratchet = 4;
+        step.ratchet_decay = 0.6;
+        let step = &mut seq.pattern_mut().steps[2][1];
+        step.active = true;
+        step.condition = StepCondition::NotPrevious;
+    }
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (_, loaded_inst) = load_project(&path).expect("load");
+
+    let seq = loaded_inst
+        .instrument(kit_id)
+        .and_then(|inst| inst.drum_sequencer())
+        .unwrap();
+    let step = &seq.patterns[0].steps[2][0];
+    assert_eq!(step.condition, StepCondition::Loop { n: 3, m: 4 });
+    assert_eq!((step.ratchet, step.ratchet_decay), (4, 0.6));
+    let step = &seq.patterns[0].steps[2][1];
+    assert_eq!(step.condition, StepCondition::NotPrevious);
+    assert_eq!((step.ratchet, step.ratchet_decay), (1, 1.0));
+
+    std::fs::remove_file(&path).ok();
+}
+
 #[test]
 fn round_trip_sampler_config() {
     let mut session = SessionState::new();
@@ -97,6 +177,65 @@ fn round_trip_sampler_config() {
     std::fs::remove_file(&path).ok();
 }
 
+#[test]
+fn round_trip_sample_warp() {
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let sampler_id = instruments.add_instrument(SourceType::TimeStretch);
+    let kit_id = instruments.add_instrument(SourceType::Kit);
+
+    if let Some(config) = instruments
+        .instrument_mut(sampler_id)
+        .and_then(|inst| inst.sampler_config_mut())
+    {
+        config.source_bpm = Some(92.5);
+        config.warp = true;
+    }
+    if let Some(seq) = instruments
+        .instrument_mut(kit_id)
+        .and_then(|inst| inst.drum_sequencer_mut())
+    {
+        seq.pads[2].warp_bpm = Some(174.0);
+        seq.chopper = Some(crate::state::drum_sequencer::ChopperState {
+            buffer_id: Some(3),
+            path: Some("/tmp/amen_174bpm.wav".to_string()),
+            name: "amen".to_string(),
+            slices: vec![crate::state::sampler::Slice::full(0)],
+            selected_slice: 0,
+            next_slice_id: 1,
+            waveform_peaks: Vec::new(),
+            duration_secs: 1.38,
+            source_bpm: Some(174.0),
+            warp: true,
+        });
+    }
+    session.piano_roll.add_track(sampler_id);
+    session.piano_roll.add_track(kit_id);
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (_, loaded_inst) = load_project(&path).expect("load");
+
+    let config = loaded_inst
+        .instrument(sampler_id)
+        .and_then(|inst| inst.sampler_config())
+        .unwrap();
+    assert_eq!(config.source_bpm, Some(92.5));
+    assert!(config.warp);
+
+    let seq = loaded_inst
+        .instrument(kit_id)
+        .and_then(|inst| inst.drum_sequencer())
+        .unwrap();
+    assert_eq!(seq.pads[2].warp_bpm, Some(174.0));
+    assert_eq!(seq.pads[0].warp_bpm, None);
+    let chopper = seq.chopper.as_ref().unwrap();
+    assert_eq!(chopper.source_bpm, Some(174.0));
+    assert!(chopper.warp);
+
+    std::fs::remove_file(&path).ok();
+}
+
 #[test]
 fn round_trip_vst_plugins() {
     let mut session = SessionState::new();
@@ -220,3 +359,83 @@ fn round_trip_layer_octave_offset() {
 
     std::fs::remove_file(&path).ok();
 }
+
+#[test]
+fn round_trip_frozen_render() {
+    let session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let frozen_id = instruments.add_instrument(SourceType::Saw);
+    let live_id = instruments.add_instrument(SourceType::Saw);
+    let render = imbolc_types::FrozenRender {
+        path: PathBuf::from("/tmp/freeze_1.wav"),
+        start_tick: 480,
+        end_tick: 4320,
+        lead_in_secs: 0.035,
+        length_secs: 8.25,
+    };
+    instruments.instrument_mut(frozen_id).unwrap().frozen = Some(render.clone());
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (_, loaded) = load_project(&path).expect("load");
+
+    let loaded_frozen = loaded.instrument(frozen_id).unwrap();
+    assert_eq!(loaded_frozen.frozen, Some(render));
+    assert_eq!(loaded_frozen.source, SourceType::Saw);
+    assert!(!loaded.instrument(live_id).unwrap().is_frozen());
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn round_trip_mod_matrix() {
+    use crate::state::instrument::{LfoShape, ModMatrixSource, ModRoute, ParameterTarget};
+
+    let session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let id = instruments.add_instrument(SourceType::Saw);
+    let plain_id = instruments.add_instrument(SourceType::Sin);
+    {
+        let matrix = &mut instruments.instrument_mut(id).unwrap().modulation.matrix;
+        matrix.lfos[1].rate = 0.25;
+        matrix.lfos[1].shape = LfoShape::Triangle;
+        matrix.envelopes[0].attack = 0.5;
+        matrix.envelopes[0].sustain = 0.2;
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::Lfo(1),
+            target: ParameterTarget::FilterCutoff,
+            depth: -0.4,
+        });
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::Envelope(0),
+            target: ParameterTarget::Pitch,
+            depth: 0.75,
+        });
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::ModWheel,
+            target: ParameterTarget::SendLevel(imbolc_types::BusId::new(2)),
+            depth: 1.0,
+        });
+    }
+    let expected = instruments
+        .instrument(id)
+        .unwrap()
+        .modulation
+        .matrix
+        .clone();
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (_, loaded) = load_project(&path).expect("load");
+
+    assert_eq!(loaded.instrument(id).unwrap().modulation.matrix, expected);
+    assert!(loaded
+        .instrument(plain_id)
+        .unwrap()
+        .modulation
+        .matrix
+        .routes
+        .is_empty());
+
+    std::fs::remove_file(&path).ok();
+}
@@ -386,3 +386,64 @@ fn round_trip_processing_chain_interleaved() {
 
     std::fs::remove_file(&path).ok();
 }
+
+#[test]
+fn round_trip_effect_sidechain_sources() {
+    use imbolc_types::{SendTapPoint, SidechainSource};
+
+    let mut session = SessionState::new();
+    let mut instruments = InstrumentState::new();
+    let kick = instruments.add_instrument(SourceType::Saw);
+    let bass = instruments.add_instrument(SourceType::Saw);
+    instruments.instrument_mut(kick).unwrap().layer.group = Some(1);
+    let bus_ids: Vec<BusId> = session.bus_ids().collect();
+    session.mixer.add_layer_group_mixer(1, &bus_ids);
+
+    let kick_key = SidechainSource::Instrument {
+        id: kick,
+        tap: SendTapPoint::PreInsert,
+    };
+    let inst = instruments.instrument_mut(bass).unwrap();
+    let comp_id = inst.add_effect(EffectType::SidechainComp);
+    inst.effect_by_id_mut(comp_id).unwrap().sidechain = Some(kick_key);
+
+    let bus = session.bus_mut(BusId::new(1)).unwrap();
+    let vocoder_id = bus.effect_chain.add_effect(EffectType::Vocoder);
+    bus.effect_chain
+        .effect_by_id_mut(vocoder_id)
+        .unwrap()
+        .sidechain = Some(SidechainSource::LayerGroup(1));
+    let plain_id = bus.effect_chain.add_effect(EffectType::EnvFollower);
+
+    session.piano_roll.add_track(kick);
+    session.piano_roll.add_track(bass);
+
+    let path = temp_db_path();
+    save_project(&path, &session, &instruments).expect("save");
+    let (loaded_session, loaded_instruments) = load_project(&path).expect("load");
+
+    let loaded_bass = loaded_instruments.instrument(bass).unwrap();
+    assert_eq!(
+        loaded_bass.effect_by_id(comp_id).unwrap().sidechain,
+        Some(kick_key)
+    );
+    let loaded_bus = loaded_session.bus(BusId::new(1)).unwrap();
+    assert_eq!(
+        loaded_bus
+            .effect_chain
+            .effect_by_id(vocoder_id)
+            .unwrap()
+            .sidechain,
+        Some(SidechainSource::LayerGroup(1))
+    );
+    assert_eq!(
+        loaded_bus
+            .effect_chain
+            .effect_by_id(plain_id)
+            .unwrap()
+            .sidechain,
+        None
+    );
+
+    std::fs::remove_file(&path).ok();
+}
@@ -6,6 +6,7 @@ use crate::state::persistence::{load_project, save_project};
 mod arrangement;
 mod basic;
 mod decoders;
+mod generative;
 mod instruments;
 mod mixer;
 
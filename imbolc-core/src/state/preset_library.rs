@@ -0,0 +1,357 @@
+//! Preset stores.
+//!
+//! Instrument presets: one JSON file per user preset in
+//! `~/.config/imbolc/presets/instruments/`, listed alongside the factory
+//! presets for the built-in sources.
+//!
+//! Effect chain presets: one JSON file per preset in
+//! `~/.config/imbolc/presets/effect_chains/`. VST effect state is copied
+//! into a directory next to the file so the preset owns it.
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+
+use imbolc_types::{
+    EffectChainPreset, EffectType, InstrumentPreset, ProcessingStage, SourceType,
+    PRESET_FORMAT_VERSION,
+};
+
+pub struct PresetEntry {
+    pub preset: InstrumentPreset,
+    /// File the preset was loaded from; `None` for factory presets
+    pub path: Option<PathBuf>,
+}
+
+impl PresetEntry {
+    pub fn is_factory(&self) -> bool {
+        self.path.is_none()
+    }
+}
+
+#[derive(Default)]
+pub struct PresetLibrary {
+    /// User presets sorted by name, followed by the factory presets
+    pub entries: Vec<PresetEntry>,
+}
+
+impl PresetLibrary {
+    pub fn load() -> Self {
+        Self::load_from(&crate::paths::instrument_presets_dir())
+    }
+
+    /// Load user presets from `dir`. Unreadable or unparseable files are
+    /// skipped so one bad file doesn't hide the rest.
+    pub fn load_from(dir: &Path) -> Self {
+        let mut user: Vec<PresetEntry> = read_dir_presets(dir, read_preset)
+            .into_iter()
+            .map(|(preset, path)| PresetEntry {
+                preset,
+                path: Some(path),
+            })
+            .collect();
+        user.sort_by_key(|e| e.preset.name.to_lowercase());
+
+        let factory = InstrumentPreset::factory_presets()
+            .into_iter()
+            .map(|preset| PresetEntry { preset, path: None });
+
+        Self {
+            entries: user.into_iter().chain(factory).collect(),
+        }
+    }
+
+    /// Indices of entries matching a source filter and search query
+    pub fn filtered(&self, source: Option<SourceType>, query: &str) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.preset.matches(source, query))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Distinct sources across all entries, in list order
+    pub fn sources(&self) -> Vec<SourceType> {
+        let mut sources = Vec::new();
+        for entry in &self.entries {
+            if !sources.contains(&entry.preset.source) {
+                sources.push(entry.preset.source);
+            }
+        }
+        sources
+    }
+}
+
+fn read_preset(path: &Path) -> Result<InstrumentPreset, String> {
+    read_versioned(path, |p: &InstrumentPreset| p.version)
+}
+
+fn read_versioned<T: DeserializeOwned>(
+    path: &Path,
+    version: impl Fn(&T) -> u32,
+) -> Result<T, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let preset: T = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+    if version(&preset) > PRESET_FORMAT_VERSION {
+        return Err(format!(
+            "format version {} is newer than supported",
+            version(&preset)
+        ));
+    }
+    Ok(preset)
+}
+
+/// Read every `*.json` preset in `dir`, skipping files that fail to load
+fn read_dir_presets<T>(dir: &Path, read: impl Fn(&Path) -> Result<T, String>) -> Vec<(T, PathBuf)> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| match read(&path) {
+            Ok(preset) => Some((preset, path)),
+            Err(e) => {
+                log::warn!(target: "presets", "Skipping preset {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// File name for a preset: its name lowercased, with anything but
+/// alphanumerics, `-` and `_` replaced by `_`
+fn preset_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.json", stem)
+}
+
+/// Write a preset into `dir`, replacing any preset with the same file name.
+pub fn save_preset(dir: &Path, preset: &InstrumentPreset) -> Result<PathBuf, String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(preset_file_name(&preset.name));
+    let json = serde_json::to_string_pretty(preset).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+pub fn delete_preset(path: &Path) -> Result<(), String> {
+    std::fs::remove_file(path).map_err(|e| e.to_string())
+}
+
+pub struct ChainPresetEntry {
+    pub preset: EffectChainPreset,
+    pub path: PathBuf,
+}
+
+#[derive(Default)]
+pub struct EffectChainLibrary {
+    /// Chain presets sorted by name
+    pub entries: Vec<ChainPresetEntry>,
+}
+
+impl EffectChainLibrary {
+    pub fn load() -> Self {
+        Self::load_from(&crate::paths::effect_chain_presets_dir())
+    }
+
+    pub fn load_from(dir: &Path) -> Self {
+        let mut entries: Vec<ChainPresetEntry> = read_dir_presets(dir, |p| {
+            read_versioned(p, |c: &EffectChainPreset| c.version)
+        })
+        .into_iter()
+        .map(|(preset, path)| ChainPresetEntry { preset, path })
+        .collect();
+        entries.sort_by_key(|e| e.preset.name.to_lowercase());
+        Self { entries }
+    }
+
+    /// Indices of entries matching `query`. With `effects_only`, chains
+    /// without any effect (nothing a bus or group could host) are left out.
+    pub fn filtered(&self, query: &str, effects_only: bool) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.preset.matches(query))
+            .filter(|(_, e)| !effects_only || e.preset.effect_count() > 0)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Directory holding a chain preset's copied VST state files
+fn chain_state_dir(preset_path: &Path) -> PathBuf {
+    preset_path.with_extension("vststate")
+}
+
+/// Write a chain preset into `dir`. VST effect state files are copied next
+/// to the preset and the saved paths point at the copies, so later saves of
+/// the original effect don't change the preset. Effects whose state file is
+/// missing are saved without state.
+pub fn save_chain_preset(dir: &Path, preset: &EffectChainPreset) -> Result<PathBuf, String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(preset_file_name(&preset.name));
+    let state_dir = chain_state_dir(&path);
+    if state_dir.exists() {
+        std::fs::remove_dir_all(&state_dir).map_err(|e| e.to_string())?;
+    }
+
+    let mut preset = preset.clone();
+    for (i, stage) in preset.stages.iter_mut().enumerate() {
+        let ProcessingStage::Effect(slot) = stage else {
+            continue;
+        };
+        if !matches!(slot.effect_type, EffectType::Vst(_)) {
+            continue;
+        }
+        let Some(source) = slot.vst_state_path.take() else {
+            continue;
+        };
+        if !source.is_file() {
+            continue;
+        }
+        std::fs::create_dir_all(&state_dir).map_err(|e| e.to_string())?;
+        let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("fxp");
+        let copy = state_dir.join(format!("stage_{}.{}", i, ext));
+        std::fs::copy(&source, &copy).map_err(|e| e.to_string())?;
+        slot.vst_state_path = Some(copy);
+    }
+
+    let json = serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Delete a chain preset and its copied VST state.
+pub fn delete_chain_preset(path: &Path) -> Result<(), String> {
+    std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    let state_dir = chain_state_dir(path);
+    if state_dir.exists() {
+        std::fs::remove_dir_all(&state_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imbolc_types::{EffectType, Instrument, InstrumentId};
+
+    #[test]
+    fn save_and_reload_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut inst = Instrument::new(InstrumentId::new(1), SourceType::Acid);
+        inst.add_effect(EffectType::Delay);
+        inst.modulation.amp_envelope.release = 2.5;
+        let preset =
+            InstrumentPreset::from_instrument(&inst, "Rubber Bass!", vec!["bass".into()]).unwrap();
+
+        let path = save_preset(dir.path(), &preset).unwrap();
+        assert_eq!(path.file_name().unwrap(), "rubber_bass_.json");
+
+        let library = PresetLibrary::load_from(dir.path());
+        let entry = &library.entries[0];
+        assert_eq!(entry.path.as_deref(), Some(path.as_path()));
+        assert_eq!(entry.preset.name, "Rubber Bass!");
+        assert_eq!(entry.preset.tags, vec!["bass".to_string()]);
+        assert_eq!(entry.preset.processing_chain.len(), 1);
+        assert_eq!(entry.preset.modulation.amp_envelope.release, 2.5);
+        assert!(library.entries[1..].iter().all(|e| e.is_factory()));
+
+        delete_preset(&path).unwrap();
+        assert!(PresetLibrary::load_from(dir.path())
+            .entries
+            .iter()
+            .all(|e| e.is_factory()));
+    }
+
+    #[test]
+    fn bad_files_are_skipped_and_filters_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+        let organ = Instrument::new(InstrumentId::new(1), SourceType::Organ);
+        let preset = InstrumentPreset::from_instrument(&organ, "Drawbar", Vec::new()).unwrap();
+        save_preset(dir.path(), &preset).unwrap();
+
+        let library = PresetLibrary::load_from(dir.path());
+        assert_eq!(
+            library.entries.len(),
+            InstrumentPreset::factory_presets().len() + 1
+        );
+        let organs = library.filtered(Some(SourceType::Organ), "");
+        assert_eq!(organs.len(), 2);
+        assert_eq!(library.filtered(None, "drums").len(), 3);
+        assert_eq!(library.sources()[0], SourceType::Organ);
+    }
+
+    #[test]
+    fn chain_preset_owns_copied_vst_state() {
+        use imbolc_types::{EffectId, EffectSlot, VstPlugin, VstPluginKind, VstPluginRegistry};
+
+        let dir = tempfile::tempdir().unwrap();
+        let original_state = dir.path().join("instrument_1_fx_0_Comp.fxp");
+        std::fs::write(&original_state, b"state-v1").unwrap();
+
+        let mut registry = VstPluginRegistry::new();
+        let plugin_id = registry.add(VstPlugin {
+            id: imbolc_types::VstPluginId::new(0),
+            name: "Comp".to_string(),
+            plugin_path: PathBuf::from("/plugins/Comp.vst3"),
+            kind: VstPluginKind::Effect,
+            params: Vec::new(),
+            latency_secs: None,
+        });
+        let mut slot = EffectSlot::new(EffectId::new(0), EffectType::Vst(plugin_id));
+        slot.vst_state_path = Some(original_state.clone());
+        let effects = vec![EffectSlot::new(EffectId::new(1), EffectType::ParaEq), slot];
+        let preset = EffectChainPreset::from_effects("Vocal Bus", Vec::new(), &effects, &registry);
+
+        let presets_dir = dir.path().join("chains");
+        let path = save_chain_preset(&presets_dir, &preset).unwrap();
+        // The original state changing later doesn't affect the preset
+        std::fs::write(&original_state, b"state-v2").unwrap();
+
+        let library = EffectChainLibrary::load_from(&presets_dir);
+        assert_eq!(library.entries.len(), 1);
+        let saved = &library.entries[0].preset;
+        let ProcessingStage::Effect(vst) = &saved.stages[1] else {
+            panic!("expected effect stage");
+        };
+        let copy = vst.vst_state_path.clone().unwrap();
+        assert!(copy.starts_with(chain_state_dir(&path)));
+        assert_eq!(std::fs::read(&copy).unwrap(), b"state-v1");
+        assert_eq!(saved.vst_plugins.len(), 1);
+        assert_eq!(library.filtered("vocal", true), vec![0]);
+
+        delete_chain_preset(&path).unwrap();
+        assert!(!copy.exists());
+        assert!(EffectChainLibrary::load_from(&presets_dir)
+            .entries
+            .is_empty());
+    }
+
+    #[test]
+    fn empty_name_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let saw = Instrument::new(InstrumentId::new(1), SourceType::Saw);
+        let preset = InstrumentPreset::from_instrument(&saw, "  ", Vec::new()).unwrap();
+        assert!(save_preset(dir.path(), &preset).is_err());
+    }
+}
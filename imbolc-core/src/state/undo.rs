@@ -279,6 +279,9 @@ pub fn undo_scope(
         DomainAction::Instrument(InstrumentAction::LinkLayer(_, _))
         | DomainAction::Instrument(InstrumentAction::UnlinkLayer(_)) => UndoScope::Full,
 
+        // Chain presets may register VST plugins in the session
+        DomainAction::Instrument(InstrumentAction::AddEffectChain(_, _)) => UndoScope::Full,
+
         // Instrument Update carries an explicit id
         DomainAction::Instrument(InstrumentAction::Update(update)) => {
             if recording {
@@ -434,7 +437,9 @@ pub fn coalesce_key(
             | SequencerAction::AdjustSwing(_)
             | SequencerAction::AdjustProbability(_, _, _)
             | SequencerAction::AdjustPadPitch(_, _)
-            | SequencerAction::AdjustStepPitch(_, _, _),
+            | SequencerAction::AdjustStepPitch(_, _, _)
+            | SequencerAction::AdjustParamLock(_, _, _, _)
+            | SequencerAction::AdjustRatchetDecay(_, _, _),
         ) => match instruments.selected_instrument() {
             Some(inst) => CoalesceKey::InstrumentParam(inst.id),
             None => CoalesceKey::None,
@@ -477,6 +482,9 @@ pub fn is_undoable(action: &DomainAction) -> bool {
                 | InstrumentAction::SelectLast
                 | InstrumentAction::Edit(_)
                 | InstrumentAction::OpenVstEffectParams(_, _)
+                | InstrumentAction::Freeze(_)
+                | InstrumentAction::SetModController(_, _, _)
+                | InstrumentAction::NoteExpression(_, _, _, _)
         ),
         DomainAction::Mixer(a) => !matches!(
             a,
@@ -484,6 +492,7 @@ pub fn is_undoable(action: &DomainAction) -> bool {
                 | MixerAction::Jump(_)
                 | MixerAction::SelectAt(_)
                 | MixerAction::CycleSection
+                | MixerAction::ResetClips
         ),
         DomainAction::PianoRoll(a) => matches!(
             a,
@@ -496,6 +505,8 @@ pub fn is_undoable(action: &DomainAction) -> bool {
                 | crate::action::PianoRollAction::AdjustSwing(_)
                 | crate::action::PianoRollAction::DeleteNotesInRegion { .. }
                 | crate::action::PianoRollAction::PasteNotes { .. }
+                | crate::action::PianoRollAction::TransformNotes { .. }
+                | crate::action::PianoRollAction::LoadNoteTake(_)
         ),
         DomainAction::Session(a) => !matches!(
             a,
@@ -505,10 +516,14 @@ pub fn is_undoable(action: &DomainAction) -> bool {
                 | SessionAction::LoadFrom(_)
                 | SessionAction::NewProject
                 | SessionAction::OpenFileBrowser(_)
+                | SessionAction::SaveInstrumentPreset { .. }
+                | SessionAction::DeleteInstrumentPreset(_)
+                | SessionAction::SaveEffectChainPreset { .. }
         ),
         DomainAction::Sequencer(a) => !matches!(
             a,
             SequencerAction::PlayStop
+                | SequencerAction::ToggleFill
                 | SequencerAction::LoadSample(_)
                 | SequencerAction::LoadSampleResult(_, _)
                 | SequencerAction::CopySteps { .. }
@@ -543,6 +558,7 @@ pub fn is_undoable(action: &DomainAction) -> bool {
                 | crate::action::ArrangementAction::MoveCursor(_)
                 | crate::action::ArrangementAction::ScrollView(_)
                 | crate::action::ArrangementAction::PlayStop
+                | crate::action::ArrangementAction::JumpToMarker(_)
         ),
         DomainAction::VstParam(a) => matches!(
             a,
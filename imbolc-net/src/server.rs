@@ -13,7 +13,7 @@ use log::{error, info, warn};
 
 use imbolc_types::{
     AutomationAction, AutomationLaneId, BusAction, BusId, InstrumentAction, InstrumentId,
-    PianoRollAction, SessionState, VstParamAction,
+    LayerGroupAction, PianoRollAction, SessionState, VstParamAction,
 };
 
 use crate::framing::{read_message, serialize_frame, write_message};
@@ -127,6 +127,14 @@ impl DirtyFlags {
                         } else {
                             self.dirty_instruments.insert(id);
                         }
+                        // Chain presets can register VST plugins in the session
+                        if matches!(a, InstrumentAction::AddEffectChain(..)) {
+                            self.session = true;
+                        }
+                        // Expression can be recorded into the instrument's notes
+                        if matches!(a, InstrumentAction::NoteExpression(..)) {
+                            self.dirty_piano_roll_tracks.insert(id);
+                        }
                     }
                     None => {
                         // Add, Select*, PlayNote, PlayDrumPad — structural
@@ -163,8 +171,11 @@ impl DirtyFlags {
             NetworkAction::Bus(a) => {
                 self.mark_bus_action(a);
             }
-            NetworkAction::LayerGroup(_) => {
+            NetworkAction::LayerGroup(a) => {
                 self.mixer_structural = true;
+                if matches!(a, LayerGroupAction::AddEffectChain(..)) {
+                    self.session = true;
+                }
             }
             NetworkAction::Session(_) | NetworkAction::Server(_) | NetworkAction::Chopper(_) => {
                 self.session = true;
@@ -209,6 +220,9 @@ impl DirtyFlags {
             PianoRollAction::PasteNotes { track, .. } => {
                 self.resolve_track_id(*track, session);
             }
+            PianoRollAction::TransformNotes { track, .. } => {
+                self.resolve_track_id(*track, session);
+            }
             PianoRollAction::TogglePolyMode(track) => {
                 self.resolve_track_id(*track, session);
             }
@@ -223,6 +237,22 @@ impl DirtyFlags {
                     self.resolve_track_id(*track, session);
                 }
             }
+            PianoRollAction::RecordNoteOn { instrument_id, .. }
+            | PianoRollAction::RecordNoteOff { instrument_id, .. } => {
+                if session.is_some_and(|s| s.piano_roll.recording) {
+                    self.dirty_piano_roll_tracks.insert(*instrument_id);
+                }
+            }
+            // Stopping writes out held notes and the last loop take
+            PianoRollAction::PlayStop | PianoRollAction::PlayStopRecord => {
+                if session.is_some_and(|s| !s.piano_roll.playing) {
+                    self.piano_roll_structural = true;
+                    self.arrangement = true;
+                }
+            }
+            PianoRollAction::LoadNoteTake(_) => {
+                self.piano_roll_structural = true;
+            }
             // Metadata / structural changes
             PianoRollAction::ToggleLoop
             | PianoRollAction::SetLoopStart(_)
@@ -232,13 +262,15 @@ impl DirtyFlags {
                 self.piano_roll_structural = true;
             }
             // Audio-only / transient — no state change to broadcast
-            PianoRollAction::PlayStop
-            | PianoRollAction::PlayStopRecord
-            | PianoRollAction::ReleaseNote { .. }
+            PianoRollAction::ReleaseNote { .. }
             | PianoRollAction::ReleaseNotes { .. }
             | PianoRollAction::BounceToWav
             | PianoRollAction::ExportStems
+            | PianoRollAction::BounceOffline
+            | PianoRollAction::ExportStemsOffline
             | PianoRollAction::CancelExport
+            | PianoRollAction::SetExportOptions(_)
+            | PianoRollAction::ExportMidi
             | PianoRollAction::RenderToWav(_) => {}
             // Clipboard-only — no state change
             PianoRollAction::CopyNotes { .. } => {}
@@ -284,9 +316,15 @@ impl DirtyFlags {
             | BusAction::RemoveEffect(id, ..)
             | BusAction::MoveEffect(id, ..)
             | BusAction::ToggleEffectBypass(id, ..)
+            | BusAction::SetEffectSidechain(id, ..)
             | BusAction::AdjustEffectParam(id, ..) => {
                 self.dirty_mixer_buses.insert(*id);
             }
+            BusAction::AddEffectChain(id, ..) => {
+                self.dirty_mixer_buses.insert(*id);
+                // Chain presets can register VST plugins in the session
+                self.session = true;
+            }
             // Structural changes
             BusAction::Add | BusAction::Remove(_) => {
                 self.mixer_structural = true;
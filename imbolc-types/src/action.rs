@@ -8,10 +8,12 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    AutomationLaneId, AutomationTarget, BusId, ClipId, ClipboardNote, CurveType, DrumStep,
-    EffectId, EffectType, EnvConfig, FilterType, GenVoiceId, GenerativeAlgorithm, InstrumentId,
-    LfoConfig, MixerSelection, MusicalSettings, Param, ParamIndex, PlacementId, ProcessingStage,
-    ServerStatus, SourceType, VstPluginKind,
+    AudioClipId, AutomationLaneId, AutomationTarget, BusId, ClipId, ClipboardNote, CurveType,
+    DrumStep, EffectChainOwner, EffectChainPreset, EffectId, EffectType, EnvConfig, ExportOptions,
+    FilterType, GenVoiceId, GenerativeAlgorithm, GrooveConfig, InstrumentId, LfoConfig, MarkerId,
+    MeterChange, MidiClockMode, MixerSelection, MusicalSettings, NoteInputConfig, NoteRecordMode,
+    NoteTransform, Param, ParamIndex, PlacementId, ProcessingStage, ServerStatus, SidechainSource,
+    SourceType, TempoEvent, VstPluginKind,
 };
 
 // ============================================================================
@@ -29,6 +31,7 @@ pub enum PaneId {
     Confirm,
     Docs,
     Eq,
+    Export,
     FileBrowser,
     FrameEdit,
     Groove,
@@ -38,6 +41,7 @@ pub enum PaneId {
     Instrument,
     InstrumentEdit,
     InstrumentPicker,
+    InstrumentPresets,
     MidiSettings,
     Mixer,
     PaneSwitcher,
@@ -65,6 +69,7 @@ impl PaneId {
             PaneId::Confirm => "confirm",
             PaneId::Docs => "docs",
             PaneId::Eq => "eq",
+            PaneId::Export => "export",
             PaneId::FileBrowser => "file_browser",
             PaneId::FrameEdit => "frame_edit",
             PaneId::Groove => "groove",
@@ -74,6 +79,7 @@ impl PaneId {
             PaneId::Instrument => "instrument",
             PaneId::InstrumentEdit => "instrument_edit",
             PaneId::InstrumentPicker => "instrument_picker",
+            PaneId::InstrumentPresets => "instrument_presets",
             PaneId::MidiSettings => "midi_settings",
             PaneId::Mixer => "mixer",
             PaneId::PaneSwitcher => "pane_switcher",
@@ -102,6 +108,7 @@ impl PaneId {
             "confirm" => Some(PaneId::Confirm),
             "docs" => Some(PaneId::Docs),
             "eq" => Some(PaneId::Eq),
+            "export" => Some(PaneId::Export),
             "file_browser" => Some(PaneId::FileBrowser),
             "frame_edit" => Some(PaneId::FrameEdit),
             "groove" => Some(PaneId::Groove),
@@ -111,6 +118,7 @@ impl PaneId {
             "instrument" => Some(PaneId::Instrument),
             "instrument_edit" => Some(PaneId::InstrumentEdit),
             "instrument_picker" => Some(PaneId::InstrumentPicker),
+            "instrument_presets" => Some(PaneId::InstrumentPresets),
             "midi_settings" => Some(PaneId::MidiSettings),
             "mixer" => Some(PaneId::Mixer),
             "pane_switcher" => Some(PaneId::PaneSwitcher),
@@ -240,7 +248,12 @@ pub enum ServerAction {
         scsynth_args: String,
     },
     RecordMaster,
+    /// Start/stop take recording on the armed AudioIn instruments (or the
+    /// selected one when none are armed)
     RecordInput,
+    ToggleRecordArm(InstrumentId),
+    SetPunchIn(Option<u32>),
+    SetPunchOut(Option<u32>),
 }
 
 /// Bus management actions.
@@ -254,12 +267,16 @@ pub enum BusAction {
     Rename(BusId, String),
     /// Add an effect to a bus
     AddEffect(BusId, EffectType),
+    /// Append the effects of a chain preset to a bus
+    AddEffectChain(BusId, Box<EffectChainPreset>),
     /// Remove an effect from a bus
     RemoveEffect(BusId, EffectId),
     /// Move an effect up/down on a bus
     MoveEffect(BusId, EffectId, i8),
     /// Toggle bypass on a bus effect
     ToggleEffectBypass(BusId, EffectId),
+    /// Set (or clear) the key source of a bus effect with a key input
+    SetEffectSidechain(BusId, EffectId, Option<SidechainSource>),
     /// Adjust a parameter on a bus effect
     AdjustEffectParam(BusId, EffectId, ParamIndex, f32),
 }
@@ -269,12 +286,16 @@ pub enum BusAction {
 pub enum LayerGroupAction {
     /// Add an effect to a layer group
     AddEffect(u32, EffectType),
+    /// Append the effects of a chain preset to a layer group
+    AddEffectChain(u32, Box<EffectChainPreset>),
     /// Remove an effect from a layer group
     RemoveEffect(u32, EffectId),
     /// Move an effect up/down on a layer group
     MoveEffect(u32, EffectId, i8),
     /// Toggle bypass on a layer group effect
     ToggleEffectBypass(u32, EffectId),
+    /// Set (or clear) the key source of a layer group effect with a key input
+    SetEffectSidechain(u32, EffectId, Option<SidechainSource>),
     /// Adjust a parameter on a layer group effect
     AdjustEffectParam(u32, EffectId, ParamIndex, f32),
     /// Toggle EQ on/off for a layer group
@@ -298,6 +319,10 @@ pub enum ChopperAction {
     NudgeSliceEnd(f32),
     MoveCursor(i8), // direction
     CommitAll,      // assign all slices to pads and return
+    /// Toggle warping assigned slices to the project tempo
+    ToggleWarp,
+    /// Nudge the loaded sample's own tempo
+    AdjustSourceBpm(f32),
 }
 
 // ============================================================================
@@ -315,6 +340,17 @@ pub enum FileSelectAction {
     LoadPitchedSample(InstrumentId),
     LoadImpulseResponse(InstrumentId, EffectId), // instrument_id, effect_id
     ImportProject,
+    ImportMidiFile(MidiImportTarget),
+    ImportAudioClip(InstrumentId, u32), // instrument_id, start_tick
+}
+
+/// Where notes from an imported Standard MIDI File are placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MidiImportTarget {
+    /// Replace the notes of piano roll tracks
+    PianoRoll,
+    /// Create arrangement clips placed at the cursor
+    Arrangement,
 }
 
 /// Navigation intent returned from dispatch — processed by the UI layer.
@@ -371,6 +407,8 @@ pub enum AudioEffect {
     SetLfoParam(InstrumentId, LfoParamKind, f32),
     SetBusEffectParam(BusId, EffectId, ParamIndex, f32),
     SetLayerGroupEffectParam(u32, EffectId, ParamIndex, f32),
+    /// Mod matrix controller value (/c_set on the controller bus)
+    SetModController(InstrumentId, crate::ModController, f32),
 }
 
 impl AudioEffect {
@@ -522,6 +560,8 @@ pub enum MixerAction {
     ToggleSend(BusId),
     CycleSendTapPoint(BusId),
     AdjustPan(f32),
+    /// Clear the latched clip indicators on every channel meter
+    ResetClips,
 }
 
 /// Session/file actions.
@@ -537,6 +577,8 @@ pub enum SessionAction {
     OpenFileBrowser(FileSelectAction),
     ImportCustomSynthDef(PathBuf),
     ImportVstPlugin(PathBuf, VstPluginKind),
+    /// Import a Standard MIDI File (format 0/1), one track/channel per instrument
+    ImportMidiFile(PathBuf, MidiImportTarget),
     AdjustHumanizeVelocity(f32),
     AdjustHumanizeTiming(f32),
     ToggleMasterMute,
@@ -548,6 +590,20 @@ pub enum SessionAction {
     RestoreCheckpoint(i64),
     /// Delete a checkpoint
     DeleteCheckpoint(i64),
+    /// Save an instrument's patch to the user preset library
+    SaveInstrumentPreset {
+        instrument_id: InstrumentId,
+        name: String,
+        tags: Vec<String>,
+    },
+    /// Delete a preset file from the user preset library
+    DeleteInstrumentPreset(PathBuf),
+    /// Save an instrument, bus or layer group effect chain as a chain preset
+    SaveEffectChainPreset {
+        owner: EffectChainOwner,
+        name: String,
+        tags: Vec<String>,
+    },
 }
 
 /// MIDI configuration actions.
@@ -567,6 +623,18 @@ pub enum MidiAction {
     SetChannelFilter(Option<u8>),
     SetLiveInputInstrument(Option<InstrumentId>),
     ToggleNotePassthrough,
+    /// Set MIDI clock sync mode (off / master / slave)
+    SetClockMode(MidiClockMode),
+    /// Set the output port index clock is sent to in master mode
+    SetClockOutputPort(u8),
+    /// Treat channels 2-16 as MPE member channels
+    ToggleMpe,
+    /// Pitch bend range of MPE member channels, in semitones
+    SetMpeBendRange(u8),
+    /// How recorded MIDI notes combine with the notes already there
+    SetNoteRecordMode(NoteRecordMode),
+    /// Grid recorded note starts snap to, in ticks
+    SetInputQuantize(Option<u32>),
 }
 
 /// Automation actions.
@@ -622,6 +690,58 @@ pub enum ArrangementAction {
         new_length: Option<u32>,
     },
     DuplicatePlacement(PlacementId),
+    /// Place a WAV file on an instrument's lane as an audio clip
+    ImportAudioClip {
+        instrument_id: InstrumentId,
+        path: PathBuf,
+        start_tick: u32,
+    },
+    MoveAudioClip {
+        clip_id: AudioClipId,
+        new_start_tick: u32,
+    },
+    DuplicateAudioClip(AudioClipId),
+    DeleteAudioClip(AudioClipId),
+    /// Switch to the next of the stacked takes under a tick
+    CycleTake {
+        instrument_id: InstrumentId,
+        tick: u32,
+    },
+    /// Add or replace the tempo change at the event's tick
+    SetTempoEvent(TempoEvent),
+    /// Remove the tempo change at a tick
+    RemoveTempoEvent(u32),
+    /// Add or replace the time signature change at the change's bar
+    SetMeterChange(MeterChange),
+    /// Remove the time signature change at a bar
+    RemoveMeterChange(u32),
+    /// Add a named marker at a tick
+    AddMarker {
+        name: String,
+        tick: u32,
+    },
+    /// Add a named section with an explicit range
+    AddSection {
+        name: String,
+        start_tick: u32,
+        end_tick: u32,
+    },
+    RenameMarker(MarkerId, String),
+    RemoveMarker(MarkerId),
+    /// Move the cursor and playhead to a marker
+    JumpToMarker(MarkerId),
+    /// Set the loop range to a marker's section and enable looping
+    LoopSection(MarkerId),
+    /// Repeat a section right after itself across all lanes
+    DuplicateSection(MarkerId),
+    /// Open empty time at a tick across all lanes and mark it as a section
+    InsertSection {
+        name: String,
+        start_tick: u32,
+        length_ticks: u32,
+    },
+    /// Cut a section and its content out of the timeline
+    DeleteSection(MarkerId),
     SelectPlacement(Option<usize>),
     SelectLane(usize),
     MoveCursor(i32),
@@ -691,7 +811,15 @@ pub enum PianoRollAction {
     },
     BounceToWav,
     ExportStems,
+    /// Bounce master with an offline render (faster than realtime)
+    BounceOffline,
+    /// Export stems with an offline render (faster than realtime)
+    ExportStemsOffline,
     CancelExport,
+    /// Set the format options for the next bounce or stem export
+    SetExportOptions(ExportOptions),
+    /// Export piano roll / arrangement and drum patterns as a Standard MIDI File
+    ExportMidi,
     /// Copy notes within a region to the clipboard
     CopyNotes {
         track: usize,
@@ -700,6 +828,31 @@ pub enum PianoRollAction {
         start_pitch: u8,
         end_pitch: u8,
     },
+    /// Quantize, transpose or otherwise rewrite the notes within a region
+    TransformNotes {
+        track: usize,
+        start_tick: u32,
+        end_tick: u32,
+        start_pitch: u8,
+        end_pitch: u8,
+        transform: NoteTransform,
+    },
+    /// MIDI key down while recording; `latency_us` is how long ago it was
+    /// struck, so the note lands where it was played
+    RecordNoteOn {
+        instrument_id: InstrumentId,
+        pitch: u8,
+        velocity: u8,
+        latency_us: u64,
+    },
+    /// MIDI key up while recording
+    RecordNoteOff {
+        instrument_id: InstrumentId,
+        pitch: u8,
+        latency_us: u64,
+    },
+    /// Load a take clip into its instrument's loop region to audition it
+    LoadNoteTake(ClipId),
 }
 
 impl PianoRollAction {
@@ -714,6 +867,8 @@ impl PianoRollAction {
             Self::ReleaseNote { instrument_id, .. } => Some(*instrument_id),
             Self::ReleaseNotes { instrument_id, .. } => Some(*instrument_id),
             Self::RenderToWav(id) => Some(*id),
+            Self::RecordNoteOn { instrument_id, .. } => Some(*instrument_id),
+            Self::RecordNoteOff { instrument_id, .. } => Some(*instrument_id),
 
             // Actions without explicit instrument_id (use track index, need state to resolve)
             Self::ToggleNote { .. }
@@ -727,10 +882,16 @@ impl PianoRollAction {
             | Self::AdjustSwing(_)
             | Self::DeleteNotesInRegion { .. }
             | Self::PasteNotes { .. }
+            | Self::TransformNotes { .. }
             | Self::BounceToWav
             | Self::ExportStems
+            | Self::BounceOffline
+            | Self::ExportStemsOffline
             | Self::CancelExport
-            | Self::CopyNotes { .. } => None,
+            | Self::SetExportOptions(_)
+            | Self::ExportMidi
+            | Self::CopyNotes { .. }
+            | Self::LoadNoteTake(_) => None,
         }
     }
 }
@@ -764,6 +925,18 @@ pub enum SequencerAction {
     ToggleReverse(usize),              // pad_idx
     AdjustPadPitch(usize, i8),         // (pad_idx, delta semitones)
     AdjustStepPitch(usize, usize, i8), // (pad_idx, step_idx, delta)
+    /// Nudge a step's parameter lock, locking the parameter first if needed
+    AdjustParamLock(usize, usize, crate::ParameterTarget, i8), // (pad_idx, step_idx, target, steps)
+    /// Remove a step's parameter lock
+    ClearParamLock(usize, usize, crate::ParameterTarget), // (pad_idx, step_idx, target)
+    /// Move a step's trig condition along `StepCondition::all()`
+    CycleStepCondition(usize, usize, i8), // (pad_idx, step_idx, direction)
+    /// Change how many hits a step ratchets
+    AdjustRatchet(usize, usize, i8), // (pad_idx, step_idx, delta)
+    /// Change how much of the previous hit's velocity each ratchet hit keeps
+    AdjustRatchetDecay(usize, usize, f32), // (pad_idx, step_idx, delta)
+    /// Turn fill mode on or off for fill-conditioned steps
+    ToggleFill,
     /// Delete steps in region (used by Cut)
     DeleteStepsInRegion {
         start_pad: usize,
@@ -807,6 +980,15 @@ pub struct InstrumentUpdate {
     pub amp_envelope: EnvConfig,
     pub polyphonic: bool,
     pub active: bool,
+    /// Arpeggiator / chord settings to apply; `None` leaves them unchanged
+    #[serde(default)]
+    pub note_input: Option<NoteInputConfig>,
+    /// Groove settings to apply; `None` leaves them unchanged
+    #[serde(default)]
+    pub groove: Option<GrooveConfig>,
+    /// Mod matrix to apply; `None` leaves it unchanged
+    #[serde(default)]
+    pub mod_matrix: Option<crate::ModMatrix>,
 }
 
 /// Instrument actions.
@@ -817,10 +999,12 @@ pub enum InstrumentAction {
     Edit(InstrumentId),
     Update(Box<InstrumentUpdate>),
     AddEffect(InstrumentId, EffectType),
+    AddEffectChain(InstrumentId, Box<EffectChainPreset>),
     RemoveEffect(InstrumentId, EffectId),
     MoveStage(InstrumentId, usize, i8),
     SetFilter(InstrumentId, Option<FilterType>),
     ToggleEffectBypass(InstrumentId, EffectId),
+    SetEffectSidechain(InstrumentId, EffectId, Option<SidechainSource>),
     ToggleFilter(InstrumentId),
     CycleFilterType(InstrumentId),
     AdjustFilterCutoff(InstrumentId, f32),
@@ -835,6 +1019,9 @@ pub enum InstrumentAction {
     SelectLast,
     PlayDrumPad(usize),
     LoadSampleResult(InstrumentId, PathBuf),
+    // Sample tempo warp
+    ToggleSampleWarp(InstrumentId),
+    AdjustSampleBpm(InstrumentId, f32),
     ToggleArp(InstrumentId),
     CycleArpDirection(InstrumentId),
     CycleArpDirectionReverse(InstrumentId),
@@ -879,6 +1066,13 @@ pub enum InstrumentAction {
     AdjustEnvelopeRelease(InstrumentId, f32),
     // Channel config
     ToggleChannelConfig(InstrumentId),
+    // Track freeze
+    Freeze(InstrumentId),
+    Unfreeze(InstrumentId),
+    // Live mod matrix controller value (0.0-1.0)
+    SetModController(InstrumentId, crate::ModController, f32),
+    // Live per-note expression (MPE): instrument, pitch, dimension, value
+    NoteExpression(InstrumentId, u8, crate::ExpressionDimension, f32),
 }
 
 impl InstrumentAction {
@@ -901,16 +1095,20 @@ impl InstrumentAction {
             Self::Delete(id)
             | Self::Edit(id)
             | Self::AddEffect(id, _)
+            | Self::AddEffectChain(id, _)
             | Self::RemoveEffect(id, _)
             | Self::MoveStage(id, _, _)
             | Self::SetFilter(id, _)
             | Self::ToggleEffectBypass(id, _)
+            | Self::SetEffectSidechain(id, _, _)
             | Self::ToggleFilter(id)
             | Self::CycleFilterType(id)
             | Self::AdjustFilterCutoff(id, _)
             | Self::AdjustFilterResonance(id, _)
             | Self::AdjustEffectParam(id, _, _, _)
             | Self::LoadSampleResult(id, _)
+            | Self::ToggleSampleWarp(id)
+            | Self::AdjustSampleBpm(id, _)
             | Self::ToggleArp(id)
             | Self::CycleArpDirection(id)
             | Self::CycleArpDirectionReverse(id)
@@ -949,7 +1147,11 @@ impl InstrumentAction {
             | Self::AdjustEnvelopeDecay(id, _)
             | Self::AdjustEnvelopeSustain(id, _)
             | Self::AdjustEnvelopeRelease(id, _)
-            | Self::ToggleChannelConfig(id) => Some(*id),
+            | Self::ToggleChannelConfig(id)
+            | Self::Freeze(id)
+            | Self::Unfreeze(id)
+            | Self::SetModController(id, _, _)
+            | Self::NoteExpression(id, _, _, _) => Some(*id),
 
             Self::Update(update) => Some(update.id),
         }
@@ -1211,6 +1413,7 @@ mod tests {
             PaneId::Confirm,
             PaneId::Docs,
             PaneId::Eq,
+            PaneId::Export,
             PaneId::FileBrowser,
             PaneId::FrameEdit,
             PaneId::Groove,
@@ -1220,6 +1423,7 @@ mod tests {
             PaneId::Instrument,
             PaneId::InstrumentEdit,
             PaneId::InstrumentPicker,
+            PaneId::InstrumentPresets,
             PaneId::MidiSettings,
             PaneId::Mixer,
             PaneId::PaneSwitcher,
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::action::VstTarget;
-use crate::{InstrumentId, VstPluginId};
+use crate::{ChannelLevels, ChannelMeterTarget, InstrumentId, VstPluginId};
 
 /// SuperCollider server status.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -45,9 +45,18 @@ pub enum AudioFeedback {
         elapsed_secs: u64,
     },
     RecordingStopped(PathBuf),
+    /// A take recorded from an instrument's input has been written to disk
+    TakeRecorded {
+        instrument_id: InstrumentId,
+        path: PathBuf,
+        start_tick: u32,
+    },
     RenderComplete {
         instrument_id: InstrumentId,
         path: PathBuf,
+        /// Scheduling lookahead while rendering: how far into the file the
+        /// render's first tick is heard
+        lead_in_secs: f64,
     },
     CompileResult(Result<String, String>),
     LoadResult(Result<String, String>),
@@ -58,6 +67,11 @@ pub enum AudioFeedback {
         vst_plugin_id: VstPluginId,
         params: Vec<(u32, String, Option<String>, f32)>, // (index, name, label, default)
     },
+    /// An opened VST plugin reported its processing latency
+    VstLatencyReported {
+        vst_plugin_id: VstPluginId,
+        latency_secs: f32,
+    },
     VstStateSaved {
         instrument_id: InstrumentId,
         target: VstTarget,
@@ -70,6 +84,10 @@ pub enum AudioFeedback {
     ExportProgress {
         progress: f32,
     },
+    /// An export stopped before producing its files.
+    ExportFailed {
+        message: String,
+    },
     /// The scsynth server process crashed or became unreachable.
     /// All tracked nodes have been invalidated.
     ServerCrashed {
@@ -101,4 +119,6 @@ pub enum AudioFeedback {
         /// Current OSC sender thread queue depth
         osc_send_queue_depth: u16,
     },
+    /// Throttled post-fader levels of the instrument, bus and layer group strips.
+    ChannelMeters(Vec<(ChannelMeterTarget, ChannelLevels)>),
 }
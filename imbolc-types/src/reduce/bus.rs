@@ -54,6 +54,15 @@ pub(super) fn reduce_bus(
             }
             true
         }
+        BusAction::AddEffectChain(bus_id, preset) => {
+            if session.bus(*bus_id).is_some() {
+                let effects = preset.resolve_effects(&mut session.vst_plugins);
+                if let Some(bus) = session.bus_mut(*bus_id) {
+                    bus.effect_chain.append_effects(effects);
+                }
+            }
+            true
+        }
         BusAction::RemoveEffect(bus_id, effect_id) => {
             if let Some(bus) = session.bus_mut(*bus_id) {
                 bus.effect_chain.remove_effect(*effect_id);
@@ -74,6 +83,14 @@ pub(super) fn reduce_bus(
             }
             true
         }
+        BusAction::SetEffectSidechain(bus_id, effect_id, source) => {
+            if let Some(bus) = session.bus_mut(*bus_id) {
+                if let Some(effect) = bus.effect_chain.effect_by_id_mut(*effect_id) {
+                    effect.sidechain = *source;
+                }
+            }
+            true
+        }
         BusAction::AdjustEffectParam(bus_id, effect_id, param_idx, delta) => {
             if let Some(bus) = session.bus_mut(*bus_id) {
                 if let Some(effect) = bus.effect_chain.effect_by_id_mut(*effect_id) {
@@ -95,6 +112,15 @@ pub(super) fn reduce_layer_group(action: &LayerGroupAction, session: &mut Sessio
             }
             true
         }
+        LayerGroupAction::AddEffectChain(group_id, preset) => {
+            if session.mixer.layer_group_mixer(*group_id).is_some() {
+                let effects = preset.resolve_effects(&mut session.vst_plugins);
+                if let Some(gm) = session.mixer.layer_group_mixer_mut(*group_id) {
+                    gm.effect_chain.append_effects(effects);
+                }
+            }
+            true
+        }
         LayerGroupAction::RemoveEffect(group_id, effect_id) => {
             if let Some(gm) = session.mixer.layer_group_mixer_mut(*group_id) {
                 gm.effect_chain.remove_effect(*effect_id);
@@ -115,6 +141,14 @@ pub(super) fn reduce_layer_group(action: &LayerGroupAction, session: &mut Sessio
             }
             true
         }
+        LayerGroupAction::SetEffectSidechain(group_id, effect_id, source) => {
+            if let Some(gm) = session.mixer.layer_group_mixer_mut(*group_id) {
+                if let Some(effect) = gm.effect_chain.effect_by_id_mut(*effect_id) {
+                    effect.sidechain = *source;
+                }
+            }
+            true
+        }
         LayerGroupAction::AdjustEffectParam(group_id, effect_id, param_idx, delta) => {
             if let Some(gm) = session.mixer.layer_group_mixer_mut(*group_id) {
                 if let Some(effect) = gm.effect_chain.effect_by_id_mut(*effect_id) {
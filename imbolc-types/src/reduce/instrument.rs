@@ -1,6 +1,6 @@
 use crate::{
-    BusId, EqParamKind, FilterType, InstrumentAction, InstrumentId, InstrumentState, Param,
-    ParamValue, SessionState, SourceType,
+    BusId, EffectId, EqParamKind, FilterType, InstrumentAction, InstrumentId, InstrumentState,
+    Param, ParamValue, SessionState, SourceExtra, SourceType, SOURCE_BPM_MAX, SOURCE_BPM_MIN,
 };
 
 pub(super) fn reduce(
@@ -28,6 +28,14 @@ pub(super) fn reduce(
         }
         InstrumentAction::Update(update) => {
             if let Some(instrument) = instruments.instrument_mut(update.id) {
+                // A preset can switch between e.g. a synth and a sampler source,
+                // which needs the matching source-specific config
+                let source_extra = SourceExtra::for_source(update.source);
+                if std::mem::discriminant(&source_extra)
+                    != std::mem::discriminant(&instrument.source_extra)
+                {
+                    instrument.source_extra = source_extra;
+                }
                 instrument.source = update.source;
                 instrument.source_params = update.source_params.clone();
                 instrument.processing_chain = update.processing_chain.clone();
@@ -35,6 +43,22 @@ pub(super) fn reduce(
                 instrument.modulation.amp_envelope = update.amp_envelope.clone();
                 instrument.polyphonic = update.polyphonic;
                 instrument.mixer.active = update.active;
+                if let Some(note_input) = &update.note_input {
+                    instrument.note_input = note_input.clone();
+                }
+                if let Some(groove) = update.groove {
+                    instrument.groove = groove;
+                }
+                if let Some(matrix) = &update.mod_matrix {
+                    instrument.modulation.matrix = matrix.clone();
+                }
+                // Effects carried in from elsewhere keep their ids; make sure
+                // newly added effects don't collide with them
+                if let Some(max_id) = instrument.effects().map(|e| e.id.get()).max() {
+                    if max_id >= instrument.next_effect_id.get() {
+                        instrument.next_effect_id = EffectId::new(max_id + 1);
+                    }
+                }
             }
             true
         }
@@ -44,6 +68,12 @@ pub(super) fn reduce(
             }
             true
         }
+        InstrumentAction::AddEffectChain(id, preset) => {
+            if let Some(instrument) = instruments.instrument_mut(*id) {
+                instrument.append_stages(preset.resolve_stages(&mut session.vst_plugins));
+            }
+            true
+        }
         InstrumentAction::RemoveEffect(id, effect_id) => {
             if let Some(instrument) = instruments.instrument_mut(*id) {
                 instrument.remove_effect(*effect_id);
@@ -70,6 +100,14 @@ pub(super) fn reduce(
             }
             true
         }
+        InstrumentAction::SetEffectSidechain(id, effect_id, source) => {
+            if let Some(instrument) = instruments.instrument_mut(*id) {
+                if let Some(effect) = instrument.effect_by_id_mut(*effect_id) {
+                    effect.sidechain = *source;
+                }
+            }
+            true
+        }
         InstrumentAction::ToggleFilter(id) => {
             if let Some(instrument) = instruments.instrument_mut(*id) {
                 instrument.toggle_filter();
@@ -165,6 +203,25 @@ pub(super) fn reduce(
             }
             true
         }
+        InstrumentAction::ToggleSampleWarp(id) => {
+            if let Some(config) = instruments
+                .instrument_mut(*id)
+                .and_then(|inst| inst.sampler_config_mut())
+            {
+                config.warp = !config.warp;
+            }
+            true
+        }
+        InstrumentAction::AdjustSampleBpm(id, delta) => {
+            if let Some(config) = instruments
+                .instrument_mut(*id)
+                .and_then(|inst| inst.sampler_config_mut())
+            {
+                let bpm = config.source_bpm.unwrap_or(session.bpm as f32) + delta;
+                config.source_bpm = Some(bpm.clamp(SOURCE_BPM_MIN, SOURCE_BPM_MAX));
+            }
+            true
+        }
         InstrumentAction::ToggleArp(id) => {
             if let Some(inst) = instruments.instrument_mut(*id) {
                 inst.note_input.arpeggiator.enabled = !inst.note_input.arpeggiator.enabled;
@@ -451,6 +508,18 @@ pub(super) fn reduce(
             }
             true
         }
+        // Track freeze: freezing renders on the main thread; unfreezing drops the render
+        InstrumentAction::Freeze(_) => true,
+        InstrumentAction::Unfreeze(id) => {
+            if let Some(inst) = instruments.instrument_mut(*id) {
+                inst.frozen = None;
+            }
+            true
+        }
+        // Controller values live only in the audio engine
+        InstrumentAction::SetModController(..) => true,
+        // Voice expression is audio-only; recording it into notes happens in dispatch
+        InstrumentAction::NoteExpression(..) => true,
     }
 }
 
@@ -609,6 +678,7 @@ mod tests {
                     label: None,
                 },
             ],
+            latency_secs: None,
         };
         session.vst_plugins.add(plugin);
         session
@@ -739,4 +809,66 @@ mod tests {
             assert_eq!(pa.max, pb.max);
         }
     }
+
+    #[test]
+    fn update_from_preset_switches_source_config_and_effect_ids() {
+        let mut session = SessionState::new();
+        let mut instruments = InstrumentState::new();
+        let id = instruments.add_instrument(SourceType::Saw);
+
+        let mut donor = crate::Instrument::new(InstrumentId::new(9), SourceType::PitchedSampler);
+        for _ in 0..3 {
+            donor.add_effect(crate::EffectType::Delay);
+        }
+        donor.note_input.arpeggiator.enabled = true;
+        let preset = crate::InstrumentPreset::from_instrument(&donor, "p", Vec::new()).unwrap();
+        let update = preset.to_update(instruments.instrument(id).unwrap());
+
+        reduce(
+            &InstrumentAction::Update(Box::new(update)),
+            &mut instruments,
+            &mut session,
+        );
+
+        let inst = instruments.instrument_mut(id).unwrap();
+        assert_eq!(inst.source, SourceType::PitchedSampler);
+        assert!(inst.sampler_config().is_some());
+        assert!(inst.note_input.arpeggiator.enabled);
+        let new_effect = inst.add_effect(crate::EffectType::Reverb);
+        assert_eq!(new_effect.get(), 3);
+    }
+
+    #[test]
+    fn unfreeze_restores_original_configuration() {
+        let mut session = SessionState::new();
+        let mut instruments = InstrumentState::new();
+        let id = instruments.add_instrument(SourceType::Saw);
+        let inst = instruments.instrument_mut(id).unwrap();
+        inst.add_effect(crate::EffectType::Reverb);
+        let original = format!("{:?}", inst);
+        inst.frozen = Some(crate::FrozenRender {
+            path: PathBuf::from("/tmp/freeze.wav"),
+            start_tick: 0,
+            end_tick: 1920,
+            lead_in_secs: 0.015,
+            length_secs: 2.0,
+        });
+
+        // Freezing happens on render completion; the action itself changes nothing
+        reduce(
+            &InstrumentAction::Freeze(id),
+            &mut instruments,
+            &mut session,
+        );
+        assert!(instruments.instrument(id).unwrap().is_frozen());
+
+        reduce(
+            &InstrumentAction::Unfreeze(id),
+            &mut instruments,
+            &mut session,
+        );
+        let inst = instruments.instrument(id).unwrap();
+        assert!(!inst.is_frozen());
+        assert_eq!(format!("{:?}", inst), original);
+    }
 }
@@ -358,5 +358,7 @@ pub(super) fn reduce(
             }
             true
         }
+        // Meters live in the UI's visualization state, not the session
+        MixerAction::ResetClips => true,
     }
 }
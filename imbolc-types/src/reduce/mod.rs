@@ -23,8 +23,8 @@ mod session;
 mod vst_param;
 
 use crate::{
-    AutomationAction, DomainAction, InstrumentState, PianoRollAction, SessionAction, SessionState,
-    VstParamAction,
+    AudioFeedback, AutomationAction, DomainAction, InstrumentAction, InstrumentState,
+    PianoRollAction, SessionAction, SessionState, VstParamAction,
 };
 
 /// Check whether an action can be incrementally reduced on the audio thread.
@@ -33,11 +33,17 @@ use crate::{
 /// state not available on the audio thread).
 pub fn is_reducible(action: &DomainAction) -> bool {
     match action {
-        DomainAction::Midi(_) | DomainAction::Tuner(_) | DomainAction::AudioFeedback(_) => true,
+        DomainAction::Midi(_) | DomainAction::Tuner(_) => true,
+        // A finished render changes the rendered instrument on the main thread
+        DomainAction::AudioFeedback(f) => !matches!(f, AudioFeedback::RenderComplete { .. }),
 
         DomainAction::Undo | DomainAction::Redo => false,
 
-        DomainAction::Instrument(_) => true,
+        // Sample loads detect the file's tempo on the main thread
+        DomainAction::Instrument(a) => !matches!(
+            a,
+            InstrumentAction::Freeze(_) | InstrumentAction::LoadSampleResult(..)
+        ),
         DomainAction::Mixer(_) => true,
         DomainAction::Bus(_) => true,
         DomainAction::LayerGroup(_) => true,
@@ -49,7 +55,11 @@ pub fn is_reducible(action: &DomainAction) -> bool {
             PianoRollAction::RenderToWav(_)
                 | PianoRollAction::BounceToWav
                 | PianoRollAction::ExportStems
+                | PianoRollAction::BounceOffline
+                | PianoRollAction::ExportStemsOffline
                 | PianoRollAction::CancelExport
+                | PianoRollAction::SetExportOptions(_)
+                | PianoRollAction::ExportMidi
         ),
         DomainAction::Automation(a) => !matches!(a, AutomationAction::ToggleRecording),
         DomainAction::VstParam(a) => !matches!(
@@ -64,9 +74,13 @@ pub fn is_reducible(action: &DomainAction) -> bool {
                 | SessionAction::Load
                 | SessionAction::LoadFrom(_)
                 | SessionAction::ImportCustomSynthDef(_)
+                | SessionAction::ImportMidiFile(_, _)
                 | SessionAction::CreateCheckpoint(_)
                 | SessionAction::RestoreCheckpoint(_)
                 | SessionAction::DeleteCheckpoint(_)
+                | SessionAction::SaveInstrumentPreset { .. }
+                | SessionAction::DeleteInstrumentPreset(_)
+                | SessionAction::SaveEffectChainPreset { .. }
         ),
 
         DomainAction::Arrangement(_) => false,
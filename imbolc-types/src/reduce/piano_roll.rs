@@ -109,6 +109,7 @@ pub(super) fn reduce(action: &PianoRollAction, session: &mut SessionState) -> bo
                                 pitch,
                                 velocity: cn.velocity,
                                 probability: cn.probability,
+                                expression: cn.expression.clone(),
                             },
                         );
                     }
@@ -116,6 +117,46 @@ pub(super) fn reduce(action: &PianoRollAction, session: &mut SessionState) -> bo
             }
             true
         }
+        PianoRollAction::TransformNotes {
+            track,
+            start_tick,
+            end_tick,
+            start_pitch,
+            end_pitch,
+            transform,
+        } => {
+            if let Some(t) = session.piano_roll.track_at_mut(*track) {
+                transform.apply(&mut t.notes, |n| {
+                    n.pitch >= *start_pitch
+                        && n.pitch <= *end_pitch
+                        && n.tick >= *start_tick
+                        && n.tick < *end_tick
+                });
+            }
+            true
+        }
+        PianoRollAction::LoadNoteTake(clip_id) => {
+            let Some(clip) = session.arrangement.clip(*clip_id) else {
+                return true;
+            };
+            let instrument_id = clip.instrument_id;
+            let notes = clip.notes.clone();
+            let pr = &mut session.piano_roll;
+            let start = pr.loop_start;
+            pr.clear_notes(instrument_id, start, start + clip.length_ticks, |_| false);
+            for note in notes {
+                pr.insert_note(
+                    instrument_id,
+                    Note {
+                        tick: start + note.tick,
+                        ..note
+                    },
+                );
+            }
+            true
+        }
+        // RecordNoteOn/RecordNoteOff: take recording lives in dispatch
+        PianoRollAction::RecordNoteOn { .. } | PianoRollAction::RecordNoteOff { .. } => true,
         // PlayNote/PlayNotes: voice spawning only
         PianoRollAction::PlayNote { .. } | PianoRollAction::PlayNotes { .. } => true,
         // ReleaseNote/ReleaseNotes: audio side effect only
@@ -126,6 +167,10 @@ pub(super) fn reduce(action: &PianoRollAction, session: &mut SessionState) -> bo
         PianoRollAction::RenderToWav(_)
         | PianoRollAction::BounceToWav
         | PianoRollAction::ExportStems
-        | PianoRollAction::CancelExport => false,
+        | PianoRollAction::BounceOffline
+        | PianoRollAction::ExportStemsOffline
+        | PianoRollAction::CancelExport
+        | PianoRollAction::SetExportOptions(_)
+        | PianoRollAction::ExportMidi => false,
     }
 }
@@ -49,6 +49,7 @@ pub(super) fn reduce(
                 plugin_path: path.clone(),
                 kind: *kind,
                 params: vec![],
+                latency_secs: None,
             };
             session.vst_plugins.add(plugin);
             true
@@ -61,8 +62,12 @@ pub(super) fn reduce(
         | SessionAction::Load
         | SessionAction::LoadFrom(_)
         | SessionAction::ImportCustomSynthDef(_)
+        | SessionAction::ImportMidiFile(_, _)
         | SessionAction::CreateCheckpoint(_)
         | SessionAction::RestoreCheckpoint(_)
-        | SessionAction::DeleteCheckpoint(_) => false,
+        | SessionAction::DeleteCheckpoint(_)
+        | SessionAction::SaveInstrumentPreset { .. }
+        | SessionAction::DeleteInstrumentPreset(_)
+        | SessionAction::SaveEffectChainPreset { .. } => false,
     }
 }
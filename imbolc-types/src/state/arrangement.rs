@@ -17,6 +17,20 @@ pub type PlacementId = u32;
 /// Unique identifier for an audio clip (recorded audio)
 pub type AudioClipId = u32;
 
+/// Unique identifier for an arrangement marker.
+pub type MarkerId = u32;
+
+/// A named position on the timeline ("Intro", "Chorus"). A marker with an
+/// end is an explicit section; otherwise its section runs to the next marker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub id: MarkerId,
+    pub name: String,
+    pub tick: u32,
+    /// Explicit section end, `None` to run to the next marker
+    pub end_tick: Option<u32>,
+}
+
 /// An audio clip represents recorded audio from an input.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioClip {
@@ -34,6 +48,25 @@ pub struct AudioClip {
     pub waveform_peaks: Vec<f32>,
     /// Original sample rate of the recording
     pub sample_rate: u32,
+    /// Length of the audio file in seconds (playback is trimmed to `duration_ticks`)
+    #[serde(default)]
+    pub length_secs: f32,
+    /// Muted clips stay on the timeline but are not played (e.g. older
+    /// takes stacked under a newer loop-record pass)
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl AudioClip {
+    pub fn end_tick(&self) -> u32 {
+        self.start_tick + self.duration_ticks
+    }
+
+    fn overlaps(&self, other: &AudioClip) -> bool {
+        self.instrument_id == other.instrument_id
+            && self.start_tick < other.end_tick()
+            && other.start_tick < self.end_tick()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -99,6 +132,12 @@ pub struct ClipEditContext {
 pub struct ArrangementState {
     pub clips: Vec<Clip>,
     pub placements: Vec<ClipPlacement>,
+    /// Recorded takes and imported audio files placed directly on the timeline
+    #[serde(default)]
+    pub audio_clips: Vec<AudioClip>,
+    /// Named markers and sections, sorted by tick
+    #[serde(default)]
+    pub markers: Vec<Marker>,
     pub play_mode: PlayMode,
     #[serde(skip)]
     pub editing_clip: Option<ClipEditContext>,
@@ -113,6 +152,18 @@ pub struct ArrangementState {
     pub(crate) next_clip_id: ClipId,
     pub(crate) next_placement_id: PlacementId,
     pub(crate) next_clip_automation_lane_id: AutomationLaneId,
+    #[serde(default = "default_next_audio_clip_id")]
+    pub(crate) next_audio_clip_id: AudioClipId,
+    #[serde(default = "default_next_marker_id")]
+    pub(crate) next_marker_id: MarkerId,
+}
+
+fn default_next_audio_clip_id() -> AudioClipId {
+    1
+}
+
+fn default_next_marker_id() -> MarkerId {
+    1
 }
 
 impl Default for ArrangementState {
@@ -126,6 +177,8 @@ impl ArrangementState {
         Self {
             clips: Vec::new(),
             placements: Vec::new(),
+            audio_clips: Vec::new(),
+            markers: Vec::new(),
             play_mode: PlayMode::default(),
             editing_clip: None,
             selected_placement: None,
@@ -136,6 +189,8 @@ impl ArrangementState {
             next_clip_id: 1,
             next_placement_id: 1,
             next_clip_automation_lane_id: 0,
+            next_audio_clip_id: 1,
+            next_marker_id: 1,
         }
     }
 
@@ -176,6 +231,30 @@ impl ArrangementState {
         }
     }
 
+    /// Keep a recorded pass of MIDI notes (ticks relative to the pass start)
+    /// as a clip named "Take N".
+    pub fn add_note_take(
+        &mut self,
+        instrument_id: InstrumentId,
+        length_ticks: u32,
+        notes: Vec<Note>,
+    ) -> ClipId {
+        let number = self.note_takes(instrument_id).len() + 1;
+        let id = self.add_clip(format!("Take {}", number), instrument_id, length_ticks);
+        if let Some(clip) = self.clip_mut(id) {
+            clip.notes = notes;
+        }
+        id
+    }
+
+    /// The instrument's recorded take clips, oldest first
+    pub fn note_takes(&self, instrument_id: InstrumentId) -> Vec<&Clip> {
+        self.clips
+            .iter()
+            .filter(|c| c.instrument_id == instrument_id && c.name.starts_with("Take "))
+            .collect()
+    }
+
     pub fn clips_for_instrument(&self, instrument_id: InstrumentId) -> Vec<&Clip> {
         self.clips
             .iter()
@@ -241,6 +320,306 @@ impl ArrangementState {
         None
     }
 
+    /// Add an audio clip on an instrument's lane. Peaks, sample rate and
+    /// file length are filled in by the caller once the file has been read.
+    pub fn add_audio_clip(
+        &mut self,
+        name: String,
+        path: PathBuf,
+        instrument_id: InstrumentId,
+        start_tick: u32,
+        duration_ticks: u32,
+    ) -> AudioClipId {
+        let id = self.next_audio_clip_id;
+        self.next_audio_clip_id += 1;
+        self.audio_clips.push(AudioClip {
+            id,
+            name,
+            path,
+            start_tick,
+            duration_ticks,
+            instrument_id,
+            waveform_peaks: Vec::new(),
+            sample_rate: 0,
+            length_secs: 0.0,
+            muted: false,
+        });
+        id
+    }
+
+    pub fn audio_clip(&self, id: AudioClipId) -> Option<&AudioClip> {
+        self.audio_clips.iter().find(|c| c.id == id)
+    }
+
+    pub fn audio_clip_mut(&mut self, id: AudioClipId) -> Option<&mut AudioClip> {
+        self.audio_clips.iter_mut().find(|c| c.id == id)
+    }
+
+    pub fn remove_audio_clip(&mut self, id: AudioClipId) -> Option<AudioClip> {
+        let pos = self.audio_clips.iter().position(|c| c.id == id)?;
+        Some(self.audio_clips.remove(pos))
+    }
+
+    pub fn move_audio_clip(&mut self, id: AudioClipId, new_start_tick: u32) {
+        if let Some(c) = self.audio_clip_mut(id) {
+            c.start_tick = new_start_tick;
+        }
+    }
+
+    /// Copy an audio clip to start right after the original. The copy shares
+    /// the source file and peaks.
+    pub fn duplicate_audio_clip(&mut self, id: AudioClipId) -> Option<AudioClipId> {
+        let mut copy = self.audio_clip(id)?.clone();
+        copy.id = self.next_audio_clip_id;
+        self.next_audio_clip_id += 1;
+        copy.start_tick = copy.end_tick();
+        let new_id = copy.id;
+        self.audio_clips.push(copy);
+        Some(new_id)
+    }
+
+    pub fn audio_clips_for_instrument(&self, instrument_id: InstrumentId) -> Vec<&AudioClip> {
+        let mut clips: Vec<&AudioClip> = self
+            .audio_clips
+            .iter()
+            .filter(|c| c.instrument_id == instrument_id)
+            .collect();
+        clips.sort_by_key(|c| c.start_tick);
+        clips
+    }
+
+    /// Audio clip under `tick` on an instrument's lane. Where takes are
+    /// stacked, the one that plays is preferred over muted ones.
+    pub fn audio_clip_at(&self, instrument_id: InstrumentId, tick: u32) -> Option<&AudioClip> {
+        let mut stack = self.take_stack_at(instrument_id, tick).into_iter();
+        let first = stack.next()?;
+        if !first.muted {
+            return Some(first);
+        }
+        stack.find(|c| !c.muted).or(Some(first))
+    }
+
+    /// All audio clips on an instrument's lane covering `tick`, oldest first.
+    pub fn take_stack_at(&self, instrument_id: InstrumentId, tick: u32) -> Vec<&AudioClip> {
+        let mut stack: Vec<&AudioClip> = self
+            .audio_clips
+            .iter()
+            .filter(|c| {
+                c.instrument_id == instrument_id && tick >= c.start_tick && tick < c.end_tick()
+            })
+            .collect();
+        stack.sort_by_key(|c| c.id);
+        stack
+    }
+
+    /// Make a take the one that plays: unmute it and mute every other clip
+    /// on the same lane that overlaps it.
+    pub fn solo_take(&mut self, id: AudioClipId) {
+        let Some(take) = self.audio_clip(id).cloned() else {
+            return;
+        };
+        for clip in &mut self.audio_clips {
+            if clip.id == id {
+                clip.muted = false;
+            } else if clip.overlaps(&take) {
+                clip.muted = true;
+            }
+        }
+    }
+
+    /// The take after the currently playing one in the stack under `tick`
+    /// (wrapping), or `None` when there is nothing to switch to.
+    pub fn next_take_at(&self, instrument_id: InstrumentId, tick: u32) -> Option<AudioClipId> {
+        let stack = self.take_stack_at(instrument_id, tick);
+        if stack.len() < 2 {
+            return None;
+        }
+        let next = match stack.iter().position(|c| !c.muted) {
+            Some(current) => (current + 1) % stack.len(),
+            None => 0,
+        };
+        Some(stack[next].id)
+    }
+
+    /// Add a marker, or a section when `end_tick` is set. Markers stay sorted
+    /// by tick; a new marker goes after existing ones at the same tick.
+    pub fn add_marker(&mut self, name: String, tick: u32, end_tick: Option<u32>) -> MarkerId {
+        let id = self.next_marker_id;
+        self.next_marker_id += 1;
+        let pos = self.markers.partition_point(|m| m.tick <= tick);
+        self.markers.insert(
+            pos,
+            Marker {
+                id,
+                name,
+                tick,
+                end_tick: end_tick.filter(|&end| end > tick),
+            },
+        );
+        id
+    }
+
+    pub fn marker(&self, id: MarkerId) -> Option<&Marker> {
+        self.markers.iter().find(|m| m.id == id)
+    }
+
+    pub fn marker_mut(&mut self, id: MarkerId) -> Option<&mut Marker> {
+        self.markers.iter_mut().find(|m| m.id == id)
+    }
+
+    pub fn remove_marker(&mut self, id: MarkerId) -> Option<Marker> {
+        let pos = self.markers.iter().position(|m| m.id == id)?;
+        Some(self.markers.remove(pos))
+    }
+
+    /// The marker whose section covers `tick`: the last one starting at or
+    /// before it whose range hasn't ended.
+    pub fn marker_at(&self, tick: u32) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .rev()
+            .filter(|m| m.tick <= tick)
+            .find(|m| self.section_range(m.id).is_some_and(|(_, end)| tick < end))
+    }
+
+    /// First marker strictly after `tick`.
+    pub fn next_marker(&self, tick: u32) -> Option<&Marker> {
+        self.markers.iter().find(|m| m.tick > tick)
+    }
+
+    /// Last marker strictly before `tick`.
+    pub fn prev_marker(&self, tick: u32) -> Option<&Marker> {
+        self.markers.iter().rev().find(|m| m.tick < tick)
+    }
+
+    /// Tick range of a marker's section: its explicit end, or up to the next
+    /// marker, or the end of the arrangement. `None` when that range is empty.
+    pub fn section_range(&self, id: MarkerId) -> Option<(u32, u32)> {
+        let marker = self.marker(id)?;
+        let end = marker.end_tick.unwrap_or_else(|| {
+            self.next_marker(marker.tick)
+                .map_or_else(|| self.arrangement_length(), |m| m.tick)
+        });
+        (end > marker.tick).then_some((marker.tick, end))
+    }
+
+    /// Open `len` ticks of empty time at `at`: placements, audio clips and
+    /// markers starting at or after it move right, and sections spanning it grow.
+    pub fn insert_time(&mut self, at: u32, len: u32) {
+        let shift = |tick: u32| if tick >= at { tick + len } else { tick };
+        for placement in &mut self.placements {
+            placement.start_tick = shift(placement.start_tick);
+        }
+        for clip in &mut self.audio_clips {
+            clip.start_tick = shift(clip.start_tick);
+        }
+        for marker in &mut self.markers {
+            marker.tick = shift(marker.tick);
+            marker.end_tick = marker
+                .end_tick
+                .map(|end| if end > at { end + len } else { end });
+        }
+    }
+
+    /// Cut `[start, end)` out of the timeline: content starting inside it is
+    /// removed and everything after moves left. Sections overlapping the cut
+    /// are shortened.
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        let len = end - start;
+        let inside = |tick: u32| tick >= start && tick < end;
+        let shift = |tick: u32| {
+            if tick >= end {
+                tick - len
+            } else {
+                tick.min(start)
+            }
+        };
+
+        self.placements.retain(|p| !inside(p.start_tick));
+        for placement in &mut self.placements {
+            placement.start_tick = shift(placement.start_tick);
+        }
+        self.audio_clips.retain(|c| !inside(c.start_tick));
+        for clip in &mut self.audio_clips {
+            clip.start_tick = shift(clip.start_tick);
+        }
+        // Markers inside the cut go unless their section reaches past it
+        self.markers
+            .retain(|m| !inside(m.tick) || m.end_tick.is_some_and(|e| e > end));
+        for marker in &mut self.markers {
+            marker.tick = shift(marker.tick);
+            marker.end_tick = marker.end_tick.map(shift);
+        }
+        self.markers.sort_by_key(|m| m.tick);
+        self.selected_placement = None;
+    }
+
+    /// Repeat `[start, end)` right after itself: later content moves right by
+    /// the range's length and placements, audio clips and markers starting
+    /// inside it are copied into the gap. Copies are trimmed to the range.
+    pub fn duplicate_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        let len = end - start;
+        let markers: Vec<(String, u32, Option<u32>)> = self
+            .markers
+            .iter()
+            .filter(|m| m.tick >= start && m.tick < end)
+            .map(|m| {
+                let copy_end = m.end_tick.map(|e| e.min(end) + len);
+                (m.name.clone(), m.tick + len, copy_end)
+            })
+            .collect();
+        self.insert_time(end, len);
+        for (name, tick, end_tick) in markers {
+            self.add_marker(name, tick, end_tick);
+        }
+
+        let placements: Vec<ClipPlacement> = self
+            .placements
+            .iter()
+            .filter(|p| p.start_tick >= start && p.start_tick < end)
+            .cloned()
+            .collect();
+        for placement in placements {
+            let Some(clip_len) = self
+                .clip(placement.clip_id)
+                .map(|c| placement.effective_length(c))
+            else {
+                continue;
+            };
+            let available = end - placement.start_tick;
+            let id = self.add_placement(
+                placement.clip_id,
+                placement.instrument_id,
+                placement.start_tick + len,
+            );
+            if clip_len > available {
+                self.resize_placement(id, Some(available));
+            } else if placement.length_override.is_some() {
+                self.resize_placement(id, placement.length_override);
+            }
+        }
+
+        let clips: Vec<AudioClip> = self
+            .audio_clips
+            .iter()
+            .filter(|c| c.start_tick >= start && c.start_tick < end)
+            .cloned()
+            .collect();
+        for mut clip in clips {
+            clip.id = self.next_audio_clip_id;
+            self.next_audio_clip_id += 1;
+            clip.duration_ticks = clip.duration_ticks.min(end - clip.start_tick);
+            clip.start_tick += len;
+            self.audio_clips.push(clip);
+        }
+    }
+
     pub fn flatten_to_notes(&self) -> HashMap<InstrumentId, Vec<Note>> {
         let mut result: HashMap<InstrumentId, Vec<Note>> = HashMap::new();
 
@@ -289,6 +668,9 @@ impl ArrangementState {
                 max_end = max_end.max(placement.end_tick(clip));
             }
         }
+        for clip in &self.audio_clips {
+            max_end = max_end.max(clip.end_tick());
+        }
         max_end
     }
 
@@ -361,6 +743,8 @@ impl ArrangementState {
         self.placements.retain(|p| {
             p.instrument_id != instrument_id && !clip_ids_to_remove.contains(&p.clip_id)
         });
+        self.audio_clips
+            .retain(|c| c.instrument_id != instrument_id);
 
         self.selected_placement = None;
     }
@@ -380,9 +764,21 @@ impl ArrangementState {
         self.next_clip_automation_lane_id
     }
 
+    /// Get the next audio clip ID counter (for persistence)
+    pub fn next_audio_clip_id(&self) -> AudioClipId {
+        self.next_audio_clip_id
+    }
+
+    /// Get the next marker ID counter (for persistence)
+    pub fn next_marker_id(&self) -> MarkerId {
+        self.next_marker_id
+    }
+
     pub fn recalculate_next_ids(&mut self) {
         self.next_clip_id = self.clips.iter().map(|c| c.id).max().unwrap_or(0) + 1;
         self.next_placement_id = self.placements.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+        self.next_audio_clip_id = self.audio_clips.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+        self.next_marker_id = self.markers.iter().map(|m| m.id).max().unwrap_or(0) + 1;
         self.next_clip_automation_lane_id = self
             .clips
             .iter()
@@ -404,6 +800,20 @@ mod tests {
     use super::*;
     use crate::InstrumentId;
 
+    #[test]
+    fn note_takes_are_numbered_per_instrument() {
+        let mut arr = ArrangementState::new();
+        let a = InstrumentId::new(1);
+        let b = InstrumentId::new(2);
+        arr.add_clip("Clip 1".to_string(), a, 480);
+        let first = arr.add_note_take(a, 1920, Vec::new());
+        arr.add_note_take(b, 1920, Vec::new());
+        let second = arr.add_note_take(a, 1920, Vec::new());
+        assert_eq!(arr.clip(first).unwrap().name, "Take 1");
+        assert_eq!(arr.clip(second).unwrap().name, "Take 2");
+        assert_eq!(arr.clip(second).unwrap().length_ticks, 1920);
+    }
+
     #[test]
     fn test_add_remove_clip() {
         let mut arr = ArrangementState::new();
@@ -467,6 +877,7 @@ mod tests {
                 velocity: 100,
                 duration: 48,
                 probability: 1.0,
+                expression: None,
             });
             clip.notes.push(Note {
                 tick: 96,
@@ -474,6 +885,7 @@ mod tests {
                 velocity: 100,
                 duration: 48,
                 probability: 1.0,
+                expression: None,
             });
         }
 
@@ -506,6 +918,7 @@ mod tests {
                 velocity: 100,
                 duration: 50,
                 probability: 1.0,
+                expression: None,
             });
             // Note at 60, duration 50 (extends past 100)
             clip.notes.push(Note {
@@ -514,6 +927,7 @@ mod tests {
                 velocity: 100,
                 duration: 50,
                 probability: 1.0,
+                expression: None,
             });
         }
 
@@ -670,4 +1084,243 @@ mod tests {
         assert_eq!(flat[0].points[0].tick, 0);
         assert_eq!(flat[0].points[1].tick, 50);
     }
+
+    #[test]
+    fn test_audio_clip_add_move_remove() {
+        let mut arr = ArrangementState::new();
+        let inst = InstrumentId::new(1);
+        let a = arr.add_audio_clip("Take 1".to_string(), PathBuf::from("a.wav"), inst, 480, 960);
+        let b = arr.add_audio_clip("Take 2".to_string(), PathBuf::from("b.wav"), inst, 0, 240);
+        assert_ne!(a, b);
+
+        // Sorted by start tick
+        let ids: Vec<_> = arr
+            .audio_clips_for_instrument(inst)
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![b, a]);
+
+        assert_eq!(arr.audio_clip_at(inst, 100).map(|c| c.id), Some(b));
+        assert!(arr.audio_clip_at(inst, 300).is_none());
+        assert_eq!(arr.audio_clip_at(inst, 1000).map(|c| c.id), Some(a));
+
+        arr.move_audio_clip(a, 1920);
+        assert_eq!(arr.audio_clip(a).unwrap().start_tick, 1920);
+        assert_eq!(arr.arrangement_length(), 1920 + 960);
+
+        assert!(arr.remove_audio_clip(b).is_some());
+        assert!(arr.audio_clip(b).is_none());
+        assert!(arr.remove_audio_clip(b).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_audio_clip() {
+        let mut arr = ArrangementState::new();
+        let inst = InstrumentId::new(2);
+        let id = arr.add_audio_clip(
+            "Loop".to_string(),
+            PathBuf::from("loop.wav"),
+            inst,
+            240,
+            480,
+        );
+        arr.audio_clip_mut(id).unwrap().waveform_peaks = vec![0.5, 1.0];
+
+        let dup = arr.duplicate_audio_clip(id).unwrap();
+        let copy = arr.audio_clip(dup).unwrap();
+        assert_eq!(copy.start_tick, 720);
+        assert_eq!(copy.path, PathBuf::from("loop.wav"));
+        assert_eq!(copy.waveform_peaks, vec![0.5, 1.0]);
+        assert!(arr.duplicate_audio_clip(999).is_none());
+    }
+
+    #[test]
+    fn test_stacked_takes_solo_and_cycle() {
+        let mut arr = ArrangementState::new();
+        let inst = InstrumentId::new(1);
+        let other = InstrumentId::new(2);
+        let t1 = arr.add_audio_clip("Take 1".to_string(), PathBuf::from("1.wav"), inst, 0, 960);
+        let t2 = arr.add_audio_clip("Take 2".to_string(), PathBuf::from("2.wav"), inst, 0, 960);
+        let after = arr.add_audio_clip("Later".to_string(), PathBuf::from("3.wav"), inst, 960, 480);
+        let bass = arr.add_audio_clip("Bass".to_string(), PathBuf::from("4.wav"), other, 0, 960);
+
+        arr.solo_take(t2);
+        assert!(arr.audio_clip(t1).unwrap().muted);
+        assert!(!arr.audio_clip(t2).unwrap().muted);
+        assert!(!arr.audio_clip(after).unwrap().muted);
+        assert!(!arr.audio_clip(bass).unwrap().muted);
+        assert_eq!(arr.audio_clip_at(inst, 100).map(|c| c.id), Some(t2));
+
+        assert_eq!(arr.next_take_at(inst, 100), Some(t1));
+        arr.solo_take(t1);
+        assert_eq!(arr.audio_clip_at(inst, 100).map(|c| c.id), Some(t1));
+        assert_eq!(arr.next_take_at(inst, 100), Some(t2));
+
+        // A single clip has no alternative take
+        assert_eq!(arr.next_take_at(inst, 1000), None);
+    }
+
+    #[test]
+    fn test_audio_clips_follow_instrument_removal_and_ids() {
+        let mut arr = ArrangementState::new();
+        arr.add_audio_clip(
+            "A".to_string(),
+            PathBuf::from("a.wav"),
+            InstrumentId::new(1),
+            0,
+            10,
+        );
+        arr.add_audio_clip(
+            "B".to_string(),
+            PathBuf::from("b.wav"),
+            InstrumentId::new(2),
+            0,
+            10,
+        );
+
+        arr.remove_instrument_data(InstrumentId::new(1));
+        assert_eq!(arr.audio_clips.len(), 1);
+        assert_eq!(arr.audio_clips[0].instrument_id, InstrumentId::new(2));
+
+        arr.audio_clips[0].id = 7;
+        arr.recalculate_next_ids();
+        assert_eq!(arr.next_audio_clip_id(), 8);
+    }
+
+    #[test]
+    fn test_markers_and_section_ranges() {
+        let mut arr = ArrangementState::new();
+        let cid = arr.add_clip("Loop".to_string(), InstrumentId::new(1), 1920);
+        arr.add_placement(cid, InstrumentId::new(1), 0);
+        arr.add_placement(cid, InstrumentId::new(1), 5760);
+
+        let chorus = arr.add_marker("Chorus".to_string(), 3840, None);
+        let intro = arr.add_marker("Intro".to_string(), 0, None);
+        let bridge = arr.add_marker("Bridge".to_string(), 5760, Some(6720));
+        let names: Vec<&str> = arr.markers.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Intro", "Chorus", "Bridge"]);
+
+        assert_eq!(arr.section_range(intro), Some((0, 3840)));
+        assert_eq!(arr.section_range(chorus), Some((3840, 5760)));
+        assert_eq!(arr.section_range(bridge), Some((5760, 6720)));
+
+        assert_eq!(arr.marker_at(4000).map(|m| m.id), Some(chorus));
+        // Past the Bridge's explicit end nothing covers the tick
+        assert!(arr.marker_at(7000).is_none());
+        assert_eq!(arr.next_marker(0).map(|m| m.id), Some(chorus));
+        assert_eq!(arr.prev_marker(3840).map(|m| m.id), Some(intro));
+
+        arr.remove_marker(chorus);
+        assert_eq!(arr.section_range(intro), Some((0, 5760)));
+        arr.recalculate_next_ids();
+        assert_eq!(arr.next_marker_id(), 4);
+    }
+
+    #[test]
+    fn test_insert_and_delete_time() {
+        let mut arr = ArrangementState::new();
+        let cid = arr.add_clip("Loop".to_string(), InstrumentId::new(1), 1920);
+        let early = arr.add_placement(cid, InstrumentId::new(1), 0);
+        let late = arr.add_placement(cid, InstrumentId::new(1), 1920);
+        let audio = arr.add_audio_clip(
+            "Vox".to_string(),
+            PathBuf::from("vox.wav"),
+            InstrumentId::new(2),
+            1920,
+            960,
+        );
+        let verse = arr.add_marker("Verse".to_string(), 0, Some(2400));
+        let outro = arr.add_marker("Outro".to_string(), 1920, None);
+
+        arr.insert_time(1920, 3840);
+        let start = |arr: &ArrangementState, id| {
+            arr.placements
+                .iter()
+                .find(|p| p.id == id)
+                .unwrap()
+                .start_tick
+        };
+        assert_eq!(start(&arr, early), 0);
+        assert_eq!(start(&arr, late), 5760);
+        assert_eq!(arr.audio_clip(audio).unwrap().start_tick, 5760);
+        // The straddling section grows, the later marker moves
+        assert_eq!(arr.marker(verse).unwrap().end_tick, Some(6240));
+        assert_eq!(arr.marker(outro).unwrap().tick, 5760);
+
+        // Deleting the first bar drops what starts there
+        arr.delete_time(0, 1920);
+        assert_eq!(arr.placements.len(), 1);
+        assert_eq!(start(&arr, late), 3840);
+        assert_eq!(arr.audio_clip(audio).unwrap().start_tick, 3840);
+        assert_eq!(arr.marker(verse).unwrap().tick, 0);
+        assert_eq!(arr.marker(verse).unwrap().end_tick, Some(4320));
+        assert_eq!(arr.marker(outro).unwrap().tick, 3840);
+
+        // A marker inside the cut goes with it
+        arr.delete_time(3840, 4800);
+        assert!(arr.marker(outro).is_none());
+        assert_eq!(arr.marker(verse).unwrap().end_tick, Some(3840));
+    }
+
+    #[test]
+    fn test_duplicate_time_copies_section_content() {
+        let mut arr = ArrangementState::new();
+        let cid = arr.add_clip("Long".to_string(), InstrumentId::new(1), 3840);
+        let short = arr.add_clip("Short".to_string(), InstrumentId::new(1), 960);
+        arr.add_placement(short, InstrumentId::new(1), 0);
+        arr.add_placement(cid, InstrumentId::new(1), 960);
+        let after = arr.add_placement(short, InstrumentId::new(1), 1920);
+        arr.add_audio_clip(
+            "Vox".to_string(),
+            PathBuf::from("vox.wav"),
+            InstrumentId::new(2),
+            480,
+            1920,
+        );
+        arr.add_marker("Verse".to_string(), 0, Some(1920));
+        let outro = arr.add_marker("Outro".to_string(), 1920, None);
+
+        arr.duplicate_time(0, 1920);
+
+        assert_eq!(arr.placements.len(), 5);
+        let after_start = arr
+            .placements
+            .iter()
+            .find(|p| p.id == after)
+            .unwrap()
+            .start_tick;
+        assert_eq!(after_start, 3840);
+        // The long clip's copy is trimmed to the duplicated range
+        let copy = arr
+            .placements
+            .iter()
+            .find(|p| p.clip_id == cid && p.start_tick == 2880)
+            .unwrap();
+        assert_eq!(copy.length_override, Some(960));
+        assert!(arr
+            .placements
+            .iter()
+            .any(|p| p.clip_id == short && p.start_tick == 1920));
+        let audio_starts: Vec<u32> = arr.audio_clips.iter().map(|c| c.start_tick).collect();
+        assert_eq!(audio_starts, vec![480, 2400]);
+        // The audio copy stops where the range ends
+        let audio_lengths: Vec<u32> = arr.audio_clips.iter().map(|c| c.duration_ticks).collect();
+        assert_eq!(audio_lengths, vec![1920, 1440]);
+        assert_ne!(arr.audio_clips[0].id, arr.audio_clips[1].id);
+        assert_eq!(arr.marker(outro).unwrap().tick, 3840);
+        let markers: Vec<(&str, u32, Option<u32>)> = arr
+            .markers
+            .iter()
+            .map(|m| (m.name.as_str(), m.tick, m.end_tick))
+            .collect();
+        assert_eq!(
+            markers,
+            vec![
+                ("Verse", 0, Some(1920)),
+                ("Verse", 1920, Some(3840)),
+                ("Outro", 3840, None)
+            ]
+        );
+    }
 }
@@ -646,6 +646,45 @@ impl AutomationLane {
     pub fn point_at_mut(&mut self, tick: u32) -> Option<&mut AutomationPoint> {
         self.points.iter_mut().find(|p| p.tick == tick)
     }
+
+    /// Move points at or after `at` right by `len` ticks
+    pub fn insert_time(&mut self, at: u32, len: u32) {
+        for point in self.points.iter_mut().filter(|p| p.tick >= at) {
+            point.tick += len;
+        }
+    }
+
+    /// Drop points in `[start, end)` and move later points left to close the gap
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        self.points.retain(|p| p.tick < start || p.tick >= end);
+        for point in self.points.iter_mut().filter(|p| p.tick >= end) {
+            point.tick -= end - start;
+        }
+    }
+
+    /// Repeat the points in `[start, end)` right after the range, moving
+    /// later points out of the way
+    pub fn duplicate_time(&mut self, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        let len = end - start;
+        self.insert_time(end, len);
+        let copies: Vec<AutomationPoint> = self
+            .points
+            .iter()
+            .filter(|p| p.tick >= start && p.tick < end)
+            .map(|p| AutomationPoint {
+                tick: p.tick + len,
+                ..p.clone()
+            })
+            .collect();
+        self.points.extend(copies);
+        self.points.sort_by_key(|p| p.tick);
+    }
 }
 
 /// Collection of automation lanes for a session
@@ -788,6 +827,27 @@ impl AutomationState {
         }
     }
 
+    /// Open `len` ticks of empty time at `at` on every lane
+    pub fn insert_time(&mut self, at: u32, len: u32) {
+        for lane in &mut self.lanes {
+            lane.insert_time(at, len);
+        }
+    }
+
+    /// Cut `[start, end)` out of every lane
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        for lane in &mut self.lanes {
+            lane.delete_time(start, end);
+        }
+    }
+
+    /// Repeat `[start, end)` right after itself on every lane
+    pub fn duplicate_time(&mut self, start: u32, end: u32) {
+        for lane in &mut self.lanes {
+            lane.duplicate_time(start, end);
+        }
+    }
+
     /// Remove all lanes for a bus (when bus is deleted)
     pub fn remove_lanes_for_bus(&mut self, bus_id: BusId) {
         self.lanes
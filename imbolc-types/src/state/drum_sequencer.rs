@@ -1,7 +1,7 @@
 //! Drum sequencer types.
 
 use super::sampler::{BufferId, Slice, SliceId};
-use crate::InstrumentId;
+use crate::{BusId, InstrumentId, ParameterTarget};
 use serde::{Deserialize, Serialize};
 
 pub const NUM_PADS: usize = 12;
@@ -64,6 +64,166 @@ impl StepResolution {
     }
 }
 
+/// A parameter held at a value for one step (Elektron-style parameter lock).
+/// The value is in the target's own units (see `ParameterTarget::default_range`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ParamLock {
+    pub target: ParameterTarget,
+    pub value: f32,
+}
+
+impl ParamLock {
+    /// Targets a step of `pad` can lock, in the order the sequencer cycles
+    /// them, with a send level for each of `buses`. Sample pads offer the
+    /// one-shot's rate and slice, instrument-trigger pads the envelope.
+    pub fn targets(pad: &DrumPad, buses: impl IntoIterator<Item = BusId>) -> Vec<ParameterTarget> {
+        let mut targets = vec![
+            ParameterTarget::FilterCutoff,
+            ParameterTarget::FilterResonance,
+            ParameterTarget::Level,
+            ParameterTarget::Pan,
+        ];
+        targets.extend(buses.into_iter().map(ParameterTarget::SendLevel));
+        targets.push(ParameterTarget::SampleAmp);
+        if pad.is_instrument_trigger() {
+            targets.extend([
+                ParameterTarget::Attack,
+                ParameterTarget::Decay,
+                ParameterTarget::Release,
+            ]);
+        } else {
+            targets.extend([
+                ParameterTarget::SampleRate,
+                ParameterTarget::SampleStart,
+                ParameterTarget::SampleEnd,
+            ]);
+        }
+        targets
+    }
+
+    /// Starting value for a new lock when the instrument stores none:
+    /// unity for one-shot rate and amp, mid-range otherwise
+    pub fn default_value(target: &ParameterTarget) -> f32 {
+        match target {
+            ParameterTarget::SampleRate | ParameterTarget::SampleAmp => 1.0,
+            _ => {
+                let (min, max) = target.default_range();
+                (min + max) / 2.0
+            }
+        }
+    }
+
+    /// Whether the lock shapes the triggered hit (one-shot rate, amp and
+    /// slice, envelope) rather than the instrument's chain
+    pub fn on_voice(target: &ParameterTarget) -> bool {
+        matches!(
+            target,
+            ParameterTarget::SampleRate
+                | ParameterTarget::SampleAmp
+                | ParameterTarget::SampleStart
+                | ParameterTarget::SampleEnd
+                | ParameterTarget::Attack
+                | ParameterTarget::Decay
+                | ParameterTarget::Release
+        )
+    }
+
+    /// `value` moved by `steps` increments: a sixth of an octave for
+    /// frequencies, a twentieth of the range otherwise
+    pub fn nudge(target: &ParameterTarget, value: f32, steps: i8) -> f32 {
+        let (min, max) = target.default_range();
+        let nudged = match target {
+            ParameterTarget::FilterCutoff | ParameterTarget::EqBandFreq(_) => {
+                value * 2.0_f32.powf(steps as f32 / 6.0)
+            }
+            _ => value + (max - min) * steps as f32 / 20.0,
+        };
+        nudged.clamp(min, max)
+    }
+}
+
+/// When an active step plays, checked before its probability
+/// (Elektron-style trig condition)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StepCondition {
+    #[default]
+    Always,
+    /// Plays on pass `n` (1-based) of every `m` loops of the pattern
+    Loop { n: u8, m: u8 },
+    /// Plays only if the pad's previous active step played
+    Previous,
+    /// Plays only if the pad's previous active step did not play
+    NotPrevious,
+    /// Plays only while fill is on
+    Fill,
+    /// Plays only while fill is off
+    NotFill,
+}
+
+impl StepCondition {
+    /// Conditions in the order the sequencer cycles them
+    pub fn all() -> Vec<StepCondition> {
+        let mut all = vec![StepCondition::Always];
+        for m in 2..=4 {
+            for n in 1..=m {
+                all.push(StepCondition::Loop { n, m });
+            }
+        }
+        all.extend([
+            StepCondition::Previous,
+            StepCondition::NotPrevious,
+            StepCondition::Fill,
+            StepCondition::NotFill,
+        ]);
+        all
+    }
+
+    /// The condition `delta` places further along `all()`, wrapping
+    pub fn cycle(self, delta: i8) -> Self {
+        let all = Self::all();
+        let pos = all.iter().position(|c| *c == self).unwrap_or(0) as i32;
+        let len = all.len() as i32;
+        all[(pos + delta as i32).rem_euclid(len) as usize]
+    }
+
+    /// Whether the step plays on loop `loop_count` (0-based) of its pattern,
+    /// given whether the pad's previous active step played and the fill state
+    pub fn is_met(&self, loop_count: u32, previous_played: bool, fill: bool) -> bool {
+        match *self {
+            StepCondition::Always => true,
+            StepCondition::Loop { n, m } => {
+                m > 0 && loop_count % m as u32 == (n.max(1) as u32 - 1) % m as u32
+            }
+            StepCondition::Previous => previous_played,
+            StepCondition::NotPrevious => !previous_played,
+            StepCondition::Fill => fill,
+            StepCondition::NotFill => !fill,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            StepCondition::Always => "Always".to_string(),
+            StepCondition::Loop { n, m } => format!("{}:{}", n, m),
+            StepCondition::Previous => "PRE".to_string(),
+            StepCondition::NotPrevious => "!PRE".to_string(),
+            StepCondition::Fill => "FILL".to_string(),
+            StepCondition::NotFill => "!FILL".to_string(),
+        }
+    }
+}
+
+/// Most hits a ratcheted step can play
+pub const MAX_RATCHET: u8 = 8;
+
+fn default_ratchet() -> u8 {
+    1
+}
+
+fn default_ratchet_decay() -> f32 {
+    1.0
+}
+
 /// A single step in a drum pattern.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrumStep {
@@ -71,6 +231,19 @@ pub struct DrumStep {
     pub velocity: u8,     // 1-127, default 100
     pub probability: f32, // 0.0-1.0, default 1.0 (always play)
     pub pitch_offset: i8, // semitone offset per step, default 0
+    /// Parameters held at a value while this step plays
+    #[serde(default)]
+    pub locks: Vec<ParamLock>,
+    /// When the step plays
+    #[serde(default)]
+    pub condition: StepCondition,
+    /// Hits spread evenly across the step (1 = a single hit)
+    #[serde(default = "default_ratchet")]
+    pub ratchet: u8,
+    /// Velocity kept by each ratchet hit from the one before: 1.0 repeats
+    /// evenly, lower values fade the retrigs out
+    #[serde(default = "default_ratchet_decay")]
+    pub ratchet_decay: f32,
 }
 
 impl Default for DrumStep {
@@ -80,10 +253,38 @@ impl Default for DrumStep {
             velocity: 100,
             probability: 1.0,
             pitch_offset: 0,
+            locks: Vec::new(),
+            condition: StepCondition::Always,
+            ratchet: 1,
+            ratchet_decay: 1.0,
         }
     }
 }
 
+impl DrumStep {
+    /// Value this step locks `target` to
+    pub fn lock(&self, target: &ParameterTarget) -> Option<f32> {
+        self.locks
+            .iter()
+            .find(|l| l.target == *target)
+            .map(|l| l.value)
+    }
+
+    pub fn set_lock(&mut self, target: ParameterTarget, value: f32) {
+        match self.locks.iter_mut().find(|l| l.target == target) {
+            Some(lock) => lock.value = value,
+            None => self.locks.push(ParamLock { target, value }),
+        }
+    }
+
+    /// Remove the lock on `target`. Returns whether there was one.
+    pub fn clear_lock(&mut self, target: &ParameterTarget) -> bool {
+        let len = self.locks.len();
+        self.locks.retain(|l| l.target != *target);
+        self.locks.len() != len
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChopperState {
     pub buffer_id: Option<BufferId>,
@@ -94,6 +295,20 @@ pub struct ChopperState {
     pub next_slice_id: SliceId,
     pub waveform_peaks: Vec<f32>,
     pub duration_secs: f32,
+    /// Tempo the loaded sample was recorded at (detected on load, editable)
+    #[serde(default)]
+    pub source_bpm: Option<f32>,
+    /// Slices assigned to pads follow the project tempo
+    #[serde(default)]
+    pub warp: bool,
+}
+
+impl ChopperState {
+    /// Tempo stamped on pads assigned from this chopper: the sample's own
+    /// tempo while warping, so those pads follow the project tempo.
+    pub fn pad_warp_bpm(&self) -> Option<f32> {
+        self.source_bpm.filter(|_| self.warp)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +330,9 @@ pub struct DrumPad {
     pub slice_end: f32,   // 0.0-1.0, default 1.0
     pub reverse: bool,    // play sample backwards
     pub pitch: i8,        // semitone offset, -24 to +24
+    /// Tempo of the chopped loop when the slice is warped to the project tempo
+    #[serde(default)]
+    pub warp_bpm: Option<f32>,
 }
 
 fn default_trigger_freq() -> f32 {
@@ -134,6 +352,7 @@ impl Default for DrumPad {
             slice_end: 1.0,
             reverse: false,
             pitch: 0,
+            warp_bpm: None,
         }
     }
 }
@@ -143,6 +362,16 @@ impl DrumPad {
     pub fn is_instrument_trigger(&self) -> bool {
         self.instrument_id.is_some()
     }
+
+    /// The pad's own setting of a slice target, which a new slice lock
+    /// starts from
+    pub fn slice_value(&self, target: &ParameterTarget) -> Option<f32> {
+        match target {
+            ParameterTarget::SampleStart => Some(self.slice_start),
+            ParameterTarget::SampleEnd => Some(self.slice_end),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -189,6 +418,20 @@ pub struct DrumSequencerState {
     /// The pad currently being edited (for instrument picker modal)
     #[serde(skip)]
     pub editing_pad: Option<usize>,
+    /// Parameters held by the locks of the last step played, to restore when
+    /// a step without them plays (runtime)
+    #[serde(skip)]
+    pub active_locks: Vec<(InstrumentId, ParameterTarget)>,
+    /// Fill mode, for steps with fill conditions (runtime performance toggle)
+    #[serde(skip)]
+    pub fill: bool,
+    /// Completed passes of each pattern since playback started (runtime)
+    #[serde(skip)]
+    pub loop_counts: Vec<u32>,
+    /// Whether each pad's last active step played, for `Previous`
+    /// conditions (runtime)
+    #[serde(skip)]
+    pub pads_played: Vec<bool>,
     /// Step resolution (grid subdivision)
     #[serde(default)]
     pub step_resolution: StepResolution,
@@ -213,6 +456,10 @@ impl DrumSequencerState {
             chain_enabled: false,
             chain_position: 0,
             editing_pad: None,
+            active_locks: Vec::new(),
+            fill: false,
+            loop_counts: vec![0; NUM_PATTERNS],
+            pads_played: vec![false; NUM_PADS],
             step_resolution: StepResolution::default(),
         }
     }
@@ -224,6 +471,37 @@ impl DrumSequencerState {
     pub fn pattern_mut(&mut self) -> &mut DrumPattern {
         &mut self.patterns[self.current_pattern]
     }
+
+    /// Completed passes of `pattern` since playback started
+    pub fn loop_count(&self, pattern: usize) -> u32 {
+        self.loop_counts.get(pattern).copied().unwrap_or(0)
+    }
+
+    /// Count a finished pass of `pattern`
+    pub fn count_loop(&mut self, pattern: usize) {
+        if self.loop_counts.len() <= pattern {
+            self.loop_counts.resize(pattern + 1, 0);
+        }
+        self.loop_counts[pattern] += 1;
+    }
+
+    /// Whether `pad`'s last active step played
+    pub fn pad_played(&self, pad: usize) -> bool {
+        self.pads_played.get(pad).copied().unwrap_or(false)
+    }
+
+    pub fn set_pad_played(&mut self, pad: usize, played: bool) {
+        if self.pads_played.len() <= pad {
+            self.pads_played.resize(pad + 1, false);
+        }
+        self.pads_played[pad] = played;
+    }
+
+    /// Forget loop counts and played steps, so conditions start over
+    pub fn reset_trig_state(&mut self) {
+        self.loop_counts.iter_mut().for_each(|c| *c = 0);
+        self.pads_played.iter_mut().for_each(|p| *p = false);
+    }
 }
 
 impl Default for DrumSequencerState {
@@ -338,6 +616,81 @@ mod tests {
         assert!(seq.pattern().steps[0][0].active);
     }
 
+    #[test]
+    fn step_locks_replace_and_clear() {
+        let mut step = DrumStep::default();
+        step.set_lock(ParameterTarget::FilterCutoff, 800.0);
+        step.set_lock(ParameterTarget::Decay, 0.2);
+        step.set_lock(ParameterTarget::FilterCutoff, 1200.0);
+        assert_eq!(step.locks.len(), 2);
+        assert_eq!(step.lock(&ParameterTarget::FilterCutoff), Some(1200.0));
+
+        assert!(step.clear_lock(&ParameterTarget::FilterCutoff));
+        assert!(!step.clear_lock(&ParameterTarget::FilterCutoff));
+        assert_eq!(step.lock(&ParameterTarget::FilterCutoff), None);
+    }
+
+    #[test]
+    fn lock_nudge_stays_in_range() {
+        let cutoff = ParamLock::nudge(&ParameterTarget::FilterCutoff, 1000.0, 6);
+        assert!((cutoff - 2000.0).abs() < 0.01);
+        assert!((ParamLock::nudge(&ParameterTarget::Level, 0.5, 2) - 0.6).abs() < 1e-6);
+        assert_eq!(ParamLock::nudge(&ParameterTarget::Level, 0.95, 5), 1.0);
+        assert_eq!(ParamLock::nudge(&ParameterTarget::Pan, -0.9, -5), -1.0);
+    }
+
+    #[test]
+    fn lock_targets_offer_a_send_per_bus() {
+        let targets = ParamLock::targets(&DrumPad::default(), [BusId::new(1), BusId::new(3)]);
+        let sends: Vec<_> = targets
+            .iter()
+            .filter(|t| matches!(t, ParameterTarget::SendLevel(_)))
+            .collect();
+        assert_eq!(
+            sends,
+            vec![
+                &ParameterTarget::SendLevel(BusId::new(1)),
+                &ParameterTarget::SendLevel(BusId::new(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn envelope_locks_only_on_instrument_trigger_pads() {
+        let sample_pad = DrumPad::default();
+        let trigger_pad = DrumPad {
+            instrument_id: Some(InstrumentId::new(2)),
+            ..DrumPad::default()
+        };
+        let sample_targets = ParamLock::targets(&sample_pad, []);
+        let trigger_targets = ParamLock::targets(&trigger_pad, []);
+        assert!(!sample_targets.contains(&ParameterTarget::Attack));
+        assert!(sample_targets.contains(&ParameterTarget::SampleStart));
+        assert!(trigger_targets.contains(&ParameterTarget::Release));
+        assert!(!trigger_targets.contains(&ParameterTarget::SampleRate));
+        assert!(trigger_targets.contains(&ParameterTarget::SampleAmp));
+    }
+
+    #[test]
+    fn loop_condition_plays_on_its_pass() {
+        let cond = StepCondition::Loop { n: 2, m: 3 };
+        let plays: Vec<bool> = (0..6).map(|l| cond.is_met(l, false, false)).collect();
+        assert_eq!(plays, vec![false, true, false, false, true, false]);
+        assert!(StepCondition::Previous.is_met(0, true, false));
+        assert!(!StepCondition::Fill.is_met(0, true, false));
+        assert!(StepCondition::NotFill.is_met(0, true, false));
+    }
+
+    #[test]
+    fn condition_cycle_wraps() {
+        assert_eq!(
+            StepCondition::Always.cycle(1),
+            StepCondition::Loop { n: 1, m: 2 }
+        );
+        assert_eq!(StepCondition::Always.cycle(-1), StepCondition::NotFill);
+        assert_eq!(StepCondition::NotFill.cycle(1), StepCondition::Always);
+    }
+
     #[test]
     fn euclidean_zero_pulses() {
         let result = euclidean_rhythm(0, 8, 0);
@@ -0,0 +1,258 @@
+//! Reusable effect chains.
+//!
+//! A chain preset holds an instrument's processing chain or a bus/group
+//! effect chain, plus the VST plugins it references so it can be inserted
+//! into projects that have never seen those plugins. Effect ids are not
+//! meaningful inside a preset; the receiving chain allocates new ones.
+
+use serde::{Deserialize, Serialize};
+
+use super::instrument::{EffectSlot, EffectType, ProcessingStage};
+use super::instrument_preset::PRESET_FORMAT_VERSION;
+use super::vst::{VstPlugin, VstPluginRegistry};
+use crate::{BusId, InstrumentId};
+
+fn default_format_version() -> u32 {
+    PRESET_FORMAT_VERSION
+}
+
+/// Where a chain preset is captured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EffectChainOwner {
+    Instrument(InstrumentId),
+    Bus(BusId),
+    LayerGroup(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectChainPreset {
+    #[serde(default = "default_format_version")]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub stages: Vec<ProcessingStage>,
+    /// Plugins referenced by VST effects in `stages`, keyed by their id at save time
+    #[serde(default)]
+    pub vst_plugins: Vec<VstPlugin>,
+}
+
+impl EffectChainPreset {
+    /// Capture an instrument processing chain. VST effects whose plugin is
+    /// missing from the registry are left out.
+    pub fn from_stages(
+        name: &str,
+        tags: Vec<String>,
+        stages: &[ProcessingStage],
+        registry: &VstPluginRegistry,
+    ) -> Self {
+        let mut vst_plugins: Vec<VstPlugin> = Vec::new();
+        let mut stages: Vec<ProcessingStage> = stages
+            .iter()
+            .filter(|stage| match stage {
+                ProcessingStage::Effect(EffectSlot {
+                    effect_type: EffectType::Vst(plugin_id),
+                    ..
+                }) => match registry.get(*plugin_id) {
+                    Some(plugin) => {
+                        if !vst_plugins.iter().any(|p| p.id == plugin.id) {
+                            vst_plugins.push(plugin.clone());
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        super::sidechain::detach_sidechains(&mut stages);
+        Self {
+            version: PRESET_FORMAT_VERSION,
+            name: name.to_string(),
+            tags,
+            stages,
+            vst_plugins,
+        }
+    }
+
+    /// Capture a bus or layer group effect chain.
+    pub fn from_effects(
+        name: &str,
+        tags: Vec<String>,
+        effects: &[EffectSlot],
+        registry: &VstPluginRegistry,
+    ) -> Self {
+        let stages: Vec<ProcessingStage> = effects
+            .iter()
+            .cloned()
+            .map(ProcessingStage::Effect)
+            .collect();
+        Self::from_stages(name, tags, &stages, registry)
+    }
+
+    /// Number of effect stages (the part that can go on a bus or group)
+    pub fn effect_count(&self) -> usize {
+        self.stages.iter().filter(|s| s.is_effect()).count()
+    }
+
+    /// Whether the chain has filter or EQ stages, which only instruments can host
+    pub fn has_instrument_stages(&self) -> bool {
+        self.stages.iter().any(|s| !s.is_effect())
+    }
+
+    /// Stages with VST effects pointed at plugins in `registry`. Plugins the
+    /// registry doesn't know yet (matched by bundle path) are registered.
+    /// Effect ids are left as saved; the receiving chain reassigns them.
+    pub fn resolve_stages(&self, registry: &mut VstPluginRegistry) -> Vec<ProcessingStage> {
+        self.stages
+            .iter()
+            .filter_map(|stage| match stage {
+                ProcessingStage::Effect(slot) => match slot.effect_type {
+                    EffectType::Vst(saved_id) => {
+                        let plugin = self.vst_plugins.iter().find(|p| p.id == saved_id)?;
+                        let id = match registry
+                            .plugins
+                            .iter()
+                            .find(|p| p.plugin_path == plugin.plugin_path)
+                        {
+                            Some(existing) => existing.id,
+                            None => registry.add(plugin.clone()),
+                        };
+                        let mut slot = slot.clone();
+                        slot.effect_type = EffectType::Vst(id);
+                        Some(ProcessingStage::Effect(slot))
+                    }
+                    _ => Some(stage.clone()),
+                },
+                _ => Some(stage.clone()),
+            })
+            .collect()
+    }
+
+    /// Effect slots resolved against `registry`, for bus and group chains
+    pub fn resolve_effects(&self, registry: &mut VstPluginRegistry) -> Vec<EffectSlot> {
+        self.resolve_stages(registry)
+            .into_iter()
+            .filter_map(|stage| match stage {
+                ProcessingStage::Effect(slot) => Some(slot),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Case-insensitive match against the name and tags
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name.to_lowercase().contains(&query)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EffectChain, EffectId, Instrument, SourceType, VstPluginId, VstPluginKind};
+    use std::path::PathBuf;
+
+    fn plugin(name: &str) -> VstPlugin {
+        VstPlugin {
+            id: VstPluginId::new(0),
+            name: name.to_string(),
+            plugin_path: PathBuf::from(format!("/plugins/{}.vst3", name)),
+            kind: VstPluginKind::Effect,
+            params: Vec::new(),
+            latency_secs: None,
+        }
+    }
+
+    #[test]
+    fn vocal_chain_round_trips_into_bus_with_fresh_ids() {
+        let mut inst = Instrument::new(InstrumentId::new(1), SourceType::AudioIn);
+        inst.add_effect(EffectType::ParaEq);
+        inst.add_effect(EffectType::TapeComp);
+        inst.add_effect(EffectType::SpringReverb);
+        let preset = EffectChainPreset::from_stages(
+            "Vocal",
+            vec!["vocal".into()],
+            &inst.processing_chain,
+            &VstPluginRegistry::new(),
+        );
+        assert_eq!(preset.effect_count(), 3);
+
+        let mut chain = EffectChain::default();
+        chain.add_effect(EffectType::Delay);
+        let mut registry = VstPluginRegistry::new();
+        chain.append_effects(preset.resolve_effects(&mut registry));
+
+        let ids: Vec<EffectId> = chain.effects.iter().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            (0..4).map(EffectId::new).collect::<Vec<_>>(),
+            "appended effects get new ids after the existing ones"
+        );
+        assert_eq!(chain.effects[3].effect_type, EffectType::SpringReverb);
+        assert_eq!(chain.next_effect_id, EffectId::new(4));
+    }
+
+    #[test]
+    fn vst_plugins_are_registered_or_reused_by_path() {
+        let mut source_registry = VstPluginRegistry::new();
+        source_registry.add(plugin("Padding"));
+        let comp_id = source_registry.add(plugin("Comp"));
+        let mut slot = EffectSlot::new(EffectId::new(0), EffectType::Vst(comp_id));
+        slot.vst_state_path = Some(PathBuf::from("/presets/vocal/fx_0.fxp"));
+        let missing = EffectSlot::new(EffectId::new(1), EffectType::Vst(VstPluginId::new(9)));
+        let preset =
+            EffectChainPreset::from_effects("Comp", Vec::new(), &[slot, missing], &source_registry);
+        assert_eq!(
+            preset.stages.len(),
+            1,
+            "effects with unknown plugins are dropped"
+        );
+        assert_eq!(preset.vst_plugins.len(), 1);
+
+        // A project that has never seen the plugin registers it
+        let mut registry = VstPluginRegistry::new();
+        let effects = preset.resolve_effects(&mut registry);
+        assert_eq!(registry.len(), 1);
+        let new_id = registry.plugins[0].id;
+        assert_eq!(effects[0].effect_type, EffectType::Vst(new_id));
+        assert_eq!(
+            effects[0].vst_state_path.as_deref(),
+            Some(std::path::Path::new("/presets/vocal/fx_0.fxp"))
+        );
+
+        // Inserting again reuses the registered plugin
+        preset.resolve_effects(&mut registry);
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn instrument_append_keeps_filters_and_allocates_ids() {
+        let mut source = Instrument::new(InstrumentId::new(1), SourceType::Saw);
+        source.set_filter(Some(crate::FilterType::Lpf));
+        source.add_effect(EffectType::Delay);
+        let preset = EffectChainPreset::from_stages(
+            "Filtered delay",
+            Vec::new(),
+            &source.processing_chain,
+            &VstPluginRegistry::new(),
+        );
+        assert!(preset.has_instrument_stages());
+
+        let mut target = Instrument::new(InstrumentId::new(2), SourceType::Saw);
+        target.add_effect(EffectType::Reverb);
+        target.add_effect(EffectType::Chorus);
+        let before = target.processing_chain.len();
+        target.append_stages(preset.resolve_stages(&mut VstPluginRegistry::new()));
+        assert_eq!(target.processing_chain.len(), before + 2);
+        let ids: Vec<EffectId> = target.effects().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            vec![EffectId::new(0), EffectId::new(1), EffectId::new(2)]
+        );
+        assert_eq!(target.next_effect_id, EffectId::new(3));
+    }
+}
@@ -0,0 +1,227 @@
+//! Export format options (file format, bit depth, sample rate, loudness).
+
+use serde::{Deserialize, Serialize};
+
+/// Integrated loudness that normalization aims for
+pub const LOUDNESS_TARGET_LUFS: f32 = -14.0;
+
+/// True-peak ceiling that normalization never exceeds
+pub const TRUE_PEAK_CEILING_DBTP: f32 = -1.0;
+
+/// Output sample rates offered for export; `None` keeps the capture rate.
+pub const EXPORT_SAMPLE_RATES: [Option<u32>; 5] =
+    [None, Some(44100), Some(48000), Some(88200), Some(96000)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Wav,
+    Flac,
+}
+
+impl ExportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "WAV",
+            ExportFormat::Flac => "FLAC",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Flac => "flac",
+        }
+    }
+
+    pub fn next(&self) -> ExportFormat {
+        match self {
+            ExportFormat::Wav => ExportFormat::Flac,
+            ExportFormat::Flac => ExportFormat::Wav,
+        }
+    }
+
+    /// FLAC stores integer samples only
+    pub fn supports(&self, bit_depth: ExportBitDepth) -> bool {
+        !matches!(
+            (self, bit_depth),
+            (ExportFormat::Flac, ExportBitDepth::Float32)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExportBitDepth {
+    Int16,
+    Int24,
+    #[default]
+    Float32,
+}
+
+impl ExportBitDepth {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportBitDepth::Int16 => "16-bit",
+            ExportBitDepth::Int24 => "24-bit",
+            ExportBitDepth::Float32 => "32-bit float",
+        }
+    }
+
+    /// Bits per sample in the output file
+    pub fn bits(&self) -> u16 {
+        match self {
+            ExportBitDepth::Int16 => 16,
+            ExportBitDepth::Int24 => 24,
+            ExportBitDepth::Float32 => 32,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, ExportBitDepth::Float32)
+    }
+
+    pub fn next(&self) -> ExportBitDepth {
+        match self {
+            ExportBitDepth::Int16 => ExportBitDepth::Int24,
+            ExportBitDepth::Int24 => ExportBitDepth::Float32,
+            ExportBitDepth::Float32 => ExportBitDepth::Int16,
+        }
+    }
+
+    pub fn prev(&self) -> ExportBitDepth {
+        match self {
+            ExportBitDepth::Int16 => ExportBitDepth::Float32,
+            ExportBitDepth::Int24 => ExportBitDepth::Int16,
+            ExportBitDepth::Float32 => ExportBitDepth::Int24,
+        }
+    }
+}
+
+/// How exported audio is written once the float capture has finished.
+/// The defaults reproduce the raw capture: 32-bit float WAV at the
+/// server's sample rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub bit_depth: ExportBitDepth,
+    /// Output sample rate; `None` keeps the capture rate
+    pub sample_rate: Option<u32>,
+    /// Normalize to `LOUDNESS_TARGET_LUFS`, limited by `TRUE_PEAK_CEILING_DBTP`
+    pub normalize: bool,
+}
+
+impl ExportOptions {
+    /// True when the capture already is the requested file and no
+    /// post-processing pass is needed
+    pub fn is_raw_capture(&self) -> bool {
+        self.format == ExportFormat::Wav
+            && self.bit_depth.is_float()
+            && self.sample_rate.is_none()
+            && !self.normalize
+    }
+
+    pub fn cycle_format(&mut self) {
+        self.format = self.format.next();
+        if !self.format.supports(self.bit_depth) {
+            self.bit_depth = ExportBitDepth::Int24;
+        }
+    }
+
+    /// Step the bit depth, skipping depths the format cannot store
+    pub fn cycle_bit_depth(&mut self, forward: bool) {
+        let mut depth = self.bit_depth;
+        loop {
+            depth = if forward { depth.next() } else { depth.prev() };
+            if self.format.supports(depth) {
+                break;
+            }
+        }
+        self.bit_depth = depth;
+    }
+
+    pub fn cycle_sample_rate(&mut self, forward: bool) {
+        let count = EXPORT_SAMPLE_RATES.len();
+        let idx = EXPORT_SAMPLE_RATES
+            .iter()
+            .position(|rate| *rate == self.sample_rate)
+            .unwrap_or(0);
+        let idx = if forward {
+            (idx + 1) % count
+        } else {
+            (idx + count - 1) % count
+        };
+        self.sample_rate = EXPORT_SAMPLE_RATES[idx];
+    }
+
+    /// Coerce combinations the writers cannot produce
+    pub fn validated(mut self) -> Self {
+        if !self.format.supports(self.bit_depth) {
+            self.bit_depth = ExportBitDepth::Int24;
+        }
+        self
+    }
+
+    /// One-line description, e.g. "FLAC 24-bit 44100 Hz, -14 LUFS"
+    pub fn summary(&self) -> String {
+        let mut text = format!("{} {}", self.format.name(), self.bit_depth.name());
+        if let Some(rate) = self.sample_rate {
+            text.push_str(&format!(" {} Hz", rate));
+        }
+        if self.normalize {
+            text.push_str(&format!(", {} LUFS", LOUDNESS_TARGET_LUFS));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_options_keep_the_raw_capture() {
+        let options = ExportOptions::default();
+        assert!(options.is_raw_capture());
+        assert_eq!(options.format.extension(), "wav");
+
+        let resampled = ExportOptions {
+            sample_rate: Some(44100),
+            ..options
+        };
+        assert!(!resampled.is_raw_capture());
+    }
+
+    #[test]
+    fn flac_never_holds_float_samples() {
+        let mut options = ExportOptions::default();
+        options.cycle_format();
+        assert_eq!(options.format, ExportFormat::Flac);
+        assert_eq!(options.bit_depth, ExportBitDepth::Int24);
+
+        options.cycle_bit_depth(true);
+        assert_eq!(options.bit_depth, ExportBitDepth::Int16);
+        options.cycle_bit_depth(false);
+        assert_eq!(options.bit_depth, ExportBitDepth::Int24);
+        options.cycle_bit_depth(true);
+        options.cycle_bit_depth(true);
+        assert_eq!(options.bit_depth, ExportBitDepth::Int24);
+
+        let invalid = ExportOptions {
+            format: ExportFormat::Flac,
+            bit_depth: ExportBitDepth::Float32,
+            ..ExportOptions::default()
+        };
+        assert_eq!(invalid.validated().bit_depth, ExportBitDepth::Int24);
+    }
+
+    #[test]
+    fn sample_rate_cycles_through_choices() {
+        let mut options = ExportOptions::default();
+        options.cycle_sample_rate(false);
+        assert_eq!(options.sample_rate, Some(96000));
+        options.cycle_sample_rate(true);
+        assert_eq!(options.sample_rate, None);
+        options.cycle_sample_rate(true);
+        assert_eq!(options.sample_rate, Some(44100));
+    }
+}
@@ -0,0 +1,73 @@
+//! Track freeze: an instrument's rendered output standing in for its live
+//! chain. The instrument keeps its full configuration while frozen, so
+//! unfreezing only drops the render.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::PianoRollState;
+
+/// A frozen instrument's render and where it sits on the timeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrozenRender {
+    /// WAV holding the post-effect, pre-fader output
+    pub path: PathBuf,
+    /// Tick the render started at (the loop start when it was frozen)
+    pub start_tick: u32,
+    /// Tick the render ends at, including the release tail
+    pub end_tick: u32,
+    /// Silence before `start_tick` is heard in the file: the scheduling
+    /// lookahead in effect while rendering
+    pub lead_in_secs: f64,
+    /// Duration of the file
+    pub length_secs: f32,
+}
+
+impl FrozenRender {
+    /// Position in the file that is heard at `tick`, following the tempo map.
+    pub fn file_offset_secs(&self, tick: u32, piano_roll: &PianoRollState) -> f64 {
+        self.lead_in_secs
+            + piano_roll.secs_between(self.start_tick as f64, tick.max(self.start_tick) as f64)
+    }
+
+    /// Whether the render has audio for `tick`.
+    pub fn covers(&self, tick: u32) -> bool {
+        tick >= self.start_tick && tick < self.end_tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render() -> FrozenRender {
+        FrozenRender {
+            path: PathBuf::from("freeze.wav"),
+            start_tick: 960,
+            end_tick: 2880,
+            lead_in_secs: 0.02,
+            length_secs: 4.0,
+        }
+    }
+
+    #[test]
+    fn file_offset_includes_lead_in() {
+        let frozen = render();
+        // 120 BPM at 480 ticks per beat: 960 ticks per second
+        let pr = PianoRollState::new();
+        assert_eq!(frozen.file_offset_secs(960, &pr), 0.02);
+        assert!((frozen.file_offset_secs(1920, &pr) - 1.02).abs() < 1e-9);
+        // Ticks before the render map to its start
+        assert_eq!(frozen.file_offset_secs(0, &pr), 0.02);
+    }
+
+    #[test]
+    fn covers_render_range_only() {
+        let frozen = render();
+        assert!(!frozen.covers(959));
+        assert!(frozen.covers(960));
+        assert!(frozen.covers(2879));
+        assert!(!frozen.covers(2880));
+    }
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{EffectId, Param, ParamValue, VstPluginId};
+use crate::{EffectId, Param, ParamValue, SidechainSource, VstPluginId};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EffectType {
@@ -1189,7 +1189,7 @@ impl EffectType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EffectSlot {
     pub id: EffectId,
     pub effect_type: EffectType,
@@ -1197,6 +1197,9 @@ pub struct EffectSlot {
     pub enabled: bool,
     pub vst_param_values: Vec<(u32, f32)>,
     pub vst_state_path: Option<PathBuf>,
+    /// Key input for effects that take one (see `EffectType::sidechain_control`)
+    #[serde(default)]
+    pub sidechain: Option<SidechainSource>,
 }
 
 impl EffectSlot {
@@ -1208,6 +1211,7 @@ impl EffectSlot {
             enabled: true,
             vst_param_values: Vec::new(),
             vst_state_path: None,
+            sidechain: None,
         }
     }
 }
@@ -1231,6 +1235,15 @@ impl EffectChain {
         id
     }
 
+    /// Append effects from a chain preset, giving each a new EffectId.
+    pub fn append_effects(&mut self, effects: Vec<EffectSlot>) {
+        for mut slot in effects {
+            slot.id = self.next_effect_id;
+            self.next_effect_id = EffectId::new(self.next_effect_id.get() + 1);
+            self.effects.push(slot);
+        }
+    }
+
     /// Find an effect by its stable EffectId.
     pub fn effect_by_id(&self, id: EffectId) -> Option<&EffectSlot> {
         self.effects.iter().find(|e| e.id == id)
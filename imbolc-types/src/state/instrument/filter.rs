@@ -88,7 +88,7 @@ impl FilterType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FilterConfig {
     pub filter_type: FilterType,
     pub cutoff: ModulatedParam,
@@ -142,7 +142,7 @@ impl EqBandType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EqBand {
     pub band_type: EqBandType,
     pub freq: f32,
@@ -153,7 +153,7 @@ pub struct EqBand {
 
 pub const EQ_BAND_COUNT: usize = 12;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EqConfig {
     pub bands: [EqBand; EQ_BAND_COUNT],
     pub enabled: bool,
@@ -59,7 +59,7 @@ impl LfoShape {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LfoConfig {
     pub enabled: bool,
     pub rate: f32,
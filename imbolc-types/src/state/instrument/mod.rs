@@ -2,12 +2,14 @@ mod effect;
 mod envelope;
 mod filter;
 mod lfo;
+mod mod_matrix;
 mod source_type;
 
 pub use effect::*;
 pub use envelope::*;
 pub use filter::*;
 pub use lfo::*;
+pub use mod_matrix::*;
 pub use source_type::*;
 
 use std::collections::BTreeMap;
@@ -17,13 +19,15 @@ use serde::{Deserialize, Serialize};
 
 use super::arpeggiator::{ArpeggiatorConfig, ChordShape};
 use super::drum_sequencer::DrumSequencerState;
+use super::freeze::FrozenRender;
 use super::groove::GrooveConfig;
 use super::sampler::SamplerConfig;
-use crate::{BusId, EffectId, InstrumentId, Param, ParamIndex};
+use crate::{BusId, EffectId, InstrumentId, Param, ParamIndex, ParameterTarget};
 
 /// Source-type-specific configuration, enforcing mutual exclusivity at compile time.
 /// Replaces the old `sampler_config`, `drum_sequencer`, `vst_param_values`, `vst_state_path` fields.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum SourceExtra {
     #[default]
     None,
@@ -35,11 +39,32 @@ pub enum SourceExtra {
     },
 }
 
+impl SourceExtra {
+    /// Empty source-specific config matching a source type
+    pub fn for_source(source: SourceType) -> Self {
+        if source.is_sample() || source.is_time_stretch() {
+            SourceExtra::Sampler(SamplerConfig::default())
+        } else if source.is_kit() {
+            SourceExtra::Kit(DrumSequencerState::new())
+        } else if source.is_vst() {
+            SourceExtra::Vst {
+                param_values: Vec::new(),
+                state_path: None,
+            }
+        } else {
+            SourceExtra::None
+        }
+    }
+}
+
 /// LFO + amp envelope configuration, always co-accessed and co-updated.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModulationConfig {
     pub lfo: LfoConfig,
     pub amp_envelope: EnvConfig,
+    /// Additional LFOs, mod envelopes and controller routes
+    #[serde(default)]
+    pub matrix: ModMatrix,
 }
 
 /// Arpeggiator and chord-shape configuration for an instrument.
@@ -148,7 +173,7 @@ pub enum OutputTarget {
 }
 
 /// Where a send taps the instrument signal chain.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum SendTapPoint {
     /// Before filter and effects
     PreInsert,
@@ -275,7 +300,7 @@ impl LayerGroupMixer {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModulatedParam {
     pub value: f32,
     pub min: f32,
@@ -283,7 +308,7 @@ pub struct ModulatedParam {
     pub mod_source: Option<ModSource>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ModSource {
     Lfo(LfoConfig),
     Envelope(EnvConfig),
@@ -293,7 +318,7 @@ pub enum ModSource {
 /// A single stage in the instrument's processing chain.
 /// Replaces the old separate filter/eq/effects fields with a unified,
 /// user-orderable signal chain.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProcessingStage {
     Filter(FilterConfig),
     Eq(EqConfig),
@@ -361,6 +386,7 @@ pub enum InstrumentSection {
     Processing(usize), // chain index
     Lfo,
     Envelope,
+    ModMatrix,
 }
 
 /// Total number of selectable rows for instrument editing.
@@ -372,6 +398,7 @@ pub fn instrument_row_count(
     source: SourceType,
     source_params: &[Param],
     processing_chain: &[ProcessingStage],
+    mod_matrix: &ModMatrix,
 ) -> usize {
     let sample_row = if source.is_sample() || source.is_time_stretch() {
         1
@@ -386,7 +413,7 @@ pub fn instrument_row_count(
     };
     let lfo_rows = 4;
     let env_rows = if source.is_vst() { 0 } else { 4 };
-    source_rows + processing_rows + lfo_rows + env_rows
+    source_rows + processing_rows + lfo_rows + env_rows + mod_matrix.row_count()
 }
 
 /// Which section a given row belongs to.
@@ -446,7 +473,13 @@ pub fn instrument_row_info(
     }
     offset += lfo_rows;
 
-    (InstrumentSection::Envelope, row - offset)
+    let env_rows = if source.is_vst() { 0 } else { 4 };
+    if row < offset + env_rows {
+        return (InstrumentSection::Envelope, row - offset);
+    }
+    offset += env_rows;
+
+    (InstrumentSection::ModMatrix, row - offset)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -474,22 +507,14 @@ pub struct Instrument {
     pub next_effect_id: EffectId,
     /// Per-track groove settings (swing, humanization, timing offset)
     pub groove: GrooveConfig,
+    /// Render playing in place of the live chain while the track is frozen
+    #[serde(default)]
+    pub frozen: Option<FrozenRender>,
 }
 
 impl Instrument {
     pub fn new(id: InstrumentId, source: SourceType) -> Self {
-        let source_extra = if source.is_sample() || source.is_time_stretch() {
-            SourceExtra::Sampler(SamplerConfig::default())
-        } else if source.is_kit() {
-            SourceExtra::Kit(DrumSequencerState::new())
-        } else if source.is_vst() {
-            SourceExtra::Vst {
-                param_values: Vec::new(),
-                state_path: None,
-            }
-        } else {
-            SourceExtra::None
-        };
+        let source_extra = SourceExtra::for_source(source);
         Self {
             id,
             name: format!("{}-{}", source.short_name(), id),
@@ -499,6 +524,7 @@ impl Instrument {
             modulation: ModulationConfig {
                 lfo: LfoConfig::default(),
                 amp_envelope: source.default_envelope(),
+                matrix: ModMatrix::default(),
             },
             polyphonic: true,
             mixer: InstrumentMixer::new(!source.is_audio_input()),
@@ -508,9 +534,15 @@ impl Instrument {
             layer: LayerConfig::default(),
             next_effect_id: EffectId::new(0),
             groove: GrooveConfig::default(),
+            frozen: None,
         }
     }
 
+    /// Whether the instrument plays its frozen render instead of voices.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
     // --- Processing chain read accessors ---
 
     /// Get the first filter in the processing chain.
@@ -597,6 +629,30 @@ impl Instrument {
         self.effects_mut().find(|e| e.id == id)
     }
 
+    /// Current setting of a target on this instrument, for targets with a
+    /// stored value
+    pub fn param_value(&self, target: &ParameterTarget) -> Option<f32> {
+        let env = &self.modulation.amp_envelope;
+        match target {
+            ParameterTarget::Level => Some(self.mixer.level),
+            ParameterTarget::Pan => Some(self.mixer.pan),
+            ParameterTarget::SendLevel(bus_id) => {
+                Some(self.mixer.sends.get(bus_id).map_or(0.0, |s| s.level))
+            }
+            ParameterTarget::FilterCutoff => self.filter().map(|f| f.cutoff.value),
+            ParameterTarget::FilterResonance => self.filter().map(|f| f.resonance.value),
+            ParameterTarget::EffectParam(effect_id, param_idx) => self
+                .effect_by_id(*effect_id)
+                .and_then(|e| e.params.get(param_idx.get()))
+                .map(|p| p.value.to_f32()),
+            ParameterTarget::Attack => Some(env.attack),
+            ParameterTarget::Decay => Some(env.decay),
+            ParameterTarget::Sustain => Some(env.sustain),
+            ParameterTarget::Release => Some(env.release),
+            _ => None,
+        }
+    }
+
     /// Get the position of an effect among effects only (not chain index).
     pub fn effect_position(&self, id: EffectId) -> Option<usize> {
         self.effects().position(|e| e.id == id)
@@ -680,6 +736,22 @@ impl Instrument {
         id
     }
 
+    /// Append stages from a chain preset, giving each effect a new EffectId.
+    /// An EQ stage is skipped when the chain already has one (single instance).
+    pub fn append_stages(&mut self, stages: Vec<ProcessingStage>) {
+        for stage in stages {
+            match stage {
+                ProcessingStage::Effect(mut slot) => {
+                    slot.id = self.next_effect_id;
+                    self.next_effect_id = EffectId::new(self.next_effect_id.get() + 1);
+                    self.processing_chain.push(ProcessingStage::Effect(slot));
+                }
+                ProcessingStage::Eq(_) if self.eq_chain_index().is_some() => {}
+                stage => self.processing_chain.push(stage),
+            }
+        }
+    }
+
     /// Remove an effect by its EffectId. Returns true if removed.
     pub fn remove_effect(&mut self, id: EffectId) -> bool {
         if let Some(idx) = self.effect_chain_index(id) {
@@ -730,7 +802,12 @@ impl Instrument {
 
     /// Total number of selectable rows for instrument editing.
     pub fn total_editable_rows(&self) -> usize {
-        instrument_row_count(self.source, &self.source_params, &self.processing_chain)
+        instrument_row_count(
+            self.source,
+            &self.source_params,
+            &self.processing_chain,
+            &self.modulation.matrix,
+        )
     }
 
     /// Which section a given row belongs to.
@@ -876,8 +953,9 @@ mod tests {
     fn row_count_basic() {
         let inst = Instrument::new(InstrumentId::new(1), SourceType::Saw);
         let count = inst.total_editable_rows();
-        // Saw: no sample row, default params + processing(empty=1) + lfo(4) + env(4)
-        let expected = inst.source_params.len().max(1) + 1 + 4 + 4;
+        // Saw: no sample row, default params + processing(empty=1) + lfo(4) + env(4) + matrix
+        let expected =
+            inst.source_params.len().max(1) + 1 + 4 + 4 + inst.modulation.matrix.row_count();
         assert_eq!(count, expected);
     }
 
@@ -907,18 +985,21 @@ mod tests {
         let mut has_processing = false;
         let mut has_lfo = false;
         let mut has_envelope = false;
+        let mut has_mod_matrix = false;
         for i in 0..total {
             match inst.section_for_row(i) {
                 InstrumentSection::Source => has_source = true,
                 InstrumentSection::Processing(_) => has_processing = true,
                 InstrumentSection::Lfo => has_lfo = true,
                 InstrumentSection::Envelope => has_envelope = true,
+                InstrumentSection::ModMatrix => has_mod_matrix = true,
             }
         }
         assert!(has_source);
         assert!(has_processing);
         assert!(has_lfo);
         assert!(has_envelope);
+        assert!(has_mod_matrix);
     }
 
     #[test]
@@ -930,6 +1011,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mod_matrix_rows_follow_envelope() {
+        let mut inst = Instrument::new(InstrumentId::new(1), SourceType::Saw);
+        let first = inst.source_params.len().max(1) + 1 + 4 + 4;
+        assert_eq!(inst.row_info(first), (InstrumentSection::ModMatrix, 0));
+        let last = inst.total_editable_rows() - 1;
+        assert_eq!(
+            inst.row_info(last),
+            (
+                InstrumentSection::ModMatrix,
+                inst.modulation.matrix.row_count() - 1
+            )
+        );
+
+        let before = inst.total_editable_rows();
+        inst.modulation.matrix.add_route();
+        inst.modulation.matrix.add_route();
+        assert_eq!(inst.total_editable_rows(), before + 1); // placeholder replaced
+    }
+
     #[test]
     fn decode_effect_cursor_empty() {
         let inst = Instrument::new(InstrumentId::new(1), SourceType::Saw);
@@ -964,8 +1065,9 @@ mod tests {
     fn timestretch_has_sample_row_in_count() {
         let inst = Instrument::new(InstrumentId::new(1), SourceType::TimeStretch);
         let count = inst.total_editable_rows();
-        // TimeStretch: 1 sample row + params + processing(empty=1) + lfo(4) + env(4)
-        let expected = 1 + inst.source_params.len().max(1) + 1 + 4 + 4;
+        // TimeStretch: 1 sample row + params + processing(empty=1) + lfo(4) + env(4) + matrix
+        let expected =
+            1 + inst.source_params.len().max(1) + 1 + 4 + 4 + inst.modulation.matrix.row_count();
         assert_eq!(count, expected);
     }
 
@@ -1419,18 +1521,21 @@ mod tests {
         let mut has_processing = false;
         let mut has_lfo = false;
         let mut has_envelope = false;
+        let mut has_mod_matrix = false;
         for i in 0..total {
             match inst.section_for_row(i) {
                 InstrumentSection::Source => has_source = true,
                 InstrumentSection::Processing(_) => has_processing = true,
                 InstrumentSection::Lfo => has_lfo = true,
                 InstrumentSection::Envelope => has_envelope = true,
+                InstrumentSection::ModMatrix => has_mod_matrix = true,
             }
         }
         assert!(has_source);
         assert!(has_processing);
         assert!(has_lfo);
         assert!(has_envelope);
+        assert!(has_mod_matrix);
     }
 
     // --- Index query tests ---
@@ -0,0 +1,375 @@
+use serde::{Deserialize, Serialize};
+
+use super::{EnvConfig, LfoShape};
+use crate::ParameterTarget;
+
+/// Number of free-running LFOs available to the mod matrix.
+pub const MOD_MATRIX_LFOS: usize = 3;
+/// Number of per-voice mod envelopes available to the mod matrix.
+pub const MOD_MATRIX_ENVELOPES: usize = 2;
+/// Maximum number of routes per instrument.
+pub const MAX_MOD_ROUTES: usize = 16;
+
+/// A modulation source that can feed mod matrix routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModMatrixSource {
+    /// Free-running matrix LFO (index into `ModMatrix::lfos`), bipolar -1..1
+    Lfo(u8),
+    /// Per-voice mod envelope (index into `ModMatrix::envelopes`), 0..1
+    Envelope(u8),
+    /// Note velocity, 0..1
+    Velocity,
+    /// Channel aftertouch, 0..1
+    Aftertouch,
+    /// Mod wheel (CC 1), 0..1
+    ModWheel,
+}
+
+impl ModMatrixSource {
+    /// All sources in cycling order.
+    pub fn all() -> Vec<ModMatrixSource> {
+        let mut sources: Vec<ModMatrixSource> = (0..MOD_MATRIX_LFOS as u8)
+            .map(ModMatrixSource::Lfo)
+            .collect();
+        sources.extend((0..MOD_MATRIX_ENVELOPES as u8).map(ModMatrixSource::Envelope));
+        sources.extend([
+            ModMatrixSource::Velocity,
+            ModMatrixSource::Aftertouch,
+            ModMatrixSource::ModWheel,
+        ]);
+        sources
+    }
+
+    pub fn next(&self) -> ModMatrixSource {
+        let all = Self::all();
+        let idx = all.iter().position(|s| s == self).unwrap_or(0);
+        all[(idx + 1) % all.len()]
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ModMatrixSource::Lfo(i) => format!("LFO {}", i + 1),
+            ModMatrixSource::Envelope(i) => format!("Env {}", i + 1),
+            ModMatrixSource::Velocity => "Velocity".to_string(),
+            ModMatrixSource::Aftertouch => "Aftertouch".to_string(),
+            ModMatrixSource::ModWheel => "Mod Wheel".to_string(),
+        }
+    }
+
+    /// Stable identifier used for persistence.
+    pub fn short_name(&self) -> String {
+        match self {
+            ModMatrixSource::Lfo(i) => format!("lfo{}", i),
+            ModMatrixSource::Envelope(i) => format!("env{}", i),
+            ModMatrixSource::Velocity => "vel".to_string(),
+            ModMatrixSource::Aftertouch => "aftertouch".to_string(),
+            ModMatrixSource::ModWheel => "modwheel".to_string(),
+        }
+    }
+
+    pub fn from_short_name(name: &str) -> Option<ModMatrixSource> {
+        match name {
+            "vel" => Some(ModMatrixSource::Velocity),
+            "aftertouch" => Some(ModMatrixSource::Aftertouch),
+            "modwheel" => Some(ModMatrixSource::ModWheel),
+            _ => {
+                if let Some(i) = name.strip_prefix("lfo") {
+                    i.parse().ok().map(ModMatrixSource::Lfo)
+                } else if let Some(i) = name.strip_prefix("env") {
+                    i.parse().ok().map(ModMatrixSource::Envelope)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Whether the source has a separate value per voice (envelopes, velocity).
+    /// Chain-level targets follow the most recently triggered voice.
+    pub fn is_per_note(&self) -> bool {
+        matches!(
+            self,
+            ModMatrixSource::Envelope(_) | ModMatrixSource::Velocity
+        )
+    }
+}
+
+/// Live MIDI controllers that feed the mod matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModController {
+    Aftertouch,
+    ModWheel,
+}
+
+impl ModController {
+    pub fn source(&self) -> ModMatrixSource {
+        match self {
+            ModController::Aftertouch => ModMatrixSource::Aftertouch,
+            ModController::ModWheel => ModMatrixSource::ModWheel,
+        }
+    }
+}
+
+/// A free-running mod matrix LFO. Output is bipolar at unit depth; routes scale it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModMatrixLfo {
+    pub rate: f32,
+    pub shape: LfoShape,
+}
+
+impl Default for ModMatrixLfo {
+    fn default() -> Self {
+        Self {
+            rate: 2.0,
+            shape: LfoShape::Sine,
+        }
+    }
+}
+
+/// One source → target connection with a signed depth (-1.0..=1.0).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModRoute {
+    pub source: ModMatrixSource,
+    pub target: ParameterTarget,
+    pub depth: f32,
+}
+
+impl ModRoute {
+    pub fn adjust_depth(&mut self, delta: f32) {
+        self.depth = (self.depth + delta).clamp(-1.0, 1.0);
+    }
+}
+
+impl Default for ModRoute {
+    fn default() -> Self {
+        Self {
+            source: ModMatrixSource::Lfo(0),
+            target: ParameterTarget::FilterCutoff,
+            depth: 0.5,
+        }
+    }
+}
+
+/// What a row in the mod matrix editor section edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModMatrixRow {
+    LfoRate(usize),
+    LfoShape(usize),
+    /// Envelope index and ADSR stage (0=attack .. 3=release)
+    Envelope(usize, usize),
+    Route(usize),
+    /// Placeholder shown when there are no routes
+    NoRoutes,
+}
+
+/// Per-instrument modulation matrix: sources plus routes into parameter targets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModMatrix {
+    pub lfos: Vec<ModMatrixLfo>,
+    pub envelopes: Vec<EnvConfig>,
+    pub routes: Vec<ModRoute>,
+}
+
+impl Default for ModMatrix {
+    fn default() -> Self {
+        Self {
+            lfos: vec![ModMatrixLfo::default(); MOD_MATRIX_LFOS],
+            envelopes: vec![
+                EnvConfig {
+                    decay: 0.3,
+                    ..EnvConfig::default()
+                };
+                MOD_MATRIX_ENVELOPES
+            ],
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl ModMatrix {
+    /// Append a default route. Returns its index, or None when the matrix is full.
+    pub fn add_route(&mut self) -> Option<usize> {
+        if self.routes.len() >= MAX_MOD_ROUTES {
+            return None;
+        }
+        self.routes.push(ModRoute::default());
+        Some(self.routes.len() - 1)
+    }
+
+    pub fn remove_route(&mut self, index: usize) {
+        if index < self.routes.len() {
+            self.routes.remove(index);
+        }
+    }
+
+    /// Routes feeding a given target.
+    pub fn routes_to(&self, target: ParameterTarget) -> impl Iterator<Item = &ModRoute> {
+        self.routes.iter().filter(move |r| r.target == target)
+    }
+
+    pub fn has_routes_to(&self, target: ParameterTarget) -> bool {
+        self.routes_to(target).next().is_some()
+    }
+
+    pub fn uses_source(&self, source: ModMatrixSource) -> bool {
+        self.routes.iter().any(|r| r.source == source)
+    }
+
+    /// Distinct targets with at least one route, in first-route order.
+    pub fn routed_targets(&self) -> Vec<ParameterTarget> {
+        let mut targets = Vec::new();
+        for route in &self.routes {
+            if !targets.contains(&route.target) {
+                targets.push(route.target);
+            }
+        }
+        targets
+    }
+
+    /// Decode a local row index within the mod matrix section.
+    pub fn row(&self, local_idx: usize) -> ModMatrixRow {
+        let lfo_rows = self.lfos.len() * 2;
+        let env_rows = self.envelopes.len() * 4;
+        if local_idx < lfo_rows {
+            if local_idx.is_multiple_of(2) {
+                ModMatrixRow::LfoRate(local_idx / 2)
+            } else {
+                ModMatrixRow::LfoShape(local_idx / 2)
+            }
+        } else if local_idx < lfo_rows + env_rows {
+            let idx = local_idx - lfo_rows;
+            ModMatrixRow::Envelope(idx / 4, idx % 4)
+        } else {
+            let idx = local_idx - lfo_rows - env_rows;
+            if idx < self.routes.len() {
+                ModMatrixRow::Route(idx)
+            } else {
+                ModMatrixRow::NoRoutes
+            }
+        }
+    }
+
+    /// Local row index of a route (inverse of `row` for routes).
+    pub fn route_row(&self, route_idx: usize) -> usize {
+        self.lfos.len() * 2 + self.envelopes.len() * 4 + route_idx
+    }
+
+    /// Number of editor rows: LFO rate/shape pairs, envelope ADSR and routes
+    /// (one placeholder row when there are no routes).
+    pub fn row_count(&self) -> usize {
+        self.lfos.len() * 2 + self.envelopes.len() * 4 + self.routes.len().max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BusId;
+
+    #[test]
+    fn default_matrix_has_sources_but_no_routes() {
+        let matrix = ModMatrix::default();
+        assert_eq!(matrix.lfos.len(), MOD_MATRIX_LFOS);
+        assert_eq!(matrix.envelopes.len(), MOD_MATRIX_ENVELOPES);
+        assert!(matrix.routes.is_empty());
+        assert_eq!(
+            matrix.row_count(),
+            MOD_MATRIX_LFOS * 2 + MOD_MATRIX_ENVELOPES * 4 + 1
+        );
+    }
+
+    #[test]
+    fn add_route_is_capped() {
+        let mut matrix = ModMatrix::default();
+        for i in 0..MAX_MOD_ROUTES {
+            assert_eq!(matrix.add_route(), Some(i));
+        }
+        assert_eq!(matrix.add_route(), None);
+        matrix.remove_route(0);
+        assert_eq!(matrix.routes.len(), MAX_MOD_ROUTES - 1);
+        matrix.remove_route(100);
+        assert_eq!(matrix.routes.len(), MAX_MOD_ROUTES - 1);
+    }
+
+    #[test]
+    fn routed_targets_are_distinct() {
+        let mut matrix = ModMatrix::default();
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::Lfo(0),
+            target: ParameterTarget::Pitch,
+            depth: 0.1,
+        });
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::Envelope(1),
+            target: ParameterTarget::FilterCutoff,
+            depth: 0.8,
+        });
+        matrix.routes.push(ModRoute {
+            source: ModMatrixSource::ModWheel,
+            target: ParameterTarget::Pitch,
+            depth: -0.2,
+        });
+        assert_eq!(
+            matrix.routed_targets(),
+            vec![ParameterTarget::Pitch, ParameterTarget::FilterCutoff]
+        );
+        assert_eq!(matrix.routes_to(ParameterTarget::Pitch).count(), 2);
+        assert!(matrix.uses_source(ModMatrixSource::Envelope(1)));
+        assert!(!matrix.uses_source(ModMatrixSource::Envelope(0)));
+        assert!(!matrix.has_routes_to(ParameterTarget::SendLevel(BusId::new(1))));
+    }
+
+    #[test]
+    fn source_short_name_round_trip() {
+        for source in ModMatrixSource::all() {
+            assert_eq!(
+                ModMatrixSource::from_short_name(&source.short_name()),
+                Some(source)
+            );
+        }
+        assert_eq!(ModMatrixSource::from_short_name("bogus"), None);
+    }
+
+    #[test]
+    fn source_next_cycles_through_all() {
+        let all = ModMatrixSource::all();
+        let mut source = all[0];
+        for expected in all.iter().skip(1) {
+            source = source.next();
+            assert_eq!(source, *expected);
+        }
+        assert_eq!(source.next(), all[0]);
+    }
+
+    #[test]
+    fn rows_decode_sources_then_routes() {
+        let mut matrix = ModMatrix::default();
+        assert_eq!(matrix.row(0), ModMatrixRow::LfoRate(0));
+        assert_eq!(matrix.row(5), ModMatrixRow::LfoShape(2));
+        assert_eq!(matrix.row(6), ModMatrixRow::Envelope(0, 0));
+        assert_eq!(matrix.row(13), ModMatrixRow::Envelope(1, 3));
+        assert_eq!(matrix.row(14), ModMatrixRow::NoRoutes);
+        matrix.add_route();
+        matrix.add_route();
+        assert_eq!(matrix.row(15), ModMatrixRow::Route(1));
+        assert_eq!(matrix.row(matrix.route_row(1)), ModMatrixRow::Route(1));
+        assert_eq!(matrix.row_count(), 16);
+    }
+
+    #[test]
+    fn route_depth_clamps() {
+        let mut route = ModRoute::default();
+        route.adjust_depth(2.0);
+        assert_eq!(route.depth, 1.0);
+        route.adjust_depth(-3.0);
+        assert_eq!(route.depth, -1.0);
+    }
+
+    #[test]
+    fn per_note_sources() {
+        assert!(ModMatrixSource::Envelope(0).is_per_note());
+        assert!(ModMatrixSource::Velocity.is_per_note());
+        assert!(!ModMatrixSource::Lfo(0).is_per_note());
+        assert!(!ModMatrixSource::ModWheel.is_per_note());
+    }
+}
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use super::envelope::EnvConfig;
 use crate::{CustomSynthDefId, Param, ParamValue, VstPluginId};
 
+/// Highest MIDI output port index a MIDI Out source can address
+const MAX_MIDI_OUT_PORT: u8 = 15;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SourceType {
     // Basic Oscillators
@@ -74,6 +77,12 @@ pub enum SourceType {
     // External
     Custom(CustomSynthDefId),
     Vst(VstPluginId),
+    /// External MIDI device: notes and controllers go to an output port
+    /// (index into the available MIDI output ports) on a zero-based channel.
+    MidiOut {
+        port: u8,
+        channel: u8,
+    },
 }
 
 impl SourceType {
@@ -136,6 +145,63 @@ impl SourceType {
             SourceType::Kit => "Kit",
             SourceType::Custom(_) => "Custom",
             SourceType::Vst(_) => "VST",
+            SourceType::MidiOut { .. } => "MIDI Out",
+        }
+    }
+
+    /// Family a source belongs to, as grouped in the Add Instrument menu
+    pub fn category(&self) -> &'static str {
+        match self {
+            SourceType::Saw
+            | SourceType::Sin
+            | SourceType::Sqr
+            | SourceType::Tri
+            | SourceType::Noise
+            | SourceType::Pulse
+            | SourceType::SuperSaw
+            | SourceType::Sync => "oscillator",
+            SourceType::Ring
+            | SourceType::FBSin
+            | SourceType::FM
+            | SourceType::PhaseMod
+            | SourceType::FMBell
+            | SourceType::FMBrass => "modulation",
+            SourceType::Pluck
+            | SourceType::Formant
+            | SourceType::Bowed
+            | SourceType::Blown
+            | SourceType::Membrane => "physical",
+            SourceType::Marimba
+            | SourceType::Vibes
+            | SourceType::Kalimba
+            | SourceType::SteelDrum
+            | SourceType::TubularBell
+            | SourceType::Glockenspiel => "mallet",
+            SourceType::Guitar | SourceType::BassGuitar | SourceType::Harp | SourceType::Koto => {
+                "plucked"
+            }
+            SourceType::Kick
+            | SourceType::Snare
+            | SourceType::HihatClosed
+            | SourceType::HihatOpen
+            | SourceType::Clap
+            | SourceType::Cowbell
+            | SourceType::Rim
+            | SourceType::Tom
+            | SourceType::Clave
+            | SourceType::Conga => "drums",
+            SourceType::Choir
+            | SourceType::EPiano
+            | SourceType::Organ
+            | SourceType::BrassStab
+            | SourceType::Strings
+            | SourceType::Acid => "classic",
+            SourceType::Gendy | SourceType::Chaos => "experimental",
+            SourceType::Additive | SourceType::Wavetable | SourceType::Granular => "synthesis",
+            SourceType::AudioIn | SourceType::BusIn | SourceType::MidiOut { .. } => "routing",
+            SourceType::PitchedSampler | SourceType::TimeStretch | SourceType::Kit => "sampler",
+            SourceType::Custom(_) => "custom",
+            SourceType::Vst(_) => "vst",
         }
     }
 
@@ -198,6 +264,7 @@ impl SourceType {
             SourceType::Kit => "kit",
             SourceType::Custom(_) => "custom",
             SourceType::Vst(_) => "vst",
+            SourceType::MidiOut { .. } => "midi_out",
         }
     }
 
@@ -261,6 +328,7 @@ impl SourceType {
             SourceType::Kit => "imbolc_sampler_oneshot",
             SourceType::Custom(_) => "imbolc_saw", // Fallback, use synth_def_name_with_registry instead
             SourceType::Vst(_) => "imbolc_vst_instrument",
+            SourceType::MidiOut { .. } => "imbolc_midi_out", // No synth; notes go to a MIDI port
         }
     }
 
@@ -336,6 +404,39 @@ impl SourceType {
         }
     }
 
+    pub fn is_midi_out(&self) -> bool {
+        matches!(self, SourceType::MidiOut { .. })
+    }
+
+    /// MIDI output port and zero-based channel for external MIDI instruments
+    pub fn midi_out(&self) -> Option<(u8, u8)> {
+        match self {
+            SourceType::MidiOut { port, channel } => Some((*port, *channel)),
+            _ => None,
+        }
+    }
+
+    /// Re-derive a source from edited params. MIDI Out keeps its port and
+    /// channel in the variant; the `port` / `channel` params (channel is
+    /// 1-based for display) are the editable mirror of those fields.
+    pub fn with_params(self, params: &[Param]) -> SourceType {
+        match self {
+            SourceType::MidiOut { port, channel } => {
+                let get = |name: &str| {
+                    params
+                        .iter()
+                        .find(|p| p.name == name)
+                        .map(|p| p.value.to_f32() as i32)
+                };
+                SourceType::MidiOut {
+                    port: get("port").map_or(port, |v| v.clamp(0, MAX_MIDI_OUT_PORT as i32) as u8),
+                    channel: get("channel").map_or(channel, |v| (v.clamp(1, 16) - 1) as u8),
+                }
+            }
+            other => other,
+        }
+    }
+
     /// Built-in source types (excluding custom)
     pub fn all() -> Vec<SourceType> {
         vec![
@@ -961,6 +1062,20 @@ impl SourceType {
             SourceType::Kit => vec![],
             SourceType::Custom(_) => vec![],
             SourceType::Vst(_) => vec![],
+            SourceType::MidiOut { port, channel } => vec![
+                Param {
+                    name: "port".to_string(),
+                    value: ParamValue::Int(*port as i32),
+                    min: 0.0,
+                    max: MAX_MIDI_OUT_PORT as f32,
+                },
+                Param {
+                    name: "channel".to_string(),
+                    value: ParamValue::Int(*channel as i32 + 1),
+                    min: 1.0,
+                    max: 16.0,
+                },
+            ],
             _ => vec![
                 Param {
                     name: "freq".to_string(),
@@ -1098,7 +1213,7 @@ impl SourceType {
             },
 
             // External: generic default
-            SourceType::Custom(_) | SourceType::Vst(_) => EnvConfig {
+            SourceType::Custom(_) | SourceType::Vst(_) | SourceType::MidiOut { .. } => EnvConfig {
                 attack: 0.01,
                 decay: 0.1,
                 sustain: 0.7,
@@ -1281,6 +1396,23 @@ mod tests {
         assert_eq!(SourceType::Saw.vst_id(), None);
     }
 
+    #[test]
+    fn midi_out_port_and_channel_follow_params() {
+        let source = SourceType::MidiOut {
+            port: 1,
+            channel: 0,
+        };
+        let mut params = source.default_params();
+        assert_eq!(params[1].value, ParamValue::Int(1));
+
+        params[0].value = ParamValue::Int(2);
+        params[1].value = ParamValue::Int(10);
+        assert_eq!(source.with_params(&params).midi_out(), Some((2, 9)));
+        params[0].value = ParamValue::Int(100);
+        assert_eq!(source.with_params(&params).midi_out(), Some((15, 9)));
+        assert_eq!(SourceType::Saw.with_params(&params), SourceType::Saw);
+    }
+
     #[test]
     fn source_type_default_params_non_empty() {
         // Basic oscillators should have params
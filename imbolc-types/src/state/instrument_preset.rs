@@ -0,0 +1,331 @@
+//! Reusable instrument patches.
+//!
+//! A preset captures everything that defines how an instrument sounds
+//! (source and its params, processing chain, LFO/envelope, note input and
+//! groove) but none of its project wiring (name, mixer, layer, samples).
+
+use serde::{Deserialize, Serialize};
+
+use super::groove::GrooveConfig;
+use super::instrument::{
+    EffectType, EnvConfig, FilterType, Instrument, ModulationConfig, NoteInputConfig,
+    ProcessingStage, SourceType,
+};
+use crate::{InstrumentUpdate, Param, ParamValue};
+
+/// Version written into preset files
+pub const PRESET_FORMAT_VERSION: u32 = 1;
+
+fn default_format_version() -> u32 {
+    PRESET_FORMAT_VERSION
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstrumentPreset {
+    #[serde(default = "default_format_version")]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub source: SourceType,
+    pub source_params: Vec<Param>,
+    #[serde(default)]
+    pub processing_chain: Vec<ProcessingStage>,
+    pub modulation: ModulationConfig,
+    pub polyphonic: bool,
+    #[serde(default)]
+    pub note_input: NoteInputConfig,
+    #[serde(default)]
+    pub groove: GrooveConfig,
+}
+
+impl InstrumentPreset {
+    /// Capture an instrument's patch. Custom synthdef and VST sources refer
+    /// to project-local registries and can't be stored.
+    pub fn from_instrument(
+        instrument: &Instrument,
+        name: &str,
+        tags: Vec<String>,
+    ) -> Result<Self, String> {
+        if instrument.source.is_custom() || instrument.source.is_vst() {
+            return Err(format!(
+                "{} sources can't be saved as presets",
+                instrument.source.name()
+            ));
+        }
+        let mut processing_chain = instrument.processing_chain.clone();
+        super::sidechain::detach_sidechains(&mut processing_chain);
+        Ok(Self {
+            version: PRESET_FORMAT_VERSION,
+            name: name.to_string(),
+            tags,
+            source: instrument.source,
+            source_params: instrument.source_params.clone(),
+            processing_chain,
+            modulation: instrument.modulation.clone(),
+            polyphonic: instrument.polyphonic,
+            note_input: instrument.note_input.clone(),
+            groove: instrument.groove,
+        })
+    }
+
+    /// Factory presets shipped alongside the user library
+    pub fn factory_presets() -> Vec<Self> {
+        use FilterType::*;
+        vec![
+            Self::factory("Hoover Lead", &["lead"], SourceType::SuperSaw, |i| {
+                set_param(i, "detune", 0.6);
+                set_param(i, "mix", 0.8);
+                set_envelope(i, 0.01, 0.3, 0.7, 0.4);
+                set_filter(i, Lpf, 4000.0, 0.3);
+                i.add_effect(EffectType::Chorus);
+                i.add_effect(EffectType::Reverb);
+            }),
+            Self::factory("Acid Squelch", &["bass"], SourceType::Acid, |i| {
+                i.polyphonic = false;
+                set_envelope(i, 0.001, 0.2, 0.3, 0.1);
+                set_filter(i, Lpf, 800.0, 0.8);
+                i.modulation.lfo.enabled = true;
+                i.modulation.lfo.rate = 0.5;
+                i.modulation.lfo.depth = 0.4;
+                i.add_effect(EffectType::Distortion);
+                i.add_effect(EffectType::Delay);
+            }),
+            Self::factory("Sub Bass", &["bass"], SourceType::Sin, |i| {
+                i.polyphonic = false;
+                set_param(i, "amp", 0.7);
+                set_envelope(i, 0.005, 0.2, 0.8, 0.15);
+                set_filter(i, Lpf, 300.0, 0.1);
+                i.add_effect(EffectType::Saturator);
+            }),
+            Self::factory("Square Pluck", &["pluck"], SourceType::Sqr, |i| {
+                set_envelope(i, 0.001, 0.25, 0.0, 0.2);
+                set_filter(i, Lpf, 2500.0, 0.4);
+                i.add_effect(EffectType::Delay);
+            }),
+            Self::factory("Nylon Pluck", &["pluck"], SourceType::Pluck, |i| {
+                set_param(i, "decay", 3.5);
+                set_param(i, "coef", 0.5);
+                i.add_effect(EffectType::Reverb);
+            }),
+            Self::factory("Glass Bell", &["keys", "bell"], SourceType::FM, |i| {
+                set_param(i, "ratio", 3.5);
+                set_param(i, "index", 4.0);
+                set_envelope(i, 0.001, 1.5, 0.0, 1.5);
+                i.add_effect(EffectType::Reverb);
+            }),
+            Self::factory("Drawbar Organ", &["keys"], SourceType::Organ, |i| {
+                set_envelope(i, 0.005, 0.1, 1.0, 0.08);
+                i.add_effect(EffectType::Leslie);
+            }),
+            Self::factory("Tine Piano", &["keys"], SourceType::EPiano, |i| {
+                set_envelope(i, 0.001, 1.8, 0.3, 0.6);
+                i.add_effect(EffectType::Tremolo);
+                i.add_effect(EffectType::Chorus);
+            }),
+            Self::factory("Warm Strings", &["pad"], SourceType::Strings, |i| {
+                set_envelope(i, 0.8, 1.0, 0.8, 2.0);
+                set_filter(i, Lpf, 2000.0, 0.2);
+                i.add_effect(EffectType::Chorus);
+                i.add_effect(EffectType::Reverb);
+            }),
+            Self::factory("Bowed Cello", &["strings"], SourceType::Bowed, |i| {
+                set_param(i, "pressure", 0.7);
+                set_param(i, "bow_pos", 0.2);
+                set_envelope(i, 0.15, 0.4, 0.9, 0.5);
+            }),
+            Self::factory("Metal Ring", &["fx"], SourceType::Ring, |i| {
+                set_param(i, "mod_ratio", 3.7);
+                set_param(i, "mod_depth", 0.8);
+                set_envelope(i, 0.01, 0.6, 0.2, 0.8);
+                i.add_effect(EffectType::Phaser);
+            }),
+            Self::factory("Grain Cloud", &["texture"], SourceType::Granular, |i| {
+                set_param(i, "grain_size", 0.2);
+                set_param(i, "density", 40.0);
+                set_param(i, "spread", 0.8);
+                set_param(i, "pitch_rnd", 0.3);
+                set_envelope(i, 1.2, 1.0, 0.8, 3.0);
+                i.add_effect(EffectType::Reverb);
+            }),
+            Self::factory("Punch Kick", &["drums"], SourceType::Kick, |i| {
+                set_param(i, "amp", 0.8);
+                set_envelope(i, 0.001, 0.45, 0.0, 0.05);
+                i.add_effect(EffectType::Saturator);
+            }),
+            Self::factory("Crushed Snare", &["drums"], SourceType::Snare, |i| {
+                set_envelope(i, 0.001, 0.2, 0.0, 0.08);
+                i.add_effect(EffectType::Bitcrusher);
+            }),
+            Self::factory("Tight Hat", &["drums"], SourceType::HihatClosed, |i| {
+                set_envelope(i, 0.001, 0.08, 0.0, 0.03);
+                set_filter(i, Hpf, 6000.0, 0.2);
+            }),
+        ]
+    }
+
+    /// Factory preset built from a source's default patch
+    fn factory(
+        name: &str,
+        tags: &[&str],
+        source: SourceType,
+        configure: impl FnOnce(&mut Instrument),
+    ) -> Self {
+        let mut instrument = Instrument::new(crate::InstrumentId::new(0), source);
+        configure(&mut instrument);
+        Self {
+            version: PRESET_FORMAT_VERSION,
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            source,
+            source_params: instrument.source_params,
+            processing_chain: instrument.processing_chain,
+            modulation: instrument.modulation,
+            polyphonic: instrument.polyphonic,
+            note_input: instrument.note_input,
+            groove: instrument.groove,
+        }
+    }
+
+    /// Remove VST effects from the chain (their plugin ids are project-local).
+    /// Returns how many were removed.
+    pub fn strip_vst_effects(&mut self) -> usize {
+        let before = self.processing_chain.len();
+        self.processing_chain.retain(|stage| match stage {
+            ProcessingStage::Effect(e) => !matches!(e.effect_type, EffectType::Vst(_)),
+            _ => true,
+        });
+        before - self.processing_chain.len()
+    }
+
+    /// Build the update that applies this preset to an instrument. The
+    /// instrument's active flag is kept.
+    pub fn to_update(&self, instrument: &Instrument) -> InstrumentUpdate {
+        InstrumentUpdate {
+            id: instrument.id,
+            source: self.source,
+            source_params: self.source_params.clone(),
+            processing_chain: self.processing_chain.clone(),
+            lfo: self.modulation.lfo.clone(),
+            amp_envelope: self.modulation.amp_envelope.clone(),
+            polyphonic: self.polyphonic,
+            active: instrument.mixer.active,
+            note_input: Some(self.note_input.clone()),
+            groove: Some(self.groove),
+            mod_matrix: Some(self.modulation.matrix.clone()),
+        }
+    }
+
+    /// Whether the preset passes a source filter and a case-insensitive
+    /// query matched against its name and tags
+    pub fn matches(&self, source: Option<SourceType>, query: &str) -> bool {
+        if source.is_some_and(|s| s != self.source) {
+            return false;
+        }
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name.to_lowercase().contains(&query)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&query))
+    }
+}
+
+/// Set a source param by name, keeping its type and range
+fn set_param(instrument: &mut Instrument, name: &str, value: f32) {
+    if let Some(p) = instrument.source_params.iter_mut().find(|p| p.name == name) {
+        let value = value.clamp(p.min, p.max);
+        p.value = match p.value {
+            ParamValue::Float(_) => ParamValue::Float(value),
+            ParamValue::Int(_) => ParamValue::Int(value.round() as i32),
+            ParamValue::Bool(_) => ParamValue::Bool(value >= 0.5),
+        };
+    }
+}
+
+fn set_envelope(instrument: &mut Instrument, attack: f32, decay: f32, sustain: f32, release: f32) {
+    instrument.modulation.amp_envelope = EnvConfig {
+        attack,
+        decay,
+        sustain,
+        release,
+    };
+}
+
+fn set_filter(instrument: &mut Instrument, filter_type: FilterType, cutoff: f32, resonance: f32) {
+    instrument.set_filter(Some(filter_type));
+    if let Some(filter) = instrument.filter_mut() {
+        filter.cutoff.value = cutoff;
+        filter.resonance.value = resonance;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InstrumentId, VstPluginId};
+
+    #[test]
+    fn factory_presets_differ_from_default_patches() {
+        let presets = InstrumentPreset::factory_presets();
+        for preset in &presets {
+            let default = Instrument::new(InstrumentId::new(0), preset.source);
+            assert!(
+                preset.source_params != default.source_params
+                    || preset.modulation.amp_envelope != default.modulation.amp_envelope
+                    || !preset.processing_chain.is_empty(),
+                "{} is the default patch",
+                preset.name
+            );
+            assert_eq!(presets.iter().filter(|p| p.name == preset.name).count(), 1);
+        }
+        let kick = presets.iter().find(|p| p.name == "Punch Kick").unwrap();
+        assert_eq!(kick.tags, vec!["drums".to_string()]);
+        assert_eq!(kick.modulation.amp_envelope.decay, 0.45);
+        assert_eq!(kick.source_params[1].value, ParamValue::Float(0.8));
+    }
+
+    #[test]
+    fn custom_and_vst_sources_are_rejected() {
+        let vst = Instrument::new(InstrumentId::new(1), SourceType::Vst(VstPluginId::new(0)));
+        assert!(InstrumentPreset::from_instrument(&vst, "x", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn update_keeps_target_identity() {
+        let mut source = Instrument::new(InstrumentId::new(1), SourceType::Acid);
+        source.polyphonic = false;
+        source.groove.timing_offset_ms = 12.0;
+        let preset =
+            InstrumentPreset::from_instrument(&source, "Squelch", vec!["bass".into()]).unwrap();
+
+        let mut target = Instrument::new(InstrumentId::new(7), SourceType::Saw);
+        target.mixer.active = false;
+        let update = preset.to_update(&target);
+        assert_eq!(update.id, InstrumentId::new(7));
+        assert_eq!(update.source, SourceType::Acid);
+        assert!(!update.polyphonic);
+        assert!(!update.active);
+        assert_eq!(update.groove.unwrap().timing_offset_ms, 12.0);
+    }
+
+    #[test]
+    fn matches_source_and_tags() {
+        let organ = Instrument::new(InstrumentId::new(1), SourceType::Organ);
+        let preset =
+            InstrumentPreset::from_instrument(&organ, "Organ", vec!["Warm".to_string()]).unwrap();
+        assert!(preset.matches(None, ""));
+        assert!(preset.matches(Some(SourceType::Organ), "warm"));
+        assert!(preset.matches(None, "ORG"));
+        assert!(!preset.matches(Some(SourceType::Saw), ""));
+        assert!(!preset.matches(None, "bright"));
+    }
+
+    #[test]
+    fn strip_vst_effects_leaves_builtin_effects() {
+        let mut inst = Instrument::new(InstrumentId::new(1), SourceType::Saw);
+        inst.add_effect(EffectType::Delay);
+        inst.add_effect(EffectType::Vst(VstPluginId::new(3)));
+        let mut preset = InstrumentPreset::from_instrument(&inst, "p", Vec::new()).unwrap();
+        assert_eq!(preset.strip_vst_effects(), 1);
+        assert_eq!(preset.processing_chain.len(), 1);
+    }
+}
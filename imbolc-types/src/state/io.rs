@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::{IoGeneration, PendingExport, PendingRender};
+use super::{ExportOptions, IoGeneration, PendingExport, PendingRender};
 
 /// I/O state for render and export operations.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -13,6 +13,9 @@ pub struct IoState {
     /// Pending export operation (master bounce or stem export)
     #[serde(skip)]
     pub pending_export: Option<PendingExport>,
+    /// Format options used by the next master bounce or stem export
+    #[serde(skip)]
+    pub export_options: ExportOptions,
     /// Export progress (0.0 to 1.0)
     #[serde(skip)]
     pub export_progress: f32,
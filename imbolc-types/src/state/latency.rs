@@ -0,0 +1,422 @@
+//! Plugin delay compensation (PDC): processing latency per stage and the
+//! compensation delays that line every signal path up at the master bus.
+
+use std::collections::BTreeMap;
+
+use super::instrument::{EffectSlot, EffectType, Instrument, OutputTarget, ProcessingStage};
+use super::instrument::{MixerSend, SendTapPoint, SourceType};
+use super::instrument_state::InstrumentState;
+use super::session::SessionState;
+use super::vst::VstPluginRegistry;
+use crate::{BusId, InstrumentId, ParamValue};
+
+/// Rate at which the frame-based latencies of built-in effects are expressed.
+const REFERENCE_SAMPLE_RATE: f32 = 48_000.0;
+
+/// FFT frame size of the spectral built-ins (Denoise, SpectralFreeze, ConvolutionReverb)
+const SPECTRAL_FRAMES: f32 = 2048.0;
+
+/// Lookahead of the safety limiter inside Denoise (`Limiter.ar(wet, 0.95, 0.01)`)
+const DENOISE_LIMITER_DUR: f32 = 0.01;
+
+/// Grain window of the Autotune pitch shifter (`PitchShift.ar(mono, 0.2, ...)`)
+const AUTOTUNE_WINDOW_SECS: f32 = 0.2;
+
+/// Longest compensation delay the output and send SynthDefs can apply.
+pub const MAX_COMPENSATION_SECS: f32 = 1.0;
+
+fn float_param(slot: &EffectSlot, name: &str, default: f32) -> f32 {
+    slot.params
+        .iter()
+        .find(|p| p.name == name)
+        .map(|p| match p.value {
+            ParamValue::Float(v) => v,
+            _ => p.value.to_f32(),
+        })
+        .unwrap_or(default)
+}
+
+impl EffectSlot {
+    /// Processing latency of this effect in seconds.
+    ///
+    /// Built-ins come from a fixed table of the lookahead/FFT delays in their
+    /// SynthDefs; VST effects use the latency the plugin reported when opened.
+    /// Bypassed effects are not in the signal path and add nothing.
+    pub fn latency_secs(&self, vst_plugins: &VstPluginRegistry) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        match self.effect_type {
+            // Limiter delays its output by twice its buffer duration
+            EffectType::Limiter => 2.0 * float_param(self, "release", 0.01),
+            EffectType::Denoise => {
+                SPECTRAL_FRAMES / REFERENCE_SAMPLE_RATE + 2.0 * DENOISE_LIMITER_DUR
+            }
+            EffectType::SpectralFreeze | EffectType::ConvolutionReverb => {
+                SPECTRAL_FRAMES / REFERENCE_SAMPLE_RATE
+            }
+            // Grains are read on average half a window behind the input
+            EffectType::Autotune => AUTOTUNE_WINDOW_SECS / 2.0,
+            EffectType::Vst(id) => vst_plugins
+                .get(id)
+                .and_then(|p| p.latency_secs)
+                .unwrap_or(0.0),
+            _ => 0.0,
+        }
+    }
+}
+
+impl ProcessingStage {
+    /// Processing latency of this stage in seconds. Filters and EQ are zero-latency.
+    pub fn latency_secs(&self, vst_plugins: &VstPluginRegistry) -> f32 {
+        match self {
+            ProcessingStage::Filter(_) | ProcessingStage::Eq(_) => 0.0,
+            ProcessingStage::Effect(e) => e.latency_secs(vst_plugins),
+        }
+    }
+}
+
+impl Instrument {
+    /// Latency from note-on to the end of the processing chain: the VSTi source
+    /// (if any) plus every stage in the chain. Frozen instruments play a render
+    /// that was already lined up when it was frozen, so they add none.
+    pub fn processing_latency_secs(&self, vst_plugins: &VstPluginRegistry) -> f32 {
+        if self.is_frozen() {
+            return 0.0;
+        }
+        let source = match self.source {
+            SourceType::Vst(id) => vst_plugins
+                .get(id)
+                .and_then(|p| p.latency_secs)
+                .unwrap_or(0.0),
+            _ => 0.0,
+        };
+        source
+            + self
+                .processing_chain
+                .iter()
+                .map(|s| s.latency_secs(vst_plugins))
+                .sum::<f32>()
+    }
+}
+
+/// Total latency of a bus or layer-group effect chain in seconds.
+pub fn effect_chain_latency_secs(effects: &[EffectSlot], vst_plugins: &VstPluginRegistry) -> f32 {
+    effects.iter().map(|e| e.latency_secs(vst_plugins)).sum()
+}
+
+/// Latency and compensation for one mixer channel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelLatency {
+    /// Latency added by the channel's own processing
+    pub processing_secs: f32,
+    /// Compensation delay applied at the channel's main output
+    pub output_delay_secs: f32,
+    /// Compensation delay applied to each active send
+    pub send_delay_secs: BTreeMap<BusId, f32>,
+}
+
+/// Compensation delays for the whole routing graph.
+///
+/// Every path from an instrument to the master bus — direct, through its layer
+/// group, into a bus via `OutputTarget`, or through a send — is delayed up to the
+/// worst path in the project, so everything arrives at master together.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyPlan {
+    pub instruments: BTreeMap<InstrumentId, ChannelLatency>,
+    pub layer_groups: BTreeMap<u32, ChannelLatency>,
+    pub buses: BTreeMap<BusId, ChannelLatency>,
+    /// Worst path latency from any instrument to the master bus
+    pub total_secs: f32,
+}
+
+fn send_is_active(send: &MixerSend) -> bool {
+    send.enabled && send.level > 0.0
+}
+
+fn compensation(arrival: f32, path: f32) -> f32 {
+    (arrival - path).clamp(0.0, MAX_COMPENSATION_SECS)
+}
+
+impl LatencyPlan {
+    pub fn compute(instruments: &InstrumentState, session: &SessionState) -> Self {
+        let vst_plugins = &session.vst_plugins;
+        let mixer = &session.mixer;
+        let mut plan = LatencyPlan::default();
+
+        // Buses feed master directly, so their chain is their whole downstream latency
+        for bus in &mixer.buses {
+            plan.buses.insert(
+                bus.id,
+                ChannelLatency {
+                    processing_secs: effect_chain_latency_secs(
+                        &bus.effect_chain.effects,
+                        vst_plugins,
+                    ),
+                    ..Default::default()
+                },
+            );
+        }
+        let bus_latency =
+            |plan: &LatencyPlan, id: BusId| plan.buses.get(&id).map_or(0.0, |c| c.processing_secs);
+        let target_latency = |plan: &LatencyPlan, target: OutputTarget| match target {
+            OutputTarget::Master => 0.0,
+            OutputTarget::Bus(id) => bus_latency(plan, id),
+        };
+
+        // Layer groups: sends tap the group bus before its effects, so they are
+        // delayed locally to line up with the group's main output.
+        let mut group_arrival: BTreeMap<u32, f32> = BTreeMap::new();
+        let active_groups = instruments.active_layer_groups();
+        for gm in &mixer.layer_group_mixers {
+            if !active_groups.contains(&gm.group_id) {
+                continue;
+            }
+            let processing = effect_chain_latency_secs(&gm.effect_chain.effects, vst_plugins);
+            let main = processing + target_latency(&plan, gm.output_target);
+            let sends: Vec<(BusId, f32)> = gm
+                .sends
+                .values()
+                .filter(|s| send_is_active(s))
+                .map(|s| (s.bus_id, bus_latency(&plan, s.bus_id)))
+                .collect();
+            let arrival = sends.iter().map(|(_, l)| *l).fold(main, f32::max);
+            group_arrival.insert(gm.group_id, arrival);
+            plan.layer_groups.insert(
+                gm.group_id,
+                ChannelLatency {
+                    processing_secs: processing,
+                    output_delay_secs: compensation(arrival, main),
+                    send_delay_secs: sends
+                        .into_iter()
+                        .map(|(id, l)| (id, compensation(arrival, l)))
+                        .collect(),
+                },
+            );
+        }
+
+        // Instruments: record the latency of every path to master, then turn each
+        // into the delay that brings it up to the worst one
+        for inst in &instruments.instruments {
+            let processing = inst.processing_latency_secs(vst_plugins);
+            let downstream = match inst.layer.group {
+                Some(group_id) => group_arrival.get(&group_id).copied().unwrap_or(0.0),
+                None => target_latency(&plan, inst.mixer.output_target),
+            };
+            let sends: BTreeMap<BusId, f32> = inst
+                .mixer
+                .sends
+                .values()
+                .filter(|s| send_is_active(s))
+                .map(|s| {
+                    let tap = match s.tap_point {
+                        SendTapPoint::PreInsert => 0.0,
+                        SendTapPoint::PostInsert => processing,
+                    };
+                    (s.bus_id, tap + bus_latency(&plan, s.bus_id))
+                })
+                .collect();
+            let main = processing + downstream;
+            plan.total_secs = sends
+                .values()
+                .copied()
+                .fold(plan.total_secs.max(main), f32::max);
+            plan.instruments.insert(
+                inst.id,
+                ChannelLatency {
+                    processing_secs: processing,
+                    output_delay_secs: main,
+                    send_delay_secs: sends,
+                },
+            );
+        }
+
+        let total = plan.total_secs;
+        for channel in plan.instruments.values_mut() {
+            channel.output_delay_secs = compensation(total, channel.output_delay_secs);
+            for delay in channel.send_delay_secs.values_mut() {
+                *delay = compensation(total, *delay);
+            }
+        }
+
+        plan
+    }
+
+    pub fn instrument(&self, id: InstrumentId) -> Option<&ChannelLatency> {
+        self.instruments.get(&id)
+    }
+
+    pub fn layer_group(&self, group_id: u32) -> Option<&ChannelLatency> {
+        self.layer_groups.get(&group_id)
+    }
+
+    pub fn bus(&self, id: BusId) -> Option<&ChannelLatency> {
+        self.buses.get(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::mixer::DEFAULT_BUS_COUNT;
+    use crate::state::session::MusicalSettings;
+    use crate::{LayerGroupMixer, VstPlugin, VstPluginKind};
+
+    fn setup() -> (InstrumentState, SessionState) {
+        (
+            InstrumentState::new(),
+            SessionState::new_with_defaults(MusicalSettings::default(), DEFAULT_BUS_COUNT),
+        )
+    }
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn no_latency_means_no_compensation() {
+        let (mut instruments, session) = setup();
+        let a = instruments.add_instrument(SourceType::Saw);
+        let b = instruments.add_instrument(SourceType::Saw);
+        instruments
+            .instrument_mut(b)
+            .unwrap()
+            .add_effect(EffectType::Delay);
+        let plan = LatencyPlan::compute(&instruments, &session);
+        assert_eq!(plan.total_secs, 0.0);
+        assert_eq!(plan.instrument(a).unwrap().output_delay_secs, 0.0);
+        assert_eq!(plan.instrument(b).unwrap().output_delay_secs, 0.0);
+    }
+
+    #[test]
+    fn limiter_delays_other_instruments() {
+        let (mut instruments, session) = setup();
+        let drums = instruments.add_instrument(SourceType::Saw);
+        let lead = instruments.add_instrument(SourceType::Saw);
+        instruments
+            .instrument_mut(lead)
+            .unwrap()
+            .add_effect(EffectType::Limiter);
+        let plan = LatencyPlan::compute(&instruments, &session);
+
+        assert!(approx(plan.total_secs, 0.02));
+        assert!(approx(plan.instrument(lead).unwrap().processing_secs, 0.02));
+        assert_eq!(plan.instrument(lead).unwrap().output_delay_secs, 0.0);
+        assert!(approx(
+            plan.instrument(drums).unwrap().output_delay_secs,
+            0.02
+        ));
+    }
+
+    #[test]
+    fn bypassed_effect_adds_no_latency() {
+        let (mut instruments, session) = setup();
+        let id = instruments.add_instrument(SourceType::Saw);
+        let inst = instruments.instrument_mut(id).unwrap();
+        let fx = inst.add_effect(EffectType::Denoise);
+        inst.effect_by_id_mut(fx).unwrap().enabled = false;
+        let plan = LatencyPlan::compute(&instruments, &session);
+        assert_eq!(plan.total_secs, 0.0);
+    }
+
+    #[test]
+    fn bus_chain_counts_toward_output_and_send_paths() {
+        let (mut instruments, mut session) = setup();
+        session.mixer.buses[0]
+            .effect_chain
+            .add_effect(EffectType::Limiter);
+        let bus_id = session.mixer.buses[0].id;
+
+        let routed = instruments.add_instrument(SourceType::Saw);
+        instruments
+            .instrument_mut(routed)
+            .unwrap()
+            .mixer
+            .output_target = OutputTarget::Bus(bus_id);
+        let sender = instruments.add_instrument(SourceType::Saw);
+        {
+            let inst = instruments.instrument_mut(sender).unwrap();
+            let mut send = MixerSend::new(bus_id);
+            send.enabled = true;
+            send.level = 0.5;
+            inst.mixer.sends.insert(bus_id, send);
+        }
+        let plan = LatencyPlan::compute(&instruments, &session);
+
+        assert!(approx(plan.total_secs, 0.02));
+        assert_eq!(plan.instrument(routed).unwrap().output_delay_secs, 0.0);
+        let sender_lat = plan.instrument(sender).unwrap();
+        assert!(approx(sender_lat.output_delay_secs, 0.02));
+        assert_eq!(sender_lat.send_delay_secs.get(&bus_id), Some(&0.0));
+    }
+
+    #[test]
+    fn group_sends_align_with_group_output() {
+        let (mut instruments, mut session) = setup();
+        let id = instruments.add_instrument(SourceType::Saw);
+        instruments.instrument_mut(id).unwrap().layer.group = Some(0);
+        let bus_id = session.mixer.buses[0].id;
+        let mut gm = LayerGroupMixer::new(0, &[]);
+        gm.effect_chain.add_effect(EffectType::Limiter);
+        let mut send = MixerSend::new(bus_id);
+        send.enabled = true;
+        send.level = 1.0;
+        gm.sends.insert(bus_id, send);
+        session.mixer.layer_group_mixers.push(gm);
+
+        let plan = LatencyPlan::compute(&instruments, &session);
+        let group = plan.layer_group(0).unwrap();
+        assert_eq!(group.output_delay_secs, 0.0);
+        assert!(approx(group.send_delay_secs[&bus_id], 0.02));
+        assert!(approx(plan.total_secs, 0.02));
+    }
+
+    #[test]
+    fn vst_latency_comes_from_registry() {
+        let (mut instruments, mut session) = setup();
+        let plugin_id = session.vst_plugins.add(VstPlugin {
+            id: crate::VstPluginId::new(0),
+            name: "Lookahead".to_string(),
+            plugin_path: "/tmp/la.vst3".into(),
+            kind: VstPluginKind::Effect,
+            params: vec![],
+            latency_secs: None,
+        });
+        let id = instruments.add_instrument(SourceType::Saw);
+        instruments
+            .instrument_mut(id)
+            .unwrap()
+            .add_effect(EffectType::Vst(plugin_id));
+        assert_eq!(LatencyPlan::compute(&instruments, &session).total_secs, 0.0);
+
+        session.vst_plugins.get_mut(plugin_id).unwrap().latency_secs = Some(0.05);
+        assert!(approx(
+            LatencyPlan::compute(&instruments, &session).total_secs,
+            0.05
+        ));
+    }
+
+    #[test]
+    fn compensation_is_capped() {
+        let (mut instruments, session) = setup();
+        let dry = instruments.add_instrument(SourceType::Saw);
+        let wet = instruments.add_instrument(SourceType::Saw);
+        for _ in 0..3 {
+            let inst = instruments.instrument_mut(wet).unwrap();
+            let fx = inst.add_effect(EffectType::Limiter);
+            if let Some(slot) = inst.effect_by_id_mut(fx) {
+                for p in &mut slot.params {
+                    if p.name == "release" {
+                        p.value = ParamValue::Float(1.0);
+                    }
+                }
+            }
+        }
+        let plan = LatencyPlan::compute(&instruments, &session);
+        assert!(approx(plan.total_secs, 6.0));
+        assert_eq!(
+            plan.instrument(dry).unwrap().output_delay_secs,
+            MAX_COMPENSATION_SECS
+        );
+    }
+}
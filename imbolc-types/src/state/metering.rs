@@ -0,0 +1,187 @@
+//! Per-channel metering: peak/RMS and short-term loudness of every instrument,
+//! bus and layer group strip, with clip indicators that latch until reset.
+
+use serde::{Deserialize, Serialize};
+
+use super::instrument_state::InstrumentState;
+use super::session::SessionState;
+use crate::{BusId, InstrumentId};
+
+/// Mixer channel a level reading belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChannelMeterTarget {
+    Instrument(InstrumentId),
+    Bus(BusId),
+    LayerGroup(u32),
+}
+
+impl ChannelMeterTarget {
+    /// Gain the strip's fader and mute/solo apply after the meter tap point.
+    /// Meters read the pre-fader bus, so readings are scaled by this.
+    pub fn fader_gain(&self, instruments: &InstrumentState, session: &SessionState) -> f32 {
+        let mixer = &session.mixer;
+        match *self {
+            ChannelMeterTarget::Instrument(id) => {
+                let Some(inst) = instruments.instrument(id) else {
+                    return 0.0;
+                };
+                let mute = if instruments.any_instrument_solo() {
+                    !inst.mixer.solo
+                } else {
+                    inst.mixer.mute || mixer.master_mute
+                };
+                if mute {
+                    0.0
+                } else {
+                    inst.mixer.level * mixer.master_level
+                }
+            }
+            ChannelMeterTarget::Bus(id) => match session.bus(id) {
+                Some(bus) if !session.effective_bus_mute(bus) => bus.level,
+                _ => 0.0,
+            },
+            ChannelMeterTarget::LayerGroup(group_id) => match mixer.layer_group_mixer(group_id) {
+                Some(gm) if !mixer.effective_layer_group_mute(gm) => gm.level,
+                _ => 0.0,
+            },
+        }
+    }
+}
+
+/// One post-fader level reading for a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ChannelLevels {
+    pub peak_l: f32,
+    pub peak_r: f32,
+    pub rms_l: f32,
+    pub rms_r: f32,
+    /// Short-term (3s) loudness in LUFS, once the analysis synth has reported it
+    pub lufs_short: Option<f32>,
+}
+
+impl ChannelLevels {
+    /// Quietest loudness reported; also the absolute gate of BS.1770.
+    pub const LUFS_FLOOR: f32 = -70.0;
+
+    /// These levels after a linear gain stage.
+    pub fn with_gain(self, gain: f32) -> Self {
+        let lufs_short = self.lufs_short.map(|lufs| {
+            if gain <= 0.0 {
+                Self::LUFS_FLOOR
+            } else {
+                (lufs + 20.0 * gain.log10()).max(Self::LUFS_FLOOR)
+            }
+        });
+        Self {
+            peak_l: self.peak_l * gain,
+            peak_r: self.peak_r * gain,
+            rms_l: self.rms_l * gain,
+            rms_r: self.rms_r * gain,
+            lufs_short,
+        }
+    }
+
+    /// Louder of the two channel peaks.
+    pub fn peak(&self) -> f32 {
+        self.peak_l.max(self.peak_r)
+    }
+
+    /// Louder of the two channel RMS values.
+    pub fn rms(&self) -> f32 {
+        self.rms_l.max(self.rms_r)
+    }
+}
+
+/// Latest levels of a channel plus its clip latch.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ChannelMeter {
+    pub levels: ChannelLevels,
+    /// Set once a peak reaches full scale; stays set until `reset_clip`
+    pub clipped: bool,
+}
+
+impl ChannelMeter {
+    /// Full-scale peak that trips the clip indicator.
+    pub const CLIP_LEVEL: f32 = 1.0;
+
+    pub fn update(&mut self, levels: ChannelLevels) {
+        if levels.peak() >= Self::CLIP_LEVEL {
+            self.clipped = true;
+        }
+        // Loudness arrives less often than peaks; keep the last reading
+        let lufs_short = levels.lufs_short.or(self.levels.lufs_short);
+        self.levels = ChannelLevels {
+            lufs_short,
+            ..levels
+        };
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clipped = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(peak: f32, lufs_short: Option<f32>) -> ChannelLevels {
+        ChannelLevels {
+            peak_l: peak,
+            peak_r: peak * 0.5,
+            rms_l: peak * 0.7,
+            rms_r: peak * 0.35,
+            lufs_short,
+        }
+    }
+
+    #[test]
+    fn clip_latches_until_reset() {
+        let mut meter = ChannelMeter::default();
+        meter.update(levels(0.5, None));
+        assert!(!meter.clipped);
+        meter.update(levels(1.2, None));
+        assert!(meter.clipped);
+        meter.update(levels(0.1, None));
+        assert!(meter.clipped, "clip stays lit after the signal drops");
+        meter.reset_clip();
+        assert!(!meter.clipped);
+    }
+
+    #[test]
+    fn gain_scales_levels_and_loudness() {
+        let scaled = levels(0.8, Some(-10.0)).with_gain(0.5);
+        assert_eq!(scaled.peak_l, 0.4);
+        assert!((scaled.lufs_short.unwrap() - (-16.0206)).abs() < 1e-3);
+        let muted = levels(0.8, Some(-10.0)).with_gain(0.0);
+        assert_eq!(muted.peak(), 0.0);
+        assert_eq!(muted.lufs_short, Some(ChannelLevels::LUFS_FLOOR));
+    }
+
+    #[test]
+    fn fader_gain_follows_level_and_solo() {
+        use crate::SourceType;
+        let mut instruments = InstrumentState::new();
+        let mut session = SessionState::new();
+        let a = instruments.add_instrument(SourceType::Saw);
+        let b = instruments.add_instrument(SourceType::Saw);
+        instruments.instrument_mut(a).unwrap().mixer.level = 0.5;
+        session.mixer.master_level = 0.8;
+        let target = ChannelMeterTarget::Instrument(a);
+        assert!((target.fader_gain(&instruments, &session) - 0.4).abs() < 1e-6);
+        instruments.instrument_mut(b).unwrap().mixer.solo = true;
+        assert_eq!(target.fader_gain(&instruments, &session), 0.0);
+        let bus = ChannelMeterTarget::Bus(BusId::new(1));
+        session.bus_mut(BusId::new(1)).unwrap().level = 0.6;
+        assert_eq!(bus.fader_gain(&instruments, &session), 0.6);
+    }
+
+    #[test]
+    fn loudness_survives_peak_only_updates() {
+        let mut meter = ChannelMeter::default();
+        meter.update(levels(0.5, Some(-14.0)));
+        meter.update(levels(0.4, None));
+        assert_eq!(meter.levels.lufs_short, Some(-14.0));
+        assert_eq!(meter.levels.peak(), 0.4);
+    }
+}
@@ -16,6 +16,77 @@ pub enum RecordMode {
     Recording,
 }
 
+/// MIDI clock / transport sync mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MidiClockMode {
+    /// No clock sent or followed
+    #[default]
+    Off,
+    /// Send 24 PPQN clock and Start/Stop/Continue/Song Position to an output port
+    Master,
+    /// Follow incoming clock: tempo and transport are driven by the input port
+    Slave,
+}
+
+impl MidiClockMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MidiClockMode::Off => "Off",
+            MidiClockMode::Master => "Master",
+            MidiClockMode::Slave => "Slave",
+        }
+    }
+
+    /// Next mode in the Off → Master → Slave cycle
+    pub fn next(&self) -> Self {
+        match self {
+            MidiClockMode::Off => MidiClockMode::Master,
+            MidiClockMode::Master => MidiClockMode::Slave,
+            MidiClockMode::Slave => MidiClockMode::Off,
+        }
+    }
+}
+
+/// How live MIDI notes land in the piano roll while recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NoteRecordMode {
+    /// Add new notes on top of what is already there
+    #[default]
+    Overdub,
+    /// Clear existing notes as the playhead passes over them
+    Replace,
+    /// Replace on every loop pass and keep each pass as a take clip
+    LoopTakes,
+}
+
+impl NoteRecordMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoteRecordMode::Overdub => "Overdub",
+            NoteRecordMode::Replace => "Replace",
+            NoteRecordMode::LoopTakes => "Loop takes",
+        }
+    }
+
+    /// Next mode in the Overdub → Replace → Loop takes cycle
+    pub fn next(&self) -> Self {
+        match self {
+            NoteRecordMode::Overdub => NoteRecordMode::Replace,
+            NoteRecordMode::Replace => NoteRecordMode::LoopTakes,
+            NoteRecordMode::LoopTakes => NoteRecordMode::Overdub,
+        }
+    }
+
+    /// Whether recording clears the notes it passes over
+    pub fn replaces(&self) -> bool {
+        !matches!(self, NoteRecordMode::Overdub)
+    }
+}
+
+/// Input quantize grids in ticks (at 480 ticks per beat), `None` = off
+pub const INPUT_QUANTIZE_GRIDS: [Option<u32>; 5] =
+    [None, Some(60), Some(120), Some(240), Some(480)];
+
 /// Mapping of a MIDI CC to an automation target
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MidiCcMapping {
@@ -102,6 +173,23 @@ pub struct MidiRecordingState {
     pub note_passthrough: bool,
     /// MIDI channel filter (None = all channels)
     pub channel_filter: Option<u8>,
+    /// MPE input: channel 1 is the master, 2-16 carry one note each with its
+    /// own bend, pressure and slide
+    #[serde(default)]
+    pub mpe: bool,
+    /// Pitch bend range of MPE member channels, in semitones
+    #[serde(default = "default_mpe_bend_range")]
+    pub mpe_bend_range: u8,
+    /// How recorded MIDI notes combine with existing ones
+    #[serde(default)]
+    pub note_record_mode: NoteRecordMode,
+    /// Grid recorded note starts snap to, in ticks (`None` = off)
+    #[serde(default)]
+    pub input_quantize: Option<u32>,
+}
+
+fn default_mpe_bend_range() -> u8 {
+    48
 }
 
 impl MidiRecordingState {
@@ -113,6 +201,10 @@ impl MidiRecordingState {
             live_input_instrument: None,
             note_passthrough: true,
             channel_filter: None,
+            mpe: false,
+            mpe_bend_range: default_mpe_bend_range(),
+            note_record_mode: NoteRecordMode::default(),
+            input_quantize: None,
         }
     }
 
@@ -189,6 +281,33 @@ impl MidiRecordingState {
     pub fn should_process_channel(&self, channel: u8) -> bool {
         self.channel_filter.is_none_or(|f| f == channel)
     }
+
+    /// Whether `channel` is an MPE member channel (per-note expression)
+    pub fn is_mpe_member(&self, channel: u8) -> bool {
+        self.mpe && channel != 0
+    }
+
+    /// Map a member channel pitch bend (-8192 to 8191) to semitones
+    pub fn mpe_bend_semitones(&self, pitch_bend: i16) -> f32 {
+        pitch_bend as f32 / 8192.0 * self.mpe_bend_range as f32
+    }
+
+    /// Snap a recorded note start to the input quantize grid
+    pub fn quantize_input(&self, tick: u32) -> u32 {
+        match self.input_quantize {
+            Some(grid) if grid > 0 => (tick + grid / 2) / grid * grid,
+            _ => tick,
+        }
+    }
+
+    /// Input quantize grid after the current one in `INPUT_QUANTIZE_GRIDS`
+    pub fn next_input_quantize(&self) -> Option<u32> {
+        let pos = INPUT_QUANTIZE_GRIDS
+            .iter()
+            .position(|g| *g == self.input_quantize)
+            .unwrap_or(0);
+        INPUT_QUANTIZE_GRIDS[(pos + 1) % INPUT_QUANTIZE_GRIDS.len()]
+    }
 }
 
 /// Common CC numbers for reference
@@ -206,6 +325,8 @@ pub mod cc {
     pub const PORTAMENTO: u8 = 65;
     pub const SOSTENUTO: u8 = 66;
     pub const SOFT_PEDAL: u8 = 67;
+    /// MPE slide / timbre (third dimension of per-note expression)
+    pub const SLIDE: u8 = 74;
     pub const ALL_SOUNDS_OFF: u8 = 120;
     pub const RESET_ALL_CONTROLLERS: u8 = 121;
     pub const ALL_NOTES_OFF: u8 = 123;
@@ -271,4 +392,37 @@ mod tests {
         state.stop_recording();
         assert_eq!(state.record_mode, RecordMode::Off);
     }
+
+    #[test]
+    fn mpe_member_channels_and_bend_range() {
+        let mut state = MidiRecordingState::new();
+        assert!(!state.is_mpe_member(1));
+        state.mpe = true;
+        assert!(!state.is_mpe_member(0));
+        assert!(state.is_mpe_member(1));
+        assert_eq!(state.mpe_bend_semitones(-8192), -48.0);
+        state.mpe_bend_range = 2;
+        assert_eq!(state.mpe_bend_semitones(4096), 1.0);
+    }
+
+    #[test]
+    fn clock_mode_cycles() {
+        let mode = MidiClockMode::default();
+        assert_eq!(mode, MidiClockMode::Off);
+        assert_eq!(mode.next(), MidiClockMode::Master);
+        assert_eq!(mode.next().next(), MidiClockMode::Slave);
+        assert_eq!(mode.next().next().next(), MidiClockMode::Off);
+    }
+
+    #[test]
+    fn input_quantize_snaps_to_nearest_grid_line() {
+        let mut state = MidiRecordingState::new();
+        assert_eq!(state.quantize_input(130), 130);
+        state.input_quantize = state.next_input_quantize();
+        assert_eq!(state.input_quantize, Some(60));
+        assert_eq!(state.quantize_input(89), 60);
+        assert_eq!(state.quantize_input(90), 120);
+        state.input_quantize = Some(480);
+        assert_eq!(state.next_input_quantize(), None);
+    }
 }
@@ -4,21 +4,32 @@ pub mod automation;
 pub mod clipboard;
 pub mod custom_synthdef;
 pub mod drum_sequencer;
+pub mod effect_chain_preset;
+pub mod export;
+pub mod freeze;
 pub mod generative;
 pub mod groove;
 pub mod humanize;
 pub mod instrument;
+pub mod instrument_preset;
 pub mod instrument_state;
 pub mod io;
+pub mod latency;
+pub mod metering;
 pub mod midi_recording;
 pub mod mixer;
 pub mod music;
+pub mod note_expression;
+pub mod note_take;
+pub mod note_transform;
 pub mod parameter_target;
 pub mod piano_roll;
 pub mod project;
 pub mod recording;
 pub mod sampler;
 pub mod session;
+pub mod sidechain;
+pub mod tempo_map;
 pub mod theme;
 pub mod vst;
 
@@ -28,25 +39,36 @@ pub use automation::*;
 pub use clipboard::{Clipboard, ClipboardContents};
 pub use custom_synthdef::*;
 pub use drum_sequencer::*;
+pub use effect_chain_preset::*;
+pub use export::*;
+pub use freeze::*;
 pub use generative::*;
 pub use groove::*;
 pub use humanize::*;
 pub use instrument::*;
+pub use instrument_preset::*;
 pub use instrument_state::*;
 pub use io::*;
+pub use latency::*;
+pub use metering::*;
 pub use midi_recording::*;
 pub use mixer::*;
 pub use music::*;
+pub use note_expression::*;
+pub use note_take::*;
+pub use note_transform::*;
 pub use parameter_target::*;
 pub use piano_roll::*;
 pub use project::*;
 pub use recording::*;
 pub use sampler::*;
 pub use session::*;
+pub use sidechain::*;
+pub use tempo_map::*;
 pub use theme::*;
 pub use vst::*;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -59,6 +81,9 @@ pub struct PendingRender {
     pub instrument_id: InstrumentId,
     pub path: PathBuf,
     pub was_looping: bool,
+    /// Freeze the instrument onto the render instead of replacing its source
+    #[serde(default)]
+    pub freeze: bool,
 }
 
 /// State for an export operation (master bounce or stem export)
@@ -67,6 +92,9 @@ pub struct PendingExport {
     pub kind: ExportKind,
     pub was_looping: bool,
     pub paths: Vec<PathBuf>,
+    /// Rendered by `scsynth -N` without touching the transport
+    #[serde(default)]
+    pub offline: bool,
 }
 
 /// Keyboard layout configuration for key translation
@@ -90,6 +118,8 @@ pub struct VisualizationState {
     pub rms_r: f32,
     /// Oscilloscope ring buffer (recent peak samples at ~30Hz)
     pub scope_buffer: VecDeque<f32>,
+    /// Post-fader levels of each instrument, bus and layer group strip
+    pub channel_meters: HashMap<ChannelMeterTarget, ChannelMeter>,
 }
 
 impl Default for VisualizationState {
@@ -101,10 +131,34 @@ impl Default for VisualizationState {
             rms_l: 0.0,
             rms_r: 0.0,
             scope_buffer: VecDeque::with_capacity(200),
+            channel_meters: HashMap::new(),
         }
     }
 }
 
+impl VisualizationState {
+    /// Fold a batch of channel readings into the meters.
+    pub fn update_channel_meters(&mut self, readings: &[(ChannelMeterTarget, ChannelLevels)]) {
+        for (target, levels) in readings {
+            self.channel_meters
+                .entry(*target)
+                .or_default()
+                .update(*levels);
+        }
+    }
+
+    /// Clear every latched clip indicator.
+    pub fn reset_clips(&mut self) {
+        for meter in self.channel_meters.values_mut() {
+            meter.reset_clip();
+        }
+    }
+
+    pub fn channel_meter(&self, target: ChannelMeterTarget) -> Option<&ChannelMeter> {
+        self.channel_meters.get(&target)
+    }
+}
+
 /// Generation counters for async I/O results (ignore stale completions).
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct IoGeneration {
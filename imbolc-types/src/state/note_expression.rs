@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+use super::ParameterTarget;
+
+/// One axis of per-note expression, as sent by MPE controllers on a note's
+/// own channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExpressionDimension {
+    /// Pitch bend in semitones
+    Bend,
+    /// Channel pressure (0.0-1.0)
+    Pressure,
+    /// Slide / timbre, CC74 (0.0-1.0)
+    Slide,
+}
+
+impl ExpressionDimension {
+    pub const ALL: [ExpressionDimension; 3] = [
+        ExpressionDimension::Bend,
+        ExpressionDimension::Pressure,
+        ExpressionDimension::Slide,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExpressionDimension::Bend => "bend",
+            ExpressionDimension::Pressure => "pressure",
+            ExpressionDimension::Slide => "slide",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// Voice parameter this dimension drives on playback
+    pub fn target(&self) -> ParameterTarget {
+        match self {
+            ExpressionDimension::Bend => ParameterTarget::Pitch,
+            ExpressionDimension::Pressure => ParameterTarget::Pressure,
+            ExpressionDimension::Slide => ParameterTarget::FilterCutoff,
+        }
+    }
+
+    /// Value a voice has before any expression arrives
+    pub fn neutral(&self) -> f32 {
+        match self {
+            ExpressionDimension::Bend => 0.0,
+            ExpressionDimension::Pressure => 0.0,
+            ExpressionDimension::Slide => 0.5,
+        }
+    }
+}
+
+/// A curve point, `tick` ticks after the note starts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExpressionPoint {
+    pub tick: u32,
+    pub value: f32,
+}
+
+/// Changes smaller than this are dropped while recording
+const RECORD_EPSILON: f32 = 1.0 / 256.0;
+
+/// Per-note expression curves. Points are sorted by tick; each value holds
+/// until the next point.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteExpression {
+    #[serde(default)]
+    pub bend: Vec<ExpressionPoint>,
+    #[serde(default)]
+    pub pressure: Vec<ExpressionPoint>,
+    #[serde(default)]
+    pub slide: Vec<ExpressionPoint>,
+}
+
+impl NoteExpression {
+    pub fn is_empty(&self) -> bool {
+        self.bend.is_empty() && self.pressure.is_empty() && self.slide.is_empty()
+    }
+
+    pub fn curve(&self, dimension: ExpressionDimension) -> &[ExpressionPoint] {
+        match dimension {
+            ExpressionDimension::Bend => &self.bend,
+            ExpressionDimension::Pressure => &self.pressure,
+            ExpressionDimension::Slide => &self.slide,
+        }
+    }
+
+    pub fn curve_mut(&mut self, dimension: ExpressionDimension) -> &mut Vec<ExpressionPoint> {
+        match dimension {
+            ExpressionDimension::Bend => &mut self.bend,
+            ExpressionDimension::Pressure => &mut self.pressure,
+            ExpressionDimension::Slide => &mut self.slide,
+        }
+    }
+
+    /// Append a live value `tick` ticks into the note. Repeats of the last
+    /// value are skipped and a second value on the same tick replaces the first.
+    pub fn record(&mut self, dimension: ExpressionDimension, tick: u32, value: f32) {
+        let curve = self.curve_mut(dimension);
+        match curve.last_mut() {
+            Some(last) if (last.value - value).abs() < RECORD_EPSILON => {}
+            Some(last) if last.tick >= tick => last.value = value,
+            _ => curve.push(ExpressionPoint { tick, value }),
+        }
+    }
+
+    /// Value of a curve `tick` ticks into the note, if it has started
+    pub fn value_at(&self, dimension: ExpressionDimension, tick: u32) -> Option<f32> {
+        let curve = self.curve(dimension);
+        let idx = curve.partition_point(|p| p.tick <= tick);
+        idx.checked_sub(1).map(|i| curve[i].value)
+    }
+
+    /// Every point as `(dimension, point)`, in tick order
+    pub fn points(&self) -> Vec<(ExpressionDimension, ExpressionPoint)> {
+        let mut points: Vec<_> = ExpressionDimension::ALL
+            .into_iter()
+            .flat_map(|d| self.curve(d).iter().map(move |p| (d, *p)))
+            .collect();
+        points.sort_by_key(|(_, p)| p.tick);
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_thins_repeats_and_same_tick_updates() {
+        let mut expr = NoteExpression::default();
+        expr.record(ExpressionDimension::Pressure, 0, 0.2);
+        expr.record(ExpressionDimension::Pressure, 10, 0.2);
+        expr.record(ExpressionDimension::Pressure, 20, 0.5);
+        expr.record(ExpressionDimension::Pressure, 20, 0.6);
+        assert_eq!(
+            expr.pressure,
+            vec![
+                ExpressionPoint {
+                    tick: 0,
+                    value: 0.2
+                },
+                ExpressionPoint {
+                    tick: 20,
+                    value: 0.6
+                },
+            ]
+        );
+        assert!(expr.bend.is_empty());
+    }
+
+    #[test]
+    fn value_holds_until_next_point() {
+        let mut expr = NoteExpression::default();
+        expr.record(ExpressionDimension::Bend, 100, 2.0);
+        expr.record(ExpressionDimension::Bend, 200, -1.0);
+        assert_eq!(expr.value_at(ExpressionDimension::Bend, 50), None);
+        assert_eq!(expr.value_at(ExpressionDimension::Bend, 150), Some(2.0));
+        assert_eq!(expr.value_at(ExpressionDimension::Bend, 900), Some(-1.0));
+    }
+
+    #[test]
+    fn points_merge_curves_in_tick_order() {
+        let mut expr = NoteExpression::default();
+        expr.record(ExpressionDimension::Slide, 30, 0.7);
+        expr.record(ExpressionDimension::Bend, 10, 1.0);
+        expr.record(ExpressionDimension::Pressure, 20, 0.4);
+        let dims: Vec<_> = expr.points().into_iter().map(|(d, _)| d).collect();
+        assert_eq!(
+            dims,
+            vec![
+                ExpressionDimension::Bend,
+                ExpressionDimension::Pressure,
+                ExpressionDimension::Slide
+            ]
+        );
+        assert_eq!(
+            ExpressionDimension::from_name("slide"),
+            Some(ExpressionDimension::Slide)
+        );
+    }
+}
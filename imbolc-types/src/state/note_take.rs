@@ -0,0 +1,187 @@
+use super::{ExpressionDimension, NoteExpression, NoteRecordMode};
+use crate::{InstrumentId, Note};
+
+/// A key held down during a take, not yet written as a note
+#[derive(Debug, Clone)]
+struct HeldNote {
+    pitch: u8,
+    velocity: u8,
+    /// Tick the key went down, before input quantize
+    pressed: u32,
+    /// Tick the note is placed at
+    tick: u32,
+    expression: Option<Box<NoteExpression>>,
+}
+
+/// MIDI notes being recorded into one instrument's piano roll track: keys
+/// still held, and the notes finished during the current loop pass.
+#[derive(Debug, Clone)]
+pub struct NoteTake {
+    pub instrument_id: InstrumentId,
+    pub mode: NoteRecordMode,
+    /// Tick where the current pass started recording
+    pub pass_start: u32,
+    /// Tick up to which replace modes have cleared the track
+    pub cleared_to: u32,
+    /// Notes finished in the current pass, in the order they were released
+    pub pass_notes: Vec<Note>,
+    held: Vec<HeldNote>,
+}
+
+impl NoteTake {
+    pub fn new(instrument_id: InstrumentId, mode: NoteRecordMode, tick: u32) -> Self {
+        Self {
+            instrument_id,
+            mode,
+            pass_start: tick,
+            cleared_to: tick,
+            pass_notes: Vec::new(),
+            held: Vec::new(),
+        }
+    }
+
+    /// A key went down at `pressed`; the note will start at `tick`
+    /// (`pressed` after input quantize).
+    pub fn note_on(&mut self, pitch: u8, velocity: u8, pressed: u32, tick: u32) {
+        self.held.retain(|h| h.pitch != pitch);
+        self.held.push(HeldNote {
+            pitch,
+            velocity,
+            pressed,
+            tick,
+            expression: None,
+        });
+    }
+
+    /// A key came up at `tick`. Returns the finished note, which is also kept
+    /// in the pass.
+    pub fn note_off(&mut self, pitch: u8, tick: u32) -> Option<Note> {
+        let pos = self.held.iter().position(|h| h.pitch == pitch)?;
+        let held = self.held.remove(pos);
+        Some(self.finish(held, tick))
+    }
+
+    /// Release every held key at `tick`, returning the finished notes
+    pub fn release_all(&mut self, tick: u32) -> Vec<Note> {
+        std::mem::take(&mut self.held)
+            .into_iter()
+            .map(|held| self.finish(held, tick))
+            .collect()
+    }
+
+    /// The playhead wrapped from `end` back to `start`: held keys are cut at
+    /// `end` and carry on as new notes from `start`. Returns the notes cut
+    /// and every note of the finished pass.
+    pub fn next_pass(&mut self, end: u32, start: u32) -> (Vec<Note>, Vec<Note>) {
+        let held = self.held.clone();
+        let cut = self.release_all(end);
+        let pass = std::mem::take(&mut self.pass_notes);
+        self.held = held
+            .into_iter()
+            .map(|h| HeldNote {
+                pressed: start,
+                tick: start,
+                expression: None,
+                ..h
+            })
+            .collect();
+        self.pass_start = start;
+        self.cleared_to = start;
+        (cut, pass)
+    }
+
+    /// Record a live expression value on a held key. Returns false when the
+    /// key is not held in this take.
+    pub fn record_expression(
+        &mut self,
+        pitch: u8,
+        tick: u32,
+        dimension: ExpressionDimension,
+        value: f32,
+    ) -> bool {
+        let Some(held) = self.held.iter_mut().find(|h| h.pitch == pitch) else {
+            return false;
+        };
+        held.expression.get_or_insert_with(Default::default).record(
+            dimension,
+            tick.saturating_sub(held.tick),
+            value,
+        );
+        true
+    }
+
+    /// Whether a note on the track was recorded in this pass (or is still held)
+    pub fn contains(&self, note: &Note) -> bool {
+        self.pass_notes
+            .iter()
+            .any(|n| n.pitch == note.pitch && n.tick == note.tick)
+            || self
+                .held
+                .iter()
+                .any(|h| h.pitch == note.pitch && h.tick == note.tick)
+    }
+
+    fn finish(&mut self, held: HeldNote, tick: u32) -> Note {
+        let note = Note {
+            tick: held.tick,
+            duration: tick.saturating_sub(held.pressed).max(1),
+            pitch: held.pitch,
+            velocity: held.velocity,
+            probability: 1.0,
+            expression: held.expression,
+        };
+        self.pass_notes.push(note.clone());
+        note
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take() -> NoteTake {
+        NoteTake::new(InstrumentId::new(1), NoteRecordMode::Overdub, 0)
+    }
+
+    #[test]
+    fn note_keeps_played_length_after_quantize() {
+        let mut take = take();
+        take.note_on(60, 100, 130, 120);
+        let note = take.note_off(60, 370).unwrap();
+        assert_eq!((note.tick, note.duration, note.velocity), (120, 240, 100));
+        assert_eq!(take.pass_notes.len(), 1);
+        assert!(take.note_off(60, 400).is_none());
+    }
+
+    #[test]
+    fn held_keys_carry_over_a_loop_wrap() {
+        let mut take = take();
+        take.note_on(60, 90, 1700, 1700);
+        take.note_on(64, 90, 100, 100);
+        take.note_off(64, 200);
+
+        let (cut, pass) = take.next_pass(1920, 0);
+        assert_eq!(cut.len(), 1);
+        assert_eq!((cut[0].tick, cut[0].duration), (1700, 220));
+        assert_eq!(pass.len(), 2);
+        assert!(take.pass_notes.is_empty());
+
+        let note = take.note_off(60, 480).unwrap();
+        assert_eq!((note.tick, note.duration), (0, 480));
+    }
+
+    #[test]
+    fn expression_follows_held_key() {
+        let mut take = take();
+        take.note_on(60, 100, 480, 480);
+        assert!(take.record_expression(60, 600, ExpressionDimension::Bend, 1.0));
+        assert!(!take.record_expression(62, 600, ExpressionDimension::Bend, 1.0));
+        let note = take.note_off(60, 960).unwrap();
+        assert_eq!(
+            note.expression
+                .unwrap()
+                .value_at(ExpressionDimension::Bend, 120),
+            Some(1.0)
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Key, Note, Scale};
+
+/// An edit applied to the selected notes of a piano roll track.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NoteTransform {
+    /// Pull note starts toward a `grid` (in ticks) by `strength` (0.0-1.0).
+    /// `swing` (0.0-1.0) delays every other grid line, like playback swing.
+    Quantize {
+        grid: u32,
+        strength: f32,
+        swing: f32,
+    },
+    /// Shift pitches by semitones
+    Transpose(i8),
+    /// Shift pitches by steps of a scale; off-scale notes snap first
+    TransposeDegrees { degrees: i8, key: Key, scale: Scale },
+    /// Stretch each note up to the start of the next one
+    Legato,
+    /// Give every note the same duration (in ticks)
+    FixedLength(u32),
+    /// Multiply velocities by a factor
+    ScaleVelocity(f32),
+    /// Pull velocities toward their average by `amount` (0.0-1.0)
+    CompressVelocity(f32),
+    /// Mirror note positions in time across the selection
+    Reverse,
+    /// Mirror pitches around the middle of the selection's range
+    Invert,
+    /// Move each note to the nearest pitch of the scale
+    SnapToScale { key: Key, scale: Scale },
+    /// Bake up to `timing` ticks and `velocity` steps of jitter into the notes.
+    /// Seeded so every replay of the action produces the same notes.
+    Humanize {
+        timing: u32,
+        velocity: u8,
+        seed: u64,
+    },
+}
+
+impl NoteTransform {
+    /// Apply the transform to the notes matching `selected`, leaving the rest
+    /// alone. Notes stay sorted by tick; a transformed note replaces any other
+    /// note it lands on at the same pitch and tick.
+    pub fn apply(&self, notes: &mut Vec<Note>, selected: impl Fn(&Note) -> bool) {
+        let (mut chosen, rest): (Vec<Note>, Vec<Note>) = notes.drain(..).partition(|n| selected(n));
+        if chosen.is_empty() {
+            *notes = rest;
+            return;
+        }
+        self.transform(&mut chosen);
+
+        let mut seen = HashSet::new();
+        chosen.retain(|n| seen.insert((n.tick, n.pitch)));
+        chosen.extend(
+            rest.into_iter()
+                .filter(|n| !seen.contains(&(n.tick, n.pitch))),
+        );
+        chosen.sort_by_key(|n| n.tick);
+        *notes = chosen;
+    }
+
+    fn transform(&self, notes: &mut [Note]) {
+        match *self {
+            NoteTransform::Quantize {
+                grid,
+                strength,
+                swing,
+            } => {
+                if grid == 0 {
+                    return;
+                }
+                let strength = strength.clamp(0.0, 1.0) as f64;
+                let swing_ticks = swing.clamp(0.0, 1.0) as f64 * grid as f64 * 0.5;
+                for note in notes.iter_mut() {
+                    let line = (note.tick as f64 / grid as f64).round();
+                    let mut target = line * grid as f64;
+                    if line as u64 % 2 == 1 {
+                        target += swing_ticks;
+                    }
+                    let tick = note.tick as f64 + (target - note.tick as f64) * strength;
+                    note.tick = tick.round().max(0.0) as u32;
+                }
+            }
+            NoteTransform::Transpose(semitones) => {
+                for note in notes.iter_mut() {
+                    if let Some(pitch) = offset_pitch(note.pitch as i32 + semitones as i32) {
+                        note.pitch = pitch;
+                    }
+                }
+            }
+            NoteTransform::TransposeDegrees {
+                degrees,
+                key,
+                scale,
+            } => {
+                for note in notes.iter_mut() {
+                    let snapped = snap_to_scale(note.pitch, key, scale);
+                    if let Some(pitch) = shift_degrees(snapped, degrees as i32, key, scale) {
+                        note.pitch = pitch;
+                    }
+                }
+            }
+            NoteTransform::Legato => {
+                let mut starts: Vec<u32> = notes.iter().map(|n| n.tick).collect();
+                starts.sort_unstable();
+                starts.dedup();
+                for note in notes.iter_mut() {
+                    let next = starts.partition_point(|&t| t <= note.tick);
+                    if let Some(&next_tick) = starts.get(next) {
+                        note.duration = next_tick - note.tick;
+                    }
+                }
+            }
+            NoteTransform::FixedLength(duration) => {
+                for note in notes.iter_mut() {
+                    note.duration = duration.max(1);
+                }
+            }
+            NoteTransform::ScaleVelocity(factor) => {
+                for note in notes.iter_mut() {
+                    note.velocity = clamp_velocity(note.velocity as f32 * factor.max(0.0));
+                }
+            }
+            NoteTransform::CompressVelocity(amount) => {
+                let amount = amount.clamp(0.0, 1.0);
+                let average =
+                    notes.iter().map(|n| n.velocity as f32).sum::<f32>() / notes.len() as f32;
+                for note in notes.iter_mut() {
+                    let velocity = note.velocity as f32;
+                    note.velocity = clamp_velocity(velocity + (average - velocity) * amount);
+                }
+            }
+            NoteTransform::Reverse => {
+                let start = notes.iter().map(|n| n.tick).min().unwrap_or(0);
+                let end = notes.iter().map(|n| n.tick + n.duration).max().unwrap_or(0);
+                for note in notes.iter_mut() {
+                    note.tick = start + end - (note.tick + note.duration);
+                }
+            }
+            NoteTransform::Invert => {
+                let low = notes.iter().map(|n| n.pitch).min().unwrap_or(0);
+                let high = notes.iter().map(|n| n.pitch).max().unwrap_or(0);
+                for note in notes.iter_mut() {
+                    note.pitch = low + high - note.pitch;
+                }
+            }
+            NoteTransform::SnapToScale { key, scale } => {
+                for note in notes.iter_mut() {
+                    note.pitch = snap_to_scale(note.pitch, key, scale);
+                }
+            }
+            NoteTransform::Humanize {
+                timing,
+                velocity,
+                seed,
+            } => {
+                let mut rng = seed;
+                for note in notes.iter_mut() {
+                    let shift = (next_random(&mut rng) - 0.5) * 2.0 * timing as f32;
+                    note.tick = (note.tick as f32 + shift).round().max(0.0) as u32;
+                    let jitter = (next_random(&mut rng) - 0.5) * 2.0 * velocity as f32;
+                    note.velocity = clamp_velocity(note.velocity as f32 + jitter);
+                }
+            }
+        }
+    }
+}
+
+/// Nearest pitch to `pitch` that belongs to `scale` in `key`, preferring the
+/// lower neighbour when two are equally close.
+pub fn snap_to_scale(pitch: u8, key: Key, scale: Scale) -> u8 {
+    let in_scale = |p: i32| {
+        let class = (p - key.semitone()).rem_euclid(12);
+        scale.intervals().contains(&class)
+    };
+    let pitch = pitch as i32;
+    for distance in 0..12 {
+        for candidate in [pitch - distance, pitch + distance] {
+            if in_scale(candidate) {
+                if let Some(p) = offset_pitch(candidate) {
+                    return p;
+                }
+            }
+        }
+    }
+    pitch as u8
+}
+
+/// Move an in-scale `pitch` by `degrees` steps of the scale
+fn shift_degrees(pitch: u8, degrees: i32, key: Key, scale: Scale) -> Option<u8> {
+    let intervals = scale.intervals();
+    let steps = intervals.len() as i32;
+    let relative = pitch as i32 - key.semitone();
+    let octave = relative.div_euclid(12);
+    let class = relative.rem_euclid(12);
+    let index = intervals.iter().position(|&i| i == class)? as i32 + degrees;
+    let target = key.semitone()
+        + (octave + index.div_euclid(steps)) * 12
+        + intervals[index.rem_euclid(steps) as usize];
+    offset_pitch(target)
+}
+
+fn offset_pitch(pitch: i32) -> Option<u8> {
+    (0..=127).contains(&pitch).then_some(pitch as u8)
+}
+
+fn clamp_velocity(velocity: f32) -> u8 {
+    velocity.round().clamp(1.0, 127.0) as u8
+}
+
+/// Uniform value in `[0, 1)` from a 64-bit LCG
+fn next_random(state: &mut u64) -> f32 {
+    *state = state
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    (*state >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(tick: u32, pitch: u8) -> Note {
+        Note {
+            tick,
+            duration: 120,
+            pitch,
+            velocity: 100,
+            probability: 1.0,
+            expression: None,
+        }
+    }
+
+    fn ticks(notes: &[Note]) -> Vec<u32> {
+        notes.iter().map(|n| n.tick).collect()
+    }
+
+    fn pitches(notes: &[Note]) -> Vec<u8> {
+        notes.iter().map(|n| n.pitch).collect()
+    }
+
+    #[test]
+    fn quantize_respects_strength_and_swing() {
+        let mut notes = vec![note(10, 60), note(250, 62), note(470, 64)];
+        let full = NoteTransform::Quantize {
+            grid: 240,
+            strength: 1.0,
+            swing: 0.0,
+        };
+        full.apply(&mut notes, |_| true);
+        assert_eq!(ticks(&notes), vec![0, 240, 480]);
+
+        let mut notes = vec![note(100, 60)];
+        let half = NoteTransform::Quantize {
+            grid: 240,
+            strength: 0.5,
+            swing: 0.0,
+        };
+        half.apply(&mut notes, |_| true);
+        assert_eq!(ticks(&notes), vec![50]);
+
+        let mut notes = vec![note(0, 60), note(240, 62)];
+        let swung = NoteTransform::Quantize {
+            grid: 240,
+            strength: 1.0,
+            swing: 0.5,
+        };
+        swung.apply(&mut notes, |_| true);
+        assert_eq!(ticks(&notes), vec![0, 300]);
+    }
+
+    #[test]
+    fn only_selected_notes_change_and_collisions_resolve() {
+        let mut notes = vec![note(0, 60), note(0, 62), note(480, 60)];
+        NoteTransform::Transpose(2).apply(&mut notes, |n| n.pitch == 60 && n.tick == 0);
+        assert_eq!(notes.len(), 2);
+        assert_eq!(pitches(&notes), vec![62, 60]);
+        assert_eq!(ticks(&notes), vec![0, 480]);
+    }
+
+    #[test]
+    fn transpose_keeps_notes_in_midi_range() {
+        let mut notes = vec![note(0, 120), note(240, 60)];
+        NoteTransform::Transpose(12).apply(&mut notes, |_| true);
+        assert_eq!(pitches(&notes), vec![120, 72]);
+    }
+
+    #[test]
+    fn transpose_by_scale_degrees_wraps_octaves() {
+        // C major: E up two degrees is G, B up one is the next C
+        let mut notes = vec![note(0, 64), note(240, 71), note(480, 61)];
+        NoteTransform::TransposeDegrees {
+            degrees: 2,
+            key: Key::C,
+            scale: Scale::Major,
+        }
+        .apply(&mut notes, |_| true);
+        assert_eq!(pitches(&notes), vec![67, 74, 64]);
+
+        let mut notes = vec![note(0, 60)];
+        NoteTransform::TransposeDegrees {
+            degrees: -1,
+            key: Key::D,
+            scale: Scale::Minor,
+        }
+        .apply(&mut notes, |_| true);
+        assert_eq!(pitches(&notes), vec![58]);
+    }
+
+    #[test]
+    fn legato_and_fixed_length_set_durations() {
+        let mut notes = vec![note(0, 60), note(0, 64), note(480, 62), note(960, 60)];
+        NoteTransform::Legato.apply(&mut notes, |_| true);
+        let durations: Vec<u32> = notes.iter().map(|n| n.duration).collect();
+        assert_eq!(durations, vec![480, 480, 480, 120]);
+
+        NoteTransform::FixedLength(60).apply(&mut notes, |_| true);
+        assert!(notes.iter().all(|n| n.duration == 60));
+    }
+
+    #[test]
+    fn velocity_scale_and_compress() {
+        let mut notes = vec![note(0, 60), note(240, 62)];
+        notes[0].velocity = 40;
+        notes[1].velocity = 120;
+        NoteTransform::ScaleVelocity(1.5).apply(&mut notes, |_| true);
+        assert_eq!(notes[0].velocity, 60);
+        assert_eq!(notes[1].velocity, 127);
+
+        notes[1].velocity = 100;
+        NoteTransform::CompressVelocity(0.5).apply(&mut notes, |_| true);
+        assert_eq!(notes[0].velocity, 70);
+        assert_eq!(notes[1].velocity, 90);
+    }
+
+    #[test]
+    fn reverse_and_invert_mirror_the_selection() {
+        let mut notes = vec![note(0, 60), note(240, 64), note(480, 67)];
+        notes[2].duration = 240;
+        NoteTransform::Reverse.apply(&mut notes, |_| true);
+        assert_eq!(ticks(&notes), vec![0, 360, 600]);
+        assert_eq!(pitches(&notes), vec![67, 64, 60]);
+
+        NoteTransform::Invert.apply(&mut notes, |_| true);
+        assert_eq!(pitches(&notes), vec![60, 63, 67]);
+    }
+
+    #[test]
+    fn snap_to_scale_prefers_lower_neighbour() {
+        assert_eq!(snap_to_scale(61, Key::C, Scale::Major), 60);
+        assert_eq!(snap_to_scale(66, Key::C, Scale::Major), 65);
+        assert_eq!(snap_to_scale(64, Key::C, Scale::Major), 64);
+        assert_eq!(snap_to_scale(61, Key::A, Scale::Pentatonic), 61);
+        assert_eq!(snap_to_scale(127, Key::C, Scale::Minor), 127);
+    }
+
+    #[test]
+    fn humanize_is_bounded_and_repeatable() {
+        let original = vec![note(480, 60), note(960, 62), note(1440, 64)];
+        let humanize = NoteTransform::Humanize {
+            timing: 20,
+            velocity: 10,
+            seed: 7,
+        };
+        let mut first = original.clone();
+        humanize.apply(&mut first, |_| true);
+        let mut second = original.clone();
+        humanize.apply(&mut second, |_| true);
+
+        assert_eq!(ticks(&first), ticks(&second));
+        for (before, after) in original.iter().zip(&first) {
+            assert!(before.tick.abs_diff(after.tick) <= 20);
+            assert!(before.velocity.abs_diff(after.velocity) <= 10);
+        }
+    }
+}
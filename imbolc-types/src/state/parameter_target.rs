@@ -82,6 +82,10 @@ pub enum ParameterTarget {
     SampleRate,
     /// Sample amplitude (0.0-1.0)
     SampleAmp,
+    /// Sample slice start point (0.0-1.0 of the buffer)
+    SampleStart,
+    /// Sample slice end point (0.0-1.0 of the buffer)
+    SampleEnd,
     /// Time stretch ratio for granular time stretch
     StretchRatio,
     /// Pitch shift for granular time stretch (semitones)
@@ -166,6 +170,8 @@ impl ParameterTarget {
             Self::MembraneTension => "Tensn",
             Self::SampleRate => "SRate",
             Self::SampleAmp => "SAmp",
+            Self::SampleStart => "SStrt",
+            Self::SampleEnd => "SEnd",
             Self::StretchRatio => "Strch",
             Self::PitchShift => "PtchSh",
             Self::DelayTime => "DlyTm",
@@ -219,6 +225,8 @@ impl ParameterTarget {
             Self::MembraneTension => "Membrane Tension".to_string(),
             Self::SampleRate => "Sample Rate".to_string(),
             Self::SampleAmp => "Sample Amp".to_string(),
+            Self::SampleStart => "Sample Start".to_string(),
+            Self::SampleEnd => "Sample End".to_string(),
             Self::StretchRatio => "Stretch Ratio".to_string(),
             Self::PitchShift => "Pitch Shift".to_string(),
             Self::DelayTime => "Delay Time".to_string(),
@@ -254,6 +262,8 @@ impl ParameterTarget {
             | Self::HumanizeVelocity
             | Self::HumanizeTiming
             | Self::SampleAmp
+            | Self::SampleStart
+            | Self::SampleEnd
             | Self::PulseWidth
             | Self::WavetablePosition
             | Self::RingModDepth
@@ -383,6 +393,8 @@ impl ParameterTarget {
             "Tensn" => Some(Self::MembraneTension),
             "SRate" => Some(Self::SampleRate),
             "SAmp" => Some(Self::SampleAmp),
+            "SStrt" => Some(Self::SampleStart),
+            "SEnd" => Some(Self::SampleEnd),
             "Strch" => Some(Self::StretchRatio),
             "PtchSh" => Some(Self::PitchShift),
             "DlyTm" => Some(Self::DelayTime),
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::InstrumentId;
+use crate::{BarPosition, ExpressionDimension, InstrumentId, NoteExpression, TempoMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
@@ -12,6 +12,9 @@ pub struct Note {
     pub pitch: u8,
     pub velocity: u8,
     pub probability: f32, // 0.0-1.0, default 1.0 (always play)
+    /// Per-note pitch bend, pressure and slide curves (MPE-style)
+    #[serde(default)]
+    pub expression: Option<Box<NoteExpression>>,
 }
 
 /// A note stored with position relative to the selection anchor.
@@ -23,6 +26,8 @@ pub struct ClipboardNote {
     pub duration: u32,
     pub velocity: u8,
     pub probability: f32,
+    #[serde(default)]
+    pub expression: Option<Box<NoteExpression>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +56,9 @@ pub struct PianoRollState {
     pub recording: bool,
     /// Swing amount: 0.0 = no swing, 1.0 = max swing (delays offbeat notes)
     pub swing_amount: f32,
+    /// Tempo and meter changes; `bpm` and `time_signature` apply before them
+    #[serde(default)]
+    pub tempo_map: TempoMap,
 }
 
 impl PianoRollState {
@@ -68,6 +76,7 @@ impl PianoRollState {
             ticks_per_beat: 480,
             recording: false,
             swing_amount: 0.0,
+            tempo_map: TempoMap::default(),
         }
     }
 
@@ -127,6 +136,7 @@ impl PianoRollState {
                         pitch,
                         velocity,
                         probability: 1.0,
+                        expression: None,
                     },
                 );
             }
@@ -144,6 +154,85 @@ impl PianoRollState {
         })
     }
 
+    /// Record a live expression value into the note of `instrument_id` at
+    /// `pitch` that is sounding at `tick`. Returns false when no note is.
+    pub fn record_expression(
+        &mut self,
+        instrument_id: InstrumentId,
+        pitch: u8,
+        tick: u32,
+        dimension: ExpressionDimension,
+        value: f32,
+    ) -> bool {
+        let Some(track) = self.tracks.get_mut(&instrument_id) else {
+            return false;
+        };
+        let Some(note) = track
+            .notes
+            .iter_mut()
+            .rev()
+            .find(|n| n.pitch == pitch && n.tick <= tick && tick < n.tick + n.duration)
+        else {
+            return false;
+        };
+        note.expression.get_or_insert_with(Default::default).record(
+            dimension,
+            tick - note.tick,
+            value,
+        );
+        true
+    }
+
+    /// Add a recorded note to an instrument's track, replacing any note of
+    /// the same pitch starting on the same tick.
+    pub fn insert_note(&mut self, instrument_id: InstrumentId, note: Note) {
+        if let Some(track) = self.tracks.get_mut(&instrument_id) {
+            track
+                .notes
+                .retain(|n| !(n.pitch == note.pitch && n.tick == note.tick));
+            let pos = track.notes.partition_point(|n| n.tick <= note.tick);
+            track.notes.insert(pos, note);
+        }
+    }
+
+    /// Remove an instrument's notes starting in `start..end`, except those
+    /// `keep` returns true for. Returns whether anything was removed.
+    pub fn clear_notes(
+        &mut self,
+        instrument_id: InstrumentId,
+        start: u32,
+        end: u32,
+        keep: impl Fn(&Note) -> bool,
+    ) -> bool {
+        let Some(track) = self.tracks.get_mut(&instrument_id) else {
+            return false;
+        };
+        let before = track.notes.len();
+        track
+            .notes
+            .retain(|n| n.tick < start || n.tick >= end || keep(n));
+        track.notes.len() != before
+    }
+
+    /// Tick `secs` of playback before `tick`, following the tempo at `tick`
+    /// and wrapping back over the loop start while looping.
+    pub fn tick_before(&self, tick: u32, secs: f64) -> u32 {
+        let ticks_per_sec = self.bpm_at(tick as f64) as f64 / 60.0 * self.ticks_per_beat as f64;
+        let back = (secs.max(0.0) * ticks_per_sec).round() as u32;
+        let in_loop = self.looping && tick >= self.loop_start && tick < self.loop_end;
+        if in_loop && tick < self.loop_start + back {
+            let loop_len = self.loop_end - self.loop_start;
+            let over = (self.loop_start + back - tick) % loop_len;
+            if over == 0 {
+                self.loop_start
+            } else {
+                self.loop_end - over
+            }
+        } else {
+            tick.saturating_sub(back)
+        }
+    }
+
     /// Find notes that start within a tick range (for playback)
     #[allow(dead_code)]
     pub fn notes_in_range(&self, track_index: usize, start_tick: u32, end_tick: u32) -> Vec<&Note> {
@@ -184,6 +273,58 @@ impl PianoRollState {
     pub fn ticks_per_bar(&self) -> u32 {
         self.ticks_per_beat * self.time_signature.0 as u32
     }
+
+    /// Tempo at `tick`, following the tempo map
+    pub fn bpm_at(&self, tick: f64) -> f32 {
+        self.tempo_map.bpm_at(self.bpm, tick)
+    }
+
+    /// Seconds between two ticks, following the tempo map
+    pub fn secs_between(&self, from: f64, to: f64) -> f64 {
+        self.tempo_map
+            .secs_between(self.bpm, self.ticks_per_beat, from, to)
+    }
+
+    /// Ticks covered by `secs` of playback from `from`, following the tempo map
+    pub fn ticks_after(&self, from: f64, secs: f64) -> f64 {
+        self.tempo_map
+            .ticks_after(self.bpm, self.ticks_per_beat, from, secs)
+    }
+
+    /// Bar containing `tick`, following meter changes
+    pub fn bar_at_tick(&self, tick: u32) -> BarPosition {
+        self.tempo_map
+            .bar_at_tick(self.time_signature, self.ticks_per_beat, tick)
+    }
+
+    /// Tick at which `bar` starts, following meter changes
+    pub fn bar_start_tick(&self, bar: u32) -> u32 {
+        self.tempo_map
+            .bar_start_tick(self.time_signature, self.ticks_per_beat, bar)
+    }
+
+    /// Open `len` ticks at `at` in the tempo map
+    pub fn insert_time(&mut self, at: u32, len: u32) {
+        self.tempo_map
+            .insert_time(self.time_signature, self.ticks_per_beat, at, len);
+    }
+
+    /// Cut `[start, end)` out of the tempo map
+    pub fn delete_time(&mut self, start: u32, end: u32) {
+        self.tempo_map
+            .delete_time(self.time_signature, self.ticks_per_beat, start, end);
+    }
+
+    /// Repeat the tempo map's `[start, end)` right after itself
+    pub fn duplicate_time(&mut self, start: u32, end: u32) {
+        self.tempo_map.duplicate_time(
+            self.bpm,
+            self.time_signature,
+            self.ticks_per_beat,
+            start,
+            end,
+        );
+    }
 }
 
 impl Default for PianoRollState {
@@ -197,6 +338,49 @@ mod tests {
     use super::*;
     use crate::InstrumentId;
 
+    #[test]
+    fn tick_before_wraps_over_loop_start() {
+        let mut pr = PianoRollState::new();
+        pr.bpm = 120.0;
+        pr.looping = false;
+        pr.loop_start = 960;
+        pr.loop_end = 1920;
+        // 0.25 s at 120 BPM is half a beat
+        assert_eq!(pr.tick_before(1200, 0.25), 960);
+        assert_eq!(pr.tick_before(500, 0.25), 260);
+        assert_eq!(pr.tick_before(100, 0.25), 0);
+        pr.looping = true;
+        assert_eq!(pr.tick_before(1000, 0.25), 1720);
+        assert_eq!(pr.tick_before(1200, 0.0), 1200);
+    }
+
+    #[test]
+    fn recorded_notes_replace_and_clear() {
+        let id = InstrumentId::new(1);
+        let mut pr = PianoRollState::new();
+        pr.add_track(id);
+        pr.toggle_note(0, 60, 0, 480, 100);
+        pr.toggle_note(0, 62, 480, 480, 100);
+        let note = |tick, velocity| Note {
+            tick,
+            duration: 240,
+            pitch: 60,
+            velocity,
+            probability: 1.0,
+            expression: None,
+        };
+        pr.insert_note(id, note(0, 50));
+        pr.insert_note(id, note(240, 70));
+        let notes = &pr.tracks[&id].notes;
+        assert_eq!(notes.len(), 3);
+        assert_eq!((notes[0].velocity, notes[1].tick), (50, 240));
+
+        assert!(pr.clear_notes(id, 0, 960, |n| n.tick == 240));
+        let ticks: Vec<u32> = pr.tracks[&id].notes.iter().map(|n| n.tick).collect();
+        assert_eq!(ticks, vec![240]);
+        assert!(!pr.clear_notes(id, 480, 960, |_| false));
+    }
+
     #[test]
     fn toggle_note_adds_and_removes() {
         let mut pr = PianoRollState::new();
@@ -237,12 +421,47 @@ mod tests {
         assert_eq!(pr.ticks_per_bar(), pr.ticks_per_beat * 3);
     }
 
+    #[test]
+    fn bar_at_tick_follows_meter_changes() {
+        let mut pr = PianoRollState::new();
+        pr.tempo_map.set_meter(crate::MeterChange {
+            bar: 1,
+            time_signature: (3, 4),
+        });
+        assert_eq!(
+            pr.bar_start_tick(2),
+            pr.ticks_per_bar() + pr.ticks_per_beat * 3
+        );
+        assert_eq!(
+            pr.bar_at_tick(pr.ticks_per_bar() + 10).time_signature,
+            (3, 4)
+        );
+    }
+
     #[test]
     fn beat_to_tick_uses_ticks_per_beat() {
         let pr = PianoRollState::new();
         assert_eq!(pr.beat_to_tick(2), pr.ticks_per_beat * 2);
     }
 
+    #[test]
+    fn record_expression_lands_in_sounding_note() {
+        let mut pr = PianoRollState::new();
+        let id = InstrumentId::new(1);
+        pr.add_track(id);
+        pr.toggle_note(0, 60, 0, 480, 100);
+        pr.toggle_note(0, 60, 480, 480, 100);
+
+        assert!(pr.record_expression(id, 60, 600, ExpressionDimension::Bend, 2.0));
+        assert!(!pr.record_expression(id, 62, 600, ExpressionDimension::Bend, 2.0));
+        assert!(!pr.record_expression(id, 60, 960, ExpressionDimension::Bend, 2.0));
+
+        let track = pr.track_at(0).unwrap();
+        assert!(track.notes[0].expression.is_none());
+        let expr = track.notes[1].expression.as_ref().unwrap();
+        assert_eq!(expr.value_at(ExpressionDimension::Bend, 120), Some(2.0));
+    }
+
     #[test]
     fn notes_stay_sorted_after_toggle() {
         let mut pr = PianoRollState::new();
@@ -1,3 +1,4 @@
+use super::NoteTake;
 use crate::InstrumentId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -19,12 +20,21 @@ pub struct RecordingState {
     /// Path to a recently stopped recording, pending waveform load
     #[serde(skip)]
     pub pending_recording_path: Option<PathBuf>,
+    /// Tick where take recording starts (recording starts at the playhead when unset)
+    #[serde(skip)]
+    pub punch_in: Option<u32>,
+    /// Tick where take recording stops (recording runs until stopped when unset)
+    #[serde(skip)]
+    pub punch_out: Option<u32>,
     /// Tracks armed for recording (instrument IDs)
     #[serde(skip)]
     pub armed_tracks: HashSet<InstrumentId>,
     /// Tracks currently recording (instrument IDs)
     #[serde(skip)]
     pub recording_tracks: HashSet<InstrumentId>,
+    /// MIDI notes being recorded into the piano roll
+    #[serde(skip)]
+    pub note_take: Option<NoteTake>,
 }
 
 impl RecordingState {
@@ -80,6 +90,26 @@ impl RecordingState {
     pub fn get_armed_tracks(&self) -> Vec<InstrumentId> {
         self.armed_tracks.iter().copied().collect()
     }
+
+    /// Set the punch-in point, dropping a punch-out that would come before it
+    pub fn set_punch_in(&mut self, tick: Option<u32>) {
+        self.punch_in = tick;
+        if let (Some(start), Some(end)) = (self.punch_in, self.punch_out) {
+            if end <= start {
+                self.punch_out = None;
+            }
+        }
+    }
+
+    /// Set the punch-out point, dropping a punch-in that would come after it
+    pub fn set_punch_out(&mut self, tick: Option<u32>) {
+        self.punch_out = tick;
+        if let (Some(start), Some(end)) = (self.punch_in, self.punch_out) {
+            if end <= start {
+                self.punch_in = None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -94,4 +124,19 @@ mod tests {
         assert_eq!(state.recording_secs, 0);
         assert!(state.pending_recording_path.is_none());
     }
+
+    #[test]
+    fn punch_points_stay_ordered() {
+        let mut state = RecordingState::default();
+        state.set_punch_in(Some(960));
+        state.set_punch_out(Some(1920));
+        assert_eq!((state.punch_in, state.punch_out), (Some(960), Some(1920)));
+
+        // Moving punch-in past punch-out clears punch-out
+        state.set_punch_in(Some(2400));
+        assert_eq!((state.punch_in, state.punch_out), (Some(2400), None));
+
+        state.set_punch_out(Some(480));
+        assert_eq!((state.punch_in, state.punch_out), (None, Some(480)));
+    }
 }
@@ -7,6 +7,19 @@ use serde::{Deserialize, Serialize};
 pub type BufferId = u32;
 pub type SliceId = u32;
 
+/// Range accepted for a sample's own tempo
+pub const SOURCE_BPM_MIN: f32 = 20.0;
+pub const SOURCE_BPM_MAX: f32 = 400.0;
+
+/// Playback speed factor that conforms material recorded at `source_bpm` to
+/// `project_bpm`. 1.0 when the source tempo is unknown.
+pub fn warp_ratio(source_bpm: Option<f32>, project_bpm: f32) -> f32 {
+    match source_bpm {
+        Some(source) if source > 0.0 && project_bpm > 0.0 => project_bpm / source,
+        _ => 1.0,
+    }
+}
+
 /// A loaded sample buffer
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SampleBuffer {
@@ -91,6 +104,12 @@ pub struct SamplerConfig {
     pub pitch_tracking: bool,
     /// Next slice ID for auto-increment
     pub next_slice_id: SliceId,
+    /// Tempo the loaded sample was recorded at (detected on load, editable)
+    #[serde(default)]
+    pub source_bpm: Option<f32>,
+    /// Warp playback to the project tempo using `source_bpm`
+    #[serde(default)]
+    pub warp: bool,
 }
 
 impl SamplerConfig {
@@ -104,6 +123,8 @@ impl SamplerConfig {
             loop_mode: false,
             pitch_tracking: true,
             next_slice_id: 0,
+            source_bpm: None,
+            warp: false,
         };
         // Add initial full-buffer slice
         config.add_slice(0.0, 1.0);
@@ -161,6 +182,15 @@ impl SamplerConfig {
         self.next_slice_id = id;
     }
 
+    /// Playback speed factor for the current project tempo (1.0 unless warping)
+    pub fn warp_ratio(&self, project_bpm: f32) -> f32 {
+        if self.warp {
+            warp_ratio(self.source_bpm, project_bpm)
+        } else {
+            1.0
+        }
+    }
+
     /// Find which slice to play for a given MIDI note (in mapped mode)
     pub fn slice_for_note(&self, note: u8) -> Option<&Slice> {
         // Simple mapping: notes 0-127 map to slices modulo slice count
@@ -251,6 +281,20 @@ mod tests {
         assert_eq!(config.slices.len(), 1);
     }
 
+    #[test]
+    fn test_warp_ratio_follows_project_tempo() {
+        let mut config = SamplerConfig::new();
+        config.source_bpm = Some(100.0);
+        assert_eq!(config.warp_ratio(120.0), 1.0); // warp off
+
+        config.warp = true;
+        assert!((config.warp_ratio(120.0) - 1.2).abs() < 1e-6);
+        assert!((config.warp_ratio(50.0) - 0.5).abs() < 1e-6);
+
+        config.source_bpm = None;
+        assert_eq!(config.warp_ratio(120.0), 1.0);
+    }
+
     #[test]
     fn test_sample_registry() {
         let mut registry = SampleRegistry::new();
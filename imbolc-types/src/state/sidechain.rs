@@ -0,0 +1,315 @@
+//! Sidechain key routing: which signal drives the key input of SidechainComp,
+//! the Vocoder carrier and EnvFollower, and the instrument build order that
+//! computes every key before the effects listening to it.
+
+use serde::{Deserialize, Serialize};
+
+use super::effect_chain_preset::EffectChainOwner;
+use super::instrument::{EffectSlot, EffectType, Instrument, ProcessingStage, SendTapPoint};
+use super::instrument_state::InstrumentState;
+use super::session::SessionState;
+use crate::InstrumentId;
+
+/// Signal feeding an effect's key input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SidechainSource {
+    /// An instrument's own signal, tapped before or after its inserts (always pre-fader)
+    Instrument { id: InstrumentId, tap: SendTapPoint },
+    /// Summed post-insert signal of a layer group's members
+    LayerGroup(u32),
+}
+
+impl SidechainSource {
+    /// Short label for effect headers, e.g. "Kick post-fx" or "Group 2".
+    pub fn label(&self, instruments: &InstrumentState) -> String {
+        match self {
+            SidechainSource::Instrument { id, tap } => {
+                let name = instruments
+                    .instrument(*id)
+                    .map(|inst| inst.name.clone())
+                    .unwrap_or_else(|| format!("I{} (missing)", id));
+                let tap = match tap {
+                    SendTapPoint::PreInsert => "pre-fx",
+                    SendTapPoint::PostInsert => "post-fx",
+                };
+                format!("{} {}", name, tap)
+            }
+            SidechainSource::LayerGroup(group_id) => format!("Group {}", group_id),
+        }
+    }
+
+    /// Whether `instrument` produces (part of) this key.
+    pub fn is_fed_by(&self, instrument: &Instrument) -> bool {
+        match self {
+            SidechainSource::Instrument { id, .. } => *id == instrument.id,
+            SidechainSource::LayerGroup(group_id) => instrument.layer.group == Some(*group_id),
+        }
+    }
+
+    /// The source after `current` in `candidates`, wrapping through "no key".
+    pub fn cycle(
+        current: Option<SidechainSource>,
+        candidates: &[SidechainSource],
+    ) -> Option<SidechainSource> {
+        match current.and_then(|c| candidates.iter().position(|s| *s == c)) {
+            Some(pos) => candidates.get(pos + 1).copied(),
+            None if current.is_some() => None,
+            None => candidates.first().copied(),
+        }
+    }
+}
+
+impl EffectType {
+    /// Synth control that receives the key bus, for effects with a key input.
+    pub fn sidechain_control(&self) -> Option<&'static str> {
+        match self {
+            EffectType::SidechainComp | EffectType::EnvFollower => Some("sidechain_in"),
+            EffectType::Vocoder => Some("carrier_in"),
+            _ => None,
+        }
+    }
+}
+
+impl EffectSlot {
+    /// The key source this effect listens to, if it has a key input.
+    pub fn sidechain_source(&self) -> Option<SidechainSource> {
+        self.effect_type.sidechain_control().and(self.sidechain)
+    }
+}
+
+/// Drop key sources from a chain headed for a preset; they name instruments
+/// and layer groups that only exist in the project it came from.
+pub(crate) fn detach_sidechains(stages: &mut [ProcessingStage]) {
+    for stage in stages {
+        if let ProcessingStage::Effect(slot) = stage {
+            slot.sidechain = None;
+        }
+    }
+}
+
+fn instrument_keys(instrument: &Instrument) -> impl Iterator<Item = SidechainSource> + '_ {
+    instrument.effects().filter_map(|e| e.sidechain_source())
+}
+
+/// Every key source referenced by an instrument, bus or layer group effect.
+/// Bypassed effects count, so toggling them never needs new key feeds.
+pub fn sidechain_sources(
+    instruments: &InstrumentState,
+    session: &SessionState,
+) -> Vec<SidechainSource> {
+    let mut sources: Vec<SidechainSource> = Vec::new();
+    let bus_effects = session
+        .mixer
+        .buses
+        .iter()
+        .flat_map(|b| &b.effect_chain.effects);
+    let group_effects = session
+        .mixer
+        .layer_group_mixers
+        .iter()
+        .flat_map(|g| &g.effect_chain.effects);
+    let keys = instruments
+        .instruments
+        .iter()
+        .flat_map(instrument_keys)
+        .chain(
+            bus_effects
+                .chain(group_effects)
+                .filter_map(|e| e.sidechain_source()),
+        );
+    for key in keys {
+        if !sources.contains(&key) {
+            sources.push(key);
+        }
+    }
+    sources
+}
+
+impl InstrumentState {
+    /// Instrument indexes in the order their chains must be built so every
+    /// instrument feeding a key comes before the instruments listening to it.
+    /// Otherwise keeps the existing order; cycles are broken where they close.
+    pub fn sidechain_build_order(&self) -> Vec<usize> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Visiting,
+            Done,
+        }
+
+        fn visit(state: &InstrumentState, i: usize, marks: &mut [Mark], order: &mut Vec<usize>) {
+            if marks[i] != Mark::New {
+                return;
+            }
+            marks[i] = Mark::Visiting;
+            for key in instrument_keys(&state.instruments[i]) {
+                for (j, feeder) in state.instruments.iter().enumerate() {
+                    if j != i && key.is_fed_by(feeder) {
+                        visit(state, j, marks, order);
+                    }
+                }
+            }
+            marks[i] = Mark::Done;
+            order.push(i);
+        }
+
+        let mut marks = vec![Mark::New; self.instruments.len()];
+        let mut order = Vec::with_capacity(self.instruments.len());
+        for i in 0..self.instruments.len() {
+            visit(self, i, &mut marks, &mut order);
+        }
+        order
+    }
+
+    /// Key sources an effect on `owner` can pick from, in picker order. An
+    /// instrument can't key itself or the layer group it belongs to.
+    pub fn sidechain_candidates(&self, owner: EffectChainOwner) -> Vec<SidechainSource> {
+        let (own_id, own_group) = match owner {
+            EffectChainOwner::Instrument(id) => {
+                (Some(id), self.instrument(id).and_then(|i| i.layer.group))
+            }
+            EffectChainOwner::LayerGroup(group_id) => (None, Some(group_id)),
+            EffectChainOwner::Bus(_) => (None, None),
+        };
+        let mut candidates = Vec::new();
+        for inst in &self.instruments {
+            if Some(inst.id) == own_id {
+                continue;
+            }
+            for tap in [SendTapPoint::PostInsert, SendTapPoint::PreInsert] {
+                candidates.push(SidechainSource::Instrument { id: inst.id, tap });
+            }
+        }
+        for group_id in self.active_layer_groups() {
+            if Some(group_id) != own_group {
+                candidates.push(SidechainSource::LayerGroup(group_id));
+            }
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::mixer::DEFAULT_BUS_COUNT;
+    use crate::state::session::MusicalSettings;
+    use crate::{BusId, SourceType};
+
+    fn keyed_comp(instruments: &mut InstrumentState, on: InstrumentId, key: SidechainSource) {
+        let inst = instruments.instrument_mut(on).unwrap();
+        let effect_id = inst.add_effect(EffectType::SidechainComp);
+        inst.effect_by_id_mut(effect_id).unwrap().sidechain = Some(key);
+    }
+
+    #[test]
+    fn build_order_puts_key_before_listener() {
+        let mut instruments = InstrumentState::new();
+        let bass = instruments.add_instrument(SourceType::Saw);
+        let kick = instruments.add_instrument(SourceType::Saw);
+        keyed_comp(
+            &mut instruments,
+            bass,
+            SidechainSource::Instrument {
+                id: kick,
+                tap: SendTapPoint::PostInsert,
+            },
+        );
+        assert_eq!(instruments.sidechain_build_order(), vec![1, 0]);
+    }
+
+    #[test]
+    fn build_order_waits_for_every_group_member() {
+        let mut instruments = InstrumentState::new();
+        let pad = instruments.add_instrument(SourceType::Saw);
+        let kick = instruments.add_instrument(SourceType::Saw);
+        let snare = instruments.add_instrument(SourceType::Saw);
+        instruments.instrument_mut(kick).unwrap().layer.group = Some(2);
+        instruments.instrument_mut(snare).unwrap().layer.group = Some(2);
+        keyed_comp(&mut instruments, pad, SidechainSource::LayerGroup(2));
+        assert_eq!(instruments.sidechain_build_order(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn build_order_survives_cycles() {
+        let mut instruments = InstrumentState::new();
+        let a = instruments.add_instrument(SourceType::Saw);
+        let b = instruments.add_instrument(SourceType::Saw);
+        let post = SendTapPoint::PostInsert;
+        keyed_comp(
+            &mut instruments,
+            a,
+            SidechainSource::Instrument { id: b, tap: post },
+        );
+        keyed_comp(
+            &mut instruments,
+            b,
+            SidechainSource::Instrument { id: a, tap: post },
+        );
+        let mut order = instruments.sidechain_build_order();
+        order.sort_unstable();
+        assert_eq!(order, vec![0, 1]);
+    }
+
+    #[test]
+    fn sources_only_count_effects_with_a_key_input() {
+        let mut instruments = InstrumentState::new();
+        let mut session =
+            SessionState::new_with_defaults(MusicalSettings::default(), DEFAULT_BUS_COUNT);
+        let kick = instruments.add_instrument(SourceType::Saw);
+        let bus_id = BusId::new(1);
+        let bus = session.bus_mut(bus_id).unwrap();
+        let vocoder = bus.effect_chain.add_effect(EffectType::Vocoder);
+        let delay = bus.effect_chain.add_effect(EffectType::Delay);
+        let key = SidechainSource::Instrument {
+            id: kick,
+            tap: SendTapPoint::PreInsert,
+        };
+        bus.effect_chain
+            .effect_by_id_mut(vocoder)
+            .unwrap()
+            .sidechain = Some(key);
+        bus.effect_chain.effect_by_id_mut(delay).unwrap().sidechain =
+            Some(SidechainSource::LayerGroup(9));
+        assert_eq!(sidechain_sources(&instruments, &session), vec![key]);
+    }
+
+    #[test]
+    fn candidates_exclude_own_instrument_and_group() {
+        let mut instruments = InstrumentState::new();
+        let kick = instruments.add_instrument(SourceType::Saw);
+        let bass = instruments.add_instrument(SourceType::Saw);
+        instruments.instrument_mut(bass).unwrap().layer.group = Some(1);
+        let candidates = instruments.sidechain_candidates(EffectChainOwner::Instrument(bass));
+        assert_eq!(
+            candidates,
+            vec![
+                SidechainSource::Instrument {
+                    id: kick,
+                    tap: SendTapPoint::PostInsert
+                },
+                SidechainSource::Instrument {
+                    id: kick,
+                    tap: SendTapPoint::PreInsert
+                },
+            ]
+        );
+        let bus_candidates = instruments.sidechain_candidates(EffectChainOwner::Bus(BusId::new(1)));
+        assert_eq!(bus_candidates.len(), 5);
+        assert_eq!(bus_candidates[4], SidechainSource::LayerGroup(1));
+    }
+
+    #[test]
+    fn cycle_wraps_through_no_key() {
+        let a = SidechainSource::LayerGroup(1);
+        let b = SidechainSource::LayerGroup(2);
+        assert_eq!(SidechainSource::cycle(None, &[a, b]), Some(a));
+        assert_eq!(SidechainSource::cycle(Some(a), &[a, b]), Some(b));
+        assert_eq!(SidechainSource::cycle(Some(b), &[a, b]), None);
+        // A stale source (deleted instrument) resets to no key
+        assert_eq!(
+            SidechainSource::cycle(Some(SidechainSource::LayerGroup(7)), &[a]),
+            None
+        );
+    }
+}
@@ -0,0 +1,617 @@
+//! Tempo map: explicit tempo and meter changes on the arrangement timeline.
+//!
+//! The piano roll's `bpm` and `time_signature` are the base values in effect
+//! before the first change. A tempo event holds from its tick until the next
+//! one, or ramps linearly (in BPM per tick) to the next event's tempo when
+//! `ramp` is set. Meter changes are keyed by bar so bar lines never fall
+//! mid-bar.
+//!
+//! Structural edits (insert, delete or duplicate a stretch of ticks) move
+//! meter changes by their start tick and re-key them to whatever bar that
+//! tick falls in afterwards.
+
+use serde::{Deserialize, Serialize};
+
+/// Tempo range accepted for tempo events (matches the BPM automation lane)
+pub const TEMPO_MIN_BPM: f32 = 30.0;
+pub const TEMPO_MAX_BPM: f32 = 300.0;
+
+/// A tempo change at a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoEvent {
+    pub tick: u32,
+    pub bpm: f32,
+    /// Ramp linearly from this tempo to the next event's tempo
+    pub ramp: bool,
+}
+
+/// A time signature change taking effect at the start of a bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeterChange {
+    /// Zero-based bar index
+    pub bar: u32,
+    pub time_signature: (u8, u8),
+}
+
+/// Where a tick falls in the bar grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarPosition {
+    /// Zero-based bar index
+    pub bar: u32,
+    pub bar_start: u32,
+    pub ticks_per_bar: u32,
+    pub time_signature: (u8, u8),
+}
+
+impl BarPosition {
+    /// Zero-based beat within the bar.
+    pub fn beat(&self, tick: u32, ticks_per_beat: u32) -> u32 {
+        tick.saturating_sub(self.bar_start) / ticks_per_beat.max(1)
+    }
+}
+
+/// Tempo and meter changes, each kept sorted and unique by position.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TempoMap {
+    pub tempos: Vec<TempoEvent>,
+    pub meters: Vec<MeterChange>,
+}
+
+/// A stretch of the timeline with a constant or linearly ramping tempo.
+struct Segment {
+    start: u32,
+    end: Option<u32>,
+    bpm_start: f64,
+    bpm_end: f64,
+}
+
+impl Segment {
+    /// BPM change per tick (zero for constant segments)
+    fn slope(&self) -> f64 {
+        match self.end {
+            Some(end) if end > self.start && self.bpm_end != self.bpm_start => {
+                (self.bpm_end - self.bpm_start) / (end - self.start) as f64
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn bpm_at(&self, tick: f64) -> f64 {
+        self.bpm_start + self.slope() * (tick - self.start as f64)
+    }
+
+    /// Seconds between two ticks inside this segment.
+    fn secs(&self, from: f64, to: f64, ticks_per_beat: f64) -> f64 {
+        let slope = self.slope();
+        if slope.abs() < 1e-12 {
+            return (to - from) * 60.0 / (self.bpm_start * ticks_per_beat);
+        }
+        60.0 / (ticks_per_beat * slope) * (self.bpm_at(to) / self.bpm_at(from)).ln()
+    }
+
+    /// Ticks covered in `secs` starting at `from`, ignoring the segment end.
+    fn ticks(&self, from: f64, secs: f64, ticks_per_beat: f64) -> f64 {
+        let slope = self.slope();
+        let bpm_from = self.bpm_at(from);
+        if slope.abs() < 1e-12 {
+            return secs * bpm_from * ticks_per_beat / 60.0;
+        }
+        let bpm_to = bpm_from * (secs * ticks_per_beat * slope / 60.0).exp();
+        (bpm_to - bpm_from) / slope
+    }
+}
+
+impl TempoMap {
+    pub fn is_empty(&self) -> bool {
+        self.tempos.is_empty() && self.meters.is_empty()
+    }
+
+    /// Insert a tempo event, replacing any at the same tick.
+    pub fn set_tempo(&mut self, event: TempoEvent) {
+        let event = TempoEvent {
+            bpm: event.bpm.clamp(TEMPO_MIN_BPM, TEMPO_MAX_BPM),
+            ..event
+        };
+        match self.tempos.binary_search_by_key(&event.tick, |e| e.tick) {
+            Ok(idx) => self.tempos[idx] = event,
+            Err(idx) => self.tempos.insert(idx, event),
+        }
+    }
+
+    /// Remove the tempo event at `tick`. Returns whether one was removed.
+    pub fn remove_tempo(&mut self, tick: u32) -> bool {
+        match self.tempos.binary_search_by_key(&tick, |e| e.tick) {
+            Ok(idx) => {
+                self.tempos.remove(idx);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The tempo event exactly at `tick`, if any.
+    pub fn tempo_event(&self, tick: u32) -> Option<&TempoEvent> {
+        self.tempos
+            .binary_search_by_key(&tick, |e| e.tick)
+            .ok()
+            .map(|idx| &self.tempos[idx])
+    }
+
+    /// Insert a meter change, replacing any at the same bar.
+    pub fn set_meter(&mut self, change: MeterChange) {
+        let (num, denom) = change.time_signature;
+        let change = MeterChange {
+            time_signature: (num.max(1), denom.max(1)),
+            ..change
+        };
+        match self.meters.binary_search_by_key(&change.bar, |m| m.bar) {
+            Ok(idx) => self.meters[idx] = change,
+            Err(idx) => self.meters.insert(idx, change),
+        }
+    }
+
+    /// Remove the meter change at `bar`. Returns whether one was removed.
+    pub fn remove_meter(&mut self, bar: u32) -> bool {
+        match self.meters.binary_search_by_key(&bar, |m| m.bar) {
+            Ok(idx) => {
+                self.meters.remove(idx);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The meter change exactly at `bar`, if any.
+    pub fn meter_change(&self, bar: u32) -> Option<&MeterChange> {
+        self.meters
+            .binary_search_by_key(&bar, |m| m.bar)
+            .ok()
+            .map(|idx| &self.meters[idx])
+    }
+
+    fn segment_at(&self, base_bpm: f32, tick: f64) -> Segment {
+        let idx = self.tempos.partition_point(|e| e.tick as f64 <= tick);
+        if idx == 0 {
+            let base = base_bpm.max(1.0) as f64;
+            return Segment {
+                start: 0,
+                end: self.tempos.first().map(|e| e.tick),
+                bpm_start: base,
+                bpm_end: base,
+            };
+        }
+        let event = &self.tempos[idx - 1];
+        let next = self.tempos.get(idx);
+        Segment {
+            start: event.tick,
+            end: next.map(|e| e.tick),
+            bpm_start: event.bpm as f64,
+            bpm_end: match next {
+                Some(next) if event.ramp => next.bpm as f64,
+                _ => event.bpm as f64,
+            },
+        }
+    }
+
+    /// Tempo in effect at `tick`.
+    pub fn bpm_at(&self, base_bpm: f32, tick: f64) -> f32 {
+        self.segment_at(base_bpm, tick).bpm_at(tick) as f32
+    }
+
+    /// Seconds from tick `from` to tick `to` (negative if `to` is earlier).
+    pub fn secs_between(&self, base_bpm: f32, ticks_per_beat: u32, from: f64, to: f64) -> f64 {
+        if to < from {
+            return -self.secs_between(base_bpm, ticks_per_beat, to, from);
+        }
+        let tpb = ticks_per_beat.max(1) as f64;
+        let mut secs = 0.0;
+        let mut pos = from;
+        while pos < to {
+            let segment = self.segment_at(base_bpm, pos);
+            let seg_end = segment.end.map_or(to, |end| (end as f64).min(to));
+            secs += segment.secs(pos, seg_end, tpb);
+            pos = seg_end;
+        }
+        secs
+    }
+
+    /// Ticks covered by `secs` of playback starting at tick `from`.
+    pub fn ticks_after(&self, base_bpm: f32, ticks_per_beat: u32, from: f64, secs: f64) -> f64 {
+        let tpb = ticks_per_beat.max(1) as f64;
+        let mut remaining = secs.max(0.0);
+        let mut pos = from;
+        loop {
+            let segment = self.segment_at(base_bpm, pos);
+            if let Some(end) = segment.end {
+                let to_end = segment.secs(pos, end as f64, tpb);
+                if remaining >= to_end {
+                    remaining -= to_end;
+                    pos = end as f64;
+                    continue;
+                }
+            }
+            return pos + segment.ticks(pos, remaining, tpb) - from;
+        }
+    }
+
+    /// Time signature in effect for `bar`.
+    pub fn time_signature_at_bar(&self, base: (u8, u8), bar: u32) -> (u8, u8) {
+        let idx = self.meters.partition_point(|m| m.bar <= bar);
+        if idx == 0 {
+            base
+        } else {
+            self.meters[idx - 1].time_signature
+        }
+    }
+
+    /// Bar containing `tick`. Bar length is `ticks_per_beat * numerator`.
+    pub fn bar_at_tick(&self, base: (u8, u8), ticks_per_beat: u32, tick: u32) -> BarPosition {
+        let tpb = ticks_per_beat.max(1);
+        let mut bar = 0;
+        let mut bar_start = 0u32;
+        let mut time_signature = base;
+        for change in &self.meters {
+            let ticks_per_bar = tpb * time_signature.0.max(1) as u32;
+            let change_start = bar_start
+                .saturating_add(change.bar.saturating_sub(bar).saturating_mul(ticks_per_bar));
+            if change_start > tick {
+                break;
+            }
+            bar = change.bar;
+            bar_start = change_start;
+            time_signature = change.time_signature;
+        }
+        let ticks_per_bar = tpb * time_signature.0.max(1) as u32;
+        let bars_in = (tick - bar_start) / ticks_per_bar;
+        BarPosition {
+            bar: bar + bars_in,
+            bar_start: bar_start + bars_in * ticks_per_bar,
+            ticks_per_bar,
+            time_signature,
+        }
+    }
+
+    /// Tick at which `bar` starts.
+    pub fn bar_start_tick(&self, base: (u8, u8), ticks_per_beat: u32, bar: u32) -> u32 {
+        let tpb = ticks_per_beat.max(1);
+        let mut current_bar = 0;
+        let mut tick = 0u32;
+        let mut time_signature = base;
+        for change in self.meters.iter().take_while(|m| m.bar <= bar) {
+            let ticks_per_bar = tpb * time_signature.0.max(1) as u32;
+            tick = tick.saturating_add((change.bar - current_bar).saturating_mul(ticks_per_bar));
+            current_bar = change.bar;
+            time_signature = change.time_signature;
+        }
+        let ticks_per_bar = tpb * time_signature.0.max(1) as u32;
+        tick.saturating_add((bar - current_bar).saturating_mul(ticks_per_bar))
+    }
+
+    /// Open `len` ticks at `at`: tempo and meter changes at or after it move right.
+    pub fn insert_time(&mut self, base: (u8, u8), ticks_per_beat: u32, at: u32, len: u32) {
+        for event in self.tempos.iter_mut().filter(|e| e.tick >= at) {
+            event.tick += len;
+        }
+        let mut meters = self.meter_ticks(base, ticks_per_beat);
+        for (tick, _) in meters.iter_mut().filter(|(tick, _)| *tick >= at) {
+            *tick += len;
+        }
+        self.set_meter_ticks(base, ticks_per_beat, meters);
+    }
+
+    /// Cut `[start, end)` out of the map. The last change inside the cut is
+    /// kept at `start` so the tempo and meter after the cut are unchanged.
+    pub fn delete_time(&mut self, base: (u8, u8), ticks_per_beat: u32, start: u32, end: u32) {
+        if end <= start {
+            return;
+        }
+        let len = end - start;
+        let cut = |tick: u32| tick >= start && tick < end;
+
+        // A change right at `end` already covers what follows the cut
+        let carried = self
+            .tempos
+            .iter()
+            .rev()
+            .find(|e| cut(e.tick))
+            .filter(|_| self.tempo_event(end).is_none())
+            .copied();
+        self.tempos.retain(|e| !cut(e.tick));
+        for event in self.tempos.iter_mut().filter(|e| e.tick >= end) {
+            event.tick -= len;
+        }
+        if let Some(event) = carried {
+            self.set_tempo(TempoEvent {
+                tick: start,
+                ..event
+            });
+        }
+
+        let mut meters = self.meter_ticks(base, ticks_per_beat);
+        let carried = meters
+            .iter()
+            .rev()
+            .find(|(tick, _)| cut(*tick))
+            .filter(|_| !meters.iter().any(|(tick, _)| *tick == end))
+            .copied();
+        meters.retain(|(tick, _)| !cut(*tick));
+        for (tick, _) in meters.iter_mut().filter(|(tick, _)| *tick >= end) {
+            *tick -= len;
+        }
+        if let Some((_, time_signature)) = carried {
+            meters.push((start, time_signature));
+        }
+        self.set_meter_ticks(base, ticks_per_beat, meters);
+    }
+
+    /// Repeat the changes in `[start, end)` right after the range. When the
+    /// range holds changes, the tempo and meter in effect at `start` are
+    /// restated at the copy's start so both passes sound the same.
+    pub fn duplicate_time(
+        &mut self,
+        base_bpm: f32,
+        base: (u8, u8),
+        ticks_per_beat: u32,
+        start: u32,
+        end: u32,
+    ) {
+        if end <= start {
+            return;
+        }
+        let len = end - start;
+        let inside = |tick: u32| tick >= start && tick < end;
+
+        let bpm_at_start = self.bpm_at(base_bpm, start as f64);
+        let meter_at_start = self.bar_at_tick(base, ticks_per_beat, start).time_signature;
+        let tempo_copies: Vec<TempoEvent> = self
+            .tempos
+            .iter()
+            .filter(|e| inside(e.tick))
+            .map(|e| TempoEvent {
+                tick: e.tick + len,
+                ..*e
+            })
+            .collect();
+        let meter_copies: Vec<(u32, (u8, u8))> = self
+            .meter_ticks(base, ticks_per_beat)
+            .into_iter()
+            .filter(|(tick, _)| inside(*tick))
+            .map(|(tick, time_signature)| (tick + len, time_signature))
+            .collect();
+
+        self.insert_time(base, ticks_per_beat, end, len);
+
+        if !tempo_copies.is_empty() && tempo_copies[0].tick != end {
+            self.set_tempo(TempoEvent {
+                tick: end,
+                bpm: bpm_at_start,
+                ramp: false,
+            });
+        }
+        for event in tempo_copies {
+            self.set_tempo(event);
+        }
+
+        if !meter_copies.is_empty() {
+            let mut meters = self.meter_ticks(base, ticks_per_beat);
+            if meter_copies[0].0 != end {
+                meters.push((end, meter_at_start));
+            }
+            meters.extend(meter_copies);
+            self.set_meter_ticks(base, ticks_per_beat, meters);
+        }
+    }
+
+    /// Meter changes as (start tick, time signature) pairs
+    fn meter_ticks(&self, base: (u8, u8), ticks_per_beat: u32) -> Vec<(u32, (u8, u8))> {
+        self.meters
+            .iter()
+            .map(|m| {
+                (
+                    self.bar_start_tick(base, ticks_per_beat, m.bar),
+                    m.time_signature,
+                )
+            })
+            .collect()
+    }
+
+    /// Rebuild meter changes from start ticks, snapping each to the bar it
+    /// lands in given the changes before it. Later entries win on the same bar.
+    fn set_meter_ticks(
+        &mut self,
+        base: (u8, u8),
+        ticks_per_beat: u32,
+        mut meters: Vec<(u32, (u8, u8))>,
+    ) {
+        meters.sort_by_key(|(tick, _)| *tick);
+        self.meters.clear();
+        for (tick, time_signature) in meters {
+            let bar = self.bar_at_tick(base, ticks_per_beat, tick).bar;
+            self.set_meter(MeterChange {
+                bar,
+                time_signature,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TPB: u32 = 480;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn empty_map_uses_base_tempo() {
+        let map = TempoMap::default();
+        assert_eq!(map.bpm_at(120.0, 10_000.0), 120.0);
+        // One beat at 120 BPM is half a second
+        assert!(close(map.secs_between(120.0, TPB, 0.0, 480.0), 0.5));
+        assert!(close(map.ticks_after(120.0, TPB, 960.0, 0.5), 480.0));
+    }
+
+    #[test]
+    fn step_change_splits_conversion() {
+        let mut map = TempoMap::default();
+        map.set_tempo(TempoEvent {
+            tick: 960,
+            bpm: 60.0,
+            ramp: false,
+        });
+        assert_eq!(map.bpm_at(120.0, 959.0), 120.0);
+        assert_eq!(map.bpm_at(120.0, 960.0), 60.0);
+        // Two beats at 120 then one beat at 60
+        assert!(close(map.secs_between(120.0, TPB, 0.0, 1440.0), 2.0));
+        assert!(close(map.ticks_after(120.0, TPB, 0.0, 2.0), 1440.0));
+        assert!(close(map.secs_between(120.0, TPB, 1440.0, 0.0), -2.0));
+    }
+
+    #[test]
+    fn ramp_interpolates_and_round_trips() {
+        let mut map = TempoMap::default();
+        map.set_tempo(TempoEvent {
+            tick: 0,
+            bpm: 60.0,
+            ramp: true,
+        });
+        map.set_tempo(TempoEvent {
+            tick: 1920,
+            bpm: 120.0,
+            ramp: false,
+        });
+        assert!(close(map.bpm_at(100.0, 960.0) as f64, 90.0));
+        assert_eq!(map.bpm_at(100.0, 5000.0), 120.0);
+        // Accelerating: faster than 60 BPM throughout, slower than 120
+        let secs = map.secs_between(100.0, TPB, 0.0, 1920.0);
+        assert!(secs < 4.0 && secs > 2.0);
+        // 60 * 4 beats / (60 BPM span) * ln 2
+        assert!(close(secs, 4.0 * (2.0f64).ln()));
+        for tick in [100.0, 960.0, 1920.0, 3000.0] {
+            let secs = map.secs_between(100.0, TPB, 0.0, tick);
+            assert!((map.ticks_after(100.0, TPB, 0.0, secs) - tick).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn set_tempo_replaces_and_keeps_order() {
+        let mut map = TempoMap::default();
+        for tick in [960, 0, 480] {
+            map.set_tempo(TempoEvent {
+                tick,
+                bpm: 100.0,
+                ramp: false,
+            });
+        }
+        map.set_tempo(TempoEvent {
+            tick: 480,
+            bpm: 1000.0,
+            ramp: true,
+        });
+        let ticks: Vec<u32> = map.tempos.iter().map(|e| e.tick).collect();
+        assert_eq!(ticks, vec![0, 480, 960]);
+        assert_eq!(map.tempo_event(480).unwrap().bpm, TEMPO_MAX_BPM);
+        assert!(map.remove_tempo(480));
+        assert!(!map.remove_tempo(480));
+    }
+
+    #[test]
+    fn meter_changes_shift_bar_grid() {
+        let mut map = TempoMap::default();
+        map.set_meter(MeterChange {
+            bar: 2,
+            time_signature: (3, 4),
+        });
+        map.set_meter(MeterChange {
+            bar: 4,
+            time_signature: (5, 4),
+        });
+        // Bars 0-1 are 4/4, 2-3 are 3/4, then 5/4
+        assert_eq!(map.bar_start_tick((4, 4), TPB, 2), 3840);
+        assert_eq!(map.bar_start_tick((4, 4), TPB, 3), 3840 + 1440);
+        assert_eq!(map.bar_start_tick((4, 4), TPB, 5), 3840 + 2880 + 2400);
+
+        let pos = map.bar_at_tick((4, 4), TPB, 3840 + 1440 + 480);
+        assert_eq!(pos.bar, 3);
+        assert_eq!(pos.ticks_per_bar, 1440);
+        assert_eq!(pos.beat(3840 + 1440 + 480, TPB), 1);
+
+        let pos = map.bar_at_tick((4, 4), TPB, 3840 + 2880 + 2400 + 10);
+        assert_eq!(pos.bar, 5);
+        assert_eq!(pos.time_signature, (5, 4));
+        assert_eq!(map.time_signature_at_bar((4, 4), 1), (4, 4));
+        assert_eq!(map.time_signature_at_bar((4, 4), 3), (3, 4));
+    }
+
+    #[test]
+    fn bar_at_tick_matches_bar_start_tick() {
+        let mut map = TempoMap::default();
+        map.set_meter(MeterChange {
+            bar: 1,
+            time_signature: (7, 8),
+        });
+        for bar in 0..6 {
+            let start = map.bar_start_tick((4, 4), TPB, bar);
+            let pos = map.bar_at_tick((4, 4), TPB, start);
+            assert_eq!((pos.bar, pos.bar_start), (bar, start));
+        }
+    }
+
+    fn song_map() -> TempoMap {
+        let mut map = TempoMap::default();
+        map.set_tempo(TempoEvent {
+            tick: 1920,
+            bpm: 90.0,
+            ramp: false,
+        });
+        map.set_tempo(TempoEvent {
+            tick: 3840,
+            bpm: 140.0,
+            ramp: false,
+        });
+        map.set_meter(MeterChange {
+            bar: 2,
+            time_signature: (3, 4),
+        });
+        map
+    }
+
+    #[test]
+    fn insert_and_delete_time_move_changes() {
+        let mut map = song_map();
+        map.insert_time((4, 4), TPB, 1920, 1920);
+        let ticks: Vec<u32> = map.tempos.iter().map(|e| e.tick).collect();
+        assert_eq!(ticks, vec![3840, 5760]);
+        assert_eq!(map.meters[0].bar, 3);
+
+        map.delete_time((4, 4), TPB, 1920, 3840);
+        assert_eq!(map, song_map());
+
+        // Cutting across a change keeps the tempo that follows the cut
+        map.delete_time((4, 4), TPB, 0, 2400);
+        assert_eq!(map.tempos[0].tick, 0);
+        assert_eq!(map.tempos[0].bpm, 90.0);
+        assert_eq!(map.tempos[1].tick, 1440);
+    }
+
+    #[test]
+    fn duplicate_time_repeats_changes() {
+        let mut map = song_map();
+        map.duplicate_time(120.0, (4, 4), TPB, 1920, 3840);
+        let tempos: Vec<(u32, f32)> = map.tempos.iter().map(|e| (e.tick, e.bpm)).collect();
+        assert_eq!(tempos, vec![(1920, 90.0), (3840, 90.0), (5760, 140.0)]);
+        assert_eq!(map.meters[0].bar, 3);
+
+        // A copy starting mid-tempo restates the tempo in effect
+        let mut map = song_map();
+        map.duplicate_time(120.0, (4, 4), TPB, 0, 3840);
+        let tempos: Vec<(u32, f32)> = map.tempos.iter().map(|e| (e.tick, e.bpm)).collect();
+        assert_eq!(
+            tempos,
+            vec![(1920, 90.0), (3840, 120.0), (5760, 90.0), (7680, 140.0)]
+        );
+    }
+}
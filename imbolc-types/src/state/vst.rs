@@ -14,7 +14,7 @@ pub enum VstPluginKind {
 }
 
 /// Specification for a VST parameter
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VstParamSpec {
     pub index: u32, // VST param index (0-based)
     pub name: String,
@@ -23,13 +23,16 @@ pub struct VstParamSpec {
 }
 
 /// A registered VST plugin
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VstPlugin {
     pub id: VstPluginId,
     pub name: String,         // display name (from filename)
     pub plugin_path: PathBuf, // path to .vst3/.vst bundle
     pub kind: VstPluginKind,
     pub params: Vec<VstParamSpec>,
+    /// Processing latency reported by the plugin when opened (not persisted)
+    #[serde(default)]
+    pub latency_secs: Option<f32>,
 }
 
 /// Registry of all VST plugins
@@ -103,6 +106,7 @@ mod tests {
             plugin_path: PathBuf::from("/tmp/test.vst3"),
             kind,
             params: vec![],
+            latency_secs: None,
         }
     }
 
@@ -5,9 +5,9 @@ use crate::action::{
 use crate::audio::AudioHandle;
 use crate::dispatch::LocalDispatcher;
 use crate::panes::{
-    AutomationPane, CommandPalettePane, ConfirmPane, DocsPane, FileBrowserPane, FrameEditPane,
-    HelpPane, InstrumentEditPane, PaneSwitcherPane, PendingAction, PianoRollPane, SaveAsPane,
-    SequencerPane, ServerPane, VstParamPane,
+    AddEffectPane, AutomationPane, CommandPalettePane, ConfirmPane, DocsPane, FileBrowserPane,
+    FrameEditPane, HelpPane, InstrumentEditPane, InstrumentPresetPane, PaneSwitcherPane,
+    PendingAction, PianoRollPane, SaveAsPane, SequencerPane, ServerPane, TrackPane, VstParamPane,
 };
 use crate::state::{AppState, ClipboardContents, MixerSelection};
 use crate::ui::action_id::{ActionId, GlobalActionId, PaneId as ShortcutPaneId};
@@ -108,9 +108,7 @@ pub(crate) fn sync_piano_roll_to_selection(
                 }
                 // Set view mode on piano roll when instrument type changes
                 if target == NavPaneId::PianoRoll {
-                    if let Some(pr_pane) =
-                        panes.get_pane_mut::<PianoRollPane>("piano_roll")
-                    {
+                    if let Some(pr_pane) = panes.get_pane_mut::<PianoRollPane>("piano_roll") {
                         use crate::panes::ViewMode;
                         if is_kit {
                             pr_pane.set_view_mode(ViewMode::StepSequencer);
@@ -209,6 +207,15 @@ pub(crate) fn process_text_edit_auto_pop(panes: &mut PaneManager, layer_stack: &
             "server" => panes
                 .get_pane_mut::<ServerPane>("server")
                 .is_some_and(|p| p.is_editing_scsynth_args()),
+            "add_effect" => panes
+                .get_pane_mut::<AddEffectPane>("add_effect")
+                .is_some_and(|p| p.is_editing()),
+            "instrument_presets" => panes
+                .get_pane_mut::<InstrumentPresetPane>("instrument_presets")
+                .is_some_and(|p| p.is_editing()),
+            "track" => panes
+                .get_pane_mut::<TrackPane>("track")
+                .is_some_and(|p| p.is_editing()),
             _ => false,
         };
         if !still_editing {
@@ -513,9 +520,7 @@ pub(crate) fn handle_global_action(
                 switch_to_pane(target, panes, dispatcher, audio, app_frame, layer_stack);
                 // Set view mode after switching
                 if target == NavPaneId::PianoRoll {
-                    if let Some(pr_pane) =
-                        panes.get_pane_mut::<PianoRollPane>("piano_roll")
-                    {
+                    if let Some(pr_pane) = panes.get_pane_mut::<PianoRollPane>("piano_roll") {
                         use crate::panes::ViewMode;
                         if is_kit {
                             pr_pane.set_view_mode(ViewMode::StepSequencer);
@@ -781,7 +786,12 @@ pub(crate) fn handle_global_action(
                 }
             }
             GlobalActionId::CommandPalette => {
-                let commands = layer_stack.collect_commands();
+                let mut commands: Vec<(ActionId, String, String)> = layer_stack
+                    .collect_commands()
+                    .into_iter()
+                    .map(|(action, desc, keybinding)| (action, desc.to_string(), keybinding))
+                    .collect();
+                commands.extend(marker_commands(dispatcher.state()));
                 if let Some(palette) = panes.get_pane_mut::<CommandPalettePane>("command_palette") {
                     palette.open(commands);
                 }
@@ -847,6 +857,18 @@ pub(crate) fn handle_global_action(
                 panes.push_to(NavPaneId::CheckpointList, dispatcher.state());
                 sync_pane_layer(panes, layer_stack);
             }
+            GlobalActionId::OpenInstrumentPresets => {
+                panes.push_to(NavPaneId::InstrumentPresets, dispatcher.state());
+                sync_pane_layer(panes, layer_stack);
+            }
+            GlobalActionId::GotoMarker(id) => {
+                let mut r = dispatcher.dispatch_domain(
+                    &DomainAction::Arrangement(ui::ArrangementAction::JumpToMarker(id)),
+                    audio,
+                );
+                pending_audio_effects.extend(std::mem::take(&mut r.audio_effects));
+                apply_dispatch_result(r, dispatcher, panes, app_frame, audio);
+            }
             GlobalActionId::RequestPrivilege => {
                 // No-op in standalone mode (handled in network client loop)
             }
@@ -856,6 +878,25 @@ pub(crate) fn handle_global_action(
     GlobalResult::Handled
 }
 
+/// Command palette entries for jumping to each arrangement marker.
+fn marker_commands(state: &AppState) -> Vec<(ActionId, String, String)> {
+    let pr = &state.session.piano_roll;
+    state
+        .session
+        .arrangement
+        .markers
+        .iter()
+        .map(|marker| {
+            let bar = pr.bar_at_tick(marker.tick).bar + 1;
+            (
+                ActionId::Global(GlobalActionId::GotoMarker(marker.id)),
+                format!("Jump to {} (bar {})", marker.name, bar),
+                String::new(),
+            )
+        })
+        .collect()
+}
+
 /// Handle a quit intent: check dirty state and show prompt if needed.
 /// Returns `GlobalResult::Quit` for immediate exit, `GlobalResult::Handled` if prompt was shown.
 pub(crate) fn handle_quit_intent(
@@ -12,6 +12,7 @@ mod midi_dispatch;
 #[cfg(feature = "net")]
 mod network;
 mod panes;
+mod repl;
 mod runtime;
 mod setup;
 mod ui;
@@ -20,8 +21,8 @@ use std::fs::File;
 
 use panes::{
     AddEffectPane, AddPane, AutomationPane, CheckpointListPane,
-    CommandPalettePane, ConfirmPane, DocsPane, EqPane, FileBrowserPane, FrameEditPane, GroovePane,
-    HelpPane, HomePane, InstrumentEditPane, InstrumentPane, InstrumentPickerPane,
+    CommandPalettePane, ConfirmPane, DocsPane, EqPane, ExportPane, FileBrowserPane, FrameEditPane, GroovePane,
+    HelpPane, HomePane, InstrumentEditPane, InstrumentPane, InstrumentPickerPane, InstrumentPresetPane,
     MidiSettingsPane, MixerPane, PaneSwitcherPane, PianoRollPane, ProjectBrowserPane,
     QuitPromptPane, SampleChopperPane, SaveAsPane, SequencerPane, ServerPane, TrackPane,
     GenerativePane, TunerPane, VstParamPane, WaveformPane,
@@ -105,6 +106,12 @@ fn main() -> std::io::Result<()> {
         }
     }
 
+    // Text REPL for screen readers: plain stdin/stdout, no terminal setup
+    if args.iter().any(|a| a == "--repl") {
+        let project_arg = args.iter().skip(1).find(|a| !a.starts_with('-')).cloned();
+        return repl::run_repl(project_arg);
+    }
+
     // Install panic hook to restore terminal before printing panic info
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
@@ -231,5 +238,10 @@ pub(crate) fn register_all_panes(
         keymaps,
         "checkpoint_list",
     ))));
+    panes.add_pane(Box::new(InstrumentPresetPane::new(pane_keymap(
+        keymaps,
+        "instrument_presets",
+    ))));
+    panes.add_pane(Box::new(ExportPane::new(pane_keymap(keymaps, "export"))));
     panes
 }
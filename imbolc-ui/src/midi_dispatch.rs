@@ -1,11 +1,38 @@
-use crate::action::{Action, AutomationAction, InstrumentAction};
+use crate::action::{Action, AutomationAction, InstrumentAction, PianoRollAction};
 use crate::midi::{MidiEvent, MidiEventKind};
+use crate::state::instrument::ModController;
+use crate::state::midi_recording::cc;
 use crate::state::AppState;
+use imbolc_types::{ExpressionDimension, InstrumentId};
+
+/// Note held on each MPE member channel, so channel-wide bend, pressure and
+/// slide reach that note's voice.
+#[derive(Debug, Default)]
+pub struct MpeChannels {
+    notes: [Option<u8>; 16],
+}
+
+impl MpeChannels {
+    fn note(&self, channel: u8) -> Option<u8> {
+        self.notes.get(channel as usize).copied().flatten()
+    }
+
+    fn set(&mut self, channel: u8, note: Option<u8>) {
+        if let Some(slot) = self.notes.get_mut(channel as usize) {
+            *slot = note;
+        }
+    }
+}
 
 /// Process a MIDI event and return an Action if one should be dispatched.
-/// The timestamp in MidiEvent can be used for sample-accurate scheduling
-/// (passed through InstrumentAction::PlayNoteWithOffset if needed).
-pub fn process_midi_event(event: &MidiEvent, state: &AppState) -> Option<Action> {
+/// `latency_us` is how long ago the event was played (from its timestamp);
+/// recorded notes are moved back by it.
+pub fn process_midi_event(
+    event: &MidiEvent,
+    state: &AppState,
+    mpe: &mut MpeChannels,
+    latency_us: u64,
+) -> Option<Action> {
     let midi_rec = &state.session.midi_recording;
 
     match &event.kind {
@@ -19,8 +46,24 @@ pub fn process_midi_event(event: &MidiEvent, state: &AppState) -> Option<Action>
                 return None;
             }
 
-            // Look up CC mapping
-            let mapping = midi_rec.find_cc_mapping(*controller, *channel)?;
+            // Slide on an MPE member channel belongs to its note
+            if *controller == cc::SLIDE && midi_rec.is_mpe_member(*channel) {
+                let note = mpe.note(*channel)?;
+                return note_expression_action(
+                    state,
+                    note,
+                    ExpressionDimension::Slide,
+                    *value as f32 / 127.0,
+                );
+            }
+
+            // Look up CC mapping; an unmapped mod wheel feeds the mod matrix
+            let Some(mapping) = midi_rec.find_cc_mapping(*controller, *channel) else {
+                if *controller == cc::MOD_WHEEL {
+                    return mod_controller_action(state, ModController::ModWheel, *value);
+                }
+                return None;
+            };
             let target = mapping.target.clone();
             let mapped_value = mapping.map_value(*value);
 
@@ -40,21 +83,47 @@ pub fn process_midi_event(event: &MidiEvent, state: &AppState) -> Option<Action>
                 return None;
             }
 
+            if midi_rec.is_mpe_member(*channel) {
+                mpe.set(*channel, Some(*note));
+            }
+
             if !midi_rec.note_passthrough {
                 return None;
             }
 
+            // While the piano roll records, notes are taken on the input
+            // instrument's track where they were played
+            if state.session.piano_roll.recording {
+                return Some(Action::PianoRoll(PianoRollAction::RecordNoteOn {
+                    instrument_id: input_instrument(state)?,
+                    pitch: *note,
+                    velocity: *velocity,
+                    latency_us,
+                }));
+            }
+
             // PlayNote uses the selected instrument
             Some(Action::Instrument(InstrumentAction::PlayNote(
                 *note, *velocity,
             )))
         }
 
-        MidiEventKind::NoteOff { channel, .. } => {
+        MidiEventKind::NoteOff { channel, note } => {
             // Note release is handled by voice duration in the audio engine
             if !midi_rec.should_process_channel(*channel) {
                 return None;
             }
+            if mpe.note(*channel) == Some(*note) {
+                mpe.set(*channel, None);
+            }
+            // A recorded note ends when its key comes up
+            if state.session.piano_roll.recording && midi_rec.note_passthrough {
+                return Some(Action::PianoRoll(PianoRollAction::RecordNoteOff {
+                    instrument_id: input_instrument(state)?,
+                    pitch: *note,
+                    latency_us,
+                }));
+            }
             None
         }
 
@@ -63,6 +132,16 @@ pub fn process_midi_event(event: &MidiEvent, state: &AppState) -> Option<Action>
                 return None;
             }
 
+            if midi_rec.is_mpe_member(*channel) {
+                let note = mpe.note(*channel)?;
+                return note_expression_action(
+                    state,
+                    note,
+                    ExpressionDimension::Bend,
+                    midi_rec.mpe_bend_semitones(*value),
+                );
+            }
+
             // Look up pitch bend config for the target instrument
             let instrument_id = midi_rec
                 .live_input_instrument
@@ -78,10 +157,77 @@ pub fn process_midi_event(event: &MidiEvent, state: &AppState) -> Option<Action>
             )))
         }
 
+        MidiEventKind::Aftertouch { channel, pressure } => {
+            if !midi_rec.should_process_channel(*channel) {
+                return None;
+            }
+            if midi_rec.is_mpe_member(*channel) {
+                let note = mpe.note(*channel)?;
+                return note_expression_action(
+                    state,
+                    note,
+                    ExpressionDimension::Pressure,
+                    *pressure as f32 / 127.0,
+                );
+            }
+            mod_controller_action(state, ModController::Aftertouch, *pressure)
+        }
+
+        MidiEventKind::PolyAftertouch {
+            channel,
+            note,
+            pressure,
+        } => {
+            if !midi_rec.should_process_channel(*channel) {
+                return None;
+            }
+            note_expression_action(
+                state,
+                *note,
+                ExpressionDimension::Pressure,
+                *pressure as f32 / 127.0,
+            )
+        }
+
         _ => None,
     }
 }
 
+/// Instrument that live MIDI input plays: the live input instrument, or the selected one.
+fn input_instrument(state: &AppState) -> Option<InstrumentId> {
+    state
+        .session
+        .midi_recording
+        .live_input_instrument
+        .or_else(|| state.instruments.selected_instrument().map(|i| i.id))
+}
+
+/// Route a controller value to the live input (or selected) instrument's mod matrix.
+fn mod_controller_action(state: &AppState, controller: ModController, value: u8) -> Option<Action> {
+    let instrument_id = input_instrument(state)?;
+    Some(Action::Instrument(InstrumentAction::SetModController(
+        instrument_id,
+        controller,
+        value as f32 / 127.0,
+    )))
+}
+
+/// Route a per-note expression value to the input instrument's voice at `note`.
+fn note_expression_action(
+    state: &AppState,
+    note: u8,
+    dimension: ExpressionDimension,
+    value: f32,
+) -> Option<Action> {
+    let instrument_id = input_instrument(state)?;
+    Some(Action::Instrument(InstrumentAction::NoteExpression(
+        instrument_id,
+        note,
+        dimension,
+        value,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,7 +259,7 @@ mod tests {
                 value: 64,
             },
         );
-        let action = process_midi_event(&event, &state);
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
         assert!(action.is_some());
     }
 
@@ -128,7 +274,7 @@ mod tests {
                 value: 64,
             },
         );
-        let action = process_midi_event(&event, &state);
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
         assert!(action.is_none());
     }
 
@@ -144,7 +290,7 @@ mod tests {
                 value: 64,
             },
         );
-        let action = process_midi_event(&event, &state);
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
         assert!(action.is_none());
     }
 
@@ -160,7 +306,7 @@ mod tests {
                 velocity: 100,
             },
         );
-        let action = process_midi_event(&event, &state);
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
         // PlayNote dispatches to selected instrument, which will be a no-op if none
         assert!(action.is_some());
     }
@@ -177,7 +323,154 @@ mod tests {
                 velocity: 100,
             },
         );
-        let action = process_midi_event(&event, &state);
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
         assert!(action.is_none());
     }
+
+    #[test]
+    fn test_unmapped_mod_wheel_drives_mod_matrix() {
+        let mut state = AppState::new();
+        let id = state.add_instrument(crate::state::SourceType::Saw);
+        let event = MidiEvent::new(
+            0,
+            MidiEventKind::ControlChange {
+                channel: 0,
+                controller: cc::MOD_WHEEL,
+                value: 127,
+            },
+        );
+        let action = process_midi_event(&event, &state, &mut MpeChannels::default(), 0);
+        assert!(matches!(
+            action,
+            Some(Action::Instrument(InstrumentAction::SetModController(
+                target,
+                ModController::ModWheel,
+                v
+            ))) if target == id && v == 1.0
+        ));
+    }
+
+    #[test]
+    fn test_aftertouch_without_instrument_returns_none() {
+        let state = AppState::new();
+        let event = MidiEvent::new(
+            0,
+            MidiEventKind::Aftertouch {
+                channel: 0,
+                pressure: 64,
+            },
+        );
+        assert!(process_midi_event(&event, &state, &mut MpeChannels::default(), 0).is_none());
+    }
+
+    #[test]
+    fn test_mpe_member_bend_follows_its_note() {
+        let mut state = AppState::new();
+        let id = state.add_instrument(crate::state::SourceType::Saw);
+        state.session.midi_recording.mpe = true;
+        state.session.midi_recording.mpe_bend_range = 48;
+        let mut mpe = MpeChannels::default();
+
+        let note_on = MidiEvent::new(
+            0,
+            MidiEventKind::NoteOn {
+                channel: 3,
+                note: 64,
+                velocity: 90,
+            },
+        );
+        assert!(process_midi_event(&note_on, &state, &mut mpe, 0).is_some());
+
+        let bend = MidiEvent::new(
+            0,
+            MidiEventKind::PitchBend {
+                channel: 3,
+                value: 2048,
+            },
+        );
+        assert!(matches!(
+            process_midi_event(&bend, &state, &mut mpe, 0),
+            Some(Action::Instrument(InstrumentAction::NoteExpression(
+                target,
+                64,
+                ExpressionDimension::Bend,
+                v
+            ))) if target == id && v == 12.0
+        ));
+
+        let note_off = MidiEvent::new(
+            0,
+            MidiEventKind::NoteOff {
+                channel: 3,
+                note: 64,
+            },
+        );
+        process_midi_event(&note_off, &state, &mut mpe, 0);
+        assert!(process_midi_event(&bend, &state, &mut mpe, 0).is_none());
+    }
+
+    #[test]
+    fn test_poly_aftertouch_sets_note_pressure() {
+        let mut state = AppState::new();
+        let id = state.add_instrument(crate::state::SourceType::Saw);
+        let event = MidiEvent::new(
+            0,
+            MidiEventKind::PolyAftertouch {
+                channel: 0,
+                note: 60,
+                pressure: 127,
+            },
+        );
+        assert!(matches!(
+            process_midi_event(&event, &state, &mut MpeChannels::default(), 0),
+            Some(Action::Instrument(InstrumentAction::NoteExpression(
+                target,
+                60,
+                ExpressionDimension::Pressure,
+                v
+            ))) if target == id && v == 1.0
+        ));
+    }
+
+    #[test]
+    fn test_recording_takes_notes_with_latency() {
+        let mut state = AppState::new();
+        let id = state.add_instrument(crate::state::SourceType::Saw);
+        state.session.piano_roll.recording = true;
+        let mut mpe = MpeChannels::default();
+
+        let note_on = MidiEvent::new(
+            0,
+            MidiEventKind::NoteOn {
+                channel: 0,
+                note: 60,
+                velocity: 100,
+            },
+        );
+        assert!(matches!(
+            process_midi_event(&note_on, &state, &mut mpe, 3_000),
+            Some(Action::PianoRoll(PianoRollAction::RecordNoteOn {
+                instrument_id,
+                pitch: 60,
+                velocity: 100,
+                latency_us: 3_000,
+            })) if instrument_id == id
+        ));
+
+        let note_off = MidiEvent::new(
+            0,
+            MidiEventKind::NoteOff {
+                channel: 0,
+                note: 60,
+            },
+        );
+        assert!(matches!(
+            process_midi_event(&note_off, &state, &mut mpe, 0),
+            Some(Action::PianoRoll(PianoRollAction::RecordNoteOff {
+                instrument_id,
+                pitch: 60,
+                latency_us: 0,
+            })) if instrument_id == id
+        ));
+    }
 }
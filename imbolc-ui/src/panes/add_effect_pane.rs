@@ -1,14 +1,18 @@
 use std::any::Any;
 
+use imbolc_core::state::preset_library::EffectChainLibrary;
+
+use super::instrument_preset_pane::parse_name_and_tags;
 use crate::action::{BusAction, LayerGroupAction};
 use crate::state::{AppState, EffectType, EffectTypeExt, VstPluginRegistry};
-use crate::ui::action_id::{ActionId, AddActionId};
+use crate::ui::action_id::{ActionId, AddActionId, ModeActionId};
 use crate::ui::layout_helpers::center_rect;
+use crate::ui::widgets::TextInput;
 use crate::ui::{
     Action, Color, FileSelectAction, InputEvent, InstrumentAction, Keymap, MouseButton, MouseEvent,
     MouseEventKind, NavAction, Pane, Rect, RenderBuf, SessionAction, Style,
 };
-use imbolc_types::{BusId, VstPluginId};
+use imbolc_types::{BusId, EffectChainOwner, VstPluginId};
 
 /// Target for the add-effect modal: which entity receives the new effect.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,6 +28,9 @@ enum AddEffectOption {
     Effect(EffectType),
     Separator(&'static str),
     ImportVst,
+    /// Index into the chain preset library
+    ChainPreset(usize),
+    SaveChain,
 }
 
 const LIST_HEIGHT: usize = 14;
@@ -34,6 +41,11 @@ pub struct AddEffectPane {
     scroll_offset: usize,
     cached_options: Vec<AddEffectOption>,
     effect_target: EffectTarget,
+    chain_presets: EffectChainLibrary,
+    /// Name prompt for saving the target's chain (`name #tag ...`)
+    save_input: Option<TextInput>,
+    /// Re-read chain presets before the next render (after a save was dispatched)
+    needs_refresh: bool,
 }
 
 impl AddEffectPane {
@@ -44,6 +56,9 @@ impl AddEffectPane {
             scroll_offset: 0,
             cached_options: Self::build_options_static(),
             effect_target: EffectTarget::Instrument,
+            chain_presets: EffectChainLibrary::default(),
+            save_input: None,
+            needs_refresh: false,
         }
     }
 
@@ -56,6 +71,10 @@ impl AddEffectPane {
         self.effect_target
     }
 
+    pub fn is_editing(&self) -> bool {
+        self.save_input.is_some()
+    }
+
     fn adjust_scroll(&mut self) {
         if self.selected < self.scroll_offset {
             self.scroll_offset = self.selected;
@@ -135,7 +154,19 @@ impl AddEffectPane {
             .effects()
             .map(|p| (p.id, EffectType::Vst(p.id)))
             .collect();
-        Self::build_effect_list(&vst_effects)
+        let mut options = Self::build_effect_list(&vst_effects);
+
+        // Buses and groups only host effects, so filter/EQ-only chains are hidden
+        let effects_only = self.effect_target != EffectTarget::Instrument;
+        options.push(AddEffectOption::Separator("── Chain Presets ──"));
+        options.extend(
+            self.chain_presets
+                .filtered("", effects_only)
+                .into_iter()
+                .map(AddEffectOption::ChainPreset),
+        );
+        options.push(AddEffectOption::SaveChain);
+        options
     }
 
     fn update_options(&mut self, vst_registry: &VstPluginRegistry) {
@@ -170,7 +201,7 @@ impl AddEffectPane {
     }
 
     /// Convert the given option to an Action based on current effect target
-    fn option_to_action(&self, option: &AddEffectOption, state: &AppState) -> Action {
+    fn option_to_action(&mut self, option: &AddEffectOption, state: &AppState) -> Action {
         match option {
             AddEffectOption::Effect(effect_type) => match self.effect_target {
                 EffectTarget::Bus(bus_id) => {
@@ -190,10 +221,76 @@ impl AddEffectPane {
             AddEffectOption::ImportVst => Action::Session(SessionAction::OpenFileBrowser(
                 FileSelectAction::ImportVstEffect,
             )),
+            AddEffectOption::ChainPreset(idx) => {
+                let Some(entry) = self.chain_presets.entries.get(*idx) else {
+                    return Action::None;
+                };
+                let preset = Box::new(entry.preset.clone());
+                match self.effect_target {
+                    EffectTarget::Bus(bus_id) => {
+                        Action::Bus(BusAction::AddEffectChain(bus_id, preset))
+                    }
+                    EffectTarget::LayerGroup(group_id) => {
+                        Action::LayerGroup(LayerGroupAction::AddEffectChain(group_id, preset))
+                    }
+                    EffectTarget::Instrument => match state.instruments.selected_instrument() {
+                        Some(inst) => {
+                            Action::Instrument(InstrumentAction::AddEffectChain(inst.id, preset))
+                        }
+                        None => Action::None,
+                    },
+                }
+            }
+            AddEffectOption::SaveChain => {
+                if self.chain_owner(state).is_none() {
+                    return Action::None;
+                }
+                let mut input = TextInput::new("");
+                input.set_value("");
+                input.set_focused(true);
+                self.save_input = Some(input);
+                Action::PushLayer("text_edit")
+            }
             AddEffectOption::Separator(_) => Action::None,
         }
     }
 
+    /// The chain the current target would be saved from
+    fn chain_owner(&self, state: &AppState) -> Option<EffectChainOwner> {
+        match self.effect_target {
+            EffectTarget::Bus(bus_id) => Some(EffectChainOwner::Bus(bus_id)),
+            EffectTarget::LayerGroup(group_id) => Some(EffectChainOwner::LayerGroup(group_id)),
+            EffectTarget::Instrument => state
+                .instruments
+                .selected_instrument()
+                .map(|inst| EffectChainOwner::Instrument(inst.id)),
+        }
+    }
+
+    fn handle_save_prompt(&mut self, action: ModeActionId, state: &AppState) -> Action {
+        let Some(input) = self.save_input.as_ref() else {
+            return Action::None;
+        };
+        match action {
+            ModeActionId::TextConfirm => {
+                let (name, tags) = parse_name_and_tags(input.value());
+                self.save_input = None;
+                match self.chain_owner(state) {
+                    Some(owner) if !name.is_empty() => {
+                        self.needs_refresh = true;
+                        Action::Session(SessionAction::SaveEffectChainPreset { owner, name, tags })
+                    }
+                    _ => Action::None,
+                }
+            }
+            ModeActionId::TextCancel => {
+                self.save_input = None;
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
     fn select_prev(&mut self) {
         let len = self.cached_options.len();
         if len == 0 {
@@ -228,9 +325,10 @@ impl Pane for AddEffectPane {
 
     fn handle_action(&mut self, action: ActionId, _event: &InputEvent, state: &AppState) -> Action {
         match action {
+            ActionId::Mode(mode_action) => self.handle_save_prompt(mode_action, state),
             ActionId::Add(AddActionId::Confirm) => {
-                if let Some(option) = self.cached_options.get(self.selected) {
-                    self.option_to_action(option, state)
+                if let Some(option) = self.cached_options.get(self.selected).cloned() {
+                    self.option_to_action(&option, state)
                 } else {
                     Action::None
                 }
@@ -269,7 +367,8 @@ impl Pane for AddEffectPane {
                         }
                         self.selected = idx;
                         self.adjust_scroll();
-                        return self.option_to_action(&self.cached_options[idx].clone(), state);
+                        let option = self.cached_options[idx].clone();
+                        return self.option_to_action(&option, state);
                     }
                 }
                 Action::None
@@ -287,6 +386,10 @@ impl Pane for AddEffectPane {
     }
 
     fn render(&mut self, area: Rect, buf: &mut RenderBuf, state: &AppState) {
+        if std::mem::take(&mut self.needs_refresh) {
+            self.chain_presets = EffectChainLibrary::load();
+            self.update_options(&state.session.vst_plugins);
+        }
         let vst_registry = &state.session.vst_plugins;
         let rect = center_rect(area, 40, 20);
 
@@ -361,7 +464,10 @@ impl Pane for AddEffectPane {
                         }
                     }
                 }
-                AddEffectOption::ImportVst => {
+                AddEffectOption::ChainPreset(idx) => {
+                    let Some(entry) = self.chain_presets.entries.get(*idx) else {
+                        continue;
+                    };
                     if is_selected {
                         buf.set_cell(
                             content_x,
@@ -371,12 +477,58 @@ impl Pane for AddEffectPane {
                         );
                     }
 
+                    let (name_style, count_style) = if is_selected {
+                        (
+                            Style::new().fg(Color::FX_COLOR).bg(Color::SELECTION_BG),
+                            Style::new().fg(Color::DARK_GRAY).bg(Color::SELECTION_BG),
+                        )
+                    } else {
+                        (
+                            Style::new().fg(Color::FX_COLOR),
+                            Style::new().fg(Color::DARK_GRAY),
+                        )
+                    };
+                    let count = format!(" ({})", entry.preset.stages.len());
+                    buf.draw_line(
+                        Rect::new(content_x + 2, y, inner.width.saturating_sub(4), 1),
+                        &[(&entry.preset.name, name_style), (&count, count_style)],
+                    );
+
+                    if is_selected {
+                        let fill_start = content_x
+                            + 2
+                            + (entry.preset.name.chars().count() + count.len()) as u16;
+                        let fill_end = inner.x + inner.width;
+                        for x in fill_start..fill_end {
+                            buf.set_cell(x, y, ' ', sel_bg);
+                        }
+                    }
+                }
+                AddEffectOption::ImportVst | AddEffectOption::SaveChain => {
+                    if is_selected {
+                        buf.set_cell(
+                            content_x,
+                            y,
+                            '>',
+                            Style::new().fg(Color::WHITE).bg(Color::SELECTION_BG).bold(),
+                        );
+                    }
+
+                    let color = if matches!(option, AddEffectOption::ImportVst) {
+                        Color::VST_COLOR
+                    } else {
+                        Color::FX_COLOR
+                    };
                     let text_style = if is_selected {
-                        Style::new().fg(Color::VST_COLOR).bg(Color::SELECTION_BG)
+                        Style::new().fg(color).bg(Color::SELECTION_BG)
+                    } else {
+                        Style::new().fg(color)
+                    };
+                    let label = if matches!(option, AddEffectOption::ImportVst) {
+                        "+ Import VST Effect..."
                     } else {
-                        Style::new().fg(Color::VST_COLOR)
+                        "+ Save Chain as Preset..."
                     };
-                    let label = "+ Import VST Effect...";
                     buf.draw_line(
                         Rect::new(content_x + 2, y, inner.width.saturating_sub(4), 1),
                         &[(label, text_style)],
@@ -392,6 +544,29 @@ impl Pane for AddEffectPane {
                 }
             }
         }
+
+        // Save prompt below the list
+        if let Some(input) = self.save_input.as_mut() {
+            let prompt_y = list_y + LIST_HEIGHT as u16;
+            let label = "Name: ";
+            buf.draw_line(
+                Rect::new(content_x, prompt_y, inner.width.saturating_sub(2), 1),
+                &[(label, Style::new().fg(Color::YELLOW))],
+            );
+            input.render_buf(
+                buf.raw_buf(),
+                content_x + label.len() as u16,
+                prompt_y,
+                inner.width.saturating_sub(2 + label.len() as u16),
+            );
+        }
+    }
+
+    fn handle_raw_input(&mut self, event: &InputEvent, _state: &AppState) -> Action {
+        if let Some(input) = self.save_input.as_mut() {
+            input.handle_input(event);
+        }
+        Action::None
     }
 
     fn keymap(&self) -> &Keymap {
@@ -399,14 +574,91 @@ impl Pane for AddEffectPane {
     }
 
     fn on_enter(&mut self, state: &AppState) {
+        self.chain_presets = EffectChainLibrary::load();
         self.update_options(&state.session.vst_plugins);
     }
 
     fn on_exit(&mut self, _state: &AppState) {
         self.effect_target = EffectTarget::Instrument;
+        self.save_input = None;
     }
 
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imbolc_core::state::preset_library::ChainPresetEntry;
+    use imbolc_types::{EffectChainPreset, EffectId, EffectSlot, FilterConfig, FilterType};
+
+    fn preset(name: &str, stages: Vec<imbolc_types::ProcessingStage>) -> ChainPresetEntry {
+        ChainPresetEntry {
+            preset: EffectChainPreset {
+                version: 1,
+                name: name.to_string(),
+                tags: Vec::new(),
+                stages,
+                vst_plugins: Vec::new(),
+            },
+            path: std::path::PathBuf::from(format!("/tmp/{}.json", name)),
+        }
+    }
+
+    fn pane_with_presets(target: EffectTarget, state: &AppState) -> AddEffectPane {
+        let mut pane = AddEffectPane::default();
+        pane.set_effect_target(target);
+        pane.chain_presets.entries = vec![
+            preset(
+                "Filter only",
+                vec![imbolc_types::ProcessingStage::Filter(FilterConfig::new(
+                    FilterType::Lpf,
+                ))],
+            ),
+            preset(
+                "Vocal",
+                vec![imbolc_types::ProcessingStage::Effect(EffectSlot::new(
+                    EffectId::new(0),
+                    EffectType::TapeComp,
+                ))],
+            ),
+        ];
+        pane.update_options(&state.session.vst_plugins);
+        pane
+    }
+
+    fn chain_options(pane: &AddEffectPane) -> Vec<usize> {
+        pane.cached_options
+            .iter()
+            .filter_map(|o| match o {
+                AddEffectOption::ChainPreset(i) => Some(*i),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bus_target_lists_effect_chains_and_inserts_preset() {
+        let state = AppState::new();
+        let mut pane = pane_with_presets(EffectTarget::Bus(BusId::new(1)), &state);
+        assert_eq!(chain_options(&pane), vec![1]);
+
+        let action = pane.option_to_action(&AddEffectOption::ChainPreset(1), &state);
+        match action {
+            Action::Bus(BusAction::AddEffectChain(bus_id, preset)) => {
+                assert_eq!(bus_id, BusId::new(1));
+                assert_eq!(preset.name, "Vocal");
+            }
+            other => panic!("expected AddEffectChain, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn instrument_target_lists_all_chains() {
+        let state = AppState::new();
+        let pane = pane_with_presets(EffectTarget::Instrument, &state);
+        assert_eq!(chain_options(&pane), vec![0, 1]);
+    }
+}
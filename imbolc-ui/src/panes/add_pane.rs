@@ -111,6 +111,10 @@ impl AddPane {
             AddOption::Separator("── Routing ──"),
             AddOption::Source(SourceType::AudioIn),
             AddOption::Source(SourceType::BusIn),
+            AddOption::Source(SourceType::MidiOut {
+                port: 0,
+                channel: 0,
+            }),
             // Samplers
             AddOption::Separator("── Samplers ──"),
             AddOption::Source(SourceType::PitchedSampler),
@@ -39,12 +39,12 @@ impl CommandPalettePane {
     }
 
     /// Called before push to populate the palette with available commands.
-    pub fn open(&mut self, commands: Vec<(ActionId, &'static str, String)>) {
+    pub fn open(&mut self, commands: Vec<(ActionId, String, String)>) {
         let entries = commands
             .into_iter()
-            .map(|(action, desc, keybinding)| CommandEntry {
+            .map(|(action, description, keybinding)| CommandEntry {
                 action,
-                description: desc.to_string(),
+                description,
                 keybinding,
             })
             .collect();
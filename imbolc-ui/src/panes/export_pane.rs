@@ -0,0 +1,249 @@
+use std::any::Any;
+
+use crate::action::{Action, NavAction, PianoRollAction};
+use crate::state::{AppState, ExportOptions};
+use crate::ui::action_id::{ActionId, ExportActionId};
+use crate::ui::layout_helpers::center_rect;
+use crate::ui::{Color, InputEvent, Keymap, Pane, Rect, RenderBuf, Style};
+use imbolc_types::{LOUDNESS_TARGET_LUFS, TRUE_PEAK_CEILING_DBTP};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    Target,
+    Render,
+    Format,
+    BitDepth,
+    SampleRate,
+    Normalize,
+}
+
+const ROWS: [Row; 6] = [
+    Row::Target,
+    Row::Render,
+    Row::Format,
+    Row::BitDepth,
+    Row::SampleRate,
+    Row::Normalize,
+];
+
+/// Format dialog shown before a master bounce or stem export starts
+pub struct ExportPane {
+    keymap: Keymap,
+    stems: bool,
+    offline: bool,
+    cursor: usize,
+}
+
+impl ExportPane {
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            stems: false,
+            offline: false,
+            cursor: 0,
+        }
+    }
+
+    /// Configure the dialog for the export the user asked for
+    pub fn set_request(&mut self, stems: bool, offline: bool) {
+        self.stems = stems;
+        self.offline = offline;
+        self.cursor = 0;
+    }
+
+    fn export_action(&self) -> Action {
+        Action::PianoRoll(match (self.stems, self.offline) {
+            (false, false) => PianoRollAction::BounceToWav,
+            (true, false) => PianoRollAction::ExportStems,
+            (false, true) => PianoRollAction::BounceOffline,
+            (true, true) => PianoRollAction::ExportStemsOffline,
+        })
+    }
+
+    fn cycle(&mut self, forward: bool, state: &AppState) -> Action {
+        let mut options = state.io.export_options;
+        match ROWS[self.cursor] {
+            Row::Target => {
+                self.stems = !self.stems;
+                return Action::None;
+            }
+            Row::Render => {
+                self.offline = !self.offline;
+                return Action::None;
+            }
+            Row::Format => options.cycle_format(),
+            Row::BitDepth => options.cycle_bit_depth(forward),
+            Row::SampleRate => options.cycle_sample_rate(forward),
+            Row::Normalize => options.normalize = !options.normalize,
+        }
+        Action::PianoRoll(PianoRollAction::SetExportOptions(options))
+    }
+
+    fn row_value(&self, row: Row, options: &ExportOptions) -> String {
+        match row {
+            Row::Target => if self.stems { "Stems" } else { "Master" }.to_string(),
+            Row::Render => if self.offline { "Offline" } else { "Realtime" }.to_string(),
+            Row::Format => options.format.name().to_string(),
+            Row::BitDepth => options.bit_depth.name().to_string(),
+            Row::SampleRate => match options.sample_rate {
+                Some(rate) => format!("{} Hz", rate),
+                None => "Native".to_string(),
+            },
+            Row::Normalize => {
+                if options.normalize {
+                    format!(
+                        "{} LUFS, {} dBTP",
+                        LOUDNESS_TARGET_LUFS, TRUE_PEAK_CEILING_DBTP
+                    )
+                } else {
+                    "Off".to_string()
+                }
+            }
+        }
+    }
+}
+
+impl Pane for ExportPane {
+    fn id(&self) -> &'static str {
+        "export"
+    }
+
+    fn handle_action(&mut self, action: ActionId, _event: &InputEvent, state: &AppState) -> Action {
+        match action {
+            ActionId::Export(ExportActionId::Confirm) => self.export_action(),
+            ActionId::Export(ExportActionId::Close) => Action::Nav(NavAction::PopPane),
+            ActionId::Export(ExportActionId::Up) => {
+                self.cursor = self.cursor.saturating_sub(1);
+                Action::None
+            }
+            ActionId::Export(ExportActionId::Down) => {
+                self.cursor = (self.cursor + 1).min(ROWS.len() - 1);
+                Action::None
+            }
+            ActionId::Export(ExportActionId::Left) => self.cycle(false, state),
+            ActionId::Export(ExportActionId::Right) => self.cycle(true, state),
+            _ => Action::None,
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut RenderBuf, state: &AppState) {
+        let rect = center_rect(area, 44.min(area.width.saturating_sub(4)), 12);
+        let border_style = Style::new().fg(Color::CYAN);
+        let inner = buf.draw_block(rect, " Export ", border_style, border_style);
+        if inner.height < 3 || inner.width < 20 {
+            return;
+        }
+
+        let label_style = Style::new().fg(Color::GRAY);
+        let value_style = Style::new().fg(Color::WHITE);
+        let highlight = Style::new().fg(Color::WHITE).bg(Color::SELECTION_BG).bold();
+        let dim = Style::new().fg(Color::DARK_GRAY);
+
+        let x = inner.x + 1;
+        let w = inner.width.saturating_sub(2);
+        let options = state.io.export_options;
+        let mut y = inner.y + 1;
+        for (i, row) in ROWS.iter().enumerate() {
+            if y >= inner.y + inner.height {
+                return;
+            }
+            let label = match row {
+                Row::Target => " Target      ",
+                Row::Render => " Render      ",
+                Row::Format => " Format      ",
+                Row::BitDepth => " Bit depth   ",
+                Row::SampleRate => " Sample rate ",
+                Row::Normalize => " Normalize   ",
+            };
+            let value = format!(" {} ", self.row_value(*row, &options));
+            let style = if i == self.cursor {
+                highlight
+            } else {
+                value_style
+            };
+            buf.draw_line(
+                Rect::new(x, y, w, 1),
+                &[(label, label_style), (&value, style)],
+            );
+            y += 1;
+        }
+
+        let hint_y = inner.y + inner.height.saturating_sub(1);
+        if hint_y > y {
+            buf.draw_line(
+                Rect::new(x, hint_y, w, 1),
+                &[(" Enter: export  Left/Right: change  Esc: close", dim)],
+            );
+        }
+    }
+
+    fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ExportBitDepth, ExportFormat};
+    use crate::ui::{KeyCode, Modifiers};
+
+    fn dummy_event() -> InputEvent {
+        InputEvent::new(KeyCode::Enter, Modifiers::none())
+    }
+
+    #[test]
+    fn option_rows_emit_set_export_options() {
+        let mut pane = ExportPane::new(Keymap::new());
+        let state = AppState::new();
+        pane.cursor = 2;
+        let action = pane.handle_action(
+            ActionId::Export(ExportActionId::Right),
+            &dummy_event(),
+            &state,
+        );
+        match action {
+            Action::PianoRoll(PianoRollAction::SetExportOptions(options)) => {
+                assert_eq!(options.format, ExportFormat::Flac);
+                assert_eq!(options.bit_depth, ExportBitDepth::Int24);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn confirm_starts_the_requested_export() {
+        let mut pane = ExportPane::new(Keymap::new());
+        let state = AppState::new();
+        pane.set_request(true, true);
+        let action = pane.handle_action(
+            ActionId::Export(ExportActionId::Confirm),
+            &dummy_event(),
+            &state,
+        );
+        assert!(matches!(
+            action,
+            Action::PianoRoll(PianoRollAction::ExportStemsOffline)
+        ));
+
+        // Target and render rows toggle locally
+        pane.handle_action(
+            ActionId::Export(ExportActionId::Left),
+            &dummy_event(),
+            &state,
+        );
+        let action = pane.handle_action(
+            ActionId::Export(ExportActionId::Confirm),
+            &dummy_event(),
+            &state,
+        );
+        assert!(matches!(
+            action,
+            Action::PianoRoll(PianoRollAction::BounceOffline)
+        ));
+    }
+}
@@ -7,9 +7,9 @@ use crate::state::VstPluginKind;
 use crate::ui::action_id::{ActionId, FileBrowserActionId};
 use crate::ui::layout_helpers::center_rect;
 use crate::ui::{
-    Action, ChopperAction, Color, FileSelectAction, InputEvent, InstrumentAction, Keymap,
-    MouseButton, MouseEvent, MouseEventKind, NavAction, Pane, Rect, RenderBuf, SequencerAction,
-    SessionAction, Style,
+    Action, ArrangementAction, ChopperAction, Color, FileSelectAction, InputEvent,
+    InstrumentAction, Keymap, MouseButton, MouseEvent, MouseEventKind, NavAction, Pane, Rect,
+    RenderBuf, SequencerAction, SessionAction, Style,
 };
 
 struct DirEntry {
@@ -63,12 +63,16 @@ impl FileBrowserPane {
             FileSelectAction::LoadDrumSample(_)
             | FileSelectAction::LoadChopperSample
             | FileSelectAction::LoadPitchedSample(_)
-            | FileSelectAction::LoadImpulseResponse(_, _) => Some(vec![
+            | FileSelectAction::LoadImpulseResponse(_, _)
+            | FileSelectAction::ImportAudioClip(_, _) => Some(vec![
                 "wav".to_string(),
                 "aiff".to_string(),
                 "aif".to_string(),
             ]),
             FileSelectAction::ImportProject => Some(vec!["sqlite".to_string()]),
+            FileSelectAction::ImportMidiFile(_) => {
+                Some(vec!["mid".to_string(), "midi".to_string()])
+            }
         };
         let default_dir = match &self.on_select_action {
             FileSelectAction::ImportVstInstrument | FileSelectAction::ImportVstEffect => {
@@ -219,6 +223,16 @@ impl Pane for FileBrowserPane {
                             FileSelectAction::ImportProject => {
                                 Action::Session(SessionAction::LoadFrom(entry.path.clone()))
                             }
+                            FileSelectAction::ImportMidiFile(target) => Action::Session(
+                                SessionAction::ImportMidiFile(entry.path.clone(), target),
+                            ),
+                            FileSelectAction::ImportAudioClip(instrument_id, start_tick) => {
+                                Action::Arrangement(ArrangementAction::ImportAudioClip {
+                                    instrument_id,
+                                    path: entry.path.clone(),
+                                    start_tick,
+                                })
+                            }
                         }
                     }
                 } else {
@@ -289,6 +303,8 @@ impl Pane for FileBrowserPane {
             FileSelectAction::LoadPitchedSample(_) => " Load Sample ",
             FileSelectAction::LoadImpulseResponse(_, _) => " Load Impulse Response ",
             FileSelectAction::ImportProject => " Import Project ",
+            FileSelectAction::ImportMidiFile(_) => " Import MIDI File ",
+            FileSelectAction::ImportAudioClip(_, _) => " Import Audio Clip ",
         };
         let border_style = Style::new().fg(Color::PURPLE);
         let inner = buf.draw_block(rect, title, border_style, border_style);
@@ -505,6 +521,24 @@ impl Pane for FileBrowserPane {
                                             self.entries[clicked_idx].path.clone(),
                                         ));
                                     }
+                                    FileSelectAction::ImportMidiFile(target) => {
+                                        return Action::Session(SessionAction::ImportMidiFile(
+                                            self.entries[clicked_idx].path.clone(),
+                                            target,
+                                        ));
+                                    }
+                                    FileSelectAction::ImportAudioClip(
+                                        instrument_id,
+                                        start_tick,
+                                    ) => {
+                                        return Action::Arrangement(
+                                            ArrangementAction::ImportAudioClip {
+                                                instrument_id,
+                                                path: self.entries[clicked_idx].path.clone(),
+                                                start_tick,
+                                            },
+                                        );
+                                    }
                                 }
                             }
                         } else {
@@ -1,6 +1,6 @@
 use super::InstrumentEditPane;
 use crate::state::param::{adjust_freq_semitone, adjust_musical_step};
-use crate::state::InstrumentSection;
+use crate::state::{InstrumentSection, ModMatrix, ModMatrixLfo, ModMatrixRow, ModRoute};
 use crate::ui::{Action, InstrumentAction, InstrumentUpdate};
 use imbolc_types::ProcessingStage;
 
@@ -184,6 +184,77 @@ impl InstrumentEditPane {
                     *val = (*val - delta).max(0.0);
                 }
             }
+            InstrumentSection::ModMatrix => match self.mod_matrix.row(local_idx) {
+                ModMatrixRow::LfoRate(i) => {
+                    let delta = match mode {
+                        AdjustMode::Tiny => 0.1,
+                        AdjustMode::Musical => 1.0,
+                        AdjustMode::Big => 2.0,
+                        AdjustMode::Normal => 0.5,
+                    };
+                    let lfo = &mut self.mod_matrix.lfos[i];
+                    if increase {
+                        lfo.rate = (lfo.rate + delta).min(32.0);
+                    } else {
+                        lfo.rate = (lfo.rate - delta).max(0.1);
+                    }
+                }
+                ModMatrixRow::LfoShape(_) => {} // use 's' to cycle
+                ModMatrixRow::Envelope(i, stage) => {
+                    let delta = match mode {
+                        AdjustMode::Tiny => 0.01,
+                        AdjustMode::Normal => 0.05,
+                        AdjustMode::Musical | AdjustMode::Big => 0.1,
+                    };
+                    let max = if stage == 2 { 1.0 } else { 5.0 };
+                    if let Some(val) = env_stage_mut(&mut self.mod_matrix.envelopes[i], stage) {
+                        if increase {
+                            *val = (*val + delta).min(max);
+                        } else {
+                            *val = (*val - delta).max(0.0);
+                        }
+                    }
+                }
+                ModMatrixRow::Route(i) => {
+                    let delta = match mode {
+                        AdjustMode::Tiny => 0.01,
+                        AdjustMode::Normal => 0.05,
+                        AdjustMode::Musical | AdjustMode::Big => 0.1,
+                    };
+                    self.mod_matrix.routes[i].adjust_depth(if increase { delta } else { -delta });
+                }
+                ModMatrixRow::NoRoutes => {}
+            },
+        }
+    }
+
+    /// Apply a typed value to a mod matrix row (text edit confirm/cancel).
+    pub(super) fn set_mod_matrix_value(&mut self, local_idx: usize, text: &str) {
+        let Ok(v) = text.parse::<f32>() else {
+            return;
+        };
+        match self.mod_matrix.row(local_idx) {
+            ModMatrixRow::LfoRate(i) => self.mod_matrix.lfos[i].rate = v.clamp(0.1, 32.0),
+            ModMatrixRow::Envelope(i, stage) => {
+                let max = if stage == 2 { 1.0 } else { 5.0 };
+                if let Some(val) = env_stage_mut(&mut self.mod_matrix.envelopes[i], stage) {
+                    *val = v.clamp(0.0, max);
+                }
+            }
+            ModMatrixRow::Route(i) => self.mod_matrix.routes[i].depth = v.clamp(-1.0, 1.0),
+            ModMatrixRow::LfoShape(_) | ModMatrixRow::NoRoutes => {}
+        }
+    }
+
+    /// Index of the mod route under the cursor, if any.
+    pub(super) fn current_mod_route(&self) -> Option<usize> {
+        let (section, local_idx) = self.row_info(self.selected_row);
+        if section != InstrumentSection::ModMatrix {
+            return None;
+        }
+        match self.mod_matrix.row(local_idx) {
+            ModMatrixRow::Route(i) => Some(i),
+            _ => None,
         }
     }
 
@@ -191,13 +262,16 @@ impl InstrumentEditPane {
         if let Some(id) = self.instrument_id {
             Action::Instrument(InstrumentAction::Update(Box::new(InstrumentUpdate {
                 id,
-                source: self.source,
+                source: self.source.with_params(&self.source_params),
                 source_params: self.source_params.clone(),
                 processing_chain: self.processing_chain.clone(),
                 lfo: self.lfo.clone(),
                 amp_envelope: self.amp_envelope.clone(),
+                mod_matrix: Some(self.mod_matrix.clone()),
                 polyphonic: self.polyphonic,
                 active: self.active,
+                note_input: None,
+                groove: None,
             })))
         } else {
             Action::None
@@ -266,6 +340,16 @@ impl InstrumentEditPane {
                 3 => self.amp_envelope.release = 0.0,
                 _ => {}
             },
+            InstrumentSection::ModMatrix => match self.mod_matrix.row(local_idx) {
+                ModMatrixRow::LfoRate(i) => self.mod_matrix.lfos[i].rate = 0.1,
+                ModMatrixRow::Envelope(i, stage) => {
+                    if let Some(val) = env_stage_mut(&mut self.mod_matrix.envelopes[i], stage) {
+                        *val = 0.0;
+                    }
+                }
+                ModMatrixRow::Route(i) => self.mod_matrix.routes[i].depth = 0.0,
+                ModMatrixRow::LfoShape(_) | ModMatrixRow::NoRoutes => {}
+            },
         }
     }
 
@@ -345,6 +429,29 @@ impl InstrumentEditPane {
                     _ => {}
                 }
             }
+            InstrumentSection::ModMatrix => match self.mod_matrix.row(local_idx) {
+                ModMatrixRow::LfoRate(i) => {
+                    self.mod_matrix.lfos[i].rate = ModMatrixLfo::default().rate;
+                }
+                ModMatrixRow::Envelope(i, stage) => {
+                    let defaults = ModMatrix::default();
+                    let Some(default) = defaults.envelopes.get(i) else {
+                        return;
+                    };
+                    let env = &mut self.mod_matrix.envelopes[i];
+                    match stage {
+                        0 => env.attack = default.attack,
+                        1 => env.decay = default.decay,
+                        2 => env.sustain = default.sustain,
+                        3 => env.release = default.release,
+                        _ => {}
+                    }
+                }
+                ModMatrixRow::Route(i) => {
+                    self.mod_matrix.routes[i].depth = ModRoute::default().depth;
+                }
+                ModMatrixRow::LfoShape(_) | ModMatrixRow::NoRoutes => {}
+            },
         }
     }
 
@@ -387,6 +494,20 @@ impl InstrumentEditPane {
                 self.amp_envelope.sustain = 0.0;
                 self.amp_envelope.release = 0.0;
             }
+            InstrumentSection::ModMatrix => {
+                for lfo in &mut self.mod_matrix.lfos {
+                    lfo.rate = 0.1;
+                }
+                for env in &mut self.mod_matrix.envelopes {
+                    env.attack = 0.0;
+                    env.decay = 0.0;
+                    env.sustain = 0.0;
+                    env.release = 0.0;
+                }
+                for route in &mut self.mod_matrix.routes {
+                    route.depth = 0.0;
+                }
+            }
         }
     }
 
@@ -440,7 +561,28 @@ impl InstrumentEditPane {
                 3 => format!("{:.2}", self.amp_envelope.release),
                 _ => String::new(),
             },
+            InstrumentSection::ModMatrix => match self.mod_matrix.row(local_idx) {
+                ModMatrixRow::LfoRate(i) => format!("{:.2}", self.mod_matrix.lfos[i].rate),
+                ModMatrixRow::Envelope(i, stage) => {
+                    let env = &self.mod_matrix.envelopes[i];
+                    let val = [env.attack, env.decay, env.sustain, env.release][stage];
+                    format!("{:.2}", val)
+                }
+                ModMatrixRow::Route(i) => format!("{:.2}", self.mod_matrix.routes[i].depth),
+                ModMatrixRow::LfoShape(_) | ModMatrixRow::NoRoutes => String::new(),
+            },
             _ => String::new(),
         }
     }
 }
+
+/// ADSR stage (0=attack .. 3=release) of an envelope.
+fn env_stage_mut(env: &mut crate::state::EnvConfig, stage: usize) -> Option<&mut f32> {
+    match stage {
+        0 => Some(&mut env.attack),
+        1 => Some(&mut env.decay),
+        2 => Some(&mut env.sustain),
+        3 => Some(&mut env.release),
+        _ => None,
+    }
+}
@@ -1,12 +1,14 @@
 use super::editing::AdjustMode;
 use super::InstrumentEditPane;
-use crate::state::{AppState, FilterConfig, FilterType, InstrumentSection};
+use crate::state::{AppState, FilterConfig, FilterType, InstrumentSection, ModMatrixRow};
 use crate::ui::action_id::{ActionId, InstrumentEditActionId, ModeActionId};
 use crate::ui::{
     translate_key, Action, FileSelectAction, InputEvent, InstrumentAction, KeyCode, PaneId,
     SessionAction,
 };
-use imbolc_types::ProcessingStage;
+use imbolc_types::{
+    EffectChainOwner, ProcessingStage, SidechainSource, SOURCE_BPM_MAX, SOURCE_BPM_MIN,
+};
 
 impl InstrumentEditPane {
     pub(super) fn handle_action_impl(
@@ -139,6 +141,7 @@ impl InstrumentEditPane {
                                 }
                             }
                         }
+                        InstrumentSection::ModMatrix => self.set_mod_matrix_value(local_idx, &text),
                         _ => {}
                     }
                     self.editing = false;
@@ -212,6 +215,9 @@ impl InstrumentEditPane {
                                     }
                                 }
                             }
+                            InstrumentSection::ModMatrix => {
+                                self.set_mod_matrix_value(local_idx, backup)
+                            }
                             _ => {}
                         }
                     }
@@ -227,6 +233,10 @@ impl InstrumentEditPane {
             return Action::None;
         };
 
+        if let Some(action) = self.sample_tempo_action(action, state) {
+            return action;
+        }
+
         match action {
             // Normal pane actions
             InstrumentEditActionId::Done => self.emit_update(),
@@ -302,6 +312,15 @@ impl InstrumentEditPane {
                         _ => {}
                     }
                 }
+                // Shape and placeholder rows in the mod matrix have no text value
+                if section == InstrumentSection::ModMatrix
+                    && matches!(
+                        self.mod_matrix.row(local_idx),
+                        ModMatrixRow::LfoShape(_) | ModMatrixRow::NoRoutes
+                    )
+                {
+                    return Action::None;
+                }
                 self.edit_backup_value = Some(self.current_value_string());
                 self.editing = true;
                 let current_val = self.current_value_string();
@@ -411,6 +430,13 @@ impl InstrumentEditPane {
                     Action::None
                 }
             }
+            InstrumentEditActionId::ToggleSampleWarp => match self.instrument_id {
+                Some(id) if self.source.is_sample() => {
+                    self.sample_warp = !self.sample_warp;
+                    Action::Instrument(InstrumentAction::ToggleSampleWarp(id))
+                }
+                _ => Action::None,
+            },
             InstrumentEditActionId::ZeroParam => {
                 self.zero_current_param();
                 self.emit_update()
@@ -448,6 +474,16 @@ impl InstrumentEditPane {
                 self.emit_update()
             }
             InstrumentEditActionId::CycleLfoShape => {
+                let (section, local_idx) = self.row_info(self.selected_row);
+                if section == InstrumentSection::ModMatrix {
+                    if let ModMatrixRow::LfoRate(i) | ModMatrixRow::LfoShape(i) =
+                        self.mod_matrix.row(local_idx)
+                    {
+                        let lfo = &mut self.mod_matrix.lfos[i];
+                        lfo.shape = lfo.shape.next();
+                        return self.emit_update();
+                    }
+                }
                 self.lfo.shape = self.lfo.shape.next();
                 self.emit_update()
             }
@@ -505,12 +541,13 @@ impl InstrumentEditPane {
                     }
                     InstrumentSection::Lfo => {
                         if skip_env {
-                            InstrumentSection::Source
+                            InstrumentSection::ModMatrix
                         } else {
                             InstrumentSection::Envelope
                         }
                     }
-                    InstrumentSection::Envelope => InstrumentSection::Source,
+                    InstrumentSection::Envelope => InstrumentSection::ModMatrix,
+                    InstrumentSection::ModMatrix => InstrumentSection::Source,
                 };
                 for i in 0..self.total_rows() {
                     if self.section_for_row(i) == next {
@@ -525,13 +562,7 @@ impl InstrumentEditPane {
                 let skip_env = self.source.is_vst();
                 let n = self.processing_chain.len();
                 let prev = match current {
-                    InstrumentSection::Source => {
-                        if skip_env {
-                            InstrumentSection::Lfo
-                        } else {
-                            InstrumentSection::Envelope
-                        }
-                    }
+                    InstrumentSection::Source => InstrumentSection::ModMatrix,
                     InstrumentSection::Processing(i) => {
                         if i > 0 {
                             InstrumentSection::Processing(i - 1)
@@ -547,6 +578,13 @@ impl InstrumentEditPane {
                         }
                     }
                     InstrumentSection::Envelope => InstrumentSection::Lfo,
+                    InstrumentSection::ModMatrix => {
+                        if skip_env {
+                            InstrumentSection::Lfo
+                        } else {
+                            InstrumentSection::Envelope
+                        }
+                    }
                 };
                 for i in 0..self.total_rows() {
                     if self.section_for_row(i) == prev {
@@ -587,9 +625,90 @@ impl InstrumentEditPane {
                 }
                 Action::None
             }
+            InstrumentEditActionId::CycleSidechain => {
+                let (Some(id), InstrumentSection::Processing(i)) =
+                    (self.instrument_id, self.current_section())
+                else {
+                    return Action::None;
+                };
+                let candidates = state
+                    .instruments
+                    .sidechain_candidates(EffectChainOwner::Instrument(id));
+                if let Some(ProcessingStage::Effect(e)) = self.processing_chain.get_mut(i) {
+                    if e.effect_type.sidechain_control().is_some() {
+                        e.sidechain = SidechainSource::cycle(e.sidechain, &candidates);
+                        return self.emit_update();
+                    }
+                }
+                Action::None
+            }
+            InstrumentEditActionId::AddModRoute => {
+                let Some(route_idx) = self.mod_matrix.add_route() else {
+                    return Action::None;
+                };
+                if let Some(start) = self.first_row_of(InstrumentSection::ModMatrix) {
+                    self.selected_row = start + self.mod_matrix.route_row(route_idx);
+                }
+                self.emit_update()
+            }
+            InstrumentEditActionId::RemoveModRoute => {
+                if let Some(route_idx) = self.current_mod_route() {
+                    self.mod_matrix.remove_route(route_idx);
+                    let max = self.total_rows().saturating_sub(1);
+                    self.selected_row = self.selected_row.min(max);
+                    return self.emit_update();
+                }
+                Action::None
+            }
+            InstrumentEditActionId::CycleModSource => {
+                if let Some(route_idx) = self.current_mod_route() {
+                    let route = &mut self.mod_matrix.routes[route_idx];
+                    route.source = route.source.next();
+                    return self.emit_update();
+                }
+                Action::None
+            }
+            InstrumentEditActionId::CycleModTarget => {
+                if let Some(route_idx) = self.current_mod_route() {
+                    let route = &mut self.mod_matrix.routes[route_idx];
+                    route.target = route.target.next_lfo_target();
+                    return self.emit_update();
+                }
+                Action::None
+            }
         }
     }
 
+    /// Value keys on the sample row nudge the sample's own tempo: 1 BPM,
+    /// 10 BPM with the big step, 0.1 BPM fine, and double/halve musically.
+    fn sample_tempo_action(
+        &mut self,
+        action: InstrumentEditActionId,
+        state: &AppState,
+    ) -> Option<Action> {
+        let (section, local_idx) = self.row_info(self.selected_row);
+        if !self.source.is_sample() || section != InstrumentSection::Source || local_idx != 0 {
+            return None;
+        }
+        let id = self.instrument_id?;
+        let bpm = self.sample_bpm.unwrap_or(state.session.bpm as f32);
+        let delta = match action {
+            InstrumentEditActionId::Increase => 1.0,
+            InstrumentEditActionId::Decrease => -1.0,
+            InstrumentEditActionId::IncreaseBig => 10.0,
+            InstrumentEditActionId::DecreaseBig => -10.0,
+            InstrumentEditActionId::IncreaseTiny => 0.1,
+            InstrumentEditActionId::DecreaseTiny => -0.1,
+            InstrumentEditActionId::IncreaseMusical => bpm,
+            InstrumentEditActionId::DecreaseMusical => -bpm / 2.0,
+            _ => return None,
+        };
+        self.sample_bpm = Some((bpm + delta).clamp(SOURCE_BPM_MIN, SOURCE_BPM_MAX));
+        Some(Action::Instrument(InstrumentAction::AdjustSampleBpm(
+            id, delta,
+        )))
+    }
+
     pub(super) fn handle_raw_input_impl(&mut self, event: &InputEvent) {
         if self.editing {
             self.edit_input.handle_input(event);
@@ -6,7 +6,7 @@ use std::any::Any;
 
 use crate::state::{
     instrument::{instrument_row_count, instrument_row_info, instrument_section_for_row},
-    AppState, EnvConfig, InstrumentId, InstrumentSection, LfoConfig, Param, SourceType,
+    AppState, EnvConfig, InstrumentId, InstrumentSection, LfoConfig, ModMatrix, Param, SourceType,
 };
 use crate::ui::action_id::ActionId;
 use crate::ui::performance::PerformanceController;
@@ -21,9 +21,13 @@ pub struct InstrumentEditPane {
     source: SourceType,
     source_params: Vec<Param>,
     sample_name: Option<String>,
+    /// Loaded sample's own tempo and whether it warps to the project tempo
+    sample_bpm: Option<f32>,
+    sample_warp: bool,
     processing_chain: Vec<ProcessingStage>,
     lfo: LfoConfig,
     amp_envelope: EnvConfig,
+    mod_matrix: ModMatrix,
     polyphonic: bool,
     active: bool,
     channel_config: ChannelConfig,
@@ -44,9 +48,12 @@ impl InstrumentEditPane {
             source: SourceType::Saw,
             source_params: Vec::new(),
             sample_name: None,
+            sample_bpm: None,
+            sample_warp: false,
             processing_chain: Vec::new(),
             lfo: LfoConfig::default(),
             amp_envelope: EnvConfig::default(),
+            mod_matrix: ModMatrix::default(),
             polyphonic: true,
             active: true,
             channel_config: ChannelConfig::default(),
@@ -71,9 +78,12 @@ impl InstrumentEditPane {
         self.sample_name = instrument
             .sampler_config()
             .and_then(|c| c.sample_name.clone());
+        self.sample_bpm = instrument.sampler_config().and_then(|c| c.source_bpm);
+        self.sample_warp = instrument.sampler_config().is_some_and(|c| c.warp);
         self.processing_chain = instrument.processing_chain.clone();
         self.lfo = instrument.modulation.lfo.clone();
         self.amp_envelope = instrument.modulation.amp_envelope.clone();
+        self.mod_matrix = instrument.modulation.matrix.clone();
         self.polyphonic = instrument.polyphonic;
         self.active = instrument.mixer.active;
         self.channel_config = instrument.mixer.channel_config;
@@ -92,9 +102,12 @@ impl InstrumentEditPane {
         self.sample_name = instrument
             .sampler_config()
             .and_then(|c| c.sample_name.clone());
+        self.sample_bpm = instrument.sampler_config().and_then(|c| c.source_bpm);
+        self.sample_warp = instrument.sampler_config().is_some_and(|c| c.warp);
         self.processing_chain = instrument.processing_chain.clone();
         self.lfo = instrument.modulation.lfo.clone();
         self.amp_envelope = instrument.modulation.amp_envelope.clone();
+        self.mod_matrix = instrument.modulation.matrix.clone();
         self.polyphonic = instrument.polyphonic;
         self.active = instrument.mixer.active;
         self.channel_config = instrument.mixer.channel_config;
@@ -109,7 +122,8 @@ impl InstrumentEditPane {
     }
 
     /// Get current tab as index (for view state).
-    /// Dynamic encoding: 0=Source, 1..=N=Processing(0..N-1), N+1=Lfo, N+2=Envelope.
+    /// Dynamic encoding: 0=Source, 1..=N=Processing(0..N-1), N+1=Lfo, N+2=Envelope,
+    /// N+3=ModMatrix.
     pub fn tab_index(&self) -> u8 {
         let n = self.processing_chain.len();
         match self.current_section() {
@@ -117,11 +131,13 @@ impl InstrumentEditPane {
             InstrumentSection::Processing(i) => (i + 1) as u8,
             InstrumentSection::Lfo => (n + 1) as u8,
             InstrumentSection::Envelope => (n + 2) as u8,
+            InstrumentSection::ModMatrix => (n + 3) as u8,
         }
     }
 
     /// Set tab from index (for view state restoration).
-    /// Dynamic decoding: 0=Source, 1..=N=Processing(0..N-1), N+1=Lfo, N+2=Envelope.
+    /// Dynamic decoding: 0=Source, 1..=N=Processing(0..N-1), N+1=Lfo, N+2=Envelope,
+    /// N+3=ModMatrix.
     pub fn set_tab_index(&mut self, idx: u8) {
         let n = self.processing_chain.len();
         let target = if idx == 0 {
@@ -130,8 +146,10 @@ impl InstrumentEditPane {
             InstrumentSection::Processing(idx as usize - 1)
         } else if idx as usize == n + 1 {
             InstrumentSection::Lfo
-        } else {
+        } else if idx as usize == n + 2 {
             InstrumentSection::Envelope
+        } else {
+            InstrumentSection::ModMatrix
         };
         for i in 0..self.total_rows() {
             if self.section_for_row(i) == target {
@@ -149,26 +167,34 @@ impl InstrumentEditPane {
         instrument.processing_chain = self.processing_chain.clone();
         instrument.modulation.lfo = self.lfo.clone();
         instrument.modulation.amp_envelope = self.amp_envelope.clone();
+        instrument.modulation.matrix = self.mod_matrix.clone();
         instrument.polyphonic = self.polyphonic;
         instrument.mixer.active = self.active;
     }
 
     /// Total number of selectable rows across all sections
     fn total_rows(&self) -> usize {
-        instrument_row_count(self.source, &self.source_params, &self.processing_chain)
+        instrument_row_count(
+            self.source,
+            &self.source_params,
+            &self.processing_chain,
+            &self.mod_matrix,
+        )
     }
 
     /// Calculate non-selectable visual lines (headers + separators)
     fn visual_overhead(&self) -> usize {
         // Headers: 1 (source) + one per filter in chain + 1 (LFO) + (1 if !VST for envelope)
+        // + 1 (mod matrix)
         let filter_count = self
             .processing_chain
             .iter()
             .filter(|s| s.is_filter())
             .count();
-        let headers = 1 + filter_count + 1 + if self.source.is_vst() { 0 } else { 1 };
-        // Separators: 1 (after source) + 1 (after chain) + (1 after LFO if !VST)
-        let separators = 1 + 1 + if self.source.is_vst() { 0 } else { 1 };
+        let headers = 1 + filter_count + 1 + if self.source.is_vst() { 0 } else { 1 } + 1;
+        // Separators: 1 (after source) + 1 (after chain) + 1 (after LFO)
+        // + (1 after envelope if !VST)
+        let separators = 1 + 1 + 1 + if self.source.is_vst() { 0 } else { 1 };
         headers + separators
     }
 
@@ -207,6 +233,11 @@ impl InstrumentEditPane {
         self.section_for_row(self.selected_row)
     }
 
+    /// First row of a section, if it has any rows.
+    fn first_row_of(&self, section: InstrumentSection) -> Option<usize> {
+        (0..self.total_rows()).find(|&i| self.section_for_row(i) == section)
+    }
+
     /// Find the first row belonging to a given processing stage chain index,
     /// offset by local_idx within that stage. Used for cursor stability after MoveStage.
     fn row_for_processing_stage(&self, chain_idx: usize, local_idx: usize) -> usize {
@@ -402,6 +433,14 @@ mod tests {
         );
         assert_eq!(pane.current_section(), InstrumentSection::Envelope);
 
+        // Tab → ModMatrix
+        pane.handle_action_impl(
+            ActionId::InstrumentEdit(InstrumentEditActionId::NextSection),
+            &event,
+            &state,
+        );
+        assert_eq!(pane.current_section(), InstrumentSection::ModMatrix);
+
         // Tab → Source (wrap)
         pane.handle_action_impl(
             ActionId::InstrumentEdit(InstrumentEditActionId::NextSection),
@@ -478,7 +517,43 @@ mod tests {
         let source_rows = pane.source_params.len().max(1);
         let lfo_rows = 4;
         let env_rows = 4;
+        let matrix_rows = pane.mod_matrix.row_count();
         // Empty chain should contribute 1 placeholder row
-        assert_eq!(total, source_rows + 1 + lfo_rows + env_rows);
+        assert_eq!(total, source_rows + 1 + lfo_rows + env_rows + matrix_rows);
+    }
+
+    #[test]
+    fn test_mod_route_add_cycle_remove() {
+        let mut pane = make_pane_with_chain(vec![]);
+        let state = AppState::new();
+        let event = dummy_event();
+        let run = |pane: &mut InstrumentEditPane, action| {
+            pane.handle_action_impl(ActionId::InstrumentEdit(action), &event, &state)
+        };
+
+        run(&mut pane, InstrumentEditActionId::AddModRoute);
+        assert_eq!(pane.mod_matrix.routes.len(), 1);
+        assert_eq!(pane.current_section(), InstrumentSection::ModMatrix);
+        let (_, local_idx) = pane.row_info(pane.selected_row);
+        assert_eq!(
+            pane.mod_matrix.row(local_idx),
+            crate::state::ModMatrixRow::Route(0)
+        );
+
+        run(&mut pane, InstrumentEditActionId::CycleModSource);
+        assert_eq!(
+            pane.mod_matrix.routes[0].source,
+            crate::state::ModMatrixSource::Lfo(1)
+        );
+        let target = pane.mod_matrix.routes[0].target;
+        run(&mut pane, InstrumentEditActionId::CycleModTarget);
+        assert_eq!(pane.mod_matrix.routes[0].target, target.next_lfo_target());
+
+        run(&mut pane, InstrumentEditActionId::Decrease);
+        assert!((pane.mod_matrix.routes[0].depth - 0.45).abs() < 1e-6);
+
+        run(&mut pane, InstrumentEditActionId::RemoveModRoute);
+        assert!(pane.mod_matrix.routes.is_empty());
+        assert!(pane.selected_row < pane.total_rows());
     }
 }
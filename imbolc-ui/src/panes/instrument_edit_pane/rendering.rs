@@ -1,12 +1,12 @@
 use super::InstrumentEditPane;
-use crate::state::{AppState, Param, ParamValue};
+use crate::state::{AppState, ModMatrixRow, Param, ParamValue, MAX_MOD_ROUTES};
 use crate::ui::layout_helpers::center_rect;
 use crate::ui::widgets::TextInput;
 use crate::ui::{Color, Rect, RenderBuf, Style};
 use imbolc_types::ProcessingStage;
 
 impl InstrumentEditPane {
-    pub(super) fn render_impl(&mut self, area: Rect, buf: &mut RenderBuf, _state: &AppState) {
+    pub(super) fn render_impl(&mut self, area: Rect, buf: &mut RenderBuf, state: &AppState) {
         let rect = center_rect(area, 97, 29);
 
         let title = format!(" Edit: {} ({}) ", self.instrument_name, self.source.name());
@@ -128,12 +128,21 @@ impl InstrumentEditPane {
             if is_visible(global_row) && visual_y < max_y {
                 let is_sel = self.selected_row == global_row;
                 let display_name = self.sample_name.as_deref().unwrap_or("(no sample)");
+                let tempo = match (self.sample_bpm, self.sample_warp) {
+                    (Some(bpm), true) => {
+                        format!("{:.1}→{} BPM  (w: warp on)", bpm, state.session.bpm)
+                    }
+                    (Some(bpm), false) => format!("{:.1} BPM  (w: warp off)", bpm),
+                    (None, true) => "? BPM  (w: warp on)".to_string(),
+                    (None, false) => "? BPM  (w: warp off)".to_string(),
+                };
+                let display = format!("{}  {}", display_name, tempo);
                 render_label_value_row_buf(
                     buf,
                     content_x,
                     visual_y,
                     "Sample",
-                    display_name,
+                    &display,
                     Color::CYAN,
                     is_sel,
                 );
@@ -333,6 +342,14 @@ impl InstrumentEditPane {
                                 Rect::new(content_x + 2, visual_y, 18, 1),
                                 &[(&effect_text, effect_style)],
                             );
+                            if let Some(source) = effect.sidechain_source() {
+                                let key_text =
+                                    format!("key \u{2190} {}", source.label(&state.instruments));
+                                buf.draw_line(
+                                    Rect::new(content_x + 21, visual_y, 30, 1),
+                                    &[(&key_text, Style::new().fg(Color::DARK_GRAY))],
+                                );
+                            }
                             visual_y += 1;
                         }
                         global_row += 1;
@@ -524,6 +541,117 @@ impl InstrumentEditPane {
                 }
                 global_row += 1;
             }
+
+            // Separator after envelope section
+            if visual_y < max_y {
+                visual_y += 1;
+            }
+        }
+
+        // === MOD MATRIX SECTION ===
+        let matrix_start = global_row;
+        let matrix_end = matrix_start + self.mod_matrix.row_count();
+
+        if (matrix_start..matrix_end).any(&is_visible) && visual_y < max_y {
+            let matrix_header = format!(
+                "MOD MATRIX [{}/{}]  (r: add, R: remove, g: source, G: target)",
+                self.mod_matrix.routes.len(),
+                MAX_MOD_ROUTES
+            );
+            buf.draw_line(
+                Rect::new(content_x, visual_y, inner.width.saturating_sub(2), 1),
+                &[(&matrix_header, Style::new().fg(Color::LFO_COLOR).bold())],
+            );
+            visual_y += 1;
+        }
+
+        let env_labels = ["Attack", "Decay", "Sustain", "Release"];
+        for local_idx in 0..self.mod_matrix.row_count() {
+            if is_visible(global_row) && visual_y < max_y {
+                let is_sel = self.selected_row == global_row;
+                let is_editing = self.editing && is_sel;
+                match self.mod_matrix.row(local_idx) {
+                    ModMatrixRow::LfoRate(i) => {
+                        render_value_row_buf(
+                            buf,
+                            content_x,
+                            visual_y,
+                            &format!("LFO{} Rate", i + 1),
+                            self.mod_matrix.lfos[i].rate,
+                            0.1,
+                            32.0,
+                            is_sel,
+                            is_editing,
+                            &mut self.edit_input,
+                        );
+                    }
+                    ModMatrixRow::LfoShape(i) => {
+                        render_label_value_row_buf(
+                            buf,
+                            content_x,
+                            visual_y,
+                            &format!("LFO{} Shape", i + 1),
+                            self.mod_matrix.lfos[i].shape.name(),
+                            Color::LFO_COLOR,
+                            is_sel,
+                        );
+                    }
+                    ModMatrixRow::Envelope(i, stage) => {
+                        let env = &self.mod_matrix.envelopes[i];
+                        let val = [env.attack, env.decay, env.sustain, env.release][stage];
+                        render_value_row_buf(
+                            buf,
+                            content_x,
+                            visual_y,
+                            &format!("Env{} {}", i + 1, env_labels[stage]),
+                            val,
+                            0.0,
+                            if stage == 2 { 1.0 } else { 5.0 },
+                            is_sel,
+                            is_editing,
+                            &mut self.edit_input,
+                        );
+                    }
+                    ModMatrixRow::Route(i) => {
+                        let route = self.mod_matrix.routes[i];
+                        render_value_row_buf(
+                            buf,
+                            content_x,
+                            visual_y,
+                            &format!("Route {}", i + 1),
+                            route.depth,
+                            -1.0,
+                            1.0,
+                            is_sel,
+                            is_editing,
+                            &mut self.edit_input,
+                        );
+                        let route_style = if is_sel {
+                            Style::new().fg(Color::LFO_COLOR).bg(Color::SELECTION_BG)
+                        } else {
+                            Style::new().fg(Color::LFO_COLOR)
+                        };
+                        let route_str =
+                            format!("{} → {}", route.source.name(), route.target.name());
+                        for (j, ch) in route_str.chars().enumerate() {
+                            buf.set_cell(content_x + 42 + j as u16, visual_y, ch, route_style);
+                        }
+                    }
+                    ModMatrixRow::NoRoutes => {
+                        render_label_value_row_buf(
+                            buf,
+                            content_x,
+                            visual_y,
+                            "Routes",
+                            "(none - r: add)",
+                            Color::DARK_GRAY,
+                            is_sel,
+                        );
+                    }
+                }
+                visual_y += 1;
+            }
+            global_row += 1;
         }
 
         // Suppress unused variable warning
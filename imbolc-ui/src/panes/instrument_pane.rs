@@ -39,6 +39,7 @@ fn source_color(source: SourceType) -> Color {
         SourceType::BusIn => Color::BUS_IN_COLOR,
         SourceType::Custom(_) => Color::CUSTOM_COLOR,
         SourceType::Vst(_) => Color::VST_COLOR,
+        SourceType::MidiOut { .. } => Color::MIDI_COLOR,
     }
 }
 
@@ -202,6 +203,15 @@ impl Pane for InstrumentPane {
                     Action::None
                 }
             }
+            ActionId::InstrumentList(InstrumentListActionId::ToggleFreeze) => {
+                match state.instruments.selected_instrument() {
+                    Some(instrument) if instrument.is_frozen() => {
+                        Action::Instrument(InstrumentAction::Unfreeze(instrument.id))
+                    }
+                    Some(instrument) => Action::Instrument(InstrumentAction::Freeze(instrument.id)),
+                    None => Action::None,
+                }
+            }
 
             // Piano layer actions
             ActionId::Mode(ModeActionId::PianoEscape) => {
@@ -363,6 +373,7 @@ impl Pane for InstrumentPane {
                 Some(g) => format!(" [L{}]", g),
                 None => String::new(),
             };
+            let frozen_str = if instrument.is_frozen() { " [FRZ]" } else { "" };
 
             // Ownership indicator for network mode
             let ownership_str = match state.ownership_status(instrument.id) {
@@ -391,6 +402,9 @@ impl Pane for InstrumentPane {
             if !layer_str.is_empty() {
                 spans.push((&layer_str, mk_style(Color::ORANGE)));
             }
+            if !frozen_str.is_empty() {
+                spans.push((frozen_str, mk_style(Color::SKY_BLUE)));
+            }
             if !ownership_str.is_empty() {
                 spans.push((&ownership_str, mk_style(ownership_color)));
             }
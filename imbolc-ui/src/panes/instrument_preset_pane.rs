@@ -0,0 +1,464 @@
+use std::any::Any;
+
+use imbolc_core::state::preset_library::PresetLibrary;
+
+use crate::state::{AppState, SourceType};
+use crate::ui::action_id::{ActionId, InstrumentPresetsActionId, ModeActionId};
+use crate::ui::layout_helpers::center_rect;
+use crate::ui::widgets::TextInput;
+use crate::ui::{
+    Action, Color, InputEvent, InstrumentAction, Keymap, NavAction, Pane, Rect, RenderBuf,
+    SessionAction, Style,
+};
+
+/// Text prompt currently open in the browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Search,
+    /// Preset name followed by `#tag` words
+    Save,
+}
+
+pub struct InstrumentPresetPane {
+    keymap: Keymap,
+    library: PresetLibrary,
+    /// Indices into `library.entries` passing the current filters
+    filtered: Vec<usize>,
+    selected: usize,
+    source_filter: Option<SourceType>,
+    query: String,
+    input: TextInput,
+    prompt: Option<Prompt>,
+    /// Re-read the library before the next render (after a save was dispatched)
+    needs_refresh: bool,
+}
+
+impl InstrumentPresetPane {
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            library: PresetLibrary::default(),
+            filtered: Vec::new(),
+            selected: 0,
+            source_filter: None,
+            query: String::new(),
+            input: TextInput::new(""),
+            prompt: None,
+            needs_refresh: false,
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn refresh(&mut self) {
+        self.library = PresetLibrary::load();
+        self.refilter();
+    }
+
+    fn refilter(&mut self) {
+        self.filtered = self.library.filtered(self.source_filter, &self.query);
+        if self.selected >= self.filtered.len() {
+            self.selected = self.filtered.len().saturating_sub(1);
+        }
+    }
+
+    fn cycle_source(&mut self, forward: bool) {
+        let mut options: Vec<Option<SourceType>> = vec![None];
+        options.extend(self.library.sources().into_iter().map(Some));
+        let pos = options
+            .iter()
+            .position(|o| *o == self.source_filter)
+            .unwrap_or(0);
+        let next = if forward {
+            (pos + 1) % options.len()
+        } else {
+            (pos + options.len() - 1) % options.len()
+        };
+        self.source_filter = options[next];
+        self.selected = 0;
+        self.refilter();
+    }
+
+    fn open_prompt(&mut self, prompt: Prompt, value: &str) -> Action {
+        self.input.set_value(value);
+        self.input.select_all();
+        self.input.set_focused(true);
+        self.prompt = Some(prompt);
+        Action::PushLayer("text_edit")
+    }
+
+    fn close_prompt(&mut self) {
+        self.prompt = None;
+        self.input.set_focused(false);
+    }
+
+    fn handle_prompt_action(&mut self, action: ModeActionId, state: &AppState) -> Action {
+        let Some(prompt) = self.prompt else {
+            return Action::None;
+        };
+        match action {
+            ModeActionId::TextConfirm => {
+                self.close_prompt();
+                match prompt {
+                    Prompt::Search => {
+                        self.query = self.input.value().to_string();
+                        Action::None
+                    }
+                    Prompt::Save => {
+                        let Some(inst) = state.instruments.selected_instrument() else {
+                            return Action::None;
+                        };
+                        let (name, tags) = parse_name_and_tags(self.input.value());
+                        if name.is_empty() {
+                            return Action::None;
+                        }
+                        self.needs_refresh = true;
+                        Action::Session(SessionAction::SaveInstrumentPreset {
+                            instrument_id: inst.id,
+                            name,
+                            tags,
+                        })
+                    }
+                }
+            }
+            ModeActionId::TextCancel => {
+                self.close_prompt();
+                if prompt == Prompt::Search {
+                    self.query.clear();
+                    self.refilter();
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+}
+
+/// Split prompt text into a preset name and its `#tag` words
+pub(crate) fn parse_name_and_tags(text: &str) -> (String, Vec<String>) {
+    let mut name = Vec::new();
+    let mut tags = Vec::new();
+    for word in text.split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => tags.push(tag.to_string()),
+            Some(_) => {}
+            None => name.push(word),
+        }
+    }
+    (name.join(" "), tags)
+}
+
+impl Pane for InstrumentPresetPane {
+    fn id(&self) -> &'static str {
+        "instrument_presets"
+    }
+
+    fn on_enter(&mut self, state: &AppState) {
+        self.source_filter = state.instruments.selected_instrument().map(|i| i.source);
+        self.refresh();
+        // Fall back to everything when the current source has no presets
+        if self.filtered.is_empty() {
+            self.source_filter = None;
+            self.refilter();
+        }
+        self.selected = 0;
+    }
+
+    fn handle_action(&mut self, action: ActionId, _event: &InputEvent, state: &AppState) -> Action {
+        if let ActionId::Mode(mode_action) = action {
+            return self.handle_prompt_action(mode_action, state);
+        }
+        let ActionId::InstrumentPresets(action) = action else {
+            return Action::None;
+        };
+
+        match action {
+            InstrumentPresetsActionId::Close => Action::Nav(NavAction::PopPane),
+            InstrumentPresetsActionId::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            InstrumentPresetsActionId::Down => {
+                if self.selected + 1 < self.filtered.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            InstrumentPresetsActionId::NextSource => {
+                self.cycle_source(true);
+                Action::None
+            }
+            InstrumentPresetsActionId::PrevSource => {
+                self.cycle_source(false);
+                Action::None
+            }
+            InstrumentPresetsActionId::Search => {
+                let query = self.query.clone();
+                self.open_prompt(Prompt::Search, &query)
+            }
+            InstrumentPresetsActionId::Save => {
+                let Some(inst) = state.instruments.selected_instrument() else {
+                    return Action::None;
+                };
+                let default = format!("{} #{}", inst.name, inst.source.category());
+                self.open_prompt(Prompt::Save, &default)
+            }
+            InstrumentPresetsActionId::Load => {
+                let Some(inst) = state.instruments.selected_instrument() else {
+                    return Action::None;
+                };
+                match self
+                    .filtered
+                    .get(self.selected)
+                    .and_then(|&i| self.library.entries.get(i))
+                {
+                    Some(entry) => Action::Instrument(InstrumentAction::Update(Box::new(
+                        entry.preset.to_update(inst),
+                    ))),
+                    None => Action::None,
+                }
+            }
+            InstrumentPresetsActionId::Delete => {
+                let path = self
+                    .filtered
+                    .get(self.selected)
+                    .and_then(|&i| self.library.entries.get(i))
+                    .and_then(|e| e.path.clone());
+                match path {
+                    Some(path) => {
+                        self.needs_refresh = true;
+                        Action::Session(SessionAction::DeleteInstrumentPreset(path))
+                    }
+                    None => Action::None,
+                }
+            }
+        }
+    }
+
+    fn handle_raw_input(&mut self, event: &InputEvent, _state: &AppState) -> Action {
+        if let Some(prompt) = self.prompt {
+            self.input.handle_input(event);
+            if prompt == Prompt::Search {
+                // Filter as you type
+                self.query = self.input.value().to_string();
+                self.selected = 0;
+                self.refilter();
+            }
+        }
+        Action::None
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut RenderBuf, state: &AppState) {
+        if std::mem::take(&mut self.needs_refresh) {
+            self.refresh();
+        }
+
+        let width = 64_u16.min(area.width.saturating_sub(4));
+        let height = 26_u16.min(area.height.saturating_sub(4)).max(12);
+        let rect = center_rect(area, width, height);
+
+        let border_style = Style::new().fg(Color::CYAN);
+        let inner = buf.draw_block(rect, " Instrument Presets ", border_style, border_style);
+        let content_width = inner.width.saturating_sub(2);
+
+        // Header: load target and active filters
+        let dim = Style::new().fg(Color::DARK_GRAY);
+        let value = Style::new().fg(Color::WHITE);
+        let target = state
+            .instruments
+            .selected_instrument()
+            .map(|i| i.name.as_str())
+            .unwrap_or("(no instrument)");
+        let source = self.source_filter.map_or("All", |s| s.name());
+        buf.draw_line(
+            Rect::new(inner.x + 1, inner.y, content_width, 1),
+            &[
+                ("Target: ", dim),
+                (target, value),
+                ("   Source: ", dim),
+                (source, Style::new().fg(Color::CYAN)),
+            ],
+        );
+
+        // Search / save prompt line
+        let prompt_y = inner.y + 1;
+        match self.prompt {
+            Some(prompt) => {
+                let label = match prompt {
+                    Prompt::Search => "Search: ",
+                    Prompt::Save => "Save as: ",
+                };
+                buf.draw_line(
+                    Rect::new(inner.x + 1, prompt_y, content_width, 1),
+                    &[(label, Style::new().fg(Color::YELLOW))],
+                );
+                let field_x = inner.x + 1 + label.len() as u16;
+                let field_width = content_width.saturating_sub(label.len() as u16);
+                self.input
+                    .render_buf(buf.raw_buf(), field_x, prompt_y, field_width);
+            }
+            None if !self.query.is_empty() => {
+                buf.draw_line(
+                    Rect::new(inner.x + 1, prompt_y, content_width, 1),
+                    &[("Search: ", dim), (&self.query, value)],
+                );
+            }
+            None => {}
+        }
+
+        // Preset list
+        let list_y = inner.y + 3;
+        let max_visible = inner.height.saturating_sub(5) as usize;
+        if self.filtered.is_empty() {
+            buf.draw_line(
+                Rect::new(inner.x + 1, list_y, content_width, 1),
+                &[("No matching presets", dim)],
+            );
+        }
+        let scroll = if self.selected >= max_visible {
+            self.selected - max_visible + 1
+        } else {
+            0
+        };
+
+        for (row, &idx) in self
+            .filtered
+            .iter()
+            .skip(scroll)
+            .take(max_visible)
+            .enumerate()
+        {
+            let entry = &self.library.entries[idx];
+            let y = list_y + row as u16;
+            let is_selected = scroll + row == self.selected;
+
+            let (name_style, meta_style) = if is_selected {
+                (
+                    Style::new().fg(Color::BLACK).bg(Color::CYAN).bold(),
+                    Style::new().fg(Color::BLACK).bg(Color::CYAN),
+                )
+            } else {
+                (
+                    Style::new().fg(Color::WHITE),
+                    Style::new().fg(Color::DARK_GRAY),
+                )
+            };
+            if is_selected {
+                for x in (inner.x + 1)..(inner.x + 1 + content_width) {
+                    buf.set_cell(x, y, ' ', name_style);
+                }
+            }
+
+            let origin = if entry.is_factory() {
+                "factory"
+            } else {
+                "user"
+            };
+            let meta = format!(
+                "{}  {}  {}",
+                entry
+                    .preset
+                    .tags
+                    .iter()
+                    .map(|t| format!("#{}", t))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                entry.preset.source.name(),
+                origin
+            );
+            let prefix = if is_selected { " > " } else { "   " };
+            let name_max = (content_width as usize)
+                .saturating_sub(meta.len() + prefix.len() + 2)
+                .max(8);
+            let name: String = entry.preset.name.chars().take(name_max).collect();
+            let padding = " ".repeat(name_max.saturating_sub(name.chars().count()));
+            let meta_col = format!("  {}", meta);
+            buf.draw_line(
+                Rect::new(inner.x, y, inner.width, 1),
+                &[
+                    (prefix, name_style),
+                    (&name, name_style),
+                    (&padding, name_style),
+                    (&meta_col, meta_style),
+                ],
+            );
+        }
+
+        // Footer
+        let footer_y = rect.y + rect.height.saturating_sub(2);
+        if footer_y < area.y + area.height {
+            let hi = Style::new().fg(Color::CYAN).bold();
+            let lo = Style::new().fg(Color::DARK_GRAY);
+            let footer: &[(&str, Style)] = if self.prompt == Some(Prompt::Save) {
+                &[
+                    ("name #tag #tag  ", lo),
+                    ("[Enter]", hi),
+                    (" Save  ", lo),
+                    ("[Esc]", hi),
+                    (" Cancel", lo),
+                ]
+            } else {
+                &[
+                    ("[Enter]", hi),
+                    (" Load  ", lo),
+                    ("[Tab]", hi),
+                    (" Source  ", lo),
+                    ("[/]", hi),
+                    (" Search  ", lo),
+                    ("[S]", hi),
+                    ("ave  ", lo),
+                    ("[D]", hi),
+                    ("elete  ", lo),
+                    ("[Esc]", hi),
+                    (" Close", lo),
+                ]
+            };
+            buf.draw_line(Rect::new(inner.x + 1, footer_y, content_width, 1), footer);
+        }
+    }
+
+    fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_and_tags_are_split() {
+        let (name, tags) = parse_name_and_tags("  Warm  Pad #pad # #slow ");
+        assert_eq!(name, "Warm Pad");
+        assert_eq!(tags, vec!["pad".to_string(), "slow".to_string()]);
+    }
+
+    #[test]
+    fn load_applies_preset_as_update() {
+        let mut state = AppState::new();
+        let id = state.add_instrument(SourceType::Saw);
+        let mut pane = InstrumentPresetPane::new(Keymap::new());
+        pane.on_enter(&state);
+        // Saw has a factory preset, so the filter follows the selected source
+        assert_eq!(pane.source_filter, Some(SourceType::Saw));
+
+        let action = pane.handle_action(
+            ActionId::InstrumentPresets(InstrumentPresetsActionId::Load),
+            &InputEvent::new(crate::ui::KeyCode::Enter, crate::ui::Modifiers::none()),
+            &state,
+        );
+        match action {
+            Action::Instrument(InstrumentAction::Update(update)) => {
+                assert_eq!(update.id, id);
+                assert_eq!(update.source, SourceType::Saw);
+            }
+            other => panic!("expected Update, got {:?}", other),
+        }
+    }
+}
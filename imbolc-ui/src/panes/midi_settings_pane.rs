@@ -5,6 +5,9 @@ use crate::state::AppState;
 use crate::ui::action_id::{ActionId, MidiSettingsActionId};
 use crate::ui::{Color, InputEvent, Keymap, Pane, Rect, RenderBuf, Style};
 
+/// Member channel bend ranges offered by the settings pane, in semitones
+const MPE_BEND_RANGES: [u8; 4] = [2, 12, 24, 48];
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Section {
     Ports,
@@ -113,6 +116,34 @@ impl Pane for MidiSettingsPane {
             ActionId::MidiSettings(MidiSettingsActionId::ClearLiveInstrument) => {
                 Action::Midi(MidiAction::SetLiveInputInstrument(None))
             }
+            ActionId::MidiSettings(MidiSettingsActionId::CycleClockMode) => {
+                Action::Midi(MidiAction::SetClockMode(state.midi.clock_mode.next()))
+            }
+            ActionId::MidiSettings(MidiSettingsActionId::CycleClockPort) => {
+                let count = state.midi.output_port_names.len().max(1);
+                let next = (state.midi.clock_output_port as usize + 1) % count;
+                Action::Midi(MidiAction::SetClockOutputPort(next as u8))
+            }
+            ActionId::MidiSettings(MidiSettingsActionId::ToggleMpe) => {
+                Action::Midi(MidiAction::ToggleMpe)
+            }
+            ActionId::MidiSettings(MidiSettingsActionId::CycleMpeBendRange) => {
+                let range = state.session.midi_recording.mpe_bend_range;
+                let next = MPE_BEND_RANGES
+                    .iter()
+                    .copied()
+                    .find(|&r| r > range)
+                    .unwrap_or(MPE_BEND_RANGES[0]);
+                Action::Midi(MidiAction::SetMpeBendRange(next))
+            }
+            ActionId::MidiSettings(MidiSettingsActionId::CycleNoteRecordMode) => {
+                let mode = state.session.midi_recording.note_record_mode;
+                Action::Midi(MidiAction::SetNoteRecordMode(mode.next()))
+            }
+            ActionId::MidiSettings(MidiSettingsActionId::CycleInputQuantize) => {
+                let grid = state.session.midi_recording.next_input_quantize();
+                Action::Midi(MidiAction::SetInputQuantize(grid))
+            }
             _ => Action::None,
         }
     }
@@ -176,6 +207,14 @@ impl Pane for MidiSettingsPane {
                     y += 1;
                 }
             }
+            for (i, name) in state.midi.output_port_names.iter().enumerate() {
+                if y >= inner.y + inner.height {
+                    break;
+                }
+                let text = format!("   out {}: {}", i, name);
+                buf.draw_line(Rect::new(x, y, w, 1), &[(&text, dim)]);
+                y += 1;
+            }
         }
         y += 1;
 
@@ -268,6 +307,37 @@ impl Pane for MidiSettingsPane {
                         None => "(selected)".to_string(),
                     }
                 ),
+                format!("  Clock sync: {}", state.midi.clock_mode.name()),
+                format!(
+                    "  Clock out port: {}",
+                    match state
+                        .midi
+                        .output_port_names
+                        .get(state.midi.clock_output_port as usize)
+                    {
+                        Some(name) => format!("{} ({})", state.midi.clock_output_port, name),
+                        None => format!("{} (unavailable)", state.midi.clock_output_port),
+                    }
+                ),
+                format!(
+                    "  MPE input: {}",
+                    if state.session.midi_recording.mpe {
+                        format!("ON (bend ±{})", state.session.midi_recording.mpe_bend_range)
+                    } else {
+                        "OFF".to_string()
+                    }
+                ),
+                format!(
+                    "  Note recording: {}",
+                    state.session.midi_recording.note_record_mode.name()
+                ),
+                format!(
+                    "  Input quantize: {}",
+                    match state.session.midi_recording.input_quantize {
+                        Some(grid) => format!("{} ticks", grid),
+                        None => "Off".to_string(),
+                    }
+                ),
             ];
 
             for line in &settings {
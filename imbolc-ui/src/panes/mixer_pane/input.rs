@@ -9,7 +9,7 @@ use crate::ui::{
     Action, BusAction, InputEvent, InstrumentAction, LayerGroupAction, MixerAction, MouseButton,
     MouseEvent, MouseEventKind, NavAction, PaneId, Rect,
 };
-use imbolc_types::{BusId, EffectId};
+use imbolc_types::{BusId, EffectChainOwner, EffectId};
 
 impl MixerPane {
     pub(super) fn handle_action_impl(
@@ -18,6 +18,11 @@ impl MixerPane {
         _event: &InputEvent,
         state: &AppState,
     ) -> Action {
+        // Clip indicators are shared by every view
+        if action == ActionId::Mixer(MixerActionId::ResetClips) {
+            return Action::Mixer(MixerAction::ResetClips);
+        }
+
         // Detail mode handling
         if let Some(DetailTarget::Bus(_)) = self.detail_mode {
             return self.handle_bus_detail_action(action, state);
@@ -337,6 +342,25 @@ impl MixerPane {
                 }
                 Action::None
             }
+            ActionId::Mixer(MixerActionId::CycleSidechain) => {
+                if self.detail_section == MixerSection::Effects {
+                    if let Some((ei, _)) = self.decode_effect_cursor(state) {
+                        let effect = state
+                            .instruments
+                            .instrument(inst_id)
+                            .and_then(|inst| inst.effect_by_id(ei));
+                        let owner = EffectChainOwner::Instrument(inst_id);
+                        if let Some(next) =
+                            effect.and_then(|e| Self::next_sidechain(state, owner, e))
+                        {
+                            return Action::Instrument(InstrumentAction::SetEffectSidechain(
+                                inst_id, ei, next,
+                            ));
+                        }
+                    }
+                }
+                Action::None
+            }
             ActionId::Mixer(MixerActionId::ToggleFilter) => {
                 Action::Instrument(InstrumentAction::ToggleFilter(inst_id))
             }
@@ -497,6 +521,23 @@ impl MixerPane {
                 }
                 Action::None
             }
+            ActionId::Mixer(MixerActionId::CycleSidechain) => {
+                if self.bus_detail_section == BusDetailSection::Effects {
+                    if let Some((ei, _)) = self.decode_bus_effect_cursor(state) {
+                        let effect = state
+                            .session
+                            .bus(bus_id)
+                            .and_then(|bus| bus.effect_chain.effect_by_id(ei));
+                        let owner = EffectChainOwner::Bus(bus_id);
+                        if let Some(next) =
+                            effect.and_then(|e| Self::next_sidechain(state, owner, e))
+                        {
+                            return Action::Bus(BusAction::SetEffectSidechain(bus_id, ei, next));
+                        }
+                    }
+                }
+                Action::None
+            }
             ActionId::Mixer(MixerActionId::MoveUp) => {
                 if self.bus_detail_section == BusDetailSection::Effects {
                     if let Some((ei, _)) = self.decode_bus_effect_cursor(state) {
@@ -611,6 +652,26 @@ impl MixerPane {
                 }
                 Action::None
             }
+            ActionId::Mixer(MixerActionId::CycleSidechain) => {
+                if self.group_detail_section == GroupDetailSection::Effects {
+                    if let Some((ei, _)) = self.decode_group_effect_cursor(state) {
+                        let effect = state
+                            .session
+                            .mixer
+                            .layer_group_mixer(gid)
+                            .and_then(|gm| gm.effect_chain.effect_by_id(ei));
+                        let owner = EffectChainOwner::LayerGroup(gid);
+                        if let Some(next) =
+                            effect.and_then(|e| Self::next_sidechain(state, owner, e))
+                        {
+                            return Action::LayerGroup(LayerGroupAction::SetEffectSidechain(
+                                gid, ei, next,
+                            ));
+                        }
+                    }
+                }
+                Action::None
+            }
             ActionId::Mixer(MixerActionId::MoveUp) => {
                 if self.group_detail_section == GroupDetailSection::Effects {
                     if let Some((ei, _)) = self.decode_group_effect_cursor(state) {
@@ -7,10 +7,12 @@ use crate::panes::add_effect_pane::EffectTarget;
 use crate::state::{AppState, InstrumentId};
 use crate::ui::action_id::ActionId;
 use crate::ui::{Action, InputEvent, Keymap, MouseEvent, Pane, Rect, RenderBuf};
-use imbolc_types::BusId;
+use imbolc_types::{BusId, EffectChainOwner, EffectSlot, SidechainSource};
 
 const CHANNEL_WIDTH: u16 = 8;
 const METER_HEIGHT: u16 = 12;
+/// Span of the per-channel signal meters, in dB below full scale
+const SIGNAL_METER_RANGE_DB: f32 = 60.0;
 const NUM_VISIBLE_CHANNELS: usize = 8;
 const NUM_VISIBLE_GROUPS: usize = 2;
 const NUM_VISIBLE_BUSES: usize = 2;
@@ -283,6 +285,17 @@ impl MixerPane {
         crate::state::decode_effect_cursor_from_slice(&gm.effect_chain.effects, self.detail_cursor)
     }
 
+    /// Key source after the effect's current one, or `None` if the effect has no key input
+    fn next_sidechain(
+        state: &AppState,
+        owner: EffectChainOwner,
+        effect: &EffectSlot,
+    ) -> Option<Option<SidechainSource>> {
+        effect.effect_type.sidechain_control()?;
+        let candidates = state.instruments.sidechain_candidates(owner);
+        Some(SidechainSource::cycle(effect.sidechain, &candidates))
+    }
+
     /// Get the current effect target based on detail mode (for add_effect pane bridging)
     pub fn effect_target(&self) -> EffectTarget {
         match self.detail_mode {
@@ -1,12 +1,14 @@
 use super::{BusDetailSection, GroupDetailSection, MixerPane, MixerSection};
 use super::{
     BLOCK_CHARS, CHANNEL_WIDTH, METER_HEIGHT, NUM_VISIBLE_BUSES, NUM_VISIBLE_CHANNELS,
-    NUM_VISIBLE_GROUPS,
+    NUM_VISIBLE_GROUPS, SIGNAL_METER_RANGE_DB,
 };
 use crate::state::{AppState, MixerSelection, OutputTarget, ParamValue};
 use crate::ui::layout_helpers::center_rect;
 use crate::ui::{Color, Rect, RenderBuf, Style};
-use imbolc_types::BusId;
+use imbolc_types::{
+    BusId, ChannelLevels, ChannelMeter, ChannelMeterTarget, EffectSlot, LatencyPlan,
+};
 
 impl MixerPane {
     fn level_to_db(level: f32) -> String {
@@ -46,6 +48,34 @@ impl MixerPane {
         }
     }
 
+    /// Compact latency label for a channel strip; empty when the channel adds none.
+    fn format_latency(secs: f32) -> String {
+        let ms = secs * 1000.0;
+        if ms < 0.05 {
+            String::new()
+        } else if ms < 10.0 {
+            format!("{:.1}ms", ms)
+        } else if ms < 1000.0 {
+            format!("{:.0}ms", ms)
+        } else {
+            format!("{:.1}s", secs)
+        }
+    }
+
+    /// Effect header row: position, bypass dot, type and key source if any.
+    fn effect_label(index: usize, effect: &EffectSlot, state: &AppState) -> String {
+        let bypass_char = if effect.enabled {
+            '\u{25CF}'
+        } else {
+            '\u{25CB}'
+        };
+        let mut label = format!("{} [{}] {:?}", index + 1, bypass_char, effect.effect_type);
+        if let Some(source) = effect.sidechain_source() {
+            label.push_str(&format!(" \u{2190}{}", source.label(&state.instruments)));
+        }
+        label
+    }
+
     fn write_str(buf: &mut RenderBuf, x: u16, y: u16, text: &str, style: Style) {
         for (i, ch) in text.chars().enumerate() {
             buf.set_cell(x + i as u16, y, ch, style);
@@ -54,6 +84,8 @@ impl MixerPane {
 
     pub(super) fn render_mixer_buf(&self, buf: &mut RenderBuf, area: Rect, state: &AppState) {
         let active_groups = state.instruments.active_layer_groups();
+        let latency = LatencyPlan::compute(&state.instruments, &state.session);
+        let meters = &state.audio.visualization;
         let num_group_slots = active_groups.len().min(NUM_VISIBLE_GROUPS);
         let group_section_width = if num_group_slots > 0 {
             num_group_slots as u16 * CHANNEL_WIDTH + 2 // +2 for separator
@@ -137,6 +169,10 @@ impl MixerPane {
                     instrument.mixer.mute,
                     instrument.mixer.solo,
                     Some(instrument.mixer.output_target),
+                    latency
+                        .instrument(instrument.id)
+                        .map_or(0.0, |l| l.processing_secs),
+                    meters.channel_meter(ChannelMeterTarget::Instrument(instrument.id)),
                     is_selected,
                     label_y,
                     name_y,
@@ -189,6 +225,10 @@ impl MixerPane {
                         gm.mute,
                         gm.solo,
                         Some(gm.output_target),
+                        latency
+                            .layer_group(group_id)
+                            .map_or(0.0, |l| l.processing_secs),
+                        meters.channel_meter(ChannelMeterTarget::LayerGroup(group_id)),
                         is_selected,
                         label_y,
                         name_y,
@@ -229,6 +269,8 @@ impl MixerPane {
                 bus.mute,
                 bus.solo,
                 None,
+                latency.bus(bus.id).map_or(0.0, |l| l.processing_secs),
+                meters.channel_meter(ChannelMeterTarget::Bus(bus.id)),
                 is_selected,
                 label_y,
                 name_y,
@@ -248,8 +290,18 @@ impl MixerPane {
         }
         x += 2;
 
-        // Master
+        // Master (post-limiter analysis; its clip latch isn't tracked)
         let is_master_selected = matches!(state.session.mixer.selection, MixerSelection::Master);
+        let master_meter = ChannelMeter {
+            levels: ChannelLevels {
+                peak_l: meters.peak_l,
+                peak_r: meters.peak_r,
+                rms_l: meters.rms_l,
+                rms_r: meters.rms_r,
+                lufs_short: None,
+            },
+            clipped: false,
+        };
         Self::render_channel_buf(
             buf,
             x,
@@ -259,6 +311,8 @@ impl MixerPane {
             state.session.mixer.master_mute,
             false,
             None,
+            latency.total_secs,
+            Some(&master_meter),
             is_master_selected,
             label_y,
             name_y,
@@ -305,6 +359,44 @@ impl MixerPane {
                     &[(&info, Style::new().fg(Color::TEAL).bold())],
                 );
             }
+        } else {
+            let channel_latency = match state.session.mixer.selection {
+                MixerSelection::Instrument(idx) => state
+                    .instruments
+                    .instruments
+                    .get(idx)
+                    .and_then(|inst| latency.instrument(inst.id)),
+                MixerSelection::LayerGroup(gid) => latency.layer_group(gid),
+                MixerSelection::Bus(id) => latency.bus(id),
+                MixerSelection::Master => None,
+            };
+            let mut info = match channel_latency {
+                Some(l) => format!(
+                    "Latency: {:.1}ms  PDC delay: {:.1}ms  (worst path {:.1}ms)",
+                    l.processing_secs * 1000.0,
+                    l.output_delay_secs * 1000.0,
+                    latency.total_secs * 1000.0
+                ),
+                None => format!("Worst path latency: {:.1}ms", latency.total_secs * 1000.0),
+            };
+            let meter_target = match state.session.mixer.selection {
+                MixerSelection::Instrument(idx) => state
+                    .instruments
+                    .instruments
+                    .get(idx)
+                    .map(|inst| ChannelMeterTarget::Instrument(inst.id)),
+                MixerSelection::LayerGroup(gid) => Some(ChannelMeterTarget::LayerGroup(gid)),
+                MixerSelection::Bus(id) => Some(ChannelMeterTarget::Bus(id)),
+                MixerSelection::Master => None,
+            };
+            if let Some(meter) = meter_target.and_then(|t| meters.channel_meter(t)) {
+                info.push_str("  \u{2502}  ");
+                info.push_str(&Self::meter_readout(meter));
+            }
+            buf.draw_line(
+                Rect::new(base_x, send_y, rect.width.saturating_sub(4), 1),
+                &[(&info, Style::new().fg(Color::DARK_GRAY))],
+            );
         }
     }
 
@@ -372,12 +464,10 @@ impl MixerPane {
                 break;
             }
 
-            let bypass_char = if effect.enabled {
-                '\u{25CF}'
-            } else {
-                '\u{25CB}'
-            };
-            let effect_label = format!("{} [{}] {:?}", ei + 1, bypass_char, effect.effect_type);
+            let effect_label: String = Self::effect_label(ei, effect, state)
+                .chars()
+                .take(col1_w as usize)
+                .collect();
             let style = if self.detail_section == MixerSection::Effects
                 && self.detail_cursor == cursor_pos
             {
@@ -696,13 +786,7 @@ impl MixerPane {
                         break;
                     }
 
-                    let bypass_char = if effect.enabled {
-                        '\u{25CF}'
-                    } else {
-                        '\u{25CB}'
-                    };
-                    let effect_label =
-                        format!("{} [{}] {:?}", ei + 1, bypass_char, effect.effect_type);
+                    let effect_label = Self::effect_label(ei, effect, state);
                     let style = if self.detail_cursor == cursor_pos {
                         selected_style
                     } else {
@@ -887,13 +971,7 @@ impl MixerPane {
                         break;
                     }
 
-                    let bypass_char = if effect.enabled {
-                        '\u{25CF}'
-                    } else {
-                        '\u{25CB}'
-                    };
-                    let effect_label =
-                        format!("{} [{}] {:?}", ei + 1, bypass_char, effect.effect_type);
+                    let effect_label = Self::effect_label(ei, effect, state);
                     let style = if self.detail_cursor == cursor_pos {
                         selected_style
                     } else {
@@ -989,6 +1067,8 @@ impl MixerPane {
         mute: bool,
         solo: bool,
         output: Option<OutputTarget>,
+        latency_secs: f32,
+        meter: Option<&ChannelMeter>,
         selected: bool,
         label_y: u16,
         name_y: u16,
@@ -1035,6 +1115,11 @@ impl MixerPane {
         let meter_x = x + (CHANNEL_WIDTH / 2).saturating_sub(1);
         Self::render_meter_buf(buf, meter_x, meter_top_y, METER_HEIGHT, level);
 
+        // Signal meter beside the fader
+        let signal_x = meter_x + 2;
+        let levels = meter.map(|m| m.levels).unwrap_or_default();
+        Self::render_signal_meter_buf(buf, signal_x, meter_top_y, METER_HEIGHT, &levels);
+
         // Selection indicator
         if selected {
             let sel_x = meter_x + 1;
@@ -1057,6 +1142,17 @@ impl MixerPane {
             buf.set_cell(x + j as u16, db_y, ch, db_style);
         }
 
+        // Clip indicator (latched until reset)
+        if meter.is_some_and(|m| m.clipped) {
+            Self::write_str(
+                buf,
+                x + 4,
+                db_y,
+                "CLP",
+                Style::new().fg(Color::METER_HIGH).bold(),
+            );
+        }
+
         // Mute/Solo indicator
         let (indicator, indicator_style) = if mute {
             ("M", Style::new().fg(Color::MUTE_COLOR).bold())
@@ -1069,6 +1165,13 @@ impl MixerPane {
             buf.set_cell(x + j as u16, indicator_y, ch, indicator_style);
         }
 
+        // Processing latency (PDC)
+        let latency_str = Self::format_latency(latency_secs);
+        let latency_style = Style::new().fg(Color::ORANGE);
+        for (j, ch) in latency_str.chars().take(channel_w - 2).enumerate() {
+            buf.set_cell(x + 2 + j as u16, indicator_y, ch, latency_style);
+        }
+
         // Output routing
         if let Some(target) = output {
             let routing_style = if selected {
@@ -1116,6 +1219,72 @@ impl MixerPane {
         }
     }
 
+    /// Position of a linear amplitude on the signal meter's -60..0 dB scale.
+    fn signal_meter_frac(amplitude: f32) -> f32 {
+        if amplitude <= 0.0 {
+            return 0.0;
+        }
+        let db = 20.0 * amplitude.log10();
+        ((db + SIGNAL_METER_RANGE_DB) / SIGNAL_METER_RANGE_DB).clamp(0.0, 1.0)
+    }
+
+    /// Post-fader signal meter: solid up to RMS, shaded up to peak.
+    fn render_signal_meter_buf(
+        buf: &mut RenderBuf,
+        x: u16,
+        top_y: u16,
+        height: u16,
+        levels: &ChannelLevels,
+    ) {
+        let total_sub = height as f32 * 8.0;
+        let peak_sub = (Self::signal_meter_frac(levels.peak()) * total_sub) as u16;
+        let rms_sub = (Self::signal_meter_frac(levels.rms()) * total_sub) as u16;
+
+        for row in 0..height {
+            let inverted_row = height - 1 - row;
+            let y = top_y + row;
+            let row_start = inverted_row * 8;
+            let row_end = row_start + 8;
+            let color = Self::meter_color(inverted_row, height);
+
+            let (ch, c) = if rms_sub >= row_end {
+                ('\u{2588}', color)
+            } else if peak_sub >= row_end {
+                ('\u{2592}', color)
+            } else if peak_sub > row_start {
+                let sub_level = (peak_sub - row_start) as usize;
+                (BLOCK_CHARS[sub_level.saturating_sub(1).min(7)], color)
+            } else {
+                (' ', Color::DARK_GRAY)
+            };
+
+            buf.set_cell(x, y, ch, Style::new().fg(c));
+        }
+    }
+
+    /// Readout of a channel's levels for the info line.
+    fn meter_readout(meter: &ChannelMeter) -> String {
+        let db = |amplitude: f32| {
+            if amplitude <= 0.0 {
+                "-\u{221e}".to_string()
+            } else {
+                format!("{:.1}", (20.0 * amplitude.log10()).max(-99.0))
+            }
+        };
+        let mut readout = format!(
+            "Peak {}dB  RMS {}dB",
+            db(meter.levels.peak()),
+            db(meter.levels.rms())
+        );
+        if let Some(lufs) = meter.levels.lufs_short {
+            readout.push_str(&format!("  S {:.1} LUFS", lufs));
+        }
+        if meter.clipped {
+            readout.push_str("  CLIP");
+        }
+        readout
+    }
+
     fn render_meter_buf(buf: &mut RenderBuf, x: u16, top_y: u16, height: u16, level: f32) {
         let total_sub = height as f32 * 8.0;
         let filled_sub = (level * total_sub) as u16;
@@ -7,6 +7,7 @@ mod command_palette_pane;
 mod confirm_pane;
 mod docs_pane;
 mod eq_pane;
+mod export_pane;
 mod file_browser_pane;
 mod frame_edit_pane;
 mod groove_pane;
@@ -15,6 +16,7 @@ mod home_pane;
 mod instrument_edit_pane;
 mod instrument_pane;
 mod instrument_picker_pane;
+mod instrument_preset_pane;
 mod midi_settings_pane;
 mod mixer_pane;
 mod pane_switcher_pane;
@@ -39,6 +41,7 @@ pub use command_palette_pane::CommandPalettePane;
 pub use confirm_pane::{ConfirmPane, PendingAction};
 pub use docs_pane::DocsPane;
 pub use eq_pane::EqPane;
+pub use export_pane::ExportPane;
 pub use file_browser_pane::FileBrowserPane;
 pub use frame_edit_pane::FrameEditPane;
 pub use groove_pane::GroovePane;
@@ -47,6 +50,7 @@ pub use home_pane::HomePane;
 pub use instrument_edit_pane::InstrumentEditPane;
 pub use instrument_pane::InstrumentPane;
 pub use instrument_picker_pane::InstrumentPickerPane;
+pub use instrument_preset_pane::InstrumentPresetPane;
 pub use midi_settings_pane::MidiSettingsPane;
 pub use mixer_pane::MixerPane;
 pub use pane_switcher_pane::PaneSwitcherPane;
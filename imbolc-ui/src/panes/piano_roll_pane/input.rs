@@ -1,12 +1,14 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::state::drum_sequencer::NUM_PADS;
 use crate::state::AppState;
 use crate::ui::action_id::{ActionId, ModeActionId, PianoRollActionId};
 use crate::ui::layout_helpers::center_rect;
 use crate::ui::{
-    translate_key, Action, InputEvent, KeyCode, MouseButton, MouseEvent, MouseEventKind,
-    PianoRollAction, Rect, SequencerAction,
+    translate_key, Action, FileSelectAction, InputEvent, KeyCode, MidiImportTarget, MouseButton,
+    MouseEvent, MouseEventKind, NavAction, PaneId, PianoRollAction, Rect, SequencerAction, SessionAction,
 };
-use imbolc_types::InstrumentId;
+use imbolc_types::{InstrumentId, NoteTransform};
 
 use super::{PianoRollPane, ViewMode};
 
@@ -22,6 +24,56 @@ impl PianoRollPane {
             .unwrap_or(InstrumentId::new(0))
     }
 
+    /// Load the current track's next recorded take into the loop region
+    fn audition_next_take(&mut self, state: &AppState) -> Action {
+        let takes = state
+            .session
+            .arrangement
+            .note_takes(self.current_instrument_id(state));
+        let next = match takes
+            .iter()
+            .position(|c| Some(c.id) == self.auditioned_take)
+        {
+            Some(pos) => takes.get(pos + 1).or(takes.first()),
+            None => takes.first(),
+        };
+        match next {
+            Some(clip) => {
+                self.auditioned_take = Some(clip.id);
+                Action::PianoRoll(PianoRollAction::LoadNoteTake(clip.id))
+            }
+            None => Action::None,
+        }
+    }
+
+    /// Open the export dialog, or cancel the export that is running
+    fn request_export(&mut self, state: &AppState, stems: bool, offline: bool) -> Action {
+        if state.io.pending_export.is_some() {
+            return Action::PianoRoll(PianoRollAction::CancelExport);
+        }
+        self.export_request = Some((stems, offline));
+        Action::Nav(NavAction::PushPane(PaneId::Export))
+    }
+
+    /// Apply a note transform to the selection, or to the whole track when
+    /// nothing is selected
+    fn transform_notes(&self, transform: NoteTransform) -> Action {
+        let (track, start_tick, end_tick, start_pitch, end_pitch) =
+            if self.selection_anchor.is_some() {
+                self.selection_region()
+            } else {
+                (self.current_track, 0, u32::MAX, 0, 127)
+            };
+        Action::PianoRoll(PianoRollAction::TransformNotes {
+            track,
+            start_tick,
+            end_tick,
+            start_pitch,
+            end_pitch,
+            transform,
+        })
+    }
+
     /// Visible steps that fit in the sequencer grid (same logic as standalone sequencer pane)
     fn seq_visible_steps(&self, box_width: u16) -> usize {
         let available = (box_width as usize).saturating_sub(15);
@@ -29,6 +81,28 @@ impl PianoRollPane {
     }
 }
 
+/// Bake the project's humanize amounts into notes (half strength when
+/// humanize is off), with a fresh seed each time.
+fn humanize_transform(state: &AppState) -> NoteTransform {
+    let settings = state.session.humanize;
+    let (timing, velocity) = if settings.timing > 0.0 || settings.velocity > 0.0 {
+        (settings.timing, settings.velocity)
+    } else {
+        (0.5, 0.5)
+    };
+    // Same ranges as playback humanize: up to 20ms and 30 velocity steps
+    let pr = &state.session.piano_roll;
+    let ticks_per_sec = pr.bpm as f64 / 60.0 * pr.ticks_per_beat as f64;
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    NoteTransform::Humanize {
+        timing: (timing as f64 * 0.02 * ticks_per_sec).round() as u32,
+        velocity: (velocity * 30.0).round() as u8,
+        seed,
+    }
+}
+
 impl PianoRollPane {
     pub(super) fn handle_action_impl(
         &mut self,
@@ -166,32 +240,28 @@ impl PianoRollPane {
             }
             ActionId::PianoRoll(PianoRollActionId::SelectUp) => {
                 if self.seq_selection_anchor.is_none() {
-                    self.seq_selection_anchor =
-                        Some((self.seq_cursor_pad, self.seq_cursor_step));
+                    self.seq_selection_anchor = Some((self.seq_cursor_pad, self.seq_cursor_step));
                 }
                 self.seq_cursor_pad = self.seq_cursor_pad.saturating_sub(1);
                 Action::None
             }
             ActionId::PianoRoll(PianoRollActionId::SelectDown) => {
                 if self.seq_selection_anchor.is_none() {
-                    self.seq_selection_anchor =
-                        Some((self.seq_cursor_pad, self.seq_cursor_step));
+                    self.seq_selection_anchor = Some((self.seq_cursor_pad, self.seq_cursor_step));
                 }
                 self.seq_cursor_pad = (self.seq_cursor_pad + 1).min(NUM_PADS - 1);
                 Action::None
             }
             ActionId::PianoRoll(PianoRollActionId::SelectLeft) => {
                 if self.seq_selection_anchor.is_none() {
-                    self.seq_selection_anchor =
-                        Some((self.seq_cursor_pad, self.seq_cursor_step));
+                    self.seq_selection_anchor = Some((self.seq_cursor_pad, self.seq_cursor_step));
                 }
                 self.seq_cursor_step = self.seq_cursor_step.saturating_sub(1);
                 Action::None
             }
             ActionId::PianoRoll(PianoRollActionId::SelectRight) => {
                 if self.seq_selection_anchor.is_none() {
-                    self.seq_selection_anchor =
-                        Some((self.seq_cursor_pad, self.seq_cursor_step));
+                    self.seq_selection_anchor = Some((self.seq_cursor_pad, self.seq_cursor_step));
                 }
                 self.seq_cursor_step = (self.seq_cursor_step + 1).min(pattern_length - 1);
                 Action::None
@@ -233,18 +303,19 @@ impl PianoRollPane {
                 PianoRollAction::RenderToWav(self.current_instrument_id(state)),
             ),
             ActionId::PianoRoll(PianoRollActionId::BounceToWav) => {
-                if state.io.pending_export.is_some() {
-                    Action::PianoRoll(PianoRollAction::CancelExport)
-                } else {
-                    Action::PianoRoll(PianoRollAction::BounceToWav)
-                }
+                self.request_export(state, false, false)
             }
             ActionId::PianoRoll(PianoRollActionId::ExportStems) => {
-                if state.io.pending_export.is_some() {
-                    Action::PianoRoll(PianoRollAction::CancelExport)
-                } else {
-                    Action::PianoRoll(PianoRollAction::ExportStems)
-                }
+                self.request_export(state, true, false)
+            }
+            ActionId::PianoRoll(PianoRollActionId::BounceOffline) => {
+                self.request_export(state, false, true)
+            }
+            ActionId::PianoRoll(PianoRollActionId::ExportStemsOffline) => {
+                self.request_export(state, true, true)
+            }
+            ActionId::PianoRoll(PianoRollActionId::ExportMidi) => {
+                Action::PianoRoll(PianoRollAction::ExportMidi)
             }
             _ => Action::None,
         }
@@ -410,23 +481,104 @@ impl PianoRollPane {
                 PianoRollAction::RenderToWav(self.current_instrument_id(state)),
             ),
             ActionId::PianoRoll(PianoRollActionId::BounceToWav) => {
-                if state.io.pending_export.is_some() {
-                    Action::PianoRoll(PianoRollAction::CancelExport)
-                } else {
-                    Action::PianoRoll(PianoRollAction::BounceToWav)
-                }
+                self.request_export(state, false, false)
             }
             ActionId::PianoRoll(PianoRollActionId::ExportStems) => {
-                if state.io.pending_export.is_some() {
-                    Action::PianoRoll(PianoRollAction::CancelExport)
-                } else {
-                    Action::PianoRoll(PianoRollAction::ExportStems)
-                }
+                self.request_export(state, true, false)
+            }
+            ActionId::PianoRoll(PianoRollActionId::BounceOffline) => {
+                self.request_export(state, false, true)
+            }
+            ActionId::PianoRoll(PianoRollActionId::ExportStemsOffline) => {
+                self.request_export(state, true, true)
+            }
+            ActionId::PianoRoll(PianoRollActionId::ExportMidi) => {
+                Action::PianoRoll(PianoRollAction::ExportMidi)
+            }
+            ActionId::PianoRoll(PianoRollActionId::ImportMidi) => {
+                Action::Session(SessionAction::OpenFileBrowser(
+                    FileSelectAction::ImportMidiFile(MidiImportTarget::PianoRoll),
+                ))
             }
             ActionId::PianoRoll(PianoRollActionId::ToggleAutomation) => {
                 self.automation_overlay_visible = !self.automation_overlay_visible;
                 Action::None
             }
+            ActionId::PianoRoll(PianoRollActionId::Quantize) => {
+                self.transform_notes(NoteTransform::Quantize {
+                    grid: self.ticks_per_cell(),
+                    strength: 1.0,
+                    swing: 0.0,
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::QuantizeSoft) => {
+                self.transform_notes(NoteTransform::Quantize {
+                    grid: self.ticks_per_cell(),
+                    strength: 0.5,
+                    swing: 0.0,
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::QuantizeSwing) => {
+                let swing = state.session.piano_roll.swing_amount;
+                self.transform_notes(NoteTransform::Quantize {
+                    grid: self.ticks_per_cell(),
+                    strength: 1.0,
+                    swing: if swing > 0.0 { swing } else { 0.5 },
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::TransposeUp) => {
+                self.transform_notes(NoteTransform::Transpose(1))
+            }
+            ActionId::PianoRoll(PianoRollActionId::TransposeDown) => {
+                self.transform_notes(NoteTransform::Transpose(-1))
+            }
+            ActionId::PianoRoll(PianoRollActionId::DegreeUp) => {
+                self.transform_notes(NoteTransform::TransposeDegrees {
+                    degrees: 1,
+                    key: state.session.key,
+                    scale: state.session.scale,
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::DegreeDown) => {
+                self.transform_notes(NoteTransform::TransposeDegrees {
+                    degrees: -1,
+                    key: state.session.key,
+                    scale: state.session.scale,
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::Legato) => {
+                self.transform_notes(NoteTransform::Legato)
+            }
+            ActionId::PianoRoll(PianoRollActionId::FixedLength) => {
+                self.transform_notes(NoteTransform::FixedLength(self.default_duration))
+            }
+            ActionId::PianoRoll(PianoRollActionId::VelocityScaleUp) => {
+                self.transform_notes(NoteTransform::ScaleVelocity(1.1))
+            }
+            ActionId::PianoRoll(PianoRollActionId::VelocityScaleDown) => {
+                self.transform_notes(NoteTransform::ScaleVelocity(0.9))
+            }
+            ActionId::PianoRoll(PianoRollActionId::CompressVelocity) => {
+                self.transform_notes(NoteTransform::CompressVelocity(0.5))
+            }
+            ActionId::PianoRoll(PianoRollActionId::ReverseNotes) => {
+                self.transform_notes(NoteTransform::Reverse)
+            }
+            ActionId::PianoRoll(PianoRollActionId::InvertNotes) => {
+                self.transform_notes(NoteTransform::Invert)
+            }
+            ActionId::PianoRoll(PianoRollActionId::SnapToScale) => {
+                self.transform_notes(NoteTransform::SnapToScale {
+                    key: state.session.key,
+                    scale: state.session.scale,
+                })
+            }
+            ActionId::PianoRoll(PianoRollActionId::HumanizeNotes) => {
+                self.transform_notes(humanize_transform(state))
+            }
+            ActionId::PianoRoll(PianoRollActionId::AuditionNextTake) => {
+                self.audition_next_take(state)
+            }
             ActionId::PianoRoll(PianoRollActionId::AutomationLanePrev) => {
                 if self.automation_overlay_visible {
                     match self.automation_overlay_lane_idx {
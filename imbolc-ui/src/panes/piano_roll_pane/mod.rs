@@ -11,7 +11,7 @@ use crate::ui::{
     Action, InputEvent, Keymap, MouseEvent, Pane, PianoKeyboard, PianoRollAction, Rect, RenderBuf,
     ToggleResult,
 };
-use imbolc_types::InstrumentId;
+use imbolc_types::{ClipId, InstrumentId};
 
 /// View mode for the piano roll pane: note editor (default) or step sequencer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,6 +47,10 @@ pub struct PianoRollPane {
     pub(super) seq_cursor_step: usize,
     pub(super) seq_view_start_step: usize,
     pub(super) seq_selection_anchor: Option<(usize, usize)>, // (pad, step)
+    /// Export the export dialog should be opened for: (stems, offline)
+    pub(super) export_request: Option<(bool, bool)>,
+    /// Take clip last loaded into the track for auditioning
+    pub(super) auditioned_take: Option<ClipId>,
 }
 
 impl PianoRollPane {
@@ -71,9 +75,16 @@ impl PianoRollPane {
             seq_cursor_step: 0,
             seq_view_start_step: 0,
             seq_selection_anchor: None,
+            export_request: None,
+            auditioned_take: None,
         }
     }
 
+    /// Take the export the user asked for, to configure the export dialog
+    pub fn take_export_request(&mut self) -> Option<(bool, bool)> {
+        self.export_request.take()
+    }
+
     /// Set current track index directly (for external syncing from global instrument selection)
     #[allow(dead_code)]
     pub fn current_track(&self) -> usize {
@@ -44,6 +44,13 @@ impl SampleChopperPane {
             .and_then(|d| d.chopper.as_ref())
     }
 
+    /// Loaded sample's tempo, or the project tempo when none was detected
+    fn source_bpm(&self, state: &AppState) -> f32 {
+        self.get_chopper_state(state)
+            .and_then(|c| c.source_bpm)
+            .unwrap_or(state.session.bpm as f32)
+    }
+
     fn should_show_file_browser(&self, state: &AppState) -> bool {
         self.selected_drum_sequencer(state)
             .map(|d| d.chopper.is_none())
@@ -116,6 +123,23 @@ impl Pane for SampleChopperPane {
             ActionId::SampleChopper(SampleChopperActionId::Preview) => {
                 Action::Chopper(ChopperAction::PreviewSlice)
             }
+            ActionId::SampleChopper(SampleChopperActionId::ToggleWarp) => {
+                Action::Chopper(ChopperAction::ToggleWarp)
+            }
+            ActionId::SampleChopper(SampleChopperActionId::BpmUp) => {
+                Action::Chopper(ChopperAction::AdjustSourceBpm(1.0))
+            }
+            ActionId::SampleChopper(SampleChopperActionId::BpmDown) => {
+                Action::Chopper(ChopperAction::AdjustSourceBpm(-1.0))
+            }
+            ActionId::SampleChopper(SampleChopperActionId::DoubleBpm) => {
+                let bpm = self.source_bpm(state);
+                Action::Chopper(ChopperAction::AdjustSourceBpm(bpm))
+            }
+            ActionId::SampleChopper(SampleChopperActionId::HalveBpm) => {
+                let bpm = self.source_bpm(state);
+                Action::Chopper(ChopperAction::AdjustSourceBpm(-bpm / 2.0))
+            }
             ActionId::SampleChopper(SampleChopperActionId::Back) => Action::Nav(NavAction::PopPane),
             ActionId::SampleChopper(SampleChopperActionId::AssignToPad(pad_num)) => {
                 Action::Chopper(ChopperAction::AssignToPad(
@@ -185,12 +209,18 @@ impl Pane for SampleChopperPane {
             &[(&filename, Style::new().fg(Color::CYAN).bold())],
         );
 
+        let tempo = match (chopper.source_bpm, chopper.warp) {
+            (Some(bpm), true) => format!("{:.1}→{} BPM", bpm, state.session.bpm),
+            (Some(bpm), false) => format!("{:.1} BPM", bpm),
+            (None, _) => "? BPM".to_string(),
+        };
         let info = format!(
-            "{:.1}s   {} slices",
+            "{}   {:.1}s   {} slices",
+            tempo,
             chopper.duration_secs,
             chopper.slices.len()
         );
-        let info_x = rect.x + rect.width - 2 - info.len() as u16;
+        let info_x = rect.x + rect.width - 2 - info.chars().count() as u16;
         buf.draw_line(
             Rect::new(
                 info_x,
@@ -1,7 +1,7 @@
 use std::any::Any;
 
-use crate::state::drum_sequencer::NUM_PADS;
-use crate::state::AppState;
+use crate::state::drum_sequencer::{DrumPad, ParamLock, StepCondition, NUM_PADS};
+use crate::state::{AppState, ParameterTarget};
 use crate::ui::action_id::{ActionId, SequencerActionId};
 use crate::ui::layout_helpers::center_rect;
 use crate::ui::{
@@ -16,6 +16,9 @@ pub struct SequencerPane {
     view_start_step: usize,
     /// Selection anchor (pad, step). None = no selection.
     pub(crate) selection_anchor: Option<(usize, usize)>,
+    /// Index into the cursor pad's `ParamLock::targets()` of the parameter
+    /// lock being edited
+    pub(crate) lock_target: usize,
 }
 
 impl SequencerPane {
@@ -26,9 +29,26 @@ impl SequencerPane {
             cursor_step: 0,
             view_start_step: 0,
             selection_anchor: None,
+            lock_target: 0,
         }
     }
 
+    /// Targets the pad under the cursor can lock
+    fn lock_targets(&self, state: &AppState) -> Vec<ParameterTarget> {
+        let default = DrumPad::default();
+        let pad = state
+            .instruments
+            .selected_drum_sequencer()
+            .and_then(|seq| seq.pads.get(self.cursor_pad))
+            .unwrap_or(&default);
+        ParamLock::targets(pad, state.session.bus_ids())
+    }
+
+    fn lock_target(&self, state: &AppState) -> ParameterTarget {
+        let targets = self.lock_targets(state);
+        targets[self.lock_target % targets.len()]
+    }
+
     /// Returns the selection region as (start_pad, end_pad, start_step, end_step),
     /// or a single cell at the cursor if no selection is active.
     pub(crate) fn selection_region(&self) -> (usize, usize, usize, usize) {
@@ -61,6 +81,17 @@ impl SequencerPane {
     }
 }
 
+/// Compact display of a parameter lock value in the target's units
+fn lock_value_label(target: &ParameterTarget, value: f32) -> String {
+    match target {
+        ParameterTarget::FilterCutoff => format!("{:.0}Hz", value),
+        ParameterTarget::Attack | ParameterTarget::Decay | ParameterTarget::Release => {
+            format!("{:.2}s", value)
+        }
+        _ => format!("{:.2}", value),
+    }
+}
+
 impl Default for SequencerPane {
     fn default() -> Self {
         Self::new(Keymap::new())
@@ -223,6 +254,55 @@ impl Pane for SequencerPane {
             ActionId::Sequencer(SequencerActionId::CycleGrid) => {
                 Action::Sequencer(SequencerAction::CycleStepResolution)
             }
+            ActionId::Sequencer(SequencerActionId::CycleLockParam) => {
+                let count = self.lock_targets(state).len();
+                self.lock_target = (self.lock_target + 1) % count;
+                Action::None
+            }
+            ActionId::Sequencer(SequencerActionId::LockUp) => {
+                Action::Sequencer(SequencerAction::AdjustParamLock(
+                    self.cursor_pad,
+                    self.cursor_step,
+                    self.lock_target(state),
+                    1,
+                ))
+            }
+            ActionId::Sequencer(SequencerActionId::LockDown) => {
+                Action::Sequencer(SequencerAction::AdjustParamLock(
+                    self.cursor_pad,
+                    self.cursor_step,
+                    self.lock_target(state),
+                    -1,
+                ))
+            }
+            ActionId::Sequencer(SequencerActionId::NextCondition) => Action::Sequencer(
+                SequencerAction::CycleStepCondition(self.cursor_pad, self.cursor_step, 1),
+            ),
+            ActionId::Sequencer(SequencerActionId::PrevCondition) => Action::Sequencer(
+                SequencerAction::CycleStepCondition(self.cursor_pad, self.cursor_step, -1),
+            ),
+            ActionId::Sequencer(SequencerActionId::RatchetUp) => Action::Sequencer(
+                SequencerAction::AdjustRatchet(self.cursor_pad, self.cursor_step, 1),
+            ),
+            ActionId::Sequencer(SequencerActionId::RatchetDown) => Action::Sequencer(
+                SequencerAction::AdjustRatchet(self.cursor_pad, self.cursor_step, -1),
+            ),
+            ActionId::Sequencer(SequencerActionId::RatchetDecayUp) => Action::Sequencer(
+                SequencerAction::AdjustRatchetDecay(self.cursor_pad, self.cursor_step, 0.1),
+            ),
+            ActionId::Sequencer(SequencerActionId::RatchetDecayDown) => Action::Sequencer(
+                SequencerAction::AdjustRatchetDecay(self.cursor_pad, self.cursor_step, -0.1),
+            ),
+            ActionId::Sequencer(SequencerActionId::ToggleFill) => {
+                Action::Sequencer(SequencerAction::ToggleFill)
+            }
+            ActionId::Sequencer(SequencerActionId::ClearLock) => {
+                Action::Sequencer(SequencerAction::ClearParamLock(
+                    self.cursor_pad,
+                    self.cursor_step,
+                    self.lock_target(state),
+                ))
+            }
             _ => Action::None,
         }
     }
@@ -291,6 +371,7 @@ impl Pane for SequencerPane {
         let grid_str = format!("  Grid: {}", grid_label);
         let bpm_str = format!("  BPM: {:.0}", state.audio.bpm);
         let play_str = format!("  {}", play_label);
+        let fill_str = if seq.fill { "  FILL" } else { "" };
         buf.draw_line(
             Rect::new(cx, cy, rect.width.saturating_sub(4), 1),
             &[
@@ -299,6 +380,7 @@ impl Pane for SequencerPane {
                 (&grid_str, Style::new().fg(Color::CYAN)),
                 (&bpm_str, Style::new().fg(Color::DARK_GRAY)),
                 (&play_str, Style::new().fg(play_color).bold()),
+                (fill_str, Style::new().fg(Color::MAGENTA).bold()),
             ],
         );
 
@@ -404,7 +486,15 @@ impl Pane for SequencerPane {
                 };
 
                 let style = Style::new().fg(fg).bg(bg);
-                let chars: Vec<char> = if step.active { " █ " } else { " · " }.chars().collect();
+                // Steps with parameter locks get their own mark
+                let mark = if !step.active {
+                    " · "
+                } else if step.locks.is_empty() {
+                    " █ "
+                } else {
+                    " ◆ "
+                };
+                let chars: Vec<char> = mark.chars().collect();
                 for (j, ch) in chars.iter().enumerate() {
                     buf.set_cell(x + j as u16, y, *ch, style);
                 }
@@ -499,15 +589,55 @@ impl Pane for SequencerPane {
 
         // Velocity
         let step = &pattern.steps[self.cursor_pad][self.cursor_step];
-        let vel_str = if step.pitch_offset != 0 {
+        let mut vel_str = if step.pitch_offset != 0 {
             format!("Vel: {}  P:{:+}", step.velocity, step.pitch_offset)
         } else {
             format!("Vel: {}", step.velocity)
         };
+        if step.condition != StepCondition::Always {
+            vel_str.push_str(&format!("  {}", step.condition.label()));
+        }
+        if step.ratchet > 1 {
+            vel_str.push_str(&format!("  x{} {:.1}", step.ratchet, step.ratchet_decay));
+        }
         for (j, ch) in vel_str.chars().enumerate() {
             buf.set_cell(info_x + info_offset + j as u16, detail_y, ch, dark_gray);
         }
 
+        // Parameter lock line: the parameter being edited and the step's locks
+        let lock_y = detail_y + 1;
+        let lock_target = self.lock_target(state);
+        let lock_str = format!("Lock: {}", lock_target.name());
+        let lock_value = step
+            .lock(&lock_target)
+            .map(|v| lock_value_label(&lock_target, v))
+            .unwrap_or_else(|| "—".to_string());
+        let step_locks: Vec<String> = step
+            .locks
+            .iter()
+            .map(|l| {
+                format!(
+                    "{} {}",
+                    l.target.short_name(),
+                    lock_value_label(&l.target, l.value)
+                )
+            })
+            .collect();
+        let step_locks_str = if step_locks.is_empty() {
+            String::new()
+        } else {
+            format!("   Step: {}", step_locks.join("  "))
+        };
+        let lock_value_str = format!(" {}", lock_value);
+        buf.draw_line(
+            Rect::new(cx, lock_y, rect.width.saturating_sub(4), 1),
+            &[
+                (&lock_str, Style::new().fg(Color::CYAN)),
+                (&lock_value_str, Style::new().fg(Color::WHITE).bold()),
+                (&step_locks_str, dark_gray),
+            ],
+        );
+
         // Scroll indicator
         if pattern.length > visible {
             let scroll_str = format!(
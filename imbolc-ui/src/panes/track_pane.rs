@@ -1,13 +1,25 @@
 use std::any::Any;
 
-use crate::state::arrangement::PlayMode;
+use crate::state::arrangement::{Marker, MarkerId, PlayMode};
+use crate::state::piano_roll::{MeterChange, TempoEvent};
 use crate::state::{AppState, SourceType};
-use crate::ui::action_id::{ActionId, TrackActionId};
+use crate::ui::action_id::{ActionId, ModeActionId, TrackActionId};
 use crate::ui::layout_helpers::center_rect;
+use crate::ui::widgets::TextInput;
 use crate::ui::{
-    Action, ArrangementAction, Color, InputEvent, Keymap, Pane, Rect, RenderBuf, Style,
+    Action, ArrangementAction, Color, FileSelectAction, InputEvent, Keymap, MidiImportTarget, Pane,
+    Rect, RenderBuf, ServerAction, SessionAction, Style,
 };
 
+/// Glyphs for drawing audio clip waveforms, from quietest to loudest
+const WAVE_GLYPHS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Time signatures offered when cycling a bar's meter
+const METERS: [(u8, u8); 6] = [(4, 4), (3, 4), (2, 4), (5, 4), (6, 8), (7, 8)];
+
+/// Length in bars of sections added or inserted from the track pane
+const SECTION_BARS: u32 = 4;
+
 fn source_color(source: SourceType) -> Color {
     match source {
         // Oscillators and synths
@@ -36,6 +48,7 @@ fn source_color(source: SourceType) -> Color {
         SourceType::BusIn => Color::BUS_IN_COLOR,
         SourceType::Custom(_) => Color::CUSTOM_COLOR,
         SourceType::Vst(_) => Color::VST_COLOR,
+        SourceType::MidiOut { .. } => Color::MIDI_COLOR,
     }
 }
 
@@ -43,6 +56,9 @@ pub struct TrackPane {
     keymap: Keymap,
     /// Index into current instrument's clips list for placement selection
     selected_clip_index: usize,
+    /// Marker being renamed, while the name prompt is open
+    renaming_marker: Option<MarkerId>,
+    marker_input: TextInput,
 }
 
 impl TrackPane {
@@ -50,12 +66,83 @@ impl TrackPane {
         Self {
             keymap,
             selected_clip_index: 0,
+            renaming_marker: None,
+            marker_input: TextInput::new(""),
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.renaming_marker.is_some()
+    }
+
+    fn open_rename(&mut self, marker: &Marker) -> Action {
+        self.marker_input.set_value(&marker.name);
+        self.marker_input.select_all();
+        self.marker_input.set_focused(true);
+        self.renaming_marker = Some(marker.id);
+        Action::PushLayer("text_edit")
+    }
+
+    fn handle_rename_action(&mut self, action: ModeActionId) -> Action {
+        let Some(id) = self.renaming_marker else {
+            return Action::None;
+        };
+        match action {
+            ModeActionId::TextConfirm | ModeActionId::TextCancel => {
+                self.renaming_marker = None;
+                self.marker_input.set_focused(false);
+                let name = self.marker_input.value().trim();
+                if action == ModeActionId::TextConfirm && !name.is_empty() {
+                    Action::Arrangement(ArrangementAction::RenameMarker(id, name.to_string()))
+                } else {
+                    Action::None
+                }
+            }
+            _ => Action::None,
         }
     }
 
+    /// Bar-aligned range of a new section starting at the cursor's bar
+    fn new_section_range(state: &AppState) -> (u32, u32) {
+        let pr = &state.session.piano_roll;
+        let pos = pr.bar_at_tick(state.session.arrangement.cursor_tick);
+        (pos.bar_start, pr.bar_start_tick(pos.bar + SECTION_BARS))
+    }
+
+    /// Run `f` on the section under the cursor, if any
+    fn with_section(state: &AppState, f: impl FnOnce(MarkerId) -> ArrangementAction) -> Action {
+        let arr = &state.session.arrangement;
+        match arr.marker_at(arr.cursor_tick) {
+            Some(marker) => Action::Arrangement(f(marker.id)),
+            None => Action::None,
+        }
+    }
+
+    /// Length of the bar under the cursor
     fn ticks_per_bar(&self, state: &AppState) -> u32 {
-        let (beats, _) = state.session.time_signature;
-        beats as u32 * 480
+        let arr = &state.session.arrangement;
+        state
+            .session
+            .piano_roll
+            .bar_at_tick(arr.cursor_tick)
+            .ticks_per_bar
+    }
+
+    /// The tempo event in effect at `tick`: the last one at or before it
+    fn tempo_event_at(state: &AppState, tick: u32) -> Option<TempoEvent> {
+        let tempos = &state.session.piano_roll.tempo_map.tempos;
+        let idx = tempos.partition_point(|e| e.tick <= tick);
+        idx.checked_sub(1).map(|i| tempos[i])
+    }
+
+    fn nudge_tempo(state: &AppState, delta: f32) -> Action {
+        match Self::tempo_event_at(state, state.session.arrangement.cursor_tick) {
+            Some(event) => Action::Arrangement(ArrangementAction::SetTempoEvent(TempoEvent {
+                bpm: event.bpm + delta,
+                ..event
+            })),
+            None => Action::None,
+        }
     }
 }
 
@@ -71,6 +158,9 @@ impl Pane for TrackPane {
     }
 
     fn handle_action(&mut self, action: ActionId, _event: &InputEvent, state: &AppState) -> Action {
+        if let ActionId::Mode(mode_action) = action {
+            return self.handle_rename_action(mode_action);
+        }
         let arr = &state.session.arrangement;
         let num_instruments = state.instruments.instruments.len();
         if num_instruments == 0 {
@@ -148,9 +238,11 @@ impl Pane for TrackPane {
                 }
             }
             ActionId::Track(TrackActionId::Delete) => {
-                // Delete selected placement
+                // Delete selected placement, or the audio clip under the cursor
                 if let Some(placement) = arr.placement_at(instrument_id, arr.cursor_tick) {
                     Action::Arrangement(ArrangementAction::RemovePlacement(placement.id))
+                } else if let Some(clip) = arr.audio_clip_at(instrument_id, arr.cursor_tick) {
+                    Action::Arrangement(ArrangementAction::DeleteAudioClip(clip.id))
                 } else {
                     Action::None
                 }
@@ -168,6 +260,8 @@ impl Pane for TrackPane {
             ActionId::Track(TrackActionId::Duplicate) => {
                 if let Some(placement) = arr.placement_at(instrument_id, arr.cursor_tick) {
                     Action::Arrangement(ArrangementAction::DuplicatePlacement(placement.id))
+                } else if let Some(clip) = arr.audio_clip_at(instrument_id, arr.cursor_tick) {
+                    Action::Arrangement(ArrangementAction::DuplicateAudioClip(clip.id))
                 } else {
                     Action::None
                 }
@@ -185,6 +279,11 @@ impl Pane for TrackPane {
                         placement_id: placement.id,
                         new_start_tick: new_start,
                     })
+                } else if let Some(clip) = arr.audio_clip_at(instrument_id, arr.cursor_tick) {
+                    Action::Arrangement(ArrangementAction::MoveAudioClip {
+                        clip_id: clip.id,
+                        new_start_tick: clip.start_tick.saturating_sub(arr.ticks_per_col),
+                    })
                 } else {
                     Action::None
                 }
@@ -196,6 +295,11 @@ impl Pane for TrackPane {
                         placement_id: placement.id,
                         new_start_tick: new_start,
                     })
+                } else if let Some(clip) = arr.audio_clip_at(instrument_id, arr.cursor_tick) {
+                    Action::Arrangement(ArrangementAction::MoveAudioClip {
+                        clip_id: clip.id,
+                        new_start_tick: clip.start_tick + arr.ticks_per_col,
+                    })
                 } else {
                     Action::None
                 }
@@ -254,10 +358,143 @@ impl Pane for TrackPane {
                 }
                 Action::None
             }
+            ActionId::Track(TrackActionId::ImportMidi) => {
+                Action::Session(SessionAction::OpenFileBrowser(
+                    FileSelectAction::ImportMidiFile(MidiImportTarget::Arrangement),
+                ))
+            }
+            ActionId::Track(TrackActionId::ImportAudio) => {
+                Action::Session(SessionAction::OpenFileBrowser(
+                    FileSelectAction::ImportAudioClip(instrument_id, arr.cursor_tick),
+                ))
+            }
+            ActionId::Track(TrackActionId::ToggleArm) => {
+                Action::Server(ServerAction::ToggleRecordArm(instrument_id))
+            }
+            ActionId::Track(TrackActionId::Record) => Action::Server(ServerAction::RecordInput),
+            ActionId::Track(TrackActionId::PunchIn) => {
+                let tick =
+                    (state.recording.punch_in != Some(arr.cursor_tick)).then_some(arr.cursor_tick);
+                Action::Server(ServerAction::SetPunchIn(tick))
+            }
+            ActionId::Track(TrackActionId::PunchOut) => {
+                let tick =
+                    (state.recording.punch_out != Some(arr.cursor_tick)).then_some(arr.cursor_tick);
+                Action::Server(ServerAction::SetPunchOut(tick))
+            }
+            ActionId::Track(TrackActionId::CycleTake) => {
+                Action::Arrangement(ArrangementAction::CycleTake {
+                    instrument_id,
+                    tick: arr.cursor_tick,
+                })
+            }
+            ActionId::Track(TrackActionId::ToggleTempo) => {
+                let pr = &state.session.piano_roll;
+                if pr.tempo_map.tempo_event(arr.cursor_tick).is_some() {
+                    Action::Arrangement(ArrangementAction::RemoveTempoEvent(arr.cursor_tick))
+                } else {
+                    Action::Arrangement(ArrangementAction::SetTempoEvent(TempoEvent {
+                        tick: arr.cursor_tick,
+                        bpm: pr.bpm_at(arr.cursor_tick as f64).round(),
+                        ramp: false,
+                    }))
+                }
+            }
+            ActionId::Track(TrackActionId::ToggleTempoRamp) => {
+                match Self::tempo_event_at(state, arr.cursor_tick) {
+                    Some(event) => {
+                        Action::Arrangement(ArrangementAction::SetTempoEvent(TempoEvent {
+                            ramp: !event.ramp,
+                            ..event
+                        }))
+                    }
+                    None => Action::None,
+                }
+            }
+            ActionId::Track(TrackActionId::TempoUp) => Self::nudge_tempo(state, 1.0),
+            ActionId::Track(TrackActionId::TempoDown) => Self::nudge_tempo(state, -1.0),
+            ActionId::Track(TrackActionId::CycleMeter) => {
+                let pr = &state.session.piano_roll;
+                let bar = pr.bar_at_tick(arr.cursor_tick).bar;
+                let map = &pr.tempo_map;
+                let current = map.time_signature_at_bar(pr.time_signature, bar);
+                let next = METERS
+                    .iter()
+                    .position(|m| *m == current)
+                    .map_or(METERS[0], |i| METERS[(i + 1) % METERS.len()]);
+                let before = match bar {
+                    0 => pr.time_signature,
+                    _ => map.time_signature_at_bar(pr.time_signature, bar - 1),
+                };
+                // Cycling back to the preceding meter drops the change
+                if next == before {
+                    Action::Arrangement(ArrangementAction::RemoveMeterChange(bar))
+                } else {
+                    Action::Arrangement(ArrangementAction::SetMeterChange(MeterChange {
+                        bar,
+                        time_signature: next,
+                    }))
+                }
+            }
+            ActionId::Track(TrackActionId::ToggleMarker) => {
+                match arr.markers.iter().find(|m| m.tick == arr.cursor_tick) {
+                    Some(marker) => Action::Arrangement(ArrangementAction::RemoveMarker(marker.id)),
+                    None => Action::Arrangement(ArrangementAction::AddMarker {
+                        name: format!("Marker {}", arr.next_marker_id()),
+                        tick: arr.cursor_tick,
+                    }),
+                }
+            }
+            ActionId::Track(TrackActionId::RenameMarker) => {
+                match arr.marker_at(arr.cursor_tick).cloned() {
+                    Some(marker) => self.open_rename(&marker),
+                    None => Action::None,
+                }
+            }
+            ActionId::Track(TrackActionId::NextMarker) => match arr.next_marker(arr.cursor_tick) {
+                Some(marker) => Action::Arrangement(ArrangementAction::JumpToMarker(marker.id)),
+                None => Action::None,
+            },
+            ActionId::Track(TrackActionId::PrevMarker) => match arr.prev_marker(arr.cursor_tick) {
+                Some(marker) => Action::Arrangement(ArrangementAction::JumpToMarker(marker.id)),
+                None => Action::None,
+            },
+            ActionId::Track(TrackActionId::LoopSection) => {
+                Self::with_section(state, ArrangementAction::LoopSection)
+            }
+            ActionId::Track(TrackActionId::AddSection) => {
+                let (start_tick, end_tick) = Self::new_section_range(state);
+                Action::Arrangement(ArrangementAction::AddSection {
+                    name: format!("Section {}", arr.next_marker_id()),
+                    start_tick,
+                    end_tick,
+                })
+            }
+            ActionId::Track(TrackActionId::InsertSection) => {
+                let (start_tick, end_tick) = Self::new_section_range(state);
+                Action::Arrangement(ArrangementAction::InsertSection {
+                    name: format!("Section {}", arr.next_marker_id()),
+                    start_tick,
+                    length_ticks: end_tick - start_tick,
+                })
+            }
+            ActionId::Track(TrackActionId::DuplicateSection) => {
+                Self::with_section(state, ArrangementAction::DuplicateSection)
+            }
+            ActionId::Track(TrackActionId::DeleteSection) => {
+                Self::with_section(state, ArrangementAction::DeleteSection)
+            }
             _ => Action::None,
         }
     }
 
+    fn handle_raw_input(&mut self, event: &InputEvent, _state: &AppState) -> Action {
+        if self.renaming_marker.is_some() {
+            self.marker_input.handle_input(event);
+        }
+        Action::None
+    }
+
     fn render(&mut self, area: Rect, buf: &mut RenderBuf, state: &AppState) {
         let rect = center_rect(area, 97, 29);
         let arr = &state.session.arrangement;
@@ -309,26 +546,102 @@ impl Pane for TrackPane {
         let bar_line_style = Style::new().fg(Color::new(50, 50, 50));
         let separator_style = Style::new().fg(Color::new(40, 40, 40));
 
-        // Compute bar spacing in columns
-        let (beats_per_bar, _) = state.session.time_signature;
-        let ticks_per_bar = beats_per_bar as u32 * 480;
-        let cols_per_bar = ticks_per_bar / ticks_per_col;
+        // Bar starts per column, following meter changes
+        let pr = &state.session.piano_roll;
         let cols_per_beat = 480 / ticks_per_col;
+        let bar_starts: Vec<Option<u32>> = (0..timeline_width as u32)
+            .map(|col| {
+                let tick = arr.view_start_tick + col * ticks_per_col;
+                let pos = pr.bar_at_tick(tick);
+                (pos.ticks_per_bar >= ticks_per_col && tick - pos.bar_start < ticks_per_col)
+                    .then_some(pos.bar)
+            })
+            .collect();
 
-        // --- Header: bar numbers ---
+        // --- Header: bar numbers, with the meter where it changes ---
         let header_y = inner.y;
         let header_label_style = Style::new().fg(Color::DARK_GRAY);
-        for col in 0..timeline_width as u32 {
-            let tick = arr.view_start_tick + col * ticks_per_col;
-            if cols_per_bar > 0 && (tick % ticks_per_bar) < ticks_per_col {
-                let bar_num = tick / ticks_per_bar + 1;
-                let label = format!("{}", bar_num);
-                let x = timeline_x + col as u16;
-                for (j, ch) in label.chars().enumerate() {
-                    if x + (j as u16) < inner.x + inner.width {
-                        buf.set_cell(x + j as u16, header_y, ch, header_label_style);
-                    }
+        let meter_label_style = Style::new().fg(Color::CYAN);
+        for (col, bar) in bar_starts.iter().enumerate() {
+            let Some(bar) = *bar else {
+                continue;
+            };
+            let (label, style) = match pr.tempo_map.meter_change(bar) {
+                Some(change) => (
+                    format!(
+                        "{} {}/{}",
+                        bar + 1,
+                        change.time_signature.0,
+                        change.time_signature.1
+                    ),
+                    meter_label_style,
+                ),
+                None => (format!("{}", bar + 1), header_label_style),
+            };
+            let x = timeline_x + col as u16;
+            for (j, ch) in label.chars().enumerate() {
+                if x + (j as u16) < inner.x + inner.width {
+                    buf.set_cell(x + j as u16, header_y, ch, style);
+                }
+            }
+        }
+
+        // Markers: '▼' and the name, cut short at the next marker
+        let marker_style = Style::new().fg(Color::MAGENTA).bold();
+        let timeline_end = timeline_x + timeline_width;
+        for (i, marker) in arr.markers.iter().enumerate() {
+            if marker.tick < arr.view_start_tick {
+                continue;
+            }
+            let col = (marker.tick - arr.view_start_tick) / ticks_per_col;
+            if col >= timeline_width as u32 {
+                break;
+            }
+            let x = timeline_x + col as u16;
+            let limit = arr.markers.get(i + 1).map_or(timeline_end, |next| {
+                let next_col = next.tick.saturating_sub(arr.view_start_tick) / ticks_per_col;
+                timeline_x + (next_col as u16).min(timeline_width)
+            });
+            buf.set_cell(x, header_y, '▼', marker_style);
+            for (j, ch) in marker.name.chars().enumerate() {
+                let cx = x + 1 + j as u16;
+                if cx >= limit {
+                    break;
                 }
+                buf.set_cell(cx, header_y, ch, marker_style);
+            }
+        }
+
+        // Tempo changes: 'T' for a step, '/' or '\' for a ramp into the next
+        let tempo_style = Style::new().fg(Color::YELLOW).bold();
+        for (i, event) in pr.tempo_map.tempos.iter().enumerate() {
+            if event.tick < arr.view_start_tick {
+                continue;
+            }
+            let col = (event.tick - arr.view_start_tick) / ticks_per_col;
+            if col >= timeline_width as u32 {
+                break;
+            }
+            let marker = match pr.tempo_map.tempos.get(i + 1) {
+                Some(next) if event.ramp && next.bpm < event.bpm => '\\',
+                Some(_) if event.ramp => '/',
+                _ => 'T',
+            };
+            buf.set_cell(timeline_x + col as u16, header_y, marker, tempo_style);
+        }
+
+        // Punch in/out markers
+        let punch_style = Style::new().fg(Color::RED).bold();
+        for (tick, marker) in [
+            (state.recording.punch_in, '['),
+            (state.recording.punch_out, ']'),
+        ] {
+            let Some(tick) = tick.filter(|&t| t >= arr.view_start_tick) else {
+                continue;
+            };
+            let col = (tick - arr.view_start_tick) / ticks_per_col;
+            if col < timeline_width as u32 {
+                buf.set_cell(timeline_x + col as u16, header_y, marker, punch_style);
             }
         }
 
@@ -406,6 +719,21 @@ impl Pane for TrackPane {
                 )],
             );
 
+            // Record arm indicator
+            let rec_flag = if state.recording.is_track_recording(instrument.id) {
+                Some(("REC", Style::new().fg(Color::WHITE).bg(Color::RED).bold()))
+            } else if state.recording.is_armed(instrument.id) {
+                Some(("ARM", Style::new().fg(Color::RED).bold()))
+            } else {
+                None
+            };
+            if let Some((flag, style)) = rec_flag {
+                buf.draw_line(
+                    Rect::new(inner.x + label_width - 4, lane_y + 1, 3, 1),
+                    &[(flag, style)],
+                );
+            }
+
             // Separator between label and timeline
             for row in 0..lane_height {
                 let y = lane_y + row;
@@ -422,7 +750,7 @@ impl Pane for TrackPane {
             for col in 0..timeline_width as u32 {
                 let tick = arr.view_start_tick + col * ticks_per_col;
                 let x = timeline_x + col as u16;
-                let is_bar = cols_per_bar > 0 && (tick % ticks_per_bar) < ticks_per_col;
+                let is_bar = bar_starts[col as usize].is_some();
                 let is_beat = cols_per_beat > 0 && (tick % 480) < ticks_per_col;
 
                 for row in 0..lane_height {
@@ -546,6 +874,64 @@ impl Pane for TrackPane {
                 }
             }
 
+            // Draw audio clips for this instrument: name on the first row,
+            // waveform peaks on the second. Muted takes go first so the
+            // audible take of a stack is drawn on top.
+            let view_end_tick = arr.view_start_tick + (timeline_width as u32) * ticks_per_col;
+            let mut audio_clips = arr.audio_clips_for_instrument(inst_id);
+            audio_clips.sort_by_key(|c| !c.muted);
+            for clip in audio_clips {
+                if clip.end_tick() <= arr.view_start_tick || clip.start_tick >= view_end_tick {
+                    continue;
+                }
+                let vis_start =
+                    (clip.start_tick.saturating_sub(arr.view_start_tick) / ticks_per_col) as u16;
+                let vis_end = (((clip.end_tick() - arr.view_start_tick) / ticks_per_col) as u16)
+                    .min(timeline_width);
+                if vis_start >= vis_end {
+                    continue;
+                }
+
+                let style = if clip.muted {
+                    Style::new().fg(Color::GRAY).bg(Color::DARK_GRAY)
+                } else {
+                    Style::new().fg(Color::BLACK).bg(source_c)
+                };
+                let block_width = vis_end - vis_start;
+                let x = timeline_x + vis_start;
+
+                let label: String = std::iter::once('[')
+                    .chain(clip.name.chars())
+                    .chain(std::iter::repeat(' '))
+                    .take(block_width as usize)
+                    .collect();
+                for (j, ch) in label.chars().enumerate() {
+                    buf.set_cell(x + j as u16, lane_y, ch, style);
+                }
+
+                let peaks = &clip.waveform_peaks;
+                let duration = clip.duration_ticks.max(1) as u64;
+                for j in 0..block_width {
+                    let col_tick = arr.view_start_tick + (vis_start + j) as u32 * ticks_per_col;
+                    let from = col_tick.saturating_sub(clip.start_tick) as u64;
+                    let to = (from + ticks_per_col as u64).min(duration);
+                    let lo = (from * peaks.len() as u64 / duration) as usize;
+                    let hi = ((to * peaks.len() as u64).div_ceil(duration) as usize)
+                        .clamp(lo + 1, peaks.len().max(lo + 1));
+                    let peak = peaks
+                        .get(lo..hi.min(peaks.len()))
+                        .map(|p| p.iter().fold(0.0f32, |acc, &v| acc.max(v)))
+                        .unwrap_or(0.0);
+                    let glyph = WAVE_GLYPHS[(peak.clamp(0.0, 1.0) * 7.0).round() as usize];
+                    if lane_y + 1 < lanes_area_y + lanes_area_height {
+                        buf.set_cell(x + j, lane_y + 1, glyph, style);
+                    }
+                }
+                if clip.end_tick() <= view_end_tick {
+                    buf.set_cell(x + block_width - 1, lane_y, ']', style);
+                }
+            }
+
             // Horizontal separator below each lane
             if vi + 1 < max_visible && i + 1 < num_instruments {
                 let sep_y = lane_y + lane_height;
@@ -594,9 +980,43 @@ impl Pane for TrackPane {
         // --- Footer ---
         let footer_y = inner.y + inner.height - 2;
 
+        // Section under the cursor, or the rename prompt
+        let footer_rect = Rect::new(inner.x + 1, footer_y, inner.width.saturating_sub(2), 1);
+        if self.renaming_marker.is_some() {
+            let label = "Rename: ";
+            buf.draw_line(footer_rect, &[(label, Style::new().fg(Color::YELLOW))]);
+            self.marker_input.render_buf(
+                buf.raw_buf(),
+                footer_rect.x + label.len() as u16,
+                footer_y,
+                footer_rect.width.saturating_sub(label.len() as u16),
+            );
+        } else if let Some(marker) = arr.marker_at(arr.cursor_tick) {
+            let range = arr.section_range(marker.id);
+            let bars = range.map_or(String::new(), |(start, end)| {
+                let first = pr.bar_at_tick(start).bar + 1;
+                let last = pr.bar_at_tick(end - 1).bar + 1;
+                format!("  bars {}-{}", first, last)
+            });
+            let looped = pr.looping && range == Some((pr.loop_start, pr.loop_end));
+            buf.draw_line(
+                footer_rect,
+                &[
+                    ("Section: ", Style::new().fg(Color::DARK_GRAY)),
+                    (marker.name.as_str(), Style::new().fg(Color::MAGENTA)),
+                    (bars.as_str(), Style::new().fg(Color::GRAY)),
+                    (
+                        if looped { "  [loop]" } else { "" },
+                        Style::new().fg(Color::YELLOW),
+                    ),
+                ],
+            );
+        }
+
         // Cursor position + selected clip info
-        let bar = arr.cursor_tick / ticks_per_bar + 1;
-        let beat = (arr.cursor_tick % ticks_per_bar) / 480 + 1;
+        let cursor_bar = pr.bar_at_tick(arr.cursor_tick);
+        let bar = cursor_bar.bar + 1;
+        let beat = cursor_bar.beat(arr.cursor_tick, 480) + 1;
         let inst_id = state.instruments.instruments[selected_lane].id;
         let clips = arr.clips_for_instrument(inst_id);
         let clip_info = if clips.is_empty() {
@@ -606,7 +1026,46 @@ impl Pane for TrackPane {
             format!("Clip: {} [{}/{}]", clips[idx].name, idx + 1, clips.len())
         };
 
-        let pos_str = format!("Bar {} Beat {}  |  {}", bar, beat, clip_info);
+        let mut pos_str = format!(
+            "Bar {} Beat {}  {:.0} BPM {}/{}  |  {}",
+            bar,
+            beat,
+            pr.bpm_at(arr.cursor_tick as f64),
+            cursor_bar.time_signature.0,
+            cursor_bar.time_signature.1,
+            clip_info
+        );
+        if let Some(audio_clip) = arr.audio_clip_at(inst_id, arr.cursor_tick) {
+            pos_str.push_str(&format!(
+                "  |  Audio: {} ({:.1}s)",
+                audio_clip.name, audio_clip.length_secs
+            ));
+            let stack = arr.take_stack_at(inst_id, arr.cursor_tick);
+            if stack.len() > 1 {
+                let idx = stack
+                    .iter()
+                    .position(|c| c.id == audio_clip.id)
+                    .unwrap_or(0);
+                pos_str.push_str(&format!(" [take {}/{}]", idx + 1, stack.len()));
+            }
+        }
+        let punch = |tick: Option<u32>| {
+            tick.map(|t| {
+                let pos = pr.bar_at_tick(t);
+                format!("{}.{}", pos.bar + 1, pos.beat(t, 480) + 1)
+            })
+        };
+        match (
+            punch(state.recording.punch_in),
+            punch(state.recording.punch_out),
+        ) {
+            (None, None) => {}
+            (punch_in, punch_out) => pos_str.push_str(&format!(
+                "  |  Punch {}-{}",
+                punch_in.as_deref().unwrap_or("start"),
+                punch_out.as_deref().unwrap_or("end")
+            )),
+        }
         buf.draw_line(
             Rect::new(inner.x + 1, footer_y + 1, inner.width.saturating_sub(2), 1),
             &[(&pos_str, Style::new().fg(Color::GRAY))],
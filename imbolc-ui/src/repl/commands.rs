@@ -0,0 +1,786 @@
+//! REPL command registry.
+//!
+//! Every command is one `CommandDef` entry in `COMMANDS` plus a handler.
+//! Handlers only read state: queries return text, mutations return the
+//! actions to dispatch, so the REPL goes through the same dispatch pipeline
+//! as the TUI.
+
+use std::path::PathBuf;
+
+use crate::action::{Action, InstrumentAction, MixerAction, PianoRollAction, SessionAction};
+use crate::state::{AppState, MixerSelection, SourceType};
+
+use super::display;
+use super::parse;
+
+/// Result of executing a command
+#[derive(Debug)]
+pub enum CommandResult {
+    /// Print text (queries)
+    Output(String),
+    /// Dispatch actions in order (mutations)
+    Dispatch(Vec<Action>),
+    /// Dispatch actions, then print text
+    OutputAndDispatch(String, Vec<Action>),
+    /// Leave the REPL
+    Quit,
+}
+
+type Handler = fn(&[&str], &AppState) -> Result<CommandResult, String>;
+
+pub struct CommandDef {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    pub usage: &'static str,
+    pub handler: Handler,
+}
+
+/// Topics accepted by `show`
+pub const SHOW_TOPICS: &[&str] = &[
+    "instruments",
+    "instrument",
+    "transport",
+    "mixer",
+    "notes",
+    "effects",
+    "buses",
+];
+
+/// Fields accepted by `set`
+pub const SET_FIELDS: &[&str] = &["bpm", "key", "scale", "timesig"];
+
+pub static COMMANDS: &[CommandDef] = &[
+    CommandDef {
+        name: "help",
+        aliases: &["?"],
+        description: "List commands, or describe one command",
+        usage: "help [command]",
+        handler: cmd_help,
+    },
+    CommandDef {
+        name: "show",
+        aliases: &["list", "ls"],
+        description: "Show instruments, one instrument, transport, mixer, notes, effects or buses",
+        usage: "show [instruments | instrument <n> | transport | mixer | notes <n> | effects <n> | buses]",
+        handler: cmd_show,
+    },
+    CommandDef {
+        name: "play",
+        aliases: &[],
+        description: "Start playback",
+        usage: "play",
+        handler: cmd_play,
+    },
+    CommandDef {
+        name: "stop",
+        aliases: &[],
+        description: "Stop playback and return to the start",
+        usage: "stop",
+        handler: cmd_stop,
+    },
+    CommandDef {
+        name: "select",
+        aliases: &["sel"],
+        description: "Select an instrument",
+        usage: "select <n>",
+        handler: cmd_select,
+    },
+    CommandDef {
+        name: "set",
+        aliases: &[],
+        description: "Set tempo, key, scale or time signature",
+        usage: "set bpm <20-999> | set key <C, F#, Bb...> | set scale <name> | set timesig <4/4>",
+        handler: cmd_set,
+    },
+    CommandDef {
+        name: "add",
+        aliases: &["new"],
+        description: "Add an instrument with a built-in source",
+        usage: "add <source>",
+        handler: cmd_add,
+    },
+    CommandDef {
+        name: "delete",
+        aliases: &["rm"],
+        description: "Delete an instrument",
+        usage: "delete <n>",
+        handler: cmd_delete,
+    },
+    CommandDef {
+        name: "note",
+        aliases: &[],
+        description: "Add a note, or remove the note already at that pitch and position",
+        usage: "note <n> <pitch> <bar[.beat[.tick]]> [length in beats] [velocity]",
+        handler: cmd_note,
+    },
+    CommandDef {
+        name: "mute",
+        aliases: &[],
+        description: "Toggle mute on an instrument, bus, group or master",
+        usage: "mute <n | bus<n> | group<n> | master>",
+        handler: cmd_mute,
+    },
+    CommandDef {
+        name: "solo",
+        aliases: &[],
+        description: "Toggle solo on an instrument, bus or group",
+        usage: "solo <n | bus<n> | group<n>>",
+        handler: cmd_solo,
+    },
+    CommandDef {
+        name: "level",
+        aliases: &["vol"],
+        description: "Set a channel level from 0 to 1",
+        usage: "level <n | bus<n> | group<n> | master> <0-1>",
+        handler: cmd_level,
+    },
+    CommandDef {
+        name: "pan",
+        aliases: &[],
+        description: "Set a channel pan",
+        usage: "pan <n | bus<n> | group<n>> <C | L<0-100> | R<0-100> | -1 to 1>",
+        handler: cmd_pan,
+    },
+    CommandDef {
+        name: "undo",
+        aliases: &[],
+        description: "Undo the last change",
+        usage: "undo",
+        handler: cmd_undo,
+    },
+    CommandDef {
+        name: "redo",
+        aliases: &[],
+        description: "Redo the last undone change",
+        usage: "redo",
+        handler: cmd_redo,
+    },
+    CommandDef {
+        name: "save",
+        aliases: &[],
+        description: "Save the project, or save it to a new file",
+        usage: "save [path]",
+        handler: cmd_save,
+    },
+    CommandDef {
+        name: "load",
+        aliases: &["open"],
+        description: "Load a project file",
+        usage: "load <path>",
+        handler: cmd_load,
+    },
+    CommandDef {
+        name: "quit",
+        aliases: &["exit", "q"],
+        description: "Exit; refuses while there are unsaved changes",
+        usage: "quit",
+        handler: cmd_quit,
+    },
+    CommandDef {
+        name: "quit!",
+        aliases: &["exit!"],
+        description: "Exit and discard unsaved changes",
+        usage: "quit!",
+        handler: cmd_force_quit,
+    },
+];
+
+/// Look up a command by name or alias (case-insensitive)
+pub fn find(name: &str) -> Option<&'static CommandDef> {
+    let name = name.to_lowercase();
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name || c.aliases.contains(&name.as_str()))
+}
+
+/// Parse and run one input line. Blank lines and `#` comments produce no
+/// output.
+pub fn execute(line: &str, state: &AppState) -> Result<CommandResult, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(CommandResult::Output(String::new()));
+    }
+    let tokens = parse::tokenize(line)?;
+    let args: Vec<&str> = tokens.iter().map(String::as_str).collect();
+    let (name, args) = args.split_first().ok_or("Empty command")?;
+    let def = find(name).ok_or_else(|| {
+        format!(
+            "Unknown command '{}'. Type `help` for the list of commands",
+            name
+        )
+    })?;
+    (def.handler)(args, state)
+}
+
+fn usage_error(command: &str) -> String {
+    match find(command) {
+        Some(def) => format!("Usage: {}", def.usage),
+        None => "Invalid arguments".to_string(),
+    }
+}
+
+/// Instrument named by `args[index]`, or the selected instrument when absent
+fn instrument_arg<'a>(
+    args: &[&str],
+    index: usize,
+    state: &'a AppState,
+) -> Result<&'a crate::state::Instrument, String> {
+    match args.get(index) {
+        Some(token) => {
+            let id = parse::parse_instrument_id(token, state)?;
+            state
+                .instruments
+                .instrument(id)
+                .ok_or_else(|| format!("No instrument {}", token))
+        }
+        None => state
+            .instruments
+            .selected_instrument()
+            .ok_or_else(|| "No instrument selected".to_string()),
+    }
+}
+
+fn channel_name(selection: MixerSelection, state: &AppState) -> String {
+    match selection {
+        MixerSelection::Instrument(idx) => state
+            .instruments
+            .instruments
+            .get(idx)
+            .map(|i| format!("instrument {} ({})", i.id, i.name))
+            .unwrap_or_default(),
+        MixerSelection::LayerGroup(id) => state
+            .session
+            .mixer
+            .layer_group_mixer(id)
+            .map(|g| g.name.clone())
+            .unwrap_or_default(),
+        MixerSelection::Bus(id) => state
+            .session
+            .bus(id)
+            .map(|b| b.name.clone())
+            .unwrap_or_default(),
+        MixerSelection::Master => "master".to_string(),
+    }
+}
+
+/// Current (level, pan, mute, solo) of a mixer channel
+fn channel_values(selection: MixerSelection, state: &AppState) -> (f32, f32, bool, bool) {
+    let mixer = &state.session.mixer;
+    match selection {
+        MixerSelection::Instrument(idx) => state
+            .instruments
+            .instruments
+            .get(idx)
+            .map_or((0.0, 0.0, false, false), |i| {
+                (i.mixer.level, i.mixer.pan, i.mixer.mute, i.mixer.solo)
+            }),
+        MixerSelection::LayerGroup(id) => mixer
+            .layer_group_mixer(id)
+            .map_or((0.0, 0.0, false, false), |g| {
+                (g.level, g.pan, g.mute, g.solo)
+            }),
+        MixerSelection::Bus(id) => state.session.bus(id).map_or((0.0, 0.0, false, false), |b| {
+            (b.level, b.pan, b.mute, b.solo)
+        }),
+        MixerSelection::Master => (mixer.master_level, 0.0, mixer.master_mute, false),
+    }
+}
+
+/// Leading `~/` expanded to the home directory, and `.sqlite` added when
+/// the path has no extension
+fn project_path(token: &str) -> PathBuf {
+    let path = match token.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(rest),
+        None => PathBuf::from(token),
+    };
+    if path.extension().is_none() {
+        path.with_extension("sqlite")
+    } else {
+        path
+    }
+}
+
+fn cmd_help(args: &[&str], _state: &AppState) -> Result<CommandResult, String> {
+    if let Some(name) = args.first() {
+        let def = find(name).ok_or_else(|| format!("No command '{}'", name))?;
+        let mut text = format!("{}\nUsage: {}", def.description, def.usage);
+        if !def.aliases.is_empty() {
+            text.push_str(&format!("\nAlso: {}", def.aliases.join(", ")));
+        }
+        if def.name == "add" {
+            let sources: Vec<&str> = SourceType::all().iter().map(|s| s.short_name()).collect();
+            text.push_str(&format!("\nSources: {}", sources.join(", ")));
+        }
+        return Ok(CommandResult::Output(text));
+    }
+    let width = COMMANDS.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut text = String::from("Commands:");
+    for def in COMMANDS {
+        text.push_str(&format!("\n  {:<width$}  {}", def.name, def.description));
+    }
+    text.push_str("\nType `help <command>` for usage.");
+    Ok(CommandResult::Output(text))
+}
+
+fn cmd_show(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let topic = args.first().map(|t| t.to_lowercase());
+    let text = match topic.as_deref() {
+        None | Some("instruments") => display::format_instrument_list(state),
+        Some("instrument") => {
+            display::format_instrument_detail(instrument_arg(args, 1, state)?, state)
+        }
+        Some("transport") => display::format_transport(state),
+        Some("mixer") => display::format_mixer(state),
+        Some("buses") => display::format_buses(state),
+        Some("effects") => display::format_effect_chain(instrument_arg(args, 1, state)?),
+        Some("notes") => {
+            let inst = instrument_arg(args, 1, state)?;
+            let piano_roll = &state.session.piano_roll;
+            let notes = match piano_roll.tracks.get(&inst.id) {
+                Some(track) => display::format_notes(track, piano_roll),
+                None => "No notes.".to_string(),
+            };
+            format!("Instrument {} ({}): {}", inst.id, inst.name, notes)
+        }
+        Some(other) => {
+            return Err(format!(
+                "Can't show '{}'. Try one of: {}",
+                other,
+                SHOW_TOPICS.join(", ")
+            ))
+        }
+    };
+    Ok(CommandResult::Output(text))
+}
+
+fn cmd_play(_args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if state.session.piano_roll.playing {
+        return Ok(CommandResult::Output("Already playing".to_string()));
+    }
+    Ok(CommandResult::OutputAndDispatch(
+        "Playing".to_string(),
+        vec![Action::PianoRoll(PianoRollAction::PlayStop)],
+    ))
+}
+
+fn cmd_stop(_args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if !state.session.piano_roll.playing {
+        return Ok(CommandResult::Output("Already stopped".to_string()));
+    }
+    Ok(CommandResult::OutputAndDispatch(
+        "Stopped".to_string(),
+        vec![Action::PianoRoll(PianoRollAction::PlayStop)],
+    ))
+}
+
+fn cmd_select(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token] = args else {
+        return Err(usage_error("select"));
+    };
+    let id = parse::parse_instrument_id(token, state)?;
+    let idx = state
+        .instruments
+        .instruments
+        .iter()
+        .position(|i| i.id == id)
+        .ok_or_else(|| format!("No instrument {}", token))?;
+    let name = &state.instruments.instruments[idx].name;
+    Ok(CommandResult::OutputAndDispatch(
+        format!("Selected instrument {} ({})", id, name),
+        vec![Action::Instrument(InstrumentAction::Select(idx))],
+    ))
+}
+
+fn cmd_set(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [field, value] = args else {
+        return Err(usage_error("set"));
+    };
+    let mut settings = state.session.musical_settings();
+    let message = match field.to_lowercase().as_str() {
+        "bpm" | "tempo" => {
+            let bpm: u16 = value
+                .parse()
+                .ok()
+                .filter(|b| (20..=999).contains(b))
+                .ok_or_else(|| format!("'{}' is not a BPM (20 to 999)", value))?;
+            settings.bpm = bpm;
+            format!("BPM {}", bpm)
+        }
+        "key" => {
+            settings.key = parse::parse_key(value)?;
+            format!("Key {} {}", settings.key.name(), settings.scale.name())
+        }
+        "scale" => {
+            settings.scale = parse::parse_scale(value)?;
+            format!("Key {} {}", settings.key.name(), settings.scale.name())
+        }
+        "timesig" | "meter" => {
+            let invalid = || format!("'{}' is not a time signature (try 3/4)", value);
+            let (num, den) = value.split_once('/').ok_or_else(invalid)?;
+            let num: u8 = num.parse().map_err(|_| invalid())?;
+            let den: u8 = den.parse().map_err(|_| invalid())?;
+            if num == 0 || !matches!(den, 2 | 4 | 8 | 16) {
+                return Err(invalid());
+            }
+            settings.time_signature = (num, den);
+            format!("Time signature {}/{}", num, den)
+        }
+        other => {
+            return Err(format!(
+                "Can't set '{}'. Try one of: {}",
+                other,
+                SET_FIELDS.join(", ")
+            ))
+        }
+    };
+    Ok(CommandResult::OutputAndDispatch(
+        message,
+        vec![Action::Session(SessionAction::UpdateSession(settings))],
+    ))
+}
+
+fn cmd_add(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token] = args else {
+        return Err(usage_error("add"));
+    };
+    let source = parse::parse_source(token)?;
+    Ok(CommandResult::OutputAndDispatch(
+        format!(
+            "Added instrument {} ({})",
+            state.instruments.next_id,
+            source.name()
+        ),
+        vec![Action::Instrument(InstrumentAction::Add(source))],
+    ))
+}
+
+fn cmd_delete(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if args.len() != 1 {
+        return Err(usage_error("delete"));
+    }
+    let inst = instrument_arg(args, 0, state)?;
+    Ok(CommandResult::OutputAndDispatch(
+        format!("Deleted instrument {} ({})", inst.id, inst.name),
+        vec![Action::Instrument(InstrumentAction::Delete(inst.id))],
+    ))
+}
+
+fn cmd_note(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if !(3..=5).contains(&args.len()) {
+        return Err(usage_error("note"));
+    }
+    let inst = instrument_arg(args, 0, state)?;
+    let piano_roll = &state.session.piano_roll;
+    let track = piano_roll
+        .track_order
+        .iter()
+        .position(|id| *id == inst.id)
+        .ok_or_else(|| format!("Instrument {} has no piano roll track", inst.id))?;
+    let pitch = parse::parse_pitch(args[1])?;
+    let tick = parse::parse_position(args[2], piano_roll)?;
+    let duration = match args.get(3) {
+        Some(token) => parse::parse_beats(token, piano_roll.ticks_per_beat)?,
+        None => piano_roll.ticks_per_beat,
+    };
+    let velocity = match args.get(4) {
+        Some(token) => parse::parse_velocity(token)?,
+        None => 100,
+    };
+
+    let at = format!(
+        "{} at {}",
+        display::note_name(pitch),
+        display::format_position(tick, piano_roll)
+    );
+    let message = match piano_roll.find_note(track, pitch, tick) {
+        Some(_) => format!("Removed {} from {}", at, inst.name),
+        None => format!(
+            "Added {}, {}, velocity {} to {}",
+            at,
+            display::format_beats(duration, piano_roll.ticks_per_beat),
+            velocity,
+            inst.name
+        ),
+    };
+    Ok(CommandResult::OutputAndDispatch(
+        message,
+        vec![Action::PianoRoll(PianoRollAction::ToggleNote {
+            pitch,
+            tick,
+            duration,
+            velocity,
+            track,
+        })],
+    ))
+}
+
+fn cmd_mute(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token] = args else {
+        return Err(usage_error("mute"));
+    };
+    let channel = parse::parse_channel(token, state)?;
+    let (_, _, muted, _) = channel_values(channel, state);
+    Ok(CommandResult::OutputAndDispatch(
+        format!(
+            "{} {}",
+            if muted { "Unmuted" } else { "Muted" },
+            channel_name(channel, state)
+        ),
+        vec![
+            Action::Mixer(MixerAction::SelectAt(channel)),
+            Action::Mixer(MixerAction::ToggleMute),
+        ],
+    ))
+}
+
+fn cmd_solo(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token] = args else {
+        return Err(usage_error("solo"));
+    };
+    let channel = parse::parse_channel(token, state)?;
+    if channel == MixerSelection::Master {
+        return Err("Master can't be soloed".to_string());
+    }
+    let (_, _, _, soloed) = channel_values(channel, state);
+    Ok(CommandResult::OutputAndDispatch(
+        format!(
+            "{} {}",
+            if soloed { "Unsoloed" } else { "Soloed" },
+            channel_name(channel, state)
+        ),
+        vec![
+            Action::Mixer(MixerAction::SelectAt(channel)),
+            Action::Mixer(MixerAction::ToggleSolo),
+        ],
+    ))
+}
+
+fn cmd_level(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token, value] = args else {
+        return Err(usage_error("level"));
+    };
+    let channel = parse::parse_channel(token, state)?;
+    let level = parse::parse_level(value)?;
+    let (current, _, _, _) = channel_values(channel, state);
+    Ok(CommandResult::OutputAndDispatch(
+        format!("{} level {:.2}", channel_name(channel, state), level),
+        vec![
+            Action::Mixer(MixerAction::SelectAt(channel)),
+            Action::Mixer(MixerAction::AdjustLevel(level - current)),
+        ],
+    ))
+}
+
+fn cmd_pan(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let [token, value] = args else {
+        return Err(usage_error("pan"));
+    };
+    let channel = parse::parse_channel(token, state)?;
+    if channel == MixerSelection::Master {
+        return Err("Master has no pan".to_string());
+    }
+    let pan = parse::parse_pan(value)?;
+    let (_, current, _, _) = channel_values(channel, state);
+    Ok(CommandResult::OutputAndDispatch(
+        format!(
+            "{} pan {}",
+            channel_name(channel, state),
+            display::format_pan(pan)
+        ),
+        vec![
+            Action::Mixer(MixerAction::SelectAt(channel)),
+            Action::Mixer(MixerAction::AdjustPan(pan - current)),
+        ],
+    ))
+}
+
+fn cmd_undo(_args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if !state.undo_history.can_undo() {
+        return Err("Nothing to undo".to_string());
+    }
+    Ok(CommandResult::OutputAndDispatch(
+        "Undone".to_string(),
+        vec![Action::Undo],
+    ))
+}
+
+fn cmd_redo(_args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if !state.undo_history.can_redo() {
+        return Err("Nothing to redo".to_string());
+    }
+    Ok(CommandResult::OutputAndDispatch(
+        "Redone".to_string(),
+        vec![Action::Redo],
+    ))
+}
+
+fn cmd_save(args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    let action = match args {
+        [] => {
+            if state.project.path.is_none() {
+                return Err("The project has no file yet. Use `save <path>`".to_string());
+            }
+            SessionAction::Save
+        }
+        [path] => SessionAction::SaveAs(project_path(path)),
+        _ => return Err(usage_error("save")),
+    };
+    Ok(CommandResult::Dispatch(vec![Action::Session(action)]))
+}
+
+fn cmd_load(args: &[&str], _state: &AppState) -> Result<CommandResult, String> {
+    let [path] = args else {
+        return Err(usage_error("load"));
+    };
+    Ok(CommandResult::Dispatch(vec![Action::Session(
+        SessionAction::LoadFrom(project_path(path)),
+    )]))
+}
+
+fn cmd_quit(_args: &[&str], state: &AppState) -> Result<CommandResult, String> {
+    if state.project.dirty {
+        return Err(
+            "There are unsaved changes. Type `save` first, or `quit!` to discard them".to_string(),
+        );
+    }
+    Ok(CommandResult::Quit)
+}
+
+fn cmd_force_quit(_args: &[&str], _state: &AppState) -> Result<CommandResult, String> {
+    Ok(CommandResult::Quit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with(sources: &[SourceType]) -> AppState {
+        let mut state = AppState::new();
+        for source in sources {
+            let id = state.instruments.add_instrument(*source);
+            state.session.piano_roll.add_track(id);
+        }
+        state.instruments.selected = Some(0);
+        state
+    }
+
+    fn dispatched(result: Result<CommandResult, String>) -> Vec<Action> {
+        match result {
+            Ok(CommandResult::Dispatch(actions))
+            | Ok(CommandResult::OutputAndDispatch(_, actions)) => actions,
+            other => panic!("expected dispatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_name_and_alias_is_unique() {
+        let mut names: Vec<&str> = COMMANDS
+            .iter()
+            .flat_map(|c| std::iter::once(c.name).chain(c.aliases.iter().copied()))
+            .collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+        assert!(find("LS").is_some_and(|c| c.name == "show"));
+    }
+
+    #[test]
+    fn unknown_commands_and_bad_arguments_explain_themselves() {
+        let state = state_with(&[SourceType::Saw]);
+        let err = execute("frobnicate", &state).unwrap_err();
+        assert!(err.contains("help"));
+        assert_eq!(
+            execute("level 0", &state).unwrap_err(),
+            "Usage: level <n | bus<n> | group<n> | master> <0-1>"
+        );
+        assert!(execute("show instrument 9", &state)
+            .unwrap_err()
+            .contains("No instrument 9"));
+        assert!(matches!(
+            execute("# comment", &state),
+            Ok(CommandResult::Output(text)) if text.is_empty()
+        ));
+    }
+
+    #[test]
+    fn set_builds_musical_settings_update() {
+        let state = state_with(&[]);
+        let actions = dispatched(execute("set bpm 140", &state));
+        let [Action::Session(SessionAction::UpdateSession(settings))] = actions.as_slice() else {
+            panic!("expected session update");
+        };
+        assert_eq!(settings.bpm, 140);
+        assert_eq!(settings.key, state.session.key);
+        assert!(execute("set bpm 5", &state).is_err());
+        assert!(execute("set timesig 7/3", &state).is_err());
+    }
+
+    #[test]
+    fn mixer_commands_select_the_channel_then_adjust() {
+        let mut state = state_with(&[SourceType::Kick, SourceType::Acid]);
+        state.instruments.instruments[1].mixer.level = 0.5;
+        let actions = dispatched(execute("level 1 0.8", &state));
+        assert_eq!(actions.len(), 2);
+        assert!(matches!(
+            actions[0],
+            Action::Mixer(MixerAction::SelectAt(MixerSelection::Instrument(1)))
+        ));
+        let Action::Mixer(MixerAction::AdjustLevel(delta)) = actions[1] else {
+            panic!("expected level adjustment");
+        };
+        assert!((delta - 0.3).abs() < 1e-6);
+
+        assert!(execute("pan master C", &state).is_err());
+        let bus_id = state.session.mixer.buses[0].id;
+        let actions = dispatched(execute(&format!("mute bus{}", bus_id), &state));
+        assert!(matches!(
+            actions[0],
+            Action::Mixer(MixerAction::SelectAt(MixerSelection::Bus(id))) if id == bus_id
+        ));
+    }
+
+    #[test]
+    fn note_toggles_on_the_instrument_track() {
+        let mut state = state_with(&[SourceType::Saw, SourceType::Pluck]);
+        let Ok(CommandResult::OutputAndDispatch(message, actions)) =
+            execute("note 1 C4 2.1 0.5 90", &state)
+        else {
+            panic!("expected dispatch");
+        };
+        assert!(message.starts_with("Added C4 at 2.1, 0.5 beats, velocity 90"));
+        let action = PianoRollAction::ToggleNote {
+            pitch: 60,
+            tick: 1920,
+            duration: 240,
+            velocity: 90,
+            track: 1,
+        };
+        assert!(matches!(&actions[..], [Action::PianoRoll(a)] if *a == action));
+
+        state.session.piano_roll.toggle_note(1, 60, 1920, 240, 90);
+        let Ok(CommandResult::OutputAndDispatch(message, _)) = execute("note 1 60 2.1", &state)
+        else {
+            panic!("expected dispatch");
+        };
+        assert!(message.starts_with("Removed C4 at 2.1"));
+    }
+
+    #[test]
+    fn save_requires_a_path_for_untitled_projects() {
+        let mut state = state_with(&[]);
+        assert!(execute("save", &state).is_err());
+        let actions = dispatched(execute("save /tmp/song", &state));
+        assert!(matches!(
+            &actions[..],
+            [Action::Session(SessionAction::SaveAs(p))] if p == &PathBuf::from("/tmp/song.sqlite")
+        ));
+
+        state.project.dirty = true;
+        assert!(execute("quit", &state).is_err());
+        assert!(matches!(execute("quit!", &state), Ok(CommandResult::Quit)));
+    }
+}
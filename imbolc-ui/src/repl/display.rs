@@ -0,0 +1,485 @@
+//! Plain-text formatting of project state for the REPL.
+//!
+//! Output is linear text meant to be read top to bottom by a screen reader:
+//! one item per line, no box drawing, and values spelled out rather than
+//! drawn as meters.
+
+use crate::state::piano_roll::{PianoRollState, Track};
+use crate::state::{
+    AppState, EffectSlot, Instrument, MixerBus, OutputTarget, Param, ParamValue, ProcessingStage,
+    SendTapPoint,
+};
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// MIDI pitch as a note name, with middle C (60) as C4
+pub fn note_name(pitch: u8) -> String {
+    let octave = (pitch / 12) as i8 - 1;
+    format!("{}{}", NOTE_NAMES[(pitch % 12) as usize], octave)
+}
+
+/// Tick position as 1-based `bar.beat`, with a `.tick` suffix when the
+/// position falls between beats
+pub fn format_position(tick: u32, piano_roll: &PianoRollState) -> String {
+    let tpb = piano_roll.ticks_per_beat.max(1);
+    let ticks_per_bar = piano_roll.ticks_per_bar().max(tpb);
+    let bar = tick / ticks_per_bar + 1;
+    let beat = (tick % ticks_per_bar) / tpb + 1;
+    let rem = tick % tpb;
+    if rem == 0 {
+        format!("{}.{}", bar, beat)
+    } else {
+        format!("{}.{}.{}", bar, beat, rem)
+    }
+}
+
+/// Note length in beats, e.g. "1 beat", "0.5 beats"
+pub fn format_beats(ticks: u32, ticks_per_beat: u32) -> String {
+    let beats = ticks as f32 / ticks_per_beat.max(1) as f32;
+    let text = format!("{:.3}", beats);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "1" {
+        "1 beat".to_string()
+    } else {
+        format!("{} beats", text)
+    }
+}
+
+/// Pan as C, L<percent> or R<percent>
+pub fn format_pan(pan: f32) -> String {
+    let percent = (pan * 100.0).round() as i32;
+    match percent {
+        0 => "C".to_string(),
+        p if p < 0 => format!("L{}", -p),
+        p => format!("R{}", p),
+    }
+}
+
+fn format_param(param: &Param) -> String {
+    match param.value {
+        ParamValue::Float(v) => format!("{} {:.2}", param.name, v),
+        ParamValue::Int(v) => format!("{} {}", param.name, v),
+        ParamValue::Bool(v) => format!("{} {}", param.name, if v { "on" } else { "off" }),
+    }
+}
+
+fn format_params(params: &[Param]) -> String {
+    params
+        .iter()
+        .map(format_param)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_output(target: OutputTarget, state: &AppState) -> String {
+    match target {
+        OutputTarget::Master => "Master".to_string(),
+        OutputTarget::Bus(id) => state
+            .session
+            .bus(id)
+            .map(|b| b.name.clone())
+            .unwrap_or_else(|| format!("Bus {}", id)),
+    }
+}
+
+/// Trailing mute/solo flags for a channel line
+fn channel_flags(mute: bool, solo: bool) -> String {
+    let mut flags = String::new();
+    if mute {
+        flags.push_str("  muted");
+    }
+    if solo {
+        flags.push_str("  solo");
+    }
+    flags
+}
+
+pub fn format_instrument_list(state: &AppState) -> String {
+    let instruments = &state.instruments;
+    if instruments.instruments.is_empty() {
+        return "No instruments. Use `add <source>` to create one.".to_string();
+    }
+    let name_width = instruments
+        .instruments
+        .iter()
+        .map(|i| i.name.len())
+        .max()
+        .unwrap_or(0);
+    let source_width = instruments
+        .instruments
+        .iter()
+        .map(|i| i.source.name().len() + 2)
+        .max()
+        .unwrap_or(0);
+    let selected = instruments.selected_instrument().map(|i| i.id);
+
+    let mut out = String::from("Instruments:");
+    for inst in &instruments.instruments {
+        let marker = if Some(inst.id) == selected { '*' } else { ' ' };
+        out.push_str(&format!(
+            "\n {}{}. {:<name_width$}  {:<source_width$}  vol:{:.2}  pan:{}{}",
+            marker,
+            inst.id,
+            inst.name,
+            format!("[{}]", inst.source.name()),
+            inst.mixer.level,
+            format_pan(inst.mixer.pan),
+            channel_flags(inst.mixer.mute, inst.mixer.solo),
+        ));
+    }
+    out
+}
+
+pub fn format_instrument_detail(instrument: &Instrument, state: &AppState) -> String {
+    let inst = instrument;
+    let mut lines = vec![format!("Instrument {}: {}", inst.id, inst.name)];
+    lines.push(format!("  Source: {}", inst.source.name()));
+    if !inst.source_params.is_empty() {
+        lines.push(format!("  Params: {}", format_params(&inst.source_params)));
+    }
+    lines.push(format!(
+        "  Mixer: vol {:.2}, pan {}{}",
+        inst.mixer.level,
+        format_pan(inst.mixer.pan),
+        channel_flags(inst.mixer.mute, inst.mixer.solo),
+    ));
+    lines.push(format!(
+        "  Output: {}",
+        format_output(inst.mixer.output_target, state)
+    ));
+    let sends: Vec<String> = inst
+        .mixer
+        .sends
+        .values()
+        .filter(|s| s.enabled)
+        .map(|s| {
+            let tap = match s.tap_point {
+                SendTapPoint::PreInsert => "pre-insert",
+                SendTapPoint::PostInsert => "post-insert",
+            };
+            format!(
+                "{} {:.2} {}",
+                format_output(OutputTarget::Bus(s.bus_id), state),
+                s.level,
+                tap
+            )
+        })
+        .collect();
+    if !sends.is_empty() {
+        lines.push(format!("  Sends: {}", sends.join(", ")));
+    }
+
+    let env = &inst.modulation.amp_envelope;
+    lines.push(format!(
+        "  Envelope: attack {:.2}, decay {:.2}, sustain {:.2}, release {:.2}",
+        env.attack, env.decay, env.sustain, env.release
+    ));
+    let lfo = &inst.modulation.lfo;
+    if lfo.enabled {
+        lines.push(format!(
+            "  LFO: {} {:.2} Hz, depth {:.2}, target {}",
+            lfo.shape.name(),
+            lfo.rate,
+            lfo.depth,
+            lfo.target.name()
+        ));
+    } else {
+        lines.push("  LFO: off".to_string());
+    }
+    lines.push(format!(
+        "  Voices: {}",
+        if inst.polyphonic { "poly" } else { "mono" }
+    ));
+
+    if inst.processing_chain.is_empty() {
+        lines.push("  Chain: empty".to_string());
+    } else {
+        lines.push("  Chain:".to_string());
+        for (i, stage) in inst.processing_chain.iter().enumerate() {
+            lines.push(format!("    {}. {}", i + 1, format_stage(stage)));
+        }
+    }
+
+    let note_count = state
+        .session
+        .piano_roll
+        .tracks
+        .get(&inst.id)
+        .map_or(0, |t| t.notes.len());
+    lines.push(format!("  Notes: {}", note_count));
+    lines.join("\n")
+}
+
+fn format_effect(effect: &EffectSlot) -> String {
+    let mut text = effect.effect_type.name().to_string();
+    if !effect.params.is_empty() {
+        text.push_str(": ");
+        text.push_str(&format_params(&effect.params));
+    }
+    if !effect.enabled {
+        text.push_str(" (bypassed)");
+    }
+    text
+}
+
+fn format_stage(stage: &ProcessingStage) -> String {
+    match stage {
+        ProcessingStage::Filter(f) => format!(
+            "{} filter: cutoff {:.0} Hz, resonance {:.2}{}",
+            f.filter_type.name(),
+            f.cutoff.value,
+            f.resonance.value,
+            if f.enabled { "" } else { " (bypassed)" }
+        ),
+        ProcessingStage::Eq(eq) => format!(
+            "EQ, {} bands{}",
+            eq.bands.len(),
+            if eq.enabled { "" } else { " (bypassed)" }
+        ),
+        ProcessingStage::Effect(effect) => format_effect(effect),
+    }
+}
+
+pub fn format_effect_chain(instrument: &Instrument) -> String {
+    if instrument.processing_chain.is_empty() {
+        return format!(
+            "Instrument {} ({}) has no filter or effects.",
+            instrument.id, instrument.name
+        );
+    }
+    let mut out = format!(
+        "Processing chain for instrument {} ({}):",
+        instrument.id, instrument.name
+    );
+    for (i, stage) in instrument.processing_chain.iter().enumerate() {
+        out.push_str(&format!("\n  {}. {}", i + 1, format_stage(stage)));
+    }
+    out
+}
+
+pub fn format_transport(state: &AppState) -> String {
+    let session = &state.session;
+    let piano_roll = &session.piano_roll;
+    let playing = piano_roll.playing;
+    let mut lines = vec![format!(
+        "Transport: {}{}",
+        if playing { "playing" } else { "stopped" },
+        if piano_roll.recording {
+            ", recording"
+        } else {
+            ""
+        }
+    )];
+    lines.push(format!(
+        "  Position: {}",
+        format_position(state.audio.playhead, piano_roll)
+    ));
+    lines.push(format!("  BPM: {}", session.bpm));
+    lines.push(format!(
+        "  Key: {} {}",
+        session.key.name(),
+        session.scale.name()
+    ));
+    lines.push(format!(
+        "  Time signature: {}/{}",
+        session.time_signature.0, session.time_signature.1
+    ));
+    if piano_roll.looping {
+        lines.push(format!(
+            "  Loop: {} to {}",
+            format_position(piano_roll.loop_start, piano_roll),
+            format_position(piano_roll.loop_end, piano_roll)
+        ));
+    } else {
+        lines.push("  Loop: off".to_string());
+    }
+    let project = match &state.project.path {
+        Some(path) => path.display().to_string(),
+        None => "untitled".to_string(),
+    };
+    lines.push(format!(
+        "  Project: {}{}",
+        project,
+        if state.project.dirty {
+            ", unsaved changes"
+        } else {
+            ""
+        }
+    ));
+    lines.join("\n")
+}
+
+fn format_bus_line(bus: &MixerBus) -> String {
+    format!(
+        "    {}: vol:{:.2}  pan:{}{}",
+        bus.name,
+        bus.level,
+        format_pan(bus.pan),
+        channel_flags(bus.mute, bus.solo)
+    )
+}
+
+pub fn format_mixer(state: &AppState) -> String {
+    let mut lines = vec!["Mixer:".to_string()];
+    if state.instruments.instruments.is_empty() {
+        lines.push("  Instruments: none".to_string());
+    } else {
+        lines.push("  Instruments:".to_string());
+        for inst in &state.instruments.instruments {
+            lines.push(format!(
+                "    {}. {}: vol:{:.2}  pan:{}  to {}{}",
+                inst.id,
+                inst.name,
+                inst.mixer.level,
+                format_pan(inst.mixer.pan),
+                format_output(inst.mixer.output_target, state),
+                channel_flags(inst.mixer.mute, inst.mixer.solo),
+            ));
+        }
+    }
+    let groups = &state.session.mixer.layer_group_mixers;
+    if !groups.is_empty() {
+        lines.push("  Layer groups:".to_string());
+        for group in groups {
+            lines.push(format!(
+                "    {}: vol:{:.2}  pan:{}  to {}{}",
+                group.name,
+                group.level,
+                format_pan(group.pan),
+                format_output(group.output_target, state),
+                channel_flags(group.mute, group.solo),
+            ));
+        }
+    }
+    if !state.session.mixer.buses.is_empty() {
+        lines.push("  Buses:".to_string());
+        for bus in &state.session.mixer.buses {
+            lines.push(format_bus_line(bus));
+        }
+    }
+    lines.push(format!(
+        "  Master: vol:{:.2}{}",
+        state.session.mixer.master_level,
+        if state.session.mixer.master_mute {
+            "  muted"
+        } else {
+            ""
+        }
+    ));
+    lines.join("\n")
+}
+
+pub fn format_buses(state: &AppState) -> String {
+    let buses = &state.session.mixer.buses;
+    if buses.is_empty() {
+        return "No buses.".to_string();
+    }
+    let mut lines = vec!["Buses:".to_string()];
+    for bus in buses {
+        lines.push(format_bus_line(bus));
+        if !bus.effect_chain.effects.is_empty() {
+            let effects: Vec<String> = bus
+                .effect_chain
+                .effects
+                .iter()
+                .map(|e| e.effect_type.name().to_string())
+                .collect();
+            lines.push(format!("      Effects: {}", effects.join(", ")));
+        }
+        let senders: Vec<String> = state
+            .instruments
+            .instruments
+            .iter()
+            .filter_map(|inst| {
+                let send = inst.mixer.sends.get(&bus.id).filter(|s| s.enabled)?;
+                Some(format!("{} {:.2}", inst.name, send.level))
+            })
+            .collect();
+        if !senders.is_empty() {
+            lines.push(format!("      Sends from: {}", senders.join(", ")));
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn format_notes(track: &Track, piano_roll: &PianoRollState) -> String {
+    if track.notes.is_empty() {
+        return "No notes.".to_string();
+    }
+    let mut out = format!(
+        "{} note{}:",
+        track.notes.len(),
+        if track.notes.len() == 1 { "" } else { "s" }
+    );
+    for note in &track.notes {
+        out.push_str(&format!(
+            "\n  {} at {}, {}, velocity {}",
+            note_name(note.pitch),
+            format_position(note.tick, piano_roll),
+            format_beats(note.duration, piano_roll.ticks_per_beat),
+            note.velocity
+        ));
+        if note.probability < 1.0 {
+            out.push_str(&format!(", probability {:.0}%", note.probability * 100.0));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{EffectType, InstrumentId, SourceType};
+
+    #[test]
+    fn pitches_and_positions_read_naturally() {
+        assert_eq!(note_name(60), "C4");
+        assert_eq!(note_name(61), "C#4");
+        assert_eq!(note_name(0), "C-1");
+
+        let piano_roll = PianoRollState::new();
+        assert_eq!(format_position(0, &piano_roll), "1.1");
+        assert_eq!(format_position(1920, &piano_roll), "2.1");
+        assert_eq!(format_position(480 + 240, &piano_roll), "1.2.240");
+        assert_eq!(format_beats(480, 480), "1 beat");
+        assert_eq!(format_beats(240, 480), "0.5 beats");
+        assert_eq!(format_pan(0.0), "C");
+        assert_eq!(format_pan(-0.2), "L20");
+        assert_eq!(format_pan(0.1), "R10");
+    }
+
+    #[test]
+    fn instrument_list_marks_selection_and_flags() {
+        let mut state = AppState::new();
+        let kick = state.instruments.add_instrument(SourceType::Kick);
+        let lead = state.instruments.add_instrument(SourceType::SuperSaw);
+        state.instruments.instrument_mut(kick).unwrap().mixer.mute = true;
+        state.instruments.instrument_mut(lead).unwrap().mixer.pan = -0.2;
+        state.instruments.selected = Some(1);
+
+        let text = format_instrument_list(&state);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Instruments:");
+        assert!(lines[1].starts_with("  0. "));
+        assert!(lines[1].ends_with("muted"));
+        assert!(lines[2].starts_with(" *1. "));
+        assert!(lines[2].contains("pan:L20"));
+    }
+
+    #[test]
+    fn effect_chain_lists_stages_in_order() {
+        let mut inst = Instrument::new(InstrumentId::new(3), SourceType::Saw);
+        inst.set_filter(Some(crate::state::FilterType::Lpf));
+        let delay = inst.add_effect(EffectType::Delay);
+        inst.effect_by_id_mut(delay).unwrap().enabled = false;
+
+        let text = format_effect_chain(&inst);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("  1. Low-Pass filter"));
+        assert!(lines[2].starts_with("  2. Delay: "));
+        assert!(lines[2].ends_with("(bypassed)"));
+    }
+}
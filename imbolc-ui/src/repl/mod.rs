@@ -0,0 +1,453 @@
+//! Text REPL for screen readers (`imbolc --repl`).
+//!
+//! Reads commands from stdin and prints plain text to stdout in the main
+//! terminal buffer, with no redraws. Uses the same `LocalDispatcher` and
+//! `AudioHandle` pipeline as the TUI: commands read `AppState` and return
+//! actions, which are dispatched exactly as pane actions are.
+//!
+//! When stdin is a terminal, input goes through rustyline for history and
+//! tab completion. Otherwise lines are read as-is, so a session can be
+//! scripted by piping commands in.
+
+mod commands;
+mod display;
+mod parse;
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use imbolc_types::{DomainAction, RoutedAction, UiAction};
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+use crate::action::{Action, AudioEffect, IoFeedback, SessionAction};
+use crate::audio::AudioHandle;
+use crate::config;
+use crate::dispatch::LocalDispatcher;
+use crate::setup;
+use crate::state::{self, AppState, InstrumentState, SessionState};
+
+use commands::CommandResult;
+
+/// How long to wait for a save or load to finish before giving up on
+/// reporting it
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn history_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("imbolc")
+        .join("repl_history")
+}
+
+struct Repl {
+    dispatcher: LocalDispatcher,
+    audio: AudioHandle,
+    io_rx: Receiver<IoFeedback>,
+    pending_audio_effects: Vec<AudioEffect>,
+    needs_full_sync: bool,
+}
+
+impl Repl {
+    fn new(state: AppState) -> Self {
+        let (io_tx, io_rx) = std::sync::mpsc::channel::<IoFeedback>();
+        let mut audio = AudioHandle::new();
+        audio.sync_state(&state);
+        Self {
+            dispatcher: LocalDispatcher::new(state, io_tx),
+            audio,
+            io_rx,
+            pending_audio_effects: Vec::new(),
+            needs_full_sync: false,
+        }
+    }
+
+    /// Open the project named on the command line, or adopt its path for a
+    /// new project when the file doesn't exist yet.
+    fn open_project(&mut self, path: PathBuf) -> String {
+        if !path.exists() {
+            let message = format!("New project {}", path.display());
+            self.dispatcher.state_mut().project.path = Some(path);
+            return message;
+        }
+        match state::persistence::load_project(&path) {
+            Ok((session, instruments)) => {
+                let message = format!("Loaded {}", path.display());
+                self.adopt_project(session, instruments, path);
+                message
+            }
+            Err(e) => format!("Could not load {}: {}", path.display(), e),
+        }
+    }
+
+    /// Replace the current project with a loaded one, resync audio and
+    /// restore VST plugin state.
+    fn adopt_project(
+        &mut self,
+        session: SessionState,
+        instruments: InstrumentState,
+        path: PathBuf,
+    ) {
+        {
+            let state = self.dispatcher.state_mut();
+            state.undo_history.clear();
+            state.session = session;
+            state.instruments = instruments;
+            state.instruments.rebuild_index();
+            state.project.path = Some(path);
+            state.project.dirty = false;
+        }
+        self.pending_audio_effects.extend(AudioEffect::all());
+        self.needs_full_sync = true;
+        self.apply_pending_effects();
+        crate::runtime::queue_vst_restores(self.dispatcher.state(), &mut self.audio);
+    }
+
+    fn apply_pending_effects(&mut self) {
+        if !self.pending_audio_effects.is_empty() {
+            self.audio.apply_effects(
+                self.dispatcher.state(),
+                &self.pending_audio_effects,
+                self.needs_full_sync,
+            );
+            self.pending_audio_effects.clear();
+            self.needs_full_sync = false;
+        }
+    }
+
+    /// Dispatch one action. Returns true if dispatch asked to quit.
+    fn dispatch(&mut self, action: &Action, out: &mut impl Write) -> io::Result<bool> {
+        let domain = match action.route() {
+            RoutedAction::Domain(domain) => domain,
+            RoutedAction::Ui(UiAction::Quit) => return Ok(true),
+            RoutedAction::Ui(_) => return Ok(false),
+        };
+        let mut r = self.dispatcher.dispatch_domain(&domain, &mut self.audio);
+        if r.needs_full_sync {
+            self.needs_full_sync = true;
+        }
+        self.pending_audio_effects
+            .extend(std::mem::take(&mut r.audio_effects));
+        self.apply_pending_effects();
+        if r.stop_playback {
+            self.audio.set_playing(false);
+        }
+        if r.reset_playhead {
+            self.audio.reset_playhead();
+        }
+        for event in &r.status {
+            writeln!(out, "{}", event.message)?;
+        }
+
+        if matches!(
+            domain,
+            DomainAction::Session(
+                SessionAction::Save | SessionAction::SaveAs(_) | SessionAction::LoadFrom(_)
+            )
+        ) {
+            self.wait_for_io(out)?;
+        }
+        Ok(r.quit)
+    }
+
+    /// Block until the save or load that was just started reports back, so
+    /// its outcome is printed before the next prompt.
+    fn wait_for_io(&mut self, out: &mut impl Write) -> io::Result<()> {
+        match self.io_rx.recv_timeout(IO_TIMEOUT) {
+            Ok(feedback) => self.handle_io_feedback(feedback, out),
+            Err(_) => writeln!(out, "Still working; the result will not be reported"),
+        }
+    }
+
+    fn handle_io_feedback(&mut self, feedback: IoFeedback, out: &mut impl Write) -> io::Result<()> {
+        match feedback {
+            IoFeedback::SaveComplete { id, path, result } => {
+                if id != self.dispatcher.state().io.generation.save {
+                    return Ok(());
+                }
+                let state = self.dispatcher.state_mut();
+                state.io.save_in_progress = false;
+                match result {
+                    Ok(_) => {
+                        writeln!(out, "Saved {}", path.display())?;
+                        state.project.path = Some(path);
+                        state.project.dirty = false;
+                    }
+                    Err(e) => writeln!(out, "Save failed: {}", e)?,
+                }
+            }
+            IoFeedback::LoadComplete { id, path, result } => {
+                if id != self.dispatcher.state().io.generation.load {
+                    return Ok(());
+                }
+                self.dispatcher.state_mut().io.load_in_progress = false;
+                match result {
+                    Ok((session, instruments, _name)) => {
+                        self.adopt_project(session, instruments, path.clone());
+                        writeln!(
+                            out,
+                            "Loaded {}, {} instrument(s)",
+                            path.display(),
+                            self.dispatcher.state().instruments.instruments.len()
+                        )?;
+                    }
+                    Err(e) => writeln!(out, "Load failed: {}", e)?,
+                }
+            }
+            // Not started from the REPL
+            IoFeedback::AutosaveComplete { .. }
+            | IoFeedback::ImportSynthDefComplete { .. }
+            | IoFeedback::ImportSynthDefLoaded { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Fold audio thread updates (playhead, server status) into state.
+    fn drain_audio_feedback(&mut self) {
+        for feedback in self.audio.drain_feedback() {
+            let mut r = self
+                .dispatcher
+                .dispatch_domain(&DomainAction::AudioFeedback(feedback), &mut self.audio);
+            if r.needs_full_sync {
+                self.needs_full_sync = true;
+            }
+            self.pending_audio_effects
+                .extend(std::mem::take(&mut r.audio_effects));
+            if r.stop_playback {
+                self.audio.set_playing(false);
+            }
+            if r.reset_playhead {
+                self.audio.reset_playhead();
+            }
+        }
+        self.apply_pending_effects();
+    }
+
+    /// Run one input line. Returns false when the REPL should exit.
+    fn execute_line(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        self.drain_audio_feedback();
+        let result = commands::execute(line, self.dispatcher.state());
+        let keep_going = match result {
+            Ok(CommandResult::Output(text)) => {
+                if !text.is_empty() {
+                    writeln!(out, "{}", text)?;
+                }
+                true
+            }
+            Ok(CommandResult::Dispatch(actions)) => self.dispatch_all(&actions, out)?,
+            Ok(CommandResult::OutputAndDispatch(text, actions)) => {
+                let keep_going = self.dispatch_all(&actions, out)?;
+                writeln!(out, "{}", text)?;
+                keep_going
+            }
+            Ok(CommandResult::Quit) => false,
+            Err(msg) => {
+                writeln!(out, "Error: {}", msg)?;
+                true
+            }
+        };
+        out.flush()?;
+        Ok(keep_going)
+    }
+
+    fn dispatch_all(&mut self, actions: &[Action], out: &mut impl Write) -> io::Result<bool> {
+        for action in actions {
+            if self.dispatch(action, out)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Read commands line by line until end of input or `quit`.
+    fn run_lines(&mut self, input: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            if !self.execute_line(&line?, out)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Interactive loop with line editing, history and tab completion.
+    fn run_interactive(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let mut editor: Editor<ReplHelper, DefaultHistory> =
+            Editor::new().map_err(io::Error::other)?;
+        editor.set_helper(Some(ReplHelper));
+        let history = history_path();
+        let _ = editor.load_history(&history);
+
+        loop {
+            match editor.readline("imbolc> ") {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        let _ = editor.add_history_entry(line.as_str());
+                    }
+                    if !self.execute_line(&line, out)? {
+                        break;
+                    }
+                }
+                Err(ReadlineError::Interrupted) => {
+                    writeln!(out, "Type `quit` to exit")?;
+                }
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+
+        if let Some(parent) = history.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = editor.save_history(&history);
+        Ok(())
+    }
+}
+
+/// Rustyline helper providing tab completion; hints, highlighting and
+/// validation are left at their defaults.
+struct ReplHelper;
+
+impl Helper for ReplHelper {}
+impl Highlighter for ReplHelper {}
+impl Validator for ReplHelper {}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, matches) = parse::complete(line, pos);
+        let pairs = matches
+            .into_iter()
+            .map(|m| Pair {
+                display: m.clone(),
+                replacement: m,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+/// Entry point for `--repl`. `project_arg` is an optional project file to
+/// open, as for the TUI.
+pub fn run_repl(project_arg: Option<String>) -> io::Result<()> {
+    let config = config::Config::load();
+    let mut state = AppState::new_with_defaults(config.defaults());
+    state.keyboard_layout = config.keyboard_layout();
+    let mut repl = Repl::new(state);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    writeln!(
+        out,
+        "imbolc text mode. Type `help` for commands, `quit` to exit."
+    )?;
+    if let Some(arg) = project_arg {
+        let message = repl.open_project(PathBuf::from(arg));
+        writeln!(out, "{}", message)?;
+    }
+    for event in setup::auto_start_sc(&mut repl.audio) {
+        writeln!(out, "{}", event.message)?;
+    }
+    writeln!(
+        out,
+        "{}",
+        display::format_instrument_list(repl.dispatcher.state())
+    )?;
+    out.flush()?;
+
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        repl.run_interactive(&mut out)
+    } else {
+        repl.run_lines(stdin.lock(), &mut out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(repl: &mut Repl, script: &str) -> String {
+        let mut out = Vec::new();
+        repl.run_lines(script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn piped_session_edits_and_reports() {
+        let mut repl = Repl::new(AppState::new());
+        let output = run(
+            &mut repl,
+            "add saw\n\
+             set bpm 96\n\
+             note 0 C4 1.2\n\
+             level 0 0.5\n\
+             mute 0\n\
+             show mixer\n\
+             bogus\n",
+        );
+
+        let state = repl.dispatcher.state();
+        assert_eq!(state.session.bpm, 96);
+        let inst = &state.instruments.instruments[0];
+        assert_eq!(inst.mixer.level, 0.5);
+        assert!(inst.mixer.mute);
+        assert_eq!(state.session.piano_roll.tracks[&inst.id].notes.len(), 1);
+
+        assert!(output.contains("Added instrument 0 (Saw)"));
+        assert!(output.contains("Added C4 at 1.2, 1 beat, velocity 100"));
+        assert!(output.contains("vol:0.50  pan:C  to Master  muted"));
+        assert!(output.contains("Error: Unknown command 'bogus'"));
+    }
+
+    #[test]
+    fn undo_reverts_through_dispatch() {
+        let mut repl = Repl::new(AppState::new());
+        run(&mut repl, "add saw\nadd pluck\nundo\n");
+        assert_eq!(repl.dispatcher.state().instruments.instruments.len(), 1);
+        let output = run(&mut repl, "redo\nshow\n");
+        assert!(output.contains("1. pluck-1"));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.sqlite");
+        let mut repl = Repl::new(AppState::new());
+        let output = run(
+            &mut repl,
+            &format!(
+                "add acid\nset key F#\nsave {}\nquit\nshow\n",
+                path.with_extension("").display()
+            ),
+        );
+        assert!(output.contains(&format!("Saved {}", path.display())));
+        assert!(!output.contains("Instruments:"), "quit stops reading input");
+        assert!(!repl.dispatcher.state().project.dirty);
+
+        let mut fresh = Repl::new(AppState::new());
+        let output = run(
+            &mut fresh,
+            &format!("load \"{}\"\nshow transport\n", path.display()),
+        );
+        assert!(output.contains("Loaded"));
+        assert!(output.contains("Key: F# Major"));
+        assert_eq!(fresh.dispatcher.state().instruments.instruments.len(), 1);
+    }
+}
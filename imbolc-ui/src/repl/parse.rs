@@ -0,0 +1,369 @@
+//! Tokenizer, argument parsers and tab completion for REPL commands.
+//!
+//! Commands are whitespace-separated positional arguments. Double quotes
+//! group an argument that contains spaces, such as a file path.
+
+use crate::state::music::{Key, Scale};
+use crate::state::piano_roll::PianoRollState;
+use crate::state::{AppState, InstrumentId, MixerSelection, SourceType};
+
+use super::commands;
+
+/// Split a command line into tokens.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut in_quotes = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quote".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// Lowercase with everything but letters and digits removed, so "Super Saw",
+/// "super-saw" and "supersaw" compare equal
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '#')
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+pub fn parse_instrument_id(token: &str, state: &AppState) -> Result<InstrumentId, String> {
+    let n: u32 = token
+        .parse()
+        .map_err(|_| format!("'{}' is not an instrument number", token))?;
+    let id = InstrumentId::new(n);
+    if state.instruments.instrument(id).is_none() {
+        return Err(format!(
+            "No instrument {}. Type `show instruments` for the list",
+            n
+        ));
+    }
+    Ok(id)
+}
+
+/// Mixer channel: an instrument number, `bus<N>`, `group<N>` or `master`
+pub fn parse_channel(token: &str, state: &AppState) -> Result<MixerSelection, String> {
+    let lower = token.to_lowercase();
+    if lower == "master" {
+        return Ok(MixerSelection::Master);
+    }
+    if let Some(n) = lower.strip_prefix("bus") {
+        let bus = state
+            .session
+            .mixer
+            .buses
+            .iter()
+            .find(|b| b.id.to_string() == n)
+            .ok_or_else(|| format!("No bus {}", n))?;
+        return Ok(MixerSelection::Bus(bus.id));
+    }
+    if let Some(n) = lower.strip_prefix("group") {
+        let group = state
+            .session
+            .mixer
+            .layer_group_mixers
+            .iter()
+            .find(|g| g.group_id.to_string() == n)
+            .ok_or_else(|| format!("No layer group {}", n))?;
+        return Ok(MixerSelection::LayerGroup(group.group_id));
+    }
+    let id = parse_instrument_id(token, state)?;
+    let idx = state
+        .instruments
+        .instruments
+        .iter()
+        .position(|i| i.id == id)
+        .ok_or_else(|| format!("No instrument {}", token))?;
+    Ok(MixerSelection::Instrument(idx))
+}
+
+/// Pitch as a MIDI number (`60`) or note name (`C4`, `F#3`, `Bb2`, `C-1`)
+pub fn parse_pitch(token: &str) -> Result<u8, String> {
+    let invalid = || format!("'{}' is not a pitch (try 60 or C4)", token);
+    if let Ok(n) = token.parse::<u8>() {
+        return if n <= 127 { Ok(n) } else { Err(invalid()) };
+    }
+    let mut chars = token.chars();
+    let letter = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+    let base: i32 = match letter {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return Err(invalid()),
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.chars().next() {
+        Some('#') | Some('s') => (1, &rest[1..]),
+        Some('b') => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let octave: i32 = octave.parse().map_err(|_| invalid())?;
+    let pitch = (octave + 1) * 12 + base + accidental;
+    if (0..=127).contains(&pitch) {
+        Ok(pitch as u8)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Position as 1-based `bar`, `bar.beat` or `bar.beat.tick`
+pub fn parse_position(token: &str, piano_roll: &PianoRollState) -> Result<u32, String> {
+    let invalid = || format!("'{}' is not a position (try 1.1 for bar 1, beat 1)", token);
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let number = |i: usize, default: u32| -> Result<u32, String> {
+        parts
+            .get(i)
+            .map_or(Ok(default), |p| p.parse().map_err(|_| invalid()))
+    };
+    let bar = number(0, 1)?;
+    let beat = number(1, 1)?;
+    let tick = number(2, 0)?;
+    let beats_per_bar = piano_roll.time_signature.0 as u32;
+    if bar == 0 || beat == 0 || beat > beats_per_bar || tick >= piano_roll.ticks_per_beat {
+        return Err(invalid());
+    }
+    Ok((bar - 1) * piano_roll.ticks_per_bar() + (beat - 1) * piano_roll.ticks_per_beat + tick)
+}
+
+/// Length in beats (fractions allowed), returned in ticks
+pub fn parse_beats(token: &str, ticks_per_beat: u32) -> Result<u32, String> {
+    let beats: f32 = token
+        .parse()
+        .map_err(|_| format!("'{}' is not a length in beats", token))?;
+    let ticks = (beats * ticks_per_beat as f32).round();
+    if beats <= 0.0 || ticks < 1.0 {
+        return Err("Length must be greater than zero".to_string());
+    }
+    Ok(ticks as u32)
+}
+
+pub fn parse_velocity(token: &str) -> Result<u8, String> {
+    match token.parse::<u8>() {
+        Ok(v) if (1..=127).contains(&v) => Ok(v),
+        _ => Err(format!("'{}' is not a velocity (1 to 127)", token)),
+    }
+}
+
+/// Level between 0 and 1
+pub fn parse_level(token: &str) -> Result<f32, String> {
+    match token.parse::<f32>() {
+        Ok(v) if (0.0..=1.0).contains(&v) => Ok(v),
+        _ => Err(format!("'{}' is not a level (0 to 1)", token)),
+    }
+}
+
+/// Pan as `C`, `L<percent>`, `R<percent>` or a number from -1 to 1
+pub fn parse_pan(token: &str) -> Result<f32, String> {
+    let invalid = || format!("'{}' is not a pan (try C, L20, R50 or -1 to 1)", token);
+    let lower = token.to_lowercase();
+    if lower == "c" || lower == "center" {
+        return Ok(0.0);
+    }
+    let (sign, amount) = if let Some(n) = lower.strip_prefix('l') {
+        (-1.0, n)
+    } else if let Some(n) = lower.strip_prefix('r') {
+        (1.0, n)
+    } else {
+        let v: f32 = lower.parse().map_err(|_| invalid())?;
+        return if (-1.0..=1.0).contains(&v) {
+            Ok(v)
+        } else {
+            Err(invalid())
+        };
+    };
+    let percent: f32 = amount.parse().map_err(|_| invalid())?;
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(invalid());
+    }
+    Ok(sign * percent / 100.0)
+}
+
+/// Key name; flats are accepted and mapped to their sharp equivalent
+pub fn parse_key(token: &str) -> Result<Key, String> {
+    let flats = [
+        ("db", Key::Cs),
+        ("eb", Key::Ds),
+        ("gb", Key::Fs),
+        ("ab", Key::Gs),
+        ("bb", Key::As),
+    ];
+    let lower = token.to_lowercase();
+    if let Some((_, key)) = flats.iter().find(|(name, _)| *name == lower) {
+        return Ok(*key);
+    }
+    let wanted = lower.replace('s', "#");
+    Key::ALL
+        .into_iter()
+        .find(|k| k.name().to_lowercase() == wanted)
+        .ok_or_else(|| format!("'{}' is not a key", token))
+}
+
+pub fn parse_scale(token: &str) -> Result<Scale, String> {
+    Scale::ALL
+        .into_iter()
+        .find(|s| normalize(s.name()) == normalize(token))
+        .ok_or_else(|| format!("'{}' is not a scale", token))
+}
+
+/// Built-in source by short name (`saw`, `hh_cl`) or display name
+pub fn parse_source(token: &str) -> Result<SourceType, String> {
+    let wanted = normalize(token);
+    SourceType::all()
+        .into_iter()
+        .find(|s| normalize(s.short_name()) == wanted || normalize(s.name()) == wanted)
+        .ok_or_else(|| format!("'{}' is not a source. Type `help add` for the list", token))
+}
+
+/// Candidates for completing the word ending at `pos`. Returns the byte
+/// offset where the word starts along with the matches.
+pub fn complete(line: &str, pos: usize) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    let start = before.rfind(char::is_whitespace).map_or(0, |i| {
+        i + before[i..].chars().next().map_or(1, char::len_utf8)
+    });
+    let word = before[start..].to_lowercase();
+    let previous: Vec<&str> = before[..start].split_whitespace().collect();
+
+    let candidates: Vec<String> = match previous.split_first() {
+        None => commands::COMMANDS
+            .iter()
+            .flat_map(|c| std::iter::once(c.name).chain(c.aliases.iter().copied()))
+            .map(str::to_string)
+            .collect(),
+        Some((command, args)) => match commands::find(command) {
+            Some(def) => argument_candidates(def.name, args),
+            None => Vec::new(),
+        },
+    };
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&word))
+        .collect();
+    matches.sort();
+    matches.dedup();
+    (start, matches)
+}
+
+fn argument_candidates(command: &str, args: &[&str]) -> Vec<String> {
+    let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect();
+    match (command, args) {
+        ("show", []) => names(commands::SHOW_TOPICS),
+        ("set", []) => names(commands::SET_FIELDS),
+        ("set", ["key"]) => Key::ALL.iter().map(|k| k.name().to_string()).collect(),
+        ("set", ["scale"]) => Scale::ALL.iter().map(|s| s.name().to_lowercase()).collect(),
+        ("add", []) => SourceType::all()
+            .iter()
+            .map(|s| s.short_name().to_string())
+            .collect(),
+        ("help", []) => commands::COMMANDS
+            .iter()
+            .map(|c| c.name.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_and_groups_quotes() {
+        assert_eq!(
+            tokenize("  set   bpm 140 ").unwrap(),
+            vec!["set", "bpm", "140"]
+        );
+        assert_eq!(
+            tokenize(r#"save "/tmp/my song.sqlite""#).unwrap(),
+            vec!["save", "/tmp/my song.sqlite"]
+        );
+        assert_eq!(tokenize(r#"load """#).unwrap(), vec!["load", ""]);
+        assert!(tokenize(r#"save "/tmp/x"#).is_err());
+    }
+
+    #[test]
+    fn musical_values_parse() {
+        assert_eq!(parse_pitch("60"), Ok(60));
+        assert_eq!(parse_pitch("C4"), Ok(60));
+        assert_eq!(parse_pitch("f#3"), Ok(54));
+        assert_eq!(parse_pitch("Bb2"), Ok(46));
+        assert_eq!(parse_pitch("C-1"), Ok(0));
+        assert!(parse_pitch("H2").is_err());
+        assert_eq!(parse_pitch("G9"), Ok(127));
+        assert!(parse_pitch("G#9").is_err());
+
+        assert_eq!(parse_key("c#"), Ok(Key::Cs));
+        assert_eq!(parse_key("Eb"), Ok(Key::Ds));
+        assert_eq!(parse_scale("dorian"), Ok(Scale::Dorian));
+        assert_eq!(parse_source("SuperSaw"), Ok(SourceType::SuperSaw));
+        assert_eq!(parse_source("hh_cl"), Ok(SourceType::HihatClosed));
+        assert!(parse_source("theremin").is_err());
+
+        assert_eq!(parse_pan("L20"), Ok(-0.2));
+        assert_eq!(parse_pan("c"), Ok(0.0));
+        assert_eq!(parse_pan("0.5"), Ok(0.5));
+        assert!(parse_pan("R150").is_err());
+        assert!(parse_level("1.5").is_err());
+    }
+
+    #[test]
+    fn positions_are_one_based_bars_and_beats() {
+        let piano_roll = PianoRollState::new();
+        assert_eq!(parse_position("1", &piano_roll), Ok(0));
+        assert_eq!(parse_position("2.1", &piano_roll), Ok(1920));
+        assert_eq!(parse_position("1.2.240", &piano_roll), Ok(720));
+        assert!(parse_position("1.5", &piano_roll).is_err());
+        assert!(parse_position("0.1", &piano_roll).is_err());
+        assert_eq!(parse_beats("0.5", 480), Ok(240));
+        assert!(parse_beats("0", 480).is_err());
+    }
+
+    #[test]
+    fn completion_covers_commands_and_arguments() {
+        let (start, matches) = complete("sh", 2);
+        assert_eq!(start, 0);
+        assert_eq!(matches, vec!["show"]);
+
+        let line = "show in";
+        let (start, matches) = complete(line, line.len());
+        assert_eq!(start, 5);
+        assert_eq!(matches, vec!["instrument", "instruments"]);
+
+        let line = "set scale lo";
+        assert_eq!(complete(line, line.len()).1, vec!["locrian"]);
+
+        let line = "add sup";
+        assert_eq!(complete(line, line.len()).1, vec!["supersaw"]);
+    }
+}
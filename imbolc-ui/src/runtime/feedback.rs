@@ -5,11 +5,39 @@ use std::time::Instant;
 use super::AppRuntime;
 use crate::action::{self, AudioEffect, IoFeedback};
 use crate::audio::commands::AudioCmd;
+use crate::audio::AudioHandle;
 use crate::global_actions::apply_dispatch_result;
 use crate::panes::ServerPane;
 use crate::state;
 use crate::ui::status_bar::StatusLevel;
 
+/// Send the saved state of every VST source and effect to the audio thread,
+/// after a project load.
+pub(crate) fn queue_vst_restores(state: &state::AppState, audio: &mut AudioHandle) {
+    for inst in &state.instruments.instruments {
+        if inst.source.is_vst() {
+            if let Some(path) = inst.vst_source_state_path() {
+                let _ = audio.send_cmd(AudioCmd::LoadVstState {
+                    instrument_id: inst.id,
+                    target: action::VstTarget::Source,
+                    path: path.clone(),
+                });
+            }
+        }
+        for effect in inst.effects() {
+            if let (state::EffectType::Vst(_), Some(path)) =
+                (&effect.effect_type, &effect.vst_state_path)
+            {
+                let _ = audio.send_cmd(AudioCmd::LoadVstState {
+                    instrument_id: inst.id,
+                    target: action::VstTarget::Effect(effect.id),
+                    path: path.clone(),
+                });
+            }
+        }
+    }
+}
+
 impl AppRuntime {
     /// Drain I/O feedback (save/load/import completions).
     pub(crate) fn drain_io_feedback(&mut self) {
@@ -86,7 +114,8 @@ impl AppRuntime {
                                 }
                             }
                             if recovering_autosave {
-                                self.app_frame.set_project_name("autosave-recovered".to_string());
+                                self.app_frame
+                                    .set_project_name("autosave-recovered".to_string());
                             } else {
                                 self.recent_projects.add(&path, &name);
                                 self.recent_projects.save();
@@ -104,46 +133,7 @@ impl AppRuntime {
                             self.pending_audio_effects.extend(AudioEffect::all());
                             self.needs_full_sync = true;
 
-                            // Queue VST state restores
-                            let vst_restores: Vec<_> = self
-                                .dispatcher
-                                .state()
-                                .instruments
-                                .instruments
-                                .iter()
-                                .flat_map(|inst| {
-                                    let mut restores = Vec::new();
-                                    if inst.source.is_vst() {
-                                        if let Some(path) = inst.vst_source_state_path() {
-                                            restores.push((
-                                                inst.id,
-                                                action::VstTarget::Source,
-                                                path.clone(),
-                                            ));
-                                        }
-                                    }
-                                    for effect in inst.effects() {
-                                        if let (state::EffectType::Vst(_), Some(ref path)) =
-                                            (&effect.effect_type, &effect.vst_state_path)
-                                        {
-                                            restores.push((
-                                                inst.id,
-                                                action::VstTarget::Effect(effect.id),
-                                                path.clone(),
-                                            ));
-                                        }
-                                    }
-                                    restores
-                                })
-                                .collect();
-
-                            for (instrument_id, target, path) in vst_restores {
-                                let _ = self.audio.send_cmd(AudioCmd::LoadVstState {
-                                    instrument_id,
-                                    target,
-                                    path,
-                                });
-                            }
+                            queue_vst_restores(self.dispatcher.state(), &mut self.audio);
 
                             let status_msg = if recovering_autosave {
                                 "Recovered autosave snapshot (unsaved)".to_string()
@@ -265,9 +255,12 @@ impl AppRuntime {
     pub(crate) fn drain_midi_events(&mut self) {
         use imbolc_types::RoutedAction;
         for event in self.midi_input.poll_events() {
-            if let Some(action) =
-                crate::midi_dispatch::process_midi_event(&event, self.dispatcher.state())
-            {
+            if let Some(action) = crate::midi_dispatch::process_midi_event(
+                &event,
+                self.dispatcher.state(),
+                &mut self.mpe_channels,
+                self.midi_input.event_age_us(&event),
+            ) {
                 self.render_needed = true;
                 if let RoutedAction::Domain(ref domain) = action.route() {
                     let r = self.dispatcher.dispatch_domain(domain, &mut self.audio);
@@ -175,6 +175,23 @@ impl AppRuntime {
                 }
             }
 
+            // Bridge the piano roll's export request to the export dialog
+            if matches!(
+                &routed_action,
+                RoutedAction::Ui(UiAction::Nav(action::NavAction::PushPane(
+                    action::PaneId::Export
+                )))
+            ) && self.panes.active().id() == "piano_roll"
+            {
+                if let Some(piano_roll) = self.panes.get_pane_mut::<PianoRollPane>("piano_roll") {
+                    if let Some((stems, offline)) = piano_roll.take_export_request() {
+                        if let Some(export_pane) = self.panes.get_pane_mut::<ExportPane>("export") {
+                            export_pane.set_request(stems, offline);
+                        }
+                    }
+                }
+            }
+
             // Process navigation and sync pane layer
             process_nav_and_sync(
                 &pane_action,
@@ -312,6 +329,13 @@ impl AppRuntime {
                     .iter()
                     .map(|p| p.name.clone())
                     .collect();
+                self.midi_output.refresh_ports();
+                self.dispatcher.state_mut().midi.output_port_names = self
+                    .midi_output
+                    .list_ports()
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect();
             } else if let Action::Midi(action::MidiAction::DisconnectPort) = &pane_action {
                 self.midi_input.disconnect();
                 self.dispatcher.state_mut().midi.connected_port = None;
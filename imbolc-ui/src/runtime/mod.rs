@@ -11,6 +11,8 @@ mod feedback;
 mod input;
 mod render;
 
+pub(crate) use feedback::queue_vst_restores;
+
 use std::path::PathBuf;
 use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
@@ -42,6 +44,8 @@ pub struct AppRuntime {
     pub(crate) layer_stack: LayerStack,
     pub(crate) app_frame: Frame,
     pub(crate) midi_input: midi::MidiInputManager,
+    pub(crate) midi_output: midi::MidiOutputManager,
+    pub(crate) mpe_channels: crate::midi_dispatch::MpeChannels,
     pub(crate) io_rx: Receiver<IoFeedback>,
     pub(crate) recent_projects: state::recent_projects::RecentProjects,
 
@@ -105,6 +109,17 @@ impl AppRuntime {
             .collect();
         dispatcher.state_mut().midi.connected_port =
             midi_input.connected_port_name().map(|s| s.to_string());
+        let _ = audio.set_midi_clock_input(midi_input.take_clock_receiver());
+
+        // Initialize MIDI output (ports open lazily when a MidiOut instrument plays)
+        let mut midi_output = midi::MidiOutputManager::new();
+        midi_output.refresh_ports();
+        dispatcher.state_mut().midi.output_port_names = midi_output
+            .list_ports()
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        let _ = audio.set_midi_output(midi_output.sender());
 
         let recent_projects = state::recent_projects::RecentProjects::load();
         let mut pending_audio_effects: Vec<AudioEffect> = Vec::new();
@@ -176,6 +191,8 @@ impl AppRuntime {
             layer_stack,
             app_frame,
             midi_input,
+            midi_output,
+            mpe_channels: crate::midi_dispatch::MpeChannels::default(),
             io_rx,
             recent_projects,
             ui_log: InteractionLog::ui(),
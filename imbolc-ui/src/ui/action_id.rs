@@ -86,8 +86,10 @@ pub enum GlobalActionId {
     CycleTheme,
     RequestPrivilege,
     OpenCheckpointList,
+    OpenInstrumentPresets,
     SwitchPane(PaneId),
     SelectInstrument(u8), // 1-10
+    GotoMarker(u32),      // arrangement marker id, offered by the command palette
 }
 
 impl GlobalActionId {
@@ -126,6 +128,7 @@ impl GlobalActionId {
             GlobalActionId::CycleTheme => "cycle_theme",
             GlobalActionId::RequestPrivilege => "request_privilege",
             GlobalActionId::OpenCheckpointList => "open_checkpoint_list",
+            GlobalActionId::OpenInstrumentPresets => "open_instrument_presets",
             GlobalActionId::SwitchPane(pane) => match pane {
                 PaneId::InstrumentEdit => "switch:instrument",
                 PaneId::InstrumentList => "switch:instrument_list",
@@ -154,6 +157,7 @@ impl GlobalActionId {
                 10 => "select:10",
                 _ => "select:invalid",
             },
+            GlobalActionId::GotoMarker(_) => "goto_marker",
         }
     }
 
@@ -192,6 +196,7 @@ impl GlobalActionId {
             "cycle_theme" => Some(GlobalActionId::CycleTheme),
             "request_privilege" => Some(GlobalActionId::RequestPrivilege),
             "open_checkpoint_list" => Some(GlobalActionId::OpenCheckpointList),
+            "open_instrument_presets" => Some(GlobalActionId::OpenInstrumentPresets),
             "switch:instrument" => Some(GlobalActionId::SwitchPane(PaneId::InstrumentEdit)),
             "switch:instrument_list" => Some(GlobalActionId::SwitchPane(PaneId::InstrumentList)),
             "switch:piano_roll_or_sequencer" => {
@@ -239,6 +244,7 @@ define_action_enum! {
         UnlinkLayer => "unlink_layer",
         LayerOctaveUp => "layer_octave_up",
         LayerOctaveDown => "layer_octave_down",
+        ToggleFreeze => "toggle_freeze",
     }
 }
 
@@ -273,10 +279,16 @@ define_action_enum! {
         ToggleActive => "toggle_active",
         ToggleChannelConfig => "toggle_channel_config",
         LoadSample => "load_sample",
+        ToggleSampleWarp => "toggle_sample_warp",
         VstParams => "vst_params",
         MoveStageUp => "move_stage_up",
         MoveStageDown => "move_stage_down",
         ToggleEffectBypass => "toggle_effect_bypass",
+        CycleSidechain => "cycle_sidechain",
+        AddModRoute => "add_mod_route",
+        RemoveModRoute => "remove_mod_route",
+        CycleModSource => "cycle_mod_source",
+        CycleModTarget => "cycle_mod_target",
         Done => "done",
     }
 }
@@ -306,6 +318,7 @@ define_action_enum! {
         AddEffect => "add_effect",
         RemoveEffect => "remove_effect",
         ToggleEffect => "toggle_effect",
+        CycleSidechain => "cycle_sidechain",
         ToggleFilter => "toggle_filter",
         CycleFilterType => "cycle_filter_type",
         FineLeft => "fine_left",
@@ -315,6 +328,7 @@ define_action_enum! {
         PanLeft => "pan_left",
         PanRight => "pan_right",
         ClearSend => "clear_send",
+        ResetClips => "reset_clips",
         Increase => "increase",
         Decrease => "decrease",
     }
@@ -354,9 +368,30 @@ define_action_enum! {
         RenderToWav => "render_to_wav",
         BounceToWav => "bounce_to_wav",
         ExportStems => "export_stems",
+        BounceOffline => "bounce_offline",
+        ExportStemsOffline => "export_stems_offline",
         ToggleViewMode => "toggle_view_mode",
         CyclePatternLength => "cycle_pattern_length",
         CycleStepResolution => "cycle_step_resolution",
+        ImportMidi => "import_midi",
+        ExportMidi => "export_midi",
+        Quantize => "quantize",
+        QuantizeSoft => "quantize_soft",
+        QuantizeSwing => "quantize_swing",
+        TransposeUp => "transpose_up",
+        TransposeDown => "transpose_down",
+        DegreeUp => "degree_up",
+        DegreeDown => "degree_down",
+        Legato => "legato",
+        FixedLength => "fixed_length",
+        VelocityScaleUp => "velocity_scale_up",
+        VelocityScaleDown => "velocity_scale_down",
+        CompressVelocity => "compress_velocity",
+        ReverseNotes => "reverse_notes",
+        InvertNotes => "invert_notes",
+        SnapToScale => "snap_to_scale",
+        HumanizeNotes => "humanize_notes",
+        AuditionNextTake => "audition_next_take",
     }
 }
 
@@ -396,6 +431,17 @@ define_action_enum! {
         FreqUp => "freq_up",
         FreqDown => "freq_down",
         CycleGrid => "cycle_grid",
+        CycleLockParam => "cycle_lock_param",
+        LockUp => "lock_up",
+        LockDown => "lock_down",
+        ClearLock => "clear_lock",
+        NextCondition => "next_condition",
+        PrevCondition => "prev_condition",
+        RatchetUp => "ratchet_up",
+        RatchetDown => "ratchet_down",
+        RatchetDecayUp => "ratchet_decay_up",
+        RatchetDecayDown => "ratchet_decay_down",
+        ToggleFill => "toggle_fill",
     }
 }
 
@@ -505,6 +551,11 @@ pub enum SampleChopperActionId {
     Back,
     NudgeStart,
     NudgeEnd,
+    ToggleWarp,
+    BpmUp,
+    BpmDown,
+    DoubleBpm,
+    HalveBpm,
     AssignToPad(u8), // 1-12
 }
 
@@ -524,6 +575,11 @@ impl SampleChopperActionId {
             SampleChopperActionId::Back => "back",
             SampleChopperActionId::NudgeStart => "nudge_start",
             SampleChopperActionId::NudgeEnd => "nudge_end",
+            SampleChopperActionId::ToggleWarp => "toggle_warp",
+            SampleChopperActionId::BpmUp => "bpm_up",
+            SampleChopperActionId::BpmDown => "bpm_down",
+            SampleChopperActionId::DoubleBpm => "double_bpm",
+            SampleChopperActionId::HalveBpm => "halve_bpm",
             SampleChopperActionId::AssignToPad(n) => match n {
                 1 => "assign_1",
                 2 => "assign_2",
@@ -557,6 +613,11 @@ impl SampleChopperActionId {
             "back" => Some(SampleChopperActionId::Back),
             "nudge_start" => Some(SampleChopperActionId::NudgeStart),
             "nudge_end" => Some(SampleChopperActionId::NudgeEnd),
+            "toggle_warp" => Some(SampleChopperActionId::ToggleWarp),
+            "bpm_up" => Some(SampleChopperActionId::BpmUp),
+            "bpm_down" => Some(SampleChopperActionId::BpmDown),
+            "double_bpm" => Some(SampleChopperActionId::DoubleBpm),
+            "halve_bpm" => Some(SampleChopperActionId::HalveBpm),
             "assign_1" => Some(SampleChopperActionId::AssignToPad(1)),
             "assign_2" => Some(SampleChopperActionId::AssignToPad(2)),
             "assign_3" => Some(SampleChopperActionId::AssignToPad(3)),
@@ -706,6 +767,27 @@ define_action_enum! {
         SelectPrevPlacement => "select_prev_placement",
         SelectPrevClip => "select_prev_clip",
         SelectNextClip => "select_next_clip",
+        ImportMidi => "import_midi",
+        ImportAudio => "import_audio",
+        ToggleArm => "toggle_arm",
+        Record => "record",
+        PunchIn => "punch_in",
+        PunchOut => "punch_out",
+        CycleTake => "cycle_take",
+        ToggleTempo => "toggle_tempo",
+        ToggleTempoRamp => "toggle_tempo_ramp",
+        TempoUp => "tempo_up",
+        TempoDown => "tempo_down",
+        CycleMeter => "cycle_meter",
+        ToggleMarker => "toggle_marker",
+        RenameMarker => "rename_marker",
+        NextMarker => "next_marker",
+        PrevMarker => "prev_marker",
+        LoopSection => "loop_section",
+        AddSection => "add_section",
+        InsertSection => "insert_section",
+        DuplicateSection => "duplicate_section",
+        DeleteSection => "delete_section",
     }
 }
 
@@ -753,6 +835,12 @@ define_action_enum! {
         SetChannelAll => "set_channel_all",
         SetLiveInstrument => "set_live_instrument",
         ClearLiveInstrument => "clear_live_instrument",
+        CycleClockMode => "cycle_clock_mode",
+        CycleClockPort => "cycle_clock_port",
+        ToggleMpe => "toggle_mpe",
+        CycleMpeBendRange => "cycle_mpe_bend_range",
+        CycleNoteRecordMode => "cycle_note_record_mode",
+        CycleInputQuantize => "cycle_input_quantize",
     }
 }
 
@@ -807,6 +895,33 @@ define_action_enum! {
     }
 }
 
+define_action_enum! {
+    /// Instrument preset browser layer actions
+    pub enum InstrumentPresetsActionId {
+        Load => "load",
+        Close => "close",
+        Up => "up",
+        Down => "down",
+        NextSource => "next_source",
+        PrevSource => "prev_source",
+        Search => "search",
+        Save => "save",
+        Delete => "delete",
+    }
+}
+
+define_action_enum! {
+    /// Export options dialog layer actions
+    pub enum ExportActionId {
+        Confirm => "confirm",
+        Close => "close",
+        Up => "up",
+        Down => "down",
+        Left => "left",
+        Right => "right",
+    }
+}
+
 /// Top-level action identifier wrapping all layer-specific action enums
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ActionId {
@@ -836,6 +951,8 @@ pub enum ActionId {
     Confirm(ConfirmActionId),
     ProjectBrowser(ProjectBrowserActionId),
     CheckpointList(CheckpointListActionId),
+    InstrumentPresets(InstrumentPresetsActionId),
+    Export(ExportActionId),
     Tuner(TunerActionId),
 }
 
@@ -868,6 +985,8 @@ impl ActionId {
             ActionId::Confirm(a) => a.as_str(),
             ActionId::ProjectBrowser(a) => a.as_str(),
             ActionId::CheckpointList(a) => a.as_str(),
+            ActionId::InstrumentPresets(a) => a.as_str(),
+            ActionId::Export(a) => a.as_str(),
             ActionId::Tuner(a) => a.as_str(),
         }
     }
@@ -902,6 +1021,10 @@ pub fn parse_action_id(layer: &str, action: &str) -> Option<ActionId> {
         "confirm" => ConfirmActionId::from_str(action).map(ActionId::Confirm),
         "project_browser" => ProjectBrowserActionId::from_str(action).map(ActionId::ProjectBrowser),
         "checkpoint_list" => CheckpointListActionId::from_str(action).map(ActionId::CheckpointList),
+        "instrument_presets" => {
+            InstrumentPresetsActionId::from_str(action).map(ActionId::InstrumentPresets)
+        }
+        "export" => ExportActionId::from_str(action).map(ActionId::Export),
         "piano_mode" | "pad_mode" | "text_edit" | "command_palette" | "pane_switcher" => {
             ModeActionId::from_str(action).map(ActionId::Mode)
         }
@@ -941,6 +1064,7 @@ mod tests {
             GlobalActionId::PlayStop,
             GlobalActionId::ClickTrackToggle,
             GlobalActionId::RequestPrivilege,
+            GlobalActionId::OpenInstrumentPresets,
             GlobalActionId::SelectPrevInstrument,
             GlobalActionId::SelectNextInstrument,
             GlobalActionId::SelectTwoDigit,
@@ -1033,6 +1157,11 @@ mod tests {
             InstrumentEditActionId::MoveStageUp,
             InstrumentEditActionId::MoveStageDown,
             InstrumentEditActionId::ToggleEffectBypass,
+            InstrumentEditActionId::CycleSidechain,
+            InstrumentEditActionId::AddModRoute,
+            InstrumentEditActionId::RemoveModRoute,
+            InstrumentEditActionId::CycleModSource,
+            InstrumentEditActionId::CycleModTarget,
             InstrumentEditActionId::Done,
         ];
 
@@ -1068,6 +1197,7 @@ mod tests {
             MixerActionId::AddEffect,
             MixerActionId::RemoveEffect,
             MixerActionId::ToggleEffect,
+            MixerActionId::CycleSidechain,
             MixerActionId::ToggleFilter,
             MixerActionId::CycleFilterType,
             MixerActionId::FineLeft,
@@ -1077,6 +1207,7 @@ mod tests {
             MixerActionId::PanLeft,
             MixerActionId::PanRight,
             MixerActionId::ClearSend,
+            MixerActionId::ResetClips,
             MixerActionId::Increase,
             MixerActionId::Decrease,
         ];
@@ -31,8 +31,9 @@ pub use list_selector::ListSelector;
 pub use pane::{
     Action, ArrangementAction, AutomationAction, BusAction, ChopperAction, DispatchResult,
     FileSelectAction, GenerativeAction, InstrumentAction, InstrumentUpdate, LayerGroupAction,
-    MixerAction, NavAction, NavIntent, Pane, PaneId, PaneManager, PianoRollAction,
-    SequencerAction, ServerAction, SessionAction, StatusEvent, ToggleResult, VstParamAction,
+    MidiImportTarget, MixerAction, NavAction, NavIntent, Pane, PaneId, PaneManager,
+    PianoRollAction, SequencerAction, ServerAction, SessionAction, StatusEvent, ToggleResult,
+    VstParamAction,
 };
 pub use piano_keyboard::{translate_key, PianoKeyboard};
 pub use ratatui_impl::RatatuiBackend;
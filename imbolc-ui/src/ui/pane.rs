@@ -8,8 +8,8 @@ use crate::state::AppState;
 pub use crate::action::{
     Action, ArrangementAction, AutomationAction, BusAction, ChopperAction, DispatchResult,
     FileSelectAction, GenerativeAction, InstrumentAction, InstrumentUpdate, LayerGroupAction,
-    MixerAction, NavAction, NavIntent, PaneId, PianoRollAction, SequencerAction, ServerAction,
-    SessionAction, StatusEvent, ToggleResult, VstParamAction,
+    MidiImportTarget, MixerAction, NavAction, NavIntent, PaneId, PianoRollAction, SequencerAction,
+    ServerAction, SessionAction, StatusEvent, ToggleResult, VstParamAction,
 };
 
 /// Trait for UI panes (screens/views).